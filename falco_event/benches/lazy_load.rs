@@ -0,0 +1,70 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use falco_event::events::types::PPME_SYSCALL_EXECVE_19_X;
+use falco_event::events::{Event, EventMetadata, EventToBytes, RawEvent};
+use falco_event::fields::types::{PT_ERRNO, PT_PID, PT_UID};
+use std::path::Path;
+
+fn execve_event() -> Vec<u8> {
+    let event = Event {
+        metadata: EventMetadata { ts: 1, tid: 1 },
+        params: PPME_SYSCALL_EXECVE_19_X {
+            res: Some(PT_ERRNO(0)),
+            exe: Some(c"/usr/bin/cat"),
+            args: Some(b"/etc/passwd\0"),
+            tid: Some(PT_PID(1)),
+            pid: Some(PT_PID(1)),
+            ptid: Some(PT_PID(0)),
+            cwd: Some(c"/root"),
+            fdlimit: Some(1024),
+            pgft_maj: Some(0),
+            pgft_min: Some(0),
+            vm_size: Some(0),
+            vm_rss: Some(0),
+            vm_swap: Some(0),
+            comm: Some(c"cat"),
+            cgroups: Some(b"cpuset=/\0cpu=/\0memory=/\0"),
+            env: Some(b"HOME=/root\0PATH=/usr/bin:/bin\0TERM=xterm\0"),
+            tty: Some(0),
+            pgid: Some(PT_PID(1)),
+            loginuid: Some(PT_UID(0)),
+            flags: None,
+            cap_inheritable: Some(0),
+            cap_permitted: Some(u64::MAX),
+            cap_effective: Some(u64::MAX),
+            exe_ino: Some(1234),
+            exe_ino_ctime: None,
+            exe_ino_mtime: None,
+            uid: Some(PT_UID(0)),
+            trusted_exepath: Some(Path::new("/usr/bin/cat")),
+        },
+    };
+
+    let mut buf = Vec::new();
+    event.write(&mut buf).unwrap();
+    buf
+}
+
+fn bench_load(c: &mut Criterion) {
+    let buf = execve_event();
+    let raw = RawEvent::from(&buf).unwrap();
+
+    let mut group = c.benchmark_group("execve_x.comm");
+    group.bench_function("load", |b| {
+        b.iter(|| {
+            let event = black_box(&raw).load::<PPME_SYSCALL_EXECVE_19_X>().unwrap();
+            black_box(event.params.comm);
+        })
+    });
+    group.bench_function("load_lazy", |b| {
+        b.iter(|| {
+            let event = black_box(&raw)
+                .load_lazy::<PPME_SYSCALL_EXECVE_19_X>()
+                .unwrap();
+            black_box(event.comm().unwrap());
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_load);
+criterion_main!(benches);
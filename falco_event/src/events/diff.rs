@@ -0,0 +1,195 @@
+use crate::events::types::EventType;
+use crate::events::RawEvent;
+use num_traits::FromPrimitive;
+use std::fmt::{Display, Formatter};
+
+/// A single difference between two events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// The name of the differing field (a parameter name or a metadata field)
+    pub name: String,
+    /// The formatted value in the left-hand event
+    pub left: String,
+    /// The formatted value in the right-hand event
+    pub right: String,
+}
+
+/// A structural diff between two events
+///
+/// Events are compared by metadata, event type and then parameter by parameter,
+/// using the same formatting as the event's `Debug` output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventDiff(pub Vec<FieldDiff>);
+
+impl EventDiff {
+    /// Check whether the events were equal
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn push(&mut self, name: &str, left: impl Display, right: impl Display) {
+        let left = left.to_string();
+        let right = right.to_string();
+        if left != right {
+            self.0.push(FieldDiff {
+                name: name.to_string(),
+                left,
+                right,
+            })
+        }
+    }
+}
+
+impl Display for EventDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for diff in &self.0 {
+            writeln!(f, "  {}:", diff.name)?;
+            writeln!(f, "    left:  {}", diff.left)?;
+            writeln!(f, "    right: {}", diff.right)?;
+        }
+        Ok(())
+    }
+}
+
+fn event_type_name(event_type: u16) -> String {
+    match EventType::from_u16(event_type) {
+        Some(ty) => format!("{:?}", ty),
+        None => format!("<unknown event type {}>", event_type),
+    }
+}
+
+fn params(event: &RawEvent) -> Option<Vec<(&'static str, String)>> {
+    event
+        .load_any()
+        .ok()
+        .map(|event| event.params.format_params())
+}
+
+/// Compare two events parameter by parameter
+///
+/// Parameters are only compared if the events have the same type. If either event
+/// cannot be decoded, or the raw payloads differ in a way that doesn't show up in the formatted
+/// parameters, the diff contains a `payload` entry with the raw bytes.
+pub fn diff_events(left: &RawEvent, right: &RawEvent) -> EventDiff {
+    let mut diff = EventDiff::default();
+
+    diff.push("ts", left.metadata.ts, right.metadata.ts);
+    diff.push("tid", left.metadata.tid, right.metadata.tid);
+
+    if left.event_type != right.event_type {
+        diff.push(
+            "event_type",
+            event_type_name(left.event_type),
+            event_type_name(right.event_type),
+        );
+        return diff;
+    }
+
+    let mut params_differ = false;
+    if let (Some(left_params), Some(right_params)) = (params(left), params(right)) {
+        for ((name, left_param), (_, right_param)) in left_params.iter().zip(right_params.iter()) {
+            params_differ |= left_param != right_param;
+            diff.push(name, left_param, right_param);
+        }
+    }
+
+    if !params_differ && left.payload != right.payload {
+        diff.push(
+            "payload",
+            format!("{:02x?}", left.payload),
+            format!("{:02x?}", right.payload),
+        );
+    }
+
+    diff
+}
+
+/// Assert that two events are equal, printing a per-parameter diff if they're not
+///
+/// Both arguments must be [`RawEvent`]s.
+#[macro_export]
+macro_rules! assert_events_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        let diff = $crate::events::diff_events(&$left, &$right);
+        if !diff.is_empty() {
+            panic!("events differ:\n{}", diff);
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_events;
+    use crate::events::types::{PPME_SYSCALL_CLOSE_E, PPME_SYSCALL_CLOSE_X};
+    use crate::events::{Event, EventMetadata, EventToBytes, RawEvent};
+    use crate::fields::types::{PT_ERRNO, PT_FD};
+
+    fn close_e(ts: u64, fd: i64) -> Vec<u8> {
+        let event = Event {
+            metadata: EventMetadata { ts, tid: 1 },
+            params: PPME_SYSCALL_CLOSE_E {
+                fd: Some(PT_FD(fd)),
+            },
+        };
+        let mut buf = Vec::new();
+        event.write(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_equal_events() {
+        let left = close_e(1, 3);
+        let right = close_e(1, 3);
+        let left = RawEvent::from(&left).unwrap();
+        let right = RawEvent::from(&right).unwrap();
+
+        assert!(diff_events(&left, &right).is_empty());
+        assert_events_eq!(left, right);
+    }
+
+    #[test]
+    fn test_param_diff() {
+        let left = close_e(1, 3);
+        let right = close_e(2, 4);
+        let left = RawEvent::from(&left).unwrap();
+        let right = RawEvent::from(&right).unwrap();
+
+        let diff = diff_events(&left, &right);
+        let names: Vec<_> = diff.0.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["ts", "fd"]);
+        assert_eq!(diff.0[1].left, "3");
+        assert_eq!(diff.0[1].right, "4");
+    }
+
+    #[test]
+    fn test_event_type_diff() {
+        let left = close_e(1, 3);
+        let right = Event {
+            metadata: EventMetadata { ts: 1, tid: 1 },
+            params: PPME_SYSCALL_CLOSE_X {
+                res: Some(PT_ERRNO(0)),
+            },
+        };
+        let mut right_buf = Vec::new();
+        right.write(&mut right_buf).unwrap();
+
+        let left = RawEvent::from(&left).unwrap();
+        let right = RawEvent::from(&right_buf).unwrap();
+
+        let diff = diff_events(&left, &right);
+        assert_eq!(diff.0.len(), 1);
+        assert_eq!(diff.0[0].name, "event_type");
+        assert_eq!(diff.0[0].left, "SYSCALL_CLOSE_E");
+    }
+
+    #[test]
+    #[should_panic(expected = "events differ")]
+    fn test_assert_events_eq_panics() {
+        let left = close_e(1, 3);
+        let right = close_e(1, 4);
+        assert_events_eq!(
+            RawEvent::from(&left).unwrap(),
+            RawEvent::from(&right).unwrap()
+        );
+    }
+}
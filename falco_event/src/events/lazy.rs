@@ -0,0 +1,234 @@
+use std::marker::PhantomData;
+
+use byteorder::{NativeEndian, ReadBytesExt};
+
+use crate::events::payload::{EventPayload, PayloadFromBytesError, PayloadFromBytesResult};
+use crate::events::{EventMetadata, RawEvent};
+use crate::fields::{FromBytes, FromBytesResult};
+
+/// # A lazily parsed event
+///
+/// [`RawEvent::load`] parses all the parameters of an event upfront into a struct
+/// of `Option`s. For events with many parameters (e.g. `execve`), where typically only
+/// a handful is used, most of that work (and memory) is wasted.
+///
+/// A `LazyEvent` only validates the parameter lengths when created and records which
+/// parameters are present (non-empty) in a bitmap. The parameters themselves are parsed
+/// only when accessed, either by index ([`LazyEvent::param`]) or using the per-parameter
+/// methods generated for every event type, named after the fields of the event struct:
+///
+/// ```
+/// use falco_event::events::types::PPME_SYSCALL_EXECVE_19_X;
+/// # use falco_event::events::RawEvent;
+///
+/// fn exe(event: &RawEvent) -> anyhow::Result<Option<Vec<u8>>> {
+///     let event = event.load_lazy::<PPME_SYSCALL_EXECVE_19_X>()?;
+///     Ok(event.exe()?.map(|exe| exe.to_bytes().to_vec()))
+/// }
+/// ```
+pub struct LazyEvent<'a, T> {
+    metadata: EventMetadata,
+    lengths: &'a [u8],
+    params: &'a [u8],
+    length_size: usize,
+    nparams: usize,
+    present: u64,
+    _payload: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: EventPayload> LazyEvent<'a, T> {
+    pub(crate) fn new(raw: &RawEvent<'a>) -> PayloadFromBytesResult<Self> {
+        if raw.event_type != T::ID as u16 {
+            return Err(PayloadFromBytesError::TypeMismatch);
+        }
+
+        let length_size = if T::LARGE { 4 } else { 2 };
+        let nparams = raw.nparams as usize;
+        let ll = nparams * length_size;
+
+        if raw.payload.len() < ll {
+            return Err(PayloadFromBytesError::TruncatedEvent {
+                wanted: ll,
+                got: raw.payload.len(),
+            });
+        }
+
+        let (lengths, params) = raw.payload.split_at(ll);
+        let mut lazy = Self {
+            metadata: raw.metadata.clone(),
+            lengths,
+            params,
+            length_size,
+            nparams,
+            present: 0,
+            _payload: PhantomData,
+        };
+
+        let mut total = 0usize;
+        for i in 0..nparams {
+            let len = lazy.param_len(i);
+            if len > 0 && i < u64::BITS as usize {
+                lazy.present |= 1 << i;
+            }
+            total += len;
+        }
+
+        if total > params.len() {
+            return Err(PayloadFromBytesError::TruncatedEvent {
+                wanted: ll + total,
+                got: raw.payload.len(),
+            });
+        }
+
+        Ok(lazy)
+    }
+}
+
+impl<'a, T> LazyEvent<'a, T> {
+    /// Get the event metadata
+    pub fn metadata(&self) -> &EventMetadata {
+        &self.metadata
+    }
+
+    /// Get the number of parameters in the event
+    pub fn num_params(&self) -> usize {
+        self.nparams
+    }
+
+    /// Get the presence bitmap
+    ///
+    /// Bit `i` is set if parameter `i` is present in the event (and is not empty).
+    /// Only the first 64 parameters are covered; no event type has more than that.
+    pub fn presence(&self) -> u64 {
+        self.present
+    }
+
+    /// Check whether parameter `index` is present in the event
+    pub fn is_present(&self, index: usize) -> bool {
+        match index {
+            i if i < u64::BITS as usize => self.present & (1 << i) != 0,
+            i if i < self.nparams => self.param_len(i) > 0,
+            _ => false,
+        }
+    }
+
+    /// Get the raw bytes of parameter `index`
+    ///
+    /// Returns `None` if the event has fewer parameters. A parameter with an empty
+    /// value is returned as `Some(&[])`.
+    pub fn raw_param(&self, index: usize) -> Option<&'a [u8]> {
+        if index >= self.nparams {
+            return None;
+        }
+
+        let offset = (0..index).map(|i| self.param_len(i)).sum::<usize>();
+        let len = self.param_len(index);
+
+        // the lengths were validated in `LazyEvent::new`
+        Some(&self.params[offset..offset + len])
+    }
+
+    /// Parse parameter `index`
+    ///
+    /// Missing and empty parameters are returned as `Ok(None)`, just like in the structs
+    /// returned from [`RawEvent::load`].
+    pub fn param<P: FromBytes<'a> + 'a>(&self, index: usize) -> FromBytesResult<Option<P>> {
+        let mut buf = self.raw_param(index);
+        <Option<P> as FromBytes>::from_maybe_bytes(buf.as_mut())
+    }
+
+    fn param_len(&self, index: usize) -> usize {
+        let mut buf = &self.lengths[index * self.length_size..];
+        buf.read_uint::<NativeEndian>(self.length_size).unwrap_or(0) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LazyEvent;
+    use crate::events::payload::EventPayload;
+    use crate::events::payload::PayloadFromBytesError;
+    use crate::events::types::{PPME_SYSCALL_EXECVE_19_X, PPME_SYSCALL_OPEN_X};
+    use crate::events::RawEvent;
+    use crate::fields::types::PT_FD;
+
+    fn open_event() -> Vec<u8> {
+        // built by hand, as serializing an event writes default values for
+        // missing parameters, so they would not be empty
+        let params: [&[u8]; 6] = [
+            &5i64.to_ne_bytes(),
+            b"/etc/passwd\0",
+            &[],
+            &0o644u32.to_ne_bytes(),
+            &[],
+            &1234u64.to_ne_bytes(),
+        ];
+
+        let payload_len = params.iter().map(|p| 2 + p.len()).sum::<usize>();
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&1u64.to_ne_bytes());
+        buf.extend_from_slice(&2i64.to_ne_bytes());
+        buf.extend_from_slice(&(26 + payload_len as u32).to_ne_bytes());
+        buf.extend_from_slice(&(PPME_SYSCALL_OPEN_X::ID as u16).to_ne_bytes());
+        buf.extend_from_slice(&(params.len() as u32).to_ne_bytes());
+        for param in params {
+            buf.extend_from_slice(&(param.len() as u16).to_ne_bytes());
+        }
+        for param in params {
+            buf.extend_from_slice(param);
+        }
+        buf
+    }
+
+    #[test]
+    fn test_lazy_params() {
+        let buf = open_event();
+        let raw = RawEvent::from(&buf).unwrap();
+        let event = raw.load_lazy::<PPME_SYSCALL_OPEN_X>().unwrap();
+
+        assert_eq!(event.metadata().tid, 2);
+        assert_eq!(event.num_params(), 6);
+        assert_eq!(event.presence(), 0b101011);
+        assert!(event.is_present(1));
+        assert!(!event.is_present(2));
+        assert!(!event.is_present(100));
+
+        assert_eq!(event.fd().unwrap(), Some(PT_FD(5)));
+        assert_eq!(
+            event.name().unwrap(),
+            Some(std::path::Path::new("/etc/passwd"))
+        );
+        assert!(event.flags().unwrap().is_none());
+        assert_eq!(event.ino().unwrap(), Some(1234));
+        assert_eq!(event.param::<u32>(3).unwrap(), Some(0o644));
+        assert!(event.param::<u32>(6).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_lazy_type_mismatch() {
+        let buf = open_event();
+        let raw = RawEvent::from(&buf).unwrap();
+        assert!(matches!(
+            raw.load_lazy::<PPME_SYSCALL_EXECVE_19_X>(),
+            Err(PayloadFromBytesError::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_lazy_truncated() {
+        let buf = open_event();
+        let raw = RawEvent::from(&buf[..buf.len() - 1]).unwrap();
+        assert!(matches!(
+            raw.load_lazy::<PPME_SYSCALL_OPEN_X>(),
+            Err(PayloadFromBytesError::TruncatedEvent { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lazy_event_size() {
+        assert!(
+            std::mem::size_of::<LazyEvent<PPME_SYSCALL_EXECVE_19_X>>()
+                < std::mem::size_of::<PPME_SYSCALL_EXECVE_19_X>() / 4
+        );
+    }
+}
@@ -1,4 +1,5 @@
 pub use event::Event;
+pub use lazy::LazyEvent;
 pub use metadata::EventMetadata;
 pub use payload::EventDirection;
 pub use payload::EventPayload;
@@ -7,7 +8,13 @@ pub use payload::PayloadToBytes;
 pub use raw_event::RawEvent;
 pub use to_bytes::EventToBytes;
 
+#[cfg(feature = "test-util")]
+pub use diff::{diff_events, EventDiff, FieldDiff};
+
+#[cfg(feature = "test-util")]
+mod diff;
 mod event;
+mod lazy;
 mod metadata;
 pub(crate) mod payload;
 mod raw_event;
@@ -5,7 +5,7 @@ use byteorder::{NativeEndian, ReadBytesExt, WriteBytesExt};
 use crate::events::payload::{
     EventPayload, PayloadFromBytes, PayloadFromBytesError, PayloadFromBytesResult,
 };
-use crate::events::{Event, EventMetadata, EventToBytes};
+use crate::events::{Event, EventMetadata, EventToBytes, LazyEvent};
 use crate::fields::FromBytesError;
 
 #[derive(Debug)]
@@ -68,6 +68,13 @@ impl RawEvent<'_> {
         })
     }
 
+    /// Load the event lazily, without parsing any parameters upfront
+    ///
+    /// See [`LazyEvent`] for details.
+    pub fn load_lazy<'a, T: EventPayload>(&'a self) -> PayloadFromBytesResult<LazyEvent<'a, T>> {
+        LazyEvent::new(self)
+    }
+
     unsafe fn lengths_length<T>(&self) -> usize {
         let size = std::mem::size_of::<T>();
         self.nparams as usize * size
@@ -819,3 +819,48 @@ const struct ppm_name_value finit_module_flags[] = {
         {0, 0},
 };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_names() {
+        let flags = PT_FLAGS32_file_flags::from_bits_retain(crate::ffi::PPM_O_CLOEXEC);
+        assert_eq!(flags.to_names().collect::<Vec<_>>(), vec!["O_CLOEXEC"]);
+        assert_eq!(flags.to_string(), "O_CLOEXEC");
+
+        let flags = PT_FLAGS32_file_flags::from_bits_retain(
+            crate::ffi::PPM_O_RDONLY | crate::ffi::PPM_O_CLOEXEC | 0x8000_0000,
+        );
+        assert_eq!(flags.to_string(), "O_RDONLY|O_CLOEXEC|0x80000000");
+    }
+
+    #[test]
+    fn test_zero_flag_names() {
+        // file_flags has a named zero value (O_NONE)
+        let flags = PT_FLAGS32_file_flags::from_bits_retain(0);
+        assert_eq!(flags.to_names().collect::<Vec<_>>(), vec!["O_NONE"]);
+        assert_eq!(flags.to_string(), "O_NONE");
+
+        // the zero value is only used when no other flag is set
+        let flags = PT_FLAGS32_file_flags::from_bits_retain(crate::ffi::PPM_O_RDONLY);
+        assert_eq!(flags.to_names().collect::<Vec<_>>(), vec!["O_RDONLY"]);
+
+        // linkat_flags does not, so the raw number is displayed
+        let flags = PT_FLAGS32_linkat_flags::from_bits_retain(0);
+        assert_eq!(flags.to_names().count(), 0);
+        assert_eq!(flags.to_string(), "0");
+    }
+
+    #[test]
+    fn test_enum_names() {
+        let whence = PT_ENUMFLAGS8_lseek_whence::from(crate::ffi::PPM_SEEK_SET as u8);
+        assert_eq!(whence.name(), Some("SEEK_SET"));
+        assert_eq!(whence.to_string(), "SEEK_SET");
+
+        let whence = PT_ENUMFLAGS8_lseek_whence::from(100u8);
+        assert_eq!(whence.to_names().count(), 0);
+        assert_eq!(whence.to_string(), "100");
+    }
+}
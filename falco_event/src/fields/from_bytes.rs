@@ -1,7 +1,10 @@
 use thiserror::Error;
 
 /// Error type for deserializing data from a byte buffer
+///
+/// New variants may be added in minor releases, so matches on it need a wildcard arm.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum FromBytesError {
     /// I/O error
     #[error("I/O error")]
@@ -39,6 +42,12 @@ pub enum FromBytesError {
     /// Odd item count in pair array
     #[error("odd item count in pair array")]
     OddPairItemCount,
+
+    /// Path not representable on the current platform
+    ///
+    /// On non-Unix platforms, paths must be valid UTF-8
+    #[error("path not representable on this platform")]
+    InvalidPath,
 }
 
 /// The result of a deserialization
@@ -30,6 +30,7 @@ mod event_derive {
     pub use crate::events::EventDirection;
     pub use crate::events::EventMetadata;
     pub use crate::events::EventPayload;
+    pub use crate::events::LazyEvent;
     pub use crate::events::PayloadFromBytes;
     pub use crate::events::PayloadToBytes;
     pub use crate::events::RawEvent;
@@ -40,5 +41,7 @@ mod event_derive {
     pub use crate::fields::NoDefault;
     pub use crate::fields::ToBytes;
     pub use crate::types::format::format_type;
+    #[cfg(feature = "test-util")]
+    pub use crate::types::format::DisplayFn;
     pub use crate::types::format::Format;
 }
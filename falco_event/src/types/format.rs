@@ -16,6 +16,17 @@ where
     }
 }
 
+/// Adapts a formatting closure to [`std::fmt::Display`]
+#[cfg(feature = "test-util")]
+pub struct DisplayFn<F: Fn(&mut Formatter) -> std::fmt::Result>(pub F);
+
+#[cfg(feature = "test-util")]
+impl<F: Fn(&mut Formatter) -> std::fmt::Result> std::fmt::Display for DisplayFn<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.0)(f)
+    }
+}
+
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
 pub mod format_type {
@@ -1,12 +1,11 @@
 use crate::event_derive::{FromBytes, FromBytesResult, ToBytes};
 use crate::ffi::{PPM_AF_INET, PPM_AF_INET6, PPM_AF_LOCAL, PPM_AF_UNSPEC};
 use crate::types::format::Format;
+use crate::types::path::path_from_bytes;
 use crate::types::{EndpointV4, EndpointV6};
 use byteorder::{ReadBytesExt, WriteBytesExt};
-use std::ffi::OsStr;
 use std::fmt::Formatter;
 use std::io::Write;
-use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
 
 /// A socket address
@@ -66,9 +65,9 @@ impl<'a> FromBytes<'a> for SockAddr<'a> {
         let variant = buf.read_u8()?;
         match variant as u32 {
             PPM_AF_LOCAL => {
-                let path = <OsStr as OsStrExt>::from_bytes(buf);
+                let path = path_from_bytes(buf)?;
                 *buf = &[];
-                Ok(Self::Unix(Path::new(path)))
+                Ok(Self::Unix(path))
             }
             PPM_AF_INET => {
                 let addr = EndpointV4::from_bytes(buf)?;
@@ -92,7 +91,7 @@ where
     fn format(&self, fmt: &mut Formatter) -> std::fmt::Result {
         match self {
             SockAddr::Unix(u) => {
-                let bytes = u.as_os_str().as_bytes();
+                let bytes = u.as_os_str().as_encoded_bytes();
                 fmt.write_str("unix://")?;
                 bytes.format(fmt)
             }
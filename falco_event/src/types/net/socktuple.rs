@@ -175,7 +175,6 @@ mod tests {
     use super::*;
     use crate::types::Port;
     use std::net::{Ipv4Addr, Ipv6Addr};
-    use std::os::unix::ffi::OsStrExt;
     use std::str::FromStr;
 
     #[test]
@@ -251,7 +250,7 @@ mod tests {
         assert_eq!(source_addr, 0);
         assert_eq!(dest_addr, 0xffff98bc4ecf2000);
         assert_eq!(
-            path.as_os_str().as_bytes(),
+            path.as_os_str().as_encoded_bytes(),
             b"/var/run/nscd/socket".as_slice()
         );
 
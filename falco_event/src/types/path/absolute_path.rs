@@ -1,17 +1,16 @@
-use std::ffi::{CStr, OsStr};
+use std::ffi::CStr;
 use std::fmt::Formatter;
 use std::io::Write;
-use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
 
 use crate::event_derive::{FromBytes, FromBytesResult, ToBytes};
 use crate::types::format::Format;
+use crate::types::path::path_from_bytes;
 
 impl<'a> FromBytes<'a> for &'a Path {
     fn from_bytes(buf: &mut &'a [u8]) -> FromBytesResult<Self> {
         let buf = <&CStr>::from_bytes(buf)?;
-        let osstr = OsStr::from_bytes(buf.to_bytes());
-        Ok(Path::new(osstr))
+        path_from_bytes(buf.to_bytes())
     }
 }
 
@@ -21,7 +20,7 @@ impl ToBytes for &Path {
     }
 
     fn write<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
-        self.as_os_str().as_bytes().write(&mut writer)?;
+        self.as_os_str().as_encoded_bytes().write(&mut writer)?;
         0u8.write(writer)
     }
 
@@ -35,7 +34,7 @@ where
     for<'a> &'a [u8]: Format<F>,
 {
     fn format(&self, fmt: &mut Formatter) -> std::fmt::Result {
-        let bytes = self.as_os_str().as_bytes();
+        let bytes = self.as_os_str().as_encoded_bytes();
         bytes.format(fmt)
     }
 }
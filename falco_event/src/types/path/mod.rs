@@ -1,4 +1,26 @@
+use crate::fields::FromBytesResult;
+use std::path::Path;
+
 mod absolute_path;
 mod relative_path;
 
 pub use relative_path::*;
+
+/// Interpret raw bytes from an event as a path
+///
+/// Paths in events are arbitrary byte strings, which map directly to paths on Unix.
+/// Elsewhere, only valid UTF-8 paths are supported.
+pub(crate) fn path_from_bytes(buf: &[u8]) -> FromBytesResult<&Path> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Ok(Path::new(std::ffi::OsStr::from_bytes(buf)))
+    }
+
+    #[cfg(not(unix))]
+    {
+        std::str::from_utf8(buf)
+            .map(Path::new)
+            .map_err(|_| crate::fields::FromBytesError::InvalidPath)
+    }
+}
@@ -1,6 +1,5 @@
 use std::fmt::Formatter;
 use std::io::Write;
-use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
 
 use crate::event_derive::{FromBytes, FromBytesResult, ToBytes};
@@ -41,7 +40,7 @@ where
     fn format(&self, fmt: &mut Formatter) -> std::fmt::Result {
         write!(fmt, "<...>")?;
 
-        let bytes = self.0.as_os_str().as_bytes();
+        let bytes = self.0.as_os_str().as_encoded_bytes();
         bytes.format(fmt)
     }
 }
@@ -20,7 +20,15 @@ impl ToBytes for Duration {
     }
 
     fn write<W: Write>(&self, writer: W) -> std::io::Result<()> {
-        (self.as_nanos() as u64).write(writer)
+        // durations are stored as a 64-bit nanosecond count, which covers about 584 years;
+        // refuse anything longer instead of silently wrapping around
+        let nanos = u64::try_from(self.as_nanos()).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("duration {:?} does not fit in 64-bit nanoseconds", self),
+            )
+        })?;
+        nanos.write(writer)
     }
 
     fn default_repr() -> impl ToBytes {
@@ -33,3 +41,27 @@ impl<F> Format<F> for Duration {
         std::fmt::Debug::fmt(self, fmt)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::event_derive::{FromBytes, ToBytes};
+    use std::time::Duration;
+
+    #[test]
+    fn test_duration_roundtrip() {
+        let duration = Duration::new(1, 234_567_891);
+        let mut buf = Vec::new();
+        duration.write(&mut buf).unwrap();
+        assert_eq!(buf.as_slice(), 1_234_567_891u64.to_ne_bytes());
+
+        let mut buf = buf.as_slice();
+        assert_eq!(Duration::from_bytes(&mut buf).unwrap(), duration);
+    }
+
+    #[test]
+    fn test_duration_overflow() {
+        let mut buf = Vec::new();
+        assert!(Duration::MAX.write(&mut buf).is_err());
+        assert!(buf.is_empty());
+    }
+}
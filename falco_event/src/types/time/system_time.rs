@@ -21,9 +21,12 @@ impl ToBytes for SystemTime {
     }
 
     fn write<W: Write>(&self, writer: W) -> std::io::Result<()> {
-        let duration = self
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let duration = self.duration_since(UNIX_EPOCH).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("timestamp {:?} is before the Unix epoch", self),
+            )
+        })?;
         duration.write(writer)
     }
 
@@ -38,3 +41,27 @@ impl<F> Format<F> for SystemTime {
         fmt.write_str(&dt.to_rfc2822())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::event_derive::{FromBytes, ToBytes};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_system_time_roundtrip() {
+        let ts = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        let mut buf = Vec::new();
+        ts.write(&mut buf).unwrap();
+        assert_eq!(buf.as_slice(), 1_700_000_000_123_456_789u64.to_ne_bytes());
+
+        let mut buf = buf.as_slice();
+        assert_eq!(SystemTime::from_bytes(&mut buf).unwrap(), ts);
+    }
+
+    #[test]
+    fn test_system_time_before_epoch() {
+        let mut buf = Vec::new();
+        let ts = UNIX_EPOCH - Duration::from_secs(1);
+        assert!(ts.write(&mut buf).is_err());
+    }
+}
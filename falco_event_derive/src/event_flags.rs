@@ -133,6 +133,11 @@ fn render_enum(
         .clone()
         .map(|(variant, value)| quote!(#name::#variant => crate::ffi::#value as #repr_type));
 
+    let enum_to_name = filtered.clone().map(|(variant, _)| {
+        let variant_name = variant.to_string();
+        quote!(#name::#variant => Some(#variant_name))
+    });
+
     quote!(
         #[repr(#repr_type)]
         #[allow(non_camel_case_types)]
@@ -161,6 +166,30 @@ fn render_enum(
             }
         }
 
+        impl #name {
+            /// Return the symbolic name of the value, if known
+            pub fn name(&self) -> Option<&'static str> {
+                match self {
+                    #(#enum_to_name,)*
+                    #name::Unknown(_) => None,
+                }
+            }
+
+            /// Return an iterator over the symbolic names of the value (at most one)
+            pub fn to_names(&self) -> impl Iterator<Item = &'static str> {
+                self.name().into_iter()
+            }
+        }
+
+        impl std::fmt::Display for #name {
+            fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match self {
+                    Self::Unknown(val) => write!(fmt, "{}", val),
+                    _ => fmt.write_str(self.name().unwrap_or_default()),
+                }
+            }
+        }
+
         impl crate::event_derive::ToBytes for #name {
             fn binary_size(&self) -> usize {
                 std::mem::size_of::<#repr_type>()
@@ -218,6 +247,55 @@ fn render_bitflags(
             }
         }
 
+        impl #name {
+            /// Return the name of the flag with a value of zero (like `O_NONE`), if there is one
+            fn zero_name() -> Option<&'static str> {
+                <Self as bitflags::Flags>::FLAGS
+                    .iter()
+                    .find(|flag| flag.value().bits() == 0 && !flag.name().is_empty())
+                    .map(|flag| flag.name())
+            }
+
+            /// Return an iterator over the symbolic names of the flags that are set
+            ///
+            /// Bits without a known name are skipped. If no bits are set, the name
+            /// of the zero-valued flag (if any) is returned
+            pub fn to_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+                let zero = if self.is_empty() { Self::zero_name() } else { None };
+                zero.into_iter().chain(self.iter_names().map(|(name, _)| name))
+            }
+        }
+
+        impl std::fmt::Display for #name {
+            fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let mut first = true;
+
+                let mut it = self.iter_names();
+                for (name, _) in &mut it {
+                    if !first {
+                        fmt.write_str("|")?;
+                    }
+                    first = false;
+                    fmt.write_str(name)?;
+                }
+
+                let rem = it.remaining().bits();
+                if rem != 0 {
+                    if !first {
+                        fmt.write_str("|")?;
+                    }
+                    first = false;
+                    write!(fmt, "{rem:#x}")?;
+                }
+
+                if first {
+                    fmt.write_str(Self::zero_name().unwrap_or("0"))?;
+                }
+
+                Ok(())
+            }
+        }
+
         impl crate::event_derive::ToBytes for #name {
             fn binary_size(&self) -> usize {
                 std::mem::size_of::<#repr_type>()
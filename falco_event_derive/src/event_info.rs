@@ -202,7 +202,9 @@ impl EventInfo {
         let mut fields = Vec::new();
         let mut wants_lifetime = false;
         let mut field_fmts = Vec::new();
+        let mut param_fmts = Vec::new();
         let mut dirfd_methods = Vec::new();
+        let mut lazy_methods = Vec::new();
 
         if let Some((_, _, args)) = self.args.as_ref() {
             fields = args.iter().map(|arg| arg.to_token_stream()).collect();
@@ -240,7 +242,46 @@ impl EventInfo {
                 })
                 .collect();
 
+            param_fmts = args
+                .iter()
+                .map(|field| {
+                    let name = &field.name;
+                    let ident = field.ident();
+                    let fmt = &field.field_format;
+                    let ty = field.final_field_type();
+                    let (field_ref, field_lifetime) = field.lifetimes();
+
+                    quote!(
+                        (#name, crate::event_derive::DisplayFn(|fmt: &mut std::fmt::Formatter| {
+                            <Option<#field_ref crate::event_derive::event_field_type::#ty #field_lifetime> as
+                                crate::event_derive::Format<
+                                    crate::event_derive::format_type::#fmt
+                            >>::format(&self.#ident, fmt)
+                        }).to_string())
+                    )
+                })
+                .collect();
+
             dirfd_methods = args.iter().map(|a| a.dirfd_method(&self)).collect();
+
+            lazy_methods = args
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    let ident = field.ident();
+                    let ty = field.final_field_type();
+                    let (field_ref, field_lifetime) = field.lifetimes();
+
+                    quote!(
+                        #[allow(non_snake_case)]
+                        pub fn #ident(&self) -> crate::event_derive::FromBytesResult<
+                            Option<#field_ref crate::event_derive::event_field_type::#ty #field_lifetime>
+                        > {
+                            self.param(#i)
+                        }
+                    )
+                })
+                .collect();
         }
 
         let lifetime = if wants_lifetime {
@@ -261,6 +302,16 @@ impl EventInfo {
 
             impl #lifetime #event_code #lifetime {
                 #(#dirfd_methods)*
+
+                #[cfg(feature = "test-util")]
+                #[doc(hidden)]
+                pub fn format_params(&self) -> Vec<(&'static str, String)> {
+                    vec![#(#param_fmts,)*]
+                }
+            }
+
+            impl<'a> crate::event_derive::LazyEvent<'a, #event_code #lifetime> {
+                #(#lazy_methods)*
             }
 
             impl #lifetime crate::event_derive::EventPayload for #event_code #lifetime {
@@ -350,6 +401,18 @@ impl EventInfo {
         })
     }
 
+    fn variant_params(&self) -> proc_macro2::TokenStream {
+        let event_code = &self.event_code;
+        let event_type = Ident::new(
+            &event_code.to_string().replace("PPME_", ""),
+            event_code.span(),
+        );
+
+        quote!(
+            AnyEvent::#event_type(inner) => inner.format_params()
+        )
+    }
+
     fn variant_fmt(&self) -> proc_macro2::TokenStream {
         let event_code = &self.event_code;
         let event_type = Ident::new(
@@ -397,6 +460,10 @@ impl Events {
     fn variant_fmts(&self) -> impl Iterator<Item = proc_macro2::TokenStream> + '_ {
         self.events.iter().map(|e| e.variant_fmt())
     }
+
+    fn variant_params(&self) -> impl Iterator<Item = proc_macro2::TokenStream> + '_ {
+        self.events.iter().map(|e| e.variant_params())
+    }
 }
 
 pub fn event_info(input: TokenStream) -> TokenStream {
@@ -406,16 +473,17 @@ pub fn event_info(input: TokenStream) -> TokenStream {
     let variants = events.enum_variants();
     let matches = events.enum_matches();
     let variant_fmts = events.variant_fmts();
+    let variant_params = events.variant_params();
 
     quote!(
         use falco_event_derive::BinaryPayload;
-        use num_derive::FromPrimitive;
+        use num_derive::{FromPrimitive, ToPrimitive};
         use crate::event_derive::RawEvent;
 
         #(#typedefs)*
 
         #[derive(Debug)]
-        #[derive(FromPrimitive)]
+        #[derive(FromPrimitive, ToPrimitive)]
         #[allow(non_camel_case_types)]
         #[repr(u16)]
         pub enum EventType {
@@ -428,6 +496,16 @@ pub fn event_info(input: TokenStream) -> TokenStream {
             #(#variants,)*
         }
 
+        impl AnyEvent<'_> {
+            #[cfg(feature = "test-util")]
+            #[doc(hidden)]
+            pub fn format_params(&self) -> Vec<(&'static str, String)> {
+                match self {
+                    #(#variant_params,)*
+                }
+            }
+        }
+
         impl<'a> crate::event_derive::Format<crate::event_derive::format_type::PF_NA> for AnyEvent<'a> {
             fn format(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
                 match self {
@@ -0,0 +1,6 @@
+fn main() {
+    // `REQUIRED_API_VERSION` is read from the environment at build time (via `option_env!`),
+    // so make sure changing it rebuilds the crate
+    println!("cargo:rerun-if-env-changed=FALCO_PLUGIN_API_VERSION");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -13,6 +13,23 @@ pub use serde;
 
 pub use crate::plugin::error::FailureReason;
 
+/// # Error types returned by the SDK
+///
+/// Most SDK methods return an [`anyhow::Error`] but the underlying errors are often one of
+/// the types below. You can use [`anyhow::Error::downcast_ref`] to match on a specific kind
+/// of error instead of inspecting the error message.
+pub mod errors {
+    pub use crate::plugin::convert::OutOfRange;
+    pub use crate::plugin::error::FailureReason;
+    pub use crate::plugin::extract::ArgError;
+    pub use crate::plugin::listen::routine::ThreadPoolError;
+    pub use crate::plugin::schema::SchemaError;
+    pub use crate::plugin::tables::field::FieldNotAvailable;
+    pub use crate::plugin::tables::table::EntryNotFound;
+    pub use crate::plugin::tables::vtable::TableError;
+    pub use crate::strings::from_ptr::FromPtrError;
+}
+
 /// # The common foundation for all Falco plugins
 ///
 /// All plugins must implement the [`base::Plugin`] trait which specifies some basic metadata
@@ -20,9 +37,14 @@ pub use crate::plugin::error::FailureReason;
 ///
 /// See the [`base::Plugin`] trait documentation for details.
 pub mod base {
+    pub use crate::plugin::base::config_watch::ConfigWatch;
+    pub use crate::plugin::base::health::Health;
+    pub use crate::plugin::base::metric_rates::MetricRates;
     pub use crate::plugin::base::metrics::{Metric, MetricLabel, MetricType, MetricValue};
+    pub use crate::plugin::base::storage_stats::StorageError;
     pub use crate::plugin::base::Plugin;
-    pub use crate::plugin::schema::Json;
+    pub use crate::plugin::docs::PluginDocs;
+    pub use crate::plugin::schema::{Json, StrictJson};
 }
 
 /// # Field extraction plugin support
@@ -116,11 +138,103 @@ pub mod base {
 /// See the [`extract::ExtractPlugin`] trait documentation for details.
 pub mod extract {
     pub use crate::plugin::event::EventInput;
-    pub use crate::plugin::extract::schema::field;
+    pub use crate::plugin::extract::borrowed::BorrowedStr;
+    pub use crate::plugin::extract::cache::EventCache;
+    pub use crate::plugin::extract::dynamic::DynExtract;
+    pub use crate::plugin::extract::json::JsonData;
+    #[cfg(feature = "rules-lint")]
+    pub use crate::plugin::extract::lint::{lint_rules, LintIssue, LintIssueKind};
+    pub use crate::plugin::extract::post_process::PostProcess;
+    pub use crate::plugin::extract::schema::{borrowed_field, field, field_index};
     pub use crate::plugin::extract::schema::{ExtractArgType, ExtractFieldInfo};
+    pub use crate::plugin::extract::storage::FieldStorage;
+    pub use crate::plugin::extract::time::{AbsTime, RelTime};
     pub use crate::plugin::extract::ExtractFieldRequestArg;
     pub use crate::plugin::extract::ExtractPlugin;
     pub use crate::plugin::extract::ExtractRequest;
+
+    /// # Generate the extractable field list from a declarative spec
+    ///
+    /// This macro takes a list of field declarations and generates:
+    /// - a trait (with the name of your choice), containing a method for each field,
+    ///   which you need to implement for your plugin
+    /// - an associated constant `FIELD_SPEC` on the plugin type, which you can use
+    ///   as [`ExtractPlugin::EXTRACT_FIELDS`]
+    ///
+    /// This way, the field names, descriptions, argument types and the Rust signatures
+    /// are kept in a single place. Doc comments become the field descriptions and
+    /// `#[display("...")]` and `#[arg(...)]` set the display name and the argument type
+    /// (one of the [`ExtractArgType`] variants), respectively.
+    ///
+    /// The parentheses after the method name must stay empty: the request and the argument
+    /// are always passed to the generated methods, so declaring any parameters is an error.
+    ///
+    /// ```
+    /// use std::ffi::{CStr, CString};
+    /// use falco_plugin::anyhow::Error;
+    /// use falco_plugin::base::Plugin;
+    /// use falco_plugin::event::events::types::EventType;
+    /// use falco_plugin::extract::{
+    ///     extract_field_spec,
+    ///     ExtractFieldInfo,
+    ///     ExtractFieldRequestArg,
+    ///     ExtractPlugin,
+    ///     ExtractRequest};
+    /// use falco_plugin::tables::TablesInput;
+    ///
+    /// struct SpecPlugin;
+    ///
+    /// impl Plugin for SpecPlugin {
+    ///     // ...
+    /// #    const NAME: &'static CStr = c"spec";
+    /// #    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    /// #    const DESCRIPTION: &'static CStr = c"test plugin";
+    /// #    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    /// #    type ConfigType = ();
+    /// #
+    /// #    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+    /// #        Ok(Self)
+    /// #    }
+    /// }
+    ///
+    /// extract_field_spec! {
+    ///     trait SpecPluginFields for SpecPlugin {
+    ///         /// Always ten
+    ///         "spec.ten" => fn extract_ten() -> u64;
+    ///
+    ///         /// Repeat the argument
+    ///         #[display("Echo")]
+    ///         #[arg(RequiredKey)]
+    ///         "spec.echo" => fn extract_echo() -> CString;
+    ///     }
+    /// }
+    ///
+    /// impl SpecPluginFields for SpecPlugin {
+    ///     fn extract_ten(&mut self, _req: ExtractRequest<Self>, _arg: ExtractFieldRequestArg)
+    ///         -> Result<u64, Error> {
+    ///         Ok(10)
+    ///     }
+    ///
+    ///     fn extract_echo(&mut self, _req: ExtractRequest<Self>, arg: ExtractFieldRequestArg)
+    ///         -> Result<CString, Error> {
+    ///         match arg {
+    ///             ExtractFieldRequestArg::String(s) => Ok(s.to_owned()),
+    ///             _ => anyhow::bail!("expected a string argument"),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl ExtractPlugin for SpecPlugin {
+    ///     const EVENT_TYPES: &'static [EventType] = &[];
+    ///     const EVENT_SOURCES: &'static [&'static str] = &[];
+    ///     type ExtractContext = ();
+    ///     const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = Self::FIELD_SPEC;
+    /// }
+    ///
+    /// # // make this doctest a module, not a function: https://github.com/rust-lang/rust/issues/83583#issuecomment-1083300448
+    /// # fn main() {}
+    /// ```
+    pub use falco_plugin_derive::extract_field_spec;
 }
 
 /// # Event parsing support
@@ -179,6 +293,8 @@ pub mod extract {
 /// ```
 pub mod parse {
     pub use crate::plugin::event::EventInput;
+    pub use crate::plugin::parse::correlator::Correlator;
+    pub use crate::plugin::parse::dynamic::DynParse;
     pub use crate::plugin::parse::ParseInput;
     pub use crate::plugin::parse::ParsePlugin;
 }
@@ -294,10 +410,14 @@ pub mod async_event {
     pub use falco_event::events::types::PPME_ASYNCEVENT_E as AsyncEvent;
 
 
-    pub use crate::plugin::async_event::async_handler::AsyncHandler;
+    pub use crate::plugin::async_event::async_handler::{AsyncHandler, MAX_RETAINED_BUFFER_SIZE};
     pub use crate::plugin::async_event::AsyncEventPlugin;
 
     pub use crate::plugin::async_event::background_task::BackgroundTask;
+
+    pub use crate::plugin::async_event::json_fields::{
+        JsonAsyncFieldExtractor, JsonAsyncFieldNames, JsonAsyncFields,
+    };
 }
 
 /// # Event sourcing support
@@ -398,10 +518,30 @@ pub mod async_event {
 /// ```
 pub mod source {
     pub use crate::plugin::event::EventInput;
+    pub use crate::plugin::source::deadline::DeadlineBatcher;
+    pub use crate::plugin::source::dedup::DedupPolicy;
     pub use crate::plugin::source::event_batch::EventBatch;
+    pub use crate::plugin::source::feedback::FeedbackQueue;
     pub use crate::plugin::source::open_params::{serialize_open_params, OpenParam};
+    pub use crate::plugin::source::payload::{PayloadDecodeError, PluginPayload};
+    pub use crate::plugin::source::registry::{
+        check_plugin_id, lookup_plugin_id, warn_on_plugin_id, PluginIdIssue, RegistryEntry,
+        PLUGIN_REGISTRY, TEST_PLUGIN_ID,
+    };
+    pub use crate::plugin::source::render::{render_event_data, KeyValueRenderer};
+    pub use crate::plugin::source::schema::{
+        export_payload_schemas, PayloadSchema, PayloadSchemaEntry, PayloadSchemaTable,
+        PayloadSchemas,
+    };
+    pub use crate::plugin::source::sharded::{ShardSender, ShardStats, ShardedCollector};
+    pub use crate::plugin::source::sub_sources::{
+        SubSource, SubSourceBatch, SubSourceEvent, SubSourceStats, SubSources,
+    };
+    pub use crate::plugin::source::timestamps::TimestampPolicy;
     pub use crate::plugin::source::{ProgressInfo, SourcePlugin, SourcePluginInstance};
     pub use falco_event::events::types::PPME_PLUGINEVENT_E as PluginEvent;
+
+    pub use falco_plugin_derive::PluginPayloadEnum;
 }
 
 /// # Capture listening plugins
@@ -548,6 +688,7 @@ pub mod listen {
 /// - integer types (u8/i8, u16/i16, u32/i32, u64/i64)
 /// - the bool type
 /// - CString
+/// - [`tables::ByteBuffer`] for binary data (stored as a hex-encoded string)
 ///
 /// Any other types are not supported, including in particular e.g. collections (`Vec<T>`),
 /// enums or any structs.
@@ -559,8 +700,10 @@ pub mod listen {
 /// let fd_type = threads[tid].file_descriptors[fd].fd_type;
 /// ```
 ///
-/// One important limitation is that you cannot add a nested table at runtime, so the only
-/// nested tables that exist are defined by the plugin (or Falco core) which owns the parent table.
+/// Only the plugin (or Falco core) which owns the parent table can create nested tables.
+/// Besides static `Box<Table<K, E>>` fields, an exported table can get table-valued fields
+/// at runtime, see [`tables::export::DynamicTable`]. Other plugins cannot add table-valued
+/// fields to an exported table, as they would have no way to fill them.
 ///
 /// # Exporting and importing tables
 ///
@@ -640,6 +783,10 @@ pub mod listen {
 /// can use them from your plugin (e.g. in a separate thread) concurrently to other plugins
 /// (in the main thread).
 pub mod tables {
+    pub use crate::plugin::tables::byte_buffer::{
+        ByteBuffer, ByteBufferError, MAX_BYTE_BUFFER_SIZE,
+    };
+    pub use crate::plugin::tables::byte_key::{hex_decode, hex_encode, ByteKey};
     pub use crate::plugin::tables::vtable::TableReader;
     pub use crate::plugin::tables::vtable::TableWriter;
     pub use crate::plugin::tables::vtable::TablesInput;
@@ -657,6 +804,32 @@ pub mod tables {
     /// for tables (they have no setter to replace the whole table and you can always add/remove
     /// entries from the nested table).
     ///
+    /// Fields are exported under their Rust names by default. Use `#[name(c"...")]` to export
+    /// a field under a different name, e.g. one following the Falco naming conventions
+    /// (with dots etc.), and `#[skip]` to leave a field out of the table altogether. Unlike
+    /// [`Private`](`crate::tables::export::Private`) fields, skipped fields can be of any type
+    /// implementing [`Default`] (which is used to initialize them in new entries):
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::ffi::CString;
+    /// use falco_plugin::tables::export;
+    ///
+    /// #[derive(export::Entry)]
+    /// struct Connection {
+    ///     #[name(c"conn.peer")]
+    ///     peer: export::Readonly<CString>,
+    ///
+    ///     #[skip]
+    ///     pending: HashMap<u64, Vec<u8>>,
+    /// }
+    /// ```
+    ///
+    /// Add `#[accessors]` to the struct to also generate typed accessors for all fields that
+    /// are not skipped: `get_<field>` and `get_<field>_mut` methods and a `FIELD_<FIELD>`
+    /// constant (an [`EntryField`](`crate::tables::export::EntryField`)), which you can
+    /// pass to helper functions working with any field of a particular type.
+    ///
     /// # Example
     ///
     /// ```
@@ -706,9 +879,18 @@ pub mod tables {
     /// }
     /// ```
     pub mod export {
+        pub use crate::plugin::exported_tables::field::accessor::EntryField;
         pub use crate::plugin::exported_tables::field::private::Private;
         pub use crate::plugin::exported_tables::field::public::Public;
         pub use crate::plugin::exported_tables::field::readonly::Readonly;
+        pub use crate::plugin::exported_tables::field_value::dynamic::DynamicTable;
+        pub use crate::plugin::exported_tables::replay::TableSnapshotRecord;
+        pub use crate::plugin::exported_tables::snapshot::{SnapshotTable, TableSnapshot};
+        #[cfg(feature = "sled-tables")]
+        pub use crate::plugin::exported_tables::store::SledStore;
+        pub use crate::plugin::exported_tables::store::TableStore;
+        pub use crate::plugin::exported_tables::table::BulkLoadStats;
+        pub use crate::plugin::exported_tables::table::FieldStats;
         pub use crate::plugin::exported_tables::table::Table;
 
         /// Mark a struct type as a table value
@@ -780,12 +962,68 @@ pub mod tables {
     /// unless overridden by `#[name(c"foo")]`. This is useful if a field's name is a Rust reserved
     /// word (e.g. `type`).
     ///
+    /// If a field has been renamed between host versions, you can list several candidate names,
+    /// e.g. `#[name(c"comm", c"name")]`. They are tried in order at initialization time
+    /// and the first one found in the table is used. The names must be non-empty C string
+    /// literals, otherwise the derive fails to compile:
+    ///
+    /// ```compile_fail
+    /// use std::ffi::CStr;
+    /// use std::sync::Arc;
+    /// use falco_plugin::tables::import::{Entry, Field, TableMetadata};
+    ///
+    /// #[derive(TableMetadata)]
+    /// #[entry_type(ImportedThing)]
+    /// struct ImportedThingMetadata {
+    ///     #[name()] // error: expected one or more non-empty C string literals
+    ///     comm: Field<CStr, ImportedThing>,
+    /// }
+    ///
+    /// type ImportedThing = Entry<Arc<ImportedThingMetadata>>;
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use std::ffi::CStr;
+    /// use std::sync::Arc;
+    /// use falco_plugin::tables::import::{Entry, Field, TableMetadata};
+    ///
+    /// #[derive(TableMetadata)]
+    /// #[entry_type(ImportedThing)]
+    /// struct ImportedThingMetadata {
+    ///     #[name("comm")] // error: not a C string literal
+    ///     comm: Field<CStr, ImportedThing>,
+    /// }
+    ///
+    /// type ImportedThing = Entry<Arc<ImportedThingMetadata>>;
+    /// ```
+    ///
     /// You can also add fields to imported tables. To do that, tag the field with a `#[custom]`
     /// attribute. It will be then added to the table instead of looking it up in existing fields.
     /// Note that multiple plugins can add a field with the same name and type, which will make them
     /// all use the same field (they will share the data). Adding a field multiple times
     /// with different types is not allowed and will cause an error at initialization time.
     ///
+    /// If a field may be missing from the table (e.g. it only exists in newer Falco versions),
+    /// tag it with `#[optional]` and wrap its type in an `Option`:
+    ///
+    /// ```ignore
+    /// #[optional]
+    /// maybe_missing: Option<Field<u64, ImportedThing>>,
+    /// ```
+    ///
+    /// A missing optional field does not fail the plugin initialization (a field that exists
+    /// with a different type still does). Instead, the generated getter returns
+    /// `Result<Option<T>, anyhow::Error>`, yielding `Ok(None)` when the field is not available,
+    /// while the setter fails with [`import::FieldNotAvailable`]. For optional fields holding
+    /// nested tables, `get_<field>_by_key` and `iter_<field>` fail with the same error.
+    ///
+    /// String fields are often filled with data taken straight from events (process names,
+    /// paths etc.), which may contain NUL bytes and cannot be stored in a [`CStr`](std::ffi::CStr)
+    /// as-is. Tag such fields with `#[nul_policy]` to make the generated setter take a `&[u8]`
+    /// and truncate the value at the first NUL instead of failing. To handle NULs differently,
+    /// pass a [`NulPolicy`](`crate::strings::NulPolicy`), e.g.
+    /// `#[nul_policy(NulPolicy::Strip)]`.
+    ///
     /// ## Generated methods
     ///
     /// Each scalar field gets a getter and setter method, e.g. declaring a metadata struct like
@@ -803,6 +1041,18 @@ pub mod tables {
     /// fn set_added(&self, writer: &TableWriter, value: &CStr) -> Result<(), anyhow::Error>;
     /// ```
     ///
+    /// The string getter borrows the returned `&CStr` from the entry, so the entry must outlive
+    /// the value. That's cheap and fine for a quick comparison, but gets in the way when you want
+    /// to store the value or move it elsewhere (e.g. into a batch or another thread). For these
+    /// cases, every field also gets an owned getter, copying the value out of the entry:
+    ///
+    /// ```ignore
+    /// fn get_added_owned(&self, reader: &TableReader) -> Result<CString, anyhow::Error>;
+    /// fn get_imported_owned(&self, reader: &TableReader) -> Result<u64, anyhow::Error>;
+    /// ```
+    ///
+    /// For numeric fields, the owned getter is equivalent to the regular one.
+    ///
     /// Each table-typed field (nested table) gets a getter and a nested getter, so the above example
     /// will generate the following methods for the `nested` field:
     ///
@@ -812,6 +1062,16 @@ pub mod tables {
     ///     -> Result<NestedThing, anyhow::Error>;
     /// ```
     ///
+    /// Nested tables can also be iterated over, calling a closure for each entry of the nested
+    /// table (the iteration stops early if the closure returns [`std::ops::ControlFlow::Break`]):
+    ///
+    /// ```ignore
+    /// fn iter_nested<F>(&self, reader: &TableReader, func: F)
+    ///     -> Result<ControlFlow<()>, anyhow::Error>
+    /// where
+    ///     F: FnMut(&mut NestedThing) -> ControlFlow<()>;
+    /// ```
+    ///
     /// **Note**: setters do not take `&mut self` as all the mutation happens on the other side
     /// of the API (presumably in another plugin).
     ///
@@ -856,7 +1116,8 @@ pub mod tables {
     ///
     ///     fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
     ///         let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
-    ///         let things: ImportedThingTable = input.get_table(c"things")?;
+    ///         // equivalent to `input.get_table(c"things")?`
+    ///         let things = ImportedThingTable::import(input, c"things")?;
     ///
     ///         Ok(Self { things })
     ///     }
@@ -891,6 +1152,10 @@ pub mod tables {
     /// # fn main() {}
     /// ```
     ///
+    /// If the table is missing any of the fields described by the metadata struct, importing it
+    /// fails with an error listing all the missing fields. Use [`import::Table::try_import`]
+    /// for tables that may not exist at all (e.g. when the plugin providing them is optional).
+    ///
     /// **Note**: The derive macro involves creating a private module (to avoid polluting
     /// the top-level namespace with a bunch of one-off traits), so you cannot use it inside
     /// a function due to scoping issues. See <https://github.com/rust-lang/rust/issues/83583>
@@ -991,8 +1256,12 @@ pub mod tables {
     pub mod import {
         pub use crate::plugin::tables::data::Bool;
         pub use crate::plugin::tables::data::TableData;
+        pub use crate::plugin::tables::entry::batch::EntryWriteBatch;
         pub use crate::plugin::tables::field::Field;
+        pub use crate::plugin::tables::field::FieldNotAvailable;
+        pub use crate::plugin::tables::ordered::OrderedKeys;
         pub use crate::plugin::tables::runtime::RuntimeEntry;
+        pub use crate::plugin::tables::table::EntryNotFound;
         pub use crate::plugin::tables::table::Table;
         pub use crate::plugin::tables::Entry;
 
@@ -1006,6 +1275,58 @@ pub mod tables {
 mod plugin;
 pub mod strings;
 
+/// # Helpers for unit testing plugins
+///
+/// This module is only available with the `testing` feature. Enable it in your
+/// `dev-dependencies` to use these helpers in tests:
+///
+/// ```toml
+/// [dev-dependencies]
+/// falco_plugin = { version = "*", features = ["testing"] }
+/// ```
+#[cfg(feature = "testing")]
+pub mod testing {
+    pub use crate::plugin::exported_tables::vtable_checks::{
+        check_exported_table, check_table_vtable, VtableIssue,
+    };
+    pub use crate::plugin::testing::OwnedEventInput;
+}
+
+/// # Recording and replaying host interactions
+///
+/// This module is only available with the `record` feature (which implies `testing`).
+///
+/// When the feature is enabled and the plugin config is a JSON object containing
+/// an `sdk_record` key, the SDK records every call the plugin makes to the host table
+/// vtables (together with the values returned by the host), as well as every field extraction
+/// and event parsing request, to the file named by that key. The key is removed from the config
+/// before the plugin sees it:
+///
+/// ```json
+/// {"sdk_record": "/tmp/my-plugin.tape", "other": "settings"}
+/// ```
+///
+/// The resulting tape (a sequence of JSON lines, see [`TapeRecord`]) can then be replayed
+/// with a [`Replayer`], in a regular test, without a Falco instance: the plugin gets
+/// the same answers from the (simulated) host as it did during the recording, and the replayer
+/// reports any difference between the recorded and replayed outcomes, or any host call
+/// that does not match the recording.
+///
+/// Only table interactions are recorded, so plugins relying on other host state (e.g. metrics
+/// or async events) may not replay faithfully.
+///
+/// Only one plugin instance in a process can be recorded at a time: initializing another one
+/// with `sdk_record` fails until the first one is destroyed.
+#[cfg(feature = "record")]
+pub mod record {
+    pub use crate::plugin::record::replay::{
+        ExtractOutcome, ExtractStep, ParseOutcome, ParseStep, ReplayStep, Replayer,
+    };
+    pub use crate::plugin::record::tape::{
+        ExtractArg, ExtractField, FieldInfo, HandleId, HostCall, HostReturn, TableInfo, TapeRecord,
+    };
+}
+
 #[doc(hidden)]
 pub mod internals {
     pub mod base {
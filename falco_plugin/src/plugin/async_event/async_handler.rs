@@ -2,10 +2,22 @@ use crate::plugin::error::as_result::AsResult;
 use crate::strings::from_ptr::try_str_from_ptr;
 use anyhow::Context;
 use falco_event::events::types::PPME_ASYNCEVENT_E as AsyncEvent;
-use falco_event::events::Event;
 use falco_event::events::EventToBytes;
+use falco_event::events::{Event, EventMetadata, EventPayload, RawEvent};
 use falco_plugin_api::{ss_plugin_event, ss_plugin_owner_t, ss_plugin_rc, PLUGIN_MAX_ERRLEN};
-use std::ffi::c_char;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+
+/// # The largest serialization buffer kept between [`AsyncHandler`] calls
+///
+/// Events are serialized into a per-thread buffer that is reused across calls. Buffers grown
+/// beyond this size (by an occasional huge event) are released after the event is submitted,
+/// so that a single outlier does not pin that much memory for the lifetime of the thread.
+pub const MAX_RETAINED_BUFFER_SIZE: usize = 64 * 1024;
+
+thread_local! {
+    static EMIT_BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
 
 /// # A handle to emit asynchronous events
 ///
@@ -19,6 +31,8 @@ pub struct AsyncHandler {
         evt: *const ss_plugin_event,
         err: *mut c_char,
     ) -> ss_plugin_rc,
+    /// The event names the plugin declared in `ASYNC_EVENTS`
+    pub(crate) event_names: &'static [&'static str],
 }
 
 unsafe impl Send for AsyncHandler {}
@@ -31,12 +45,100 @@ impl AsyncHandler {
     ///
     /// This method returns an error if and only if the asynchronous handler
     /// returns an error.
+    ///
+    /// ## Copy semantics
+    ///
+    /// The event is serialized into a reused per-thread buffer (see [`MAX_RETAINED_BUFFER_SIZE`]),
+    /// which is the only copy made by the SDK. The framework then copies the serialized event
+    /// into its own queue before this method returns, so nothing passed in needs to outlive
+    /// the call. In particular, the event can borrow its payload straight from a buffer you
+    /// already hold (e.g. `bytes::Bytes`), without copying it into a `Vec` first.
     pub fn emit(&self, event: Event<AsyncEvent>) -> Result<(), anyhow::Error> {
+        EMIT_BUFFER.with_borrow_mut(|buf| {
+            buf.clear();
+            event.write(&mut *buf)?;
+            let res = self.submit(buf);
+
+            if buf.capacity() > MAX_RETAINED_BUFFER_SIZE {
+                *buf = Vec::new();
+            }
+            res
+        })
+    }
+
+    /// # Emit an event with a payload held in any buffer type
+    ///
+    /// This is a shorthand for [`AsyncHandler::emit`] for the common case of events generated
+    /// from data kept in reference-counted buffers (`bytes::Bytes`, `Arc<[u8]>` etc.).
+    /// `data` is borrowed only for the duration of the call (the buffer is dropped
+    /// when this method returns), see [`AsyncHandler::emit`] for details.
+    ///
+    /// ```ignore
+    /// let payload: bytes::Bytes = receive_message()?;
+    /// handler.emit_data(EventMetadata::default(), c"my_event", payload)?;
+    /// ```
+    pub fn emit_data(
+        &self,
+        metadata: EventMetadata,
+        name: &CStr,
+        data: impl AsRef<[u8]> + Send,
+    ) -> Result<(), anyhow::Error> {
+        let event = Event {
+            metadata,
+            params: AsyncEvent {
+                plugin_id: Some(0),
+                name: Some(name),
+                data: Some(data.as_ref()),
+            },
+        };
+
+        self.emit(event)
+    }
+
+    /// # Emit an already serialized event
+    ///
+    /// For producers that already hold a fully serialized event (e.g. forwarded from another
+    /// process), this skips building and serializing an [`Event`] and submits `buf` as is.
+    ///
+    /// The buffer must contain exactly one async event (header included). Only the header
+    /// and the event name are checked: the event type must be `PPME_ASYNCEVENT_E`, the length
+    /// in the header must match the buffer and the name must be one of
+    /// [`AsyncEventPlugin::ASYNC_EVENTS`](`crate::async_event::AsyncEventPlugin::ASYNC_EVENTS`).
+    /// The event data is passed through unchanged.
+    pub fn emit_raw(&self, buf: &[u8]) -> Result<(), anyhow::Error> {
+        let event = RawEvent::from(buf).context("Invalid event header")?;
+        if event.event_type != AsyncEvent::ID as u16 {
+            anyhow::bail!(
+                "Expected an async event ({}), got event type {}",
+                AsyncEvent::ID as u16,
+                event.event_type
+            );
+        }
+        if event.len as usize != buf.len() {
+            anyhow::bail!(
+                "Event length mismatch: header says {} bytes, got {}",
+                event.len,
+                buf.len()
+            );
+        }
+
+        let event = event.load::<AsyncEvent>()?;
+        let name = event.params.name.unwrap_or_default();
+        if !self
+            .event_names
+            .iter()
+            .any(|n| n.as_bytes() == name.to_bytes())
+        {
+            anyhow::bail!("Async event {:?} is not listed in ASYNC_EVENTS", name);
+        }
+
+        self.submit(buf)
+    }
+
+    fn submit(&self, buf: &[u8]) -> Result<(), anyhow::Error> {
         let mut err = [0 as c_char; PLUGIN_MAX_ERRLEN as usize];
-        let mut buf = Vec::new();
         let err_ptr = &err as *const [c_char] as *const c_char;
 
-        event.write(&mut buf)?;
         match unsafe {
             (self.raw_handler)(self.owner, buf.as_ptr() as *const _, err.as_mut_ptr()).as_result()
         } {
@@ -48,3 +150,98 @@ impl AsyncHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncEvent, AsyncHandler, EMIT_BUFFER, MAX_RETAINED_BUFFER_SIZE};
+    use falco_event::events::types::PPME_PLUGINEVENT_E as PluginEvent;
+    use falco_event::events::{Event, EventMetadata, EventToBytes, RawEvent};
+    use falco_plugin_api::{ss_plugin_event, ss_plugin_owner_t, ss_plugin_rc};
+    use std::ffi::c_char;
+    use std::sync::Arc;
+
+    unsafe extern "C-unwind" fn check_event(
+        _o: *mut ss_plugin_owner_t,
+        evt: *const ss_plugin_event,
+        _err: *mut c_char,
+    ) -> ss_plugin_rc {
+        let event = unsafe { RawEvent::from_ptr(evt as *const _) }.unwrap();
+        let event = event.load::<AsyncEvent>().unwrap();
+        assert_eq!(event.params.name, Some(c"test"));
+        assert_eq!(
+            event.params.data.unwrap().len(),
+            event.metadata.tid as usize
+        );
+        0
+    }
+
+    #[test]
+    fn test_emit_data() {
+        let handler = AsyncHandler {
+            owner: std::ptr::null_mut(),
+            raw_handler: check_event,
+            event_names: &["test"],
+        };
+
+        let small: Arc<[u8]> = Arc::from(&b"hello"[..]);
+        let metadata = EventMetadata { ts: 1, tid: 5 };
+        handler.emit_data(metadata, c"test", small).unwrap();
+        assert!(EMIT_BUFFER.with_borrow(|buf| buf.capacity() > 0));
+
+        let huge = vec![0u8; 2 * MAX_RETAINED_BUFFER_SIZE];
+        let metadata = EventMetadata {
+            ts: 1,
+            tid: huge.len() as i64,
+        };
+        handler.emit_data(metadata, c"test", huge).unwrap();
+        assert_eq!(EMIT_BUFFER.with_borrow(|buf| buf.capacity()), 0);
+    }
+
+    fn serialize(event: impl EventToBytes) -> Vec<u8> {
+        let mut buf = Vec::new();
+        event.write(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_emit_raw() {
+        let handler = AsyncHandler {
+            owner: std::ptr::null_mut(),
+            raw_handler: check_event,
+            event_names: &["test"],
+        };
+
+        let async_event = |name| Event {
+            metadata: EventMetadata { ts: 1, tid: 3 },
+            params: AsyncEvent {
+                plugin_id: Some(0),
+                name: Some(name),
+                data: Some(b"foo"),
+            },
+        };
+
+        let buf = serialize(async_event(c"test"));
+        handler.emit_raw(&buf).unwrap();
+
+        // truncated or padded buffers
+        assert!(handler.emit_raw(&buf[..10]).is_err());
+        assert!(handler.emit_raw(&buf[..buf.len() - 1]).is_err());
+        let mut padded = buf.clone();
+        padded.push(0);
+        assert!(handler.emit_raw(&padded).is_err());
+
+        // names not in ASYNC_EVENTS
+        let buf = serialize(async_event(c"other"));
+        assert!(handler.emit_raw(&buf).is_err());
+
+        // not an async event
+        let buf = serialize(Event {
+            metadata: EventMetadata::default(),
+            params: PluginEvent {
+                plugin_id: Some(1),
+                event_data: Some(b"foo"),
+            },
+        });
+        assert!(handler.emit_raw(&buf).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::async_event::AsyncEvent;
+use crate::base::Plugin;
+use crate::extract::{
+    field, ExtractArgType, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+    JsonData,
+};
+use crate::tables::TablesInput;
+use anyhow::Error;
+use falco_event::events::types::EventType;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+
+/// # The names of the fields exposed by [`JsonAsyncFieldExtractor`]
+///
+/// Use the [`json_async_field_names`](`crate::json_async_field_names`) macro to build this
+/// from a field prefix.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonAsyncFieldNames {
+    /// The name of the async event (`<prefix>.name`)
+    pub name: &'static str,
+    /// The value of a key in the event data (`<prefix>.value[key]`)
+    pub value: &'static str,
+    /// Whether the event data contains a key (`<prefix>.has_key[key]`)
+    pub has_key: &'static str,
+    /// All the top-level keys in the event data (`<prefix>.keys`)
+    pub keys: &'static str,
+}
+
+/// # Build [`JsonAsyncFieldNames`](`crate::async_event::JsonAsyncFieldNames`) from a field prefix
+///
+/// `json_async_field_names!("myplugin")` names the fields `myplugin.name`, `myplugin.value`,
+/// `myplugin.has_key` and `myplugin.keys`.
+#[macro_export]
+macro_rules! json_async_field_names {
+    ($prefix:literal) => {
+        $crate::async_event::JsonAsyncFieldNames {
+            name: concat!($prefix, ".name"),
+            value: concat!($prefix, ".value"),
+            has_key: concat!($prefix, ".has_key"),
+            keys: concat!($prefix, ".keys"),
+        }
+    };
+}
+
+/// # The configuration of a [`JsonAsyncFieldExtractor`]
+///
+/// Implement this trait on an (empty) marker type to describe the extractor plugin.
+pub trait JsonAsyncFields: 'static {
+    /// The name of the extractor plugin
+    const NAME: &'static CStr;
+    /// The version of the extractor plugin
+    const PLUGIN_VERSION: &'static CStr;
+    /// A description of the extractor plugin
+    const DESCRIPTION: &'static CStr;
+    /// Contact information
+    const CONTACT: &'static CStr;
+
+    /// The names of the extracted fields
+    const FIELDS: JsonAsyncFieldNames;
+
+    /// The event sources to extract fields from (empty for all sources)
+    const EVENT_SOURCES: &'static [&'static str];
+
+    /// The names of the async events to handle (empty for all async events)
+    ///
+    /// Fields cannot be extracted from other async events.
+    const ASYNC_EVENTS: &'static [&'static str] = &[];
+}
+
+/// # An extract plugin exposing JSON data from async events as fields
+///
+/// Many async event plugins emit events with JSON-encoded `data` and need an accompanying
+/// extractor to make the data available in Falco rules. This type implements such an extractor,
+/// so that you only need to describe it with a [`JsonAsyncFields`] implementation:
+///
+/// ```
+/// use std::ffi::CStr;
+/// use falco_plugin::async_event::{JsonAsyncFieldExtractor, JsonAsyncFieldNames, JsonAsyncFields};
+/// use falco_plugin::{extract_plugin, json_async_field_names, plugin};
+///
+/// struct MyFields;
+///
+/// impl JsonAsyncFields for MyFields {
+///     const NAME: &'static CStr = c"my-async-fields";
+///     const PLUGIN_VERSION: &'static CStr = c"0.0.1";
+///     const DESCRIPTION: &'static CStr = c"fields from my async events";
+///     const CONTACT: &'static CStr = c"you@example.com";
+///     const FIELDS: JsonAsyncFieldNames = json_async_field_names!("myasync");
+///     const EVENT_SOURCES: &'static [&'static str] = &["syscall"];
+///     const ASYNC_EVENTS: &'static [&'static str] = &["my_event"];
+/// }
+///
+/// type MyExtractor = JsonAsyncFieldExtractor<MyFields>;
+/// plugin!(MyExtractor);
+/// extract_plugin!(MyExtractor);
+/// ```
+///
+/// The extractor provides the following fields (with `myasync` replaced by your prefix):
+/// - `myasync.name`: the name of the async event
+/// - `myasync.value[key]`: the value of a top-level key in the event data (strings as-is,
+///   other values as JSON); if the key starts with `/`, it's used as a
+///   [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901) to access nested values,
+///   e.g. `myasync.value[/container/id]`
+/// - `myasync.has_key[key]`: true if the event data contains the key (or pointer)
+/// - `myasync.keys`: all the top-level keys in the event data
+///
+/// The event data is only parsed once per event, no matter how many fields are extracted.
+pub struct JsonAsyncFieldExtractor<S>(PhantomData<S>);
+
+impl<S> std::fmt::Debug for JsonAsyncFieldExtractor<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonAsyncFieldExtractor").finish()
+    }
+}
+
+impl<S: JsonAsyncFields> Plugin for JsonAsyncFieldExtractor<S> {
+    const NAME: &'static CStr = S::NAME;
+    const PLUGIN_VERSION: &'static CStr = S::PLUGIN_VERSION;
+    const DESCRIPTION: &'static CStr = S::DESCRIPTION;
+    const CONTACT: &'static CStr = S::CONTACT;
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self(PhantomData))
+    }
+}
+
+/// The async event, parsed once for all the fields extracted from it
+#[derive(Debug)]
+pub struct ParsedAsyncEvent {
+    name: CString,
+    data: JsonData,
+}
+
+impl<S: JsonAsyncFields> JsonAsyncFieldExtractor<S> {
+    fn parsed<'a>(req: &'a mut ExtractRequest<Self>) -> Result<&'a ParsedAsyncEvent, Error> {
+        if req.context.is_none() {
+            let event = req.event.event()?;
+            let event = event.load::<AsyncEvent>()?;
+
+            let name = event.params.name.unwrap_or_default();
+            if !S::ASYNC_EVENTS.is_empty()
+                && !S::ASYNC_EVENTS
+                    .iter()
+                    .any(|n| n.as_bytes() == name.to_bytes())
+            {
+                anyhow::bail!("unsupported async event {:?}", name);
+            }
+
+            *req.context = Some(ParsedAsyncEvent {
+                name: name.to_owned(),
+                data: JsonData::parse(event.params.data.unwrap_or_default())?,
+            });
+        }
+
+        // we have just filled it in
+        Ok(req.context.as_ref().unwrap())
+    }
+
+    fn extract_name(
+        &mut self,
+        mut req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        Ok(Self::parsed(&mut req)?.name.clone())
+    }
+
+    fn extract_value(
+        &mut self,
+        mut req: ExtractRequest<Self>,
+        arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        Self::parsed(&mut req)?.data.value(&arg)
+    }
+
+    fn extract_has_key(
+        &mut self,
+        mut req: ExtractRequest<Self>,
+        arg: ExtractFieldRequestArg,
+    ) -> Result<bool, Error> {
+        Self::parsed(&mut req)?.data.has_key(&arg)
+    }
+
+    fn extract_keys(
+        &mut self,
+        mut req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<Vec<CString>, Error> {
+        Self::parsed(&mut req)?.data.keys()
+    }
+}
+
+impl<S: JsonAsyncFields> ExtractPlugin for JsonAsyncFieldExtractor<S> {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::ASYNCEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = S::EVENT_SOURCES;
+    type ExtractContext = Option<ParsedAsyncEvent>;
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field(S::FIELDS.name, &Self::extract_name).with_description("the name of the async event"),
+        field(S::FIELDS.value, &Self::extract_value)
+            .with_arg(ExtractArgType::RequiredKey)
+            .with_description("the value of a key (or JSON pointer) in the async event data"),
+        field(S::FIELDS.has_key, &Self::extract_has_key)
+            .with_arg(ExtractArgType::RequiredKey)
+            .with_description("true if the async event data contains the key (or JSON pointer)"),
+        field(S::FIELDS.keys, &Self::extract_keys)
+            .with_description("all the top-level keys of the async event data"),
+    ];
+}
@@ -3,6 +3,7 @@ use crate::plugin::async_event::async_handler::AsyncHandler;
 
 pub mod async_handler;
 pub mod background_task;
+pub mod json_fields;
 #[doc(hidden)]
 pub mod wrappers;
 
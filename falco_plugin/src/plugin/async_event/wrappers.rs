@@ -2,6 +2,7 @@ use crate::plugin::async_event::async_handler::AsyncHandler;
 use crate::plugin::async_event::AsyncEventPlugin;
 use crate::plugin::base::PluginWrapper;
 use crate::plugin::error::ffi_result::FfiResult;
+use crate::plugin::error::strict;
 use falco_plugin_api::plugin_api__bindgen_ty_4 as async_plugin_api;
 use falco_plugin_api::{
     ss_plugin_async_event_handler_t, ss_plugin_owner_t, ss_plugin_rc,
@@ -76,10 +77,15 @@ pub unsafe extern "C-unwind" fn plugin_set_async_event_handler<T: AsyncEventPlug
 ) -> ss_plugin_rc {
     unsafe {
         let Some(plugin) = (plugin as *mut PluginWrapper<T>).as_mut() else {
+            strict::unexpected_input("plugin_set_async_event_handler", "NULL plugin");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
         let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+            strict::unexpected_input(
+                "plugin_set_async_event_handler",
+                "plugin failed to initialize",
+            );
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
@@ -95,6 +101,7 @@ pub unsafe extern "C-unwind" fn plugin_set_async_event_handler<T: AsyncEventPlug
         let handler = AsyncHandler {
             owner,
             raw_handler: *raw_handler,
+            event_names: T::ASYNC_EVENTS,
         };
         if let Err(e) = actual_plugin.plugin.start_async(handler) {
             e.set_last_error(&mut plugin.error_buf);
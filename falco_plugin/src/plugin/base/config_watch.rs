@@ -0,0 +1,98 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+struct ConfigWatchInner<T> {
+    config: RwLock<Arc<T>>,
+    generation: AtomicU64,
+}
+
+/// # A shared, updatable view of the plugin configuration
+///
+/// [`Plugin::set_config`](`crate::base::Plugin::set_config`) is called on the plugin itself,
+/// so there's no built-in way to notify a running source instance or a background task
+/// about a configuration change. `ConfigWatch` is a cheaply cloneable handle to the current
+/// configuration: keep one in the plugin, clone it into instances and background tasks
+/// and return it from [`Plugin::config_watch`](`crate::base::Plugin::config_watch`).
+/// The SDK then updates it whenever the configuration changes. You can also call
+/// [`ConfigWatch::set`] directly.
+///
+/// Readers get an `Arc` to the configuration, so they can keep using a consistent snapshot
+/// even if the configuration gets replaced in the meantime. To check for updates without
+/// taking a lock, compare [`ConfigWatch::generation`] to the last value seen.
+///
+/// ```
+/// use falco_plugin::base::ConfigWatch;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct MyConfig {
+///     interval_ms: u64,
+/// }
+///
+/// let watch = ConfigWatch::new(MyConfig { interval_ms: 100 });
+///
+/// // e.g. moved into a background task
+/// let task_watch = watch.clone();
+/// let generation = task_watch.generation();
+///
+/// // done by the SDK after `set_config`
+/// watch.set(MyConfig { interval_ms: 500 });
+///
+/// assert_ne!(task_watch.generation(), generation);
+/// assert_eq!(task_watch.get().interval_ms, 500);
+/// ```
+pub struct ConfigWatch<T> {
+    inner: Arc<ConfigWatchInner<T>>,
+}
+
+impl<T> ConfigWatch<T> {
+    /// Create a new watch with the initial configuration
+    pub fn new(config: T) -> Self {
+        Self {
+            inner: Arc::new(ConfigWatchInner {
+                config: RwLock::new(Arc::new(config)),
+                generation: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Get the current configuration
+    pub fn get(&self) -> Arc<T> {
+        match self.inner.config.read() {
+            Ok(config) => Arc::clone(&config),
+            Err(e) => Arc::clone(&e.into_inner()),
+        }
+    }
+
+    /// Replace the configuration, making it visible to all clones of this watch
+    pub fn set(&self, config: T) {
+        let config = Arc::new(config);
+        match self.inner.config.write() {
+            Ok(mut guard) => *guard = config,
+            Err(e) => *e.into_inner() = config,
+        }
+        self.inner.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Return the number of times the configuration has been replaced
+    pub fn generation(&self) -> u64 {
+        self.inner.generation.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Clone for ConfigWatch<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: Debug> Debug for ConfigWatch<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigWatch")
+            .field("config", &self.get())
+            .field("generation", &self.generation())
+            .finish()
+    }
+}
@@ -0,0 +1,65 @@
+use crate::plugin::base::metrics::{Metric, MetricLabel, MetricType};
+
+/// # The result of a plugin health check
+///
+/// See [`Plugin::health_check`](`crate::base::Plugin::health_check`) for details.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Health {
+    /// The plugin does not check its health
+    #[default]
+    Unknown,
+    /// Everything works as expected
+    Healthy,
+    /// Something is wrong, with a human-readable description of the problem
+    Unhealthy(String),
+}
+
+/// Tracks health check results to report transitions
+#[derive(Debug, Default)]
+pub(crate) struct HealthTracker {
+    last: Health,
+}
+
+impl HealthTracker {
+    /// Record the result of a health check, returning the `healthy` metric
+    ///
+    /// Every change of the health status is logged.
+    pub(crate) fn record(&mut self, health: Health) -> Option<Metric> {
+        if health != self.last {
+            match (&self.last, &health) {
+                (_, Health::Unhealthy(reason)) => log::error!("Plugin is unhealthy: {}", reason),
+                (Health::Unhealthy(_), Health::Healthy) => log::info!("Plugin is healthy again"),
+                _ => {}
+            }
+            self.last = health;
+        }
+
+        let healthy = match &self.last {
+            Health::Unknown => return None,
+            Health::Healthy => true,
+            Health::Unhealthy(_) => false,
+        };
+
+        Some(MetricLabel::new(c"healthy", MetricType::NonMonotonic).with_value(healthy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Health, HealthTracker};
+
+    #[test]
+    fn test_health_tracker() {
+        let mut tracker = HealthTracker::default();
+        assert!(tracker.record(Health::Unknown).is_none());
+
+        let metric = tracker.record(Health::Healthy).unwrap();
+        assert_eq!(unsafe { metric.as_raw().value.u32_ }, 1);
+
+        let metric = tracker
+            .record(Health::Unhealthy("queue full".to_string()))
+            .unwrap();
+        assert_eq!(unsafe { metric.as_raw().value.u32_ }, 0);
+        assert_eq!(tracker.last, Health::Unhealthy("queue full".to_string()));
+    }
+}
@@ -0,0 +1,249 @@
+//! Config includes and overlays
+//!
+//! With the `config-includes` feature enabled, JSON plugin configs may refer to other files:
+//!
+//! * any string value of the form `"@file:/path/to/file.json"` is replaced with the (parsed)
+//!   contents of that file,
+//! * an object containing an `"@overlay"` key (with a path or a list of paths as its value)
+//!   gets the contents of these files deep-merged on top of it, in order.
+//!
+//! Relative paths are resolved against the directory of the file containing the reference
+//! (or the current directory for the config passed by the framework). Included files may
+//! contain further references. Without the feature, the config is passed through unchanged.
+
+#[cfg(feature = "config-includes")]
+use anyhow::Context;
+#[cfg(feature = "config-includes")]
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+#[cfg(feature = "config-includes")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "config-includes")]
+const FILE_PREFIX: &str = "@file:";
+#[cfg(feature = "config-includes")]
+const OVERLAY_KEY: &str = "@overlay";
+
+/// How deep includes may nest (this also catches include cycles)
+#[cfg(feature = "config-includes")]
+const MAX_DEPTH: usize = 16;
+
+/// Resolve all includes and overlays in a JSON config
+///
+/// Configs that are not JSON objects, or that do not refer to any files, are returned as-is.
+#[inline(always)]
+pub(crate) fn resolve(config: &str) -> Result<Cow<'_, str>, anyhow::Error> {
+    #[cfg(feature = "config-includes")]
+    {
+        if !config.contains(FILE_PREFIX) && !config.contains(OVERLAY_KEY) {
+            return Ok(Cow::Borrowed(config));
+        }
+        let Ok(mut value @ Value::Object(_)) = serde_json::from_str(config) else {
+            return Ok(Cow::Borrowed(config));
+        };
+
+        resolve_value(&mut value, Path::new(""), 0)?;
+        Ok(Cow::Owned(serde_json::to_string(&value)?))
+    }
+
+    #[cfg(not(feature = "config-includes"))]
+    Ok(Cow::Borrowed(config))
+}
+
+#[cfg(feature = "config-includes")]
+fn load_file(path: &str, base: &Path, depth: usize) -> Result<Value, anyhow::Error> {
+    if depth >= MAX_DEPTH {
+        anyhow::bail!(
+            "Config includes nested too deep (include cycle?) at {}",
+            path
+        );
+    }
+
+    let path: PathBuf = base.join(path);
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config include {}", path.display()))?;
+    let mut value: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse config include {}", path.display()))?;
+
+    let base = path.parent().unwrap_or(Path::new(""));
+    resolve_value(&mut value, base, depth + 1)?;
+    Ok(value)
+}
+
+#[cfg(feature = "config-includes")]
+fn resolve_value(value: &mut Value, base: &Path, depth: usize) -> Result<(), anyhow::Error> {
+    match value {
+        Value::String(s) => {
+            if let Some(path) = s.strip_prefix(FILE_PREFIX) {
+                *value = load_file(path, base, depth)?;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                resolve_value(item, base, depth)?;
+            }
+        }
+        Value::Object(object) => {
+            let overlays = object.remove(OVERLAY_KEY);
+            for item in object.values_mut() {
+                resolve_value(item, base, depth)?;
+            }
+
+            let overlays = match overlays {
+                None => Vec::new(),
+                Some(Value::String(path)) => vec![path],
+                Some(Value::Array(paths)) => paths
+                    .into_iter()
+                    .map(|path| match path {
+                        Value::String(path) => Ok(path),
+                        _ => Err(anyhow::anyhow!("{} paths must be strings", OVERLAY_KEY)),
+                    })
+                    .collect::<Result<_, _>>()?,
+                Some(_) => anyhow::bail!("{} must be a path or a list of paths", OVERLAY_KEY),
+            };
+
+            for path in overlays {
+                match load_file(&path, base, depth)? {
+                    Value::Object(overlay) => merge(object, overlay),
+                    _ => anyhow::bail!("Config overlay {} is not a JSON object", path),
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Deep-merge `overlay` into `object`: objects are merged recursively, other values replaced
+#[cfg(feature = "config-includes")]
+fn merge(object: &mut Map<String, Value>, overlay: Map<String, Value>) {
+    for (key, value) in overlay {
+        match (object.get_mut(&key), value) {
+            (Some(Value::Object(existing)), Value::Object(value)) => merge(existing, value),
+            (_, value) => {
+                object.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "config-includes"))]
+mod tests {
+    use super::resolve;
+    use serde_json::{json, Value};
+    use std::ops::Deref;
+    use std::path::{Path, PathBuf};
+
+    /// A scratch directory that is removed when the test finishes
+    struct TestDir(PathBuf);
+
+    impl Deref for TestDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn test_dir(name: &str) -> TestDir {
+        let dir = std::env::temp_dir().join(format!(
+            "falco_plugin_includes_{}_{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        TestDir(dir)
+    }
+
+    fn resolve_json(config: &str) -> Value {
+        serde_json::from_str(&resolve(config).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_no_includes() {
+        let config = r#"{"foo": "bar"}"#;
+        assert!(matches!(
+            resolve(config).unwrap(),
+            std::borrow::Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            resolve("@file:not json").unwrap(),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_file_include() {
+        let dir = test_dir("file");
+        std::fs::write(dir.join("nested.json"), r#"["a", "b"]"#).unwrap();
+        std::fs::write(
+            dir.join("rules.json"),
+            r#"{"rules": "@file:nested.json", "enabled": true}"#,
+        )
+        .unwrap();
+
+        let config = json!({
+            "ruleset": format!("@file:{}", dir.join("rules.json").display()),
+            "other": 1,
+        });
+        let resolved = resolve_json(&config.to_string());
+        assert_eq!(
+            resolved,
+            json!({
+                "ruleset": {"rules": ["a", "b"], "enabled": true},
+                "other": 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_overlay() {
+        let dir = test_dir("overlay");
+        std::fs::write(
+            dir.join("first.json"),
+            r#"{"nested": {"a": 10, "c": 3}, "list": [1]}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("second.json"), r#"{"nested": {"c": 30}}"#).unwrap();
+
+        let config = json!({
+            "@overlay": [
+                dir.join("first.json").display().to_string(),
+                dir.join("second.json").display().to_string(),
+            ],
+            "nested": {"a": 1, "b": 2},
+            "list": [1, 2, 3],
+        });
+        let resolved = resolve_json(&config.to_string());
+        assert_eq!(
+            resolved,
+            json!({
+                "nested": {"a": 10, "b": 2, "c": 30},
+                "list": [1],
+            })
+        );
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let dir = test_dir("errors");
+        let cycle = dir.join("cycle.json");
+        std::fs::write(&cycle, format!(r#""@file:{}""#, cycle.display())).unwrap();
+
+        let config = json!({"x": format!("@file:{}", cycle.display())});
+        assert!(resolve(&config.to_string()).is_err());
+
+        let config = json!({"x": format!("@file:{}", dir.join("missing.json").display())});
+        assert!(resolve(&config.to_string()).is_err());
+
+        let config = json!({"@overlay": 1});
+        assert!(resolve(&config.to_string()).is_err());
+    }
+}
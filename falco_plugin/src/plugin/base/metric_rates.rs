@@ -0,0 +1,188 @@
+use crate::plugin::base::metrics::{Metric, MetricLabel, MetricType, MetricValue};
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Get the (interned) name of the rate metric derived from a counter
+///
+/// Metric names must be `'static`, so they are leaked, but only once per counter name
+/// (not once per [`MetricRates`] instance or `get_metrics` call).
+fn rate_name(counter: &'static CStr) -> &'static CStr {
+    static NAMES: Mutex<BTreeMap<&'static CStr, &'static CStr>> = Mutex::new(BTreeMap::new());
+
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    names.entry(counter).or_insert_with(|| {
+        let mut name = counter.to_bytes().to_vec();
+        name.extend_from_slice(b"_per_sec");
+        // the counter name is a C string and the suffix does not contain NULs either
+        Box::leak(CString::new(name).unwrap_or_default().into_boxed_c_str())
+    })
+}
+
+fn as_f64(value: MetricValue) -> f64 {
+    match value {
+        MetricValue::U32(v) => v.into(),
+        MetricValue::S32(v) => v.into(),
+        MetricValue::U64(v) => v as f64,
+        MetricValue::I64(v) => v as f64,
+        MetricValue::Double(v) => v,
+        MetricValue::Float(v) => v.into(),
+        MetricValue::Int(v) => v.into(),
+    }
+}
+
+/// # Per-interval rates derived from monotonic counters
+///
+/// Operators usually care about rates (events per second etc.) rather than the ever-growing
+/// totals, but monotonic counters are the natural thing to track in a plugin. This helper
+/// bridges the two: pass all your metrics through [`MetricRates::with_rates`] in
+/// [`Plugin::get_metrics`](`crate::base::Plugin::get_metrics`) and for every
+/// [`MetricType::Monotonic`] metric, it adds a [`MetricType::NonMonotonic`] metric
+/// called `<name>_per_sec`, with the average rate since the previous call:
+///
+/// ```ignore
+/// struct MyPlugin {
+///     events: u64,
+///     rates: MetricRates,
+/// }
+///
+/// impl Plugin for MyPlugin {
+///     // ...
+///     fn get_metrics(&mut self) -> impl IntoIterator<Item = Metric> {
+///         // reports `events` and `events_per_sec`
+///         self.rates.with_rates([
+///             MetricLabel::new(c"events", MetricType::Monotonic).with_value(self.events),
+///         ])
+///     }
+/// }
+/// ```
+///
+/// The first interval starts when the helper is created and counters are assumed to start
+/// at zero, so create it along with the counters (e.g. in `Plugin::new`). A counter that goes
+/// down is assumed to have been reset to zero in the meantime.
+///
+/// The rate metrics come after all the original metrics, in the same order as the counters.
+#[derive(Debug)]
+pub struct MetricRates {
+    last_values: BTreeMap<&'static CStr, f64>,
+    last_time: Instant,
+}
+
+impl Default for MetricRates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricRates {
+    /// Create a new helper, starting the first interval now
+    pub fn new() -> Self {
+        Self {
+            last_values: BTreeMap::new(),
+            last_time: Instant::now(),
+        }
+    }
+
+    /// Return `metrics` along with the rates of all monotonic counters among them
+    pub fn with_rates(&mut self, metrics: impl IntoIterator<Item = Metric>) -> Vec<Metric> {
+        self.with_rates_at(metrics, Instant::now())
+    }
+
+    fn with_rates_at(
+        &mut self,
+        metrics: impl IntoIterator<Item = Metric>,
+        now: Instant,
+    ) -> Vec<Metric> {
+        let elapsed = now.duration_since(self.last_time).as_secs_f64();
+        self.last_time = now;
+
+        let mut out = Vec::new();
+        let mut rates = Vec::new();
+        for metric in metrics {
+            if metric.label.metric_type == MetricType::Monotonic {
+                let name = metric.label.name;
+                let value = as_f64(metric.value);
+                let last = self.last_values.insert(name, value).unwrap_or(0.0);
+                let delta = match value >= last {
+                    true => value - last,
+                    false => value,
+                };
+                let rate = match elapsed > 0.0 {
+                    true => delta / elapsed,
+                    false => 0.0,
+                };
+
+                rates.push(
+                    MetricLabel::new(rate_name(name), MetricType::NonMonotonic)
+                        .with_value(MetricValue::Double(rate)),
+                );
+            }
+            out.push(metric);
+        }
+
+        out.extend(rates);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MetricRates;
+    use crate::plugin::base::metrics::{Metric, MetricLabel, MetricType, MetricValue};
+    use std::time::{Duration, Instant};
+
+    fn values(metrics: &[Metric]) -> Vec<(&str, MetricValue)> {
+        metrics
+            .iter()
+            .map(|m| (m.label.name.to_str().unwrap(), m.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_rates() {
+        let start = Instant::now();
+        let mut rates = MetricRates {
+            last_values: Default::default(),
+            last_time: start,
+        };
+        let events = MetricLabel::new(c"events", MetricType::Monotonic);
+        let queued = MetricLabel::new(c"queued", MetricType::NonMonotonic);
+
+        let metrics = rates.with_rates_at(
+            [events.with_value(10u64), queued.with_value(3u32)],
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(
+            values(&metrics),
+            [
+                ("events", MetricValue::U64(10)),
+                ("queued", MetricValue::U32(3)),
+                ("events_per_sec", MetricValue::Double(5.0)),
+            ]
+        );
+
+        let metrics =
+            rates.with_rates_at([events.with_value(30u64)], start + Duration::from_secs(4));
+        assert_eq!(
+            values(&metrics)[1],
+            ("events_per_sec", MetricValue::Double(10.0))
+        );
+
+        // the counter was reset and counted up to 4 since then
+        let metrics =
+            rates.with_rates_at([events.with_value(4u64)], start + Duration::from_secs(6));
+        assert_eq!(
+            values(&metrics)[1],
+            ("events_per_sec", MetricValue::Double(2.0))
+        );
+
+        // no time passed
+        let metrics =
+            rates.with_rates_at([events.with_value(8u64)], start + Duration::from_secs(6));
+        assert_eq!(
+            values(&metrics)[1],
+            ("events_per_sec", MetricValue::Double(0.0))
+        );
+    }
+}
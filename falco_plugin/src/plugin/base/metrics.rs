@@ -1,3 +1,4 @@
+use crate::plugin::convert;
 use falco_plugin_api::{
     ss_plugin_metric, ss_plugin_metric_type, ss_plugin_metric_type_SS_PLUGIN_METRIC_TYPE_MONOTONIC,
     ss_plugin_metric_type_SS_PLUGIN_METRIC_TYPE_NON_MONOTONIC, ss_plugin_metric_value,
@@ -9,7 +10,9 @@ use falco_plugin_api::{
     ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_U32,
     ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_U64,
 };
-use std::ffi::CStr;
+use std::collections::BTreeSet;
+use std::ffi::{CStr, CString};
+use std::sync::Mutex;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[allow(missing_docs)]
@@ -25,8 +28,33 @@ impl MetricType {
             Self::NonMonotonic => ss_plugin_metric_type_SS_PLUGIN_METRIC_TYPE_NON_MONOTONIC,
         }
     }
+
+    /// Decode a metric type from the raw API representation
+    ///
+    /// Returns `None` for metric types not known to the SDK
+    pub fn from_raw(raw: ss_plugin_metric_type) -> Option<Self> {
+        [Self::Monotonic, Self::NonMonotonic]
+            .into_iter()
+            .find(|metric_type| metric_type.as_raw() == raw)
+    }
 }
 
+/// The value of a metric
+///
+/// The Falco plugin API supports a fixed set of value types, represented by the variants
+/// of this enum. Other Rust numeric types can be converted using [`From`], according
+/// to the following rules:
+///
+/// | Rust type         | Metric value           |
+/// |-------------------|------------------------|
+/// | `u8`, `u16`       | [`MetricValue::U32`]   |
+/// | `i8`, `i16`       | [`MetricValue::S32`]   |
+/// | `usize`           | [`MetricValue::U64`]   |
+/// | `isize`           | [`MetricValue::I64`]   |
+/// | `bool`            | [`MetricValue::U32`] (0 or 1) |
+///
+/// All these conversions are lossless (`usize` and `isize` are at most 64 bits wide
+/// on all supported platforms).
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[allow(missing_docs)]
 pub enum MetricValue {
@@ -72,6 +100,43 @@ impl MetricValue {
             ),
         }
     }
+
+    /// Decode a metric value from the raw API representation
+    ///
+    /// Returns `None` for value types not known to the SDK, so that the caller can decide
+    /// what to do with the metric (e.g. log a warning and skip it)
+    ///
+    /// # Safety
+    /// `value` must hold a value of the type described by `value_type`
+    pub unsafe fn from_raw(
+        value_type: ss_plugin_metric_value_type,
+        value: ss_plugin_metric_value,
+    ) -> Option<Self> {
+        Some(match value_type {
+            t if t == ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_U32 => {
+                Self::U32(value.u32_)
+            }
+            t if t == ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_S32 => {
+                Self::S32(value.s32)
+            }
+            t if t == ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_U64 => {
+                Self::U64(value.u64_)
+            }
+            t if t == ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_S64 => {
+                Self::I64(value.s64)
+            }
+            t if t == ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_D => {
+                Self::Double(value.d)
+            }
+            t if t == ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_F => {
+                Self::Float(value.f)
+            }
+            t if t == ss_plugin_metric_value_type_SS_PLUGIN_METRIC_VALUE_TYPE_I => {
+                Self::Int(value.i)
+            }
+            _ => return None,
+        })
+    }
 }
 
 /// A descriptor for a metric
@@ -80,8 +145,8 @@ impl MetricValue {
 /// contain a specific value
 #[derive(Debug, Clone)]
 pub struct MetricLabel {
-    name: &'static CStr,
-    metric_type: MetricType,
+    pub(in crate::plugin::base) name: &'static CStr,
+    pub(in crate::plugin::base) metric_type: MetricType,
 }
 
 impl MetricLabel {
@@ -91,10 +156,13 @@ impl MetricLabel {
     }
 
     /// Create a [`Metric`], assigning a specific value to a label
-    pub fn with_value(&self, value: MetricValue) -> Metric {
+    ///
+    /// The value can be a [`MetricValue`] or any type that converts into one
+    /// (see [`MetricValue`] for the supported conversions).
+    pub fn with_value(&self, value: impl Into<MetricValue>) -> Metric {
         Metric {
             label: self.clone(),
-            value,
+            value: value.into(),
         }
     }
 }
@@ -104,8 +172,8 @@ impl MetricLabel {
 /// This is what gets emitted to the Falco Plugin API (after a conversion to the required format)
 #[derive(Debug)]
 pub struct Metric {
-    label: MetricLabel,
-    value: MetricValue,
+    pub(in crate::plugin::base) label: MetricLabel,
+    pub(in crate::plugin::base) value: MetricValue,
 }
 
 impl Metric {
@@ -126,3 +194,199 @@ impl Metric {
         }
     }
 }
+
+/// Get a `'static` metric name for a dynamically generated string
+///
+/// Metric names must be `'static`, so they are leaked, but only once per distinct name
+/// (not once per caller), which keeps the leak bounded by the number of distinct names used.
+/// NUL bytes are stripped from the name.
+pub(crate) fn intern_metric_name(mut name: String) -> &'static CStr {
+    static NAMES: Mutex<BTreeSet<&'static CStr>> = Mutex::new(BTreeSet::new());
+
+    name.retain(|c| c != '\0');
+    let name = CString::new(name).unwrap_or_default();
+
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = names.get(name.as_c_str()) {
+        return interned;
+    }
+
+    let interned: &'static CStr = Box::leak(name.into_boxed_c_str());
+    names.insert(interned);
+    interned
+}
+
+/// Caps the number of distinct metric names emitted from a single `get_metrics` call
+///
+/// Metrics with names beyond the limit are dropped and counted in a single overflow metric,
+/// `sdk.metrics_dropped`, emitted after all the other metrics.
+#[derive(Debug, Default)]
+pub(crate) struct MetricLimiter {
+    dropped: u64,
+}
+
+impl MetricLimiter {
+    const OVERFLOW_NAME: &'static CStr = c"sdk.metrics_dropped";
+
+    pub(crate) fn collect(
+        &mut self,
+        metrics: impl IntoIterator<Item = Metric>,
+        max_names: usize,
+        out: &mut Vec<ss_plugin_metric>,
+    ) {
+        let mut names = BTreeSet::new();
+        let mut dropped = 0u64;
+        for metric in metrics {
+            if names.len() >= max_names && !names.contains(metric.label.name) {
+                dropped += 1;
+                continue;
+            }
+            names.insert(metric.label.name);
+            out.push(metric.as_raw());
+        }
+
+        if dropped > 0 {
+            // only complain when it gets worse, as this runs every time metrics are collected
+            if dropped > self.dropped {
+                log::warn!(
+                    "Dropped {} metrics over the limit of {} distinct names, see {:?}",
+                    dropped,
+                    max_names,
+                    Self::OVERFLOW_NAME
+                );
+            }
+            let overflow = MetricLabel::new(Self::OVERFLOW_NAME, MetricType::NonMonotonic);
+            out.push(overflow.with_value(dropped).as_raw());
+        }
+        self.dropped = dropped;
+    }
+}
+
+macro_rules! impl_metric_value_from {
+    ($($ty:ty => $variant:ident($conv:expr)),* $(,)?) => {
+        $(impl From<$ty> for MetricValue {
+            fn from(value: $ty) -> Self {
+                Self::$variant($conv(value))
+            }
+        })*
+    };
+}
+
+impl_metric_value_from! {
+    u8 => U32(u32::from),
+    u16 => U32(u32::from),
+    u32 => U32(std::convert::identity),
+    i8 => S32(i32::from),
+    i16 => S32(i32::from),
+    i32 => S32(std::convert::identity),
+    u64 => U64(std::convert::identity),
+    i64 => I64(std::convert::identity),
+    f32 => Float(std::convert::identity),
+    f64 => Double(std::convert::identity),
+    bool => U32(u32::from),
+}
+
+impl From<usize> for MetricValue {
+    fn from(value: usize) -> Self {
+        Self::U64(convert::saturating(value))
+    }
+}
+
+impl From<isize> for MetricValue {
+    fn from(value: isize) -> Self {
+        Self::I64(convert::saturating(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{intern_metric_name, MetricLabel, MetricLimiter, MetricType, MetricValue};
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_intern_metric_name() {
+        let name = intern_metric_name("test.interned".to_string());
+        assert_eq!(name, c"test.interned");
+        assert!(std::ptr::eq(
+            name,
+            intern_metric_name("test.inte\0rned".to_string())
+        ));
+    }
+
+    #[test]
+    fn test_value_roundtrip() {
+        let values = [
+            MetricValue::U32(1),
+            MetricValue::S32(-2),
+            MetricValue::U64(u64::MAX),
+            MetricValue::I64(i64::MIN),
+            MetricValue::Double(0.5),
+            MetricValue::Float(-0.25),
+            MetricValue::Int(7),
+        ];
+
+        for value in values {
+            let (value_type, raw) = value.as_raw();
+            assert_eq!(
+                unsafe { MetricValue::from_raw(value_type, raw) },
+                Some(value)
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_value_type() {
+        let (_, raw) = MetricValue::U32(1).as_raw();
+        assert_eq!(unsafe { MetricValue::from_raw(u32::MAX as _, raw) }, None);
+    }
+
+    #[test]
+    fn test_type_roundtrip() {
+        for metric_type in [MetricType::Monotonic, MetricType::NonMonotonic] {
+            assert_eq!(
+                MetricType::from_raw(metric_type.as_raw()),
+                Some(metric_type)
+            );
+        }
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(MetricValue::from(5usize), MetricValue::U64(5));
+        assert_eq!(MetricValue::from(-5isize), MetricValue::I64(-5));
+        assert_eq!(MetricValue::from(5u16), MetricValue::U32(5));
+        assert_eq!(MetricValue::from(-5i8), MetricValue::S32(-5));
+        assert_eq!(MetricValue::from(true), MetricValue::U32(1));
+    }
+
+    #[test]
+    fn test_metric_limiter() {
+        let a = MetricLabel::new(c"a", MetricType::Monotonic);
+        let b = MetricLabel::new(c"b", MetricType::Monotonic);
+        let c = MetricLabel::new(c"c", MetricType::Monotonic);
+        let metrics = || {
+            [
+                a.with_value(1u64),
+                b.with_value(2u64),
+                a.with_value(3u64),
+                c.with_value(4u64),
+            ]
+        };
+
+        let mut limiter = MetricLimiter::default();
+        let mut out = Vec::new();
+        limiter.collect(metrics(), 2, &mut out);
+
+        let names: Vec<_> = out
+            .iter()
+            .map(|m| unsafe { CStr::from_ptr(m.name) })
+            .collect();
+        assert_eq!(names, [c"a", c"b", c"a", c"sdk.metrics_dropped"]);
+        assert_eq!(unsafe { out[3].value.u64_ }, 1);
+
+        out.clear();
+        limiter.collect(metrics(), 3, &mut out);
+        assert_eq!(out.len(), 4);
+        assert_eq!(limiter.dropped, 0);
+    }
+}
@@ -1,15 +1,30 @@
-use crate::plugin::base::metrics::Metric;
+use crate::plugin::base::config_watch::ConfigWatch;
+use crate::plugin::base::health::{Health, HealthTracker};
+use crate::plugin::base::metrics::{Metric, MetricLimiter};
+use crate::plugin::base::scope::EventScope;
+use crate::plugin::base::storage_stats::BumpStats;
+use crate::plugin::error::error_buffer::ErrorBuffer;
 use crate::plugin::error::last_error::LastError;
+use crate::plugin::extract::storage::FieldStorage;
+use crate::plugin::extract::trace::ExtractTracer;
+#[cfg(feature = "record")]
+use crate::plugin::record::Recording;
 use crate::plugin::schema::ConfigSchema;
+use crate::plugin::source::rates::SourceRates;
 use crate::plugin::tables::vtable::TablesInput;
-use crate::strings::cstring_writer::WriteIntoCString;
 use falco_plugin_api::ss_plugin_metric;
 use std::ffi::{CStr, CString};
 use std::fmt::Display;
-use std::io::Write;
+use std::time::Duration;
 
+pub mod config_watch;
+pub mod health;
+pub(crate) mod includes;
 mod logger;
+pub mod metric_rates;
 pub mod metrics;
+pub(crate) mod scope;
+pub(crate) mod storage_stats;
 #[doc(hidden)]
 pub mod wrappers;
 
@@ -24,36 +39,74 @@ pub(crate) struct ActualPlugin<P: Plugin> {
 #[doc(hidden)]
 pub struct PluginWrapper<P: Plugin> {
     pub(crate) plugin: Option<ActualPlugin<P>>,
-    pub(crate) error_buf: CString,
-    pub(crate) field_storage: bumpalo::Bump,
+    pub(crate) error_buf: ErrorBuffer,
+    pub(crate) field_storage: FieldStorage,
+    pub(crate) extract_tracer: ExtractTracer,
+    pub(crate) field_storage_stats: BumpStats,
+    pub(crate) batch_storage_stats: BumpStats,
+    pub(crate) timestamp_clamps: Option<u64>,
+    pub(crate) dedup_dropped: Option<u64>,
+    pub(crate) source_rates: Option<SourceRates>,
     pub(crate) string_storage: CString,
     pub(crate) metric_storage: Vec<ss_plugin_metric>,
+    pub(crate) metric_limiter: MetricLimiter,
+    pub(crate) health: HealthTracker,
+    pub(crate) event_scope: EventScope,
+    pub(crate) extract_event_types: Vec<u16>,
+    pub(crate) parse_event_types: Vec<u16>,
+    #[cfg(feature = "record")]
+    pub(crate) recording: Option<Recording>,
 }
 
 impl<P: Plugin> PluginWrapper<P> {
     pub fn new(plugin: P, last_error: LastError) -> Self {
         Self {
             plugin: Some(ActualPlugin { plugin, last_error }),
-            error_buf: Default::default(),
-            field_storage: bumpalo::Bump::new(),
+            error_buf: ErrorBuffer::new(P::ERROR_DEDUP_INTERVAL),
+            field_storage: FieldStorage::new(),
+            extract_tracer: Default::default(),
+            field_storage_stats: Default::default(),
+            batch_storage_stats: Default::default(),
+            timestamp_clamps: None,
+            dedup_dropped: None,
+            source_rates: None,
             string_storage: Default::default(),
             metric_storage: Default::default(),
+            metric_limiter: Default::default(),
+            health: Default::default(),
+            event_scope: Default::default(),
+            extract_event_types: Default::default(),
+            parse_event_types: Default::default(),
+            #[cfg(feature = "record")]
+            recording: None,
         }
     }
 
     pub fn new_error(err: impl Display) -> Self {
         let mut plugin = Self {
             plugin: None,
-            error_buf: Default::default(),
-            field_storage: bumpalo::Bump::new(),
+            error_buf: ErrorBuffer::new(P::ERROR_DEDUP_INTERVAL),
+            field_storage: FieldStorage::new(),
+            extract_tracer: Default::default(),
+            field_storage_stats: Default::default(),
+            batch_storage_stats: Default::default(),
+            timestamp_clamps: None,
+            dedup_dropped: None,
+            source_rates: None,
             string_storage: Default::default(),
             metric_storage: vec![],
+            metric_limiter: Default::default(),
+            health: Default::default(),
+            event_scope: Default::default(),
+            extract_event_types: Default::default(),
+            parse_event_types: Default::default(),
+            #[cfg(feature = "record")]
+            recording: None,
         };
 
-        plugin
-            .error_buf
-            .write_into(|buf| write!(buf, "{}", err))
-            .unwrap_or_else(|err| panic!("Failed to write error message (was: {})", err));
+        if !plugin.error_buf.set(&err) {
+            panic!("Failed to write error message (was: {})", err);
+        }
 
         plugin
     }
@@ -200,6 +253,13 @@ pub trait Plugin: Sized {
     ///     // ...
     /// }
     /// ```
+    ///
+    /// ### Rejecting unknown keys
+    ///
+    /// By default, keys not present in your config struct are silently ignored, so a typo
+    /// in an optional setting simply leaves it at its default value. Use
+    /// [`StrictJson<T>`](`crate::base::StrictJson`) instead of `Json<T>` to make plugin
+    /// initialization (and config updates) fail with an error listing all the unknown keys.
     type ConfigType: ConfigSchema;
 
     /// This method takes a [`TablesInput`](`crate::tables::TablesInput`) instance, which lets you
@@ -210,11 +270,25 @@ pub trait Plugin: Sized {
 
     /// Update the configuration of a running plugin
     ///
+    /// If source instances or background tasks need to see the new configuration,
+    /// return a [`ConfigWatch`] from [`Plugin::config_watch`].
+    ///
     /// The default implementation does nothing
     fn set_config(&mut self, _config: Self::ConfigType) -> Result<(), anyhow::Error> {
         Ok(())
     }
 
+    /// Return the [`ConfigWatch`] to keep up to date with configuration updates
+    ///
+    /// After every successful [`Plugin::set_config`] call, the SDK stores the new configuration
+    /// in the returned watch, so all its clones (e.g. in source instances or background tasks)
+    /// see the update. Rejected updates are not stored.
+    ///
+    /// The default implementation returns `None`
+    fn config_watch(&self) -> Option<&ConfigWatch<Self::ConfigType>> {
+        None
+    }
+
     /// Return the plugin metrics
     ///
     /// Metrics are described by:
@@ -230,6 +304,9 @@ pub trait Plugin: Sized {
     /// probably a good idea to do so, or at least not to change the type of metric or the type
     /// of its value from call to call.
     ///
+    /// To also report per-interval rates of your monotonic counters (e.g. events per second),
+    /// pass the metrics through [`crate::base::MetricRates`].
+    ///
     /// There are two general patterns to use when emitting metrics from a plugin:
     ///
     /// 1. Predefined metrics
@@ -311,4 +388,63 @@ pub trait Plugin: Sized {
     fn get_metrics(&mut self) -> impl IntoIterator<Item = Metric> {
         []
     }
+
+    /// Check the health of the plugin
+    ///
+    /// Use this to report problems with plugin-internal dependencies, like an unreachable
+    /// remote API or a queue that keeps filling up. The SDK calls this method every time
+    /// the metrics are collected and:
+    /// - emits a `healthy` metric (1 or 0) after the metrics returned by [`Plugin::get_metrics`]
+    ///   (the framework prepends the plugin name, so it shows up as e.g. `myplugin.healthy`)
+    /// - logs every change of the health status, including the reason for [`Health::Unhealthy`]
+    ///
+    /// The default implementation returns [`Health::Unknown`], which does not emit the metric.
+    ///
+    /// This method should be cheap, as it gets called on every metrics collection. If checking
+    /// the health takes a while (e.g. requires a network call), do it in the background
+    /// and only return the latest result here.
+    fn health_check(&mut self) -> Health {
+        Health::Unknown
+    }
+
+    /// Report the memory used by the SDK on behalf of the plugin
+    ///
+    /// If set to true, the metrics returned by [`Plugin::get_metrics`] are followed by:
+    /// - `sdk.field_storage_bytes`: memory allocated for the values extracted from the last event
+    /// - `sdk.field_storage_peak_bytes`: the largest value of `sdk.field_storage_bytes` so far
+    /// - `sdk.batch_storage_bytes`: memory allocated for the last batch of events
+    /// - `sdk.batch_storage_peak_bytes`: the largest value of `sdk.batch_storage_bytes` so far
+    ///
+    /// The values are in bytes and count the memory allocated from the system (so they
+    /// also include the unused space in the allocated chunks). The field storage metrics
+    /// are only meaningful for plugins with the field extraction capability, and the batch
+    /// storage metrics for plugins with the event sourcing capability.
+    ///
+    /// See also [`ExtractPlugin::field_storage_limit`](`crate::extract::ExtractPlugin::field_storage_limit`)
+    /// and [`SourcePlugin::batch_storage_limit`](`crate::source::SourcePlugin::batch_storage_limit`)
+    /// for limiting the memory use.
+    const STORAGE_METRICS: bool = false;
+
+    /// The maximum number of distinct metric names returned from [`Plugin::get_metrics`]
+    ///
+    /// Emitting metrics per a dynamic key (e.g. one metric per container) can easily produce
+    /// more distinct metrics than the monitoring system can handle. To protect against this,
+    /// the SDK only passes metrics with the first `MAX_METRICS` distinct names to the framework.
+    /// The rest are dropped (with a warning in the log) and their number is reported as
+    /// the `sdk.metrics_dropped` metric.
+    ///
+    /// The SDK storage metrics (see [`Plugin::STORAGE_METRICS`]) do not count against the limit.
+    const MAX_METRICS: usize = 1000;
+
+    /// How long to suppress repeats of the same error message
+    ///
+    /// When a plugin method keeps failing with the same error (e.g. a parse plugin hitting
+    /// a persistent error condition for every event), the SDK only stores (and logs) the message
+    /// once per `ERROR_DEDUP_INTERVAL`. The number of suppressed repeats is logged when
+    /// the message changes or the interval expires, and their total is reported in
+    /// the `sdk.errors_suppressed` metric. The error is still reported to the framework
+    /// each time.
+    ///
+    /// Set this to [`Duration::ZERO`] to record every error.
+    const ERROR_DEDUP_INTERVAL: Duration = Duration::from_secs(1);
 }
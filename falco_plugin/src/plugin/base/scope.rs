@@ -0,0 +1,278 @@
+use crate::extract::EventInput;
+use crate::plugin::schema::ConfigSchema;
+use anyhow::Context;
+use falco_event::events::types::EventType;
+use num_traits::{FromPrimitive, ToPrimitive};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::CString;
+use std::sync::OnceLock;
+
+/// The config key narrowing the event types handled by the plugin
+pub(crate) const EVENT_TYPES_KEY: &str = "sdk_event_types";
+
+/// The config key narrowing the event sources handled by the plugin
+pub(crate) const EVENT_SOURCES_KEY: &str = "sdk_event_sources";
+
+/// Runtime overrides of `EVENT_TYPES` and `EVENT_SOURCES`
+///
+/// Operators can narrow the scope of a parse or extract plugin by adding these keys
+/// to a JSON object config:
+/// - `sdk_event_types`: a list of event types, either as numbers or names (e.g. `"ASYNCEVENT_E"`)
+/// - `sdk_event_sources`: a list of event source names
+///
+/// The keys are removed from the config before it's passed to the plugin. The overrides can only
+/// narrow the compile-time lists: an event type or source that's not declared by the plugin
+/// still won't be delivered.
+///
+/// Both overrides are kept as sets, parsed once when the config is loaded, so checking
+/// an event is just a lookup.
+#[derive(Debug, Default)]
+pub(crate) struct EventScope {
+    event_types: Option<BTreeSet<u16>>,
+    event_sources: Option<BTreeSet<CString>>,
+}
+
+/// The event types declared by the capabilities of a plugin that take `EVENT_TYPES`
+///
+/// There's one entry per capability (extract and parse), `None` meaning the plugin
+/// does not implement it.
+pub(crate) type DeclaredEventTypes = [Option<&'static [EventType]>];
+
+impl EventScope {
+    /// Extract the overrides from a config string and parse the rest of the config
+    ///
+    /// The SDK keys are removed before the config is passed on to `C`. To avoid parsing
+    /// the config twice, JSON object configs with the SDK keys are handed over as the parsed
+    /// value (see [`ConfigSchema::from_value`]). Other configs are parsed unchanged.
+    ///
+    /// A `sdk_event_types` override that does not match any of the `declared` event types
+    /// of a capability is rejected, as the plugin would not get any events for that capability.
+    pub(crate) fn parse_config<C: ConfigSchema>(
+        config: &str,
+        declared: &DeclaredEventTypes,
+    ) -> Result<(Self, C), anyhow::Error> {
+        let (scope, value) = Self::from_config(config)?;
+        scope.check_declared(declared)?;
+        let config = match value {
+            Some(value) => C::from_value(value),
+            None => C::from_str(config),
+        };
+
+        Ok((scope, config.context("Failed to parse config")?))
+    }
+
+    /// Extract the overrides from a config string
+    ///
+    /// Returns the overrides and, if there were any, the parsed config with the SDK keys
+    /// removed. Configs that are not JSON objects (or do not contain the SDK keys)
+    /// are not returned, as they need to be parsed unchanged.
+    fn from_config(config: &str) -> Result<(Self, Option<serde_json::Value>), anyhow::Error> {
+        let Ok(serde_json::Value::Object(mut object)) = serde_json::from_str(config) else {
+            return Ok((Self::default(), None));
+        };
+
+        let event_types = object.remove(EVENT_TYPES_KEY);
+        let event_sources = object.remove(EVENT_SOURCES_KEY);
+        if event_types.is_none() && event_sources.is_none() {
+            return Ok((Self::default(), None));
+        }
+
+        let event_types = event_types
+            .map(|types| Self::parse_event_types(&types))
+            .transpose()
+            .with_context(|| format!("Invalid {}", EVENT_TYPES_KEY))?;
+        let event_sources = event_sources
+            .map(|sources| Self::parse_event_sources(&sources))
+            .transpose()
+            .with_context(|| format!("Invalid {}", EVENT_SOURCES_KEY))?;
+
+        let scope = Self {
+            event_types,
+            event_sources,
+        };
+        Ok((scope, Some(serde_json::Value::Object(object))))
+    }
+
+    /// Map event type names (as in `"ASYNCEVENT_E"`) to their ids
+    ///
+    /// The map is built on first use and shared by all plugins in the process.
+    fn event_type_ids() -> &'static BTreeMap<String, u16> {
+        static IDS: OnceLock<BTreeMap<String, u16>> = OnceLock::new();
+        IDS.get_or_init(|| {
+            (0..=u16::MAX)
+                .filter_map(|id| Some((format!("{:?}", EventType::from_u16(id)?), id)))
+                .collect()
+        })
+    }
+
+    fn parse_event_types(types: &serde_json::Value) -> Result<BTreeSet<u16>, anyhow::Error> {
+        let types = types
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("expected a list of event types"))?;
+        if types.is_empty() {
+            anyhow::bail!("the list of event types must not be empty");
+        }
+
+        types
+            .iter()
+            .map(|ty| match ty {
+                serde_json::Value::Number(n) => n
+                    .as_u64()
+                    .and_then(|n| u16::try_from(n).ok())
+                    .ok_or_else(|| anyhow::anyhow!("invalid event type {}", n)),
+                serde_json::Value::String(name) => Self::event_type_ids()
+                    .get(name)
+                    .copied()
+                    .ok_or_else(|| anyhow::anyhow!("unknown event type {:?}", name)),
+                other => Err(anyhow::anyhow!("invalid event type {}", other)),
+            })
+            .collect()
+    }
+
+    fn parse_event_sources(
+        sources: &serde_json::Value,
+    ) -> Result<BTreeSet<CString>, anyhow::Error> {
+        let sources: Vec<String> = serde_json::from_value(sources.clone())?;
+        if sources.is_empty() {
+            anyhow::bail!("the list of event sources must not be empty");
+        }
+
+        sources
+            .into_iter()
+            .map(|source| CString::new(source).context("invalid event source"))
+            .collect()
+    }
+
+    /// Make sure the event type override leaves some events for every capability
+    fn check_declared(&self, declared: &DeclaredEventTypes) -> Result<(), anyhow::Error> {
+        if self.event_types.is_none() {
+            return Ok(());
+        }
+
+        for types in declared.iter().flatten() {
+            if self.event_types(types).unwrap_or_default().is_empty() {
+                anyhow::bail!(
+                    "Invalid {}: no event type declared by the plugin ({:?}) is listed",
+                    EVENT_TYPES_KEY,
+                    types
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Narrow the compile-time list of event types
+    ///
+    /// Returns `None` if there's no override. The override matches at least one of the
+    /// declared types, as [`EventScope::parse_config`] rejects configs where it does not.
+    pub(crate) fn event_types(&self, declared: &[EventType]) -> Option<Vec<u16>> {
+        let overrides = self.event_types.as_ref()?;
+        let types = match declared.is_empty() {
+            true => overrides.iter().copied().collect(),
+            false => declared
+                .iter()
+                .filter_map(|ty| ty.to_u16())
+                .filter(|ty| overrides.contains(ty))
+                .collect(),
+        };
+
+        Some(types)
+    }
+
+    /// Check whether an event is within the scope set in the config
+    ///
+    /// This is only concerned with the overrides; the framework takes care of the compile-time
+    /// lists. Just like [`EventInput::source_matches`], an event without a source name
+    /// is not filtered by source.
+    pub(crate) fn allows(&self, event: &EventInput) -> bool {
+        if let (Some(sources), Some(source)) = (&self.event_sources, event.source()) {
+            if !sources.contains(source) {
+                return false;
+            }
+        }
+
+        if let Some(types) = &self.event_types {
+            let Ok(event_type) = event.event_type() else {
+                return false;
+            };
+            if !types.contains(&event_type) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventScope;
+    use crate::plugin::schema::{Json, StrictJson};
+    use falco_event::events::types::EventType;
+    use std::collections::BTreeMap;
+
+    type JsonConfig = Json<BTreeMap<String, u64>>;
+
+    #[test]
+    fn test_config_without_overrides() {
+        let (scope, config) = EventScope::parse_config::<String>(r#"{"foo": 1}"#, &[]).unwrap();
+        assert_eq!(config, r#"{"foo": 1}"#);
+        assert!(scope.event_types(&[]).is_none());
+
+        let (_, config) = EventScope::parse_config::<String>("not json", &[]).unwrap();
+        assert_eq!(config, "not json");
+    }
+
+    #[test]
+    fn test_event_type_overrides() {
+        let config = r#"{"foo": 1, "sdk_event_types": ["ASYNCEVENT_E", 3]}"#;
+        let (scope, config) = EventScope::parse_config::<JsonConfig>(config, &[]).unwrap();
+        assert_eq!(config.0, BTreeMap::from([("foo".to_string(), 1)]));
+
+        let asyncevent = EventType::ASYNCEVENT_E as u16;
+        assert_eq!(
+            scope.event_types(&[EventType::ASYNCEVENT_E]),
+            Some(vec![asyncevent])
+        );
+        let mut all = vec![3, asyncevent];
+        all.sort();
+        assert_eq!(scope.event_types(&[]), Some(all));
+    }
+
+    #[test]
+    fn test_strict_config_overrides() {
+        // the SDK keys are not passed on to the plugin, so they are not unknown keys
+        let config = r#"{"foo": 1, "sdk_event_sources": ["syscall"]}"#;
+        let (_, config) =
+            EventScope::parse_config::<StrictJson<BTreeMap<String, u64>>>(config, &[]).unwrap();
+        assert_eq!(config.0.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        let parse = |config| EventScope::parse_config::<()>(config, &[]);
+        assert!(parse(r#"{"sdk_event_types": ["NOPE"]}"#).is_err());
+        assert!(parse(r#"{"sdk_event_types": [70000]}"#).is_err());
+        assert!(parse(r#"{"sdk_event_types": []}"#).is_err());
+        assert!(parse(r#"{"sdk_event_sources": "syscall"}"#).is_err());
+        assert!(parse(r#"{"sdk_event_sources": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_undeclared_event_types() {
+        let config = r#"{"sdk_event_types": ["ASYNCEVENT_E"]}"#;
+        let asyncevent: &'static [EventType] = &[EventType::ASYNCEVENT_E];
+        let pluginevent: &'static [EventType] = &[EventType::PLUGINEVENT_E];
+        let any: &'static [EventType] = &[];
+
+        assert!(EventScope::parse_config::<()>(config, &[Some(asyncevent), None]).is_ok());
+        // a capability accepting any event type is fine with any override
+        assert!(EventScope::parse_config::<()>(config, &[Some(any), None]).is_ok());
+        // every capability needs to get some events
+        assert!(EventScope::parse_config::<()>(config, &[Some(pluginevent), None]).is_err());
+        assert!(
+            EventScope::parse_config::<()>(config, &[Some(asyncevent), Some(pluginevent)]).is_err()
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::plugin::base::metrics::{Metric, MetricLabel, MetricType, MetricValue};
+use crate::plugin::convert;
+use std::alloc::Layout;
+use std::ffi::CStr;
+use std::io::Write;
+use thiserror::Error;
+
+/// Memory usage of a bump allocator that gets reset before every call
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct BumpStats {
+    last: usize,
+    peak: usize,
+}
+
+impl BumpStats {
+    /// Record the number of bytes allocated during a single call
+    pub(crate) fn record(&mut self, allocated_bytes: usize) {
+        self.last = allocated_bytes;
+        self.peak = self.peak.max(allocated_bytes);
+    }
+
+    pub(crate) fn metrics(
+        &self,
+        last_name: &'static CStr,
+        peak_name: &'static CStr,
+    ) -> [Metric; 2] {
+        [
+            MetricLabel::new(last_name, MetricType::NonMonotonic)
+                .with_value(MetricValue::U64(convert::saturating(self.last))),
+            MetricLabel::new(peak_name, MetricType::NonMonotonic)
+                .with_value(MetricValue::U64(convert::saturating(self.peak))),
+        ]
+    }
+}
+
+/// # Failure to allocate memory from SDK-owned storage
+///
+/// The SDK stores extracted field values and batched events in bump allocators
+/// that can be capped (see [`ExtractPlugin::field_storage_limit`](`crate::extract::ExtractPlugin::field_storage_limit`)
+/// and [`SourcePlugin::batch_storage_limit`](`crate::source::SourcePlugin::batch_storage_limit`)).
+/// When an allocation fails, the SDK returns a [`std::io::Error`] of kind
+/// [`std::io::ErrorKind::OutOfMemory`] wrapping this type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum StorageError {
+    /// The allocation would exceed the configured limit
+    #[error("{0} exceeded the allocation limit of {1} bytes")]
+    LimitExceeded(&'static str, usize),
+
+    /// The allocation failed for another reason (e.g. the system ran out of memory)
+    #[error("failed to allocate {0}")]
+    AllocationFailed(&'static str),
+}
+
+impl StorageError {
+    fn new(alloc: &bumpalo::Bump, what: &'static str) -> Self {
+        match alloc.allocation_limit() {
+            Some(limit) => Self::LimitExceeded(what, limit),
+            None => Self::AllocationFailed(what),
+        }
+    }
+}
+
+impl From<StorageError> for std::io::Error {
+    fn from(err: StorageError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::OutOfMemory, err)
+    }
+}
+
+/// Allocate `val` from `alloc`, returning an error instead of panicking if that fails
+pub(crate) fn try_alloc<'a, T>(
+    alloc: &'a bumpalo::Bump,
+    what: &'static str,
+    val: T,
+) -> std::io::Result<&'a mut T> {
+    alloc
+        .try_alloc(val)
+        .map_err(|_| StorageError::new(alloc, what).into())
+}
+
+/// Copy `bytes` to `alloc` as a NUL-terminated string
+///
+/// `bytes` must not contain the terminator itself.
+pub(crate) fn try_alloc_c_str<'a>(
+    alloc: &'a bumpalo::Bump,
+    what: &'static str,
+    bytes: &[u8],
+) -> std::io::Result<&'a [u8]> {
+    let layout = Layout::array::<u8>(bytes.len() + 1)
+        .map_err(|_| std::io::Error::from(StorageError::new(alloc, what)))?;
+    let ptr = alloc
+        .try_alloc_layout(layout)
+        .map_err(|_| std::io::Error::from(StorageError::new(alloc, what)))?;
+
+    // SAFETY: we just allocated `bytes.len() + 1` bytes at `ptr`, so everything is in bounds
+    // and the fresh allocation cannot overlap `bytes`
+    unsafe {
+        let ptr = ptr.as_ptr();
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+        ptr.add(bytes.len()).write(0);
+        Ok(std::slice::from_raw_parts(ptr, bytes.len() + 1))
+    }
+}
+
+/// Reserve space for `additional` more items, returning an error instead of panicking
+pub(crate) fn try_reserve<T>(
+    vec: &mut bumpalo::collections::Vec<'_, T>,
+    additional: usize,
+    what: &'static str,
+) -> std::io::Result<()> {
+    let alloc = vec.bump();
+    vec.try_reserve(additional)
+        .map_err(|_| StorageError::new(alloc, what).into())
+}
+
+/// A writer appending to a bump-allocated vector, failing instead of panicking when it's full
+pub(crate) struct BumpWriter<'a, 'b> {
+    buf: &'b mut bumpalo::collections::Vec<'a, u8>,
+    what: &'static str,
+}
+
+impl<'a, 'b> BumpWriter<'a, 'b> {
+    pub(crate) fn new(buf: &'b mut bumpalo::collections::Vec<'a, u8>, what: &'static str) -> Self {
+        Self { buf, what }
+    }
+}
+
+impl Write for BumpWriter<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        try_reserve(self.buf, buf.len(), self.what)?;
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_alloc, try_alloc_c_str, BumpStats, BumpWriter, StorageError};
+    use std::io::Write;
+
+    #[test]
+    fn test_stats() {
+        let mut stats = BumpStats::default();
+        stats.record(100);
+        stats.record(10);
+        assert_eq!(stats.last, 10);
+        assert_eq!(stats.peak, 100);
+    }
+
+    #[test]
+    fn test_alloc_failure() {
+        let bump = bumpalo::Bump::new();
+        bump.set_allocation_limit(Some(1024));
+
+        let err = try_alloc(&bump, "test storage", [0u8; 65536]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::OutOfMemory);
+        assert_eq!(
+            err.to_string(),
+            "test storage exceeded the allocation limit of 1024 bytes"
+        );
+        assert_eq!(
+            err.get_ref().and_then(|e| e.downcast_ref::<StorageError>()),
+            Some(&StorageError::LimitExceeded("test storage", 1024))
+        );
+
+        assert_eq!(*try_alloc(&bump, "test storage", 1u8).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_alloc_c_str() {
+        let bump = bumpalo::Bump::new();
+        assert_eq!(
+            try_alloc_c_str(&bump, "test storage", b"foo").unwrap(),
+            b"foo\0"
+        );
+
+        bump.set_allocation_limit(Some(1024));
+        let err = try_alloc_c_str(&bump, "test storage", &[b'x'; 65536]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::OutOfMemory);
+    }
+
+    #[test]
+    fn test_writer_alloc_failure() {
+        let bump = bumpalo::Bump::new();
+        bump.set_allocation_limit(Some(1024));
+
+        let mut vec = bumpalo::collections::Vec::new_in(&bump);
+        let mut writer = BumpWriter::new(&mut vec, "test storage");
+        writer.write_all(b"hello").unwrap();
+        let err = writer.write_all(&[0u8; 65536]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "test storage exceeded the allocation limit of 1024 bytes"
+        );
+        assert_eq!(vec.as_slice(), b"hello");
+    }
+}
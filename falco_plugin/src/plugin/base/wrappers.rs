@@ -1,12 +1,20 @@
-use crate::base::Plugin;
+use crate::base::{MetricLabel, MetricType, MetricValue, Plugin};
+use crate::plugin::base::includes;
 use crate::plugin::base::logger::{FalcoPluginLoggerImpl, FALCO_LOGGER};
+use crate::plugin::base::scope::EventScope;
 use crate::plugin::base::PluginWrapper;
+use crate::plugin::convert;
 use crate::plugin::error::ffi_result::FfiResult;
 use crate::plugin::error::last_error::LastError;
+use crate::plugin::error::strict;
+#[cfg(feature = "record")]
+use crate::plugin::record::Recording;
 use crate::plugin::schema::{ConfigSchema, ConfigSchemaType};
+use crate::plugin::spans;
 use crate::plugin::tables::vtable::TablesInput;
 use crate::strings::from_ptr::try_str_from_ptr;
 use anyhow::Context;
+use falco_event::events::types::EventType;
 use falco_plugin_api::{
     ss_plugin_init_input, ss_plugin_metric, ss_plugin_rc, ss_plugin_rc_SS_PLUGIN_FAILURE,
     ss_plugin_rc_SS_PLUGIN_SUCCESS, ss_plugin_t,
@@ -15,6 +23,60 @@ use std::collections::BTreeMap;
 use std::ffi::{c_char, CString};
 use std::sync::Mutex;
 
+const fn parse_api_version(version: &str) -> (usize, usize, usize) {
+    let bytes = version.as_bytes();
+    let mut parts = [0usize; 3];
+    let mut part = 0;
+    let mut digits = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'0'..=b'9' => {
+                parts[part] = parts[part] * 10 + (bytes[i] - b'0') as usize;
+                digits += 1;
+            }
+            b'.' if digits > 0 && part < 2 => {
+                part += 1;
+                digits = 0;
+            }
+            _ => panic!("FALCO_PLUGIN_API_VERSION must be in the form MAJOR.MINOR.PATCH"),
+        }
+        i += 1;
+    }
+
+    if part != 2 || digits == 0 {
+        panic!("FALCO_PLUGIN_API_VERSION must be in the form MAJOR.MINOR.PATCH");
+    }
+
+    if parts[0] != falco_plugin_api::PLUGIN_API_VERSION_MAJOR as usize {
+        panic!("FALCO_PLUGIN_API_VERSION has an unsupported major version");
+    }
+    if parts[1] > falco_plugin_api::PLUGIN_API_VERSION_MINOR as usize {
+        panic!("FALCO_PLUGIN_API_VERSION is newer than the API supported by falco_plugin");
+    }
+
+    (parts[0], parts[1], parts[2])
+}
+
+/// The API version advertised by [`plugin!`](`crate::plugin!`) and
+/// [`static_plugin!`](`crate::static_plugin!`) when no version is given explicitly
+///
+/// This defaults to the version of the bundled API headers, but can be overridden
+/// by setting the `FALCO_PLUGIN_API_VERSION` environment variable (e.g. to `3.3.0`)
+/// when building. The override is validated at compile time: the major version must match
+/// and the minor version cannot be newer than the supported one. The build script makes sure
+/// the crate is rebuilt whenever the variable changes.
+pub const REQUIRED_API_VERSION: (usize, usize, usize) =
+    match option_env!("FALCO_PLUGIN_API_VERSION") {
+        Some(version) => parse_api_version(version),
+        None => (
+            falco_plugin_api::PLUGIN_API_VERSION_MAJOR as usize,
+            falco_plugin_api::PLUGIN_API_VERSION_MINOR as usize,
+            0,
+        ),
+    };
+
 pub extern "C-unwind" fn plugin_get_required_api_version<
     const MAJOR: usize,
     const MINOR: usize,
@@ -53,10 +115,15 @@ pub extern "C-unwind" fn plugin_get_contact<T: Plugin>() -> *const c_char {
 /// # Safety
 ///
 /// init_input must be null or a valid pointer
-pub unsafe extern "C-unwind" fn plugin_init<P: Plugin>(
+///
+/// `declared_event_types` lists the `EVENT_TYPES` of the extract and parse capabilities
+/// (`None` for a capability the plugin does not implement), to validate the config against.
+pub unsafe fn plugin_init<P: Plugin>(
     init_input: *const ss_plugin_init_input,
     rc: *mut ss_plugin_rc,
+    declared_event_types: &[Option<&'static [EventType]>],
 ) -> *mut falco_plugin_api::ss_plugin_t {
+    let _span = spans::init(P::NAME);
     let res = (|| -> Result<*mut PluginWrapper<P>, anyhow::Error> {
         let init_input = unsafe { init_input.as_ref() }
             .ok_or_else(|| anyhow::anyhow!("Got empty init_input"))?;
@@ -64,7 +131,11 @@ pub unsafe extern "C-unwind" fn plugin_init<P: Plugin>(
         let init_config =
             try_str_from_ptr(&init_input.config).context("Failed to get config string")?;
 
-        let config = P::ConfigType::from_str(init_config).context("Failed to parse config")?;
+        let init_config = includes::resolve(init_config).context("Failed to resolve config")?;
+        #[cfg(feature = "record")]
+        let (recording, init_config) = Recording::from_config(P::NAME, &init_config)?;
+        let (event_scope, config) =
+            EventScope::parse_config::<P::ConfigType>(&init_config, declared_event_types)?;
         if let Some(log_fn) = init_input.log_fn {
             let logger_impl = FalcoPluginLoggerImpl {
                 owner: init_input.owner,
@@ -81,13 +152,25 @@ pub unsafe extern "C-unwind" fn plugin_init<P: Plugin>(
             log::set_max_level(log::LevelFilter::Info);
         }
 
+        #[cfg(feature = "record")]
+        let recorded_input = recording.as_ref().and_then(|r| r.init_input(init_input));
+        #[cfg(feature = "record")]
+        let init_input = recorded_input.as_deref().map_or(init_input, |i| i.input());
+
         let tables_input =
             TablesInput::try_from(init_input).context("Failed to build tables input")?;
 
         let last_error = LastError::from(init_input)?;
 
-        P::new(tables_input.as_ref(), config)
-            .map(|plugin| Box::into_raw(Box::new(PluginWrapper::new(plugin, last_error))))
+        P::new(tables_input.as_ref(), config).map(|plugin| {
+            let mut wrapper = PluginWrapper::new(plugin, last_error);
+            wrapper.event_scope = event_scope;
+            #[cfg(feature = "record")]
+            {
+                wrapper.recording = recording;
+            }
+            Box::into_raw(Box::new(wrapper))
+        })
     })();
 
     match res {
@@ -112,6 +195,7 @@ pub unsafe extern "C-unwind" fn plugin_get_init_schema<P: Plugin>(
     schema_type: *mut falco_plugin_api::ss_plugin_schema_type,
 ) -> *const c_char {
     let Some(schema_type) = schema_type.as_mut() else {
+        strict::unexpected_input("plugin_get_init_schema", "NULL schema_type");
         return std::ptr::null();
     };
     match P::ConfigType::get_schema() {
@@ -147,20 +231,29 @@ pub unsafe extern "C-unwind" fn plugin_get_last_error<P: Plugin>(
     let plugin = plugin as *mut PluginWrapper<P>;
     match unsafe { plugin.as_mut() } {
         Some(plugin) => plugin.error_buf.as_ptr(),
-        None => c"no instance".as_ptr(),
+        None => {
+            strict::unexpected_input("plugin_get_last_error", "NULL plugin");
+            c"no instance".as_ptr()
+        }
     }
 }
 
-pub unsafe extern "C-unwind" fn plugin_set_config<P: Plugin>(
+/// # Safety
+///
+/// All pointers must be valid. See [`plugin_init`] for `declared_event_types`.
+pub unsafe fn plugin_set_config<P: Plugin>(
     plugin: *mut falco_plugin_api::ss_plugin_t,
     config_input: *const falco_plugin_api::ss_plugin_set_config_input,
+    declared_event_types: &[Option<&'static [EventType]>],
 ) -> falco_plugin_api::ss_plugin_rc {
     let plugin = plugin as *mut PluginWrapper<P>;
     let Some(plugin) = plugin.as_mut() else {
+        strict::unexpected_input("plugin_set_config", "NULL plugin");
         return ss_plugin_rc_SS_PLUGIN_FAILURE;
     };
 
     let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+        strict::unexpected_input("plugin_set_config", "plugin failed to initialize");
         return ss_plugin_rc_SS_PLUGIN_FAILURE;
     };
 
@@ -169,9 +262,21 @@ pub unsafe extern "C-unwind" fn plugin_set_config<P: Plugin>(
 
         let updated_config =
             try_str_from_ptr(&config_input.config).context("Failed to get config string")?;
-        let config = P::ConfigType::from_str(updated_config).context("Failed to parse config")?;
-
-        actual_plugin.plugin.set_config(config)
+        let updated_config =
+            includes::resolve(updated_config).context("Failed to resolve config")?;
+        let (event_scope, config) =
+            EventScope::parse_config::<P::ConfigType>(&updated_config, declared_event_types)?;
+
+        actual_plugin.plugin.set_config(config)?;
+        plugin.event_scope = event_scope;
+
+        if let Some(watch) = actual_plugin.plugin.config_watch() {
+            // `set_config` consumed the parsed config, so parse another copy for the watch
+            let (_, config) =
+                EventScope::parse_config::<P::ConfigType>(&updated_config, declared_event_types)?;
+            watch.set(config);
+        }
+        Ok(())
     })();
 
     res.rc(&mut plugin.error_buf)
@@ -181,26 +286,66 @@ pub unsafe extern "C-unwind" fn plugin_get_metrics<P: Plugin>(
     plugin: *mut ss_plugin_t,
     num_metrics: *mut u32,
 ) -> *mut ss_plugin_metric {
+    // check this first, so we never write through a NULL pointer
+    let Some(num_metrics) = num_metrics.as_mut() else {
+        strict::unexpected_input("plugin_get_metrics", "NULL num_metrics");
+        return std::ptr::null_mut();
+    };
     let plugin = plugin as *mut PluginWrapper<P>;
     let Some(plugin) = plugin.as_mut() else {
+        strict::unexpected_input("plugin_get_metrics", "NULL plugin");
         *num_metrics = 0;
         return std::ptr::null_mut();
     };
     let Some(ref mut actual_plugin) = &mut plugin.plugin else {
-        *num_metrics = 0;
-        return std::ptr::null_mut();
-    };
-    let Some(num_metrics) = num_metrics.as_mut() else {
+        strict::unexpected_input("plugin_get_metrics", "plugin failed to initialize");
         *num_metrics = 0;
         return std::ptr::null_mut();
     };
 
     plugin.metric_storage.clear();
-    for metric in actual_plugin.plugin.get_metrics() {
+    plugin.metric_limiter.collect(
+        actual_plugin.plugin.get_metrics(),
+        P::MAX_METRICS,
+        &mut plugin.metric_storage,
+    );
+    if let Some(metric) = plugin.health.record(actual_plugin.plugin.health_check()) {
         plugin.metric_storage.push(metric.as_raw());
     }
+    if let Some(clamps) = plugin.timestamp_clamps {
+        let metric = MetricLabel::new(c"sdk.timestamp_clamps", MetricType::Monotonic)
+            .with_value(MetricValue::U64(clamps));
+        plugin.metric_storage.push(metric.as_raw());
+    }
+    if let Some(dropped) = plugin.dedup_dropped {
+        let metric = MetricLabel::new(c"sdk.dedup_dropped", MetricType::Monotonic)
+            .with_value(MetricValue::U64(dropped));
+        plugin.metric_storage.push(metric.as_raw());
+    }
+    let suppressed = plugin.error_buf.suppressed_total();
+    if suppressed > 0 {
+        let metric = MetricLabel::new(c"sdk.errors_suppressed", MetricType::Monotonic)
+            .with_value(MetricValue::U64(suppressed));
+        plugin.metric_storage.push(metric.as_raw());
+    }
+    if let Some(rates) = plugin.source_rates.as_mut() {
+        for metric in rates.metrics() {
+            plugin.metric_storage.push(metric.as_raw());
+        }
+    }
+    if P::STORAGE_METRICS {
+        let field_storage = plugin
+            .field_storage_stats
+            .metrics(c"sdk.field_storage_bytes", c"sdk.field_storage_peak_bytes");
+        let batch_storage = plugin
+            .batch_storage_stats
+            .metrics(c"sdk.batch_storage_bytes", c"sdk.batch_storage_peak_bytes");
+        for metric in field_storage.iter().chain(batch_storage.iter()) {
+            plugin.metric_storage.push(metric.as_raw());
+        }
+    }
 
-    *num_metrics = plugin.metric_storage.len() as u32;
+    *num_metrics = convert::saturating(plugin.metric_storage.len());
     plugin.metric_storage.as_ptr().cast_mut()
 }
 
@@ -263,7 +408,12 @@ macro_rules! wrap_ffi {
 /// ```
 ///
 /// It implements a form where you can override the required API version (for example, if
-/// you wish to advertise an older version for increased compatibility):
+/// you wish to advertise an older version for increased compatibility).
+/// The default version can also be changed without touching the code, by setting
+/// the `FALCO_PLUGIN_API_VERSION` environment variable (e.g. to `3.3.0`) at build time.
+/// The version is validated at compile time against the range supported by this crate.
+///
+/// To override the version in code:
 ///
 /// ```
 /// # use std::ffi::CStr;
@@ -306,9 +456,9 @@ macro_rules! wrap_ffi {
 macro_rules! plugin {
     ($ty:ty) => {
         plugin!(
-            falco_plugin::api::PLUGIN_API_VERSION_MAJOR as usize;
-            falco_plugin::api::PLUGIN_API_VERSION_MINOR as usize;
-            0 => $ty
+            $crate::internals::base::wrappers::REQUIRED_API_VERSION.0;
+            $crate::internals::base::wrappers::REQUIRED_API_VERSION.1;
+            $crate::internals::base::wrappers::REQUIRED_API_VERSION.2 => $ty
         );
     };
     ($maj:expr; $min:expr; $patch:expr => $ty:ty) => {
@@ -369,7 +519,12 @@ macro_rules! plugin {
 /// ## Overriding the supported API version
 ///
 /// The macro also implements a form where you can override the required API version (for example,
-/// if you wish to advertise an older version for increased compatibility):
+/// if you wish to advertise an older version for increased compatibility).
+/// The default version can also be changed without touching the code, by setting
+/// the `FALCO_PLUGIN_API_VERSION` environment variable (e.g. to `3.3.0`) at build time.
+/// The version is validated at compile time against the range supported by this crate.
+///
+/// To override the version in code:
 ///
 /// ```
 ///# use std::ffi::CStr;
@@ -414,9 +569,9 @@ macro_rules! static_plugin {
     ($name:ident = $ty:ty) => {
         static_plugin!(
             $name @ (
-            falco_plugin::api::PLUGIN_API_VERSION_MAJOR as usize;
-            falco_plugin::api::PLUGIN_API_VERSION_MINOR as usize;
-            0)
+            $crate::internals::base::wrappers::REQUIRED_API_VERSION.0;
+            $crate::internals::base::wrappers::REQUIRED_API_VERSION.1;
+            $crate::internals::base::wrappers::REQUIRED_API_VERSION.2)
             = $ty
         );
 
@@ -452,24 +607,52 @@ macro_rules! base_plugin_ffi_wrappers {
             unsafe fn plugin_get_description() -> *const std::ffi::c_char;
             unsafe fn plugin_get_contact() -> *const std::ffi::c_char;
             unsafe fn plugin_get_init_schema(schema_type: *mut u32) -> *const std::ffi::c_char;
-            unsafe fn plugin_init(
-                args: *const falco_plugin::api::ss_plugin_init_input,
-                rc: *mut i32,
-            ) -> *mut falco_plugin::api::ss_plugin_t;
             unsafe fn plugin_destroy(plugin: *mut falco_plugin::api::ss_plugin_t) -> ();
             unsafe fn plugin_get_last_error(
                 plugin: *mut falco_plugin::api::ss_plugin_t,
             ) -> *const std::ffi::c_char;
-            unsafe fn plugin_set_config(
-                plugin: *mut falco_plugin::api::ss_plugin_t,
-                config_input: *const falco_plugin::api::ss_plugin_set_config_input,
-            ) -> falco_plugin::api::ss_plugin_rc;
             unsafe fn plugin_get_metrics(
                 plugin: *mut falco_plugin::api::ss_plugin_t,
                 num_metrics: *mut u32,
             ) -> *mut falco_plugin::api::ss_plugin_metric;
         }
 
+        // the config overrides are validated against the event types of the capabilities,
+        // which are only known here
+        #[$attr]
+        pub unsafe extern "C-unwind" fn plugin_init(
+            args: *const falco_plugin::api::ss_plugin_init_input,
+            rc: *mut i32,
+        ) -> *mut falco_plugin::api::ss_plugin_t {
+            use $crate::internals::extract::wrappers::ExtractPluginFallbackApi;
+            use $crate::internals::parse::wrappers::ParsePluginFallbackApi;
+            $crate::internals::base::wrappers::plugin_init::<$ty>(
+                args,
+                rc,
+                &[
+                    $crate::internals::extract::wrappers::ExtractPluginApi::<$ty>::DECLARED_EVENT_TYPES,
+                    $crate::internals::parse::wrappers::ParsePluginApi::<$ty>::DECLARED_EVENT_TYPES,
+                ],
+            )
+        }
+
+        #[$attr]
+        pub unsafe extern "C-unwind" fn plugin_set_config(
+            plugin: *mut falco_plugin::api::ss_plugin_t,
+            config_input: *const falco_plugin::api::ss_plugin_set_config_input,
+        ) -> falco_plugin::api::ss_plugin_rc {
+            use $crate::internals::extract::wrappers::ExtractPluginFallbackApi;
+            use $crate::internals::parse::wrappers::ParsePluginFallbackApi;
+            $crate::internals::base::wrappers::plugin_set_config::<$ty>(
+                plugin,
+                config_input,
+                &[
+                    $crate::internals::extract::wrappers::ExtractPluginApi::<$ty>::DECLARED_EVENT_TYPES,
+                    $crate::internals::parse::wrappers::ParsePluginApi::<$ty>::DECLARED_EVENT_TYPES,
+                ],
+            )
+        }
+
         #[allow(dead_code)]
         pub const fn __plugin_base_api() -> falco_plugin::api::plugin_api {
             use $crate::internals::async_events::wrappers::AsyncPluginFallbackApi;
@@ -503,3 +686,92 @@ macro_rules! base_plugin_ffi_wrappers {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_api_version, plugin_set_config};
+    use crate::base::{ConfigWatch, Plugin};
+    use crate::plugin::base::PluginWrapper;
+    use crate::plugin::error::last_error::LastError;
+    use crate::tables::TablesInput;
+    use falco_plugin_api::{
+        ss_plugin_owner_t, ss_plugin_rc_SS_PLUGIN_FAILURE, ss_plugin_rc_SS_PLUGIN_SUCCESS,
+        ss_plugin_set_config_input,
+    };
+    use std::ffi::{c_char, CStr};
+
+    struct WatchedPlugin {
+        watch: ConfigWatch<String>,
+    }
+
+    impl Plugin for WatchedPlugin {
+        const NAME: &'static CStr = c"watched";
+        const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+        const DESCRIPTION: &'static CStr = c"test plugin";
+        const CONTACT: &'static CStr = c"rust@localdomain.pl";
+        type ConfigType = String;
+
+        fn new(_input: Option<&TablesInput>, config: Self::ConfigType) -> anyhow::Result<Self> {
+            Ok(Self {
+                watch: ConfigWatch::new(config),
+            })
+        }
+
+        fn set_config(&mut self, config: Self::ConfigType) -> anyhow::Result<()> {
+            anyhow::ensure!(!config.is_empty(), "empty config");
+            Ok(())
+        }
+
+        fn config_watch(&self) -> Option<&ConfigWatch<Self::ConfigType>> {
+            Some(&self.watch)
+        }
+    }
+
+    unsafe extern "C-unwind" fn no_last_error(_owner: *mut ss_plugin_owner_t) -> *const c_char {
+        std::ptr::null()
+    }
+
+    #[test]
+    fn test_set_config_updates_watch() {
+        let plugin = WatchedPlugin::new(None, String::from("initial")).unwrap();
+        let watch = plugin.watch.clone();
+        let last_error = unsafe { LastError::new(std::ptr::null_mut(), no_last_error) };
+        let plugin = Box::into_raw(Box::new(PluginWrapper::new(plugin, last_error)));
+
+        let set_config = |config: &CStr| unsafe {
+            let input = ss_plugin_set_config_input {
+                config: config.as_ptr(),
+            };
+            plugin_set_config::<WatchedPlugin>(plugin.cast(), &input, &[])
+        };
+
+        assert_eq!(set_config(c"updated"), ss_plugin_rc_SS_PLUGIN_SUCCESS);
+        assert_eq!(watch.get().as_str(), "updated");
+        assert_eq!(watch.generation(), 1);
+
+        // a rejected update is not stored
+        assert_eq!(set_config(c""), ss_plugin_rc_SS_PLUGIN_FAILURE);
+        assert_eq!(watch.get().as_str(), "updated");
+        assert_eq!(watch.generation(), 1);
+
+        drop(unsafe { Box::from_raw(plugin) });
+    }
+
+    #[test]
+    fn test_parse_api_version() {
+        assert_eq!(parse_api_version("3.3.0"), (3, 3, 0));
+        assert_eq!(parse_api_version("3.0.7"), (3, 0, 7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_api_version_malformed() {
+        parse_api_version("3.3");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_api_version_wrong_major() {
+        parse_api_version("2.0.0");
+    }
+}
@@ -0,0 +1,100 @@
+use num_traits::{Bounded, Zero};
+use std::fmt::Display;
+use thiserror::Error;
+
+/// # A numeric value that does not fit in the target type
+///
+/// Returned whenever a value crossing the plugin API boundary (a table size, a field length,
+/// an event count etc.) would otherwise be silently truncated.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("value {value} out of range for {target}")]
+pub struct OutOfRange {
+    value: String,
+    target: &'static str,
+}
+
+impl OutOfRange {
+    /// The value that failed to convert
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The name of the target type
+    pub fn target(&self) -> &'static str {
+        self.target
+    }
+}
+
+impl From<OutOfRange> for std::io::Error {
+    fn from(e: OutOfRange) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// Convert a number to another type, failing if it does not fit
+#[inline]
+pub(crate) fn checked<T, U>(value: U) -> Result<T, OutOfRange>
+where
+    T: TryFrom<U>,
+    U: Copy + Display,
+{
+    T::try_from(value).map_err(|_| OutOfRange {
+        value: value.to_string(),
+        target: std::any::type_name::<T>(),
+    })
+}
+
+/// Convert a number to another type, clamping it to the range of the target type
+///
+/// This is meant for values where an approximation is better than no value at all
+/// (e.g. counters reported as metrics).
+#[inline]
+pub(crate) fn saturating<T, U>(value: U) -> T
+where
+    T: TryFrom<U> + Bounded,
+    U: Copy + PartialOrd + Zero,
+{
+    T::try_from(value).unwrap_or_else(|_| {
+        if value < U::zero() {
+            T::min_value()
+        } else {
+            T::max_value()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checked, saturating, OutOfRange};
+
+    #[test]
+    fn test_checked() {
+        assert_eq!(checked::<u32, _>(u32::MAX as u64), Ok(u32::MAX));
+        assert_eq!(checked::<i8, _>(i8::MIN as i64), Ok(i8::MIN));
+        assert_eq!(checked::<u64, _>(0usize), Ok(0));
+
+        let err: OutOfRange = checked::<u32, _>(u32::MAX as u64 + 1).unwrap_err();
+        assert_eq!(err.value(), "4294967296");
+        assert_eq!(err.target(), "u32");
+        assert_eq!(err.to_string(), "value 4294967296 out of range for u32");
+
+        assert!(checked::<u64, _>(-1i64).is_err());
+        assert!(checked::<i8, _>(128u8).is_err());
+        assert!(checked::<i8, _>(i8::MIN as i16 - 1).is_err());
+
+        let io_err: std::io::Error = err.into();
+        assert_eq!(io_err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_saturating() {
+        assert_eq!(saturating::<u32, _>(17u64), 17);
+        assert_eq!(saturating::<u32, _>(u32::MAX as u64), u32::MAX);
+        assert_eq!(saturating::<u32, _>(u32::MAX as u64 + 1), u32::MAX);
+        assert_eq!(saturating::<u32, _>(u64::MAX), u32::MAX);
+        assert_eq!(saturating::<u64, _>(-1i64), 0);
+        assert_eq!(saturating::<i32, _>(i64::MIN), i32::MIN);
+        assert_eq!(saturating::<i64, _>(isize::MAX), isize::MAX as i64);
+        assert_eq!(saturating::<usize, _>(u64::MAX), usize::MAX);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::base::Plugin;
+use crate::extract::ExtractPlugin;
+use crate::plugin::schema::{ConfigSchema, ConfigSchemaType};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// # Documentation describing a plugin
+///
+/// This gathers the metadata, the configuration schema and the extracted field schema
+/// of a plugin, straight from the plugin code, so that the plugin README or other
+/// documentation can be generated instead of maintained by hand.
+///
+/// The description can be rendered as Markdown (using [`PluginDocs::to_markdown`]) or serialized
+/// to JSON (using e.g. [`serde_json::to_string_pretty`]).
+///
+/// A convenient way to use it is a unit test in your plugin crate, e.g. one that writes
+/// the generated Markdown to a file (or compares it to the current version of the file):
+///
+/// ```ignore
+/// #[test]
+/// fn readme_fields_are_up_to_date() {
+///     let docs = PluginDocs::new::<MyPlugin>().with_fields::<MyPlugin>();
+///     let readme = std::fs::read_to_string("FIELDS.md").unwrap();
+///     assert_eq!(readme, docs.to_markdown());
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginDocs {
+    /// the name of the plugin
+    pub name: String,
+    /// the version of the plugin
+    pub version: String,
+    /// the description of the plugin
+    pub description: String,
+    /// the contact information for the plugin
+    pub contact: String,
+    /// the JSON schema of the plugin configuration, if the plugin uses a JSON config
+    #[serde(rename = "configSchema", skip_serializing_if = "Option::is_none")]
+    pub config_schema: Option<serde_json::Value>,
+    /// the fields provided by the plugin, if it has the field extraction capability
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<serde_json::Value>,
+}
+
+impl PluginDocs {
+    /// Describe the basic metadata and the configuration schema of a plugin
+    pub fn new<P: Plugin>() -> Self {
+        let config_schema = match P::ConfigType::get_schema() {
+            ConfigSchemaType::None => None,
+            ConfigSchemaType::Json(schema) => serde_json::from_slice(schema.to_bytes()).ok(),
+        };
+
+        Self {
+            name: P::NAME.to_string_lossy().into_owned(),
+            version: P::PLUGIN_VERSION.to_string_lossy().into_owned(),
+            description: P::DESCRIPTION.to_string_lossy().into_owned(),
+            contact: P::CONTACT.to_string_lossy().into_owned(),
+            config_schema,
+            fields: None,
+        }
+    }
+
+    /// Add the fields provided by a field extraction plugin
+    pub fn with_fields<P: ExtractPlugin>(mut self) -> Self {
+        self.fields = serde_json::from_slice(P::get_fields().to_bytes()).ok();
+        self
+    }
+
+    /// Render the documentation as Markdown
+    ///
+    /// The extracted fields are rendered as a table, the configuration schema
+    /// as a JSON code block.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        // writing to a String never fails
+        let _ = self.write_markdown(&mut out);
+        out
+    }
+
+    fn write_markdown(&self, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "# {} {}", self.name, self.version)?;
+        writeln!(out)?;
+        writeln!(out, "{}", self.description)?;
+        writeln!(out)?;
+        writeln!(out, "Contact: {}", self.contact)?;
+
+        if let Some(fields) = self.fields.as_ref().and_then(|f| f.as_array()) {
+            writeln!(out)?;
+            writeln!(out, "## Fields")?;
+            writeln!(out)?;
+            writeln!(out, "| Name | Type | Argument | Description |")?;
+            writeln!(out, "|------|------|----------|-------------|")?;
+            for field in fields {
+                let mut field_type = str_field(field, "type").to_string();
+                if field.get("isList").and_then(|v| v.as_bool()) == Some(true) {
+                    field_type.push_str(" (list)");
+                }
+                writeln!(
+                    out,
+                    "| `{}` | `{}` | {} | {} |",
+                    str_field(field, "name"),
+                    field_type,
+                    describe_arg(field.get("arg")),
+                    escape_markdown_cell(str_field(field, "desc")),
+                )?;
+            }
+        }
+
+        if let Some(schema) = &self.config_schema {
+            writeln!(out)?;
+            writeln!(out, "## Configuration")?;
+            writeln!(out)?;
+            writeln!(out, "```json")?;
+            writeln!(
+                out,
+                "{}",
+                serde_json::to_string_pretty(schema).map_err(|_| std::fmt::Error)?
+            )?;
+            writeln!(out, "```")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn str_field<'a>(field: &'a serde_json::Value, name: &str) -> &'a str {
+    field.get(name).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn describe_arg(arg: Option<&serde_json::Value>) -> &'static str {
+    let Some(arg) = arg else {
+        return "";
+    };
+    let flag = |name: &str| arg.get(name).and_then(|v| v.as_bool()) == Some(true);
+
+    match (flag("isIndex"), flag("isKey"), flag("isRequired")) {
+        (true, _, true) => "index (required)",
+        (true, _, false) => "index (optional)",
+        (_, true, true) => "key (required)",
+        (_, true, false) => "key (optional)",
+        _ => "",
+    }
+}
+
+fn escape_markdown_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe_arg, escape_markdown_cell};
+
+    #[test]
+    fn test_describe_arg() {
+        assert_eq!(describe_arg(None), "");
+        assert_eq!(
+            describe_arg(Some(&serde_json::json!({"isKey": true}))),
+            "key (optional)"
+        );
+        assert_eq!(
+            describe_arg(Some(
+                &serde_json::json!({"isIndex": true, "isRequired": true})
+            )),
+            "index (required)"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_markdown_cell("a|b\nc"), "a\\|b c");
+    }
+}
@@ -0,0 +1,201 @@
+use std::ffi::{c_char, CString};
+use std::fmt;
+use std::fmt::{Display, Write};
+use std::time::{Duration, Instant};
+
+/// The last error message, as returned to the framework from `plugin_get_last_error`
+///
+/// Plugins hitting a persistent error condition (e.g. a parse plugin failing on every event)
+/// tend to report the same error over and over again. Within `interval`, a repeated message
+/// is only counted: the buffer already holds it, so there's no need to reallocate it (or to log
+/// it again). The number of suppressed repeats is logged when the message changes or
+/// the interval expires.
+///
+/// Outside the interval, a message is not compared at all. Within it, the message is compared
+/// with the stored one while it's being formatted, stopping at the first difference, so
+/// a repeat is never copied anywhere.
+///
+/// Only actual failures are deduplicated: status messages (like a timeout or end of data)
+/// are stored with [`ErrorBuffer::set_status`] and never counted as repeats.
+#[derive(Debug)]
+pub struct ErrorBuffer {
+    buf: CString,
+    scratch: String,
+    interval: Duration,
+    recorded_at: Option<Instant>,
+    suppressed: u64,
+    suppressed_total: u64,
+}
+
+impl ErrorBuffer {
+    /// Create an empty buffer, deduplicating messages repeated within `interval`
+    ///
+    /// A zero `interval` disables deduplication.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            buf: CString::default(),
+            scratch: String::new(),
+            interval,
+            recorded_at: None,
+            suppressed: 0,
+            suppressed_total: 0,
+        }
+    }
+
+    /// Get a pointer to the last error message
+    pub fn as_ptr(&self) -> *const c_char {
+        self.buf.as_ptr()
+    }
+
+    /// Store an error message
+    ///
+    /// Returns `false` if the message was a repeat that has been suppressed.
+    /// Messages containing NUL bytes are ignored.
+    pub fn set(&mut self, msg: impl Display) -> bool {
+        self.store(msg, true)
+    }
+
+    /// Store a status message that does not indicate a failure
+    ///
+    /// The message is never deduplicated. Returns `false` if it contained NUL bytes
+    /// (and was ignored).
+    pub fn set_status(&mut self, msg: impl Display) -> bool {
+        self.store(msg, false)
+    }
+
+    /// Get the number of repeated messages suppressed since the buffer was created
+    pub fn suppressed_total(&self) -> u64 {
+        self.suppressed_total
+    }
+
+    fn store(&mut self, msg: impl Display, dedup: bool) -> bool {
+        let now = Instant::now();
+        if dedup && self.is_repeat(&msg, now) {
+            self.suppressed += 1;
+            self.suppressed_total += 1;
+            return false;
+        }
+
+        self.scratch.clear();
+        if write!(self.scratch, "{}", msg).is_err() {
+            return false;
+        }
+
+        self.flush_suppressed();
+        match CString::new(self.scratch.as_bytes()) {
+            Ok(msg) => self.buf = msg,
+            Err(_) => return false,
+        }
+        self.recorded_at = dedup.then_some(now);
+
+        true
+    }
+
+    /// Check if `msg` repeats the stored failure within the interval
+    fn is_repeat(&self, msg: &impl Display, now: Instant) -> bool {
+        let Some(recorded_at) = self.recorded_at else {
+            return false;
+        };
+        if now.duration_since(recorded_at) >= self.interval {
+            return false;
+        }
+
+        let mut expected = ExpectedOutput(self.buf.as_bytes());
+        write!(expected, "{}", msg).is_ok() && expected.0.is_empty()
+    }
+
+    #[cfg(test)]
+    fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+
+    fn flush_suppressed(&mut self) {
+        if self.suppressed > 0 {
+            log::warn!(
+                "Previous error repeated {} more times: {}",
+                self.suppressed,
+                self.buf.to_string_lossy()
+            );
+            self.suppressed = 0;
+        }
+    }
+}
+
+/// A formatting sink that matches the output against the expected bytes
+///
+/// Fails as soon as the output differs, which aborts the formatting.
+struct ExpectedOutput<'a>(&'a [u8]);
+
+impl Write for ExpectedOutput<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let rest = self.0.strip_prefix(s.as_bytes()).ok_or(fmt::Error)?;
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl Default for ErrorBuffer {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ErrorBuffer;
+    use std::ffi::CStr;
+    use std::time::Duration;
+
+    fn message(buf: &ErrorBuffer) -> &CStr {
+        unsafe { CStr::from_ptr(buf.as_ptr()) }
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut buf = ErrorBuffer::new(Duration::from_secs(3600));
+        assert!(buf.set("oops"));
+        assert!(!buf.set("oops"));
+        // compared by the formatted output, even if it's written in pieces
+        assert!(!buf.set(format_args!("{}{}", "oo", "ps")));
+        assert_eq!(buf.suppressed(), 2);
+        assert_eq!(message(&buf), c"oops");
+
+        assert!(buf.set("something else"));
+        assert_eq!(buf.suppressed(), 0);
+        assert_eq!(message(&buf), c"something else");
+
+        // a prefix of the stored message is not a repeat
+        assert!(buf.set("something"));
+        assert!(buf.set("something else"));
+        assert_eq!(buf.suppressed_total(), 2);
+    }
+
+    #[test]
+    fn test_no_dedup() {
+        let mut buf = ErrorBuffer::default();
+        assert!(buf.set("oops"));
+        assert!(buf.set("oops"));
+        assert_eq!(buf.suppressed(), 0);
+
+        assert!(!buf.set("nul\0byte"));
+        assert_eq!(message(&buf), c"oops");
+    }
+
+    #[test]
+    fn test_status_no_dedup() {
+        let mut buf = ErrorBuffer::new(Duration::from_secs(3600));
+        assert!(buf.set_status("timeout"));
+        assert!(buf.set_status("timeout"));
+        assert_eq!(buf.suppressed(), 0);
+
+        // a failure with the same message is not a repeat of a status message
+        assert!(buf.set("timeout"));
+        assert!(!buf.set("timeout"));
+        assert_eq!(buf.suppressed(), 1);
+
+        // a status message flushes the suppressed failures
+        assert!(buf.set_status("end of data"));
+        assert_eq!(buf.suppressed(), 0);
+        assert_eq!(message(&buf), c"end of data");
+    }
+}
@@ -1,13 +1,13 @@
+use crate::plugin::error::error_buffer::ErrorBuffer;
 use crate::FailureReason;
 use falco_plugin_api::ss_plugin_rc;
-use std::ffi::CString;
 
 #[doc(hidden)]
 pub trait FfiResult {
     fn status_code(&self) -> ss_plugin_rc;
-    fn set_last_error(&self, lasterr: &mut CString);
+    fn set_last_error(&self, lasterr: &mut ErrorBuffer);
 
-    fn rc(&self, lasterr: &mut CString) -> ss_plugin_rc {
+    fn rc(&self, lasterr: &mut ErrorBuffer) -> ss_plugin_rc {
         self.set_last_error(lasterr);
         self.status_code()
     }
@@ -21,8 +21,15 @@ impl FfiResult for anyhow::Error {
         }
     }
 
-    fn set_last_error(&self, lasterr: &mut CString) {
-        let msg = self.to_string();
+    fn set_last_error(&self, lasterr: &mut ErrorBuffer) {
+        let stored = match self.downcast_ref::<FailureReason>() {
+            Some(FailureReason::Timeout | FailureReason::Eof) => lasterr.set_status(self),
+            _ => lasterr.set(self),
+        };
+        if !stored {
+            // a repeat of the previous error
+            return;
+        }
 
         #[cfg(debug_assertions)]
         match self.status_code() {
@@ -34,10 +41,6 @@ impl FfiResult for anyhow::Error {
             }
             _ => log::warn!("Plugin error: {:#}", self),
         }
-
-        if let Ok(mut msg) = CString::new(msg.into_bytes()) {
-            std::mem::swap(lasterr, &mut msg);
-        }
     }
 }
 
@@ -49,7 +52,7 @@ impl<T> FfiResult for Result<T, anyhow::Error> {
         }
     }
 
-    fn set_last_error(&self, lasterr: &mut CString) {
+    fn set_last_error(&self, lasterr: &mut ErrorBuffer) {
         if let Err(e) = self {
             e.set_last_error(lasterr)
         }
@@ -1,13 +1,20 @@
 use crate::strings::from_ptr::try_str_from_ptr;
 use falco_plugin_api::{ss_plugin_init_input, ss_plugin_owner_t};
 use std::ffi::c_char;
+use std::fmt::{Debug, Formatter};
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct LastError {
     owner: *mut ss_plugin_owner_t,
     get_owner_last_error: unsafe extern "C-unwind" fn(o: *mut ss_plugin_owner_t) -> *const c_char,
 }
 
+impl Debug for LastError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LastError").finish_non_exhaustive()
+    }
+}
+
 impl LastError {
     pub unsafe fn new(
         owner: *mut ss_plugin_owner_t,
@@ -1,6 +1,8 @@
 pub mod as_result;
+pub mod error_buffer;
 pub mod ffi_result;
 pub mod last_error;
+pub(crate) mod strict;
 
 use thiserror::Error;
 
@@ -0,0 +1,114 @@
+//! Diagnostics for defensive early returns in FFI wrappers
+//!
+//! The wrappers return NULL or a failure code (without setting the last error) when called
+//! with arguments they cannot handle, e.g. NULL pointers. With the `strict` feature enabled,
+//! every such return is logged, so that host incompatibilities show up in the logs instead
+//! of as silently missing functionality.
+
+#[cfg(feature = "strict")]
+use std::collections::BTreeMap;
+#[cfg(feature = "strict")]
+use std::sync::Mutex;
+
+/// How many times each (callback, reason) pair has been reported
+#[cfg(feature = "strict")]
+static HITS: Mutex<BTreeMap<(&str, &str), u64>> = Mutex::new(BTreeMap::new());
+
+/// Report a defensive early return from `callback`
+///
+/// Messages are rate-limited: for every (callback, reason) pair, only the 1st, 2nd, 4th, 8th
+/// etc. occurrence is logged. Without the `strict` feature, this does nothing.
+#[inline(always)]
+pub(crate) fn unexpected_input(callback: &'static str, reason: &'static str) {
+    #[cfg(feature = "strict")]
+    {
+        let hits = {
+            let mut hits = lock_hits();
+            let count = hits.entry((callback, reason)).or_default();
+            *count += 1;
+            *count
+        };
+
+        if hits.is_power_of_two() {
+            log::warn!(
+                "{}: returning early: {} (seen {} time{})",
+                callback,
+                reason,
+                hits,
+                if hits == 1 { "" } else { "s" }
+            );
+        }
+    }
+
+    #[cfg(not(feature = "strict"))]
+    let _ = (callback, reason);
+}
+
+#[cfg(feature = "strict")]
+fn lock_hits() -> std::sync::MutexGuard<'static, BTreeMap<(&'static str, &'static str), u64>> {
+    match HITS.lock() {
+        Ok(hits) => hits,
+        Err(e) => e.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+    use crate::plugin::exported_tables::table::Table;
+    use crate::plugin::exported_tables::wrappers::fields_vtable;
+    use crate::plugin::tables::data::FieldTypeId;
+    use falco_plugin_api::{ss_plugin_state_type, ss_plugin_table_t};
+
+    #[cfg(feature = "strict")]
+    fn hits(callback: &'static str, reason: &'static str) -> u64 {
+        super::lock_hits()
+            .get(&(callback, reason))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    const STRING: ss_plugin_state_type = FieldTypeId::String as ss_plugin_state_type;
+
+    #[test]
+    fn test_null_inputs() {
+        let fields = fields_vtable::<u64, DynamicEntry>();
+        let get_table_field = fields.get_table_field.unwrap();
+        let mut table = Table::<u64, DynamicEntry>::new(c"strict_null").unwrap();
+        let table_ptr = &mut table as *mut _ as *mut ss_plugin_table_t;
+
+        let field = unsafe { get_table_field(std::ptr::null_mut(), c"name".as_ptr(), STRING) };
+        assert!(field.is_null());
+        let field = unsafe { get_table_field(table_ptr, std::ptr::null(), STRING) };
+        assert!(field.is_null());
+
+        #[cfg(feature = "strict")]
+        {
+            assert!(hits("fields_ext.get_table_field", "NULL table") >= 1);
+            assert!(hits("fields_ext.get_table_field", "NULL name") >= 1);
+        }
+    }
+
+    #[test]
+    fn test_invalid_utf8_inputs() {
+        let fields = fields_vtable::<u64, DynamicEntry>();
+        let add_table_field = fields.add_table_field.unwrap();
+        let get_table_field = fields.get_table_field.unwrap();
+        let mut table = Table::<u64, DynamicEntry>::new(c"strict_utf8").unwrap();
+        let table_ptr = &mut table as *mut _ as *mut ss_plugin_table_t;
+
+        // field names are C strings and need not be valid UTF-8,
+        // so this is not an unexpected input
+        let name = c"\xff\xfe".as_ptr();
+        let added = unsafe { add_table_field(table_ptr, name, STRING) };
+        assert!(!added.is_null());
+        let field = unsafe { get_table_field(table_ptr, name, STRING) };
+        assert_eq!(field, added);
+
+        #[cfg(feature = "strict")]
+        {
+            assert_eq!(hits("fields_ext.add_table_field", "NULL name"), 0);
+            assert_eq!(hits("fields_ext.get_table_field", "invalid field type"), 0);
+        }
+    }
+}
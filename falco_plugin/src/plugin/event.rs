@@ -1,10 +1,16 @@
-use falco_event::events::RawEvent;
+use crate::strings::from_ptr::try_cstr_from_ptr;
+use falco_event::events::{EventMetadata, RawEvent};
 use std::ffi::CStr;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Range;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub use falco_plugin_api::ss_plugin_event_input;
 
+/// The size of the event header (timestamp, thread id, length, event type and parameter count)
+const EVENT_HEADER_LEN: usize = 26;
+
 /// # An event from which additional data may be extracted
-#[derive(Debug)]
 pub struct EventInput(pub(crate) ss_plugin_event_input);
 
 impl EventInput {
@@ -13,20 +19,43 @@ impl EventInput {
     /// This method parses the raw event data into a [`RawEvent`] instance,
     /// which can be later converted into a specific event type.
     pub fn event(&self) -> std::io::Result<RawEvent> {
-        unsafe { RawEvent::from_ptr(self.0.evt as *const _) }
+        RawEvent::from(self.as_bytes()?)
     }
 
     /// # Get the event source
     ///
     /// Return the event source (if any)
     pub fn source(&self) -> Option<&CStr> {
-        unsafe {
-            if self.0.evtsrc.is_null() {
-                None
-            } else {
-                Some(CStr::from_ptr(self.0.evtsrc))
-            }
+        unsafe { try_cstr_from_ptr(self.0.evtsrc) }
+    }
+
+    /// # Get the event source name
+    ///
+    /// Return the event source as a string slice, if it's known and valid UTF-8
+    pub fn source_name(&self) -> Option<&str> {
+        self.source()?.to_str().ok()
+    }
+
+    /// # Check if the event comes from one of the sources
+    ///
+    /// The comparison is done by name, mirroring how `EVENT_SOURCES` is interpreted by
+    /// the plugin framework:
+    /// - an empty `sources` list matches every event
+    /// - an event without a source name matches every list (this may happen e.g. when reading
+    ///   capture files, where the source is only known by its index)
+    ///
+    /// This is mostly useful for plugins that want to double-check the source themselves, e.g.
+    /// when they do not declare `EVENT_SOURCES` but only care about some of the events.
+    pub fn source_matches(&self, sources: &[&str]) -> bool {
+        if sources.is_empty() {
+            return true;
         }
+
+        let Some(source) = self.source() else {
+            return true;
+        };
+
+        sources.iter().any(|s| s.as_bytes() == source.to_bytes())
     }
 
     /// # Get the event number
@@ -35,4 +64,256 @@ impl EventInput {
     pub fn event_number(&self) -> usize {
         self.0.evtnum as usize
     }
+
+    /// # Get the event timestamp
+    ///
+    /// Return the event timestamp in nanoseconds since the Unix epoch,
+    /// as found in the [event metadata](`EventInput::metadata`)
+    pub fn timestamp_nanos(&self) -> std::io::Result<u64> {
+        Ok(self.metadata()?.ts)
+    }
+
+    /// # Get the event timestamp
+    ///
+    /// Return the event timestamp as a [`SystemTime`]
+    ///
+    /// To extract it as a field, see [`AbsTime`](`crate::extract::AbsTime`)
+    pub fn timestamp(&self) -> std::io::Result<SystemTime> {
+        Ok(UNIX_EPOCH + Duration::from_nanos(self.timestamp_nanos()?))
+    }
+
+    /// # Get the thread id
+    ///
+    /// Return the id of the thread that generated the event,
+    /// as found in the [event metadata](`EventInput::metadata`)
+    pub fn thread_id(&self) -> std::io::Result<i64> {
+        Ok(self.metadata()?.tid)
+    }
+
+    /// # Get the event metadata
+    ///
+    /// Return the timestamp and thread id of the event, without parsing the rest of it
+    pub fn metadata(&self) -> std::io::Result<EventMetadata> {
+        Ok(self.event()?.metadata)
+    }
+
+    /// # Get the event type
+    ///
+    /// Return the raw event type id, as stored in the event header
+    pub fn event_type(&self) -> std::io::Result<u16> {
+        Ok(self.event()?.event_type)
+    }
+
+    /// # Get the raw event data
+    ///
+    /// Return the whole event (including the header) as a byte slice
+    ///
+    /// Returns an error if there is no event or its length is too short to hold the header.
+    pub fn as_bytes(&self) -> std::io::Result<&[u8]> {
+        let buf = self.0.evt as *const u8;
+        if buf.is_null() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "NULL event pointer",
+            ));
+        }
+
+        let len = unsafe { std::ptr::read_unaligned(buf.offset(16) as *const u32) } as usize;
+        if len < EVENT_HEADER_LEN {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("event length {} is shorter than the event header", len),
+            ));
+        }
+
+        Ok(unsafe { std::slice::from_raw_parts(buf, len) })
+    }
+
+    /// # Get a range of the raw event data
+    ///
+    /// Return `range` of the bytes returned by [`EventInput::as_bytes`]. Offsets are relative
+    /// to the start of the event (including the header), just like the byte offsets reported
+    /// for extracted fields, so this can be used e.g. to look at the data a field was extracted from.
+    ///
+    /// Returns an error if the range does not fit within the event.
+    pub fn payload_slice(&self, range: Range<usize>) -> std::io::Result<&[u8]> {
+        let buf = self.as_bytes()?;
+        buf.get(range.clone()).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "range {:?} out of bounds for event of length {}",
+                    range,
+                    buf.len()
+                ),
+            )
+        })
+    }
+}
+
+// show the decoded header rather than the raw pointers
+impl Debug for EventInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("EventInput");
+        s.field("number", &self.event_number());
+        match self.event() {
+            Ok(event) => {
+                s.field("type", &event.event_type);
+                s.field("ts", &event.metadata.ts);
+                s.field("tid", &event.metadata.tid);
+            }
+            Err(e) => {
+                s.field("error", &e);
+            }
+        }
+        s.field("source", &self.source()).finish()
+    }
+}
+
+impl Display for EventInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "event #{}", self.event_number())?;
+        match self.event() {
+            Ok(event) => write!(
+                f,
+                " type={} ts={} tid={}",
+                event.event_type, event.metadata.ts, event.metadata.tid
+            )?,
+            Err(e) => write!(f, " <invalid: {}>", e)?,
+        }
+        match self.source() {
+            Some(source) => write!(f, " source={}", source.to_string_lossy()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventInput;
+    use falco_plugin_api::ss_plugin_event_input;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_payload_slice() {
+        // event header (26 bytes) followed by a 4-byte payload
+        let mut buf = [0u8; 32];
+        buf[16..20].copy_from_slice(&30u32.to_ne_bytes());
+        buf[26..30].copy_from_slice(b"data");
+
+        let event = EventInput(ss_plugin_event_input {
+            evt: buf.as_ptr() as *const _,
+            evtnum: 1,
+            evtsrc: std::ptr::null(),
+        });
+
+        assert_eq!(event.payload_slice(26..30).unwrap(), b"data");
+        assert_eq!(event.payload_slice(30..30).unwrap(), b"");
+        assert!(event.payload_slice(26..31).is_err());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 28..26;
+        assert!(event.payload_slice(reversed).is_err());
+    }
+
+    #[test]
+    fn test_source_matches() {
+        let buf = [0u8; 26];
+        let event = EventInput(ss_plugin_event_input {
+            evt: buf.as_ptr() as *const _,
+            evtnum: 1,
+            evtsrc: c"dummy".as_ptr(),
+        });
+
+        assert!(event.source_matches(&["dummy"]));
+        assert!(event.source_matches(&["syscall", "dummy"]));
+        assert!(!event.source_matches(&["syscall"]));
+        assert!(!event.source_matches(&["dumm"]));
+
+        // an empty list matches every source
+        assert!(event.source_matches(&[]));
+
+        // an event without a source matches every list
+        let event = EventInput(ss_plugin_event_input {
+            evt: buf.as_ptr() as *const _,
+            evtnum: 1,
+            evtsrc: std::ptr::null(),
+        });
+        assert_eq!(event.source(), None);
+        assert!(event.source_matches(&["syscall"]));
+        assert!(event.source_matches(&[]));
+    }
+
+    #[test]
+    fn test_as_bytes_invalid() {
+        let event = EventInput(ss_plugin_event_input {
+            evt: std::ptr::null(),
+            evtnum: 1,
+            evtsrc: std::ptr::null(),
+        });
+        assert!(event.as_bytes().is_err());
+        assert!(event.payload_slice(0..0).is_err());
+
+        let mut buf = [0u8; 26];
+        buf[16..20].copy_from_slice(&10u32.to_ne_bytes());
+        let event = EventInput(ss_plugin_event_input {
+            evt: buf.as_ptr() as *const _,
+            evtnum: 1,
+            evtsrc: std::ptr::null(),
+        });
+        assert!(event.as_bytes().is_err());
+    }
+
+    #[test]
+    fn test_header_accessors() {
+        let mut buf = [0u8; 26];
+        buf[0..8].copy_from_slice(&1_500_000_000u64.to_ne_bytes());
+        buf[8..16].copy_from_slice(&(-2i64).to_ne_bytes());
+        buf[16..20].copy_from_slice(&26u32.to_ne_bytes());
+
+        let event = EventInput(ss_plugin_event_input {
+            evt: buf.as_ptr() as *const _,
+            evtnum: 5,
+            evtsrc: c"dummy".as_ptr(),
+        });
+
+        assert_eq!(event.event_number(), 5);
+        assert_eq!(event.timestamp_nanos().unwrap(), 1_500_000_000);
+        assert_eq!(
+            event.timestamp().unwrap(),
+            UNIX_EPOCH + Duration::from_millis(1500)
+        );
+        assert_eq!(event.thread_id().unwrap(), -2);
+        assert_eq!(event.source_name(), Some("dummy"));
+    }
+
+    #[test]
+    fn test_debug_display() {
+        let mut buf = [0u8; 26];
+        buf[0..8].copy_from_slice(&1_500_000_000u64.to_ne_bytes());
+        buf[8..16].copy_from_slice(&(-2i64).to_ne_bytes());
+        buf[16..20].copy_from_slice(&26u32.to_ne_bytes());
+        buf[20..22].copy_from_slice(&322u16.to_ne_bytes());
+
+        let event = EventInput(ss_plugin_event_input {
+            evt: buf.as_ptr() as *const _,
+            evtnum: 5,
+            evtsrc: c"dummy".as_ptr(),
+        });
+
+        assert_eq!(
+            event.to_string(),
+            "event #5 type=322 ts=1500000000 tid=-2 source=dummy"
+        );
+        assert_eq!(
+            format!("{:?}", event),
+            r#"EventInput { number: 5, type: 322, ts: 1500000000, tid: -2, source: Some("dummy") }"#
+        );
+
+        let event = EventInput(ss_plugin_event_input {
+            evt: std::ptr::null(),
+            evtnum: 1,
+            evtsrc: std::ptr::null(),
+        });
+        assert_eq!(event.to_string(), "event #1 <invalid: NULL event pointer>");
+    }
 }
@@ -31,6 +31,19 @@ impl<E> DerefMut for ExtensibleEntry<E> {
     }
 }
 
+impl<E> ExtensibleEntry<E> {
+    /// Get mutable access to a dynamic field value, if it's set
+    pub(in crate::plugin::exported_tables) fn dynamic_field_mut(
+        &mut self,
+        key: FieldId,
+    ) -> Option<&mut DynamicFieldValue> {
+        match key {
+            FieldId::Static(_) => None,
+            FieldId::Dynamic(_) => self.custom_fields.get_mut(&key),
+        }
+    }
+}
+
 impl<E> HasMetadata for ExtensibleEntry<E>
 where
     E: HasMetadata,
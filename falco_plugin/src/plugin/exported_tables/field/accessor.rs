@@ -0,0 +1,84 @@
+use std::ffi::CStr;
+use std::fmt::{Debug, Formatter};
+
+/// # A typed reference to a field of an exported table entry
+///
+/// Structs deriving [`Entry`](`crate::tables::export::Entry`) with the `#[accessors]`
+/// attribute get an associated constant of this type for every field that is not `#[skip]`ped
+/// (e.g. `FIELD_PID` for a field called `pid`), along with `get_pid` and `get_pid_mut` methods.
+/// The constant lets you pass a specific field to helper functions, without hardcoding
+/// the field in the helper:
+///
+/// ```
+/// use falco_plugin::tables::export;
+///
+/// #[derive(export::Entry)]
+/// #[accessors]
+/// struct Process {
+///     pid: export::Public<u64>,
+///     #[name(c"proc.ppid")]
+///     ppid: export::Readonly<u64>,
+/// }
+///
+/// fn bump<E>(entry: &mut E, field: &export::EntryField<E, u64>) {
+///     *field.get_mut(entry) += 1;
+/// }
+///
+/// # fn example(entry: &mut Process) {
+/// bump(entry, &Process::FIELD_PID);
+/// bump(entry, &Process::FIELD_PPID);
+/// assert_eq!(Process::FIELD_PPID.name(), c"proc.ppid");
+/// assert_eq!(*entry.get_ppid(), 1);
+/// # }
+/// ```
+///
+/// The value is accessed through the field wrapper, so an `EntryField<E, u64>` refers
+/// to a `Public<u64>`, `Readonly<u64>` or `Private<u64>` field of `E`, and a nested table
+/// field (`Box<Table<K, V>>`) is accessed as a `Table<K, V>`.
+pub struct EntryField<E, T: ?Sized> {
+    name: &'static CStr,
+    get: fn(&E) -> &T,
+    get_mut: fn(&mut E) -> &mut T,
+}
+
+impl<E, T: ?Sized> EntryField<E, T> {
+    /// Create a field reference from its name and accessor functions
+    ///
+    /// This is only expected to be used by the derive macro.
+    pub const fn new(
+        name: &'static CStr,
+        get: fn(&E) -> &T,
+        get_mut: fn(&mut E) -> &mut T,
+    ) -> Self {
+        Self { name, get, get_mut }
+    }
+
+    /// The name of the field, as exported over the plugin API
+    pub fn name(&self) -> &'static CStr {
+        self.name
+    }
+
+    /// Get a reference to the field value in `entry`
+    pub fn get<'a>(&self, entry: &'a E) -> &'a T {
+        (self.get)(entry)
+    }
+
+    /// Get a mutable reference to the field value in `entry`
+    pub fn get_mut<'a>(&self, entry: &'a mut E) -> &'a mut T {
+        (self.get_mut)(entry)
+    }
+}
+
+impl<E, T: ?Sized> Clone for EntryField<E, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E, T: ?Sized> Copy for EntryField<E, T> {}
+
+impl<E, T: ?Sized> Debug for EntryField<E, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("EntryField").field(&self.name).finish()
+    }
+}
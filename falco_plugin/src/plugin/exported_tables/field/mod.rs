@@ -1,3 +1,4 @@
+pub mod accessor;
 pub mod private;
 pub mod public;
 pub mod readonly;
@@ -1,8 +1,89 @@
+use crate::plugin::exported_tables::entry::table_metadata::traits::TableMetadata;
+use crate::plugin::exported_tables::entry::traits::Entry;
 use crate::plugin::exported_tables::field_value::traits::seal;
 use crate::plugin::exported_tables::field_value::traits::FieldValue;
-use crate::plugin::tables::data::FieldTypeId;
-use falco_plugin_api::ss_plugin_state_data;
+use crate::plugin::exported_tables::table::Table;
+use crate::plugin::tables::data::{FieldTypeId, Key};
+use crate::strings::from_ptr::try_cstr_from_ptr;
+use falco_plugin_api::{ss_plugin_state_data, ss_plugin_table_input};
+use std::any::Any;
 use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Formatter};
+
+trait NestedTable: Any {
+    fn name(&self) -> &'static CStr;
+
+    fn vtable(&self) -> *mut ss_plugin_table_input;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<K, E> NestedTable for Box<Table<K, E>>
+where
+    K: Key + Ord + Clone + 'static,
+    E: Entry + 'static,
+    E::Metadata: TableMetadata,
+{
+    fn name(&self) -> &'static CStr {
+        Table::name(self)
+    }
+
+    fn vtable(&self) -> *mut ss_plugin_table_input {
+        self.get_boxed_vtable()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// # A nested table stored in a dynamic field
+///
+/// This lets a plugin attach a table to entries of its exported table at runtime,
+/// without declaring it as a `Box<Table<K, E>>` field of the entry struct. Other plugins
+/// (and Falco core) see the nested table just like a static one: they can look up, iterate,
+/// add and remove its entries, but they cannot replace the table itself.
+///
+/// Any boxed exported table can be stored using [`Table::set_nested_table`]:
+///
+/// ```ignore
+/// let fds = Box::new(export::Table::<u64, Fd>::new(c"file_descriptors")?);
+/// threads.set_nested_table(&mut entry, c"file_descriptors", fds)?;
+///
+/// // later
+/// let fds = threads.nested_table_mut::<u64, Fd>(&mut entry, c"file_descriptors");
+/// ```
+pub struct DynamicTable(Box<dyn NestedTable>);
+
+impl DynamicTable {
+    /// Get the nested table, if it's of the specified type
+    pub fn downcast_mut<K, E>(&mut self) -> Option<&mut Table<K, E>>
+    where
+        K: Key + Ord + Clone + 'static,
+        E: Entry + 'static,
+        E::Metadata: TableMetadata,
+    {
+        let table = self.0.as_any_mut().downcast_mut::<Box<Table<K, E>>>()?;
+        Some(table.as_mut())
+    }
+}
+
+impl<K, E> From<Box<Table<K, E>>> for DynamicTable
+where
+    K: Key + Ord + Clone + 'static,
+    E: Entry + 'static,
+    E::Metadata: TableMetadata,
+{
+    fn from(table: Box<Table<K, E>>) -> Self {
+        Self(Box::new(table))
+    }
+}
+
+impl Debug for DynamicTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("DynamicTable").field(&self.0.name()).finish()
+    }
+}
 
 /// # A value actually stored in a dynamic table
 ///
@@ -20,6 +101,7 @@ pub enum DynamicFieldValue {
     I64(i64),
     Bool(bool),
     String(CString),
+    Table(DynamicTable),
 }
 
 impl DynamicFieldValue {
@@ -36,7 +118,7 @@ impl DynamicFieldValue {
             FieldTypeId::U16 => Some(Self::U16(value.u16_)),
             FieldTypeId::U32 => Some(Self::U32(value.u32_)),
             FieldTypeId::U64 => Some(Self::U64(value.u64_)),
-            FieldTypeId::String => Some(Self::String(CStr::from_ptr(value.str_).to_owned())),
+            FieldTypeId::String => Some(Self::String(try_cstr_from_ptr(value.str_)?.to_owned())),
             FieldTypeId::Bool => Some(Self::Bool(value.b != 0)),
             _ => None,
         }
@@ -66,9 +148,80 @@ impl FieldValue for DynamicFieldValue {
             DynamicFieldValue::String(v) if type_id == FieldTypeId::String => {
                 out.str_ = v.as_c_str().as_ptr()
             }
+            DynamicFieldValue::Table(v) if type_id == FieldTypeId::Table => {
+                out.table = v.0.vtable().cast()
+            }
             _ => anyhow::bail!("Type mismatch, requested {:?}, got {:?}", type_id, self),
         };
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DynamicFieldValue;
+    use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+    use crate::plugin::exported_tables::table::Table;
+    use crate::plugin::tables::data::FieldTypeId;
+    use falco_plugin_api::{ss_plugin_state_data, ss_plugin_table_input};
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_nested_dynamic_table() {
+        let mut parent = Table::<u64, DynamicEntry>::new(c"threads").unwrap();
+        parent
+            .add_field(c"comm", FieldTypeId::String, false)
+            .unwrap();
+
+        let nested = Box::new(Table::<u64, DynamicEntry>::new(c"file_descriptors").unwrap());
+        let other = Box::new(Table::<u64, DynamicEntry>::new(c"other").unwrap());
+        let mut entry = parent.create_entry().unwrap();
+        // an existing field of a different type cannot hold a table
+        assert!(parent.set_nested_table(&mut entry, c"comm", other).is_err());
+        parent
+            .set_nested_table(&mut entry, c"file_descriptors", nested)
+            .unwrap();
+
+        assert!(parent
+            .nested_table_mut::<u32, DynamicEntry>(&mut entry, c"file_descriptors")
+            .is_none());
+        let nested = parent
+            .nested_table_mut::<u64, DynamicEntry>(&mut entry, c"file_descriptors")
+            .unwrap();
+        let fd = nested.create_entry().unwrap();
+        nested.insert(&3, fd);
+
+        let field = parent
+            .get_field(c"file_descriptors", FieldTypeId::Table)
+            .unwrap();
+        let mut out = ss_plugin_state_data { u64_: 0 };
+        parent
+            .get_field_value(&entry, field.as_ref(), &mut out)
+            .unwrap();
+
+        // SAFETY: the vtable points to the nested table owned by `entry`
+        unsafe {
+            let input = &*(out.table as *mut ss_plugin_table_input);
+            let name = input.reader.get_table_name.unwrap()(input.table);
+            assert_eq!(CStr::from_ptr(name), c"file_descriptors");
+            assert_eq!(input.reader.get_table_size.unwrap()(input.table), 1);
+        }
+    }
+
+    #[test]
+    fn test_null_string() {
+        let data = ss_plugin_state_data {
+            str_: std::ptr::null(),
+        };
+        assert!(unsafe { DynamicFieldValue::from_data(&data, FieldTypeId::String) }.is_none());
+
+        let data = ss_plugin_state_data {
+            str_: c"value".as_ptr(),
+        };
+        assert!(matches!(
+            unsafe { DynamicFieldValue::from_data(&data, FieldTypeId::String) },
+            Some(DynamicFieldValue::String(s)) if s.as_c_str() == c"value"
+        ));
+    }
+}
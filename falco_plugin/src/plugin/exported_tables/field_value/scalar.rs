@@ -1,5 +1,6 @@
 use crate::plugin::exported_tables::field_value::dynamic::DynamicFieldValue;
 use crate::plugin::exported_tables::field_value::traits::{seal, FieldValue, StaticField};
+use crate::plugin::tables::byte_buffer::ByteBuffer;
 use crate::plugin::tables::data::FieldTypeId;
 use falco_plugin_api::ss_plugin_state_data;
 use std::ffi::CString;
@@ -76,3 +77,66 @@ impl_scalar_field!(u64 => u64_ => FieldTypeId::U64 => U64);
 impl_scalar_field!(i64 => s64 => FieldTypeId::I64 => I64);
 impl_scalar_field!(bool => b => FieldTypeId::Bool => Bool);
 impl_scalar_field!(CString => str_ => FieldTypeId::String => String);
+
+impl seal::Sealed for ByteBuffer {}
+
+impl FieldValue for ByteBuffer {
+    fn to_data(
+        &self,
+        out: &mut ss_plugin_state_data,
+        type_id: FieldTypeId,
+    ) -> Result<(), anyhow::Error> {
+        if type_id != FieldTypeId::String {
+            anyhow::bail!("Type mismatch, requested {:?}, got ByteBuffer", type_id)
+        }
+
+        out.str_ = self.as_cstr().as_ptr();
+        Ok(())
+    }
+}
+
+impl StaticField for ByteBuffer {
+    const TYPE_ID: FieldTypeId = FieldTypeId::String;
+    const READONLY: bool = false;
+}
+
+/// Values written by other plugins must be valid encoded buffers within the default size limit
+impl TryFrom<DynamicFieldValue> for ByteBuffer {
+    type Error = anyhow::Error;
+
+    fn try_from(value: DynamicFieldValue) -> Result<Self, Self::Error> {
+        if let DynamicFieldValue::String(val) = value {
+            Ok(ByteBuffer::from_encoded(&val)?)
+        } else {
+            Err(anyhow::anyhow!(
+                "Type mismatch, expected ByteBuffer, got {:?}",
+                value
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::exported_tables::field_value::dynamic::DynamicFieldValue;
+    use crate::plugin::exported_tables::field_value::traits::FieldValue;
+    use crate::plugin::tables::byte_buffer::ByteBuffer;
+    use crate::plugin::tables::data::FieldTypeId;
+    use falco_plugin_api::ss_plugin_state_data;
+
+    #[test]
+    fn test_byte_buffer_field() {
+        let buf = ByteBuffer::new(b"\x01\x02").unwrap();
+        let mut out = ss_plugin_state_data { u64_: 0 };
+        buf.to_data(&mut out, FieldTypeId::String).unwrap();
+        assert_eq!(unsafe { std::ffi::CStr::from_ptr(out.str_) }, c"0102");
+        assert!(buf.to_data(&mut out, FieldTypeId::U64).is_err());
+
+        let value = DynamicFieldValue::String(c"0102".to_owned());
+        assert_eq!(ByteBuffer::try_from(value).unwrap(), buf);
+
+        let value = DynamicFieldValue::String(c"nope".to_owned());
+        assert!(ByteBuffer::try_from(value).is_err());
+        assert!(ByteBuffer::try_from(DynamicFieldValue::U64(1)).is_err());
+    }
+}
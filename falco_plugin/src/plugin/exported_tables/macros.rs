@@ -96,7 +96,7 @@ macro_rules! impl_export_table_set {
 macro_rules! impl_export_table {
     (for $name:ident {
         $([$i:literal] $field_tag:literal ($field_name_bstr:literal) as $field_name:ident: $field_type:ty)*
-    }) => {
+    } $(skip { $($skip_name:ident)* })?) => {
         const _: () = {
             $crate::table_export_use_internals!();
 
@@ -108,6 +108,7 @@ macro_rules! impl_export_table {
                 ),)*
             };
 
+            #[allow(missing_debug_implementations)]
             pub struct EntryMetadata {
                 $(pub $field_name: <$field_type as HasMetadata>::Metadata,)*
             }
@@ -130,9 +131,9 @@ macro_rules! impl_export_table {
 
                 fn add_field(
                     &mut self,
-                    name: &std::ffi::CStr,
-                    field_type: FieldTypeId,
-                    read_only: bool,
+                    _name: &std::ffi::CStr,
+                    _field_type: FieldTypeId,
+                    _read_only: bool,
                 ) ->
                     std::option::Option<FieldRef>
                 {
@@ -155,9 +156,10 @@ macro_rules! impl_export_table {
             impl HasMetadata for $name {
                 type Metadata = RefShared<EntryMetadata>;
 
-                fn new_with_metadata(tag: &'static std::ffi::CStr, meta: &Self::Metadata) -> ::std::result::Result<Self, $crate::anyhow::Error> {
+                fn new_with_metadata(_tag: &'static std::ffi::CStr, meta: &Self::Metadata) -> ::std::result::Result<Self, $crate::anyhow::Error> {
                     Ok(Self {
                        $($field_name: HasMetadata::new_with_metadata($field_tag, &meta.read().$field_name)?,)*
+                       $($($skip_name: ::std::default::Default::default(),)*)?
                     })
                 }
             }
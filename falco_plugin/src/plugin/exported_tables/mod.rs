@@ -5,7 +5,12 @@ pub mod field_value;
 pub mod macros;
 pub mod metadata;
 pub(crate) mod ref_shared;
+pub mod replay;
+pub mod snapshot;
 pub mod static_field_specialization;
+pub mod store;
 pub mod table;
 pub(crate) mod vtable;
+#[cfg(any(test, feature = "testing"))]
+pub mod vtable_checks;
 pub(super) mod wrappers;
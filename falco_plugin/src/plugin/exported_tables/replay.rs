@@ -0,0 +1,287 @@
+use crate::plugin::async_event::async_handler::AsyncHandler;
+use crate::plugin::convert;
+use crate::plugin::event::EventInput;
+use crate::plugin::exported_tables::entry::table_metadata::traits::TableMetadata;
+use crate::plugin::exported_tables::entry::traits::Entry;
+use crate::plugin::exported_tables::field_value::traits::FieldValue;
+use crate::plugin::exported_tables::snapshot::{SnapshotTable, TableSnapshot, REDACTED};
+use crate::plugin::exported_tables::store::json_to_field_value;
+use crate::plugin::exported_tables::table::Table;
+use crate::plugin::tables::data::Key;
+use anyhow::Error;
+use falco_event::events::types::PPME_ASYNCEVENT_E as AsyncEvent;
+use falco_event::events::{EventMetadata, EventPayload, RawEvent};
+use falco_plugin_api::ss_plugin_state_data;
+use serde::{Deserialize, Serialize};
+use std::ffi::CStr;
+
+/// # A single record of a table snapshot, carried in an async event
+///
+/// [`TableSnapshot::emit`] turns a snapshot into a series of async events: a
+/// [`TableSnapshotRecord::Begin`] record for every table, followed by a
+/// [`TableSnapshotRecord::Entry`] record for every dumped entry. The records are stored
+/// as JSON in the event data, e.g.
+/// ```json
+/// {"kind": "begin", "table": "users", "size": 2, "truncated": false}
+/// {"kind": "entry", "table": "users", "key": 1, "fields": {"name": "root"}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TableSnapshotRecord {
+    /// The start of a table snapshot
+    Begin {
+        /// The name of the table
+        table: String,
+        /// The number of entries in the table when the snapshot was taken
+        size: usize,
+        /// Whether some entries were left out of the snapshot
+        truncated: bool,
+    },
+    /// A single table entry
+    Entry {
+        /// The name of the table
+        table: String,
+        /// The key of the entry
+        key: serde_json::Value,
+        /// The field values of the entry
+        fields: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl TableSnapshotRecord {
+    /// The name of the table this record belongs to
+    pub fn table(&self) -> &str {
+        match self {
+            TableSnapshotRecord::Begin { table, .. } => table,
+            TableSnapshotRecord::Entry { table, .. } => table,
+        }
+    }
+
+    /// Get the snapshot record from an async event called `name`
+    ///
+    /// Returns `Ok(None)` for all other events.
+    pub fn from_event(event: &EventInput, name: &CStr) -> Result<Option<Self>, Error> {
+        Self::from_raw_event(&event.event()?, name)
+    }
+
+    fn from_raw_event(event: &RawEvent, name: &CStr) -> Result<Option<Self>, Error> {
+        if event.event_type != AsyncEvent::ID as u16 {
+            return Ok(None);
+        }
+
+        let event = event.load::<AsyncEvent>()?;
+        if event.params.name != Some(name) {
+            return Ok(None);
+        }
+
+        let data = event.params.data.unwrap_or_default();
+        Ok(Some(serde_json::from_slice(data)?))
+    }
+}
+
+impl TableSnapshot {
+    /// # Emit the snapshot as a series of async events
+    ///
+    /// Every event is called `name` (which must be listed in
+    /// [`AsyncEventPlugin::ASYNC_EVENTS`](`crate::async_event::AsyncEventPlugin::ASYNC_EVENTS`))
+    /// and carries a single [`TableSnapshotRecord`]. Emitting the snapshot when the
+    /// async event handler is set (i.e. from
+    /// [`AsyncEventPlugin::start_async`](`crate::async_event::AsyncEventPlugin::start_async`))
+    /// puts the plugin state at the start of every capture file, so that a parse plugin
+    /// can rebuild the tables on replay with [`Table::restore`].
+    ///
+    /// The limits and redactions configured for the snapshot apply as usual.
+    /// Returns the number of emitted events.
+    pub fn emit(
+        &self,
+        handler: &AsyncHandler,
+        name: &CStr,
+        tables: &[&dyn SnapshotTable],
+    ) -> Result<usize, Error> {
+        let snapshot = self.to_json(tables)?;
+        let serde_json::Value::Object(snapshot) = snapshot else {
+            return Ok(0);
+        };
+
+        let mut count = 0;
+        let mut emit = |record: TableSnapshotRecord| {
+            let data = serde_json::to_vec(&record)?;
+            handler.emit_data(EventMetadata::default(), name, data)?;
+            count += 1;
+            Ok::<_, Error>(())
+        };
+
+        for (table, dump) in snapshot {
+            emit(TableSnapshotRecord::Begin {
+                table: table.clone(),
+                size: convert::saturating(dump["size"].as_u64().unwrap_or_default()),
+                truncated: dump["truncated"].as_bool().unwrap_or_default(),
+            })?;
+
+            let serde_json::Value::Array(entries) = &dump["entries"] else {
+                continue;
+            };
+            for entry in entries {
+                let serde_json::Value::Object(fields) = &entry["fields"] else {
+                    continue;
+                };
+                emit(TableSnapshotRecord::Entry {
+                    table: table.clone(),
+                    key: entry["key"].clone(),
+                    fields: fields.clone(),
+                })?;
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+impl<K, E> Table<K, E>
+where
+    K: Key + Ord + Clone,
+    E: Entry,
+    E::Metadata: TableMetadata,
+{
+    /// # Apply a snapshot record to the table
+    ///
+    /// A [`TableSnapshotRecord::Begin`] record clears the table, while
+    /// a [`TableSnapshotRecord::Entry`] record inserts (or replaces) an entry.
+    /// Records for other tables are ignored. Returns `true` if the record was applied.
+    ///
+    /// Redacted values, values of fields the table does not have (e.g. dynamic fields
+    /// added by other plugins) and `null` values (dynamic fields never set) are skipped.
+    ///
+    /// ```ignore
+    /// fn parse_event(&mut self, event: &EventInput, _parse_input: &ParseInput) -> Result<(), Error> {
+    ///     if let Some(record) = TableSnapshotRecord::from_event(event, c"my_plugin_state")? {
+    ///         self.users_table.restore(&record)?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn restore(&mut self, record: &TableSnapshotRecord) -> Result<bool, Error> {
+        if record.table().as_bytes() != self.name().to_bytes() {
+            return Ok(false);
+        }
+
+        match record {
+            TableSnapshotRecord::Begin { .. } => self.clear(),
+            TableSnapshotRecord::Entry { key, fields, .. } => {
+                let key = json_to_key::<K>(key)?;
+                let fields = fields
+                    .iter()
+                    .filter(|(_, value)| !value.is_null() && value.as_str() != Some(REDACTED))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                self.insert_from_json(&key, fields)?;
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+fn json_to_key<K: Key + Clone>(key: &serde_json::Value) -> Result<K, Error> {
+    let value = json_to_field_value(key, K::TYPE_ID)
+        .ok_or_else(|| anyhow::anyhow!("Invalid key {} for a {:?} table", key, K::TYPE_ID))?;
+    let mut data = ss_plugin_state_data { u64_: 0 };
+    value.to_data(&mut data, K::TYPE_ID)?;
+
+    // SAFETY: `data` holds a value of the key type, borrowed from `value` (if at all)
+    let key =
+        unsafe { K::from_data(&data) }.ok_or_else(|| anyhow::anyhow!("Invalid key {}", key))?;
+    Ok(key.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TableSnapshotRecord;
+    use crate::plugin::async_event::async_handler::AsyncHandler;
+    use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+    use crate::plugin::exported_tables::snapshot::TableSnapshot;
+    use crate::plugin::exported_tables::table::Table;
+    use crate::plugin::tables::data::FieldTypeId;
+    use falco_event::events::RawEvent;
+    use falco_plugin_api::{
+        ss_plugin_event, ss_plugin_owner_t, ss_plugin_rc, ss_plugin_state_data,
+    };
+    use std::cell::RefCell;
+    use std::ffi::c_char;
+
+    thread_local! {
+        static EVENTS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+    }
+
+    unsafe extern "C-unwind" fn collect_event(
+        _o: *mut ss_plugin_owner_t,
+        evt: *const ss_plugin_event,
+        _err: *mut c_char,
+    ) -> ss_plugin_rc {
+        let event = unsafe { RawEvent::from_ptr(evt as *const _) }.unwrap();
+        let len = event.len as usize;
+        let buf = unsafe { std::slice::from_raw_parts(evt as *const u8, len) };
+        EVENTS.with_borrow_mut(|events| events.push(buf.to_vec()));
+        0
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut table = Table::<u64, DynamicEntry>::new(c"test").unwrap();
+        let field = table.add_field(c"value", FieldTypeId::U64, false).unwrap();
+        let secret = table
+            .add_field(c"secret", FieldTypeId::String, false)
+            .unwrap();
+        for key in 1..=3u64 {
+            let mut entry = table.create_entry().unwrap();
+            let value = ss_plugin_state_data { u64_: key * 10 };
+            table.write(&mut entry, field.as_ref(), &value).unwrap();
+            let value = ss_plugin_state_data {
+                str_: c"hunter2".as_ptr(),
+            };
+            table.write(&mut entry, secret.as_ref(), &value).unwrap();
+            table.insert(&key, entry);
+        }
+
+        let handler = AsyncHandler {
+            owner: std::ptr::null_mut(),
+            raw_handler: collect_event,
+            event_names: &["state"],
+        };
+        let count = TableSnapshot::new("/dev/null")
+            .redact([c"secret"])
+            .emit(&handler, c"state", &[&table])
+            .unwrap();
+        assert_eq!(count, 4);
+
+        let mut restored = Table::<u64, DynamicEntry>::new(c"test").unwrap();
+        let field = restored
+            .add_field(c"value", FieldTypeId::U64, false)
+            .unwrap();
+        restored
+            .add_field(c"secret", FieldTypeId::String, false)
+            .unwrap();
+        let mut other = Table::<u64, DynamicEntry>::new(c"other").unwrap();
+
+        for buf in EVENTS.take() {
+            let event = RawEvent::from(&buf).unwrap();
+            assert!(TableSnapshotRecord::from_raw_event(&event, c"not_state")
+                .unwrap()
+                .is_none());
+
+            let record = TableSnapshotRecord::from_raw_event(&event, c"state")
+                .unwrap()
+                .unwrap();
+            assert!(restored.restore(&record).unwrap());
+            assert!(!other.restore(&record).unwrap());
+        }
+
+        assert_eq!(restored.size(), 3);
+        let entry = restored.lookup(&2).unwrap();
+        let mut out = ss_plugin_state_data { u64_: 0 };
+        restored
+            .get_field_value(&entry, field.as_ref(), &mut out)
+            .unwrap();
+        assert_eq!(unsafe { out.u64_ }, 20);
+    }
+}
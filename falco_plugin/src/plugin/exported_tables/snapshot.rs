@@ -0,0 +1,215 @@
+use crate::plugin::tables::data::FieldTypeId;
+use crate::strings::from_ptr::try_cstr_from_ptr;
+use anyhow::Error;
+use falco_plugin_api::ss_plugin_state_data;
+use std::collections::BTreeSet;
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// The value written instead of redacted fields
+pub(in crate::plugin::exported_tables) const REDACTED: &str = "<redacted>";
+
+/// # Dump exported tables to a JSON file
+///
+/// This is meant for post-mortem analysis of plugin state, typically from
+/// [`CaptureListenPlugin::capture_close`](`crate::listen::CaptureListenPlugin::capture_close`):
+///
+/// ```ignore
+/// fn capture_close(&mut self, _listen_input: &CaptureListenInput) -> Result<(), Error> {
+///     TableSnapshot::new("/var/run/my_plugin/tables.json")
+///         .max_entries(1000)
+///         .redact([c"password"])
+///         .dump(&[&*self.users_table, &*self.sessions_table])
+/// }
+/// ```
+///
+/// The file contains an object with a key for every dumped table, e.g.
+/// ```json
+/// {
+///   "users": {
+///     "size": 2,
+///     "truncated": false,
+///     "entries": [
+///       {"key": 1, "fields": {"name": "root", "password": "<redacted>"}},
+///       {"key": 2, "fields": {"name": "nobody", "password": "<redacted>"}}
+///     ]
+///   }
+/// }
+/// ```
+///
+/// All fields visible over the plugin API are dumped, including dynamic fields added by other
+/// plugins. Fields wrapped in [`Private`](`crate::tables::export::Private`) are not exported,
+/// so they never appear in the snapshot; to hide exported fields, use [`TableSnapshot::redact`].
+/// Table-valued fields are skipped.
+///
+/// To keep the plugin state in capture files instead, see [`TableSnapshot::emit`].
+#[derive(Debug, Clone)]
+pub struct TableSnapshot {
+    path: PathBuf,
+    tables: Option<BTreeSet<CString>>,
+    redact: BTreeSet<CString>,
+    max_entries: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl TableSnapshot {
+    /// Create a snapshot writer, saving the tables to `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            tables: None,
+            redact: BTreeSet::new(),
+            max_entries: None,
+            max_bytes: None,
+        }
+    }
+
+    /// Only dump the tables with the specified names
+    ///
+    /// By default, all tables passed to [`TableSnapshot::dump`] are dumped.
+    pub fn tables<'a>(mut self, names: impl IntoIterator<Item = &'a CStr>) -> Self {
+        self.tables = Some(names.into_iter().map(CStr::to_owned).collect());
+        self
+    }
+
+    /// Replace the values of the specified fields with `"<redacted>"`
+    pub fn redact<'a>(mut self, fields: impl IntoIterator<Item = &'a CStr>) -> Self {
+        self.redact.extend(fields.into_iter().map(CStr::to_owned));
+        self
+    }
+
+    /// Limit the number of entries dumped from each table
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Limit the (approximate) total size of dumped entries, in bytes
+    ///
+    /// Entries that would exceed the limit are skipped, and the table is marked as truncated.
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Build the snapshot without writing it anywhere
+    pub fn to_json(&self, tables: &[&dyn SnapshotTable]) -> Result<serde_json::Value, Error> {
+        let mut budget = self.max_bytes.unwrap_or(usize::MAX);
+        let mut out = serde_json::Map::new();
+        for table in tables {
+            let name = table.name();
+            if let Some(names) = &self.tables {
+                if !names.contains(name) {
+                    continue;
+                }
+            }
+
+            out.insert(
+                name.to_string_lossy().into_owned(),
+                table.snapshot(self, &mut budget)?,
+            );
+        }
+
+        Ok(serde_json::Value::Object(out))
+    }
+
+    /// Build the snapshot and write it to the configured path
+    pub fn dump(&self, tables: &[&dyn SnapshotTable]) -> Result<(), Error> {
+        let snapshot = self.to_json(tables)?;
+        let mut file = BufWriter::new(File::create(&self.path)?);
+        serde_json::to_writer_pretty(&mut file, &snapshot)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    pub(in crate::plugin::exported_tables) fn is_redacted(&self, field: &CStr) -> bool {
+        self.redact.contains(field)
+    }
+
+    pub(in crate::plugin::exported_tables) fn entry_limit(&self) -> usize {
+        self.max_entries.unwrap_or(usize::MAX)
+    }
+}
+
+/// # A table that can be dumped with [`TableSnapshot`]
+///
+/// This is implemented for all [exported tables](`crate::tables::export::Table`)
+/// and exists so that tables with different key and entry types can be dumped together.
+pub trait SnapshotTable {
+    /// The name of the table
+    fn name(&self) -> &'static CStr;
+
+    /// Render the table contents as JSON, consuming the byte budget
+    #[doc(hidden)]
+    fn snapshot(
+        &self,
+        options: &TableSnapshot,
+        budget: &mut usize,
+    ) -> Result<serde_json::Value, Error>;
+}
+
+/// Convert a raw table value to JSON
+///
+/// Values of unsupported types (i.e. tables) are represented as `null`.
+///
+/// # Safety
+/// `data` must hold a valid value of type `type_id`
+pub(crate) unsafe fn state_data_to_json(
+    data: &ss_plugin_state_data,
+    type_id: FieldTypeId,
+) -> serde_json::Value {
+    unsafe {
+        match type_id {
+            FieldTypeId::I8 => data.s8.into(),
+            FieldTypeId::I16 => data.s16.into(),
+            FieldTypeId::I32 => data.s32.into(),
+            FieldTypeId::I64 => data.s64.into(),
+            FieldTypeId::U8 => data.u8_.into(),
+            FieldTypeId::U16 => data.u16_.into(),
+            FieldTypeId::U32 => data.u32_.into(),
+            FieldTypeId::U64 => data.u64_.into(),
+            FieldTypeId::Bool => (data.b != 0).into(),
+            FieldTypeId::String => try_cstr_from_ptr(data.str_)
+                .map(|s| s.to_string_lossy().into_owned())
+                .into(),
+            _ => serde_json::Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::state_data_to_json;
+    use crate::plugin::tables::data::FieldTypeId;
+    use falco_plugin_api::ss_plugin_state_data;
+    use serde_json::json;
+
+    #[test]
+    fn test_state_data_to_json() {
+        unsafe {
+            let data = ss_plugin_state_data { s32: -5 };
+            assert_eq!(state_data_to_json(&data, FieldTypeId::I32), json!(-5));
+
+            let data = ss_plugin_state_data { u64_: u64::MAX };
+            assert_eq!(state_data_to_json(&data, FieldTypeId::U64), json!(u64::MAX));
+
+            let data = ss_plugin_state_data { b: 1 };
+            assert_eq!(state_data_to_json(&data, FieldTypeId::Bool), json!(true));
+
+            let data = ss_plugin_state_data {
+                str_: c"root".as_ptr(),
+            };
+            assert_eq!(
+                state_data_to_json(&data, FieldTypeId::String),
+                json!("root")
+            );
+
+            let data = ss_plugin_state_data {
+                str_: std::ptr::null(),
+            };
+            assert_eq!(state_data_to_json(&data, FieldTypeId::String), json!(null));
+        }
+    }
+}
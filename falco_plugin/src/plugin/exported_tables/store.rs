@@ -0,0 +1,543 @@
+use crate::plugin::exported_tables::field_value::dynamic::DynamicFieldValue;
+use crate::plugin::tables::data::FieldTypeId;
+use anyhow::Error;
+use falco_plugin_api::ss_plugin_state_data;
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::sync::{Mutex, MutexGuard};
+
+/// # A persistent store for exported table entries
+///
+/// Tables with a backing store (see [`Table::with_backing_store`](`crate::tables::export::Table::with_backing_store`))
+/// keep only the most recently used entries in memory and move the rest to the store.
+/// The store is a simple byte-oriented key-value map; the table takes care of serializing
+/// the keys and entries.
+///
+/// The SDK provides implementations for:
+/// - `BTreeMap<Vec<u8>, Vec<u8>>` (in memory, mostly useful for testing)
+/// - `SledStore` (an embedded database), with the `sled-tables` feature
+///
+/// You can implement this trait to use a different store (e.g. SQLite).
+pub trait TableStore {
+    /// Get the value stored under `key`
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Store `value` under `key`, replacing any previous value
+    fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error>;
+
+    /// Remove the value stored under `key`, returning it
+    fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Remove all values
+    fn clear(&mut self) -> Result<(), Error>;
+
+    /// Return the number of stored values
+    fn len(&self) -> usize;
+
+    /// Return `true` if the store is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return all the keys in the store
+    fn keys(&self) -> Result<Vec<Vec<u8>>, Error>;
+
+    /// Make sure all the changes are persisted
+    ///
+    /// The default implementation does nothing, which is fine for stores that write
+    /// the changes out immediately (or never).
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl TableStore for BTreeMap<Vec<u8>, Vec<u8>> {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        Ok(BTreeMap::get(self, key).cloned())
+    }
+
+    fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        BTreeMap::insert(self, key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        Ok(BTreeMap::remove(self, key))
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        BTreeMap::clear(self);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn keys(&self) -> Result<Vec<Vec<u8>>, Error> {
+        Ok(BTreeMap::keys(self).cloned().collect())
+    }
+}
+
+/// # A [`TableStore`] backed by a [sled](https://docs.rs/sled) tree
+///
+/// This type is only available with the `sled-tables` feature.
+#[cfg(feature = "sled-tables")]
+#[derive(Debug, Clone)]
+pub struct SledStore(sled::Tree);
+
+#[cfg(feature = "sled-tables")]
+impl SledStore {
+    /// Open (or create) a database at `path` and use the tree called `name`
+    ///
+    /// Use a different tree name for every table stored in a single database.
+    pub fn open(path: impl AsRef<std::path::Path>, name: &str) -> Result<Self, Error> {
+        Ok(Self(sled::open(path)?.open_tree(name)?))
+    }
+
+    /// Use an already opened tree
+    pub fn new(tree: sled::Tree) -> Self {
+        Self(tree)
+    }
+}
+
+#[cfg(feature = "sled-tables")]
+impl TableStore for SledStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.0.get(key)?.map(|v| v.to_vec()))
+    }
+
+    fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.0.insert(key, value)?;
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.0.remove(key)?.map(|v| v.to_vec()))
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        Ok(self.0.clear()?)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn keys(&self) -> Result<Vec<Vec<u8>>, Error> {
+        self.0.iter().keys().map(|k| Ok(k?.to_vec())).collect()
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+/// A table store along with the entries currently kept in memory
+///
+/// Lookups on a table only need shared access, but with a backing store they may need
+/// to load an entry into memory and evict others, so the in-memory entries of a backed table
+/// live here, behind a lock, instead of in the table itself.
+pub(in crate::plugin::exported_tables) struct BackingStore<K, V> {
+    store: Mutex<Box<dyn TableStore + Send + Sync>>,
+    pub(in crate::plugin::exported_tables) hot_capacity: usize,
+    hot: Mutex<HotEntries<K, V>>,
+}
+
+/// The in-memory entries of a backed table, along with their last access "time" (a counter)
+pub(in crate::plugin::exported_tables) struct HotEntries<K, V> {
+    entries: BTreeMap<K, V>,
+    tick: u64,
+    recency: BTreeMap<K, u64>,
+}
+
+fn lock<'a, T: ?Sized>(mutex: &'a Mutex<T>, what: &str) -> Result<MutexGuard<'a, T>, Error> {
+    mutex
+        .lock()
+        .map_err(|_| anyhow::anyhow!("The {} lock is poisoned", what))
+}
+
+impl<K: Ord + Clone, V> BackingStore<K, V> {
+    pub(in crate::plugin::exported_tables) fn new(
+        store: Box<dyn TableStore + Send + Sync>,
+        hot_capacity: usize,
+    ) -> Self {
+        Self {
+            store: Mutex::new(store),
+            hot_capacity: hot_capacity.max(1),
+            hot: Mutex::new(HotEntries {
+                entries: BTreeMap::new(),
+                tick: 0,
+                recency: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Lock the store for access
+    ///
+    /// When locking both, lock the in-memory entries first.
+    pub(in crate::plugin::exported_tables) fn store(
+        &self,
+    ) -> Result<MutexGuard<'_, Box<dyn TableStore + Send + Sync>>, Error> {
+        lock(&self.store, "table store")
+    }
+
+    /// Lock the in-memory entries for access
+    pub(in crate::plugin::exported_tables) fn hot(
+        &self,
+    ) -> Result<MutexGuard<'_, HotEntries<K, V>>, Error> {
+        lock(&self.hot, "table entries")
+    }
+
+    /// Access the in-memory entries without locking
+    pub(in crate::plugin::exported_tables) fn hot_mut(
+        &mut self,
+    ) -> Result<&mut HotEntries<K, V>, Error> {
+        self.hot
+            .get_mut()
+            .map_err(|_| anyhow::anyhow!("The table entries lock is poisoned"))
+    }
+}
+
+impl<K: Ord + Clone, V> HotEntries<K, V> {
+    pub(in crate::plugin::exported_tables) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(in crate::plugin::exported_tables) fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    pub(in crate::plugin::exported_tables) fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter()
+    }
+
+    /// Add an entry, marking it as just used
+    pub(in crate::plugin::exported_tables) fn insert(&mut self, key: &K, value: V) {
+        self.entries.insert(key.clone(), value);
+        self.touch(key);
+    }
+
+    /// Remove an entry from memory
+    pub(in crate::plugin::exported_tables) fn remove(&mut self, key: &K) -> Option<V> {
+        self.recency.remove(key);
+        self.entries.remove(key)
+    }
+
+    pub(in crate::plugin::exported_tables) fn clear(&mut self) {
+        self.recency.clear();
+        self.entries.clear();
+    }
+
+    /// Mark an in-memory entry as just used
+    pub(in crate::plugin::exported_tables) fn touch(&mut self, key: &K) {
+        self.tick += 1;
+        self.recency.insert(key.clone(), self.tick);
+    }
+
+    /// Return up to `count` keys, least recently used first, for which `evictable` returns true
+    pub(in crate::plugin::exported_tables) fn eviction_candidates(
+        &self,
+        count: usize,
+        evictable: impl Fn(&V) -> bool,
+    ) -> Vec<K> {
+        let mut candidates: Vec<_> = self
+            .recency
+            .iter()
+            .filter(|(k, _)| self.entries.get(*k).is_some_and(&evictable))
+            .collect();
+        candidates.sort_by_key(|(_, tick)| **tick);
+        candidates
+            .into_iter()
+            .take(count)
+            .map(|(k, _)| k.clone())
+            .collect()
+    }
+}
+
+/// Encode a table key as bytes
+///
+/// Integers are stored in big-endian order, so that the byte order matches the numeric
+/// order for unsigned types.
+///
+/// # Safety
+/// `data` must hold a valid value of type `type_id`
+pub(in crate::plugin::exported_tables) unsafe fn encode_key(
+    data: &ss_plugin_state_data,
+    type_id: FieldTypeId,
+) -> Result<Vec<u8>, Error> {
+    unsafe {
+        Ok(match type_id {
+            FieldTypeId::I8 => data.s8.to_be_bytes().to_vec(),
+            FieldTypeId::I16 => data.s16.to_be_bytes().to_vec(),
+            FieldTypeId::I32 => data.s32.to_be_bytes().to_vec(),
+            FieldTypeId::I64 => data.s64.to_be_bytes().to_vec(),
+            FieldTypeId::U8 => data.u8_.to_be_bytes().to_vec(),
+            FieldTypeId::U16 => data.u16_.to_be_bytes().to_vec(),
+            FieldTypeId::U32 => data.u32_.to_be_bytes().to_vec(),
+            FieldTypeId::U64 => data.u64_.to_be_bytes().to_vec(),
+            FieldTypeId::Bool => data.b.to_be_bytes().to_vec(),
+            _ => anyhow::bail!("Cannot store keys of type {:?}", type_id),
+        })
+    }
+}
+
+/// Decode a table key encoded with [`encode_key`]
+pub(in crate::plugin::exported_tables) fn decode_key(
+    key: &[u8],
+    type_id: FieldTypeId,
+) -> Result<ss_plugin_state_data, Error> {
+    fn bytes<const N: usize>(key: &[u8]) -> Result<[u8; N], Error> {
+        key.try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid stored key length {}", key.len()))
+    }
+
+    Ok(match type_id {
+        FieldTypeId::I8 => ss_plugin_state_data {
+            s8: i8::from_be_bytes(bytes(key)?),
+        },
+        FieldTypeId::I16 => ss_plugin_state_data {
+            s16: i16::from_be_bytes(bytes(key)?),
+        },
+        FieldTypeId::I32 => ss_plugin_state_data {
+            s32: i32::from_be_bytes(bytes(key)?),
+        },
+        FieldTypeId::I64 => ss_plugin_state_data {
+            s64: i64::from_be_bytes(bytes(key)?),
+        },
+        FieldTypeId::U8 => ss_plugin_state_data {
+            u8_: u8::from_be_bytes(bytes(key)?),
+        },
+        FieldTypeId::U16 => ss_plugin_state_data {
+            u16_: u16::from_be_bytes(bytes(key)?),
+        },
+        FieldTypeId::U32 => ss_plugin_state_data {
+            u32_: u32::from_be_bytes(bytes(key)?),
+        },
+        FieldTypeId::U64 => ss_plugin_state_data {
+            u64_: u64::from_be_bytes(bytes(key)?),
+        },
+        FieldTypeId::Bool => ss_plugin_state_data {
+            b: u32::from_be_bytes(bytes(key)?),
+        },
+        _ => anyhow::bail!("Cannot load keys of type {:?}", type_id),
+    })
+}
+
+/// Convert a stored JSON value back into a field value of type `type_id`
+pub(in crate::plugin::exported_tables) fn json_to_field_value(
+    value: &serde_json::Value,
+    type_id: FieldTypeId,
+) -> Option<DynamicFieldValue> {
+    match type_id {
+        FieldTypeId::I8 => Some(DynamicFieldValue::I8(value.as_i64()?.try_into().ok()?)),
+        FieldTypeId::I16 => Some(DynamicFieldValue::I16(value.as_i64()?.try_into().ok()?)),
+        FieldTypeId::I32 => Some(DynamicFieldValue::I32(value.as_i64()?.try_into().ok()?)),
+        FieldTypeId::I64 => Some(DynamicFieldValue::I64(value.as_i64()?)),
+        FieldTypeId::U8 => Some(DynamicFieldValue::U8(value.as_u64()?.try_into().ok()?)),
+        FieldTypeId::U16 => Some(DynamicFieldValue::U16(value.as_u64()?.try_into().ok()?)),
+        FieldTypeId::U32 => Some(DynamicFieldValue::U32(value.as_u64()?.try_into().ok()?)),
+        FieldTypeId::U64 => Some(DynamicFieldValue::U64(value.as_u64()?)),
+        FieldTypeId::Bool => Some(DynamicFieldValue::Bool(value.as_bool()?)),
+        FieldTypeId::String => Some(DynamicFieldValue::String(
+            CString::new(value.as_str()?).ok()?,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_key, encode_key, json_to_field_value, BackingStore, TableStore};
+    use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+    use crate::plugin::exported_tables::entry::traits::Entry;
+    use crate::plugin::exported_tables::field_value::dynamic::DynamicFieldValue;
+    use crate::plugin::exported_tables::table::Table;
+    use crate::plugin::tables::data::FieldTypeId;
+    use anyhow::Error;
+    use falco_plugin_api::ss_plugin_state_data;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    /// A store that outlives the tables using it, like a database on disk
+    #[derive(Clone, Default)]
+    struct SharedStore {
+        data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<u8>>>>,
+        writes: Arc<Mutex<usize>>,
+    }
+
+    impl TableStore for SharedStore {
+        fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+            TableStore::get(&*self.data.lock().unwrap(), key)
+        }
+
+        fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+            *self.writes.lock().unwrap() += 1;
+            TableStore::insert(&mut *self.data.lock().unwrap(), key, value)
+        }
+
+        fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+            TableStore::remove(&mut *self.data.lock().unwrap(), key)
+        }
+
+        fn clear(&mut self) -> Result<(), Error> {
+            TableStore::clear(&mut *self.data.lock().unwrap())
+        }
+
+        fn len(&self) -> usize {
+            self.data.lock().unwrap().len()
+        }
+
+        fn keys(&self) -> Result<Vec<Vec<u8>>, Error> {
+            TableStore::keys(&*self.data.lock().unwrap())
+        }
+    }
+
+    #[test]
+    fn test_key_roundtrip() {
+        unsafe {
+            let key = encode_key(&ss_plugin_state_data { s16: -2 }, FieldTypeId::I16).unwrap();
+            assert_eq!(key, [0xff, 0xfe]);
+            assert_eq!(decode_key(&key, FieldTypeId::I16).unwrap().s16, -2);
+
+            let key =
+                encode_key(&ss_plugin_state_data { u64_: 1 << 40 }, FieldTypeId::U64).unwrap();
+            assert_eq!(decode_key(&key, FieldTypeId::U64).unwrap().u64_, 1 << 40);
+        }
+
+        assert!(decode_key(&[1, 2, 3], FieldTypeId::U32).is_err());
+    }
+
+    #[test]
+    fn test_json_to_field_value() {
+        assert!(matches!(
+            json_to_field_value(&json!(200), FieldTypeId::U8),
+            Some(DynamicFieldValue::U8(200))
+        ));
+        assert!(json_to_field_value(&json!(300), FieldTypeId::U8).is_none());
+        assert!(json_to_field_value(&json!(-1), FieldTypeId::U64).is_none());
+        assert!(matches!(
+            json_to_field_value(&json!("root"), FieldTypeId::String),
+            Some(DynamicFieldValue::String(s)) if s.as_c_str() == c"root"
+        ));
+    }
+
+    #[test]
+    fn test_eviction_candidates() {
+        let backing = BackingStore::<u64, bool>::new(Box::new(BTreeMap::new()), 10);
+        let mut hot = backing.hot().unwrap();
+        for key in [1, 2, 3, 4] {
+            hot.insert(&key, key != 2);
+        }
+        hot.touch(&1);
+        hot.remove(&3);
+
+        assert_eq!(hot.eviction_candidates(10, |_| true), [2, 4, 1]);
+        assert_eq!(hot.eviction_candidates(1, |evictable| *evictable), [4]);
+    }
+
+    #[test]
+    fn test_table_with_backing_store() {
+        let mut table = Table::<u64, DynamicEntry>::new(c"test")
+            .unwrap()
+            .with_backing_store(BTreeMap::new(), 2)
+            .unwrap();
+        let field = table.add_field(c"value", FieldTypeId::U64, false).unwrap();
+
+        for key in 0..5u64 {
+            let mut entry = table.create_entry().unwrap();
+            let value = ss_plugin_state_data { u64_: key * 10 + 1 };
+            table.write(&mut entry, field.as_ref(), &value).unwrap();
+            table.insert(&key, entry);
+        }
+        assert_eq!(table.size(), 5);
+
+        // the oldest entries have been evicted by now
+        let entry = table.lookup(&0).unwrap();
+        let mut out = ss_plugin_state_data { u64_: 0 };
+        table
+            .get_field_value(&entry, field.as_ref(), &mut out)
+            .unwrap();
+        assert_eq!(unsafe { out.u64_ }, 1);
+        drop(entry);
+        assert_eq!(table.size(), 5);
+
+        assert!(table.erase(&1).is_some());
+        assert!(table.erase(&1).is_none());
+        assert_eq!(table.size(), 4);
+
+        let mut sum = 0;
+        table.iterate_entries(|entry| {
+            let mut out = ss_plugin_state_data { u64_: 0 };
+            entry
+                .get(field.as_ref().index, FieldTypeId::U64, &mut out)
+                .unwrap();
+            sum += unsafe { out.u64_ };
+            true
+        });
+        assert_eq!(sum, 1 + 21 + 31 + 41);
+
+        table.clear();
+        assert_eq!(table.size(), 0);
+        assert!(table.lookup(&4).is_none());
+    }
+
+    #[test]
+    fn test_backing_store_survives_drop() {
+        let store = SharedStore::default();
+        let value = |table: &Table<u64, DynamicEntry>, key: u64| {
+            let field = table.get_field(c"value", FieldTypeId::U64).unwrap();
+            let entry = table.lookup(&key)?;
+            let mut out = ss_plugin_state_data { u64_: 0 };
+            table
+                .get_field_value(&entry, field.as_ref(), &mut out)
+                .unwrap();
+            Some(unsafe { out.u64_ })
+        };
+
+        {
+            let mut table = Table::<u64, DynamicEntry>::new(c"test")
+                .unwrap()
+                .with_backing_store(store.clone(), 10)
+                .unwrap();
+            let field = table.add_field(c"value", FieldTypeId::U64, false).unwrap();
+            for key in 0..3u64 {
+                let entry = table.create_entry().unwrap();
+                table.insert(&key, entry);
+            }
+
+            // modify an entry that's only in memory
+            let mut entry = table.lookup(&1).unwrap();
+            let data = ss_plugin_state_data { u64_: 42 };
+            table.write(&mut entry, field.as_ref(), &data).unwrap();
+        }
+
+        let mut table = Table::<u64, DynamicEntry>::new(c"test")
+            .unwrap()
+            .with_backing_store(store.clone(), 10)
+            .unwrap();
+        table.add_field(c"value", FieldTypeId::U64, false).unwrap();
+        assert_eq!(table.size(), 3);
+        assert_eq!(value(&table, 1), Some(42));
+
+        // iterating without changing anything does not write the entries back
+        table.flush().unwrap();
+        let writes = *store.writes.lock().unwrap();
+        table.iterate_entries(|_| true);
+        assert_eq!(*store.writes.lock().unwrap(), writes);
+
+        // entries loaded from the store stay there until they're erased
+        assert!(table.erase(&1).is_some());
+        drop(table);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_backing_store_rejects_readonly_fields() {
+        let mut table = Table::<u64, DynamicEntry>::new(c"test").unwrap();
+        table.add_field(c"value", FieldTypeId::U64, true).unwrap();
+        assert!(table.with_backing_store(BTreeMap::new(), 2).is_err());
+    }
+}
@@ -1,21 +1,73 @@
+use crate::plugin::base::metrics::{Metric, MetricLabel, MetricType, MetricValue};
+use crate::plugin::convert;
 use crate::plugin::exported_tables::entry::extensible::ExtensibleEntry;
 use crate::plugin::exported_tables::entry::table_metadata::extensible::ExtensibleEntryMetadata;
 use crate::plugin::exported_tables::entry::table_metadata::traits::TableMetadata;
 use crate::plugin::exported_tables::entry::traits::Entry;
-use crate::plugin::exported_tables::field_descriptor::{FieldDescriptor, FieldRef};
-use crate::plugin::exported_tables::field_value::dynamic::DynamicFieldValue;
+use crate::plugin::exported_tables::field_descriptor::{FieldDescriptor, FieldId, FieldRef};
+use crate::plugin::exported_tables::field_value::dynamic::{DynamicFieldValue, DynamicTable};
 use crate::plugin::exported_tables::metadata::HasMetadata;
 use crate::plugin::exported_tables::metadata::Metadata;
 use crate::plugin::exported_tables::ref_shared::{
     new_counted_ref, new_shared_ref, RefCounted, RefGuard, RefShared,
 };
+use crate::plugin::exported_tables::snapshot::{
+    state_data_to_json, SnapshotTable, TableSnapshot, REDACTED,
+};
+use crate::plugin::exported_tables::store::{
+    decode_key, encode_key, json_to_field_value, BackingStore, HotEntries, TableStore,
+};
 use crate::plugin::exported_tables::vtable::Vtable;
 use crate::plugin::tables::data::{FieldTypeId, Key};
 use crate::FailureReason;
 use falco_plugin_api::{ss_plugin_state_data, ss_plugin_table_fieldinfo};
-use std::collections::BTreeMap;
-use std::ffi::CStr;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::{CStr, CString};
 use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// # Access statistics for a single table field
+///
+/// See [`Table::enable_field_stats`] for details.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct FieldStats {
+    /// the number of reads of the field (via the plugin API)
+    pub reads: u64,
+    /// the number of writes to the field (via the plugin API)
+    pub writes: u64,
+}
+
+/// # Statistics of a single bulk load
+///
+/// See [`Table::bulk_load`] for details.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct BulkLoadStats {
+    /// the number of entries loaded (including the ones with duplicate keys)
+    pub entries: usize,
+    /// the time it took to build and insert all the entries
+    pub duration: Duration,
+}
+
+impl BulkLoadStats {
+    /// Return the statistics as metrics
+    ///
+    /// Metric names must be `'static`, so they are passed explicitly, e.g. to include
+    /// the table name. The duration is reported in nanoseconds.
+    pub fn metrics(
+        &self,
+        entries_name: &'static CStr,
+        duration_name: &'static CStr,
+    ) -> [Metric; 2] {
+        [
+            MetricLabel::new(entries_name, MetricType::NonMonotonic)
+                .with_value(MetricValue::U64(convert::saturating(self.entries))),
+            MetricLabel::new(duration_name, MetricType::NonMonotonic).with_value(MetricValue::U64(
+                convert::saturating(self.duration.as_nanos()),
+            )),
+        ]
+    }
+}
 
 /// # A table exported to other plugins
 ///
@@ -30,6 +82,7 @@ use std::fmt::{Debug, Formatter};
 /// - integer types (u8/i8, u16/i16, u32/i32, u64/i64)
 /// - [`crate::tables::import::Bool`] (an API equivalent of bool)
 /// - &CStr (spelled as just `CStr` when used as a generic argument)
+/// - [`crate::tables::ByteKey`] (arbitrary bytes, e.g. hashes, exposed as hex strings)
 ///
 /// See [`crate::tables::export`] for details.
 ///
@@ -44,29 +97,54 @@ where
     name: &'static CStr,
     field_descriptors: Vec<ss_plugin_table_fieldinfo>,
     metadata: RefShared<ExtensibleEntryMetadata<E::Metadata>>,
-    data: BTreeMap<K, RefShared<ExtensibleEntry<E>>>,
+    data: TableData<K, E>,
+    field_stats: Option<Mutex<BTreeMap<FieldId, FieldStats>>>,
+    on_clear: Option<Box<ClearHook>>,
+    on_erase: Option<Box<EraseHook<K>>>,
+    /// With a backing store, the in-memory entries live there and `data` stays empty
+    backing: Option<BackingStore<K, RefShared<ExtensibleEntry<E>>>>,
 
     pub(in crate::plugin::exported_tables) vtable: RefCounted<Option<Box<Vtable>>>,
 }
 
+// entries may hold sensitive data, so only the table structure is shown
 impl<K, E> Debug for Table<K, E>
 where
-    K: Key + Ord + Clone + Debug,
-    E: Entry + Debug,
-    E::Metadata: TableMetadata + Debug,
+    K: Key + Ord + Clone,
+    E: Entry,
+    E::Metadata: TableMetadata,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<_> = self
+            .metadata
+            .list_fields()
+            .into_iter()
+            // SAFETY: field names are static strings or owned by the metadata
+            .map(|info| unsafe { CStr::from_ptr(info.name) })
+            .collect();
+
         f.debug_struct("Table")
             .field("name", &self.name)
-            .field("metadata", &self.metadata)
-            .field("data", &self.data)
-            .finish()
+            .field("key_type", &K::TYPE_ID)
+            .field("fields", &fields)
+            .field("size", &self.size())
+            .finish_non_exhaustive()
     }
 }
 
+type ClearHook = dyn FnMut() + Send + Sync;
+type EraseHook<K> = dyn FnMut(&K) + Send + Sync;
+
+type TableData<K, E> = BTreeMap<K, RefShared<ExtensibleEntry<E>>>;
 type TableMetadataType<E> = RefShared<ExtensibleEntryMetadata<<E as HasMetadata>::Metadata>>;
 pub(in crate::plugin::exported_tables) type TableEntryType<E> = RefGuard<ExtensibleEntry<E>>;
 
+/// Encode a table key for the backing store
+fn stored_key<K: Key>(key: &K) -> Result<Vec<u8>, anyhow::Error> {
+    // SAFETY: the key outlives the borrowed data
+    unsafe { encode_key(&key.to_data(), K::TYPE_ID) }
+}
+
 impl<K, E> Table<K, E>
 where
     K: Key + Ord + Clone,
@@ -85,6 +163,10 @@ where
             field_descriptors: vec![],
             metadata: metadata.clone(),
             data: BTreeMap::new(),
+            field_stats: None,
+            on_clear: None,
+            on_erase: None,
+            backing: None,
 
             vtable: new_counted_ref(None),
         };
@@ -99,6 +181,10 @@ where
             field_descriptors: vec![],
             metadata: new_shared_ref(ExtensibleEntryMetadata::new()?),
             data: BTreeMap::new(),
+            field_stats: None,
+            on_clear: None,
+            on_erase: None,
+            backing: None,
 
             vtable: new_counted_ref(None),
         })
@@ -110,13 +196,36 @@ where
     }
 
     /// Return the number of entries in the table.
+    ///
+    /// With a backing store, this includes the entries that are not currently in memory.
     pub fn size(&self) -> usize {
-        self.data.len()
+        let Some(backing) = &self.backing else {
+            return self.data.len();
+        };
+
+        // every entry is in the store, even if it's also kept in memory
+        let size = backing.store().map(|store| store.len());
+        size.unwrap_or_else(|e| {
+            log::warn!("Failed to get the size of table {:?}: {}", self.name, e);
+            0
+        })
     }
 
     /// Get an entry corresponding to a particular key.
+    ///
+    /// With a backing store, this loads the entry into memory if needed.
     pub fn lookup(&self, key: &K) -> Option<TableEntryType<E>> {
-        Some(self.data.get(key)?.write_arc())
+        let Some(backing) = &self.backing else {
+            return Some(self.data.get(key)?.write_arc());
+        };
+
+        match self.lookup_backed(backing, key) {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("Failed to load entry from table {:?}: {}", self.name, e);
+                None
+            }
+        }
     }
 
     /// Get the value for a field in an entry.
@@ -128,33 +237,78 @@ where
     ) -> Result<(), anyhow::Error> {
         let (type_id, index) = { (field.type_id, field.index) };
 
-        entry.get(index, type_id, out)
+        entry.get(index, type_id, out)?;
+        self.update_field_stats(index, |stats| stats.reads += 1);
+        Ok(())
     }
 
     /// Execute a closure on all entries in the table with read-only access.
     ///
     /// The iteration continues until all entries are visited or the closure returns false.
     // TODO(upstream) the closure cannot store away the entry but we could use explicit docs
+    ///
+    /// With a backing store, entries that are not in memory are loaded one at a time
+    /// and stored back after the closure returns, if it modified them. They do not count
+    /// as recently used.
     pub fn iterate_entries<F>(&mut self, mut func: F) -> bool
     where
         F: FnMut(&mut TableEntryType<E>) -> bool,
     {
-        for value in &mut self.data.values_mut() {
-            if !func(&mut value.write_arc()) {
-                return false;
+        if self.backing.is_none() {
+            for value in self.data.values_mut() {
+                if !func(&mut value.write_arc()) {
+                    return false;
+                }
+            }
+            return true;
+        }
+
+        match self.iterate_backed_entries(&mut func) {
+            Ok(proceed) => proceed,
+            Err(e) => {
+                log::warn!("Failed to iterate over table {:?}: {}", self.name, e);
+                false
             }
         }
-        true
     }
 
     /// Remove all entries from the table.
     pub fn clear(&mut self) {
-        self.data.clear()
+        self.data.clear();
+        if let Some(backing) = &mut self.backing {
+            let cleared = backing
+                .hot_mut()
+                .map(|hot| hot.clear())
+                .and_then(|()| backing.store()?.clear());
+            if let Err(e) = cleared {
+                log::warn!("Failed to clear table {:?}: {}", self.name, e);
+            }
+        }
     }
 
     /// Erase an entry by key.
     pub fn erase(&mut self, key: &K) -> Option<TableEntryType<E>> {
-        Some(self.data.remove(key)?.write_arc())
+        let Some(backing) = &self.backing else {
+            return Some(self.data.remove(key)?.write_arc());
+        };
+
+        let erased = (|| {
+            let hot = backing.hot()?.remove(key);
+            let stored = backing.store()?.remove(&stored_key(key)?)?;
+            Ok::<_, anyhow::Error>(match (hot, stored) {
+                // the in-memory copy is the current one
+                (Some(entry), _) => Some(entry),
+                (None, Some(stored)) => Some(new_shared_ref(self.decode_entry(&stored)?)),
+                (None, None) => None,
+            })
+        })();
+        match erased {
+            Ok(entry) => Some(entry?.write_arc()),
+            Err(e) => {
+                log::warn!("Failed to erase entry from table {:?}: {}", self.name, e);
+                None
+            }
+        }
     }
 
     /// Create a new table entry.
@@ -171,12 +325,83 @@ where
     /// Attach an entry to a table key
     pub fn insert(&mut self, key: &K, entry: TableEntryType<E>) -> Option<TableEntryType<E>> {
         // note: different semantics from data.insert: we return the *new* entry
-        self.data
-            .insert(key.clone(), std::sync::Arc::clone(RefGuard::rwlock(&entry)));
+        let value = Arc::clone(RefGuard::rwlock(&entry));
         drop(entry);
+        let Some(backing) = &self.backing else {
+            self.data.insert(key.clone(), value);
+            return self.lookup(key);
+        };
+
+        // the new entry replaces the stored one right away, so it's not lost
+        // if the table is never dropped (e.g. the process crashes)
+        let inserted = (|| {
+            let stored = Self::encode_entry(&self.exported_fields(), &value.read())?;
+            backing.store()?.insert(&stored_key(key)?, &stored)?;
+            backing.hot()?.insert(key, value);
+            Ok::<_, anyhow::Error>(())
+        })();
+        if let Err(e) = inserted {
+            log::warn!("Failed to store entry in table {:?}: {}", self.name, e);
+        }
         self.lookup(key)
     }
 
+    /// Load many entries at once, e.g. the initial state of the table
+    ///
+    /// For every `(key, item)` pair, a new entry is created and passed to `init` along with
+    /// the item, so that the fields can be filled in. All entries are then added to the table
+    /// in a single step: the map is built directly from the (sorted) list of entries,
+    /// instead of inserting them one by one and looking each of them up again, like
+    /// [`Table::insert`] does. This is much faster for large datasets, especially when
+    /// the table is empty.
+    ///
+    /// Existing entries with the same keys are replaced. If a key appears more than once,
+    /// the last entry wins.
+    ///
+    /// If `init` fails, none of the entries are added and the error is returned.
+    ///
+    /// With a backing store, the entries are inserted one by one (so that the table can evict
+    /// them as it grows), which is no faster than calling [`Table::insert`] directly.
+    ///
+    /// Returns the number of loaded entries and the time it took, which you can report
+    /// from [`Plugin::get_metrics`](`crate::base::Plugin::get_metrics`)
+    /// using [`BulkLoadStats::metrics`].
+    pub fn bulk_load<T>(
+        &mut self,
+        items: impl IntoIterator<Item = (K, T)>,
+        mut init: impl FnMut(&mut TableEntryType<E>, T) -> Result<(), anyhow::Error>,
+    ) -> Result<BulkLoadStats, anyhow::Error> {
+        let start = Instant::now();
+        let items = items.into_iter();
+        let mut entries = Vec::with_capacity(items.size_hint().0);
+        for (key, item) in items {
+            let mut entry = self.create_entry()?;
+            init(&mut entry, item)?;
+            entries.push((key, entry));
+        }
+
+        let count = entries.len();
+        if self.backing.is_some() {
+            for (key, entry) in entries {
+                let _ = self.insert(&key, entry);
+            }
+        } else {
+            let entries = entries
+                .into_iter()
+                .map(|(key, entry)| (key, Arc::clone(RefGuard::rwlock(&entry))));
+            if self.data.is_empty() {
+                self.data = entries.collect();
+            } else {
+                self.data.extend(entries);
+            }
+        }
+
+        Ok(BulkLoadStats {
+            entries: count,
+            duration: start.elapsed(),
+        })
+    }
+
     /// Write a value to a field of an entry
     pub fn write(
         &self,
@@ -196,7 +421,60 @@ where
             })?
         };
 
-        entry.set(index, value)
+        entry.set(index, value)?;
+        // only count the writes that actually happened
+        self.update_field_stats(index, |stats| stats.writes += 1);
+        Ok(())
+    }
+
+    /// Add a table-valued dynamic field to the table
+    ///
+    /// Other plugins can only access fields that exist when they look them up (usually
+    /// during plugin initialization), so add the field early, even if the nested tables
+    /// are only stored in entries later (with [`Table::set_nested_table`]).
+    pub fn add_nested_table_field(&mut self, name: &CStr) -> Result<(), anyhow::Error> {
+        self.add_field(name, FieldTypeId::Table, true)
+            .ok_or_else(|| anyhow::anyhow!("Field {:?} exists and is not a table", name))?;
+        Ok(())
+    }
+
+    /// Store a nested table in a dynamic field of an entry
+    ///
+    /// The field is added to the table (see [`Table::add_nested_table_field`]) if it does
+    /// not exist yet. Other plugins see the nested table just like a static `Box<Table<K, E>>`
+    /// field, see [`DynamicTable`] for details.
+    pub fn set_nested_table(
+        &mut self,
+        entry: &mut TableEntryType<E>,
+        name: &CStr,
+        table: impl Into<DynamicTable>,
+    ) -> Result<(), anyhow::Error> {
+        let field = self
+            .add_field(name, FieldTypeId::Table, true)
+            .ok_or_else(|| anyhow::anyhow!("Field {:?} exists and is not a table", name))?;
+
+        entry.set(field.as_ref().index, DynamicFieldValue::Table(table.into()))
+    }
+
+    /// Get a nested table stored in a dynamic field of an entry
+    ///
+    /// Returns `None` if the field does not exist, is not set in this entry
+    /// or holds a table of a different type.
+    pub fn nested_table_mut<'a, NK, NE>(
+        &self,
+        entry: &'a mut TableEntryType<E>,
+        name: &CStr,
+    ) -> Option<&'a mut Table<NK, NE>>
+    where
+        NK: Key + Ord + Clone + 'static,
+        NE: Entry + 'static,
+        NE::Metadata: TableMetadata,
+    {
+        let field = self.get_field(name, FieldTypeId::Table)?;
+        match entry.dynamic_field_mut(field.as_ref().index)? {
+            DynamicFieldValue::Table(table) => table.downcast_mut(),
+            _ => None,
+        }
     }
 
     /// Return a list of fields as a slice of raw FFI objects
@@ -224,4 +502,557 @@ where
     ) -> Option<FieldRef> {
         self.metadata.add_field(name, field_type, read_only)
     }
+
+    /// Start (or stop) tracking per-field access statistics
+    ///
+    /// When enabled, the table counts reads and writes of each field done through the plugin API
+    /// (i.e. by other plugins or Falco core; accesses from your own plugin, going directly
+    /// through the entry struct, are not counted). This helps to diagnose which fields are used
+    /// most heavily in multi-plugin deployments.
+    ///
+    /// The plugin API does not identify the caller, so there's no way to tell which plugin
+    /// accessed a field.
+    ///
+    /// Tracking is disabled by default, as it adds some overhead to every field access.
+    /// Disabling it discards the collected statistics.
+    pub fn enable_field_stats(&mut self, enabled: bool) {
+        self.field_stats = match enabled {
+            true => Some(Mutex::new(BTreeMap::new())),
+            false => None,
+        };
+    }
+
+    /// Get access statistics for a field
+    ///
+    /// Returns `None` if statistics are not enabled (see [`Table::enable_field_stats`])
+    /// or the field does not exist. Fields that exist but haven't been accessed yet
+    /// return all-zero statistics.
+    pub fn field_stats(&self, name: &CStr) -> Option<FieldStats> {
+        let stats = self.field_stats.as_ref()?;
+        let field = self.metadata.get_field(name)?;
+        let index = field.as_ref().index;
+
+        let stats = match stats.lock() {
+            Ok(stats) => stats,
+            Err(e) => e.into_inner(),
+        };
+        Some(stats.get(&index).copied().unwrap_or_default())
+    }
+
+    /// Set a callback invoked when another plugin (or the host) clears the table
+    ///
+    /// If your plugin maintains data structures derived from the table contents (e.g. secondary
+    /// indexes), they would silently go out of sync when the table gets modified through
+    /// the plugin API. Use this hook (together with [`Table::on_erase`]) to keep them
+    /// up to date. The callback cannot access the table itself, so keep the derived data
+    /// in shared storage (e.g. an `Arc<Mutex<_>>`) owned by both the plugin and the closure.
+    ///
+    /// The callback is only invoked for operations coming through the plugin API,
+    /// not for calls to [`Table::clear`] made by the owning plugin.
+    pub fn on_clear(&mut self, hook: impl FnMut() + Send + Sync + 'static) {
+        self.on_clear = Some(Box::new(hook));
+    }
+
+    /// Set a callback invoked when another plugin (or the host) erases an entry
+    ///
+    /// The callback receives the key of the erased entry. It's invoked after the entry has been
+    /// removed and only if the entry existed. See [`Table::on_clear`] for details.
+    pub fn on_erase(&mut self, hook: impl FnMut(&K) + Send + Sync + 'static) {
+        self.on_erase = Some(Box::new(hook));
+    }
+
+    /// Keep entries in a persistent store, with only some of them in memory
+    ///
+    /// At most `hot_capacity` entries are kept in memory (unless more of them are in use
+    /// at the same time). When the table grows beyond that, the least recently used entries
+    /// are serialized and moved to `store`. They are transparently loaded back when looked up,
+    /// so other plugins see no difference (apart from latency) when accessing the table.
+    ///
+    /// Every entry is written to the store when it's inserted and stays there while it's
+    /// in memory. Changes to in-memory entries are written back when the entries are evicted,
+    /// when [`Table::flush`] is called and when the table is dropped.
+    ///
+    /// If the store already contains entries (e.g. from a previous run), they become part
+    /// of the table.
+    ///
+    /// Only the fields visible through the plugin API are stored. This means that:
+    /// - [`Private`](`crate::tables::export::Private`) fields are reset to their default values
+    ///   when an entry is loaded back from the store
+    /// - tables with [`Readonly`](`crate::tables::export::Readonly`) fields cannot use a backing
+    ///   store (the values could not be restored)
+    /// - nested table fields are not supported and are not stored either
+    ///
+    /// Keys are limited to integers and [`Bool`](`crate::tables::import::Bool`).
+    pub fn with_backing_store(
+        mut self,
+        store: impl TableStore + Send + Sync + 'static,
+        hot_capacity: usize,
+    ) -> Result<Self, anyhow::Error> {
+        if let Some(field) = self.list_fields().iter().find(|f| f.read_only != 0) {
+            // SAFETY: field names are static strings or owned by the metadata
+            let name = unsafe { CStr::from_ptr(field.name) };
+            anyhow::bail!(
+                "Field {:?} is read-only, so the table cannot use a backing store",
+                name
+            );
+        }
+
+        self.backing = Some(BackingStore::new(Box::new(store), hot_capacity));
+        Ok(self)
+    }
+
+    /// Write the in-memory entries back to the backing store
+    ///
+    /// This is done automatically when the table is dropped, but you can call it
+    /// periodically to limit the changes lost if the process does not shut down cleanly.
+    /// It does nothing for tables without a backing store.
+    pub fn flush(&self) -> Result<(), anyhow::Error> {
+        let Some(backing) = &self.backing else {
+            return Ok(());
+        };
+
+        let fields = self.exported_fields();
+        let hot = backing.hot()?;
+        let mut store = backing.store()?;
+        for (key, entry) in hot.iter() {
+            let stored = Self::encode_entry(&fields, &entry.read())?;
+            store.insert(&stored_key(key)?, &stored)?;
+        }
+        store.flush()
+    }
+
+    /// Return all fields that can be stored (or snapshotted), along with their names
+    fn exported_fields(&self) -> Vec<(CString, FieldRef)> {
+        self.metadata
+            .list_fields()
+            .into_iter()
+            .filter_map(|info| {
+                // SAFETY: field names are static strings or owned by the metadata
+                let name = unsafe { CStr::from_ptr(info.name) };
+                let field = self.metadata.get_field(name)?;
+                Some((name.to_owned(), field))
+            })
+            .filter(|(_, field)| field.as_ref().type_id != FieldTypeId::Table)
+            .collect()
+    }
+
+    fn encode_entry(
+        fields: &[(CString, FieldRef)],
+        entry: &ExtensibleEntry<E>,
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let mut values = serde_json::Map::new();
+        for (name, field) in fields {
+            let field = field.as_ref();
+            let mut out = ss_plugin_state_data { u64_: 0 };
+            // dynamic fields that were never set in this entry are skipped
+            if entry.get(field.index, field.type_id, &mut out).is_ok() {
+                // SAFETY: the entry just stored a value of the requested type
+                let value = unsafe { state_data_to_json(&out, field.type_id) };
+                values.insert(name.to_str()?.to_owned(), value);
+            }
+        }
+
+        Ok(serde_json::to_vec(&values)?)
+    }
+
+    fn decode_entry(&self, stored: &[u8]) -> Result<ExtensibleEntry<E>, anyhow::Error> {
+        self.entry_from_json(serde_json::from_slice(stored)?)
+    }
+
+    fn entry_from_json(
+        &self,
+        values: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<ExtensibleEntry<E>, anyhow::Error> {
+        let mut entry = ExtensibleEntry::<E>::new_with_metadata(self.name, &self.metadata)?;
+        for (name, value) in values {
+            let name = CString::new(name)?;
+            let Some(field) = self.metadata.get_field(&name) else {
+                // the field no longer exists (e.g. it was a dynamic field from a previous run)
+                continue;
+            };
+            let field = field.as_ref();
+            let value = json_to_field_value(&value, field.type_id).ok_or_else(|| {
+                anyhow::anyhow!("Invalid stored value {} for field {:?}", value, name)
+            })?;
+            entry.set(field.index, value)?;
+        }
+
+        Ok(entry)
+    }
+
+    /// Get an entry from a backed table, loading it into memory if needed
+    fn lookup_backed(
+        &self,
+        backing: &BackingStore<K, RefShared<ExtensibleEntry<E>>>,
+        key: &K,
+    ) -> Result<Option<TableEntryType<E>>, anyhow::Error> {
+        let mut hot = backing.hot()?;
+        if hot.get(key).is_none() {
+            let Some(entry) = self.load_cold(key)? else {
+                return Ok(None);
+            };
+            hot.insert(key, new_shared_ref(entry));
+        }
+
+        let Some(entry) = hot.get(key).map(|entry| entry.write_arc()) else {
+            return Ok(None);
+        };
+        hot.touch(key);
+        // the entry we're returning is still referenced, so it won't be evicted
+        self.evict(backing, &mut hot);
+        Ok(Some(entry))
+    }
+
+    /// Load an entry from the backing store, if it's there
+    ///
+    /// The stored copy is kept, so the entry survives even if it's never written back.
+    fn load_cold(&self, key: &K) -> Result<Option<ExtensibleEntry<E>>, anyhow::Error> {
+        let Some(backing) = &self.backing else {
+            return Ok(None);
+        };
+
+        let Some(stored) = backing.store()?.get(&stored_key(key)?)? else {
+            return Ok(None);
+        };
+        Ok(Some(self.decode_entry(&stored)?))
+    }
+
+    fn iterate_backed_entries<F>(&mut self, func: &mut F) -> Result<bool, anyhow::Error>
+    where
+        F: FnMut(&mut TableEntryType<E>) -> bool,
+    {
+        let Some(backing) = &mut self.backing else {
+            return Ok(true);
+        };
+
+        let hot = backing.hot_mut()?;
+        let mut in_memory = BTreeSet::new();
+        for (key, value) in hot.iter() {
+            in_memory.insert(stored_key(key)?);
+            if !func(&mut value.write_arc()) {
+                return Ok(false);
+            }
+        }
+
+        self.iterate_cold_entries(&in_memory, func)
+    }
+
+    /// Iterate over the stored entries, skipping the ones `in_memory`
+    fn iterate_cold_entries<F>(
+        &self,
+        in_memory: &BTreeSet<Vec<u8>>,
+        func: &mut F,
+    ) -> Result<bool, anyhow::Error>
+    where
+        F: FnMut(&mut TableEntryType<E>) -> bool,
+    {
+        let Some(backing) = &self.backing else {
+            return Ok(true);
+        };
+
+        let stored_keys = backing.store()?.keys()?;
+        let fields = self.exported_fields();
+        for stored_key in stored_keys {
+            if in_memory.contains(&stored_key) {
+                continue;
+            }
+            let Some(stored) = backing.store()?.get(&stored_key)? else {
+                continue;
+            };
+            let entry = new_shared_ref(self.decode_entry(&stored)?);
+            let proceed = func(&mut entry.write_arc());
+
+            // only write the entry back if the closure changed it
+            let updated = Self::encode_entry(&fields, &entry.read())?;
+            if updated != stored {
+                backing.store()?.insert(&stored_key, &updated)?;
+            }
+            if !proceed {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Move the least recently used entries to the backing store
+    fn evict(
+        &self,
+        backing: &BackingStore<K, RefShared<ExtensibleEntry<E>>>,
+        hot: &mut HotEntries<K, RefShared<ExtensibleEntry<E>>>,
+    ) {
+        let excess = hot.len().saturating_sub(backing.hot_capacity);
+        if excess == 0 {
+            return;
+        }
+
+        // entries referenced outside the table are in use, so leave them alone
+        let candidates = hot.eviction_candidates(excess, |entry| Arc::strong_count(entry) == 1);
+
+        let fields = self.exported_fields();
+        for key in candidates {
+            if let Err(e) = Self::evict_entry(backing, hot, &fields, &key) {
+                log::warn!("Failed to store entry from table {:?}: {}", self.name, e);
+            }
+        }
+    }
+
+    fn evict_entry(
+        backing: &BackingStore<K, RefShared<ExtensibleEntry<E>>>,
+        hot: &mut HotEntries<K, RefShared<ExtensibleEntry<E>>>,
+        fields: &[(CString, FieldRef)],
+        key: &K,
+    ) -> Result<(), anyhow::Error> {
+        let Some(entry) = hot.get(key) else {
+            return Ok(());
+        };
+        let stored = Self::encode_entry(fields, &entry.read())?;
+        backing.store()?.insert(&stored_key(key)?, &stored)?;
+        hot.remove(key);
+        Ok(())
+    }
+
+    /// Insert an entry with field values encoded as JSON (like in a [`TableSnapshot`])
+    ///
+    /// Values for unknown fields are ignored.
+    pub(in crate::plugin::exported_tables) fn insert_from_json(
+        &mut self,
+        key: &K,
+        values: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), anyhow::Error> {
+        let entry = new_shared_ref(self.entry_from_json(values)?);
+        self.insert(key, entry.write_arc());
+        Ok(())
+    }
+
+    pub(in crate::plugin::exported_tables) fn clear_from_api(&mut self) {
+        self.clear();
+        if let Some(hook) = &mut self.on_clear {
+            hook();
+        }
+    }
+
+    pub(in crate::plugin::exported_tables) fn erase_from_api(&mut self, key: &K) {
+        let erased = self.erase(key).is_some();
+        if erased {
+            if let Some(hook) = &mut self.on_erase {
+                hook(key);
+            }
+        }
+    }
+
+    fn update_field_stats(&self, index: FieldId, func: impl FnOnce(&mut FieldStats)) {
+        let Some(stats) = &self.field_stats else {
+            return;
+        };
+
+        let mut stats = match stats.lock() {
+            Ok(stats) => stats,
+            Err(e) => e.into_inner(),
+        };
+        func(stats.entry(index).or_default())
+    }
+}
+
+// make sure the changes to the in-memory entries of a backed table are not lost
+impl<K, E> Drop for Table<K, E>
+where
+    K: Key + Ord + Clone,
+    E: Entry,
+    E::Metadata: TableMetadata,
+{
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            log::warn!("Failed to flush table {:?}: {}", self.name, e);
+        }
+    }
+}
+
+impl<K, E> SnapshotTable for Table<K, E>
+where
+    K: Key + Ord + Clone,
+    E: Entry,
+    E::Metadata: TableMetadata,
+{
+    fn name(&self) -> &'static CStr {
+        self.name
+    }
+
+    fn snapshot(
+        &self,
+        options: &TableSnapshot,
+        budget: &mut usize,
+    ) -> Result<serde_json::Value, anyhow::Error> {
+        let fields: Vec<_> = self
+            .exported_fields()
+            .into_iter()
+            .map(|(name, field)| {
+                let redacted = options.is_redacted(&name);
+                (redacted, name.to_string_lossy().into_owned(), field)
+            })
+            .collect();
+
+        let mut entries = Vec::new();
+        let mut truncated = false;
+        let mut add_entry = |key: serde_json::Value, entry: &ExtensibleEntry<E>| {
+            if entries.len() >= options.entry_limit() {
+                truncated = true;
+                return Ok::<_, anyhow::Error>(false);
+            }
+
+            let mut values = serde_json::Map::new();
+            for (redacted, name, field) in &fields {
+                let field = field.as_ref();
+                let value = if *redacted {
+                    REDACTED.into()
+                } else {
+                    let mut out = ss_plugin_state_data { u64_: 0 };
+                    match entry.get(field.index, field.type_id, &mut out) {
+                        // SAFETY: the entry just stored a value of the requested type
+                        Ok(()) => unsafe { state_data_to_json(&out, field.type_id) },
+                        // dynamic fields that were never set in this entry
+                        Err(_) => serde_json::Value::Null,
+                    }
+                };
+                values.insert(name.clone(), value);
+            }
+
+            let entry = serde_json::json!({"key": key, "fields": values});
+            let size = serde_json::to_vec(&entry)?.len();
+            if size > *budget {
+                truncated = true;
+                return Ok(false);
+            }
+            *budget -= size;
+            entries.push(entry);
+            Ok(true)
+        };
+
+        // with a backing store, keep the in-memory entries locked while reading the stored ones
+        let hot = self.backing.as_ref().map(|b| b.hot()).transpose()?;
+        let in_memory: Box<dyn Iterator<Item = (&K, &RefShared<ExtensibleEntry<E>>)> + '_> =
+            match &hot {
+                Some(hot) => Box::new(hot.iter()),
+                None => Box::new(self.data.iter()),
+            };
+
+        let mut more = true;
+        let mut seen = BTreeSet::new();
+        for (key, entry) in in_memory {
+            if self.backing.is_some() {
+                seen.insert(stored_key(key)?);
+            }
+            // SAFETY: the key outlives the borrowed data
+            let key = unsafe { state_data_to_json(&key.to_data(), K::TYPE_ID) };
+            more = add_entry(key, &entry.read())?;
+            if !more {
+                break;
+            }
+        }
+
+        if let (true, Some(backing)) = (more, &self.backing) {
+            let stored_keys = backing.store()?.keys()?;
+            for stored_key in stored_keys {
+                // the in-memory copy is the current one
+                if seen.contains(&stored_key) {
+                    continue;
+                }
+                let Some(stored) = backing.store()?.get(&stored_key)? else {
+                    continue;
+                };
+                let key = decode_key(&stored_key, K::TYPE_ID)?;
+                // SAFETY: decode_key returns data of the requested type
+                let key = unsafe { state_data_to_json(&key, K::TYPE_ID) };
+                if !add_entry(key, &self.decode_entry(&stored)?)? {
+                    break;
+                }
+            }
+        }
+
+        drop(hot);
+        Ok(serde_json::json!({
+            "size": self.size(),
+            "truncated": truncated,
+            "entries": entries,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+    use crate::plugin::exported_tables::table::{FieldStats, Table};
+    use crate::plugin::tables::data::FieldTypeId;
+    use falco_plugin_api::ss_plugin_state_data;
+
+    #[test]
+    fn test_debug() {
+        let mut table = Table::<u64, DynamicEntry>::new(c"users").unwrap();
+        table
+            .add_field(c"name", FieldTypeId::String, false)
+            .unwrap();
+        let entry = table.create_entry().unwrap();
+        let _ = table.insert(&1, entry);
+
+        // field names but no field values
+        assert_eq!(
+            format!("{:?}", table),
+            r#"Table { name: "users", key_type: U64, fields: ["name"], size: 1, .. }"#
+        );
+    }
+
+    #[test]
+    fn test_field_stats() {
+        let mut table = Table::<u64, DynamicEntry>::new(c"counters").unwrap();
+        let field = table.add_field(c"count", FieldTypeId::U64, false).unwrap();
+        let name = table
+            .add_field(c"name", FieldTypeId::String, false)
+            .unwrap();
+        table.add_field(c"unused", FieldTypeId::U64, false).unwrap();
+        let mut entry = table.create_entry().unwrap();
+
+        // disabled by default
+        assert_eq!(table.field_stats(c"count"), None);
+
+        table.enable_field_stats(true);
+        assert_eq!(table.field_stats(c"count"), Some(FieldStats::default()));
+        assert_eq!(table.field_stats(c"missing"), None);
+
+        let value = ss_plugin_state_data { u64_: 5 };
+        table.write(&mut entry, field.as_ref(), &value).unwrap();
+
+        let mut out = ss_plugin_state_data { u64_: 0 };
+        table
+            .get_field_value(&entry, field.as_ref(), &mut out)
+            .unwrap();
+        table
+            .get_field_value(&entry, field.as_ref(), &mut out)
+            .unwrap();
+        assert_eq!(unsafe { out.u64_ }, 5);
+
+        assert_eq!(
+            table.field_stats(c"count"),
+            Some(FieldStats {
+                reads: 2,
+                writes: 1
+            })
+        );
+        assert_eq!(table.field_stats(c"unused"), Some(FieldStats::default()));
+
+        // failed accesses are not counted
+        let value = ss_plugin_state_data {
+            str_: std::ptr::null(),
+        };
+        assert!(table.write(&mut entry, name.as_ref(), &value).is_err());
+        assert!(table
+            .get_field_value(&entry, name.as_ref(), &mut out)
+            .is_err());
+        assert_eq!(table.field_stats(c"name"), Some(FieldStats::default()));
+
+        // disabling the stats discards them, so re-enabling starts from scratch
+        table.enable_field_stats(false);
+        assert_eq!(table.field_stats(c"count"), None);
+        table.enable_field_stats(true);
+        assert_eq!(table.field_stats(c"count"), Some(FieldStats::default()));
+    }
 }
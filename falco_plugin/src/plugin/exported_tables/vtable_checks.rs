@@ -0,0 +1,547 @@
+//! Hostile input checks for table vtables
+//!
+//! Other plugins (and the framework) access exported tables only through raw vtables,
+//! so the wrappers have to cope with whatever they get: NULL pointers, invalid type ids,
+//! field handles belonging to another table etc. [`check_table_vtable`] calls every vtable
+//! entry with such inputs and reports any unexpected result, so that both the SDK's own
+//! [`Table`] and other table implementations exposing the same vtables can lock
+//! in their defensive behavior in tests.
+
+use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+use crate::plugin::exported_tables::entry::table_metadata::traits::TableMetadata;
+use crate::plugin::exported_tables::entry::traits::Entry;
+use crate::plugin::exported_tables::table::Table;
+use crate::plugin::tables::data::{FieldTypeId, Key};
+use falco_plugin_api::{
+    ss_plugin_rc_SS_PLUGIN_SUCCESS, ss_plugin_state_data, ss_plugin_state_type,
+    ss_plugin_table_entry_t, ss_plugin_table_input, ss_plugin_table_iterator_state_t,
+};
+use num_traits::FromPrimitive;
+use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
+
+/// The dynamic field added (as `u64`) to the checked table
+const FIELD_NAME: &CStr = c"__vtable_check";
+
+/// The dynamic field added (as a string) to the other table, to get a foreign field handle
+const OTHER_FIELD_NAME: &CStr = c"__vtable_check_other";
+
+/// A type id that does not correspond to any [`FieldTypeId`]
+const INVALID_TYPE: ss_plugin_state_type = 0xffff;
+
+/// The key of the entry added by the checks, for tables with string keys
+///
+/// This is a valid hex string, so that it works for [`ByteKey`](`crate::tables::ByteKey`) too.
+const STRING_KEY: &CStr = c"c0ffee";
+
+/// # An unexpected result of a vtable call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VtableIssue {
+    /// The name of the check, e.g. `read_entry_field.null_entry`
+    pub check: &'static str,
+    /// What went wrong
+    pub message: String,
+}
+
+impl Display for VtableIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.check, self.message)
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    issues: Vec<VtableIssue>,
+}
+
+impl Checker {
+    fn check(&mut self, check: &'static str, ok: bool, message: &str) -> bool {
+        if !ok {
+            self.issues.push(VtableIssue {
+                check,
+                message: message.to_string(),
+            });
+        }
+        ok
+    }
+}
+
+fn key_data(key_type: ss_plugin_state_type) -> Option<ss_plugin_state_data> {
+    let mut data = ss_plugin_state_data { u64_: 0 };
+    match FieldTypeId::from_u32(key_type)? {
+        FieldTypeId::I8 => data.s8 = -7,
+        FieldTypeId::I16 => data.s16 = -7,
+        FieldTypeId::I32 => data.s32 = -7,
+        FieldTypeId::I64 => data.s64 = -7,
+        FieldTypeId::U8 => data.u8_ = 7,
+        FieldTypeId::U16 => data.u16_ = 7,
+        FieldTypeId::U32 => data.u32_ = 7,
+        FieldTypeId::U64 => data.u64_ = 7,
+        FieldTypeId::Bool => data.b = 1,
+        FieldTypeId::String => data.str_ = STRING_KEY.as_ptr(),
+        _ => return None,
+    }
+    Some(data)
+}
+
+/// # Exercise a table vtable with hostile inputs
+///
+/// The checks add a `u64` field called `__vtable_check` to `table` and insert (and then erase)
+/// an entry, so the table must be writable and support dynamic fields. If `other` is passed,
+/// a string field is added to it and used (as a foreign field handle) on `table` entries.
+///
+/// The checks cover:
+/// - NULL table, entry, field, key and output pointers passed to every vtable entry
+/// - invalid type ids and fields requested with a type different from the one they were added with
+/// - failed `add_table_entry` calls not taking ownership of the entry (which the caller
+///   then destroys, so taking ownership would mean a double destroy)
+/// - field handles from another table not breaking the table (their effect on
+///   the entry is not checked, since the vtables cannot detect them in general)
+///
+/// Returns the list of unexpected results (empty if all checks passed).
+///
+/// # Safety
+/// `table` (and `other`, if passed) must be valid table inputs, e.g. from [`check_exported_table`]
+/// or obtained from the framework
+pub unsafe fn check_table_vtable(
+    table: &ss_plugin_table_input,
+    other: Option<&ss_plugin_table_input>,
+) -> Vec<VtableIssue> {
+    let mut c = Checker::default();
+
+    let (Some(reader), Some(writer), Some(fields)) = (unsafe {
+        (
+            table.reader_ext.as_ref(),
+            table.writer_ext.as_ref(),
+            table.fields_ext.as_ref(),
+        )
+    }) else {
+        c.check("vtable", false, "missing extended vtables");
+        return c.issues;
+    };
+
+    let (
+        Some(get_table_name),
+        Some(get_table_size),
+        Some(get_table_entry),
+        Some(read_entry_field),
+        Some(release_table_entry),
+        Some(iterate_entries),
+    ) = (
+        reader.get_table_name,
+        reader.get_table_size,
+        reader.get_table_entry,
+        reader.read_entry_field,
+        reader.release_table_entry,
+        reader.iterate_entries,
+    )
+    else {
+        c.check("vtable.reader_ext", false, "incomplete reader vtable");
+        return c.issues;
+    };
+
+    let (
+        Some(clear_table),
+        Some(erase_table_entry),
+        Some(create_table_entry),
+        Some(destroy_table_entry),
+        Some(add_table_entry),
+        Some(write_entry_field),
+    ) = (
+        writer.clear_table,
+        writer.erase_table_entry,
+        writer.create_table_entry,
+        writer.destroy_table_entry,
+        writer.add_table_entry,
+        writer.write_entry_field,
+    )
+    else {
+        c.check("vtable.writer_ext", false, "incomplete writer vtable");
+        return c.issues;
+    };
+
+    let (Some(list_table_fields), Some(get_table_field), Some(add_table_field)) = (
+        fields.list_table_fields,
+        fields.get_table_field,
+        fields.add_table_field,
+    ) else {
+        c.check("vtable.fields_ext", false, "incomplete fields vtable");
+        return c.issues;
+    };
+
+    let t = table.table;
+    let null_table = std::ptr::null_mut();
+    let success = ss_plugin_rc_SS_PLUGIN_SUCCESS;
+    let u64_type = FieldTypeId::U64 as ss_plugin_state_type;
+    let string_type = FieldTypeId::String as ss_plugin_state_type;
+
+    unsafe {
+        // table-level calls
+        c.check(
+            "get_table_name.null_table",
+            get_table_name(null_table).is_null(),
+            "returned a name for a NULL table",
+        );
+        c.check(
+            "get_table_size.null_table",
+            get_table_size(null_table) == 0,
+            "returned a non-zero size for a NULL table",
+        );
+        c.check(
+            "clear_table.null_table",
+            clear_table(null_table) != success,
+            "succeeded for a NULL table",
+        );
+        c.check(
+            "iterate_entries.null_func",
+            iterate_entries(t, None, std::ptr::null_mut()) == 0,
+            "succeeded without an iterator function",
+        );
+
+        // fields
+        let mut nfields = 0u32;
+        c.check(
+            "list_table_fields.null_table",
+            list_table_fields(null_table, &mut nfields).is_null(),
+            "returned fields for a NULL table",
+        );
+        c.check(
+            "list_table_fields.null_count",
+            list_table_fields(t, std::ptr::null_mut()).is_null(),
+            "returned fields without a place to store their number",
+        );
+        c.check(
+            "get_table_field.null_name",
+            get_table_field(t, std::ptr::null(), u64_type).is_null(),
+            "returned a field for a NULL name",
+        );
+        c.check(
+            "add_table_field.null_name",
+            add_table_field(t, std::ptr::null(), u64_type).is_null(),
+            "added a field with a NULL name",
+        );
+        c.check(
+            "add_table_field.invalid_type",
+            add_table_field(t, FIELD_NAME.as_ptr(), INVALID_TYPE).is_null(),
+            "added a field with an invalid type id",
+        );
+
+        let field = add_table_field(t, FIELD_NAME.as_ptr(), u64_type);
+        if !c.check("add_table_field", !field.is_null(), "failed to add a field") {
+            return c.issues;
+        }
+
+        c.check(
+            "add_table_field.type_mismatch",
+            add_table_field(t, FIELD_NAME.as_ptr(), string_type).is_null(),
+            "re-added an existing field with a different type",
+        );
+        c.check(
+            "get_table_field.invalid_type",
+            get_table_field(t, FIELD_NAME.as_ptr(), INVALID_TYPE).is_null(),
+            "returned a field for an invalid type id",
+        );
+        c.check(
+            "get_table_field.type_mismatch",
+            get_table_field(t, FIELD_NAME.as_ptr(), string_type).is_null(),
+            "returned a field for a different type",
+        );
+        c.check(
+            "get_table_field",
+            get_table_field(t, FIELD_NAME.as_ptr(), u64_type) == field,
+            "returned a different handle for an existing field",
+        );
+
+        // entries
+        let Some(key) = key_data(table.key_type) else {
+            c.check("key_type", false, "unsupported key type");
+            return c.issues;
+        };
+        let size = get_table_size(t);
+
+        c.check(
+            "get_table_entry.null_key",
+            get_table_entry(t, std::ptr::null()).is_null(),
+            "returned an entry for a NULL key",
+        );
+        c.check(
+            "get_table_entry.null_table",
+            get_table_entry(null_table, &key).is_null(),
+            "returned an entry for a NULL table",
+        );
+        c.check(
+            "create_table_entry.null_table",
+            create_table_entry(null_table).is_null(),
+            "created an entry for a NULL table",
+        );
+
+        let entry = create_table_entry(t);
+        if !c.check(
+            "create_table_entry",
+            !entry.is_null(),
+            "failed to create an entry",
+        ) {
+            return c.issues;
+        }
+
+        let value = ss_plugin_state_data { u64_: 42 };
+        let null_entry: *mut ss_plugin_table_entry_t = std::ptr::null_mut();
+        c.check(
+            "write_entry_field.null_table",
+            write_entry_field(null_table, entry, field, &value) != success,
+            "succeeded for a NULL table",
+        );
+        c.check(
+            "write_entry_field.null_entry",
+            write_entry_field(t, null_entry, field, &value) != success,
+            "succeeded for a NULL entry",
+        );
+        c.check(
+            "write_entry_field.null_field",
+            write_entry_field(t, entry, std::ptr::null(), &value) != success,
+            "succeeded for a NULL field",
+        );
+        c.check(
+            "write_entry_field.null_value",
+            write_entry_field(t, entry, field, std::ptr::null()) != success,
+            "succeeded for a NULL value",
+        );
+        c.check(
+            "write_entry_field",
+            write_entry_field(t, entry, field, &value) == success,
+            "failed to write a field",
+        );
+
+        let mut out = ss_plugin_state_data { u64_: 0 };
+        c.check(
+            "read_entry_field.null_table",
+            read_entry_field(null_table, entry, field, &mut out) != success,
+            "succeeded for a NULL table",
+        );
+        c.check(
+            "read_entry_field.null_entry",
+            read_entry_field(t, null_entry, field, &mut out) != success,
+            "succeeded for a NULL entry",
+        );
+        c.check(
+            "read_entry_field.null_field",
+            read_entry_field(t, entry, std::ptr::null(), &mut out) != success,
+            "succeeded for a NULL field",
+        );
+        c.check(
+            "read_entry_field.null_out",
+            read_entry_field(t, entry, field, std::ptr::null_mut()) != success,
+            "succeeded without a place to store the value",
+        );
+
+        // failed additions must leave the entry with the caller
+        c.check(
+            "add_table_entry.null_table",
+            add_table_entry(null_table, &key, entry).is_null(),
+            "added an entry to a NULL table",
+        );
+        c.check(
+            "add_table_entry.null_key",
+            add_table_entry(t, std::ptr::null(), entry).is_null(),
+            "added an entry with a NULL key",
+        );
+        c.check(
+            "add_table_entry.null_entry",
+            add_table_entry(t, &key, null_entry).is_null(),
+            "added a NULL entry",
+        );
+        c.check(
+            "add_table_entry.size",
+            get_table_size(t) == size,
+            "failed additions changed the table size",
+        );
+
+        let added = add_table_entry(t, &key, entry);
+        if !c.check(
+            "add_table_entry",
+            !added.is_null(),
+            "failed to add an entry",
+        ) {
+            destroy_table_entry(t, entry);
+            return c.issues;
+        }
+        release_table_entry(t, added);
+        c.check(
+            "add_table_entry.size",
+            get_table_size(t) == size + 1,
+            "adding an entry did not grow the table",
+        );
+
+        // releasing/destroying NULL is a no-op
+        release_table_entry(t, null_entry);
+        destroy_table_entry(t, null_entry);
+
+        let entry = get_table_entry(t, &key);
+        if !c.check(
+            "get_table_entry",
+            !entry.is_null(),
+            "failed to look up an added entry",
+        ) {
+            return c.issues;
+        }
+
+        let read_back = |out: &mut ss_plugin_state_data| {
+            read_entry_field(t, entry, field, out) == success && out.u64_ == 42
+        };
+        c.check(
+            "read_entry_field",
+            read_back(&mut out),
+            "failed to read back a written value",
+        );
+
+        // field handles from another table
+        if let Some(other) = other {
+            let foreign = other
+                .fields_ext
+                .as_ref()
+                .and_then(|fields| fields.add_table_field)
+                .map(|add| add(other.table, OTHER_FIELD_NAME.as_ptr(), string_type))
+                .unwrap_or(std::ptr::null_mut());
+
+            if c.check(
+                "foreign_field",
+                !foreign.is_null(),
+                "failed to add a field to the other table",
+            ) {
+                read_entry_field(t, entry, foreign, &mut out);
+                c.check(
+                    "foreign_field.read",
+                    read_back(&mut out),
+                    "reading a foreign field changed the entry",
+                );
+
+                let value = ss_plugin_state_data {
+                    str_: c"foreign".as_ptr(),
+                };
+                write_entry_field(t, entry, foreign, &value);
+                c.check(
+                    "foreign_field.write",
+                    get_table_size(t) == size + 1,
+                    "writing a foreign field changed the table size",
+                );
+            }
+        }
+        release_table_entry(t, entry);
+
+        c.check(
+            "erase_table_entry.null_key",
+            erase_table_entry(t, std::ptr::null()) != success,
+            "succeeded for a NULL key",
+        );
+        c.check(
+            "erase_table_entry.null_table",
+            erase_table_entry(null_table, &key) != success,
+            "succeeded for a NULL table",
+        );
+        c.check(
+            "erase_table_entry",
+            erase_table_entry(t, &key) == success && get_table_size(t) == size,
+            "failed to erase an entry",
+        );
+        c.check(
+            "erase_table_entry.missing",
+            get_table_entry(t, &key).is_null(),
+            "the erased entry is still there",
+        );
+
+        let mut visited = 0usize;
+        unsafe extern "C-unwind" fn count(
+            state: *mut ss_plugin_table_iterator_state_t,
+            _entry: *mut ss_plugin_table_entry_t,
+        ) -> falco_plugin_api::ss_plugin_bool {
+            unsafe { *(state as *mut usize) += 1 };
+            1
+        }
+        c.check(
+            "iterate_entries",
+            iterate_entries(t, Some(count), &mut visited as *mut usize as *mut _) != 0
+                && visited as u64 == size,
+            "failed to visit all the entries",
+        );
+    }
+
+    c.issues
+}
+
+/// # Exercise the vtable of an exported table with hostile inputs
+///
+/// This builds the vtable the SDK exposes to other plugins for `table` and runs
+/// [`check_table_vtable`] against it, using a second, empty table for the foreign
+/// field handle checks.
+pub fn check_exported_table<K, E>(table: Table<K, E>) -> Vec<VtableIssue>
+where
+    K: Key + Ord + Clone,
+    E: Entry,
+    E::Metadata: TableMetadata,
+{
+    let table = Box::new(table);
+    let other = match Table::<u64, DynamicEntry>::new(c"__vtable_check_other") {
+        Ok(other) => Box::new(other),
+        Err(e) => {
+            return vec![VtableIssue {
+                check: "other_table",
+                message: e.to_string(),
+            }]
+        }
+    };
+
+    // SAFETY: both vtables point to the boxed tables, which outlive the checks
+    unsafe {
+        let input = &*table.get_boxed_vtable();
+        let other_input = &*other.get_boxed_vtable();
+        check_table_vtable(input, Some(other_input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_exported_table;
+    use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+    use crate::plugin::exported_tables::entry::table_metadata::traits::TableMetadata;
+    use crate::plugin::exported_tables::entry::traits::Entry;
+    use crate::plugin::exported_tables::table::Table;
+    use crate::plugin::tables::byte_key::ByteKey;
+    use crate::plugin::tables::data::{FieldTypeId, Key};
+
+    fn assert_no_issues<K, E>(table: Table<K, E>)
+    where
+        K: Key + Ord + Clone,
+        E: Entry,
+        E::Metadata: TableMetadata,
+    {
+        let issues = check_exported_table(table);
+        assert!(
+            issues.is_empty(),
+            "{}",
+            issues
+                .iter()
+                .map(|issue| issue.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_u64_table() {
+        let mut table = Table::<u64, DynamicEntry>::new(c"u64").unwrap();
+        table
+            .add_field(c"existing", FieldTypeId::String, false)
+            .unwrap();
+        assert_no_issues(table);
+    }
+
+    #[test]
+    fn test_i8_table() {
+        assert_no_issues(Table::<i8, DynamicEntry>::new(c"i8").unwrap());
+    }
+
+    #[test]
+    fn test_byte_key_table() {
+        assert_no_issues(Table::<ByteKey<Vec<u8>>, DynamicEntry>::new(c"bytes").unwrap());
+    }
+}
@@ -1,9 +1,12 @@
+use crate::plugin::convert;
 use crate::plugin::error::ffi_result::FfiResult;
+use crate::plugin::error::strict;
 use crate::plugin::exported_tables::entry::table_metadata::traits::TableMetadata;
 use crate::plugin::exported_tables::entry::traits::Entry;
 use crate::plugin::exported_tables::field_descriptor::FieldDescriptor;
 use crate::plugin::exported_tables::table::{Table, TableEntryType};
 use crate::plugin::tables::data::{FieldTypeId, Key};
+use crate::strings::from_ptr::try_cstr_from_ptr;
 use falco_plugin_api::{
     ss_plugin_bool, ss_plugin_rc, ss_plugin_rc_SS_PLUGIN_FAILURE, ss_plugin_rc_SS_PLUGIN_SUCCESS,
     ss_plugin_state_data, ss_plugin_state_type, ss_plugin_table_entry_t, ss_plugin_table_field_t,
@@ -12,7 +15,7 @@ use falco_plugin_api::{
     ss_plugin_table_writer_vtable_ext,
 };
 use num_traits::FromPrimitive;
-use std::ffi::{c_char, CStr};
+use std::ffi::c_char;
 
 // SAFETY: `table` must be a valid pointer to Table<K,E>
 unsafe extern "C-unwind" fn get_table_name<K, E>(table: *mut ss_plugin_table_t) -> *const c_char
@@ -23,6 +26,7 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("reader_ext.get_table_name", "NULL table");
             return std::ptr::null_mut();
         };
         table.name().as_ptr()
@@ -38,9 +42,10 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("reader_ext.get_table_size", "NULL table");
             return 0;
         };
-        table.size() as u64
+        convert::saturating(table.size())
     }
 }
 
@@ -57,14 +62,19 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("reader_ext.get_table_entry", "NULL table");
             return std::ptr::null_mut();
         };
         let Some(key) = key.as_ref() else {
+            strict::unexpected_input("reader_ext.get_table_entry", "NULL key");
             return std::ptr::null_mut();
         };
 
-        let key = K::from_data(key);
-        match table.lookup(key) {
+        let Some(key) = K::from_data(key) else {
+            strict::unexpected_input("reader_ext.get_table_entry", "invalid key");
+            return std::ptr::null_mut();
+        };
+        match table.lookup(&key) {
             Some(entry) => Box::into_raw(Box::new(entry)) as *mut _,
             None => std::ptr::null_mut(),
         }
@@ -85,15 +95,19 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("reader_ext.read_entry_field", "NULL table");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(entry) = (entry as *mut TableEntryType<E>).as_mut() else {
+            strict::unexpected_input("reader_ext.read_entry_field", "NULL entry");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(field) = (field as *const FieldDescriptor).as_ref() else {
+            strict::unexpected_input("reader_ext.read_entry_field", "NULL field");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(out) = out.as_mut() else {
+            strict::unexpected_input("reader_ext.read_entry_field", "NULL out");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
@@ -128,10 +142,12 @@ where
     E::Metadata: TableMetadata,
 {
     let Some(func) = func else {
+        strict::unexpected_input("reader_ext.iterate_entries", "NULL iterator function");
         return 0;
     };
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("reader_ext.iterate_entries", "NULL table");
             return 0;
         };
 
@@ -153,9 +169,10 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("writer_ext.clear_table", "NULL table");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
-        table.clear();
+        table.clear_from_api();
     }
     ss_plugin_rc_SS_PLUGIN_SUCCESS
 }
@@ -173,13 +190,18 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("writer_ext.erase_table_entry", "NULL table");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(key) = key.as_ref() else {
+            strict::unexpected_input("writer_ext.erase_table_entry", "NULL key");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
-        let key = K::from_data(key);
-        table.erase(key);
+        let Some(key) = K::from_data(key) else {
+            strict::unexpected_input("writer_ext.erase_table_entry", "invalid key");
+            return ss_plugin_rc_SS_PLUGIN_FAILURE;
+        };
+        table.erase_from_api(&key);
     }
     ss_plugin_rc_SS_PLUGIN_SUCCESS
 }
@@ -195,6 +217,7 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("writer_ext.create_table_entry", "NULL table");
             return std::ptr::null_mut();
         };
 
@@ -218,20 +241,28 @@ where
     E::Metadata: TableMetadata,
 {
     if entry.is_null() {
+        strict::unexpected_input("writer_ext.add_table_entry", "NULL entry");
         return std::ptr::null_mut();
     }
 
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("writer_ext.add_table_entry", "NULL table");
             return std::ptr::null_mut();
         };
         let Some(key) = key.as_ref() else {
+            strict::unexpected_input("writer_ext.add_table_entry", "NULL key");
+            return std::ptr::null_mut();
+        };
+        // on failure, the entry still belongs to the caller (who will destroy it),
+        // so only take ownership once nothing can go wrong
+        let Some(key) = K::from_data(key) else {
+            strict::unexpected_input("writer_ext.add_table_entry", "invalid key");
             return std::ptr::null_mut();
         };
-        let key = K::from_data(key);
         let entry = Box::from_raw(entry as *mut TableEntryType<E>);
 
-        match table.insert(key, *entry) {
+        match table.insert(&key, *entry) {
             Some(entry) => Box::into_raw(Box::new(entry)) as *mut _,
             None => std::ptr::null_mut(),
         }
@@ -252,15 +283,19 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("writer_ext.write_entry_field", "NULL table");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(entry) = (entry as *mut TableEntryType<E>).as_mut() else {
+            strict::unexpected_input("writer_ext.write_entry_field", "NULL entry");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(field) = (field as *const FieldDescriptor).as_ref() else {
+            strict::unexpected_input("writer_ext.write_entry_field", "NULL field");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(value) = value.as_ref() else {
+            strict::unexpected_input("writer_ext.write_entry_field", "NULL value");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         table.write(entry, field, value).status_code()
@@ -279,10 +314,15 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("fields_ext.list_table_fields", "NULL table");
+            return std::ptr::null_mut();
+        };
+        let Some(nfields) = nfields.as_mut() else {
+            strict::unexpected_input("fields_ext.list_table_fields", "NULL nfields");
             return std::ptr::null_mut();
         };
         let fields = table.list_fields();
-        *nfields = fields.len() as u32;
+        *nfields = convert::saturating(fields.len());
         fields.as_ptr()
     }
 }
@@ -300,15 +340,16 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("fields_ext.get_table_field", "NULL table");
             return std::ptr::null_mut();
         };
         let Some(data_type) = FieldTypeId::from_usize(data_type as usize) else {
+            strict::unexpected_input("fields_ext.get_table_field", "invalid field type");
             return std::ptr::null_mut();
         };
-        let name = if name.is_null() {
+        let Some(name) = try_cstr_from_ptr(name) else {
+            strict::unexpected_input("fields_ext.get_table_field", "NULL name");
             return std::ptr::null_mut();
-        } else {
-            CStr::from_ptr(name)
         };
         match table.get_field(name, data_type) {
             Some(field) => field.as_ref() as *const _ as *mut _,
@@ -330,16 +371,22 @@ where
 {
     unsafe {
         let Some(table) = (table as *mut Table<K, E>).as_mut() else {
+            strict::unexpected_input("fields_ext.add_table_field", "NULL table");
             return std::ptr::null_mut();
         };
         let Some(data_type) = FieldTypeId::from_usize(data_type as usize) else {
+            strict::unexpected_input("fields_ext.add_table_field", "invalid field type");
             return std::ptr::null_mut();
         };
-        let name = if name.is_null() {
+        let Some(name) = try_cstr_from_ptr(name) else {
+            strict::unexpected_input("fields_ext.add_table_field", "NULL name");
             return std::ptr::null_mut();
-        } else {
-            CStr::from_ptr(name)
         };
+        // nested tables can only be created by the plugin owning the table,
+        // so nobody else could ever store a value in the field
+        if data_type == FieldTypeId::Table {
+            return std::ptr::null_mut();
+        }
         match table.add_field(name, data_type, false) {
             Some(field) => field.as_ref() as *const _ as *mut _,
             None => std::ptr::null_mut(),
@@ -391,3 +438,37 @@ where
         add_table_field: Some(add_table_field::<K, E>),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{add_table_field, get_table_entry, get_table_field};
+    use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+    use crate::plugin::exported_tables::table::Table;
+    use crate::plugin::tables::data::{FieldTypeId, TableData};
+    use falco_plugin_api::{ss_plugin_state_type, ss_plugin_table_t};
+
+    #[test]
+    fn test_null_inputs() {
+        let mut table = Table::<u64, DynamicEntry>::new(c"table").unwrap();
+        let entry = table.create_entry().unwrap();
+        table.insert(&1, entry);
+
+        let raw = &mut table as *mut Table<u64, DynamicEntry> as *mut ss_plugin_table_t;
+        let string = FieldTypeId::String as ss_plugin_state_type;
+        unsafe {
+            let name = c"name".as_ptr();
+            assert!(add_table_field::<u64, DynamicEntry>(raw, std::ptr::null(), string).is_null());
+            assert!(get_table_field::<u64, DynamicEntry>(raw, std::ptr::null(), string).is_null());
+            assert!(!add_table_field::<u64, DynamicEntry>(raw, name, string).is_null());
+            assert!(!get_table_field::<u64, DynamicEntry>(raw, name, string).is_null());
+
+            assert!(get_table_entry::<u64, DynamicEntry>(raw, std::ptr::null()).is_null());
+            let key = 1u64.to_data();
+            let entry = get_table_entry::<u64, DynamicEntry>(raw, &key);
+            assert!(!entry.is_null());
+            drop(Box::from_raw(
+                entry as *mut super::TableEntryType<DynamicEntry>,
+            ));
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::extract::ExtractFieldRequestArg;
+use crate::plugin::base::storage_stats::{try_alloc, try_alloc_c_str};
+use crate::plugin::extract::fields::{Extract, ExtractFieldTypeId};
+use crate::plugin::extract::schema::{ExtractArgType, Extractor};
+use crate::plugin::extract::storage::FIELD_STORAGE;
+use crate::plugin::extract::{ExtractField, ExtractPlugin, ExtractRequest};
+use anyhow::Error;
+use falco_plugin_api::ss_plugin_extract_field;
+use std::borrow::Cow;
+use std::ffi::{CStr, CString};
+
+/// # A string field value that may borrow from the event
+///
+/// Returning a [`CString`] from an extractor means copying the value at least once, even
+/// if it's just a part of the event payload. Extractors registered with
+/// [`borrowed_field`](`crate::extract::borrowed_field`) return a `BorrowedStr` instead, which
+/// can point straight into the event (or any other data that outlives the extraction request).
+///
+/// The SDK only copies the value (into the field storage) when it needs to add
+/// a NUL terminator, i.e. for [`BorrowedStr::Bytes`] values that don't already end with one.
+///
+/// Values can be created from `&[u8]`, `&str`, `&CStr`, `Cow<CStr>` and `CString`
+/// with [`From`]/[`Into`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BorrowedStr<'e> {
+    /// Raw bytes, without a NUL terminator (a single trailing NUL is allowed and used in place)
+    ///
+    /// The bytes must not contain any other NULs.
+    Bytes(&'e [u8]),
+    /// A NUL-terminated string, borrowed or owned
+    CStr(Cow<'e, CStr>),
+}
+
+impl<'e> From<&'e [u8]> for BorrowedStr<'e> {
+    fn from(value: &'e [u8]) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl<'e> From<&'e str> for BorrowedStr<'e> {
+    fn from(value: &'e str) -> Self {
+        Self::Bytes(value.as_bytes())
+    }
+}
+
+impl<'e> From<&'e CStr> for BorrowedStr<'e> {
+    fn from(value: &'e CStr) -> Self {
+        Self::CStr(Cow::Borrowed(value))
+    }
+}
+
+impl<'e> From<Cow<'e, CStr>> for BorrowedStr<'e> {
+    fn from(value: Cow<'e, CStr>) -> Self {
+        Self::CStr(value)
+    }
+}
+
+impl From<CString> for BorrowedStr<'_> {
+    fn from(value: CString) -> Self {
+        Self::CStr(Cow::Owned(value))
+    }
+}
+
+impl BorrowedStr<'_> {
+    /// Return a pointer to a NUL-terminated copy of the value, copying only when needed
+    fn as_ptr(&self, storage: &bumpalo::Bump) -> Result<*const u8, std::io::Error> {
+        match self {
+            BorrowedStr::CStr(Cow::Borrowed(s)) => Ok(s.as_ptr().cast()),
+            BorrowedStr::CStr(Cow::Owned(s)) => {
+                // the CString goes away when we return, so it needs to be copied anyway
+                Ok(try_alloc_c_str(storage, FIELD_STORAGE, s.to_bytes())?.as_ptr())
+            }
+            BorrowedStr::Bytes(bytes) => match memchr::memchr(0, bytes) {
+                Some(pos) if pos == bytes.len() - 1 => Ok(bytes.as_ptr()),
+                Some(_) => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "NUL byte inside a string field",
+                )),
+                None => Ok(try_alloc_c_str(storage, FIELD_STORAGE, bytes)?.as_ptr()),
+            },
+        }
+    }
+}
+
+impl Extract for BorrowedStr<'_> {
+    const IS_LIST: bool = false;
+    const TYPE_ID: ExtractFieldTypeId = ExtractFieldTypeId::String;
+
+    fn extract_to(
+        &self,
+        req: &mut ss_plugin_extract_field,
+        storage: &mut bumpalo::Bump,
+    ) -> Result<(), std::io::Error> {
+        let ptr = self.as_ptr(storage)?;
+        let ptr_buf = try_alloc(storage, FIELD_STORAGE, ptr)?;
+        req.res.u64_ = ptr_buf as *mut _ as *mut _;
+        req.res_len = 1;
+        Ok(())
+    }
+}
+
+/// An extractor function returning a [`BorrowedStr`] tied to the lifetime of the event
+///
+/// This is a separate type (and not just another implementation of [`Extractor`] for functions),
+/// since the return type of the function depends on the (higher-ranked) event lifetime.
+#[repr(transparent)]
+pub(crate) struct BorrowingExtractor<F>(F);
+
+impl<F> BorrowingExtractor<F> {
+    pub(crate) const fn wrap(func: &F) -> &Self {
+        // SAFETY: `BorrowingExtractor` is a `repr(transparent)` wrapper around `F`
+        unsafe { &*(func as *const F as *const Self) }
+    }
+}
+
+impl<P, F> Extractor<P> for BorrowingExtractor<F>
+where
+    P: ExtractPlugin,
+    F: for<'e> Fn(
+        &mut P,
+        ExtractRequest<'_, 'e, '_, P>,
+        ExtractFieldRequestArg,
+    ) -> Result<BorrowedStr<'e>, Error>,
+{
+    fn extract<'a>(
+        &self,
+        plugin: &'a mut P,
+        field: &mut ss_plugin_extract_field,
+        request: ExtractRequest<'a, '_, '_, P>,
+        arg_type: ExtractArgType,
+        storage: &'a mut bumpalo::Bump,
+    ) -> Result<(), Error> {
+        // the event (and so the borrowed value) outlives the extraction request,
+        // so the framework can read the value straight from the event
+        let result = (self.0)(plugin, request, unsafe { field.key(arg_type) }?)?;
+        Ok(result.extract_to(field, storage)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BorrowedStr;
+    use std::borrow::Cow;
+    use std::ffi::CStr;
+
+    fn read<'a>(ptr: *const u8) -> &'a CStr {
+        unsafe { CStr::from_ptr(ptr.cast()) }
+    }
+
+    #[test]
+    fn test_borrowed_cstr_is_not_copied() {
+        let storage = bumpalo::Bump::new();
+        let value = c"foo";
+
+        let ptr = BorrowedStr::from(value).as_ptr(&storage).unwrap();
+        assert_eq!(ptr, value.as_ptr().cast());
+        assert_eq!(storage.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn test_nul_terminated_bytes_are_not_copied() {
+        let storage = bumpalo::Bump::new();
+        let value = b"foo\0";
+
+        let ptr = BorrowedStr::from(&value[..]).as_ptr(&storage).unwrap();
+        assert_eq!(ptr, value.as_ptr());
+        assert_eq!(storage.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn test_bytes_get_terminated() {
+        let storage = bumpalo::Bump::new();
+        let value = b"foobar";
+
+        let ptr = BorrowedStr::from(&value[..3]).as_ptr(&storage).unwrap();
+        assert_ne!(ptr, value.as_ptr());
+        assert_eq!(read(ptr), c"foo");
+
+        let ptr = BorrowedStr::from("").as_ptr(&storage).unwrap();
+        assert_eq!(read(ptr), c"");
+    }
+
+    #[test]
+    fn test_owned_cstr() {
+        let storage = bumpalo::Bump::new();
+        let value = BorrowedStr::from(Cow::Owned(c"foo".to_owned()));
+
+        let ptr = value.as_ptr(&storage).unwrap();
+        assert_eq!(read(ptr), c"foo");
+    }
+
+    #[test]
+    fn test_internal_nul() {
+        let storage = bumpalo::Bump::new();
+        assert!(BorrowedStr::from(&b"foo\0bar"[..])
+            .as_ptr(&storage)
+            .is_err());
+    }
+}
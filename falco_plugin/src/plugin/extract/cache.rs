@@ -0,0 +1,164 @@
+use crate::plugin::event::EventInput;
+
+/// # A cache for data derived from a single event
+///
+/// The [`ExtractPlugin::ExtractContext`](`crate::extract::ExtractPlugin::ExtractContext`)
+/// only lives for a single call to `extract_fields`, but the plugin framework may call it
+/// several times for the same event (e.g. when evaluating different rules). If extracting
+/// the fields requires an expensive preprocessing step (like decoding a JSON payload),
+/// keep an `EventCache` in your plugin and the preprocessed data will be reused until
+/// a different event comes in:
+///
+/// ```ignore
+/// struct MyPlugin {
+///     payload: EventCache<serde_json::Value>,
+/// }
+///
+/// impl MyPlugin {
+///     fn extract_field_one(
+///         &mut self,
+///         req: ExtractRequest<Self>,
+///         arg: ExtractFieldRequestArg) -> Result<u64, anyhow::Error> {
+///         let payload = self.payload.get_or_try_insert_with(req.event, || {
+///             let event = req.event.event()?;
+///             let event = event.load::<PPME_PLUGINEVENT_E>()?;
+///             let data = event.params.event_data.unwrap_or_default();
+///             Ok::<_, anyhow::Error>(serde_json::from_slice(data)?)
+///         })?;
+///
+///         // use payload
+///     }
+/// }
+/// ```
+///
+/// Events are identified by the event number and timestamp (the event number alone
+/// starts over e.g. when a new capture is opened). Events without a valid header
+/// are never cached, i.e. the value gets recomputed for every call.
+///
+/// Only a single (the most recent) event is kept in the cache.
+#[derive(Debug)]
+pub struct EventCache<T> {
+    entry: Option<(Option<(usize, u64)>, T)>,
+}
+
+impl<T> Default for EventCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> EventCache<T> {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self { entry: None }
+    }
+
+    fn event_key(event: &EventInput) -> Option<(usize, u64)> {
+        let metadata = event.metadata().ok()?;
+        Some((event.event_number(), metadata.ts))
+    }
+
+    /// Get the value for `event`, computing it with `f` if it's not cached yet
+    pub fn get_or_insert_with(&mut self, event: &EventInput, f: impl FnOnce() -> T) -> &mut T {
+        match self.get_or_try_insert_with(event, || Ok::<_, std::convert::Infallible>(f())) {
+            Ok(value) => value,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Get the value for `event`, computing it with `f` if it's not cached yet
+    ///
+    /// If `f` fails, the error is returned and nothing is cached, so the next call
+    /// for the same event will try again.
+    pub fn get_or_try_insert_with<E>(
+        &mut self,
+        event: &EventInput,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<&mut T, E> {
+        let key = Self::event_key(event);
+        let entry = match self.entry.take() {
+            Some((cached_key, value)) if key.is_some() && cached_key == key => (key, value),
+            _ => (key, f()?),
+        };
+
+        let (_, value) = self.entry.insert(entry);
+        Ok(value)
+    }
+
+    /// Drop the cached value (if any)
+    pub fn clear(&mut self) {
+        self.entry = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventCache;
+    use crate::plugin::event::EventInput;
+    use falco_plugin_api::ss_plugin_event_input;
+
+    fn event_buf(ts: u64) -> [u8; 26] {
+        // event header without any parameters
+        let mut buf = [0u8; 26];
+        buf[0..8].copy_from_slice(&ts.to_ne_bytes());
+        buf[16..20].copy_from_slice(&26u32.to_ne_bytes());
+        buf
+    }
+
+    fn event_input(buf: &[u8], evtnum: u64) -> EventInput {
+        EventInput(ss_plugin_event_input {
+            evt: buf.as_ptr() as *const _,
+            evtnum,
+            evtsrc: std::ptr::null(),
+        })
+    }
+
+    #[test]
+    fn test_event_cache() {
+        let mut cache = EventCache::new();
+        let mut computed = 0;
+        let mut get = |cache: &mut EventCache<u32>, event: &EventInput| {
+            *cache.get_or_insert_with(event, || {
+                computed += 1;
+                computed
+            })
+        };
+
+        let buf = event_buf(1000);
+        let other_buf = event_buf(2000);
+
+        assert_eq!(get(&mut cache, &event_input(&buf, 1)), 1);
+        assert_eq!(get(&mut cache, &event_input(&buf, 1)), 1);
+        // same timestamp, different event number
+        assert_eq!(get(&mut cache, &event_input(&buf, 2)), 2);
+        // same event number, different timestamp
+        assert_eq!(get(&mut cache, &event_input(&other_buf, 2)), 3);
+        assert_eq!(get(&mut cache, &event_input(&other_buf, 2)), 3);
+
+        cache.clear();
+        assert_eq!(get(&mut cache, &event_input(&other_buf, 2)), 4);
+    }
+
+    #[test]
+    fn test_event_cache_error() {
+        let mut cache = EventCache::<u32>::new();
+        let buf = event_buf(1000);
+        let event = event_input(&buf, 1);
+
+        assert!(cache
+            .get_or_try_insert_with(&event, || Err("parse error"))
+            .is_err());
+        assert_eq!(
+            *cache
+                .get_or_try_insert_with(&event, || Ok::<_, &str>(5))
+                .unwrap(),
+            5
+        );
+        assert_eq!(
+            *cache
+                .get_or_try_insert_with(&event, || Err("not called"))
+                .unwrap(),
+            5
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use crate::extract::EventInput;
+use crate::plugin::extract::storage::FieldStorage;
+use crate::plugin::extract::ExtractPlugin;
+use crate::tables::TableReader;
+use falco_event::events::types::EventType;
+use falco_plugin_api::ss_plugin_extract_field;
+use std::ffi::CStr;
+
+/// # An object-safe facade over [`ExtractPlugin`]
+///
+/// [`ExtractPlugin`] describes the plugin with associated constants and types,
+/// so it cannot be used as a trait object. `DynExtract` exposes the same functionality
+/// through methods only and is implemented for every extract plugin, so frameworks composing
+/// several Rust plugins (e.g. routers dispatching events to a set of extractors) can keep
+/// them in a single collection and dispatch dynamically.
+///
+/// ```
+/// use std::ffi::CStr;
+/// use falco_plugin::anyhow::Error;
+/// use falco_plugin::base::Plugin;
+/// use falco_plugin::event::events::types::EventType;
+/// use falco_plugin::extract::{
+///     field, DynExtract, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+/// };
+/// use falco_plugin::tables::TablesInput;
+///
+/// struct Counter(u64);
+///
+/// impl Plugin for Counter {
+///     // ...
+/// #    const NAME: &'static CStr = c"counter";
+/// #    const PLUGIN_VERSION: &'static CStr = c"0.0.1";
+/// #    const DESCRIPTION: &'static CStr = c"counts things";
+/// #    const CONTACT: &'static CStr = c"you@example.com";
+/// #    type ConfigType = ();
+/// #
+/// #    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+/// #        Ok(Self(0))
+/// #    }
+/// }
+///
+/// impl Counter {
+///     fn extract_count(
+///         &mut self,
+///         _req: ExtractRequest<Self>,
+///         _arg: ExtractFieldRequestArg,
+///     ) -> Result<u64, Error> {
+///         self.0 += 1;
+///         Ok(self.0)
+///     }
+/// }
+///
+/// impl ExtractPlugin for Counter {
+///     const EVENT_TYPES: &'static [EventType] = &[];
+///     const EVENT_SOURCES: &'static [&'static str] = &["syscall"];
+///     type ExtractContext = ();
+///     const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+///         &[field("counter.count", &Self::extract_count)];
+/// }
+///
+/// let extractors: Vec<Box<dyn DynExtract>> = vec![Box::new(Counter(0))];
+/// for extractor in &extractors {
+///     assert_eq!(extractor.name(), c"counter");
+///     assert_eq!(extractor.field_index("counter.count"), Some(0));
+/// }
+/// ```
+pub trait DynExtract {
+    /// The plugin name, see [`Plugin::NAME`]
+    fn name(&self) -> &'static CStr;
+
+    /// The supported event types, see [`ExtractPlugin::EVENT_TYPES`]
+    fn event_types(&self) -> &'static [EventType];
+
+    /// The supported event sources, see [`ExtractPlugin::EVENT_SOURCES`]
+    fn event_sources(&self) -> &'static [&'static str];
+
+    /// The JSON description of the extractable fields, see [`ExtractPlugin::get_fields`]
+    fn fields_schema(&self) -> &'static CStr;
+
+    /// Find the index of a field by its name, see [`ExtractPlugin::field_index`]
+    fn field_index(&self, name: &str) -> Option<usize>;
+
+    /// Perform the actual field extraction, see [`ExtractPlugin::extract_fields_with_storage`]
+    fn extract_fields<'a>(
+        &'a mut self,
+        event_input: &EventInput,
+        table_reader: &TableReader,
+        fields: &mut [ss_plugin_extract_field],
+        storage: &'a mut FieldStorage,
+    ) -> Result<(), anyhow::Error>;
+}
+
+impl<T: ExtractPlugin> DynExtract for T {
+    fn name(&self) -> &'static CStr {
+        T::NAME
+    }
+
+    fn event_types(&self) -> &'static [EventType] {
+        T::EVENT_TYPES
+    }
+
+    fn event_sources(&self) -> &'static [&'static str] {
+        T::EVENT_SOURCES
+    }
+
+    fn fields_schema(&self) -> &'static CStr {
+        T::get_fields()
+    }
+
+    fn field_index(&self, name: &str) -> Option<usize> {
+        T::field_index(name)
+    }
+
+    fn extract_fields<'a>(
+        &'a mut self,
+        event_input: &EventInput,
+        table_reader: &TableReader,
+        fields: &mut [ss_plugin_extract_field],
+        storage: &'a mut FieldStorage,
+    ) -> Result<(), anyhow::Error> {
+        ExtractPlugin::extract_fields_with_storage(self, event_input, table_reader, fields, storage)
+    }
+}
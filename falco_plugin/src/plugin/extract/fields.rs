@@ -1,3 +1,7 @@
+use crate::plugin::base::storage_stats::{try_alloc, try_reserve, BumpWriter};
+use crate::plugin::convert;
+use crate::plugin::extract::storage::FIELD_STORAGE;
+use crate::plugin::extract::time::{AbsTime, RelTime};
 use falco_event::fields::types::PT_IPNET;
 use falco_event::fields::ToBytes;
 use falco_plugin_api::{
@@ -52,7 +56,7 @@ mod direct {
         storage: &mut bumpalo::Bump,
     ) -> Result<(*mut c_void, u64), std::io::Error> {
         let mut buf = bumpalo::collections::Vec::new_in(storage);
-        val.write(&mut buf)?;
+        val.write(BumpWriter::new(&mut buf, FIELD_STORAGE))?;
         Ok((buf.as_mut_ptr().cast(), 1))
     }
 
@@ -61,10 +65,11 @@ mod direct {
         storage: &mut bumpalo::Bump,
     ) -> Result<(*mut c_void, u64), std::io::Error> {
         let mut buf = bumpalo::collections::Vec::new_in(storage);
+        let mut writer = BumpWriter::new(&mut buf, FIELD_STORAGE);
         for item in val.iter() {
-            item.write(&mut buf)?;
+            item.write(&mut writer)?;
         }
-        Ok((buf.as_mut_ptr().cast(), val.len() as u64))
+        Ok((buf.as_mut_ptr().cast(), convert::checked(val.len())?))
     }
 }
 
@@ -76,9 +81,9 @@ mod by_pointer {
         storage: &mut bumpalo::Bump,
     ) -> Result<(*mut c_void, u64), std::io::Error> {
         let mut buf = bumpalo::collections::Vec::new_in(storage);
-        val.write(&mut buf)?;
+        val.write(BumpWriter::new(&mut buf, FIELD_STORAGE))?;
 
-        let ptr_buf = storage.alloc(buf.as_ptr());
+        let ptr_buf = try_alloc(storage, FIELD_STORAGE, buf.as_ptr())?;
         Ok((ptr_buf as *mut _ as *mut _, 1))
     }
 
@@ -87,22 +92,23 @@ mod by_pointer {
         storage: &mut bumpalo::Bump,
     ) -> Result<(*mut c_void, u64), std::io::Error> {
         let mut sizes = bumpalo::collections::Vec::new_in(storage);
-        sizes.reserve(val.len());
+        try_reserve(&mut sizes, val.len(), FIELD_STORAGE)?;
 
         let mut buf = bumpalo::collections::Vec::new_in(storage);
+        let mut writer = BumpWriter::new(&mut buf, FIELD_STORAGE);
         for item in val.iter() {
-            item.write(&mut buf)?;
+            item.write(&mut writer)?;
             sizes.push(item.binary_size());
         }
 
         let mut ptr_buf = bumpalo::collections::Vec::new_in(storage);
-        ptr_buf.reserve(sizes.len());
+        try_reserve(&mut ptr_buf, sizes.len(), FIELD_STORAGE)?;
         let mut ptr = buf.as_ptr();
         for size in sizes {
             ptr_buf.push(ptr);
             ptr = unsafe { ptr.add(size) };
         }
-        Ok((ptr_buf.as_mut_ptr().cast(), val.len() as u64))
+        Ok((ptr_buf.as_mut_ptr().cast(), convert::checked(val.len())?))
     }
 }
 
@@ -114,12 +120,16 @@ mod by_bytebuf {
         storage: &mut bumpalo::Bump,
     ) -> Result<(*mut c_void, u64), std::io::Error> {
         let mut buf = bumpalo::collections::Vec::new_in(storage);
-        val.write(&mut buf)?;
+        val.write(BumpWriter::new(&mut buf, FIELD_STORAGE))?;
 
-        let bb_buf = storage.alloc(ss_plugin_byte_buffer {
-            len: val.binary_size() as u32,
-            ptr: buf.as_ptr().cast(),
-        });
+        let bb_buf = try_alloc(
+            storage,
+            FIELD_STORAGE,
+            ss_plugin_byte_buffer {
+                len: convert::checked(val.binary_size())?,
+                ptr: buf.as_ptr().cast(),
+            },
+        )?;
 
         Ok((bb_buf as *mut _ as *mut _, 1))
     }
@@ -129,26 +139,27 @@ mod by_bytebuf {
         storage: &mut bumpalo::Bump,
     ) -> Result<(*mut c_void, u64), std::io::Error> {
         let mut sizes = bumpalo::collections::Vec::new_in(storage);
-        sizes.reserve(val.len());
+        try_reserve(&mut sizes, val.len(), FIELD_STORAGE)?;
 
         let mut buf = bumpalo::collections::Vec::new_in(storage);
+        let mut writer = BumpWriter::new(&mut buf, FIELD_STORAGE);
         for item in val.iter() {
-            item.write(&mut buf)?;
+            item.write(&mut writer)?;
             sizes.push(item.binary_size());
         }
 
         let mut bb_buf = bumpalo::collections::Vec::new_in(storage);
-        bb_buf.reserve(sizes.len());
+        try_reserve(&mut bb_buf, sizes.len(), FIELD_STORAGE)?;
 
         let mut ptr = buf.as_ptr();
         for size in sizes {
             bb_buf.push(ss_plugin_byte_buffer {
-                len: size as u32,
+                len: convert::checked(size)?,
                 ptr: ptr.cast(),
             });
             ptr = unsafe { ptr.add(size) };
         }
-        Ok((bb_buf.as_mut_ptr().cast(), val.len() as u64))
+        Ok((bb_buf.as_mut_ptr().cast(), convert::checked(val.len())?))
     }
 }
 
@@ -191,6 +202,8 @@ macro_rules! extract {
 extract!(u64: direct => ExtractFieldTypeId::U64);
 extract!(Duration: direct => ExtractFieldTypeId::RelTime);
 extract!(SystemTime: direct => ExtractFieldTypeId::AbsTime);
+extract!(RelTime: direct => ExtractFieldTypeId::RelTime);
+extract!(AbsTime: direct => ExtractFieldTypeId::AbsTime);
 extract!(bool: direct => ExtractFieldTypeId::Bool);
 extract!(CString: by_pointer => ExtractFieldTypeId::String);
 extract!(IpAddr: by_bytebuf => ExtractFieldTypeId::IpAddr);
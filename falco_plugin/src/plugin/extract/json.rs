@@ -0,0 +1,118 @@
+use crate::plugin::extract::ExtractFieldRequestArg;
+use anyhow::Error;
+use std::ffi::CString;
+
+/// # JSON data to extract fields from
+///
+/// Plugins exposing JSON payloads as fields tend to need the same set of fields:
+/// the value of a key, whether a key exists and the list of all keys. This type implements
+/// them on top of a parsed [`serde_json::Value`], taking the key from the field argument
+/// (so the fields should use [`ExtractArgType::RequiredKey`](`crate::extract::ExtractArgType::RequiredKey`)).
+///
+/// If the key starts with `/`, it's used as a [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+/// to access nested values, e.g. `/container/id`.
+///
+/// Parsing the payload is the expensive part, so keep the `JsonData` in the
+/// [`ExtractPlugin::ExtractContext`](`crate::extract::ExtractPlugin::ExtractContext`)
+/// to parse every event only once, no matter how many fields are extracted from it.
+#[derive(Debug, Default, Clone)]
+pub struct JsonData(pub serde_json::Value);
+
+impl JsonData {
+    /// Parse a JSON payload
+    ///
+    /// An empty payload is treated as `null`.
+    pub fn parse(payload: &[u8]) -> Result<Self, Error> {
+        match payload.is_empty() {
+            true => Ok(Self(serde_json::Value::Null)),
+            false => Ok(Self(serde_json::from_slice(payload)?)),
+        }
+    }
+
+    /// Look up the value of a key (or JSON pointer) passed as the field argument
+    pub fn get(&self, arg: &ExtractFieldRequestArg) -> Result<Option<&serde_json::Value>, Error> {
+        let ExtractFieldRequestArg::String(key) = arg else {
+            anyhow::bail!("expected a key argument, got {:?}", arg);
+        };
+        let key = key.to_str()?;
+
+        Ok(match key.starts_with('/') {
+            true => self.0.pointer(key),
+            false => self.0.get(key),
+        })
+    }
+
+    /// Extract the value of a key (or JSON pointer)
+    ///
+    /// Strings are extracted as-is, everything else as JSON. A missing key is an error.
+    pub fn value(&self, arg: &ExtractFieldRequestArg) -> Result<CString, Error> {
+        let value = self
+            .get(arg)?
+            .ok_or_else(|| anyhow::anyhow!("no key {:?} in event", arg))?;
+
+        let value = match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        Ok(CString::new(value)?)
+    }
+
+    /// Check whether the data contains a key (or JSON pointer)
+    pub fn has_key(&self, arg: &ExtractFieldRequestArg) -> Result<bool, Error> {
+        Ok(self.get(arg)?.is_some())
+    }
+
+    /// Extract all the top-level keys
+    ///
+    /// Data other than JSON objects has no keys.
+    pub fn keys(&self) -> Result<Vec<CString>, Error> {
+        let Some(data) = self.0.as_object() else {
+            return Ok(Vec::new());
+        };
+
+        Ok(data
+            .keys()
+            .map(|k| CString::new(k.as_str()))
+            .collect::<Result<_, _>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonData;
+    use crate::plugin::extract::ExtractFieldRequestArg;
+
+    fn key(key: &'static std::ffi::CStr) -> ExtractFieldRequestArg<'static> {
+        ExtractFieldRequestArg::String(key)
+    }
+
+    #[test]
+    fn test_json_data() {
+        let data = JsonData::parse(br#"{"user":"root","uid":0,"container":{"id":"abc"}}"#).unwrap();
+
+        assert_eq!(data.value(&key(c"user")).unwrap().as_c_str(), c"root");
+        assert_eq!(data.value(&key(c"uid")).unwrap().as_c_str(), c"0");
+        assert_eq!(
+            data.value(&key(c"/container/id")).unwrap().as_c_str(),
+            c"abc"
+        );
+        assert!(data.value(&key(c"missing")).is_err());
+        assert!(data.value(&ExtractFieldRequestArg::Int(1)).is_err());
+
+        assert!(data.has_key(&key(c"uid")).unwrap());
+        assert!(!data.has_key(&key(c"/container/name")).unwrap());
+
+        let mut keys = data.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, [c"container", c"uid", c"user"]);
+    }
+
+    #[test]
+    fn test_json_data_not_object() {
+        let data = JsonData::parse(b"").unwrap();
+        assert!(data.keys().unwrap().is_empty());
+        assert!(!data.has_key(&key(c"user")).unwrap());
+
+        assert!(JsonData::parse(b"{").is_err());
+    }
+}
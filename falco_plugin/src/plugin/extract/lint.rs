@@ -0,0 +1,261 @@
+use crate::extract::{ExtractArgType, ExtractFieldInfo, ExtractPlugin};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// # The kind of problem found by [`lint_rules`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintIssueKind {
+    /// The field is not defined by the plugin (with the closest match, if any)
+    UnknownField {
+        /// a field with a similar name
+        suggestion: Option<&'static str>,
+    },
+    /// The field requires an argument, but none was given
+    MissingArgument,
+    /// The field does not take an argument, but one was given
+    UnexpectedArgument,
+    /// The field takes an index argument, but the argument is not a number
+    ExpectedIndex,
+}
+
+/// # A problem with a field reference in Falco rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// where the field was found, e.g. `rule "Some rule" (condition)`
+    pub location: String,
+    /// the field reference, as written in the rules (including the argument)
+    pub field: String,
+    /// what is wrong with the reference
+    pub kind: LintIssueKind,
+}
+
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: ", self.location, self.field)?;
+        match &self.kind {
+            LintIssueKind::UnknownField {
+                suggestion: Some(suggestion),
+            } => write!(f, "unknown field (did you mean {}?)", suggestion),
+            LintIssueKind::UnknownField { suggestion: None } => f.write_str("unknown field"),
+            LintIssueKind::MissingArgument => f.write_str("missing required argument"),
+            LintIssueKind::UnexpectedArgument => f.write_str("field does not take an argument"),
+            LintIssueKind::ExpectedIndex => f.write_str("argument must be a number"),
+        }
+    }
+}
+
+/// A field reference found in a condition or output
+#[derive(Debug, PartialEq, Eq)]
+struct FieldRef<'a> {
+    name: &'a str,
+    arg: Option<&'a str>,
+    text: &'a str,
+}
+
+/// Find all references to fields starting with one of `prefixes` (followed by a dot)
+///
+/// Quoted strings are skipped, since they can contain anything.
+fn find_field_refs<'a>(text: &'a str, prefixes: &BTreeSet<&str>) -> Vec<FieldRef<'a>> {
+    fn is_field_char(b: u8) -> bool {
+        b.is_ascii_alphanumeric() || matches!(b, b'_' | b'.' | b'-')
+    }
+
+    let bytes = text.as_bytes();
+    let mut refs = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let b = bytes[pos];
+        if b == b'"' || b == b'\'' {
+            pos += bytes[pos + 1..]
+                .iter()
+                .position(|c| *c == b)
+                .map(|end| end + 2)
+                .unwrap_or(bytes.len() - pos);
+            continue;
+        }
+        if !is_field_char(b) {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        while pos < bytes.len() && is_field_char(bytes[pos]) {
+            pos += 1;
+        }
+        let name = text[start..pos].trim_end_matches('.');
+        let is_ours = name
+            .split_once('.')
+            .is_some_and(|(prefix, _)| prefixes.contains(prefix));
+
+        let mut arg = None;
+        if pos < bytes.len() && bytes[pos] == b'[' {
+            if let Some(len) = bytes[pos..].iter().position(|c| *c == b']') {
+                arg = Some(&text[pos + 1..pos + len]);
+                pos += len + 1;
+            }
+        }
+
+        if is_ours {
+            refs.push(FieldRef {
+                name,
+                arg,
+                text: &text[start..pos],
+            });
+        }
+    }
+
+    refs
+}
+
+/// The Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+fn check_field<P: ExtractPlugin>(
+    fields: &[ExtractFieldInfo<P>],
+    field: &FieldRef,
+) -> Option<LintIssueKind> {
+    let Some(info) = fields.iter().find(|f| f.name == field.name) else {
+        let suggestion = fields
+            .iter()
+            .map(|f| (edit_distance(f.name, field.name), f.name))
+            .filter(|(distance, _)| *distance <= 2)
+            .min()
+            .map(|(_, name)| name);
+        return Some(LintIssueKind::UnknownField { suggestion });
+    };
+
+    match (info.arg, field.arg) {
+        (ExtractArgType::None, Some(_)) => Some(LintIssueKind::UnexpectedArgument),
+        (ExtractArgType::RequiredIndex | ExtractArgType::RequiredKey, None) => {
+            Some(LintIssueKind::MissingArgument)
+        }
+        (ExtractArgType::RequiredIndex | ExtractArgType::OptionalIndex, Some(arg))
+            if arg.trim().parse::<u64>().is_err() =>
+        {
+            Some(LintIssueKind::ExpectedIndex)
+        }
+        _ => None,
+    }
+}
+
+/// # Check Falco rules against the fields defined by a plugin
+///
+/// This parses a Falco rules file (YAML) and checks all references to the plugin's fields
+/// in rule conditions, rule outputs and macro conditions. It reports:
+/// - fields that the plugin does not define (e.g. typos), with a suggestion if a similarly
+///   named field exists
+/// - fields used with an argument they do not accept (or without a required one)
+/// - index arguments that are not numbers
+///
+/// Only fields starting with one of the plugin's prefixes (the part of the field names before
+/// the first dot, e.g. `myplugin` in `myplugin.user`) are checked, since the rules may also use
+/// fields from Falco itself and other plugins.
+///
+/// This is meant to be called from a test in the plugin repository, to check the rules
+/// shipped with the plugin against the fields defined in the code:
+///
+/// ```ignore
+/// #[test]
+/// fn test_rules() {
+///     let rules = std::fs::read_to_string("rules/my_plugin_rules.yaml").unwrap();
+///     let issues = lint_rules(&rules, MyPlugin::EXTRACT_FIELDS).unwrap();
+///     assert!(issues.is_empty(), "{:#?}", issues);
+/// }
+/// ```
+///
+/// This function is only available with the `rules-lint` feature.
+pub fn lint_rules<P: ExtractPlugin>(
+    rules_yaml: &str,
+    fields: &[ExtractFieldInfo<P>],
+) -> Result<Vec<LintIssue>, anyhow::Error> {
+    let prefixes: BTreeSet<&str> = fields
+        .iter()
+        .filter_map(|f| f.name.split_once('.'))
+        .map(|(prefix, _)| prefix)
+        .collect();
+
+    let items: Vec<serde_yaml::Mapping> = serde_yaml::from_str(rules_yaml)?;
+    let mut issues = Vec::new();
+    for item in &items {
+        let get = |key: &str| item.get(key).and_then(|v| v.as_str());
+        let (kind, name, keys): (_, _, &[&str]) = if let Some(name) = get("rule") {
+            ("rule", name, &["condition", "output"])
+        } else if let Some(name) = get("macro") {
+            ("macro", name, &["condition"])
+        } else {
+            continue;
+        };
+
+        for key in keys {
+            let Some(text) = get(key) else {
+                continue;
+            };
+
+            for field in find_field_refs(text, &prefixes) {
+                if let Some(issue) = check_field(fields, &field) {
+                    issues.push(LintIssue {
+                        location: format!("{} {:?} ({})", kind, name, key),
+                        field: field.text.to_string(),
+                        kind: issue,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, find_field_refs, FieldRef};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_find_field_refs() {
+        let prefixes = BTreeSet::from(["dummy"]);
+        let refs = find_field_refs(
+            r#"dummy.a = "dummy.quoted" and dummy.b[foo] in (x) and proc.name=dummy and %dummy.c."#,
+            &prefixes,
+        );
+        assert_eq!(
+            refs,
+            [
+                FieldRef {
+                    name: "dummy.a",
+                    arg: None,
+                    text: "dummy.a"
+                },
+                FieldRef {
+                    name: "dummy.b",
+                    arg: Some("foo"),
+                    text: "dummy.b[foo]"
+                },
+                FieldRef {
+                    name: "dummy.c",
+                    arg: None,
+                    text: "dummy.c."
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("dummy.payload", "dummy.payload"), 0);
+        assert_eq!(edit_distance("dummy.paylaod", "dummy.payload"), 2);
+        assert_eq!(edit_distance("dummy.pay", "dummy.payload"), 4);
+    }
+}
@@ -1,6 +1,9 @@
 use crate::extract::{EventInput, ExtractArgType};
 use crate::plugin::base::Plugin;
+use crate::plugin::extract::post_process::apply_post_process;
 use crate::plugin::extract::schema::ExtractFieldInfo;
+use crate::plugin::extract::storage::FieldStorage;
+use crate::strings::from_ptr::try_cstr_from_ptr;
 use crate::tables::TableReader;
 use falco_event::events::types::EventType;
 use falco_plugin_api::ss_plugin_extract_field;
@@ -10,8 +13,18 @@ use std::ffi::{CStr, CString};
 use std::sync::Mutex;
 use thiserror::Error;
 
+pub mod borrowed;
+pub mod cache;
+pub mod dynamic;
 pub mod fields;
+pub mod json;
+#[cfg(feature = "rules-lint")]
+pub mod lint;
+pub mod post_process;
 pub mod schema;
+pub mod storage;
+pub mod time;
+pub(crate) mod trace;
 #[doc(hidden)]
 pub mod wrappers;
 
@@ -32,17 +45,22 @@ pub enum ExtractFieldRequestArg<'a> {
     String(&'a CStr),
 }
 
+/// # An error validating the argument passed to an extracted field
 #[derive(Debug, Error)]
 pub enum ArgError {
+    /// The field requires an argument but none was passed
     #[error("required argument missing")]
     Missing,
 
+    /// The field does not take an argument but one was passed
     #[error("unexpected argument")]
     Unexpected,
 
+    /// The field takes a string argument but got an integer
     #[error("expected string argument")]
     ExpectedString,
 
+    /// The field takes an integer argument but got a string
     #[error("expected int argument")]
     ExpectedInt,
 }
@@ -105,6 +123,28 @@ pub struct ExtractRequest<'c, 'e, 't, P: ExtractPlugin> {
     ///
     /// See [`crate::tables`] for details
     pub table_reader: &'t TableReader,
+
+    field_name: &'static str,
+    arg: ExtractFieldRequestArg<'c>,
+}
+
+impl<'c, P: ExtractPlugin> ExtractRequest<'c, '_, '_, P> {
+    /// # The name of the field being extracted
+    ///
+    /// This is the name from [`ExtractPlugin::EXTRACT_FIELDS`], so it can be used
+    /// in shared helpers (e.g. in error messages or as a cache key) without passing it
+    /// around explicitly.
+    pub fn field_name(&self) -> &'static str {
+        self.field_name
+    }
+
+    /// # The argument passed to the field being extracted
+    ///
+    /// This is the argument as passed by the framework, before validating it against
+    /// the [`ExtractArgType`] of the field (the extractor function receives the validated one).
+    pub fn arg(&self) -> &ExtractFieldRequestArg<'c> {
+        &self.arg
+    }
 }
 
 /// # Support for field extraction plugins
@@ -120,6 +160,12 @@ where
     /// **Note**: some notable event types are:
     /// - [`EventType::ASYNCEVENT_E`], generated from async plugins
     /// - [`EventType::PLUGINEVENT_E`], generated from source plugins
+    ///
+    /// **Note**: operators can narrow this list (but not extend it) at runtime by adding
+    /// a `sdk_event_types` key (a list of event type numbers or names, like `"ASYNCEVENT_E"`)
+    /// to the plugin config, if it's a JSON object. The SDK removes the key before passing
+    /// the config to the plugin. A list that does not include any of the declared event types
+    /// is rejected when loading the config.
     const EVENT_TYPES: &'static [EventType];
     /// The set of event sources supported by this plugin
     ///
@@ -127,6 +173,10 @@ where
     /// only get invoked for events from sources named in this list.
     ///
     /// **Note**: one notable event source is called `syscall`
+    ///
+    /// **Note**: operators can narrow this list (but not extend it) at runtime by adding
+    /// a `sdk_event_sources` key (a list of event source names) to the plugin config,
+    /// if it's a JSON object. The SDK removes the key before passing the config to the plugin.
     const EVENT_SOURCES: &'static [&'static str];
 
     /// The extraction context
@@ -138,23 +188,36 @@ where
     /// If you do not need a context to share between extracting fields of the same event, use `()`
     /// as the type.
     ///
-    /// Since the context is created using the [`Default`] trait, you may prefer to use an Option
-    /// wrapping the actual context type:
+    /// Note that the plugin framework may call `extract_fields` more than once for the same event.
+    /// To reuse the preprocessed data across these calls as well, store it in an
+    /// [`EventCache`](`crate::extract::EventCache`) in your plugin instead.
+    ///
+    /// The context is created using [`ExtractPlugin::make_context`], which defaults to calling
+    /// [`Default::default`]. If the context needs data from the plugin itself (e.g. compiled
+    /// regular expressions or lookup tables), override `make_context` instead.
+    ///
+    /// Since the context is created for every extraction batch, you may prefer to use an Option
+    /// wrapping the actual context type and only initialize it lazily:
     ///
     /// ```ignore
     /// impl ExtractPlugin for MyPlugin {
     ///     type ExtractContext = Option<ActualContext>;
     ///     // ...
+    ///
+    ///     // this is the default implementation, shown here for clarity
+    ///     fn make_context(&mut self) -> Self::ExtractContext {
+    ///         None
+    ///     }
     /// }
     ///
     /// impl MyPlugin {
-    ///     fn make_context(&mut self, ...) -> ActualContext { /* ... */ }
+    ///     fn make_actual_context(&mut self, ...) -> ActualContext { /* ... */ }
     ///
     ///     fn extract_field_one(
     ///         &mut self,
-    ///         req: ExtractContext<Self>,
+    ///         req: ExtractRequest<Self>,
     ///         arg: ExtractRequestArg) -> ... {
-    ///         let context = req.context.get_or_insert_with(|| self.make_context(...));
+    ///         let context = req.context.get_or_insert_with(|| self.make_actual_context(...));
     ///
     ///         // use context
     ///     }
@@ -181,11 +244,15 @@ where
     /// - [`u64`]
     /// - [`bool`]
     /// - [`CString`]
-    /// - [`std::time::SystemTime`]
-    /// - [`std::time::Duration`]
+    /// - [`std::time::SystemTime`] or [`AbsTime`](`crate::extract::AbsTime`)
+    /// - [`std::time::Duration`] or [`RelTime`](`crate::extract::RelTime`)
     /// - [`std::net::IpAddr`]
     /// - [`falco_event::fields::types::PT_IPNET`]
     ///
+    /// Time values are extracted with nanosecond resolution. Values that cannot be represented
+    /// (timestamps before the Unix epoch, intervals longer than `u64::MAX` nanoseconds)
+    /// make the extraction fail rather than being silently truncated.
+    ///
     /// `req` is the extraction request ([`ExtractRequest`]), containing the context in which
     /// the plugin is doing the work.
     ///
@@ -259,6 +326,33 @@ where
     /// using [`ExtractFieldInfo::with_arg`] if the function expects an argument.
     const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>];
 
+    /// The minimum size of the [`FieldStorage`] chunk, in bytes
+    ///
+    /// If your plugin regularly extracts large values (e.g. long strings or lists), you can
+    /// preallocate the storage to avoid growing it while processing the first events.
+    /// The default of zero leaves the allocation strategy to the storage itself.
+    const FIELD_STORAGE_CHUNK_SIZE: usize = 0;
+
+    /// The maximum size of the [`FieldStorage`], in bytes
+    ///
+    /// A buggy extractor (e.g. one returning an unbounded list) could otherwise allocate
+    /// without bound inside the host process. When the limit is reached, the extraction
+    /// fails with an error instead. The limit applies to the memory allocated from the system,
+    /// so it should be comfortably larger than the values extracted from a single event.
+    ///
+    /// The SDK reports a failed allocation as a [`StorageError`](`crate::base::StorageError`).
+    /// If you allocate from the storage yourself (e.g. in a custom `extract_fields`), use
+    /// the fallible `try_alloc*` methods of [`bumpalo::Bump`], since the others panic
+    /// when they hit the limit.
+    ///
+    /// This is a method, so that the limit can be taken from the plugin configuration.
+    /// Consider enabling [`Plugin::STORAGE_METRICS`] to find a reasonable value.
+    ///
+    /// The default implementation returns `None` (no limit).
+    fn field_storage_limit(&self) -> Option<usize> {
+        None
+    }
+
     /// Generate the field schema for the Falco plugin framework
     ///
     /// The default implementation inspects all fields from [`Self::EXTRACT_FIELDS`] and generates
@@ -291,9 +385,46 @@ where
         }
     }
 
+    /// Find the index of a field in [`ExtractPlugin::EXTRACT_FIELDS`] by its name
+    ///
+    /// The plugin framework refers to fields by their index in `EXTRACT_FIELDS`, so you can
+    /// use this e.g. in tests, to make sure the field order stays the same across plugin
+    /// versions. To resolve the index at compile time, use [`field_index`](`crate::extract::field_index`)
+    /// in a constant instead.
+    fn field_index(name: &str) -> Option<usize> {
+        schema::field_index(Self::EXTRACT_FIELDS, name)
+    }
+
+    /// Enable tracing of extraction requests
+    ///
+    /// When this returns true, every extraction request (the field name, its argument,
+    /// the event number and the extracted value or the error) is logged at trace level.
+    /// The messages are rate limited to avoid flooding the log.
+    ///
+    /// This is meant for debugging rules that don't match as expected, so it's best tied
+    /// to a configuration option, letting you turn it on in the field without rebuilding
+    /// the plugin. Note that Falco needs to be configured to forward trace level messages
+    /// to the log (`log_level: trace`).
+    ///
+    /// The default implementation returns false.
+    fn trace_extractions(&self) -> bool {
+        false
+    }
+
+    /// Create the extraction context
+    ///
+    /// This method is called once for every event (a batch of field extraction requests)
+    /// and the returned context is shared between all the extractions for that event.
+    ///
+    /// The default implementation returns [`Default::default()`].
+    fn make_context(&mut self) -> Self::ExtractContext {
+        Self::ExtractContext::default()
+    }
+
     /// Perform the actual field extraction
     ///
-    /// The default implementation creates an empty context and loops over all extraction
+    /// The default implementation creates a context (using [`ExtractPlugin::make_context`])
+    /// and loops over all extraction
     /// requests, invoking the relevant function to actually generate the field value.
     ///
     /// You probably won't need to provide your own implementation.
@@ -304,21 +435,64 @@ where
         fields: &mut [ss_plugin_extract_field],
         storage: &'a mut bumpalo::Bump,
     ) -> Result<(), anyhow::Error> {
-        let mut context = Self::ExtractContext::default();
+        let mut context = self.make_context();
 
         for req in fields {
             let info = Self::EXTRACT_FIELDS
                 .get(req.field_id as usize)
                 .ok_or_else(|| anyhow::anyhow!("field index out of bounds"))?;
 
+            // the framework refers to fields by index, so make sure we agree on which
+            // field that is (e.g. after reordering EXTRACT_FIELDS in a newer plugin version)
+            if let Some(name) = unsafe { try_cstr_from_ptr(req.field) } {
+                if name.to_bytes() != info.name.as_bytes() {
+                    anyhow::bail!(
+                        "field index {} refers to {}, but {} was requested",
+                        req.field_id,
+                        info.name,
+                        name.to_string_lossy()
+                    );
+                }
+            }
+
+            // SAFETY: the argument string is owned by the framework and remains valid
+            // for the whole extraction call, so we can detach it from `req`
+            let arg = match unsafe { req.key_unchecked() } {
+                ExtractFieldRequestArg::String(s) => {
+                    ExtractFieldRequestArg::String(unsafe { CStr::from_ptr(s.as_ptr()) })
+                }
+                ExtractFieldRequestArg::None => ExtractFieldRequestArg::None,
+                ExtractFieldRequestArg::Int(i) => ExtractFieldRequestArg::Int(i),
+            };
+
             let request = ExtractRequest::<Self> {
                 context: &mut context,
                 event: event_input,
                 table_reader,
+                field_name: info.name,
+                arg,
             };
 
             info.func.extract(self, req, request, info.arg, storage)?;
+            unsafe { apply_post_process(self, info.post_process, req, storage)? };
         }
         Ok(())
     }
+
+    /// Perform the actual field extraction, with access to the whole [`FieldStorage`]
+    ///
+    /// This is the method the SDK actually calls. The default implementation
+    /// simply calls [`ExtractPlugin::extract_fields`] with the underlying allocator.
+    ///
+    /// Override it instead of `extract_fields` if you need to inspect the storage
+    /// while extracting (e.g. to check its [high water mark](`FieldStorage::high_water_mark`)).
+    fn extract_fields_with_storage<'a>(
+        &'a mut self,
+        event_input: &EventInput,
+        table_reader: &TableReader,
+        fields: &mut [ss_plugin_extract_field],
+        storage: &'a mut FieldStorage,
+    ) -> Result<(), anyhow::Error> {
+        self.extract_fields(event_input, table_reader, fields, storage)
+    }
 }
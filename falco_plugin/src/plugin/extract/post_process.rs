@@ -0,0 +1,192 @@
+use crate::plugin::base::storage_stats::try_alloc_c_str;
+use crate::plugin::extract::storage::FIELD_STORAGE;
+use anyhow::Error;
+use falco_plugin_api::ss_plugin_extract_field;
+use std::borrow::Cow;
+use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Formatter};
+
+/// # A transformation applied to extracted string values
+///
+/// Post-processors are attached to string fields with
+/// [`ExtractFieldInfo::with_post_process`](`crate::extract::ExtractFieldInfo::with_post_process`)
+/// and run by the SDK after the extractor returns, in the order they are listed. For list fields,
+/// they are applied to every element.
+///
+/// This lets you declare simple value normalization once, instead of repeating it
+/// in every extractor:
+///
+/// ```ignore
+/// const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+///     field("my.user", &Self::extract_user).with_post_process(&[
+///         PostProcess::StripPrefix("DOMAIN\\"),
+///         PostProcess::Lowercase,
+///         PostProcess::Lookup(|plugin, user| plugin.user_aliases.get(user).map(String::as_str)),
+///     ]),
+/// ];
+/// ```
+pub enum PostProcess<P> {
+    /// Convert the value to lowercase
+    ///
+    /// Values that aren't valid UTF-8 only get ASCII characters converted
+    Lowercase,
+    /// Convert the value to uppercase
+    ///
+    /// Values that aren't valid UTF-8 only get ASCII characters converted
+    Uppercase,
+    /// Remove a prefix from the value, if present
+    StripPrefix(&'static str),
+    /// Remove a suffix from the value, if present
+    StripSuffix(&'static str),
+    /// Replace the value using a lookup table owned by the plugin (e.g. loaded from config)
+    ///
+    /// The function returns the replacement value, or `None` to leave the value unchanged.
+    /// Values that aren't valid UTF-8 are never looked up.
+    Lookup(for<'a> fn(&'a P, &str) -> Option<&'a str>),
+    /// Apply an arbitrary transformation
+    Custom(fn(&P, &CStr) -> Result<CString, Error>),
+}
+
+// functions are shown as just the variant name, their addresses are meaningless
+impl<P> Debug for PostProcess<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PostProcess::Lowercase => f.write_str("Lowercase"),
+            PostProcess::Uppercase => f.write_str("Uppercase"),
+            PostProcess::StripPrefix(prefix) => f.debug_tuple("StripPrefix").field(prefix).finish(),
+            PostProcess::StripSuffix(suffix) => f.debug_tuple("StripSuffix").field(suffix).finish(),
+            PostProcess::Lookup(_) => f.write_str("Lookup(..)"),
+            PostProcess::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl<P> PostProcess<P> {
+    /// Apply the transformation, returning `None` if the value doesn't change
+    fn apply(&self, plugin: &P, value: &CStr) -> Result<Option<CString>, Error> {
+        let bytes = value.to_bytes();
+        let changed = match self {
+            PostProcess::Lowercase => Some(match value.to_str() {
+                Ok(s) => s.to_lowercase().into_bytes(),
+                Err(_) => bytes.to_ascii_lowercase(),
+            }),
+            PostProcess::Uppercase => Some(match value.to_str() {
+                Ok(s) => s.to_uppercase().into_bytes(),
+                Err(_) => bytes.to_ascii_uppercase(),
+            }),
+            PostProcess::StripPrefix(prefix) => bytes
+                .strip_prefix(prefix.as_bytes())
+                .map(|rest| rest.to_vec()),
+            PostProcess::StripSuffix(suffix) => bytes
+                .strip_suffix(suffix.as_bytes())
+                .map(|rest| rest.to_vec()),
+            PostProcess::Lookup(lookup) => value
+                .to_str()
+                .ok()
+                .and_then(|s| lookup(plugin, s))
+                .map(|s| s.as_bytes().to_vec()),
+            PostProcess::Custom(func) => return func(plugin, value).map(Some),
+        };
+
+        // values returned by `Lookup` come from the plugin and may contain a NUL
+        Ok(changed.map(CString::new).transpose()?)
+    }
+}
+
+/// Run post-processors on an extracted string field
+///
+/// Changed values are copied to `storage` and the pointers in the field result are updated.
+///
+/// # Safety
+/// `field` must contain a string (or string list) result stored in `storage`
+pub(crate) unsafe fn apply_post_process<P>(
+    plugin: &P,
+    steps: &[PostProcess<P>],
+    field: &mut ss_plugin_extract_field,
+    storage: &bumpalo::Bump,
+) -> Result<(), Error> {
+    if steps.is_empty() || field.res_len == 0 {
+        return Ok(());
+    }
+
+    // the pointer array lives in our storage, so we're allowed to modify it
+    let values = unsafe { std::slice::from_raw_parts_mut(field.res.str_, field.res_len as usize) };
+
+    for value in values {
+        let mut current = Cow::Borrowed(unsafe { CStr::from_ptr(*value) });
+        for step in steps {
+            if let Some(changed) = step.apply(plugin, &current)? {
+                current = Cow::Owned(changed);
+            }
+        }
+
+        if let Cow::Owned(changed) = current {
+            *value = try_alloc_c_str(storage, FIELD_STORAGE, changed.to_bytes())?
+                .as_ptr()
+                .cast();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PostProcess;
+    use std::collections::BTreeMap;
+    use std::ffi::CString;
+
+    struct Plugin {
+        aliases: BTreeMap<String, String>,
+    }
+
+    fn run(steps: &[PostProcess<Plugin>], value: &[u8]) -> CString {
+        let plugin = Plugin {
+            aliases: BTreeMap::from([("adm".to_string(), "admin".to_string())]),
+        };
+        let mut current = CString::new(value).unwrap();
+        for step in steps {
+            if let Some(changed) = step.apply(&plugin, &current).unwrap() {
+                current = changed;
+            }
+        }
+        current
+    }
+
+    #[test]
+    fn test_builtin_steps() {
+        let steps: [PostProcess<Plugin>; 3] = [
+            PostProcess::StripPrefix("DOMAIN\\"),
+            PostProcess::Lowercase,
+            PostProcess::Lookup(|p, s| p.aliases.get(s).map(String::as_str)),
+        ];
+        assert_eq!(run(&steps, b"DOMAIN\\ADM").as_c_str(), c"admin");
+        assert_eq!(run(&steps, b"Other\\Adm").as_c_str(), c"other\\adm");
+        assert_eq!(run(&steps, b"ZA\xffX").as_bytes(), b"za\xffx");
+    }
+
+    #[test]
+    fn test_custom_step() {
+        let steps: [PostProcess<Plugin>; 3] = [
+            PostProcess::StripSuffix(".exe"),
+            PostProcess::Custom(|_, value| {
+                Ok(CString::new(format!("[{}]", value.to_string_lossy()))?)
+            }),
+            PostProcess::Uppercase,
+        ];
+        assert_eq!(run(&steps, b"cmd.exe").as_c_str(), c"[CMD]");
+    }
+
+    #[test]
+    fn test_debug() {
+        let steps: [PostProcess<Plugin>; 3] = [
+            PostProcess::StripPrefix("DOMAIN\\"),
+            PostProcess::Lowercase,
+            PostProcess::Lookup(|p, s| p.aliases.get(s).map(String::as_str)),
+        ];
+        assert_eq!(
+            format!("{:?}", steps),
+            r#"[StripPrefix("DOMAIN\\"), Lowercase, Lookup(..)]"#
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use crate::extract::ExtractFieldRequestArg;
+use crate::plugin::extract::borrowed::{BorrowedStr, BorrowingExtractor};
 use crate::plugin::extract::fields::{Extract, ExtractFieldTypeId};
+use crate::plugin::extract::post_process::PostProcess;
 use crate::plugin::extract::{ExtractField, ExtractPlugin, ExtractRequest};
 use anyhow::Error;
 use falco_plugin_api::ss_plugin_extract_field;
@@ -131,12 +133,22 @@ pub struct ExtractFieldInfo<P: ExtractPlugin> {
     #[serde(skip)]
     /// the function implementing the actual extraction
     pub func: &'static dyn Extractor<P>,
+    #[serde(skip)]
+    /// transformations applied to the extracted value (string fields only)
+    pub post_process: &'static [PostProcess<P>],
 }
 
 impl<P: ExtractPlugin> Debug for ExtractFieldInfo<P> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let json = serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?;
-        f.write_str(&json)
+        f.debug_struct("ExtractFieldInfo")
+            .field("name", &self.name)
+            .field("field_type", &self.field_type)
+            .field("is_list", &self.is_list)
+            .field("arg", &self.arg)
+            .field("display_name", &self.display_name)
+            .field("description", &self.description)
+            .field("post_process", &self.post_process)
+            .finish_non_exhaustive()
     }
 }
 
@@ -160,6 +172,19 @@ impl<P: ExtractPlugin> ExtractFieldInfo<P> {
         self.description = description;
         self
     }
+
+    /// Transform the extracted values before returning them to the framework
+    ///
+    /// See [`PostProcess`] for the available transformations. Only string fields
+    /// can be post-processed (this is checked at compile time).
+    pub const fn with_post_process(mut self, steps: &'static [PostProcess<P>]) -> Self {
+        assert!(
+            matches!(self.field_type, ExtractFieldTypeId::String),
+            "only string fields can be post-processed"
+        );
+        self.post_process = steps;
+        self
+    }
 }
 
 /// Wrap a function or method to make it usable as a field extractor
@@ -179,5 +204,92 @@ where
         display_name: None,
         description: name,
         func: func as &'static dyn Extractor<P>,
+        post_process: &[],
+    }
+}
+
+/// Wrap a function or method returning a (possibly) borrowed string to make it usable as a field extractor
+///
+/// This is like [`field`], except the function returns a [`BorrowedStr`], which may borrow
+/// from the event (the lifetime `'e` in the example below):
+///
+/// ```ignore
+/// impl MyPlugin {
+///     fn extract_payload<'e>(
+///         &mut self,
+///         req: ExtractRequest<'_, 'e, '_, Self>,
+///         _arg: ExtractFieldRequestArg,
+///     ) -> Result<BorrowedStr<'e>, Error> {
+///         // skip the header and the parameter lengths of a plugin event
+///         Ok(req.event.payload_slice(38..req.event.as_bytes()?.len())?.into())
+///     }
+/// }
+///
+/// impl ExtractPlugin for MyPlugin {
+///     // ...
+///     const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+///         &[borrowed_field("my.payload", &Self::extract_payload)];
+/// }
+/// ```
+///
+/// This avoids copying large string values that are already present in the event.
+/// Only single (non-list) string fields are supported.
+pub const fn borrowed_field<P, F>(name: &'static str, func: &'static F) -> ExtractFieldInfo<P>
+where
+    P: ExtractPlugin,
+    F: for<'e> Fn(
+        &mut P,
+        ExtractRequest<'_, 'e, '_, P>,
+        ExtractFieldRequestArg,
+    ) -> Result<BorrowedStr<'e>, Error>,
+{
+    ExtractFieldInfo {
+        name,
+        field_type: <BorrowedStr as Extract>::TYPE_ID,
+        is_list: <BorrowedStr as Extract>::IS_LIST,
+        arg: ExtractArgType::None,
+        display_name: None,
+        description: name,
+        func: BorrowingExtractor::wrap(func) as &'static dyn Extractor<P>,
+        post_process: &[],
+    }
+}
+
+/// Find the index of a field in `fields` by its name, at compile time if needed
+///
+/// This is a `const fn`, so the lookup can be evaluated once, in a constant:
+///
+/// ```ignore
+/// const COUNT_INDEX: Option<usize> = field_index(MyPlugin::EXTRACT_FIELDS, "my.count");
+/// const _: () = assert!(matches!(COUNT_INDEX, Some(0)));
+/// ```
+///
+/// See also [`ExtractPlugin::field_index`](`crate::extract::ExtractPlugin::field_index`).
+pub const fn field_index<P: ExtractPlugin>(
+    fields: &[ExtractFieldInfo<P>],
+    name: &str,
+) -> Option<usize> {
+    let mut i = 0;
+    while i < fields.len() {
+        if str_eq(fields[i].name, name) {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
     }
+    true
 }
@@ -0,0 +1,128 @@
+use std::ops::{Deref, DerefMut};
+
+/// The name of the field storage in allocation errors
+pub(crate) const FIELD_STORAGE: &str = "field storage";
+
+/// # Storage for extracted field values
+///
+/// Extracted values need to outlive the call to [`crate::extract::ExtractPlugin::extract_fields`]
+/// (they are read by the plugin framework afterwards), so they are stored in a bump allocator
+/// owned by the SDK, instead of on the stack.
+///
+/// The storage is reset right before every call to `extract_fields`, so the values
+/// stored there remain valid until the next extraction request. Resetting keeps the largest
+/// allocated chunk around, so after warming up, extraction does not need to allocate memory
+/// from the system at all (unless a single event needs more storage than ever before).
+///
+/// The high-water mark (the largest amount of memory the storage held during a single
+/// extraction request) is tracked across resets, so you can expose it as a metric to monitor
+/// the memory behavior of your plugin. Note that it's measured in allocated chunk capacity,
+/// not in bytes actually taken by the values, so it's an upper bound on the real usage.
+///
+/// This type dereferences to [`bumpalo::Bump`], so you can use it directly as an allocator.
+#[derive(Debug, Default)]
+pub struct FieldStorage {
+    bump: bumpalo::Bump,
+    high_water_mark: usize,
+}
+
+impl FieldStorage {
+    /// Create an empty field storage
+    ///
+    /// No memory is allocated until the first value gets stored.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a field storage with at least `capacity` bytes preallocated
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bump: bumpalo::Bump::with_capacity(capacity),
+            high_water_mark: 0,
+        }
+    }
+
+    /// Make sure the storage can hold at least `capacity` bytes without allocating
+    ///
+    /// If the current chunk is too small, it gets replaced with a larger one.
+    /// This drops all the data currently in the storage, so only call it between extractions.
+    pub fn ensure_capacity(&mut self, capacity: usize) {
+        if self.bump.chunk_capacity() < capacity {
+            self.bump = bumpalo::Bump::with_capacity(capacity);
+        }
+    }
+
+    /// Drop all values from the storage
+    ///
+    /// The memory is kept for reuse, except for the largest chunk (bumpalo frees all the other
+    /// chunks when resetting).
+    pub fn reset(&mut self) {
+        self.update_high_water_mark();
+        self.bump.reset();
+    }
+
+    /// Return the number of bytes currently allocated from the system
+    ///
+    /// This includes free space in the current chunk, so it's an upper bound on the memory
+    /// used by stored values.
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+
+    /// Return the largest number of bytes allocated between two resets
+    ///
+    /// Like [`FieldStorage::allocated_bytes`], this includes the free space in the chunks.
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark.max(self.bump.allocated_bytes())
+    }
+
+    fn update_high_water_mark(&mut self) {
+        self.high_water_mark = self.high_water_mark();
+    }
+}
+
+impl Deref for FieldStorage {
+    type Target = bumpalo::Bump;
+
+    fn deref(&self) -> &Self::Target {
+        &self.bump
+    }
+}
+
+impl DerefMut for FieldStorage {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.bump
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldStorage;
+
+    #[test]
+    fn test_high_water_mark() {
+        let mut storage = FieldStorage::new();
+        assert_eq!(storage.high_water_mark(), 0);
+
+        storage.alloc_slice_fill_copy(4096, 0u8);
+        let hwm = storage.high_water_mark();
+        assert!(hwm >= 4096);
+
+        // bumpalo counts the chunk metadata after a reset, so the retained chunk
+        // may look a bit larger than before
+        storage.reset();
+        let hwm = storage.high_water_mark();
+        assert!(hwm >= 4096);
+
+        // the retained chunk is reused, so the high water mark does not grow
+        storage.alloc(1u8);
+        assert_eq!(storage.high_water_mark(), hwm);
+    }
+
+    #[test]
+    fn test_ensure_capacity() {
+        let mut storage = FieldStorage::new();
+        storage.ensure_capacity(65536);
+        assert!(storage.chunk_capacity() >= 65536);
+    }
+}
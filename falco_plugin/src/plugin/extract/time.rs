@@ -0,0 +1,154 @@
+use falco_event::fields::ToBytes;
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// # A relative time (time interval) field value
+///
+/// Extracted as a `reltime` field: a 64-bit count of nanoseconds. This is what you get
+/// when returning a plain [`Duration`], but the wrapper makes the intent explicit at
+/// the extractor signature and provides conversions from raw nanosecond counts.
+///
+/// Durations are extracted with full nanosecond resolution. Durations longer than
+/// `u64::MAX` nanoseconds (about 584 years) cannot be represented and fail the extraction
+/// instead of being silently truncated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RelTime(pub Duration);
+
+impl RelTime {
+    /// Create a relative time from a nanosecond count
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(Duration::from_nanos(nanos))
+    }
+
+    /// Get the nanosecond count, as stored in the extracted field
+    ///
+    /// Returns `None` if the duration does not fit in 64 bits.
+    pub fn as_nanos(&self) -> Option<u64> {
+        u64::try_from(self.0.as_nanos()).ok()
+    }
+}
+
+impl From<Duration> for RelTime {
+    fn from(value: Duration) -> Self {
+        Self(value)
+    }
+}
+
+impl From<RelTime> for Duration {
+    fn from(value: RelTime) -> Self {
+        value.0
+    }
+}
+
+impl ToBytes for RelTime {
+    fn binary_size(&self) -> usize {
+        self.0.binary_size()
+    }
+
+    fn write<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        self.0.write(writer)
+    }
+
+    fn default_repr() -> impl ToBytes {
+        0u64
+    }
+}
+
+/// # An absolute time (timestamp) field value
+///
+/// Extracted as an `abstime` field: a 64-bit count of nanoseconds since the Unix epoch.
+/// This is what you get when returning a plain [`SystemTime`], but the wrapper makes
+/// the intent explicit and lets you build the value directly from the nanosecond timestamps
+/// used in event metadata, avoiding a round trip through [`SystemTime`].
+///
+/// Timestamps are extracted with full nanosecond resolution. Timestamps before the Unix epoch
+/// (or too far in the future to fit in 64 bits) cannot be represented and fail
+/// the extraction instead of being silently clamped or truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AbsTime(pub SystemTime);
+
+impl AbsTime {
+    /// Create an absolute time from a nanosecond timestamp (since the Unix epoch)
+    pub fn from_unix_nanos(nanos: u64) -> Self {
+        Self(UNIX_EPOCH + Duration::from_nanos(nanos))
+    }
+
+    /// Create an absolute time from the time elapsed since the Unix epoch
+    pub fn from_since_epoch(since_epoch: Duration) -> Self {
+        Self(UNIX_EPOCH + since_epoch)
+    }
+
+    /// Get the nanosecond timestamp, as stored in the extracted field
+    ///
+    /// Returns `None` if the timestamp is before the Unix epoch or does not fit in 64 bits.
+    pub fn as_unix_nanos(&self) -> Option<u64> {
+        let since_epoch = self.0.duration_since(UNIX_EPOCH).ok()?;
+        u64::try_from(since_epoch.as_nanos()).ok()
+    }
+}
+
+impl From<SystemTime> for AbsTime {
+    fn from(value: SystemTime) -> Self {
+        Self(value)
+    }
+}
+
+impl From<AbsTime> for SystemTime {
+    fn from(value: AbsTime) -> Self {
+        value.0
+    }
+}
+
+impl ToBytes for AbsTime {
+    fn binary_size(&self) -> usize {
+        self.0.binary_size()
+    }
+
+    fn write<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        self.0.write(writer)
+    }
+
+    fn default_repr() -> impl ToBytes {
+        0u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AbsTime, RelTime};
+    use falco_event::fields::ToBytes;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_reltime() {
+        let t = RelTime::from_nanos(1_500_000_001);
+        assert_eq!(t.0, Duration::new(1, 500_000_001));
+        assert_eq!(t.as_nanos(), Some(1_500_000_001));
+
+        let mut buf = Vec::new();
+        t.write(&mut buf).unwrap();
+        assert_eq!(buf.as_slice(), 1_500_000_001u64.to_ne_bytes());
+
+        let t = RelTime(Duration::MAX);
+        assert_eq!(t.as_nanos(), None);
+        assert!(t.write(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_abstime() {
+        let t = AbsTime::from_unix_nanos(1_700_000_000_000_000_001);
+        assert_eq!(t.as_unix_nanos(), Some(1_700_000_000_000_000_001));
+        assert_eq!(
+            t,
+            AbsTime::from_since_epoch(Duration::new(1_700_000_000, 1))
+        );
+
+        let mut buf = Vec::new();
+        t.write(&mut buf).unwrap();
+        assert_eq!(buf.as_slice(), 1_700_000_000_000_000_001u64.to_ne_bytes());
+
+        let t = AbsTime(UNIX_EPOCH - Duration::from_nanos(1));
+        assert_eq!(t.as_unix_nanos(), None);
+        assert!(t.write(&mut buf).is_err());
+    }
+}
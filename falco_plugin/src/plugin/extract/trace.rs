@@ -0,0 +1,198 @@
+use crate::extract::EventInput;
+use crate::strings::from_ptr::try_cstr_from_ptr;
+use falco_plugin_api::{
+    ss_plugin_extract_field, ss_plugin_field_type_FTYPE_ABSTIME, ss_plugin_field_type_FTYPE_BOOL,
+    ss_plugin_field_type_FTYPE_RELTIME, ss_plugin_field_type_FTYPE_STRING,
+    ss_plugin_field_type_FTYPE_UINT64,
+};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// The maximum number of extraction trace messages logged per [`TRACE_WINDOW`]
+const TRACE_MESSAGES_PER_WINDOW: u32 = 100;
+const TRACE_WINDOW: Duration = Duration::from_secs(1);
+
+/// # Rate-limited logging of extraction requests
+///
+/// Tracing every extraction request in a busy Falco instance would flood the log,
+/// so at most [`TRACE_MESSAGES_PER_WINDOW`] messages are emitted every second. The number
+/// of dropped messages is reported when the next window starts.
+#[derive(Debug, Default)]
+pub(crate) struct ExtractTracer {
+    window_start: Option<Instant>,
+    logged: u32,
+    suppressed: u64,
+}
+
+impl ExtractTracer {
+    fn should_log(&mut self, now: Instant) -> bool {
+        match self.window_start {
+            Some(start) if now.duration_since(start) < TRACE_WINDOW => {}
+            _ => {
+                if self.suppressed > 0 {
+                    trace(format_args!(
+                        "{} extraction trace messages suppressed",
+                        self.suppressed
+                    ));
+                }
+                self.window_start = Some(now);
+                self.logged = 0;
+                self.suppressed = 0;
+            }
+        }
+
+        if self.logged < TRACE_MESSAGES_PER_WINDOW {
+            self.logged += 1;
+            true
+        } else {
+            self.suppressed += 1;
+            false
+        }
+    }
+
+    /// Log the outcome of a single `extract_fields` call
+    ///
+    /// # Safety
+    ///
+    /// On success, the result pointers in `fields` must have been filled by the extractors
+    pub(crate) unsafe fn log(
+        &mut self,
+        event_input: &EventInput,
+        fields: &[ss_plugin_extract_field],
+        result: &Result<(), anyhow::Error>,
+    ) {
+        let evt_num = event_input.event_number();
+        let now = Instant::now();
+        match result {
+            Ok(()) => {
+                for field in fields {
+                    if self.should_log(now) {
+                        trace(format_args!(
+                            "extract {} for event {}: {}",
+                            unsafe { describe_field(field) },
+                            evt_num,
+                            unsafe { describe_result(field) }
+                        ));
+                    }
+                }
+            }
+            Err(e) => {
+                if self.should_log(now) {
+                    let names = fields
+                        .iter()
+                        .map(|f| unsafe { describe_field(f) })
+                        .collect::<Vec<_>>();
+                    trace(format_args!(
+                        "extract [{}] for event {} failed: {:#}",
+                        names.join(", "),
+                        evt_num,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Emit a trace-level log message
+///
+/// This bypasses the global maximum log level (which is capped at `Info` in release builds),
+/// since tracing is explicitly requested by the plugin configuration. The Falco logger
+/// still applies its own severity filter.
+fn trace(args: std::fmt::Arguments) {
+    log::logger().log(
+        &log::Record::builder()
+            .level(log::Level::Trace)
+            .target(module_path!())
+            .args(args)
+            .build(),
+    );
+}
+
+unsafe fn describe_field(field: &ss_plugin_extract_field) -> String {
+    let mut out = match unsafe { try_cstr_from_ptr(field.field) } {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => format!("#{}", field.field_id),
+    };
+
+    if field.arg_present != 0 {
+        match unsafe { try_cstr_from_ptr(field.arg_key) } {
+            Some(key) => {
+                let _ = write!(out, "[{}]", key.to_string_lossy());
+            }
+            None => {
+                let _ = write!(out, "[{}]", field.arg_index);
+            }
+        }
+    }
+
+    out
+}
+
+pub(crate) unsafe fn describe_result(field: &ss_plugin_extract_field) -> String {
+    let len = field.res_len as usize;
+    if len == 0 {
+        return String::from("<no value>");
+    }
+
+    let values: Vec<String> = unsafe {
+        match field.ftype {
+            t if t == ss_plugin_field_type_FTYPE_UINT64
+                || t == ss_plugin_field_type_FTYPE_RELTIME
+                || t == ss_plugin_field_type_FTYPE_ABSTIME =>
+            {
+                std::slice::from_raw_parts(field.res.u64_, len)
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect()
+            }
+            t if t == ss_plugin_field_type_FTYPE_BOOL => {
+                std::slice::from_raw_parts(field.res.boolean, len)
+                    .iter()
+                    .map(|v| (*v != 0).to_string())
+                    .collect()
+            }
+            t if t == ss_plugin_field_type_FTYPE_STRING => {
+                std::slice::from_raw_parts(field.res.str_, len)
+                    .iter()
+                    .map(|s| match try_cstr_from_ptr(*s) {
+                        Some(s) => format!("{:?}", s),
+                        None => String::from("<null>"),
+                    })
+                    .collect()
+            }
+            _ => std::slice::from_raw_parts(field.res.buf, len)
+                .iter()
+                .map(|b| format!("<{} bytes>", b.len))
+                .collect(),
+        }
+    };
+
+    match values.as_slice() {
+        [value] if field.flist == 0 => value.clone(),
+        _ => format!("[{}]", values.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExtractTracer, TRACE_MESSAGES_PER_WINDOW, TRACE_WINDOW};
+    use std::time::Instant;
+
+    #[test]
+    fn test_rate_limit() {
+        let mut tracer = ExtractTracer::default();
+        let now = Instant::now();
+
+        for _ in 0..TRACE_MESSAGES_PER_WINDOW {
+            assert!(tracer.should_log(now));
+        }
+        assert!(!tracer.should_log(now));
+        assert!(!tracer.should_log(now));
+        assert_eq!(tracer.suppressed, 2);
+
+        assert!(tracer.should_log(now + TRACE_WINDOW));
+        assert_eq!(tracer.suppressed, 0);
+        assert_eq!(tracer.logged, 1);
+    }
+}
@@ -1,11 +1,19 @@
 use crate::plugin::base::PluginWrapper;
+use crate::plugin::convert;
 use crate::plugin::error::ffi_result::FfiResult;
+use crate::plugin::error::strict;
 use crate::plugin::event::EventInput;
 use crate::plugin::extract::ExtractPlugin;
+#[cfg(feature = "record")]
+use crate::plugin::record;
+use crate::plugin::spans;
 use crate::tables::TableReader;
+use falco_event::events::types::EventType;
 use falco_plugin_api::plugin_api__bindgen_ty_2 as extract_plugin_api;
 use falco_plugin_api::ss_plugin_rc;
-use falco_plugin_api::{ss_plugin_event_input, ss_plugin_rc_SS_PLUGIN_FAILURE};
+use falco_plugin_api::{
+    ss_plugin_event_input, ss_plugin_rc_SS_PLUGIN_FAILURE, ss_plugin_rc_SS_PLUGIN_SUCCESS,
+};
 use falco_plugin_api::{ss_plugin_field_extract_input, ss_plugin_t};
 use std::any::TypeId;
 use std::collections::BTreeMap;
@@ -19,6 +27,9 @@ pub trait ExtractPluginFallbackApi {
         get_fields: None,
         extract_fields: None,
     };
+
+    /// The event types declared by the plugin, if it implements this capability
+    const DECLARED_EVENT_TYPES: Option<&'static [EventType]> = None;
 }
 impl<T> ExtractPluginFallbackApi for T {}
 
@@ -32,6 +43,8 @@ impl<T: ExtractPlugin> ExtractPluginApi<T> {
         get_fields: Some(plugin_get_fields::<T>),
         extract_fields: Some(plugin_extract_fields::<T>),
     };
+
+    pub const DECLARED_EVENT_TYPES: Option<&'static [EventType]> = Some(T::EVENT_TYPES);
 }
 
 pub extern "C-unwind" fn plugin_get_fields<T: ExtractPlugin>() -> *const c_char {
@@ -43,10 +56,19 @@ pub extern "C-unwind" fn plugin_get_fields<T: ExtractPlugin>() -> *const c_char
 /// All pointers must be valid
 pub unsafe extern "C-unwind" fn plugin_get_extract_event_types<T: ExtractPlugin>(
     numtypes: *mut u32,
-    _plugin: *mut ss_plugin_t,
+    plugin: *mut ss_plugin_t,
 ) -> *mut u16 {
+    let plugin = plugin as *mut PluginWrapper<T>;
+    if let Some(plugin) = unsafe { plugin.as_mut() } {
+        if let Some(types) = plugin.event_scope.event_types(T::EVENT_TYPES) {
+            plugin.extract_event_types = types;
+            unsafe { *numtypes = convert::saturating(plugin.extract_event_types.len()) };
+            return plugin.extract_event_types.as_mut_ptr();
+        }
+    }
+
     let types = T::EVENT_TYPES;
-    unsafe { *numtypes = types.len() as u32 };
+    unsafe { *numtypes = convert::saturating(types.len()) };
     types.as_ptr() as *const u16 as *mut u16 // TODO(spec): this should ****really**** be const
 }
 
@@ -78,43 +100,78 @@ pub unsafe extern "C-unwind" fn plugin_extract_fields<T: ExtractPlugin>(
     let plugin = plugin as *mut PluginWrapper<T>;
     unsafe {
         let Some(plugin) = plugin.as_mut() else {
+            strict::unexpected_input("plugin_extract_fields", "NULL plugin");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+            strict::unexpected_input("plugin_extract_fields", "plugin failed to initialize");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
         let Some(event_input) = event_input.as_ref() else {
+            strict::unexpected_input("plugin_extract_fields", "NULL event_input");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let event_input = EventInput(*event_input);
 
         let Some(extract_input) = extract_input.as_ref() else {
+            strict::unexpected_input("plugin_extract_fields", "NULL extract_input");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
         let fields =
             std::slice::from_raw_parts_mut(extract_input.fields, extract_input.num_fields as usize);
 
+        if !plugin.event_scope.allows(&event_input) {
+            // narrowed down in the config: no values for any field
+            for field in fields.iter_mut() {
+                field.res_len = 0;
+            }
+            return ss_plugin_rc_SS_PLUGIN_SUCCESS;
+        }
+
         let Some(reader_ext) = extract_input.table_reader_ext.as_ref() else {
+            strict::unexpected_input("plugin_extract_fields", "NULL table_reader_ext");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
+        #[cfg(feature = "record")]
+        let reader_ext = &record::reader_ext(plugin.recording.as_ref(), reader_ext);
+
         let Ok(table_reader) = TableReader::try_from(reader_ext, actual_plugin.last_error.clone())
         else {
+            strict::unexpected_input("plugin_extract_fields", "invalid table_reader");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
+        let _span = spans::extract_fields(T::NAME, &event_input, fields.len());
         plugin.field_storage.reset();
-        actual_plugin
-            .plugin
-            .extract_fields(
-                &event_input,
-                &table_reader,
-                fields,
-                &mut plugin.field_storage,
-            )
-            .rc(&mut plugin.error_buf)
+        plugin
+            .field_storage
+            .ensure_capacity(T::FIELD_STORAGE_CHUNK_SIZE);
+        let limit = actual_plugin.plugin.field_storage_limit();
+        plugin.field_storage.set_allocation_limit(limit);
+        #[cfg(feature = "record")]
+        if let Some(recording) = &plugin.recording {
+            recording.extract_begin(&event_input, fields);
+        }
+        let result = actual_plugin.plugin.extract_fields_with_storage(
+            &event_input,
+            &table_reader,
+            &mut *fields,
+            &mut plugin.field_storage,
+        );
+        plugin
+            .field_storage_stats
+            .record(plugin.field_storage.allocated_bytes());
+        #[cfg(feature = "record")]
+        if let Some(recording) = &plugin.recording {
+            recording.extract_end(fields, &result);
+        }
+        if actual_plugin.plugin.trace_extractions() {
+            plugin.extract_tracer.log(&event_input, fields, &result);
+        }
+        result.rc(&mut plugin.error_buf)
     }
 }
 
@@ -7,8 +7,10 @@ use falco_plugin_api::{
 use std::ops::ControlFlow;
 use thiserror::Error;
 
+/// # An error setting up access to the thread pool
 #[derive(Error, Debug)]
 pub enum ThreadPoolError {
+    /// The plugin framework did not provide a required thread pool operation
     #[error("Missing entry {0} in thread pool operations vtable")]
     BadVtable(&'static str),
 }
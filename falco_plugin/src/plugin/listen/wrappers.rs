@@ -1,6 +1,7 @@
 use crate::listen::CaptureListenPlugin;
 use crate::plugin::base::PluginWrapper;
 use crate::plugin::error::ffi_result::FfiResult;
+use crate::plugin::error::strict;
 use crate::plugin::listen::CaptureListenInput;
 use falco_plugin_api::{
     plugin_api__bindgen_ty_5 as listen_plugin_api, ss_plugin_capture_listen_input, ss_plugin_rc,
@@ -30,16 +31,19 @@ pub unsafe extern "C-unwind" fn plugin_capture_open<T: CaptureListenPlugin>(
     listen_input: *const ss_plugin_capture_listen_input,
 ) -> ss_plugin_rc {
     let Some(plugin) = (plugin as *mut PluginWrapper<T>).as_mut() else {
+        strict::unexpected_input("plugin_capture_open", "NULL plugin");
         return ss_plugin_rc_SS_PLUGIN_FAILURE;
     };
 
     let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+        strict::unexpected_input("plugin_capture_open", "plugin failed to initialize");
         return ss_plugin_rc_SS_PLUGIN_FAILURE;
     };
 
     let Ok(listen_input) =
         CaptureListenInput::try_from(listen_input, actual_plugin.last_error.clone())
     else {
+        strict::unexpected_input("plugin_capture_open", "invalid listen_input");
         return ss_plugin_rc_SS_PLUGIN_FAILURE;
     };
 
@@ -56,16 +60,19 @@ pub unsafe extern "C-unwind" fn plugin_capture_close<T: CaptureListenPlugin>(
     listen_input: *const ss_plugin_capture_listen_input,
 ) -> ss_plugin_rc {
     let Some(plugin) = (plugin as *mut PluginWrapper<T>).as_mut() else {
+        strict::unexpected_input("plugin_capture_close", "NULL plugin");
         return ss_plugin_rc_SS_PLUGIN_FAILURE;
     };
 
     let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+        strict::unexpected_input("plugin_capture_close", "plugin failed to initialize");
         return ss_plugin_rc_SS_PLUGIN_FAILURE;
     };
 
     let Ok(listen_input) =
         CaptureListenInput::try_from(listen_input, actual_plugin.last_error.clone())
     else {
+        strict::unexpected_input("plugin_capture_close", "invalid listen_input");
         return ss_plugin_rc_SS_PLUGIN_FAILURE;
     };
 
@@ -1,12 +1,19 @@
 pub mod async_event;
 pub mod base;
+pub(crate) mod convert;
+pub mod docs;
 pub mod error;
 pub(crate) mod event;
 pub mod exported_tables;
 pub mod extract;
 pub mod listen;
 pub mod parse;
+#[cfg(feature = "record")]
+pub(crate) mod record;
 pub mod schema;
 pub mod source;
+pub(crate) mod spans;
 pub mod tables;
+#[cfg(feature = "testing")]
+pub mod testing;
 // TODO(sdk) review all pub
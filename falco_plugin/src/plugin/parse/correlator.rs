@@ -0,0 +1,179 @@
+use crate::plugin::base::metrics::{Metric, MetricLabel, MetricValue};
+use crate::plugin::convert;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+/// # A helper to correlate pairs of events
+///
+/// Many protocols consist of pairs of events (e.g. a request and a response) that share
+/// some identifier. This type stores the data from the first event of the pair (keyed
+/// by the identifier) until the matching event arrives or the entry times out.
+///
+/// Time is driven by event timestamps (in nanoseconds since the epoch, as found in
+/// [`EventMetadata::ts`](`crate::event::events::EventMetadata::ts`)), not by the wall clock,
+/// so replaying a capture file yields the same results as processing live events.
+///
+/// ```
+/// use std::time::Duration;
+/// use falco_plugin::parse::Correlator;
+///
+/// let mut requests = Correlator::<u64, &str>::new(Duration::from_secs(1));
+///
+/// requests.insert(1, 1_000_000_000, "GET /");
+/// requests.insert(2, 1_100_000_000, "GET /favicon.ico");
+///
+/// // a response for request 1 arrives
+/// assert_eq!(requests.take(&1), Some("GET /"));
+///
+/// // request 2 never gets a response
+/// let mut expired = Vec::new();
+/// requests.expire(3_000_000_000, |key, value| expired.push((key, value)));
+/// assert_eq!(expired, vec![(2, "GET /favicon.ico")]);
+/// ```
+#[derive(Debug)]
+pub struct Correlator<K, V> {
+    timeout: u64,
+    pending: BTreeMap<K, (u64, V)>,
+    deadlines: BTreeSet<(u64, K)>,
+    matched: u64,
+    expired: u64,
+}
+
+impl<K: Ord + Clone, V> Correlator<K, V> {
+    /// Create a new correlator, expiring entries older than `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout: timeout.as_nanos().try_into().unwrap_or(u64::MAX),
+            pending: BTreeMap::new(),
+            deadlines: BTreeSet::new(),
+            matched: 0,
+            expired: 0,
+        }
+    }
+
+    /// Store `value` under `key`, waiting for a matching event
+    ///
+    /// `ts` is the timestamp of the event, in nanoseconds since the epoch.
+    ///
+    /// If there was already a pending entry for this key, it gets replaced and the old
+    /// value is returned.
+    pub fn insert(&mut self, key: K, ts: u64, value: V) -> Option<V> {
+        let deadline = ts.saturating_add(self.timeout);
+        self.deadlines.insert((deadline, key.clone()));
+        let (old_deadline, old_value) = self.pending.insert(key.clone(), (deadline, value))?;
+        if old_deadline != deadline {
+            self.deadlines.remove(&(old_deadline, key));
+        }
+        Some(old_value)
+    }
+
+    /// Remove and return the pending entry for `key`, if any
+    ///
+    /// Call this when the second event of the pair arrives.
+    pub fn take(&mut self, key: &K) -> Option<V> {
+        let (deadline, value) = self.pending.remove(key)?;
+        self.deadlines.remove(&(deadline, key.clone()));
+        self.matched += 1;
+        Some(value)
+    }
+
+    /// Look up the pending entry for `key` without removing it
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.pending.get(key).map(|(_, value)| value)
+    }
+
+    /// Expire all the entries that timed out at `now`
+    ///
+    /// `now` is the timestamp of the current event, in nanoseconds since the epoch.
+    /// The `on_expire` closure is called for every expired entry, in the order of their
+    /// deadlines. It can e.g. log the unmatched entry or emit an async event describing it.
+    pub fn expire<F>(&mut self, now: u64, mut on_expire: F)
+    where
+        F: FnMut(K, V),
+    {
+        while let Some((deadline, _)) = self.deadlines.first() {
+            if *deadline > now {
+                break;
+            }
+            let Some((_, key)) = self.deadlines.pop_first() else {
+                break;
+            };
+            if let Some((_, value)) = self.pending.remove(&key) {
+                self.expired += 1;
+                on_expire(key, value);
+            }
+        }
+    }
+
+    /// Return the number of entries waiting for a match
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Return the number of entries that have been matched so far
+    pub fn matched(&self) -> u64 {
+        self.matched
+    }
+
+    /// Return the number of entries that have expired without a match so far
+    pub fn expired(&self) -> u64 {
+        self.expired
+    }
+
+    /// Describe the state of the correlator as metrics
+    ///
+    /// The returned metrics contain the number of pending (unmatched) entries
+    /// and the number of expired entries, respectively.
+    pub fn metrics(&self, pending: &MetricLabel, expired: &MetricLabel) -> [Metric; 2] {
+        [
+            pending.with_value(MetricValue::U64(convert::saturating(self.pending.len()))),
+            expired.with_value(MetricValue::U64(self.expired)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Correlator;
+    use std::time::Duration;
+
+    #[test]
+    fn test_match() {
+        let mut c = Correlator::new(Duration::from_nanos(100));
+        assert_eq!(c.insert(1u32, 10, "one"), None);
+        assert_eq!(c.get(&1), Some(&"one"));
+        assert_eq!(c.take(&1), Some("one"));
+        assert_eq!(c.take(&1), None);
+        assert_eq!(c.pending(), 0);
+        assert_eq!(c.matched(), 1);
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut c = Correlator::new(Duration::from_nanos(100));
+        assert_eq!(c.insert(1u32, 10, "one"), None);
+        assert_eq!(c.insert(1u32, 50, "uno"), Some("one"));
+
+        let mut expired = Vec::new();
+        c.expire(120, |k, v| expired.push((k, v)));
+        assert!(expired.is_empty());
+
+        c.expire(150, |k, v| expired.push((k, v)));
+        assert_eq!(expired, vec![(1, "uno")]);
+    }
+
+    #[test]
+    fn test_expire_in_order() {
+        let mut c = Correlator::new(Duration::from_nanos(100));
+        c.insert(3u32, 30, "three");
+        c.insert(1u32, 10, "one");
+        c.insert(2u32, 20, "two");
+        c.take(&2);
+
+        let mut expired = Vec::new();
+        c.expire(1000, |k, v| expired.push((k, v)));
+        assert_eq!(expired, vec![(1, "one"), (3, "three")]);
+        assert_eq!(c.expired(), 2);
+        assert_eq!(c.pending(), 0);
+    }
+}
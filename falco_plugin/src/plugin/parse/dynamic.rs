@@ -0,0 +1,45 @@
+use crate::parse::{EventInput, ParseInput};
+use crate::plugin::parse::ParsePlugin;
+use falco_event::events::types::EventType;
+use std::ffi::CStr;
+
+/// # An object-safe facade over [`ParsePlugin`]
+///
+/// [`ParsePlugin`] describes the plugin with associated constants, so it cannot be used
+/// as a trait object. `DynParse` exposes the same functionality through methods only
+/// and is implemented for every parse plugin, so frameworks composing several Rust plugins
+/// can keep them in a single collection, e.g. `Vec<Box<dyn DynParse>>`, and dispatch
+/// dynamically.
+///
+/// See [`DynExtract`](`crate::extract::DynExtract`) for an example.
+pub trait DynParse {
+    /// The plugin name, see [`Plugin::NAME`]
+    fn name(&self) -> &'static CStr;
+
+    /// The supported event types, see [`ParsePlugin::EVENT_TYPES`]
+    fn event_types(&self) -> &'static [EventType];
+
+    /// The supported event sources, see [`ParsePlugin::EVENT_SOURCES`]
+    fn event_sources(&self) -> &'static [&'static str];
+
+    /// Parse an event, see [`ParsePlugin::parse_event`]
+    fn parse_event(&mut self, event: &EventInput, parse_input: &ParseInput) -> anyhow::Result<()>;
+}
+
+impl<T: ParsePlugin> DynParse for T {
+    fn name(&self) -> &'static CStr {
+        T::NAME
+    }
+
+    fn event_types(&self) -> &'static [EventType] {
+        T::EVENT_TYPES
+    }
+
+    fn event_sources(&self) -> &'static [&'static str] {
+        T::EVENT_SOURCES
+    }
+
+    fn parse_event(&mut self, event: &EventInput, parse_input: &ParseInput) -> anyhow::Result<()> {
+        ParsePlugin::parse_event(self, event, parse_input)
+    }
+}
@@ -5,6 +5,8 @@ use crate::plugin::tables::vtable::{TableReader, TableWriter};
 use falco_event::events::types::EventType;
 use falco_plugin_api::ss_plugin_event_parse_input;
 
+pub mod correlator;
+pub mod dynamic;
 #[doc(hidden)]
 pub mod wrappers;
 
@@ -32,6 +34,12 @@ pub trait ParsePlugin: Plugin {
     /// **Note**: some notable event types are:
     /// - [`EventType::ASYNCEVENT_E`], generated from async plugins
     /// - [`EventType::PLUGINEVENT_E`], generated from source plugins
+    ///
+    /// **Note**: operators can narrow this list (but not extend it) at runtime by adding
+    /// a `sdk_event_types` key (a list of event type numbers or names, like `"ASYNCEVENT_E"`)
+    /// to the plugin config, if it's a JSON object. The SDK removes the key before passing
+    /// the config to the plugin. A list that does not include any of the declared event types
+    /// is rejected when loading the config.
     const EVENT_TYPES: &'static [EventType];
 
     /// # Supported event sources
@@ -43,6 +51,10 @@ pub trait ParsePlugin: Plugin {
     /// receive events from all event sources.
     ///
     /// **Note**: one notable event source is called `syscall`
+    ///
+    /// **Note**: operators can narrow this list (but not extend it) at runtime by adding
+    /// a `sdk_event_sources` key (a list of event source names) to the plugin config,
+    /// if it's a JSON object. The SDK removes the key before passing the config to the plugin.
     const EVENT_SOURCES: &'static [&'static str];
 
     /// # Parse an event
@@ -51,6 +63,12 @@ pub trait ParsePlugin: Plugin {
     /// The plugin is guaranteed to receive an event at most once, after any
     /// operation related the event sourcing capability, and before
     /// any operation related to the field extraction capability.
+    ///
+    /// The event number assigned by the framework (useful e.g. for ordering or deduplication)
+    /// is available as [`EventInput::event_number`]. The plugin API does not tell the plugin
+    /// whether the events come from a live capture or a capture file, nor when the capture
+    /// started, so if you need that information, derive it from the events themselves
+    /// (e.g. remember the timestamp of the first event seen).
     fn parse_event(&mut self, event: &EventInput, parse_input: &ParseInput) -> anyhow::Result<()>;
 }
 
@@ -1,11 +1,15 @@
 use crate::parse::EventInput;
 use crate::plugin::base::PluginWrapper;
+use crate::plugin::convert;
 use crate::plugin::error::ffi_result::FfiResult;
+use crate::plugin::error::strict;
 use crate::plugin::parse::{ParseInput, ParsePlugin};
+use crate::plugin::spans;
+use falco_event::events::types::EventType;
 use falco_plugin_api::plugin_api__bindgen_ty_3 as parse_plugin_api;
 use falco_plugin_api::{
     ss_plugin_event_input, ss_plugin_event_parse_input, ss_plugin_rc,
-    ss_plugin_rc_SS_PLUGIN_FAILURE, ss_plugin_t,
+    ss_plugin_rc_SS_PLUGIN_FAILURE, ss_plugin_rc_SS_PLUGIN_SUCCESS, ss_plugin_t,
 };
 use std::any::TypeId;
 use std::collections::BTreeMap;
@@ -18,6 +22,9 @@ pub trait ParsePluginFallbackApi {
         get_parse_event_sources: None,
         parse_event: None,
     };
+
+    /// The event types declared by the plugin, if it implements this capability
+    const DECLARED_EVENT_TYPES: Option<&'static [EventType]> = None;
 }
 impl<T> ParsePluginFallbackApi for T {}
 
@@ -30,6 +37,8 @@ impl<T: ParsePlugin + 'static> ParsePluginApi<T> {
         get_parse_event_sources: Some(plugin_get_parse_event_sources::<T>),
         parse_event: Some(plugin_parse_event::<T>),
     };
+
+    pub const DECLARED_EVENT_TYPES: Option<&'static [EventType]> = Some(T::EVENT_TYPES);
 }
 
 /// # Safety
@@ -37,11 +46,20 @@ impl<T: ParsePlugin + 'static> ParsePluginApi<T> {
 /// All pointers must be valid
 pub unsafe extern "C-unwind" fn plugin_get_parse_event_types<T: ParsePlugin>(
     numtypes: *mut u32,
-    _plugin: *mut ss_plugin_t,
+    plugin: *mut ss_plugin_t,
 ) -> *mut u16 {
+    let plugin = plugin as *mut PluginWrapper<T>;
+    if let (Some(numtypes), Some(plugin)) = (numtypes.as_mut(), plugin.as_mut()) {
+        if let Some(types) = plugin.event_scope.event_types(T::EVENT_TYPES) {
+            plugin.parse_event_types = types;
+            *numtypes = convert::saturating(plugin.parse_event_types.len());
+            return plugin.parse_event_types.as_mut_ptr();
+        }
+    }
+
     let types = T::EVENT_TYPES;
     if let Some(numtypes) = numtypes.as_mut() {
-        *numtypes = types.len() as u32;
+        *numtypes = convert::saturating(types.len());
         types.as_ptr() as *const u16 as *mut u16 // this should ****really**** be const
     } else {
         std::ptr::null_mut()
@@ -78,26 +96,51 @@ pub unsafe extern "C-unwind" fn plugin_parse_event<T: ParsePlugin>(
     let plugin = plugin as *mut PluginWrapper<T>;
     unsafe {
         let Some(plugin) = plugin.as_mut() else {
+            strict::unexpected_input("plugin_parse_event", "NULL plugin");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+            strict::unexpected_input("plugin_parse_event", "plugin failed to initialize");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
         let Some(event) = event.as_ref() else {
+            strict::unexpected_input("plugin_parse_event", "NULL event");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let event = EventInput(*event);
+        if !plugin.event_scope.allows(&event) {
+            // narrowed down in the config
+            return ss_plugin_rc_SS_PLUGIN_SUCCESS;
+        }
+
+        #[cfg(feature = "record")]
+        let recorded_input = plugin
+            .recording
+            .as_ref()
+            .and_then(|r| r.parse_input(parse_input));
+        #[cfg(feature = "record")]
+        let parse_input = recorded_input
+            .as_deref()
+            .map_or(parse_input, |i| i.as_ptr());
 
         let Ok(parse_input) = ParseInput::try_from(parse_input, actual_plugin.last_error.clone())
         else {
+            strict::unexpected_input("plugin_parse_event", "invalid parse_input");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
-        actual_plugin
-            .plugin
-            .parse_event(&event, &parse_input)
-            .rc(&mut plugin.error_buf)
+        let _span = spans::parse_event(T::NAME, &event);
+        #[cfg(feature = "record")]
+        if let Some(recording) = &plugin.recording {
+            recording.parse_begin(&event);
+        }
+        let result = actual_plugin.plugin.parse_event(&event, &parse_input);
+        #[cfg(feature = "record")]
+        if let Some(recording) = &plugin.recording {
+            recording.parse_end(&result);
+        }
+        result.rc(&mut plugin.error_buf)
     }
 }
 
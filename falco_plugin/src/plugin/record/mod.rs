@@ -0,0 +1,136 @@
+use crate::plugin::event::EventInput;
+use crate::plugin::exported_tables::snapshot::state_data_to_json;
+use crate::plugin::record::recorder::{InterposedInit, InterposedParse};
+use crate::plugin::tables::data::FieldTypeId;
+use crate::strings::from_ptr::try_cstr_from_ptr;
+use falco_plugin_api::{
+    ss_plugin_event_parse_input, ss_plugin_extract_field, ss_plugin_init_input,
+    ss_plugin_state_data, ss_plugin_table_reader_vtable_ext,
+};
+use num_traits::FromPrimitive;
+use std::borrow::Cow;
+use std::ffi::{c_char, CStr};
+
+pub(crate) mod recorder;
+pub mod replay;
+pub mod tape;
+
+/// The config key enabling the recording
+pub(crate) const RECORD_KEY: &str = "sdk_record";
+
+/// # An active recording of host vtable interactions
+///
+/// Setting `sdk_record` to a file path in a JSON object config starts recording all calls
+/// the plugin makes to the host table vtables (along with the values returned), as well as
+/// all extraction and parsing requests, to that file. The key is removed from the config
+/// before it's passed to the plugin.
+///
+/// The recording stops when this object (owned by the plugin instance) is dropped.
+///
+/// The recorder state is global, as the interposed vtable functions get no context to find it
+/// from. A `Recording` is only created when it was the one to start the recorder, so dropping it
+/// always stops its own recording, and a plugin instance asking for a recording while another one
+/// is active fails to initialize.
+#[derive(Debug)]
+pub(crate) struct Recording(());
+
+impl Recording {
+    /// Start recording if requested in the config
+    ///
+    /// Returns the recording (if any) and the config with the SDK key removed. Configs that
+    /// are not JSON objects are returned unchanged.
+    pub(crate) fn from_config<'a>(
+        plugin: &CStr,
+        config: &'a str,
+    ) -> Result<(Option<Self>, Cow<'a, str>), anyhow::Error> {
+        let Ok(serde_json::Value::Object(mut object)) = serde_json::from_str(config) else {
+            return Ok((None, Cow::Borrowed(config)));
+        };
+
+        let Some(path) = object.remove(RECORD_KEY) else {
+            return Ok((None, Cow::Borrowed(config)));
+        };
+
+        let path = path
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid {}: expected a file path", RECORD_KEY))?;
+        let config = serde_json::to_string(&object)?;
+        recorder::start(path, plugin, &config)?;
+        Ok((Some(Self(())), Cow::Owned(config)))
+    }
+
+    /// Interpose on the table vtables passed to `plugin_init`
+    pub(crate) fn init_input(&self, host: &ss_plugin_init_input) -> Option<Box<InterposedInit>> {
+        InterposedInit::new(host)
+    }
+
+    /// Interpose on the table vtables passed to `plugin_parse_event`
+    pub(crate) fn parse_input(
+        &self,
+        host: *const ss_plugin_event_parse_input,
+    ) -> Option<Box<InterposedParse>> {
+        InterposedParse::new(host)
+    }
+
+    /// # Safety
+    /// All pointers in `fields` must be valid
+    pub(crate) unsafe fn extract_begin(
+        &self,
+        event: &EventInput,
+        fields: &[ss_plugin_extract_field],
+    ) {
+        unsafe { recorder::extract_begin(event, fields) }
+    }
+
+    /// # Safety
+    /// On success, the result pointers in `fields` must have been filled by the extractors
+    pub(crate) unsafe fn extract_end(
+        &self,
+        fields: &[ss_plugin_extract_field],
+        result: &Result<(), anyhow::Error>,
+    ) {
+        unsafe { recorder::extract_end(fields, result) }
+    }
+
+    pub(crate) fn parse_begin(&self, event: &EventInput) {
+        recorder::parse_begin(event)
+    }
+
+    pub(crate) fn parse_end(&self, result: &Result<(), anyhow::Error>) {
+        recorder::parse_end(result)
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        recorder::stop()
+    }
+}
+
+/// Interpose on the table reader vtable passed to `plugin_extract_fields`, if recording
+pub(crate) fn reader_ext(
+    recording: Option<&Recording>,
+    host: &ss_plugin_table_reader_vtable_ext,
+) -> ss_plugin_table_reader_vtable_ext {
+    match recording {
+        Some(_) => recorder::reader_ext(host),
+        None => *host,
+    }
+}
+
+/// Convert a raw value of type `type_id` to JSON
+///
+/// # Safety
+/// `data` must be null or point to a valid value of type `type_id`
+unsafe fn data_to_json(data: *const ss_plugin_state_data, type_id: u32) -> serde_json::Value {
+    match (unsafe { data.as_ref() }, FieldTypeId::from_u32(type_id)) {
+        (Some(data), Some(type_id)) => unsafe { state_data_to_json(data, type_id) },
+        _ => serde_json::Value::Null,
+    }
+}
+
+/// # Safety
+/// `s` must be null or point to a valid C string
+unsafe fn string(s: *const c_char) -> Option<String> {
+    unsafe { try_cstr_from_ptr(s) }.map(|s| s.to_string_lossy().into_owned())
+}
@@ -0,0 +1,866 @@
+use crate::plugin::event::EventInput;
+use crate::plugin::extract::trace::describe_result;
+use crate::plugin::record::tape::{
+    to_hex, ExtractArg, ExtractField, FieldInfo, HandleId, HostCall, HostReturn, TableInfo,
+    TapeRecord,
+};
+use crate::plugin::record::{data_to_json, string};
+use crate::plugin::tables::data::FieldTypeId;
+use anyhow::Context;
+use falco_plugin_api::{
+    ss_plugin_bool, ss_plugin_event_parse_input, ss_plugin_extract_field, ss_plugin_init_input,
+    ss_plugin_init_tables_input, ss_plugin_owner_t, ss_plugin_rc, ss_plugin_rc_SS_PLUGIN_FAILURE,
+    ss_plugin_rc_SS_PLUGIN_SUCCESS, ss_plugin_state_data, ss_plugin_state_type,
+    ss_plugin_table_entry_t, ss_plugin_table_field_t, ss_plugin_table_fieldinfo,
+    ss_plugin_table_fields_vtable_ext, ss_plugin_table_info, ss_plugin_table_input,
+    ss_plugin_table_iterator_func_t, ss_plugin_table_iterator_state_t,
+    ss_plugin_table_reader_vtable_ext, ss_plugin_table_t, ss_plugin_table_writer_vtable_ext,
+};
+use num_traits::FromPrimitive;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ptr::{null, null_mut};
+use std::sync::{Mutex, PoisonError};
+
+type ListTablesFn =
+    unsafe extern "C-unwind" fn(*mut ss_plugin_owner_t, *mut u32) -> *mut ss_plugin_table_info;
+type GetTableFn = unsafe extern "C-unwind" fn(
+    *mut ss_plugin_owner_t,
+    *const c_char,
+    ss_plugin_state_type,
+) -> *mut ss_plugin_table_t;
+type AddTableFn = unsafe extern "C-unwind" fn(
+    *mut ss_plugin_owner_t,
+    *const ss_plugin_table_input,
+) -> ss_plugin_rc;
+
+/// The host functions of the tables input
+///
+/// We can't keep a copy of the whole `ss_plugin_init_tables_input`, since it contains
+/// raw pointers, which would make the recorder `!Send`
+#[derive(Default)]
+struct HostTables {
+    list_tables: Option<ListTablesFn>,
+    get_table: Option<GetTableFn>,
+    add_table: Option<AddTableFn>,
+}
+
+/// The state of the active recording
+///
+/// The interposed vtable functions are plain `extern "C"` functions without any context,
+/// so there can be only one recording in a process at a time.
+struct Recorder {
+    out: BufWriter<File>,
+    last_handle: HandleId,
+    tables: HashMap<usize, (HandleId, u32)>,
+    fields: HashMap<usize, (HandleId, Option<FieldTypeId>)>,
+    entries: HashMap<usize, HandleId>,
+
+    host_tables: HostTables,
+    host_reader: Option<ss_plugin_table_reader_vtable_ext>,
+    host_writer: Option<ss_plugin_table_writer_vtable_ext>,
+    host_fields: Option<ss_plugin_table_fields_vtable_ext>,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+impl Recorder {
+    fn emit(&mut self, record: &TapeRecord) {
+        let res = serde_json::to_writer(&mut self.out, record)
+            .map_err(std::io::Error::from)
+            .and_then(|()| self.out.write_all(b"\n"));
+        if let Err(e) = res {
+            log::error!("Failed to write recording: {}", e);
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(e) = self.out.flush() {
+            log::error!("Failed to write recording: {}", e);
+        }
+    }
+
+    fn new_handle(&mut self) -> HandleId {
+        self.last_handle += 1;
+        self.last_handle
+    }
+
+    fn add_table(&mut self, t: *mut ss_plugin_table_t, key_type: u32) -> (HandleId, u32) {
+        if t.is_null() {
+            return (0, 0);
+        }
+
+        let handle = self.new_handle();
+        self.tables.insert(t as usize, (handle, key_type));
+        (handle, key_type)
+    }
+
+    fn table_with_key_type(&mut self, t: *mut ss_plugin_table_t) -> (HandleId, u32) {
+        if t.is_null() {
+            return (0, 0);
+        }
+
+        if let Some(table) = self.tables.get(&(t as usize)) {
+            return *table;
+        }
+
+        // the SDK makes the same assumption when checking the key type of imported tables
+        let key_type = unsafe { (*(t as *const ss_plugin_table_input)).key_type };
+        self.add_table(t, key_type)
+    }
+
+    fn table(&mut self, t: *mut ss_plugin_table_t) -> HandleId {
+        self.table_with_key_type(t).0
+    }
+
+    fn add_field(&mut self, f: *const ss_plugin_table_field_t, data_type: u32) -> HandleId {
+        if f.is_null() {
+            return 0;
+        }
+
+        let handle = self.new_handle();
+        self.fields
+            .insert(f as usize, (handle, FieldTypeId::from_u32(data_type)));
+        handle
+    }
+
+    fn field(&mut self, f: *const ss_plugin_table_field_t) -> HandleId {
+        if f.is_null() {
+            return 0;
+        }
+
+        match self.fields.get(&(f as usize)) {
+            Some((handle, _)) => *handle,
+            None => {
+                let handle = self.new_handle();
+                self.fields.insert(f as usize, (handle, None));
+                handle
+            }
+        }
+    }
+
+    /// Assign a new handle to an entry pointer returned by the host
+    ///
+    /// Entry pointers may be reused by the host after the entry is released,
+    /// so every entry gets a new handle when it's handed out.
+    fn new_entry(&mut self, e: *mut ss_plugin_table_entry_t) -> HandleId {
+        if e.is_null() {
+            return 0;
+        }
+
+        let handle = self.new_handle();
+        self.entries.insert(e as usize, handle);
+        handle
+    }
+
+    fn entry(&mut self, e: *mut ss_plugin_table_entry_t) -> HandleId {
+        match self.entries.get(&(e as usize)) {
+            Some(handle) => *handle,
+            None => self.new_entry(e),
+        }
+    }
+
+    unsafe fn key(
+        &mut self,
+        t: *mut ss_plugin_table_t,
+        key: *const ss_plugin_state_data,
+    ) -> serde_json::Value {
+        let (_, key_type) = self.table_with_key_type(t);
+        unsafe { data_to_json(key, key_type) }
+    }
+
+    unsafe fn value(
+        &mut self,
+        f: *const ss_plugin_table_field_t,
+        data: *const ss_plugin_state_data,
+    ) -> serde_json::Value {
+        let Some(data) = (unsafe { data.as_ref() }) else {
+            return serde_json::Value::Null;
+        };
+
+        match self.fields.get(&(f as usize)).and_then(|(_, ty)| *ty) {
+            Some(FieldTypeId::Table) => {
+                let (table, key_type) = self.table_with_key_type(unsafe { data.table });
+                serde_json::json!({"table": table, "key_type": key_type})
+            }
+            Some(ty) => unsafe { data_to_json(data, ty as u32) },
+            None => serde_json::Value::Null,
+        }
+    }
+}
+
+fn with_recorder<T>(f: impl FnOnce(&mut Recorder) -> T) -> Option<T> {
+    let mut recorder = RECORDER.lock().unwrap_or_else(PoisonError::into_inner);
+    recorder.as_mut().map(f)
+}
+
+fn record(f: impl FnOnce(&mut Recorder) -> TapeRecord) {
+    with_recorder(|r| {
+        let record = f(r);
+        r.emit(&record);
+    });
+}
+
+fn record_call(f: impl FnOnce(&mut Recorder) -> (HostCall, HostReturn)) {
+    record(|r| {
+        let (call, ret) = f(r);
+        TapeRecord::Call { call, ret }
+    })
+}
+
+/// Start recording to a file at `path`
+pub(super) fn start(path: &str, plugin: &CStr, config: &str) -> Result<(), anyhow::Error> {
+    let mut recorder = RECORDER.lock().unwrap_or_else(PoisonError::into_inner);
+    if recorder.is_some() {
+        anyhow::bail!(
+            "Cannot record {:?}: only one plugin instance per process can use {} at a time",
+            plugin,
+            super::RECORD_KEY
+        );
+    }
+
+    let file = File::create(path).with_context(|| format!("Failed to create {}", path))?;
+    let mut r = Recorder {
+        out: BufWriter::new(file),
+        last_handle: 0,
+        tables: Default::default(),
+        fields: Default::default(),
+        entries: Default::default(),
+        host_tables: Default::default(),
+        host_reader: None,
+        host_writer: None,
+        host_fields: None,
+    };
+
+    r.emit(&TapeRecord::Init {
+        plugin: plugin.to_string_lossy().into_owned(),
+        config: config.to_string(),
+    });
+    r.flush();
+    *recorder = Some(r);
+    Ok(())
+}
+
+/// Stop the active recording
+pub(super) fn stop() {
+    let mut recorder = RECORDER.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(mut r) = recorder.take() {
+        r.flush();
+    }
+}
+
+fn event_bytes(event: &EventInput) -> &[u8] {
+    let evt = event.0.evt;
+    if evt.is_null() {
+        return &[];
+    }
+
+    unsafe {
+        let len = (*evt).len as usize;
+        std::slice::from_raw_parts(evt.cast(), len)
+    }
+}
+
+/// Record an extraction request, before calling the plugin
+///
+/// # Safety
+/// All pointers in `fields` must be valid
+pub(super) unsafe fn extract_begin(event: &EventInput, fields: &[ss_plugin_extract_field]) {
+    let fields = fields
+        .iter()
+        .map(|f| {
+            let arg = if f.arg_present == 0 {
+                ExtractArg::None
+            } else if let Some(key) = unsafe { string(f.arg_key) } {
+                ExtractArg::Key(key)
+            } else {
+                ExtractArg::Index(f.arg_index)
+            };
+
+            ExtractField {
+                id: f.field_id,
+                name: unsafe { string(f.field) }.unwrap_or_default(),
+                arg,
+                ftype: f.ftype,
+                flist: f.flist != 0,
+            }
+        })
+        .collect();
+
+    let record = TapeRecord::Extract {
+        evtnum: event.event_number() as u64,
+        source: event.source().map(|s| s.to_string_lossy().into_owned()),
+        event: to_hex(event_bytes(event)),
+        fields,
+    };
+    with_recorder(|r| r.emit(&record));
+}
+
+/// Record the outcome of an extraction request
+///
+/// # Safety
+/// On success, the result pointers in `fields` must have been filled by the extractors
+pub(super) unsafe fn extract_end(
+    fields: &[ss_plugin_extract_field],
+    result: &Result<(), anyhow::Error>,
+) {
+    let record = match result {
+        Ok(()) => TapeRecord::ExtractResult {
+            values: fields
+                .iter()
+                .map(|f| unsafe { describe_result(f) })
+                .collect(),
+            error: None,
+        },
+        Err(e) => TapeRecord::ExtractResult {
+            values: Vec::new(),
+            error: Some(format!("{:#}", e)),
+        },
+    };
+
+    with_recorder(|r| {
+        r.emit(&record);
+        r.flush();
+    });
+}
+
+/// Record a parse request, before calling the plugin
+pub(super) fn parse_begin(event: &EventInput) {
+    let record = TapeRecord::Parse {
+        evtnum: event.event_number() as u64,
+        source: event.source().map(|s| s.to_string_lossy().into_owned()),
+        event: to_hex(event_bytes(event)),
+    };
+    with_recorder(|r| r.emit(&record));
+}
+
+/// Record the outcome of a parse request
+pub(super) fn parse_end(result: &Result<(), anyhow::Error>) {
+    let record = TapeRecord::ParseResult {
+        error: result.as_ref().err().map(|e| format!("{:#}", e)),
+    };
+
+    with_recorder(|r| {
+        r.emit(&record);
+        r.flush();
+    });
+}
+
+/// Replace the host table reader functions with recording ones
+pub(super) fn reader_ext(
+    host: &ss_plugin_table_reader_vtable_ext,
+) -> ss_plugin_table_reader_vtable_ext {
+    with_recorder(|r| r.host_reader = Some(*host));
+    ss_plugin_table_reader_vtable_ext {
+        get_table_name: host.get_table_name.map(|_| get_table_name as _),
+        get_table_size: host.get_table_size.map(|_| get_table_size as _),
+        get_table_entry: host.get_table_entry.map(|_| get_table_entry as _),
+        read_entry_field: host.read_entry_field.map(|_| read_entry_field as _),
+        release_table_entry: host.release_table_entry.map(|_| release_table_entry as _),
+        iterate_entries: host.iterate_entries.map(|_| iterate_entries as _),
+    }
+}
+
+/// Replace the host table writer functions with recording ones
+pub(super) fn writer_ext(
+    host: &ss_plugin_table_writer_vtable_ext,
+) -> ss_plugin_table_writer_vtable_ext {
+    with_recorder(|r| r.host_writer = Some(*host));
+    ss_plugin_table_writer_vtable_ext {
+        clear_table: host.clear_table.map(|_| clear_table as _),
+        erase_table_entry: host.erase_table_entry.map(|_| erase_table_entry as _),
+        create_table_entry: host.create_table_entry.map(|_| create_table_entry as _),
+        destroy_table_entry: host.destroy_table_entry.map(|_| destroy_table_entry as _),
+        add_table_entry: host.add_table_entry.map(|_| add_table_entry as _),
+        write_entry_field: host.write_entry_field.map(|_| write_entry_field as _),
+    }
+}
+
+/// Replace the host table field functions with recording ones
+pub(super) fn fields_ext(
+    host: &ss_plugin_table_fields_vtable_ext,
+) -> ss_plugin_table_fields_vtable_ext {
+    with_recorder(|r| r.host_fields = Some(*host));
+    ss_plugin_table_fields_vtable_ext {
+        list_table_fields: host.list_table_fields.map(|_| list_table_fields as _),
+        get_table_field: host.get_table_field.map(|_| get_table_field as _),
+        add_table_field: host.add_table_field.map(|_| add_table_field as _),
+    }
+}
+
+/// # A copy of the plugin init input, with the table vtables replaced by recording ones
+#[derive(Debug)]
+pub(crate) struct InterposedInit {
+    input: ss_plugin_init_input,
+    tables: ss_plugin_init_tables_input,
+    reader: ss_plugin_table_reader_vtable_ext,
+    writer: ss_plugin_table_writer_vtable_ext,
+    fields: ss_plugin_table_fields_vtable_ext,
+}
+
+impl InterposedInit {
+    /// Returns `None` if the host does not provide (valid) table access
+    pub(super) fn new(host: &ss_plugin_init_input) -> Option<Box<Self>> {
+        let host_tables = unsafe { host.tables.as_ref() }?;
+        let reader = unsafe { host_tables.reader_ext.as_ref() }?;
+        let writer = unsafe { host_tables.writer_ext.as_ref() }?;
+        let fields = unsafe { host_tables.fields_ext.as_ref() }?;
+
+        with_recorder(|r| {
+            r.host_tables = HostTables {
+                list_tables: host_tables.list_tables,
+                get_table: host_tables.get_table,
+                add_table: host_tables.add_table,
+            }
+        });
+
+        let mut interposed = Box::new(Self {
+            input: *host,
+            tables: ss_plugin_init_tables_input {
+                list_tables: host_tables.list_tables.map(|_| list_tables as _),
+                get_table: host_tables.get_table.map(|_| get_table as _),
+                add_table: host_tables.add_table.map(|_| add_table as _),
+                ..*host_tables
+            },
+            reader: reader_ext(reader),
+            writer: writer_ext(writer),
+            fields: fields_ext(fields),
+        });
+
+        // the box never moves its contents, so the pointers remain valid
+        interposed.tables.reader_ext = &mut interposed.reader;
+        interposed.tables.writer_ext = &mut interposed.writer;
+        interposed.tables.fields_ext = &mut interposed.fields;
+        interposed.input.tables = &interposed.tables;
+        Some(interposed)
+    }
+
+    pub(crate) fn input(&self) -> &ss_plugin_init_input {
+        &self.input
+    }
+}
+
+/// # A copy of the parse input, with the table vtables replaced by recording ones
+#[derive(Debug)]
+pub(crate) struct InterposedParse {
+    input: ss_plugin_event_parse_input,
+    reader: ss_plugin_table_reader_vtable_ext,
+    writer: ss_plugin_table_writer_vtable_ext,
+}
+
+impl InterposedParse {
+    /// Returns `None` if the host does not provide (valid) table access
+    pub(super) fn new(host: *const ss_plugin_event_parse_input) -> Option<Box<Self>> {
+        let host = unsafe { host.as_ref() }?;
+        let reader = unsafe { host.table_reader_ext.as_ref() }?;
+        let writer = unsafe { host.table_writer_ext.as_ref() }?;
+
+        let mut interposed = Box::new(Self {
+            input: *host,
+            reader: reader_ext(reader),
+            writer: writer_ext(writer),
+        });
+
+        interposed.input.table_reader_ext = &mut interposed.reader;
+        interposed.input.table_writer_ext = &mut interposed.writer;
+        Some(interposed)
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const ss_plugin_event_parse_input {
+        &self.input
+    }
+}
+
+unsafe extern "C-unwind" fn list_tables(
+    o: *mut ss_plugin_owner_t,
+    ntables: *mut u32,
+) -> *mut ss_plugin_table_info {
+    let Some(host) = with_recorder(|r| r.host_tables.list_tables).flatten() else {
+        return null_mut();
+    };
+
+    let tables = unsafe { host(o, ntables) };
+    record_call(|_| {
+        let infos = match unsafe { ntables.as_ref() } {
+            Some(n) if !tables.is_null() => {
+                unsafe { std::slice::from_raw_parts(tables, *n as usize) }
+                    .iter()
+                    .map(|t| TableInfo {
+                        name: unsafe { string(t.name) }.unwrap_or_default(),
+                        key_type: t.key_type,
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        (HostCall::ListTables, HostReturn::Tables(infos))
+    });
+    tables
+}
+
+unsafe extern "C-unwind" fn get_table(
+    o: *mut ss_plugin_owner_t,
+    name: *const c_char,
+    key_type: ss_plugin_state_type,
+) -> *mut ss_plugin_table_t {
+    let Some(host) = with_recorder(|r| r.host_tables.get_table).flatten() else {
+        return null_mut();
+    };
+
+    let table = unsafe { host(o, name, key_type) };
+    record_call(|r| {
+        let call = HostCall::GetTable {
+            name: unsafe { string(name) }.unwrap_or_default(),
+            key_type,
+        };
+        (call, HostReturn::Handle(r.add_table(table, key_type).0))
+    });
+    table
+}
+
+unsafe extern "C-unwind" fn add_table(
+    o: *mut ss_plugin_owner_t,
+    in_: *const ss_plugin_table_input,
+) -> ss_plugin_rc {
+    let Some(host) = with_recorder(|r| r.host_tables.add_table).flatten() else {
+        return ss_plugin_rc_SS_PLUGIN_FAILURE;
+    };
+
+    let rc = unsafe { host(o, in_) };
+    record_call(|_| {
+        let (name, key_type) = match unsafe { in_.as_ref() } {
+            Some(input) => (
+                unsafe { string(input.name) }.unwrap_or_default(),
+                input.key_type,
+            ),
+            None => (String::new(), 0),
+        };
+        (HostCall::AddTable { name, key_type }, HostReturn::Rc(rc))
+    });
+    rc
+}
+
+unsafe extern "C-unwind" fn list_table_fields(
+    t: *mut ss_plugin_table_t,
+    nfields: *mut u32,
+) -> *const ss_plugin_table_fieldinfo {
+    let Some(host) = with_recorder(|r| r.host_fields?.list_table_fields).flatten() else {
+        return null();
+    };
+
+    let fields = unsafe { host(t, nfields) };
+    record_call(|r| {
+        let infos = match unsafe { nfields.as_ref() } {
+            Some(n) if !fields.is_null() => {
+                unsafe { std::slice::from_raw_parts(fields, *n as usize) }
+                    .iter()
+                    .map(|f| FieldInfo {
+                        name: unsafe { string(f.name) }.unwrap_or_default(),
+                        field_type: f.field_type,
+                        read_only: f.read_only != 0,
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        let call = HostCall::ListTableFields { table: r.table(t) };
+        (call, HostReturn::Fields(infos))
+    });
+    fields
+}
+
+unsafe extern "C-unwind" fn get_table_field(
+    t: *mut ss_plugin_table_t,
+    name: *const c_char,
+    data_type: ss_plugin_state_type,
+) -> *mut ss_plugin_table_field_t {
+    let Some(host) = with_recorder(|r| r.host_fields?.get_table_field).flatten() else {
+        return null_mut();
+    };
+
+    let field = unsafe { host(t, name, data_type) };
+    record_call(|r| {
+        let call = HostCall::GetTableField {
+            table: r.table(t),
+            name: unsafe { string(name) }.unwrap_or_default(),
+            data_type,
+        };
+        (call, HostReturn::Handle(r.add_field(field, data_type)))
+    });
+    field
+}
+
+unsafe extern "C-unwind" fn add_table_field(
+    t: *mut ss_plugin_table_t,
+    name: *const c_char,
+    data_type: ss_plugin_state_type,
+) -> *mut ss_plugin_table_field_t {
+    let Some(host) = with_recorder(|r| r.host_fields?.add_table_field).flatten() else {
+        return null_mut();
+    };
+
+    let field = unsafe { host(t, name, data_type) };
+    record_call(|r| {
+        let call = HostCall::AddTableField {
+            table: r.table(t),
+            name: unsafe { string(name) }.unwrap_or_default(),
+            data_type,
+        };
+        (call, HostReturn::Handle(r.add_field(field, data_type)))
+    });
+    field
+}
+
+unsafe extern "C-unwind" fn get_table_name(t: *mut ss_plugin_table_t) -> *const c_char {
+    let Some(host) = with_recorder(|r| r.host_reader?.get_table_name).flatten() else {
+        return null();
+    };
+
+    let name = unsafe { host(t) };
+    record_call(|r| {
+        let call = HostCall::GetTableName { table: r.table(t) };
+        (call, HostReturn::Name(unsafe { string(name) }))
+    });
+    name
+}
+
+unsafe extern "C-unwind" fn get_table_size(t: *mut ss_plugin_table_t) -> u64 {
+    let Some(host) = with_recorder(|r| r.host_reader?.get_table_size).flatten() else {
+        return 0;
+    };
+
+    let size = unsafe { host(t) };
+    record_call(|r| {
+        let call = HostCall::GetTableSize { table: r.table(t) };
+        (call, HostReturn::Size(size))
+    });
+    size
+}
+
+unsafe extern "C-unwind" fn get_table_entry(
+    t: *mut ss_plugin_table_t,
+    key: *const ss_plugin_state_data,
+) -> *mut ss_plugin_table_entry_t {
+    let Some(host) = with_recorder(|r| r.host_reader?.get_table_entry).flatten() else {
+        return null_mut();
+    };
+
+    let entry = unsafe { host(t, key) };
+    record_call(|r| {
+        let call = HostCall::GetTableEntry {
+            table: r.table(t),
+            key: unsafe { r.key(t, key) },
+        };
+        (call, HostReturn::Handle(r.new_entry(entry)))
+    });
+    entry
+}
+
+unsafe extern "C-unwind" fn read_entry_field(
+    t: *mut ss_plugin_table_t,
+    e: *mut ss_plugin_table_entry_t,
+    f: *const ss_plugin_table_field_t,
+    out: *mut ss_plugin_state_data,
+) -> ss_plugin_rc {
+    let Some(host) = with_recorder(|r| r.host_reader?.read_entry_field).flatten() else {
+        return ss_plugin_rc_SS_PLUGIN_FAILURE;
+    };
+
+    let rc = unsafe { host(t, e, f, out) };
+    record_call(|r| {
+        let call = HostCall::ReadEntryField {
+            table: r.table(t),
+            entry: r.entry(e),
+            field: r.field(f),
+        };
+        let value = if rc == ss_plugin_rc_SS_PLUGIN_SUCCESS {
+            unsafe { r.value(f, out) }
+        } else {
+            serde_json::Value::Null
+        };
+        (call, HostReturn::Value { rc, value })
+    });
+    rc
+}
+
+unsafe extern "C-unwind" fn release_table_entry(
+    t: *mut ss_plugin_table_t,
+    e: *mut ss_plugin_table_entry_t,
+) {
+    let Some(host) = with_recorder(|r| r.host_reader?.release_table_entry).flatten() else {
+        return;
+    };
+
+    unsafe { host(t, e) };
+    record_call(|r| {
+        let call = HostCall::ReleaseTableEntry {
+            table: r.table(t),
+            entry: r.entry(e),
+        };
+        (call, HostReturn::Nothing)
+    });
+}
+
+struct IterationState {
+    table: *mut ss_plugin_table_t,
+    it: unsafe extern "C-unwind" fn(
+        s: *mut ss_plugin_table_iterator_state_t,
+        e: *mut ss_plugin_table_entry_t,
+    ) -> ss_plugin_bool,
+    s: *mut ss_plugin_table_iterator_state_t,
+}
+
+unsafe extern "C-unwind" fn iterate_entry(
+    s: *mut ss_plugin_table_iterator_state_t,
+    e: *mut ss_plugin_table_entry_t,
+) -> ss_plugin_bool {
+    let state = unsafe { &*(s as *const IterationState) };
+    record(|r| TapeRecord::IterateEntry {
+        table: r.table(state.table),
+        entry: r.new_entry(e),
+    });
+
+    // the lock is not held here, the callback is free to make more host calls
+    unsafe { (state.it)(state.s, e) }
+}
+
+unsafe extern "C-unwind" fn iterate_entries(
+    t: *mut ss_plugin_table_t,
+    it: ss_plugin_table_iterator_func_t,
+    s: *mut ss_plugin_table_iterator_state_t,
+) -> ss_plugin_bool {
+    let Some(host) = with_recorder(|r| r.host_reader?.iterate_entries).flatten() else {
+        return 0;
+    };
+
+    let result = match it {
+        Some(it) => {
+            let mut state = IterationState { table: t, it, s };
+            unsafe {
+                host(
+                    t,
+                    Some(iterate_entry),
+                    &mut state as *mut IterationState as *mut _,
+                )
+            }
+        }
+        None => unsafe { host(t, it, s) },
+    };
+
+    record_call(|r| {
+        let call = HostCall::IterateEntries { table: r.table(t) };
+        (call, HostReturn::Bool(result != 0))
+    });
+    result
+}
+
+unsafe extern "C-unwind" fn clear_table(t: *mut ss_plugin_table_t) -> ss_plugin_rc {
+    let Some(host) = with_recorder(|r| r.host_writer?.clear_table).flatten() else {
+        return ss_plugin_rc_SS_PLUGIN_FAILURE;
+    };
+
+    let rc = unsafe { host(t) };
+    record_call(|r| {
+        let call = HostCall::ClearTable { table: r.table(t) };
+        (call, HostReturn::Rc(rc))
+    });
+    rc
+}
+
+unsafe extern "C-unwind" fn erase_table_entry(
+    t: *mut ss_plugin_table_t,
+    key: *const ss_plugin_state_data,
+) -> ss_plugin_rc {
+    let Some(host) = with_recorder(|r| r.host_writer?.erase_table_entry).flatten() else {
+        return ss_plugin_rc_SS_PLUGIN_FAILURE;
+    };
+
+    let rc = unsafe { host(t, key) };
+    record_call(|r| {
+        let call = HostCall::EraseTableEntry {
+            table: r.table(t),
+            key: unsafe { r.key(t, key) },
+        };
+        (call, HostReturn::Rc(rc))
+    });
+    rc
+}
+
+unsafe extern "C-unwind" fn create_table_entry(
+    t: *mut ss_plugin_table_t,
+) -> *mut ss_plugin_table_entry_t {
+    let Some(host) = with_recorder(|r| r.host_writer?.create_table_entry).flatten() else {
+        return null_mut();
+    };
+
+    let entry = unsafe { host(t) };
+    record_call(|r| {
+        let call = HostCall::CreateTableEntry { table: r.table(t) };
+        (call, HostReturn::Handle(r.new_entry(entry)))
+    });
+    entry
+}
+
+unsafe extern "C-unwind" fn destroy_table_entry(
+    t: *mut ss_plugin_table_t,
+    e: *mut ss_plugin_table_entry_t,
+) {
+    let Some(host) = with_recorder(|r| r.host_writer?.destroy_table_entry).flatten() else {
+        return;
+    };
+
+    unsafe { host(t, e) };
+    record_call(|r| {
+        let call = HostCall::DestroyTableEntry {
+            table: r.table(t),
+            entry: r.entry(e),
+        };
+        (call, HostReturn::Nothing)
+    });
+}
+
+unsafe extern "C-unwind" fn add_table_entry(
+    t: *mut ss_plugin_table_t,
+    key: *const ss_plugin_state_data,
+    entry: *mut ss_plugin_table_entry_t,
+) -> *mut ss_plugin_table_entry_t {
+    let Some(host) = with_recorder(|r| r.host_writer?.add_table_entry).flatten() else {
+        return null_mut();
+    };
+
+    let added = unsafe { host(t, key, entry) };
+    record_call(|r| {
+        let call = HostCall::AddTableEntry {
+            table: r.table(t),
+            key: unsafe { r.key(t, key) },
+            entry: r.entry(entry),
+        };
+        (call, HostReturn::Handle(r.new_entry(added)))
+    });
+    added
+}
+
+unsafe extern "C-unwind" fn write_entry_field(
+    t: *mut ss_plugin_table_t,
+    e: *mut ss_plugin_table_entry_t,
+    f: *const ss_plugin_table_field_t,
+    in_: *const ss_plugin_state_data,
+) -> ss_plugin_rc {
+    let Some(host) = with_recorder(|r| r.host_writer?.write_entry_field).flatten() else {
+        return ss_plugin_rc_SS_PLUGIN_FAILURE;
+    };
+
+    let rc = unsafe { host(t, e, f, in_) };
+    record_call(|r| {
+        let call = HostCall::WriteEntryField {
+            table: r.table(t),
+            entry: r.entry(e),
+            field: r.field(f),
+            value: unsafe { r.value(f, in_) },
+        };
+        (call, HostReturn::Rc(rc))
+    });
+    rc
+}
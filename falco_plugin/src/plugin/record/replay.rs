@@ -0,0 +1,1119 @@
+use crate::plugin::base::scope::EventScope;
+use crate::plugin::base::Plugin;
+use crate::plugin::convert;
+use crate::plugin::error::last_error::LastError;
+use crate::plugin::event::EventInput;
+use crate::plugin::extract::storage::FieldStorage;
+use crate::plugin::extract::trace::describe_result;
+use crate::plugin::extract::ExtractPlugin;
+use crate::plugin::parse::{ParseInput, ParsePlugin};
+use crate::plugin::record::tape::{
+    from_hex, ExtractArg, ExtractField, HandleId, HostCall, HostReturn, TapeRecord,
+};
+use crate::plugin::record::{data_to_json, string};
+use crate::plugin::tables::data::FieldTypeId;
+use crate::plugin::tables::vtable::{TableReader, TableWriter, TablesInput};
+use crate::plugin::testing::OwnedEventInput;
+use anyhow::Context;
+use falco_plugin_api::{
+    ss_plugin_bool, ss_plugin_extract_field, ss_plugin_init_input, ss_plugin_init_tables_input,
+    ss_plugin_owner_t, ss_plugin_rc, ss_plugin_rc_SS_PLUGIN_FAILURE,
+    ss_plugin_rc_SS_PLUGIN_SUCCESS, ss_plugin_state_data, ss_plugin_state_type,
+    ss_plugin_table_entry_t, ss_plugin_table_field_t, ss_plugin_table_fieldinfo,
+    ss_plugin_table_fields_vtable_ext, ss_plugin_table_info, ss_plugin_table_input,
+    ss_plugin_table_iterator_func_t, ss_plugin_table_iterator_state_t,
+    ss_plugin_table_reader_vtable_ext, ss_plugin_table_t, ss_plugin_table_writer_vtable_ext,
+};
+use num_traits::FromPrimitive;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::ffi::{c_char, CString};
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::ptr::{null, null_mut};
+
+thread_local! {
+    static ACTIVE: RefCell<Option<ReplayState>> = const { RefCell::new(None) };
+}
+
+#[derive(Default)]
+struct ReplayState {
+    records: VecDeque<TapeRecord>,
+    line: usize,
+    // the SDK looks at the key type of imported tables, so table handles
+    // need to point to an actual `ss_plugin_table_input`
+    tables: BTreeMap<HandleId, Box<ss_plugin_table_input>>,
+    field_types: BTreeMap<HandleId, u32>,
+    // all the memory handed out to the plugin is kept around for the whole replay
+    strings: Vec<CString>,
+    table_infos: Vec<Vec<ss_plugin_table_info>>,
+    field_infos: Vec<Vec<ss_plugin_table_fieldinfo>>,
+    divergence: Option<String>,
+}
+
+enum IterationStep {
+    Entry(HandleId),
+    Done(bool),
+}
+
+impl ReplayState {
+    fn next_record(&mut self) -> Option<TapeRecord> {
+        let record = self.records.pop_front()?;
+        self.line += 1;
+        Some(record)
+    }
+
+    fn expect_call(&mut self, call: HostCall) -> Result<HostReturn, String> {
+        match self.next_record() {
+            Some(TapeRecord::Call {
+                call: recorded,
+                ret,
+            }) if recorded == call => Ok(ret),
+            Some(other) => Err(format!(
+                "line {}: the tape has {:?}, but the plugin called {:?}",
+                self.line, other, call
+            )),
+            None => Err(format!("end of tape: the plugin called {:?}", call)),
+        }
+    }
+
+    fn next_iteration(&mut self, table: HandleId, stopped: bool) -> Result<IterationStep, String> {
+        if !stopped && matches!(self.records.front(), Some(TapeRecord::IterateEntry { .. })) {
+            match self.next_record() {
+                Some(TapeRecord::IterateEntry { table: t, entry }) if t == table => {
+                    return Ok(IterationStep::Entry(entry))
+                }
+                other => {
+                    return Err(format!(
+                        "line {}: the tape has {:?}, but the plugin is iterating over table {}",
+                        self.line, other, table
+                    ))
+                }
+            }
+        }
+
+        match self.expect_call(HostCall::IterateEntries { table })? {
+            HostReturn::Bool(result) => Ok(IterationStep::Done(result)),
+            ret => Err(unexpected(ret)),
+        }
+    }
+
+    fn table_ptr(&mut self, handle: HandleId, key_type: u32) -> *mut ss_plugin_table_t {
+        if handle == 0 {
+            return null_mut();
+        }
+
+        let table = self.tables.entry(handle).or_insert_with(|| {
+            let mut table: ss_plugin_table_input = unsafe { std::mem::zeroed() };
+            table.key_type = key_type;
+            Box::new(table)
+        });
+        table.as_mut() as *mut ss_plugin_table_input as *mut ss_plugin_table_t
+    }
+
+    fn table(&self, t: *mut ss_plugin_table_t) -> Result<(HandleId, u32), String> {
+        if t.is_null() {
+            return Ok((0, 0));
+        }
+
+        self.tables
+            .iter()
+            .find(|(_, table)| std::ptr::eq(table.as_ref(), t as *const ss_plugin_table_input))
+            .map(|(handle, table)| (*handle, table.key_type))
+            .ok_or_else(|| format!("unknown table handle {:?}", t))
+    }
+
+    fn table_id(&self, t: *mut ss_plugin_table_t) -> Result<HandleId, String> {
+        Ok(self.table(t)?.0)
+    }
+
+    unsafe fn key(
+        &self,
+        t: *mut ss_plugin_table_t,
+        key: *const ss_plugin_state_data,
+    ) -> Result<serde_json::Value, String> {
+        let (_, key_type) = self.table(t)?;
+        Ok(unsafe { data_to_json(key, key_type) })
+    }
+
+    unsafe fn value(
+        &self,
+        f: *const ss_plugin_table_field_t,
+        data: *const ss_plugin_state_data,
+    ) -> Result<serde_json::Value, String> {
+        let Some(data) = (unsafe { data.as_ref() }) else {
+            return Ok(serde_json::Value::Null);
+        };
+
+        match self.field_types.get(&handle_id(f)) {
+            Some(ty) if FieldTypeId::from_u32(*ty) == Some(FieldTypeId::Table) => {
+                let (table, key_type) = self.table(unsafe { data.table })?;
+                Ok(serde_json::json!({"table": table, "key_type": key_type}))
+            }
+            Some(ty) => Ok(unsafe { data_to_json(data, *ty) }),
+            None => Ok(serde_json::Value::Null),
+        }
+    }
+
+    fn write_value(
+        &mut self,
+        field: HandleId,
+        value: &serde_json::Value,
+        out: &mut ss_plugin_state_data,
+    ) -> Result<(), String> {
+        let bad_value = || format!("invalid value {} for field {}", value, field);
+        let int = || value.as_i64().ok_or_else(bad_value);
+        let uint = || value.as_u64().ok_or_else(bad_value);
+
+        let type_id = self
+            .field_types
+            .get(&field)
+            .and_then(|ty| FieldTypeId::from_u32(*ty))
+            .ok_or_else(|| format!("unknown type of field {}", field))?;
+
+        match type_id {
+            FieldTypeId::I8 => out.s8 = int()?.try_into().map_err(|_| bad_value())?,
+            FieldTypeId::I16 => out.s16 = int()?.try_into().map_err(|_| bad_value())?,
+            FieldTypeId::I32 => out.s32 = int()?.try_into().map_err(|_| bad_value())?,
+            FieldTypeId::I64 => out.s64 = int()?,
+            FieldTypeId::U8 => out.u8_ = uint()?.try_into().map_err(|_| bad_value())?,
+            FieldTypeId::U16 => out.u16_ = uint()?.try_into().map_err(|_| bad_value())?,
+            FieldTypeId::U32 => out.u32_ = uint()?.try_into().map_err(|_| bad_value())?,
+            FieldTypeId::U64 => out.u64_ = uint()?,
+            FieldTypeId::Bool => {
+                out.b = value.as_bool().ok_or_else(bad_value)? as ss_plugin_bool;
+            }
+            FieldTypeId::String => {
+                let s = value.as_str().ok_or_else(bad_value)?;
+                let s = CString::new(s).map_err(|_| bad_value())?;
+                out.str_ = s.as_ptr();
+                self.strings.push(s);
+            }
+            FieldTypeId::Table => {
+                let table = value.get("table").and_then(|t| t.as_u64());
+                let key_type = value
+                    .get("key_type")
+                    .and_then(|t| t.as_u64())
+                    .and_then(|t| u32::try_from(t).ok());
+                let (Some(table), Some(key_type)) = (table, key_type) else {
+                    return Err(bad_value());
+                };
+                out.table = self.table_ptr(table, key_type);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cstring(&mut self, s: String) -> Result<*const c_char, String> {
+        let s = CString::new(s).map_err(|e| e.to_string())?;
+        let ptr = s.as_ptr();
+        self.strings.push(s);
+        Ok(ptr)
+    }
+}
+
+fn unexpected(ret: HostReturn) -> String {
+    format!("unexpected host return value {:?} on the tape", ret)
+}
+
+fn handle_ptr<T>(handle: HandleId) -> *mut T {
+    handle as usize as *mut T
+}
+
+fn handle_id<T>(ptr: *const T) -> HandleId {
+    ptr as usize as HandleId
+}
+
+/// Run `f` on the active replay state
+///
+/// Once the replay diverges from the tape, all further calls fail
+fn with_state<T>(f: impl FnOnce(&mut ReplayState) -> Result<T, String>) -> Option<T> {
+    ACTIVE.with(|active| {
+        let mut active = active.borrow_mut();
+        let state = active.as_mut()?;
+        if state.divergence.is_some() {
+            return None;
+        }
+
+        match f(state) {
+            Ok(val) => Some(val),
+            Err(e) => {
+                state.divergence = Some(e);
+                None
+            }
+        }
+    })
+}
+
+unsafe extern "C-unwind" fn get_owner_last_error(_o: *mut ss_plugin_owner_t) -> *const c_char {
+    let diverged = ACTIVE.with(|active| {
+        active
+            .borrow()
+            .as_ref()
+            .is_some_and(|state| state.divergence.is_some())
+    });
+
+    if diverged {
+        c"replay diverged from the recording".as_ptr()
+    } else {
+        null()
+    }
+}
+
+fn last_error() -> LastError {
+    unsafe { LastError::new(null_mut(), get_owner_last_error) }
+}
+
+unsafe extern "C-unwind" fn list_tables(
+    _o: *mut ss_plugin_owner_t,
+    ntables: *mut u32,
+) -> *mut ss_plugin_table_info {
+    with_state(|s| match s.expect_call(HostCall::ListTables)? {
+        HostReturn::Tables(tables) => {
+            let mut infos = Vec::with_capacity(tables.len());
+            for table in tables {
+                infos.push(ss_plugin_table_info {
+                    name: s.cstring(table.name)?,
+                    key_type: table.key_type,
+                });
+            }
+
+            if let Some(ntables) = unsafe { ntables.as_mut() } {
+                *ntables = convert::saturating(infos.len());
+            }
+            let ptr = infos.as_mut_ptr();
+            s.table_infos.push(infos);
+            Ok(ptr)
+        }
+        ret => Err(unexpected(ret)),
+    })
+    .unwrap_or(null_mut())
+}
+
+unsafe extern "C-unwind" fn get_table(
+    _o: *mut ss_plugin_owner_t,
+    name: *const c_char,
+    key_type: ss_plugin_state_type,
+) -> *mut ss_plugin_table_t {
+    with_state(|s| {
+        let call = HostCall::GetTable {
+            name: unsafe { string(name) }.unwrap_or_default(),
+            key_type,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Handle(table) => Ok(s.table_ptr(table, key_type)),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(null_mut())
+}
+
+unsafe extern "C-unwind" fn add_table(
+    _o: *mut ss_plugin_owner_t,
+    in_: *const ss_plugin_table_input,
+) -> ss_plugin_rc {
+    with_state(|s| {
+        let (name, key_type) = match unsafe { in_.as_ref() } {
+            Some(input) => (
+                unsafe { string(input.name) }.unwrap_or_default(),
+                input.key_type,
+            ),
+            None => (String::new(), 0),
+        };
+        match s.expect_call(HostCall::AddTable { name, key_type })? {
+            HostReturn::Rc(rc) => Ok(rc),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(ss_plugin_rc_SS_PLUGIN_FAILURE)
+}
+
+unsafe extern "C-unwind" fn list_table_fields(
+    t: *mut ss_plugin_table_t,
+    nfields: *mut u32,
+) -> *const ss_plugin_table_fieldinfo {
+    with_state(|s| {
+        let call = HostCall::ListTableFields {
+            table: s.table_id(t)?,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Fields(fields) => {
+                let mut infos = Vec::with_capacity(fields.len());
+                for field in fields {
+                    infos.push(ss_plugin_table_fieldinfo {
+                        name: s.cstring(field.name)?,
+                        field_type: field.field_type,
+                        read_only: field.read_only as ss_plugin_bool,
+                    });
+                }
+
+                if let Some(nfields) = unsafe { nfields.as_mut() } {
+                    *nfields = convert::saturating(infos.len());
+                }
+                let ptr = infos.as_ptr();
+                s.field_infos.push(infos);
+                Ok(ptr)
+            }
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(null())
+}
+
+fn replay_field(s: &mut ReplayState, call: HostCall, data_type: u32) -> Result<HandleId, String> {
+    match s.expect_call(call)? {
+        HostReturn::Handle(field) => {
+            if field != 0 {
+                s.field_types.insert(field, data_type);
+            }
+            Ok(field)
+        }
+        ret => Err(unexpected(ret)),
+    }
+}
+
+unsafe extern "C-unwind" fn get_table_field(
+    t: *mut ss_plugin_table_t,
+    name: *const c_char,
+    data_type: ss_plugin_state_type,
+) -> *mut ss_plugin_table_field_t {
+    with_state(|s| {
+        let call = HostCall::GetTableField {
+            table: s.table_id(t)?,
+            name: unsafe { string(name) }.unwrap_or_default(),
+            data_type,
+        };
+        replay_field(s, call, data_type).map(handle_ptr)
+    })
+    .unwrap_or(null_mut())
+}
+
+unsafe extern "C-unwind" fn add_table_field(
+    t: *mut ss_plugin_table_t,
+    name: *const c_char,
+    data_type: ss_plugin_state_type,
+) -> *mut ss_plugin_table_field_t {
+    with_state(|s| {
+        let call = HostCall::AddTableField {
+            table: s.table_id(t)?,
+            name: unsafe { string(name) }.unwrap_or_default(),
+            data_type,
+        };
+        replay_field(s, call, data_type).map(handle_ptr)
+    })
+    .unwrap_or(null_mut())
+}
+
+unsafe extern "C-unwind" fn get_table_name(t: *mut ss_plugin_table_t) -> *const c_char {
+    with_state(|s| {
+        let call = HostCall::GetTableName {
+            table: s.table_id(t)?,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Name(Some(name)) => s.cstring(name),
+            HostReturn::Name(None) => Ok(null()),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(null())
+}
+
+unsafe extern "C-unwind" fn get_table_size(t: *mut ss_plugin_table_t) -> u64 {
+    with_state(|s| {
+        let call = HostCall::GetTableSize {
+            table: s.table_id(t)?,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Size(size) => Ok(size),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(0)
+}
+
+unsafe extern "C-unwind" fn get_table_entry(
+    t: *mut ss_plugin_table_t,
+    key: *const ss_plugin_state_data,
+) -> *mut ss_plugin_table_entry_t {
+    with_state(|s| {
+        let call = HostCall::GetTableEntry {
+            table: s.table_id(t)?,
+            key: unsafe { s.key(t, key) }?,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Handle(entry) => Ok(handle_ptr(entry)),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(null_mut())
+}
+
+unsafe extern "C-unwind" fn read_entry_field(
+    t: *mut ss_plugin_table_t,
+    e: *mut ss_plugin_table_entry_t,
+    f: *const ss_plugin_table_field_t,
+    out: *mut ss_plugin_state_data,
+) -> ss_plugin_rc {
+    with_state(|s| {
+        let call = HostCall::ReadEntryField {
+            table: s.table_id(t)?,
+            entry: handle_id(e),
+            field: handle_id(f),
+        };
+        match s.expect_call(call)? {
+            HostReturn::Value { rc, value } => {
+                if rc == ss_plugin_rc_SS_PLUGIN_SUCCESS {
+                    if let Some(out) = unsafe { out.as_mut() } {
+                        s.write_value(handle_id(f), &value, out)?;
+                    }
+                }
+                Ok(rc)
+            }
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(ss_plugin_rc_SS_PLUGIN_FAILURE)
+}
+
+unsafe extern "C-unwind" fn release_table_entry(
+    t: *mut ss_plugin_table_t,
+    e: *mut ss_plugin_table_entry_t,
+) {
+    with_state(|s| {
+        let call = HostCall::ReleaseTableEntry {
+            table: s.table_id(t)?,
+            entry: handle_id(e),
+        };
+        match s.expect_call(call)? {
+            HostReturn::Nothing => Ok(()),
+            ret => Err(unexpected(ret)),
+        }
+    });
+}
+
+unsafe extern "C-unwind" fn iterate_entries(
+    t: *mut ss_plugin_table_t,
+    it: ss_plugin_table_iterator_func_t,
+    s: *mut ss_plugin_table_iterator_state_t,
+) -> ss_plugin_bool {
+    let Some(table) = with_state(|state| state.table_id(t)) else {
+        return 0;
+    };
+
+    let mut stopped = false;
+    loop {
+        // the state must not be borrowed while running the callback,
+        // since it will make more host calls
+        match with_state(|state| state.next_iteration(table, stopped)) {
+            Some(IterationStep::Entry(entry)) => {
+                if let Some(it) = it {
+                    stopped = unsafe { it(s, handle_ptr(entry)) } == 0;
+                }
+            }
+            Some(IterationStep::Done(result)) => return result as ss_plugin_bool,
+            None => return 0,
+        }
+    }
+}
+
+unsafe extern "C-unwind" fn clear_table(t: *mut ss_plugin_table_t) -> ss_plugin_rc {
+    with_state(|s| {
+        let call = HostCall::ClearTable {
+            table: s.table_id(t)?,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Rc(rc) => Ok(rc),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(ss_plugin_rc_SS_PLUGIN_FAILURE)
+}
+
+unsafe extern "C-unwind" fn erase_table_entry(
+    t: *mut ss_plugin_table_t,
+    key: *const ss_plugin_state_data,
+) -> ss_plugin_rc {
+    with_state(|s| {
+        let call = HostCall::EraseTableEntry {
+            table: s.table_id(t)?,
+            key: unsafe { s.key(t, key) }?,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Rc(rc) => Ok(rc),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(ss_plugin_rc_SS_PLUGIN_FAILURE)
+}
+
+unsafe extern "C-unwind" fn create_table_entry(
+    t: *mut ss_plugin_table_t,
+) -> *mut ss_plugin_table_entry_t {
+    with_state(|s| {
+        let call = HostCall::CreateTableEntry {
+            table: s.table_id(t)?,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Handle(entry) => Ok(handle_ptr(entry)),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(null_mut())
+}
+
+unsafe extern "C-unwind" fn destroy_table_entry(
+    t: *mut ss_plugin_table_t,
+    e: *mut ss_plugin_table_entry_t,
+) {
+    with_state(|s| {
+        let call = HostCall::DestroyTableEntry {
+            table: s.table_id(t)?,
+            entry: handle_id(e),
+        };
+        match s.expect_call(call)? {
+            HostReturn::Nothing => Ok(()),
+            ret => Err(unexpected(ret)),
+        }
+    });
+}
+
+unsafe extern "C-unwind" fn add_table_entry(
+    t: *mut ss_plugin_table_t,
+    key: *const ss_plugin_state_data,
+    entry: *mut ss_plugin_table_entry_t,
+) -> *mut ss_plugin_table_entry_t {
+    with_state(|s| {
+        let call = HostCall::AddTableEntry {
+            table: s.table_id(t)?,
+            key: unsafe { s.key(t, key) }?,
+            entry: handle_id(entry),
+        };
+        match s.expect_call(call)? {
+            HostReturn::Handle(entry) => Ok(handle_ptr(entry)),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(null_mut())
+}
+
+unsafe extern "C-unwind" fn write_entry_field(
+    t: *mut ss_plugin_table_t,
+    e: *mut ss_plugin_table_entry_t,
+    f: *const ss_plugin_table_field_t,
+    in_: *const ss_plugin_state_data,
+) -> ss_plugin_rc {
+    with_state(|s| {
+        let call = HostCall::WriteEntryField {
+            table: s.table_id(t)?,
+            entry: handle_id(e),
+            field: handle_id(f),
+            value: unsafe { s.value(f, in_) }?,
+        };
+        match s.expect_call(call)? {
+            HostReturn::Rc(rc) => Ok(rc),
+            ret => Err(unexpected(ret)),
+        }
+    })
+    .unwrap_or(ss_plugin_rc_SS_PLUGIN_FAILURE)
+}
+
+fn reader_ext() -> ss_plugin_table_reader_vtable_ext {
+    ss_plugin_table_reader_vtable_ext {
+        get_table_name: Some(get_table_name),
+        get_table_size: Some(get_table_size),
+        get_table_entry: Some(get_table_entry),
+        read_entry_field: Some(read_entry_field),
+        release_table_entry: Some(release_table_entry),
+        iterate_entries: Some(iterate_entries),
+    }
+}
+
+fn writer_ext() -> ss_plugin_table_writer_vtable_ext {
+    ss_plugin_table_writer_vtable_ext {
+        clear_table: Some(clear_table),
+        erase_table_entry: Some(erase_table_entry),
+        create_table_entry: Some(create_table_entry),
+        destroy_table_entry: Some(destroy_table_entry),
+        add_table_entry: Some(add_table_entry),
+        write_entry_field: Some(write_entry_field),
+    }
+}
+
+fn fields_ext() -> ss_plugin_table_fields_vtable_ext {
+    ss_plugin_table_fields_vtable_ext {
+        list_table_fields: Some(list_table_fields),
+        get_table_field: Some(get_table_field),
+        add_table_field: Some(add_table_field),
+    }
+}
+
+/// # Replay a recording of plugin/host interactions without a host
+///
+/// When a plugin runs with `sdk_record` set to a file path in its (JSON object) config
+/// and the SDK is built with the `record` feature, all table operations the plugin performs
+/// through the host vtables (along with the values returned by the host) and all extraction
+/// and parsing requests are logged to that file.
+///
+/// The `Replayer` reads such a log and runs the plugin again, in a unit test or a debugger,
+/// serving all table operations from the log instead of a live host. Every host call
+/// the plugin makes is checked against the log: as long as the plugin behaves the same way
+/// as in production, it sees exactly the same data. If the plugin makes a different call
+/// (e.g. looks up a different key), the replay stops with an error pointing to the first
+/// mismatched record.
+///
+/// ```no_run
+/// # use falco_plugin::anyhow::Error;
+/// # use falco_plugin::base::Plugin;
+/// # use falco_plugin::event::events::types::EventType;
+/// # use falco_plugin::extract::{ExtractFieldInfo, ExtractPlugin};
+/// # use falco_plugin::tables::TablesInput;
+/// use falco_plugin::record::{ReplayStep, Replayer};
+/// # use std::ffi::CStr;
+/// #
+/// # struct MyPlugin;
+/// #
+/// # impl Plugin for MyPlugin {
+/// #     const NAME: &'static CStr = c"sample-plugin-rs";
+/// #     const PLUGIN_VERSION: &'static CStr = c"0.0.1";
+/// #     const DESCRIPTION: &'static CStr = c"A sample Falco plugin that does nothing";
+/// #     const CONTACT: &'static CStr = c"you@example.com";
+/// #     type ConfigType = ();
+/// #
+/// #     fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+/// #         Ok(MyPlugin)
+/// #     }
+/// # }
+/// #
+/// # impl ExtractPlugin for MyPlugin {
+/// #     const EVENT_TYPES: &'static [EventType] = &[];
+/// #     const EVENT_SOURCES: &'static [&'static str] = &[];
+/// #     type ExtractContext = ();
+/// #     const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[];
+/// # }
+///
+/// let mut replayer = Replayer::open("/tmp/my-plugin.tape")?;
+/// let mut plugin = replayer.init::<MyPlugin>()?;
+///
+/// while let Some(step) = replayer.next_step()? {
+///     match step {
+///         ReplayStep::Extract(step) => {
+///             let evtnum = step.event().event_number();
+///             let outcome = step.run(&mut plugin)?;
+///             assert!(outcome.matches(), "event {}: {:?}", evtnum, outcome);
+///         }
+///         ReplayStep::Parse(step) => {
+///             // MyPlugin is not a parse plugin
+///             anyhow::bail!("unexpected parse request for event {}", step.event().event_number());
+///         }
+///     }
+/// }
+/// # Ok::<(), Error>(())
+/// ```
+///
+/// **Note**: the host-side state is not simulated in any way: the replay only works
+/// if the plugin makes the same calls as during the recording. Exported tables, owned
+/// by the plugin itself, are not recorded, as they're not accessed through the host.
+pub struct Replayer {
+    state: ReplayState,
+}
+
+impl Debug for Replayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Replayer")
+            .field("line", &self.state.line)
+            .field("remaining", &self.state.records.len())
+            .field("divergence", &self.state.divergence)
+            .finish()
+    }
+}
+
+impl Replayer {
+    /// Load a recording from a file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open recording {}", path.display()))?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// Load a recording from a reader, one JSON record per line
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, anyhow::Error> {
+        let records = reader
+            .lines()
+            .enumerate()
+            .map(|(lineno, line)| {
+                let line = line?;
+                serde_json::from_str(&line)
+                    .with_context(|| format!("Invalid record on line {}", lineno + 1))
+            })
+            .collect::<Result<Vec<TapeRecord>, anyhow::Error>>()?;
+
+        Ok(Self::from_records(records))
+    }
+
+    /// Build a replayer from a list of records
+    pub fn from_records(records: impl IntoIterator<Item = TapeRecord>) -> Self {
+        Self {
+            state: ReplayState {
+                records: records.into_iter().collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Initialize the plugin with the recorded config
+    ///
+    /// This must be the first call, since the recording starts with plugin initialization.
+    /// The plugin gets a [`TablesInput`] backed by the recording, so it can import tables
+    /// (and look up their fields) just like when running in the host.
+    pub fn init<P: Plugin>(&mut self) -> Result<P, anyhow::Error> {
+        let Some(TapeRecord::Init { plugin, config }) = self.state.next_record() else {
+            anyhow::bail!("The recording does not start with plugin initialization");
+        };
+
+        let name = P::NAME.to_string_lossy();
+        if plugin != name {
+            anyhow::bail!(
+                "The recording was made with plugin {:?}, not {:?}",
+                plugin,
+                name
+            );
+        }
+
+        // the event scope does not matter when replaying the recorded calls
+        let (_, config) = EventScope::parse_config::<P::ConfigType>(&config, &[])?;
+
+        let mut reader_ext = reader_ext();
+        let mut writer_ext = writer_ext();
+        let mut fields_ext = fields_ext();
+
+        let mut tables: ss_plugin_init_tables_input = unsafe { std::mem::zeroed() };
+        tables.list_tables = Some(list_tables);
+        tables.get_table = Some(get_table);
+        tables.add_table = Some(add_table);
+        tables.reader_ext = &mut reader_ext;
+        tables.writer_ext = &mut writer_ext;
+        tables.fields_ext = &mut fields_ext;
+
+        let mut input: ss_plugin_init_input = unsafe { std::mem::zeroed() };
+        input.get_owner_last_error = Some(get_owner_last_error);
+        input.tables = &tables;
+
+        let tables_input = TablesInput::try_from(&input)?;
+        self.run(|| P::new(tables_input.as_ref(), config))?
+    }
+
+    /// Get the next recorded extraction or parsing request
+    ///
+    /// Returns `Ok(None)` at the end of the recording.
+    pub fn next_step(&mut self) -> Result<Option<ReplayStep<'_>>, anyhow::Error> {
+        self.check_divergence()?;
+        let Some(record) = self.state.next_record() else {
+            return Ok(None);
+        };
+
+        match record {
+            TapeRecord::Extract {
+                evtnum,
+                source,
+                event,
+                fields,
+            } => {
+                let event = event_input(evtnum, source, &event)?;
+                Ok(Some(ReplayStep::Extract(ExtractStep {
+                    replayer: self,
+                    event,
+                    fields,
+                })))
+            }
+            TapeRecord::Parse {
+                evtnum,
+                source,
+                event,
+            } => {
+                let event = event_input(evtnum, source, &event)?;
+                Ok(Some(ReplayStep::Parse(ParseStep {
+                    replayer: self,
+                    event,
+                })))
+            }
+            other => anyhow::bail!("Unexpected record on line {}: {:?}", self.state.line, other),
+        }
+    }
+
+    fn check_divergence(&self) -> Result<(), anyhow::Error> {
+        match &self.state.divergence {
+            Some(divergence) => Err(anyhow::anyhow!("Replay diverged at {}", divergence)),
+            None => Ok(()),
+        }
+    }
+
+    /// Run `f` with the table vtables served from the recording
+    fn run<T>(&mut self, f: impl FnOnce() -> T) -> Result<T, anyhow::Error> {
+        struct Restore<'a>(&'a mut ReplayState);
+
+        impl Drop for Restore<'_> {
+            fn drop(&mut self) {
+                if let Some(state) = ACTIVE.with(|active| active.borrow_mut().take()) {
+                    *self.0 = state;
+                }
+            }
+        }
+
+        self.check_divergence()?;
+        if ACTIVE.with(|active| active.borrow().is_some()) {
+            anyhow::bail!("Another replay is already running on this thread");
+        }
+
+        ACTIVE.with(|active| *active.borrow_mut() = Some(std::mem::take(&mut self.state)));
+        let restore = Restore(&mut self.state);
+        let result = f();
+        drop(restore);
+
+        self.check_divergence()?;
+        Ok(result)
+    }
+
+    /// Mark the replay as diverged, when `record` is not the expected outcome of a request
+    fn diverge(&mut self, record: Option<TapeRecord>) -> anyhow::Error {
+        let divergence = match record {
+            Some(other) => format!(
+                "line {}: the plugin returned, but the tape has {:?}",
+                self.state.line, other
+            ),
+            None => String::from("end of tape: the plugin returned, but there's no result"),
+        };
+
+        let err = anyhow::anyhow!("Replay diverged at {}", divergence);
+        self.state.divergence = Some(divergence);
+        err
+    }
+}
+
+fn event_input(
+    evtnum: u64,
+    source: Option<String>,
+    event: &str,
+) -> Result<OwnedEventInput, anyhow::Error> {
+    let event = from_hex(event).context("Invalid event data")?;
+    let mut input = OwnedEventInput::from_bytes(event).with_event_number(evtnum);
+    if let Some(source) = source {
+        input = input.with_source(&CString::new(source)?);
+    }
+    Ok(input)
+}
+
+/// # A single recorded request
+#[derive(Debug)]
+pub enum ReplayStep<'a> {
+    /// A field extraction request
+    Extract(ExtractStep<'a>),
+    /// An event parsing request
+    Parse(ParseStep<'a>),
+}
+
+/// # A recorded field extraction request
+#[derive(Debug)]
+pub struct ExtractStep<'a> {
+    replayer: &'a mut Replayer,
+    event: OwnedEventInput,
+    fields: Vec<ExtractField>,
+}
+
+impl ExtractStep<'_> {
+    /// The event the fields were extracted from
+    pub fn event(&self) -> &EventInput {
+        self.event.event_input()
+    }
+
+    /// The requested fields
+    pub fn fields(&self) -> &[ExtractField] {
+        &self.fields
+    }
+
+    /// Replay the request
+    ///
+    /// Returns an error if the plugin diverged from the recording (i.e. it made different
+    /// host calls than the ones on the tape). Otherwise, returns the extracted values
+    /// (or the extraction error), formatted as strings, along with the recorded ones.
+    pub fn run<P: ExtractPlugin>(self, plugin: &mut P) -> Result<ExtractOutcome, anyhow::Error> {
+        let names = self
+            .fields
+            .iter()
+            .map(|f| CString::new(f.name.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let keys = self
+            .fields
+            .iter()
+            .map(|f| match &f.arg {
+                ExtractArg::Key(key) => CString::new(key.as_str()).map(Some),
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut fields = self
+            .fields
+            .iter()
+            .zip(names.iter().zip(keys.iter()))
+            .map(|(f, (name, key))| {
+                let mut field: ss_plugin_extract_field = unsafe { std::mem::zeroed() };
+                field.field_id = f.id;
+                field.field = name.as_ptr();
+                field.ftype = f.ftype;
+                field.flist = f.flist as ss_plugin_bool;
+                match (&f.arg, key) {
+                    (ExtractArg::None, _) => {}
+                    (ExtractArg::Index(index), _) => {
+                        field.arg_present = 1;
+                        field.arg_index = *index;
+                    }
+                    (ExtractArg::Key(_), key) => {
+                        field.arg_present = 1;
+                        field.arg_key = key.as_ref().map_or(null(), |k| k.as_ptr());
+                    }
+                }
+                field
+            })
+            .collect::<Vec<_>>();
+
+        let reader = TableReader::try_from(&reader_ext(), last_error())?;
+        let mut storage = FieldStorage::new();
+        let event = self.event.event_input();
+        let replayed = self.replayer.run(|| {
+            match plugin.extract_fields(event, &reader, &mut fields, &mut storage) {
+                Ok(()) => Ok(fields
+                    .iter()
+                    .map(|f| unsafe { describe_result(f) })
+                    .collect()),
+                Err(e) => Err(format!("{:#}", e)),
+            }
+        })?;
+
+        let recorded = match self.replayer.state.next_record() {
+            Some(TapeRecord::ExtractResult { values, error }) => error.map_or(Ok(values), Err),
+            other => return Err(self.replayer.diverge(other)),
+        };
+        Ok(ExtractOutcome { recorded, replayed })
+    }
+}
+
+/// # The outcome of a replayed field extraction request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractOutcome {
+    /// The values of all requested fields (or the error) when recording
+    pub recorded: Result<Vec<String>, String>,
+    /// The values of all requested fields (or the error) when replaying
+    pub replayed: Result<Vec<String>, String>,
+}
+
+impl ExtractOutcome {
+    /// Check if the replay returned the same result as the recording
+    pub fn matches(&self) -> bool {
+        self.recorded == self.replayed
+    }
+}
+
+/// # A recorded event parsing request
+#[derive(Debug)]
+pub struct ParseStep<'a> {
+    replayer: &'a mut Replayer,
+    event: OwnedEventInput,
+}
+
+impl ParseStep<'_> {
+    /// The parsed event
+    pub fn event(&self) -> &EventInput {
+        self.event.event_input()
+    }
+
+    /// Replay the request
+    ///
+    /// Returns an error if the plugin diverged from the recording (i.e. it made different
+    /// host calls than the ones on the tape). Otherwise, returns the outcome of parsing
+    /// the event, along with the recorded one.
+    pub fn run<P: ParsePlugin>(self, plugin: &mut P) -> Result<ParseOutcome, anyhow::Error> {
+        let parse_input = ParseInput {
+            reader: TableReader::try_from(&reader_ext(), last_error())?,
+            writer: TableWriter::try_from(&writer_ext(), last_error())?,
+        };
+
+        let event = self.event.event_input();
+        let replayed = self.replayer.run(|| {
+            plugin
+                .parse_event(event, &parse_input)
+                .map_err(|e| format!("{:#}", e))
+        })?;
+
+        let recorded = match self.replayer.state.next_record() {
+            Some(TapeRecord::ParseResult { error }) => error.map_or(Ok(()), Err),
+            other => return Err(self.replayer.diverge(other)),
+        };
+        Ok(ParseOutcome { recorded, replayed })
+    }
+}
+
+/// # The outcome of a replayed event parsing request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOutcome {
+    /// The result of parsing the event when recording
+    pub recorded: Result<(), String>,
+    /// The result of parsing the event when replaying
+    pub replayed: Result<(), String>,
+}
+
+impl ParseOutcome {
+    /// Check if the replay returned the same result as the recording
+    pub fn matches(&self) -> bool {
+        self.recorded == self.replayed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_table, get_table_size, Replayer};
+    use crate::plugin::record::tape::{HostCall, HostReturn, TapeRecord};
+    use std::ptr::null_mut;
+
+    fn get_size(name: &std::ffi::CStr) -> u64 {
+        unsafe {
+            let table = get_table(null_mut(), name.as_ptr(), 8);
+            get_table_size(table)
+        }
+    }
+
+    #[test]
+    fn test_replay_calls() {
+        let mut replayer = Replayer::from_records([
+            TapeRecord::Call {
+                call: HostCall::GetTable {
+                    name: String::from("threads"),
+                    key_type: 8,
+                },
+                ret: HostReturn::Handle(1),
+            },
+            TapeRecord::Call {
+                call: HostCall::GetTableSize { table: 1 },
+                ret: HostReturn::Size(5),
+            },
+            TapeRecord::Call {
+                call: HostCall::GetTable {
+                    name: String::from("threads"),
+                    key_type: 8,
+                },
+                ret: HostReturn::Handle(1),
+            },
+        ]);
+
+        assert_eq!(replayer.run(|| get_size(c"threads")).unwrap(), 5);
+
+        let err = replayer.run(|| get_size(c"fds")).unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{}", err);
+
+        // the divergence is sticky
+        assert!(replayer.run(|| ()).is_err());
+        assert!(replayer.next_step().is_err());
+    }
+}
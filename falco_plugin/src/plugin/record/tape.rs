@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+
+/// # A table, field or entry handle, as stored on the tape
+///
+/// Raw pointers from the host are replaced with small sequential numbers, so that the tape
+/// does not depend on the memory layout of the recording process. `0` stands for a null pointer.
+pub type HandleId = u64;
+
+/// # A single call from the plugin to a host vtable
+///
+/// Only the arguments are stored here, the value returned by the host is stored
+/// as a [`HostReturn`] next to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum HostCall {
+    ListTables,
+    GetTable {
+        name: String,
+        key_type: u32,
+    },
+    AddTable {
+        name: String,
+        key_type: u32,
+    },
+    ListTableFields {
+        table: HandleId,
+    },
+    GetTableField {
+        table: HandleId,
+        name: String,
+        data_type: u32,
+    },
+    AddTableField {
+        table: HandleId,
+        name: String,
+        data_type: u32,
+    },
+    GetTableName {
+        table: HandleId,
+    },
+    GetTableSize {
+        table: HandleId,
+    },
+    GetTableEntry {
+        table: HandleId,
+        key: serde_json::Value,
+    },
+    ReadEntryField {
+        table: HandleId,
+        entry: HandleId,
+        field: HandleId,
+    },
+    ReleaseTableEntry {
+        table: HandleId,
+        entry: HandleId,
+    },
+    IterateEntries {
+        table: HandleId,
+    },
+    ClearTable {
+        table: HandleId,
+    },
+    EraseTableEntry {
+        table: HandleId,
+        key: serde_json::Value,
+    },
+    CreateTableEntry {
+        table: HandleId,
+    },
+    DestroyTableEntry {
+        table: HandleId,
+        entry: HandleId,
+    },
+    AddTableEntry {
+        table: HandleId,
+        key: serde_json::Value,
+        entry: HandleId,
+    },
+    WriteEntryField {
+        table: HandleId,
+        entry: HandleId,
+        field: HandleId,
+        value: serde_json::Value,
+    },
+}
+
+/// # The value returned by the host for a [`HostCall`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HostReturn {
+    /// The call does not return anything
+    Nothing,
+    /// A table, field or entry handle (`0` for a null pointer)
+    Handle(HandleId),
+    /// A status code
+    Rc(i32),
+    /// A field value read from an entry
+    ///
+    /// Table-valued fields are stored as `{"table": <handle>, "key_type": <type>}`.
+    Value {
+        /// The status code
+        rc: i32,
+        /// The value (`null` if the read failed)
+        value: serde_json::Value,
+    },
+    /// A table name
+    Name(Option<String>),
+    /// A table size
+    Size(u64),
+    /// The result of an iteration over table entries
+    Bool(bool),
+    /// The list of tables
+    Tables(Vec<TableInfo>),
+    /// The list of fields of a table
+    Fields(Vec<FieldInfo>),
+}
+
+/// # A table description returned by [`HostCall::ListTables`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct TableInfo {
+    pub name: String,
+    pub key_type: u32,
+}
+
+/// # A field description returned by [`HostCall::ListTableFields`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct FieldInfo {
+    pub name: String,
+    pub field_type: u32,
+    pub read_only: bool,
+}
+
+/// # The argument of a field extraction request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractArg {
+    /// No argument
+    None,
+    /// A numeric argument, e.g. `field[1]`
+    Index(u64),
+    /// A string argument, e.g. `field[foo]`
+    Key(String),
+}
+
+/// # A single field in a recorded extraction request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractField {
+    /// The field index in `EXTRACT_FIELDS`
+    pub id: u32,
+    /// The field name
+    pub name: String,
+    /// The field argument
+    pub arg: ExtractArg,
+    /// The field type, as requested by the framework
+    pub ftype: u32,
+    /// Whether the framework expects a list of values
+    pub flist: bool,
+}
+
+/// # A single record of a vtable interaction log
+///
+/// A tape is a sequence of records, stored as JSON lines:
+/// ```json
+/// {"kind":"init","plugin":"dummy","config":""}
+/// {"kind":"call","call":{"op":"get_table","name":"threads","key_type":4},"ret":{"handle":1}}
+/// {"kind":"extract","evtnum":1,"source":"syscall","event":"...","fields":[...]}
+/// {"kind":"call","call":{"op":"get_table_entry","table":1,"key":1},"ret":{"handle":3}}
+/// {"kind":"extract_result","values":["1"],"error":null}
+/// ```
+///
+/// Every plugin invocation (`init`, `extract` or `parse`) is followed by all host calls
+/// made by the plugin while handling it, in the order they were made, and by the outcome
+/// of the invocation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TapeRecord {
+    /// The plugin was initialized
+    Init {
+        /// The plugin name
+        plugin: String,
+        /// The config string passed to the plugin (without the `sdk_record` key)
+        config: String,
+    },
+    /// A host vtable call
+    Call {
+        /// The call arguments
+        call: HostCall,
+        /// The value returned by the host
+        ret: HostReturn,
+    },
+    /// The host invoked the iteration callback for an entry
+    ///
+    /// The records for all entries (and the calls made from the callback) come before
+    /// the [`HostCall::IterateEntries`] record, which is stored when the iteration completes.
+    IterateEntry {
+        /// The table being iterated
+        table: HandleId,
+        /// The entry passed to the callback
+        entry: HandleId,
+    },
+    /// The plugin was asked to extract fields from an event
+    Extract {
+        /// The event number
+        evtnum: u64,
+        /// The event source name
+        source: Option<String>,
+        /// The raw event (including the header), hex-encoded
+        event: String,
+        /// The requested fields
+        fields: Vec<ExtractField>,
+    },
+    /// The outcome of a field extraction
+    ExtractResult {
+        /// The formatted values of all requested fields (empty on error)
+        values: Vec<String>,
+        /// The error returned by the plugin
+        error: Option<String>,
+    },
+    /// The plugin was asked to parse an event
+    Parse {
+        /// The event number
+        evtnum: u64,
+        /// The event source name
+        source: Option<String>,
+        /// The raw event (including the header), hex-encoded
+        event: String,
+    },
+    /// The outcome of event parsing
+    ParseResult {
+        /// The error returned by the plugin
+        error: Option<String>,
+    },
+}
+
+pub(crate) fn to_hex(buf: &[u8]) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::with_capacity(buf.len() * 2);
+    for b in buf {
+        let _ = write!(out, "{:02x}", b);
+    }
+    out
+}
+
+pub(crate) fn from_hex(hex: &str) -> Result<Vec<u8>, anyhow::Error> {
+    if !hex.len().is_multiple_of(2) {
+        anyhow::bail!("odd number of hex digits");
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(|| anyhow::anyhow!("invalid hex digits at offset {}", i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_hex, to_hex, HostCall, HostReturn, TapeRecord};
+    use serde_json::json;
+
+    #[test]
+    fn test_hex_roundtrip() {
+        assert_eq!(to_hex(&[0, 0x7f, 0xff]), "007fff");
+        assert_eq!(from_hex("007fff").unwrap(), [0, 0x7f, 0xff]);
+        assert!(from_hex("0").is_err());
+        assert!(from_hex("zz").is_err());
+    }
+
+    #[test]
+    fn test_record_format() {
+        let record = TapeRecord::Call {
+            call: HostCall::GetTableEntry {
+                table: 1,
+                key: json!(5),
+            },
+            ret: HostReturn::Handle(3),
+        };
+
+        let line = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            line,
+            r#"{"kind":"call","call":{"op":"get_table_entry","table":1,"key":5},"ret":{"handle":3}}"#
+        );
+        assert_eq!(serde_json::from_str::<TapeRecord>(&line).unwrap(), record);
+
+        let line = r#"{"kind":"call","call":{"op":"list_tables"},"ret":"nothing"}"#;
+        assert!(matches!(
+            serde_json::from_str::<TapeRecord>(line).unwrap(),
+            TapeRecord::Call {
+                call: HostCall::ListTables,
+                ret: HostReturn::Nothing
+            }
+        ));
+    }
+}
@@ -6,10 +6,16 @@ use std::ffi::{CStr, CString};
 use std::sync::Mutex;
 use thiserror::Error;
 
+/// # An error parsing the plugin configuration
 #[derive(Error, Debug)]
 pub enum SchemaError {
+    /// The configuration was not valid JSON or did not match the expected type
     #[error("JSON deserialization error: {0}")]
     JsonError(#[from] serde_json::Error),
+
+    /// The configuration contained keys not known to the plugin (see [`StrictJson`])
+    #[error("unknown configuration keys: {}", .0.join(", "))]
+    UnknownKeys(Vec<String>),
 }
 
 pub type SchemaResult<T> = Result<T, SchemaError>;
@@ -26,46 +32,126 @@ pub enum ConfigSchemaType {
 #[derive(Debug)]
 pub struct Json<T: JsonSchema + DeserializeOwned>(pub T);
 
+/// A wrapper to mark a configuration schema as JSON-encoded, rejecting unknown keys
+///
+/// This works just like [`Json`], except that keys not recognized by the configuration type
+/// (at any nesting level) make plugin initialization fail with an error listing all of them,
+/// so typos in the configuration are caught at load time instead of being silently ignored.
+///
+/// The generated schema is the same as for [`Json`] (the check happens while deserializing
+/// the configuration), so the SDK-level keys (like `sdk_event_types`) are still accepted.
+#[derive(Debug)]
+pub struct StrictJson<T: JsonSchema + DeserializeOwned>(pub T);
+
 pub trait ConfigSchema: Sized {
     fn get_schema() -> ConfigSchemaType;
 
     fn from_str(s: &str) -> SchemaResult<Self>;
+
+    /// Build the configuration from an already parsed JSON value
+    ///
+    /// This is used when the SDK has parsed the configuration already (e.g. to remove
+    /// SDK-level keys). The default implementation formats the value back into a string.
+    fn from_value(value: serde_json::Value) -> SchemaResult<Self> {
+        Self::from_str(&value.to_string())
+    }
+}
+
+fn json_schema<T: JsonSchema + 'static>() -> ConfigSchemaType {
+    static CONFIG_SCHEMA: Mutex<BTreeMap<TypeId, CString>> = Mutex::new(BTreeMap::new());
+
+    let ty = TypeId::of::<T>();
+    let mut schema_map = CONFIG_SCHEMA.lock().unwrap();
+    // Safety:
+    //
+    // we only generate the string once and never change or delete it
+    // so the pointer should remain valid for the static lifetime
+    // hence the dance of converting a reference to a raw pointer and back
+    // to erase the lifetime
+    let ptr = unsafe {
+        CStr::from_ptr(
+            schema_map
+                .entry(ty)
+                .or_insert_with(|| {
+                    let schema = schema_for!(T);
+                    let schema = serde_json::to_string_pretty(&schema)
+                        .expect("failed to serialize config schema");
+                    CString::new(schema.into_bytes()).expect("failed to add NUL to config schema")
+                })
+                .as_ptr(),
+        )
+    };
+
+    ConfigSchemaType::Json(ptr)
 }
 
 impl<T: JsonSchema + DeserializeOwned + 'static> ConfigSchema for Json<T> {
     fn get_schema() -> ConfigSchemaType {
-        static CONFIG_SCHEMA: Mutex<BTreeMap<TypeId, CString>> = Mutex::new(BTreeMap::new());
-
-        let ty = TypeId::of::<Self>();
-        let mut schema_map = CONFIG_SCHEMA.lock().unwrap();
-        // Safety:
-        //
-        // we only generate the string once and never change or delete it
-        // so the pointer should remain valid for the static lifetime
-        // hence the dance of converting a reference to a raw pointer and back
-        // to erase the lifetime
-        let ptr = unsafe {
-            CStr::from_ptr(
-                schema_map
-                    .entry(ty)
-                    .or_insert_with(|| {
-                        let schema = schema_for!(T);
-                        let schema = serde_json::to_string_pretty(&schema)
-                            .expect("failed to serialize config schema");
-                        CString::new(schema.into_bytes())
-                            .expect("failed to add NUL to config schema")
-                    })
-                    .as_ptr(),
-            )
-        };
-
-        ConfigSchemaType::Json(ptr)
+        json_schema::<T>()
     }
 
     fn from_str(s: &str) -> SchemaResult<Self> {
         let target: T = serde_json::from_str(s)?;
         Ok(Json(target))
     }
+
+    fn from_value(value: serde_json::Value) -> SchemaResult<Self> {
+        let target: T = serde_json::from_value(value)?;
+        Ok(Json(target))
+    }
+}
+
+/// Format the path of an unknown key like `inner.port`
+///
+/// Unlike the [`Display`](`std::fmt::Display`) impl of [`serde_ignored::Path`], this skips
+/// `Option`s and newtypes, which do not show up in the configuration.
+fn key_path(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+
+    let (parent, key) = match path {
+        Path::Root => return String::new(),
+        Path::Seq { parent, index } => (parent, index.to_string()),
+        Path::Map { parent, key } => (parent, key.clone()),
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => return key_path(parent),
+    };
+
+    match key_path(parent) {
+        parent if parent.is_empty() => key,
+        parent => format!("{}.{}", parent, key),
+    }
+}
+
+impl<T: JsonSchema + DeserializeOwned + 'static> ConfigSchema for StrictJson<T> {
+    fn get_schema() -> ConfigSchemaType {
+        json_schema::<T>()
+    }
+
+    fn from_str(s: &str) -> SchemaResult<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(s);
+        let target = strict_deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(target)
+    }
+
+    fn from_value(value: serde_json::Value) -> SchemaResult<Self> {
+        strict_deserialize(value)
+    }
+}
+
+fn strict_deserialize<'de, T, D>(deserializer: D) -> SchemaResult<StrictJson<T>>
+where
+    T: JsonSchema + DeserializeOwned,
+    D: serde::Deserializer<'de, Error = serde_json::Error>,
+{
+    let mut unknown = Vec::new();
+    let target: T = serde_ignored::deserialize(deserializer, |path| unknown.push(key_path(&path)))?;
+
+    if !unknown.is_empty() {
+        return Err(SchemaError::UnknownKeys(unknown));
+    }
+    Ok(StrictJson(target))
 }
 
 impl ConfigSchema for String {
@@ -87,3 +173,74 @@ impl ConfigSchema for () {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigSchema, Json, SchemaError, StrictJson};
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+
+    #[derive(Debug, JsonSchema, Deserialize)]
+    struct Inner {
+        port: u16,
+    }
+
+    #[derive(Debug, JsonSchema, Deserialize)]
+    struct Config {
+        name: String,
+        #[serde(default)]
+        inner: Option<Inner>,
+    }
+
+    #[test]
+    fn test_strict_json_accepts_known_keys() {
+        let StrictJson(config) =
+            StrictJson::<Config>::from_str(r#"{"name": "foo", "inner": {"port": 80}}"#).unwrap();
+        assert_eq!(config.name, "foo");
+        assert_eq!(config.inner.unwrap().port, 80);
+    }
+
+    #[test]
+    fn test_strict_json_rejects_unknown_keys() {
+        let config = r#"{"name": "foo", "nmae": "bar", "inner": {"port": 80, "prot": 81}}"#;
+
+        // the default wrapper ignores them
+        assert!(Json::<Config>::from_str(config).is_ok());
+
+        let err = StrictJson::<Config>::from_str(config).unwrap_err();
+        let SchemaError::UnknownKeys(keys) = &err else {
+            panic!("unexpected error {:?}", err);
+        };
+        assert_eq!(keys, &["nmae", "inner.prot"]);
+        assert_eq!(
+            err.to_string(),
+            "unknown configuration keys: nmae, inner.prot"
+        );
+    }
+
+    #[test]
+    fn test_strict_json_invalid() {
+        assert!(matches!(
+            StrictJson::<Config>::from_str(r#"{"inner": {}}"#),
+            Err(SchemaError::JsonError(_))
+        ));
+        assert!(matches!(
+            StrictJson::<Config>::from_str(r#"{"name": "foo"} trailing"#),
+            Err(SchemaError::JsonError(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_value() {
+        let value = serde_json::json!({"name": "foo", "inner": {"port": 80, "prot": 81}});
+
+        let config = Json::<Config>::from_value(value.clone()).unwrap().0;
+        assert_eq!(config.inner.unwrap().port, 80);
+
+        let err = StrictJson::<Config>::from_value(value).unwrap_err();
+        let SchemaError::UnknownKeys(keys) = &err else {
+            panic!("unexpected error {:?}", err);
+        };
+        assert_eq!(keys, &["inner.prot"]);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::source::EventBatch;
+use crate::FailureReason;
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender};
+use std::time::{Duration, Instant};
+
+/// The default maximum number of items in a batch
+pub const DEFAULT_MAX_EVENTS: usize = 512;
+
+/// The default time to wait for a batch to fill up
+pub const DEFAULT_DEADLINE: Duration = Duration::from_millis(20);
+
+/// # Deadline-driven batching for streaming sources
+///
+/// Sources that receive events one by one (from a socket, a subscription callback,
+/// a background thread etc.) have to balance latency against batch size: returning
+/// every event in its own batch is expensive, while waiting for a full batch may delay
+/// events indefinitely when traffic is low.
+///
+/// A `DeadlineBatcher` receives items through a bounded channel and fills each batch
+/// until either `max_events` items have been added, or the deadline (measured from the start
+/// of [`DeadlineBatcher::next_batch`]) has passed, whichever comes first.
+///
+/// The producer side is a plain [`SyncSender`], so it can be moved to any thread.
+/// When the queue is full, the producers block until `next_batch` catches up.
+///
+/// ```
+/// use falco_plugin::source::DeadlineBatcher;
+/// use std::time::Duration;
+///
+/// let (tx, batcher) = DeadlineBatcher::<Vec<u8>>::new(1024);
+/// let mut batcher = batcher.with_max_events(128).with_deadline(Duration::from_millis(50));
+///
+/// std::thread::spawn(move || {
+///     for i in 0..10u64 {
+///         if tx.send(i.to_le_bytes().to_vec()).is_err() {
+///             break;
+///         }
+///     }
+/// });
+///
+/// // in next_batch:
+/// // batcher.next_batch(batch, |batch, payload| {
+/// //     batch.add(Self::plugin_event(&payload))?;
+/// //     Ok(())
+/// // })
+/// # drop(batcher);
+/// ```
+#[derive(Debug)]
+pub struct DeadlineBatcher<T> {
+    rx: Receiver<T>,
+    max_events: usize,
+    deadline: Duration,
+}
+
+impl<T> DeadlineBatcher<T> {
+    /// Create a batcher with a queue of up to `queue_size` items
+    ///
+    /// Returns the sending side of the queue along with the batcher. The batcher uses
+    /// [`DEFAULT_MAX_EVENTS`] and [`DEFAULT_DEADLINE`] unless configured otherwise.
+    pub fn new(queue_size: usize) -> (SyncSender<T>, Self) {
+        let (tx, rx) = sync_channel(queue_size);
+        (
+            tx,
+            Self {
+                rx,
+                max_events: DEFAULT_MAX_EVENTS,
+                deadline: DEFAULT_DEADLINE,
+            },
+        )
+    }
+
+    /// Set the maximum number of items in a single batch
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = max_events.max(1);
+        self
+    }
+
+    /// Set the maximum time to wait for a batch to fill up
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Fill a batch with items received from the queue
+    ///
+    /// Every item is converted to an event (or events) and added to the batch by `add`.
+    /// Returns as soon as `max_events` items have been added or the deadline has passed.
+    ///
+    /// Returns [`FailureReason::Timeout`] if no items arrived before the deadline
+    /// and [`FailureReason::Eof`] when all the senders have been dropped and the queue
+    /// is empty, so the result can be returned from
+    /// [`next_batch`](`crate::source::SourcePluginInstance::next_batch`) directly.
+    pub fn next_batch<F>(&mut self, batch: &mut EventBatch, mut add: F) -> Result<(), anyhow::Error>
+    where
+        F: FnMut(&mut EventBatch, T) -> Result<(), anyhow::Error>,
+    {
+        let deadline = Instant::now() + self.deadline;
+        let mut count = 0;
+
+        while count < self.max_events {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.rx.recv_timeout(timeout) {
+                Ok(item) => {
+                    add(batch, item)?;
+                    count += 1;
+                }
+                Err(_) if count > 0 => break,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(anyhow::anyhow!("no events before the deadline")
+                        .context(FailureReason::Timeout))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(anyhow::anyhow!("all senders finished").context(FailureReason::Eof))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeadlineBatcher;
+    use crate::source::EventBatch;
+    use crate::FailureReason;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_max_events() {
+        let (tx, batcher) = DeadlineBatcher::new(16);
+        let mut batcher = batcher
+            .with_max_events(4)
+            .with_deadline(Duration::from_secs(5));
+        for i in 0..10u32 {
+            tx.send(i).unwrap();
+        }
+
+        let mut alloc = bumpalo::Bump::new();
+        let mut batch = EventBatch::new(&mut alloc);
+        let mut items = Vec::new();
+        let start = Instant::now();
+        batcher
+            .next_batch(&mut batch, |_, item| {
+                items.push(item);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(items, [0, 1, 2, 3]);
+        // a full batch does not wait for the deadline
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_deadline() {
+        let (tx, batcher) = DeadlineBatcher::new(16);
+        let mut batcher = batcher.with_deadline(Duration::from_millis(50));
+        tx.send(1u32).unwrap();
+
+        let mut alloc = bumpalo::Bump::new();
+        let mut batch = EventBatch::new(&mut alloc);
+        let mut items = Vec::new();
+        let start = Instant::now();
+        batcher
+            .next_batch(&mut batch, |_, item| {
+                items.push(item);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(items, [1]);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        let err = batcher.next_batch(&mut batch, |_, _| Ok(())).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FailureReason>(),
+            Some(FailureReason::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_eof() {
+        let (tx, batcher) = DeadlineBatcher::new(16);
+        let mut batcher = batcher.with_deadline(Duration::from_secs(5));
+        tx.send(1u32).unwrap();
+        tx.send(2u32).unwrap();
+        drop(tx);
+
+        let mut alloc = bumpalo::Bump::new();
+        let mut batch = EventBatch::new(&mut alloc);
+        let mut items = Vec::new();
+        // the remaining items are returned without waiting for the deadline
+        batcher
+            .next_batch(&mut batch, |_, item| {
+                items.push(item);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(items, [1, 2]);
+
+        let err = batcher.next_batch(&mut batch, |_, _| Ok(())).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FailureReason>(),
+            Some(FailureReason::Eof)
+        ));
+    }
+}
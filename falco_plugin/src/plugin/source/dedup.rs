@@ -0,0 +1,177 @@
+use crate::source::PluginEvent;
+use falco_event::events::RawEvent;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// # Dropping duplicate events in a source plugin
+///
+/// Some event sources (e.g. log files read by several collectors, or at-least-once message
+/// queues) can deliver the same record more than once. With a `DedupPolicy` set in
+/// [`SourcePlugin::DEDUP_POLICY`](`crate::source::SourcePlugin::DEDUP_POLICY`), the SDK
+/// remembers the keys of recent events of each open instance and silently drops
+/// any [`PluginEvent`] whose key has been seen within the window, before it enters the batch.
+///
+/// The key is calculated by `key` from the event data (the payload of the plugin event).
+/// Events for which it returns `None` are never considered duplicates. Events other
+/// than [`PluginEvent`] are not deduplicated.
+///
+/// A key is remembered for at most `max_age` since the first time it was seen, and at most
+/// `max_entries` keys are remembered (the oldest ones are forgotten first), so the memory used
+/// by the window stays bounded.
+///
+/// The number of dropped events is reported in the `sdk.dedup_dropped` metric.
+///
+/// ```
+/// use falco_plugin::source::DedupPolicy;
+/// use std::hash::{DefaultHasher, Hash, Hasher};
+/// use std::time::Duration;
+///
+/// fn hash_line(data: &[u8]) -> Option<u64> {
+///     let mut hasher = DefaultHasher::new();
+///     data.hash(&mut hasher);
+///     Some(hasher.finish())
+/// }
+///
+/// const DEDUP_POLICY: Option<DedupPolicy> = Some(DedupPolicy {
+///     key: hash_line,
+///     max_entries: 10000,
+///     max_age: Duration::from_secs(60),
+/// });
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DedupPolicy {
+    /// Calculate the deduplication key of the event data
+    pub key: fn(&[u8]) -> Option<u64>,
+    /// The maximum number of keys to remember
+    pub max_entries: usize,
+    /// The maximum time to remember a key for
+    pub max_age: Duration,
+}
+
+#[derive(Debug)]
+pub(crate) struct DedupWindow {
+    policy: DedupPolicy,
+    seen: HashMap<u64, Instant>,
+    order: VecDeque<(u64, Instant)>,
+    dropped: u64,
+}
+
+impl DedupWindow {
+    pub(crate) fn new(policy: DedupPolicy) -> Self {
+        Self {
+            policy,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some(&(key, first_seen)) = self.order.front() {
+            if self.order.len() <= self.policy.max_entries
+                && now.duration_since(first_seen) < self.policy.max_age
+            {
+                break;
+            }
+            self.order.pop_front();
+            self.seen.remove(&key);
+        }
+    }
+
+    /// Check whether the event data is a duplicate of a recently seen one
+    pub(crate) fn is_duplicate_data(&mut self, data: &[u8], now: Instant) -> bool {
+        let Some(key) = (self.policy.key)(data) else {
+            return false;
+        };
+
+        self.expire(now);
+        if self.seen.contains_key(&key) {
+            self.dropped += 1;
+            return true;
+        }
+
+        self.seen.insert(key, now);
+        self.order.push_back((key, now));
+        self.expire(now);
+        false
+    }
+
+    /// Check whether a serialized event is a duplicate of a recently seen one
+    pub(crate) fn is_duplicate(&mut self, event: &[u8]) -> bool {
+        let Ok(event) = RawEvent::from(event) else {
+            return false;
+        };
+        let Ok(event) = event.load::<PluginEvent>() else {
+            return false;
+        };
+
+        let data = event.params.event_data.unwrap_or_default();
+        self.is_duplicate_data(data, Instant::now())
+    }
+
+    /// Return the number of dropped events since the last call
+    pub(crate) fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DedupPolicy, DedupWindow};
+    use std::time::{Duration, Instant};
+
+    fn first_byte(data: &[u8]) -> Option<u64> {
+        data.first().map(|b| *b as u64)
+    }
+
+    fn policy(max_entries: usize, max_age: Duration) -> DedupPolicy {
+        DedupPolicy {
+            key: first_byte,
+            max_entries,
+            max_age,
+        }
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut window = DedupWindow::new(policy(100, Duration::from_secs(10)));
+        let now = Instant::now();
+
+        assert!(!window.is_duplicate_data(b"a1", now));
+        assert!(!window.is_duplicate_data(b"b1", now));
+        assert!(window.is_duplicate_data(b"a2", now));
+        // no key, never a duplicate
+        assert!(!window.is_duplicate_data(b"", now));
+        assert!(!window.is_duplicate_data(b"", now));
+
+        assert_eq!(window.take_dropped(), 1);
+        assert_eq!(window.take_dropped(), 0);
+    }
+
+    #[test]
+    fn test_dedup_max_age() {
+        let mut window = DedupWindow::new(policy(100, Duration::from_secs(10)));
+        let now = Instant::now();
+
+        assert!(!window.is_duplicate_data(b"a", now));
+        assert!(window.is_duplicate_data(b"a", now + Duration::from_secs(9)));
+        // the window is measured from the first occurrence
+        assert!(!window.is_duplicate_data(b"a", now + Duration::from_secs(10)));
+        assert!(window.is_duplicate_data(b"a", now + Duration::from_secs(11)));
+    }
+
+    #[test]
+    fn test_dedup_max_entries() {
+        let mut window = DedupWindow::new(policy(2, Duration::from_secs(10)));
+        let now = Instant::now();
+
+        assert!(!window.is_duplicate_data(b"a", now));
+        assert!(!window.is_duplicate_data(b"b", now));
+        assert!(!window.is_duplicate_data(b"c", now));
+        assert_eq!(window.seen.len(), 2);
+
+        // `a` was forgotten to make room for `c`
+        assert!(!window.is_duplicate_data(b"a", now));
+        assert!(window.is_duplicate_data(b"c", now));
+    }
+}
@@ -1,5 +1,10 @@
+use crate::plugin::base::storage_stats::{try_reserve, BumpWriter};
+use crate::plugin::source::dedup::DedupWindow;
+use crate::plugin::source::timestamps::MonotonicTimestamps;
 use falco_event::events::EventToBytes;
 
+const BATCH_STORAGE: &str = "batch storage";
+
 /// # An object that describes a batch of events
 ///
 /// This is only available by reference, not by ownership, since the data needs to outlive
@@ -9,12 +14,39 @@ use falco_event::events::EventToBytes;
 pub struct EventBatch<'a> {
     alloc: &'a bumpalo::Bump,
     pointers: bumpalo::collections::Vec<'a, *const u8>,
+    timestamps: Option<&'a mut MonotonicTimestamps>,
+    dedup: Option<&'a mut DedupWindow>,
+    bytes: usize,
 }
 
-impl EventBatch<'_> {
+impl<'a> EventBatch<'a> {
     pub(in crate::plugin::source) fn new(alloc: &mut bumpalo::Bump) -> EventBatch {
         let pointers = bumpalo::collections::Vec::new_in(alloc);
-        EventBatch { alloc, pointers }
+        EventBatch {
+            alloc,
+            pointers,
+            timestamps: None,
+            dedup: None,
+            bytes: 0,
+        }
+    }
+
+    pub(in crate::plugin::source) fn with_timestamps(
+        mut self,
+        timestamps: &'a mut MonotonicTimestamps,
+    ) -> Self {
+        if timestamps.is_enabled() {
+            self.timestamps = Some(timestamps);
+        }
+        self
+    }
+
+    pub(in crate::plugin::source) fn with_dedup(
+        mut self,
+        dedup: Option<&'a mut DedupWindow>,
+    ) -> Self {
+        self.dedup = dedup;
+        self
     }
 
     /// # Add an event to a batch
@@ -26,9 +58,30 @@ impl EventBatch<'_> {
     /// **Note**: to generate such events, you may use
     /// the [`source::SourcePluginInstance::plugin_event`](`crate::source::SourcePluginInstance::plugin_event`)
     /// helper method.
+    ///
+    /// **Note**: if the plugin has a [`DedupPolicy`](`crate::source::DedupPolicy`), duplicate
+    /// events are silently dropped here.
+    ///
+    /// If the batch storage limit is exceeded, an error of kind
+    /// [`std::io::ErrorKind::OutOfMemory`] is returned, wrapping a
+    /// [`StorageError`](`crate::base::StorageError`).
     pub fn add(&mut self, event: impl EventToBytes) -> std::io::Result<()> {
         let mut event_buf = bumpalo::collections::Vec::new_in(self.alloc);
-        event.write(&mut event_buf)?;
+        event.write(BumpWriter::new(&mut event_buf, BATCH_STORAGE))?;
+        if let Some(dedup) = self.dedup.as_deref_mut() {
+            if dedup.is_duplicate(&event_buf) {
+                return Ok(());
+            }
+        }
+        if let Some(timestamps) = self.timestamps.as_deref_mut() {
+            // the timestamp is the first field of the event header
+            if let Some(ts_buf) = event_buf.first_chunk_mut::<8>() {
+                let ts = timestamps.adjust(u64::from_ne_bytes(*ts_buf));
+                *ts_buf = ts.to_ne_bytes();
+            }
+        }
+        try_reserve(&mut self.pointers, 1, BATCH_STORAGE)?;
+        self.bytes += event_buf.len();
         self.pointers.push(event_buf.as_ptr());
         Ok(())
     }
@@ -43,10 +96,164 @@ impl EventBatch<'_> {
     /// than the reserved size, but that mostly defeats the purpose of reserving
     /// space
     pub fn reserve(&mut self, num_events: usize) {
-        self.pointers.reserve(num_events);
+        // a failure here will be reported when adding the events
+        let _ = self.pointers.try_reserve(num_events);
+    }
+
+    /// # Add events generated from an iterator
+    ///
+    /// This pulls items from `iter`, converts each of them into an event using `map_fn`
+    /// and adds the event to the batch, until the batch contains `max_events` events
+    /// or the iterator is exhausted. It returns the number of items consumed from the iterator.
+    ///
+    /// The iterator is taken by mutable reference, so no items are lost when the batch fills up:
+    /// keep the iterator in your instance and call this method again from the next call
+    /// to `next_batch` to continue where you left off.
+    ///
+    /// If `map_fn` fails, the error is returned immediately. The events added before
+    /// the failure remain in the batch and the failing item has already been consumed
+    /// from the iterator.
+    ///
+    /// ```ignore
+    /// fn next_batch(
+    ///     &mut self,
+    ///     plugin: &mut Self::Plugin,
+    ///     batch: &mut EventBatch,
+    /// ) -> Result<(), anyhow::Error> {
+    ///     let n = batch.extend_from_iter(&mut self.records, 512, |record| {
+    ///         Ok(Self::plugin_event(record.serialize()?.as_slice()))
+    ///     })?;
+    ///     if n == 0 {
+    ///         anyhow::bail!(FailureReason::Eof);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn extend_from_iter<T, E, F>(
+        &mut self,
+        iter: &mut impl Iterator<Item = T>,
+        max_events: usize,
+        mut map_fn: F,
+    ) -> Result<usize, anyhow::Error>
+    where
+        E: EventToBytes,
+        F: FnMut(T) -> Result<E, anyhow::Error>,
+    {
+        let mut consumed = 0;
+        while self.len() < max_events {
+            let Some(item) = iter.next() else {
+                break;
+            };
+            consumed += 1;
+            self.add(map_fn(item)?)?;
+        }
+
+        Ok(consumed)
+    }
+
+    /// # Return the number of events in the batch
+    pub fn len(&self) -> usize {
+        self.pointers.len()
+    }
+
+    /// # Check if the batch is empty
+    pub fn is_empty(&self) -> bool {
+        self.pointers.is_empty()
+    }
+
+    pub(in crate::plugin::source) fn allocated_bytes(&self) -> usize {
+        self.alloc.allocated_bytes()
+    }
+
+    pub(in crate::plugin::source) fn event_bytes(&self) -> usize {
+        self.bytes
     }
 
     pub(in crate::plugin::source) fn get_events(&self) -> &[*const u8] {
         self.pointers.as_slice()
     }
+
+    pub(in crate::plugin::source) fn take_dedup_dropped(&mut self) -> Option<u64> {
+        self.dedup.as_deref_mut().map(DedupWindow::take_dropped)
+    }
+
+    pub(in crate::plugin::source) fn take_timestamp_clamps(&mut self) -> Option<u64> {
+        self.timestamps
+            .as_deref_mut()
+            .map(MonotonicTimestamps::take_clamps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventBatch;
+    use crate::source::PluginEvent;
+    use falco_event::events::{Event, EventMetadata};
+
+    fn event(data: &[u8]) -> Event<PluginEvent> {
+        Event {
+            metadata: EventMetadata::default(),
+            params: PluginEvent {
+                plugin_id: Some(1),
+                event_data: Some(data),
+            },
+        }
+    }
+
+    #[test]
+    fn test_extend_from_iter() {
+        let mut alloc = bumpalo::Bump::new();
+        let mut batch = EventBatch::new(&mut alloc);
+        let records = [b"a", b"b", b"c"];
+        let mut iter = records.iter();
+
+        let n = batch
+            .extend_from_iter(&mut iter, 2, |r| Ok(event(r.as_slice())))
+            .unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(batch.len(), 2);
+        assert_eq!(iter.len(), 1);
+
+        let n = batch
+            .extend_from_iter(&mut iter, 4, |r| Ok(event(r.as_slice())))
+            .unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(batch.len(), 3);
+    }
+
+    #[test]
+    fn test_extend_from_iter_error() {
+        let mut alloc = bumpalo::Bump::new();
+        let mut batch = EventBatch::new(&mut alloc);
+        let mut iter = 0..10;
+
+        let res = batch.extend_from_iter(&mut iter, 10, |i| match i {
+            3 => Err(anyhow::anyhow!("bad record")),
+            _ => Ok(event(b"ok")),
+        });
+        assert!(res.is_err());
+        assert_eq!(batch.len(), 3);
+        assert_eq!(iter.next(), Some(4));
+    }
+
+    #[test]
+    fn test_allocation_limit() {
+        let mut alloc = bumpalo::Bump::new();
+        alloc.set_allocation_limit(Some(4096));
+        let mut batch = EventBatch::new(&mut alloc);
+        let data = [0u8; 256];
+
+        let err = loop {
+            if let Err(e) = batch.add(event(&data)) {
+                break e;
+            }
+            assert!(batch.len() < 100, "the allocation limit was not enforced");
+        };
+        assert_eq!(err.kind(), std::io::ErrorKind::OutOfMemory);
+        assert_eq!(
+            err.to_string(),
+            "batch storage exceeded the allocation limit of 4096 bytes"
+        );
+        assert!(batch.allocated_bytes() <= 4096);
+    }
 }
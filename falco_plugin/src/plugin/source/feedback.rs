@@ -0,0 +1,112 @@
+use std::collections::VecDeque;
+
+/// # A bounded queue for feedback from parse/extract code to the source instance
+///
+/// Some event sources can adapt their collection based on what happens to the events
+/// further down the pipeline (e.g. stop tailing a file no rules care about). When a single
+/// plugin type implements both the source capability and the parse (or extract) capability,
+/// you can store a `FeedbackQueue` in the plugin struct, [`push`](`FeedbackQueue::push`)
+/// messages from [`ParsePlugin::parse_event`](`crate::parse::ParsePlugin::parse_event`)
+/// or the extraction methods, and [`drain`](`FeedbackQueue::drain`) them in
+/// [`SourcePluginInstance::next_batch`](`crate::source::SourcePluginInstance::next_batch`),
+/// which receives a mutable reference to the plugin.
+///
+/// All the callbacks run on the same thread and get exclusive access to the plugin, so the queue
+/// does not need any synchronization. It is, however, bounded: when the queue is full,
+/// [`push`](`FeedbackQueue::push`) returns the rejected message and the number of dropped
+/// messages is tracked, so a source that does not consume feedback cannot cause unbounded
+/// memory growth.
+///
+/// ```
+/// use falco_plugin::source::FeedbackQueue;
+///
+/// enum Feedback {
+///     StopTailing(String),
+/// }
+///
+/// let mut queue = FeedbackQueue::new(16);
+///
+/// // in parse_event:
+/// let _ = queue.push(Feedback::StopTailing(String::from("/var/log/boring.log")));
+///
+/// // in next_batch:
+/// for feedback in queue.drain() {
+///     match feedback {
+///         Feedback::StopTailing(path) => assert_eq!(path, "/var/log/boring.log"),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FeedbackQueue<T> {
+    queue: VecDeque<T>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl<T> FeedbackQueue<T> {
+    /// Create a new queue, holding up to `capacity` messages
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// Add a message to the queue
+    ///
+    /// If the queue is full, the message is returned back as the error value
+    pub fn push(&mut self, message: T) -> Result<(), T> {
+        if self.queue.len() >= self.capacity {
+            self.dropped += 1;
+            return Err(message);
+        }
+
+        self.queue.push_back(message);
+        Ok(())
+    }
+
+    /// Take the oldest message from the queue, if any
+    pub fn pop(&mut self) -> Option<T> {
+        self.queue.pop_front()
+    }
+
+    /// Take all messages from the queue, oldest first
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.queue.drain(..)
+    }
+
+    /// Return the number of messages waiting in the queue
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Return true if there are no messages waiting in the queue
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Return the number of messages rejected because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FeedbackQueue;
+
+    #[test]
+    fn test_bounded() {
+        let mut queue = FeedbackQueue::new(2);
+        assert_eq!(queue.push(1), Ok(()));
+        assert_eq!(queue.push(2), Ok(()));
+        assert_eq!(queue.push(3), Err(3));
+        assert_eq!(queue.dropped(), 1);
+
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.push(4), Ok(()));
+        assert_eq!(queue.drain().collect::<Vec<_>>(), vec![2, 4]);
+        assert!(queue.is_empty());
+    }
+}
@@ -1,12 +1,25 @@
 use crate::plugin::base::Plugin;
-use crate::source::{EventBatch, EventInput};
+use crate::plugin::source::dedup::DedupWindow;
+use crate::plugin::source::timestamps::MonotonicTimestamps;
+use crate::source::{DedupPolicy, EventBatch, EventInput, TimestampPolicy};
 use falco_event::events::types::PPME_PLUGINEVENT_E as PluginEvent;
 use falco_event::events::Event;
 use falco_event::events::EventMetadata;
 use std::ffi::{CStr, CString};
 
+pub mod deadline;
+pub mod dedup;
 pub mod event_batch;
+pub mod feedback;
 pub mod open_params;
+pub mod payload;
+pub(crate) mod rates;
+pub mod registry;
+pub mod render;
+pub mod schema;
+pub mod sharded;
+pub mod sub_sources;
+pub mod timestamps;
 #[doc(hidden)]
 pub mod wrappers;
 
@@ -45,6 +58,9 @@ pub trait SourcePlugin: Plugin {
     /// > EVERY PLUGIN WITH EVENT SOURCING CAPABILITY IMPLEMENTING A SPECIFIC EVENT SOURCE MUST
     /// > OBTAIN AN OFFICIAL ID FROM THE FALCOSECURITY ORGANIZATION, OTHERWISE IT WON'T PROPERLY
     /// > COEXIST WITH OTHER PLUGINS.
+    ///
+    /// See [`warn_on_plugin_id`](`crate::source::warn_on_plugin_id`) for checking the ID
+    /// against a snapshot of the plugin registry at build time.
     const PLUGIN_ID: u32;
 
     /// # List sample open parameters
@@ -56,15 +72,34 @@ pub trait SourcePlugin: Plugin {
     /// [`crate::source::serialize_open_params`] and [`crate::source::OpenParam`] to build
     /// a description of what the [`SourcePlugin::open`] method expects.
     ///
-    /// **Note**: as of API version 3.4.0, this appears unused.
+    /// **Note**: the suggestions are only informational (e.g. shown to users listing the available
+    /// plugins). The framework does not validate the actual open parameters against this list.
     fn list_open_params(&mut self) -> Result<&CStr, anyhow::Error> {
         Ok(unsafe { CStr::from_ptr(b"\0".as_ptr().cast()) })
     }
 
     /// # Open a capture instance
     ///
-    /// This method receives the `open` parameter from Falco configuration and returns
-    /// a new instance of the source plugin.
+    /// This method receives the open parameters and returns a new instance of the source plugin.
+    ///
+    /// In Falco, the parameters come from the `open_params` key of the plugin's entry
+    /// in the configuration file:
+    ///
+    /// ```yaml
+    /// plugins:
+    ///   - name: my_plugin
+    ///     library_path: libmy_plugin.so
+    ///     init_config: ""
+    ///     open_params: "/var/log/app.jsonl"
+    /// ```
+    ///
+    /// The string is passed to the plugin verbatim (it is up to the plugin to define its format
+    /// and e.g. split it into several values). Unlike `init_config`, the open parameters
+    /// can differ between captures, since every capture opens a new instance.
+    ///
+    /// **Note**: `params` is `None` only if the framework passes no parameters at all.
+    /// Falco passes an empty string when `open_params` is not set, so treat `Some("")`
+    /// as "no parameters" too.
     fn open(&mut self, params: Option<&str>) -> Result<Self::Instance, anyhow::Error>;
 
     /// # Close a capture instance
@@ -77,7 +112,59 @@ pub trait SourcePlugin: Plugin {
     ///
     /// This string will be available as `%evt.plugininfo` in Falco rules. You may consider
     /// using the helpers from [`crate::strings`] to build the resulting CString.
-    fn event_to_string(&mut self, event: &EventInput) -> Result<CString, anyhow::Error>;
+    ///
+    /// The default implementation renders the event data using
+    /// [`render_event_data`](`crate::source::render_event_data`), limited to
+    /// [`SourcePlugin::EVENT_TO_STRING_MAX_LEN`] bytes.
+    ///
+    /// To show selected fields of the event instead, in the same `key=value` format as other
+    /// plugins, use [`KeyValueRenderer`](`crate::source::KeyValueRenderer`).
+    fn event_to_string(&mut self, event: &EventInput) -> Result<CString, anyhow::Error> {
+        let event = event.event()?;
+        let plugin_event = event.load::<PluginEvent>()?;
+        let data = plugin_event.params.event_data.unwrap_or_default();
+        Ok(render::render_event_data(
+            data,
+            Self::EVENT_TO_STRING_MAX_LEN,
+        ))
+    }
+
+    /// # Maximum length of the default event string representation
+    ///
+    /// Used by the default implementation of [`SourcePlugin::event_to_string`].
+    const EVENT_TO_STRING_MAX_LEN: usize = 256;
+
+    /// # Maximum size of the event batch storage
+    ///
+    /// The events added to an [`EventBatch`] are stored in memory owned by the SDK until
+    /// the next call to [`SourcePluginInstance::next_batch`]. This method returns the limit
+    /// (in bytes) for that storage: when a batch grows beyond it, `next_batch` fails with
+    /// an error instead of allocating without bound inside the host process.
+    ///
+    /// The limit applies to the memory allocated from the system, not just to the event data.
+    /// Consider enabling [`Plugin::STORAGE_METRICS`] to find a reasonable value.
+    ///
+    /// The default implementation returns `None` (no limit).
+    fn batch_storage_limit(&self) -> Option<usize> {
+        None
+    }
+
+    /// # Timestamp ordering policy
+    ///
+    /// Set this to [`TimestampPolicy::Clamp`] or [`TimestampPolicy::Offset`] to guarantee
+    /// that the timestamps of events generated by each open instance never decrease,
+    /// even across wall clock adjustments. See [`TimestampPolicy`] for details.
+    ///
+    /// The default is [`TimestampPolicy::PassThrough`] (no adjustments).
+    const TIMESTAMP_POLICY: TimestampPolicy = TimestampPolicy::PassThrough;
+
+    /// # Duplicate event policy
+    ///
+    /// Set this to drop duplicate events (as determined by a key calculated from the event data)
+    /// within a sliding window. See [`DedupPolicy`] for details.
+    ///
+    /// The default is `None` (no deduplication).
+    const DEDUP_POLICY: Option<DedupPolicy> = None;
 }
 
 /// Information about capture progress
@@ -92,6 +179,8 @@ pub struct ProgressInfo<'a> {
 pub(crate) struct SourcePluginInstanceWrapper<I: SourcePluginInstance> {
     pub(crate) instance: I,
     pub(crate) batch: bumpalo::Bump,
+    pub(crate) timestamps: MonotonicTimestamps,
+    pub(crate) dedup: Option<DedupWindow>,
 }
 
 /// # An open instance of a source plugin
@@ -4,7 +4,8 @@ use std::ffi::{CStr, CString};
 
 /// # Specification of open parameters for a source plugin instance
 ///
-/// **Note**: this appears unused as of API version 3.4.0
+/// A list of these, serialized with [`serialize_open_params`], describes the values
+/// accepted by [`SourcePlugin::open`](`crate::source::SourcePlugin::open`).
 #[derive(Debug)]
 pub enum OpenParam<'a> {
     /// # A single string valid as a sample open parameter
@@ -58,9 +59,21 @@ impl Serialize for OpenParam<'_> {
 /// # Serialize the open parameter specification
 ///
 /// This function can be used in [`SourcePlugin::list_open_params`](`crate::source::SourcePlugin::list_open_params`)
-/// to describe the allowed values for the instance open parameters.
+/// to describe the allowed values for the instance open parameters. The returned string
+/// is stored in `storage`, which should live in the plugin, so that it remains valid
+/// after `list_open_params` returns:
 ///
-/// **Note**: this appears unused as of API version 3.4.0
+/// ```ignore
+/// fn list_open_params(&mut self) -> Result<&CStr, anyhow::Error> {
+///     serialize_open_params(
+///         &[OpenParam::Item {
+///             value: "/var/log/app.jsonl",
+///             desc: "the file to read events from",
+///         }],
+///         &mut self.open_params_storage,
+///     )
+/// }
+/// ```
 pub fn serialize_open_params<'a>(
     params: &[OpenParam],
     storage: &'a mut CString,
@@ -70,3 +83,38 @@ pub fn serialize_open_params<'a>(
     std::mem::swap(&mut buf, storage);
     Ok(storage.as_c_str())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{serialize_open_params, OpenParam};
+    use std::ffi::CString;
+
+    #[test]
+    fn test_serialize_open_params() {
+        let mut storage = CString::default();
+        let params = serialize_open_params(
+            &[
+                OpenParam::Item {
+                    value: "/var/log/app.jsonl",
+                    desc: "a single file",
+                },
+                OpenParam::Seq {
+                    values: &["a.jsonl", "b.jsonl"],
+                    desc: "several files",
+                    separator: ';',
+                },
+            ],
+            &mut storage,
+        )
+        .unwrap();
+
+        let params: serde_json::Value = serde_json::from_str(params.to_str().unwrap()).unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!([
+                {"value": "/var/log/app.jsonl", "desc": "a single file"},
+                {"value": "a.jsonl;b.jsonl", "desc": "several files", "separator": ";"},
+            ])
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::io::Write;
+use thiserror::Error;
+
+/// # An error decoding a plugin event payload
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PayloadDecodeError {
+    /// The payload was empty (no variant tag present)
+    #[error("empty payload")]
+    Empty,
+
+    /// The variant tag did not match any known variant
+    #[error("unknown payload tag {0}")]
+    UnknownTag(u8),
+
+    /// The payload was not valid UTF-8
+    #[error("invalid UTF-8 in payload")]
+    InvalidUtf8,
+
+    /// There was unexpected data after the payload
+    #[error("{0} trailing bytes after payload")]
+    TrailingData(usize),
+
+    /// The payload was shorter than its fixed-size header
+    #[error("truncated payload: expected at least {expected} bytes, got {actual}")]
+    Truncated {
+        /// the minimum length of the payload
+        expected: usize,
+        /// the actual length of the payload
+        actual: usize,
+    },
+}
+
+/// # A type that can be stored in the data field of a plugin event
+///
+/// This trait is implemented for raw byte slices and strings (which take up the whole
+/// remaining payload) and can be derived for enums using
+/// [`PluginPayloadEnum`](`crate::source::PluginPayloadEnum`).
+///
+/// A plugin that generates several kinds of events can describe them as an enum and derive
+/// the encoding. Every variant is encoded as a single tag byte (the index of the variant,
+/// unless overridden with `#[tag = N]`), followed by the payload of the variant's field
+/// (if any). Since the encoding ends up in capture files, assign the tags explicitly if you
+/// expect to reorder the variants later.
+///
+/// The source plugin encodes the enum into the event data and the parse/extract plugins
+/// decode it back from [`PluginEvent::event_data`](`crate::source::PluginEvent`):
+///
+/// ```
+/// use falco_plugin::source::{PayloadDecodeError, PluginPayload, PluginPayloadEnum};
+///
+/// #[derive(Debug, PartialEq, PluginPayloadEnum)]
+/// enum MyEvent<'a> {
+///     Started,
+///     Message(&'a str),
+///     #[tag = 10]
+///     Raw(&'a [u8]),
+/// }
+///
+/// let buf = MyEvent::Message("hello").to_vec().unwrap();
+/// assert_eq!(buf, b"\x01hello");
+/// assert_eq!(MyEvent::decode(&buf), Ok(MyEvent::Message("hello")));
+///
+/// let buf = MyEvent::Raw(b"\x00\x01").to_vec().unwrap();
+/// assert_eq!(buf[0], 10);
+/// assert_eq!(MyEvent::decode(&buf), Ok(MyEvent::Raw(b"\x00\x01")));
+///
+/// assert_eq!(MyEvent::decode(b"\x00"), Ok(MyEvent::Started));
+/// assert_eq!(MyEvent::decode(b"\x05"), Err(PayloadDecodeError::UnknownTag(5)));
+/// ```
+pub trait PluginPayload<'a>: Sized {
+    /// Write the payload to `writer`
+    fn encode<W: Write>(&self, writer: W) -> std::io::Result<()>;
+
+    /// Decode the payload from `buf`
+    fn decode(buf: &'a [u8]) -> Result<Self, PayloadDecodeError>;
+
+    /// Encode the payload into a new byte vector
+    fn to_vec(&self) -> std::io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+impl<'a> PluginPayload<'a> for () {
+    fn encode<W: Write>(&self, _writer: W) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn decode(buf: &'a [u8]) -> Result<Self, PayloadDecodeError> {
+        match buf.len() {
+            0 => Ok(()),
+            n => Err(PayloadDecodeError::TrailingData(n)),
+        }
+    }
+}
+
+impl<'a> PluginPayload<'a> for &'a [u8] {
+    fn encode<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self)
+    }
+
+    fn decode(buf: &'a [u8]) -> Result<Self, PayloadDecodeError> {
+        Ok(buf)
+    }
+}
+
+impl<'a> PluginPayload<'a> for &'a str {
+    fn encode<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self.as_bytes())
+    }
+
+    fn decode(buf: &'a [u8]) -> Result<Self, PayloadDecodeError> {
+        std::str::from_utf8(buf).map_err(|_| PayloadDecodeError::InvalidUtf8)
+    }
+}
+
+impl<'a> PluginPayload<'a> for Vec<u8> {
+    fn encode<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self)
+    }
+
+    fn decode(buf: &'a [u8]) -> Result<Self, PayloadDecodeError> {
+        Ok(buf.to_vec())
+    }
+}
+
+impl<'a> PluginPayload<'a> for String {
+    fn encode<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self.as_bytes())
+    }
+
+    fn decode(buf: &'a [u8]) -> Result<Self, PayloadDecodeError> {
+        <&str>::decode(buf).map(String::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PayloadDecodeError, PluginPayload};
+
+    #[test]
+    fn test_str_roundtrip() {
+        let buf = "hello".to_vec().unwrap();
+        assert_eq!(<&str>::decode(&buf), Ok("hello"));
+        assert_eq!(
+            <&str>::decode(b"\xff"),
+            Err(PayloadDecodeError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn test_unit() {
+        assert_eq!(<()>::decode(b""), Ok(()));
+        assert_eq!(<()>::decode(b"x"), Err(PayloadDecodeError::TrailingData(1)));
+    }
+}
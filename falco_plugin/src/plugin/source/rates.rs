@@ -0,0 +1,105 @@
+use crate::plugin::base::metrics::{Metric, MetricLabel, MetricType, MetricValue};
+use crate::plugin::convert;
+use std::time::Instant;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Counters {
+    events: u64,
+    bytes: u64,
+    batches: u64,
+}
+
+/// Throughput of a source plugin, reported under `sdk.source.*`
+///
+/// The totals cover the whole lifetime of the plugin (across all instances), while the rates
+/// are calculated over the interval between two consecutive calls to [`SourceRates::metrics`]
+/// (i.e. between two `get_metrics` calls from the framework).
+#[derive(Debug)]
+pub(crate) struct SourceRates {
+    total: Counters,
+    window: Counters,
+    window_start: Instant,
+}
+
+impl Default for SourceRates {
+    fn default() -> Self {
+        Self {
+            total: Default::default(),
+            window: Default::default(),
+            window_start: Instant::now(),
+        }
+    }
+}
+
+impl SourceRates {
+    /// Record a successful batch of `events` events, `bytes` bytes in total
+    pub(crate) fn record(&mut self, events: usize, bytes: usize) {
+        for counters in [&mut self.total, &mut self.window] {
+            counters.events += convert::saturating::<u64, _>(events);
+            counters.bytes += convert::saturating::<u64, _>(bytes);
+            counters.batches += 1;
+        }
+    }
+
+    pub(crate) fn metrics(&mut self) -> [Metric; 7] {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.window_start).as_secs_f64();
+        let window = std::mem::take(&mut self.window);
+        self.window_start = now;
+
+        let rate = |n: u64| {
+            if elapsed > 0.0 {
+                n as f64 / elapsed
+            } else {
+                0.0
+            }
+        };
+        let avg_batch_size = match window.batches {
+            0 => 0.0,
+            batches => window.events as f64 / batches as f64,
+        };
+
+        [
+            MetricLabel::new(c"sdk.source.events", MetricType::Monotonic)
+                .with_value(MetricValue::U64(self.total.events)),
+            MetricLabel::new(c"sdk.source.bytes", MetricType::Monotonic)
+                .with_value(MetricValue::U64(self.total.bytes)),
+            MetricLabel::new(c"sdk.source.batches", MetricType::Monotonic)
+                .with_value(MetricValue::U64(self.total.batches)),
+            MetricLabel::new(c"sdk.source.events_per_sec", MetricType::NonMonotonic)
+                .with_value(MetricValue::Double(rate(window.events))),
+            MetricLabel::new(c"sdk.source.bytes_per_sec", MetricType::NonMonotonic)
+                .with_value(MetricValue::Double(rate(window.bytes))),
+            MetricLabel::new(c"sdk.source.batches_per_sec", MetricType::NonMonotonic)
+                .with_value(MetricValue::Double(rate(window.batches))),
+            MetricLabel::new(c"sdk.source.avg_batch_size", MetricType::NonMonotonic)
+                .with_value(MetricValue::Double(avg_batch_size)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SourceRates;
+
+    #[test]
+    fn test_source_rates() {
+        let mut rates = SourceRates::default();
+        rates.record(10, 1000);
+        rates.record(0, 0);
+        rates.record(20, 500);
+
+        assert_eq!(rates.total.events, 30);
+        assert_eq!(rates.total.bytes, 1500);
+        assert_eq!(rates.total.batches, 3);
+
+        let metrics = rates.metrics();
+        assert_eq!(metrics.len(), 7);
+        assert_eq!(rates.window.batches, 0);
+
+        // the totals survive a metrics call
+        rates.record(5, 50);
+        assert_eq!(rates.total.events, 35);
+        assert_eq!(rates.window.events, 5);
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt::{Display, Formatter};
+
+/// # An entry in the Falcosecurity plugin registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistryEntry {
+    /// The plugin ID
+    pub id: u32,
+    /// The name of the plugin
+    pub name: &'static str,
+    /// The event source implemented by the plugin
+    pub source: &'static str,
+}
+
+/// The plugin ID reserved for testing
+pub const TEST_PLUGIN_ID: u32 = 999;
+
+/// # A partial snapshot of the plugin IDs in the Falcosecurity plugin registry
+///
+/// The authoritative list lives in the
+/// [falcosecurity/plugins](https://github.com/falcosecurity/plugins/blob/main/registry.yaml)
+/// repository. This snapshot only contains a few well-known entries, so an ID missing here
+/// is not necessarily unregistered: it is reported as [`PluginIdIssue::Unknown`] and needs
+/// to be checked against the upstream registry.
+pub const PLUGIN_REGISTRY: &[RegistryEntry] = &[
+    RegistryEntry {
+        id: 1,
+        name: "k8saudit",
+        source: "k8s_audit",
+    },
+    RegistryEntry {
+        id: 2,
+        name: "cloudtrail",
+        source: "aws_cloudtrail",
+    },
+    RegistryEntry {
+        id: 3,
+        name: "dummy",
+        source: "dummy",
+    },
+    RegistryEntry {
+        id: 4,
+        name: "dummy_c",
+        source: "dummy_c",
+    },
+    RegistryEntry {
+        id: 5,
+        name: "docker",
+        source: "docker",
+    },
+    RegistryEntry {
+        id: 6,
+        name: "seccompagent",
+        source: "seccompagent",
+    },
+    RegistryEntry {
+        id: 7,
+        name: "okta",
+        source: "okta",
+    },
+    RegistryEntry {
+        id: 8,
+        name: "github",
+        source: "github",
+    },
+    RegistryEntry {
+        id: 9,
+        name: "k8saudit-eks",
+        source: "k8s_audit",
+    },
+    RegistryEntry {
+        id: TEST_PLUGIN_ID,
+        name: "test",
+        source: "test",
+    },
+];
+
+/// # Find a plugin ID in the registry snapshot
+///
+/// This is a `const fn`, so it can be used in compile-time assertions:
+///
+/// ```
+/// use falco_plugin::source::lookup_plugin_id;
+///
+/// const PLUGIN_ID: u32 = 2;
+/// const _: () = assert!(lookup_plugin_id(PLUGIN_ID).is_some());
+/// ```
+pub const fn lookup_plugin_id(id: u32) -> Option<&'static RegistryEntry> {
+    let mut i = 0;
+    while i < PLUGIN_REGISTRY.len() {
+        if PLUGIN_REGISTRY[i].id == id {
+            return Some(&PLUGIN_REGISTRY[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// # A potential problem with a plugin ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginIdIssue {
+    /// The plugin ID is zero, which is not valid for plugins with an event source
+    Zero,
+    /// The plugin ID is reserved for testing
+    Test,
+    /// The plugin ID is not in the registry snapshot (it may still be registered upstream)
+    Unknown(u32),
+    /// The plugin ID is registered for a different event source
+    Collision(RegistryEntry),
+}
+
+impl PluginIdIssue {
+    /// Check whether the issue only matters for plugins shipped to users
+    ///
+    /// Test plugins commonly use unregistered (or the test) IDs.
+    pub fn is_release_only(&self) -> bool {
+        matches!(self, PluginIdIssue::Test | PluginIdIssue::Unknown(_))
+    }
+}
+
+impl Display for PluginIdIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginIdIssue::Zero => f.write_str("plugin ID 0 is not valid for an event source"),
+            PluginIdIssue::Test => write!(f, "plugin ID {} is reserved for testing", TEST_PLUGIN_ID),
+            PluginIdIssue::Unknown(id) => write!(
+                f,
+                "plugin ID {} is unknown to this SDK version, please make sure it's in the plugin registry",
+                id
+            ),
+            PluginIdIssue::Collision(entry) => write!(
+                f,
+                "plugin ID {} is registered to plugin {:?} (event source {:?})",
+                entry.id, entry.name, entry.source
+            ),
+        }
+    }
+}
+
+/// # Validate a plugin ID against the registry snapshot
+///
+/// Returns `None` if the ID is registered for `event_source`.
+pub fn check_plugin_id(id: u32, event_source: &str) -> Option<PluginIdIssue> {
+    match (id, lookup_plugin_id(id)) {
+        (0, _) => Some(PluginIdIssue::Zero),
+        (TEST_PLUGIN_ID, _) => Some(PluginIdIssue::Test),
+        (_, None) => Some(PluginIdIssue::Unknown(id)),
+        (_, Some(entry)) if entry.source != event_source => Some(PluginIdIssue::Collision(*entry)),
+        _ => None,
+    }
+}
+
+/// # Warn about a suspicious plugin ID from a build script
+///
+/// Call this from your `build.rs` (with `falco_plugin` added to `build-dependencies`)
+/// to get a compile-time warning about plugin IDs that may collide with other plugins:
+///
+/// ```no_run
+/// // in `main()` of build.rs
+/// falco_plugin::source::warn_on_plugin_id(999, "my_source");
+/// ```
+///
+/// Unknown IDs (and the test ID) are only reported for release builds, so test
+/// plugins don't generate noise.
+pub fn warn_on_plugin_id(id: u32, event_source: &str) {
+    let Some(issue) = check_plugin_id(id, event_source) else {
+        return;
+    };
+
+    let release = std::env::var("PROFILE").is_ok_and(|profile| profile == "release");
+    if issue.is_release_only() && !release {
+        return;
+    }
+
+    println!("cargo:warning={}", issue);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_plugin_id, lookup_plugin_id, PluginIdIssue, TEST_PLUGIN_ID};
+
+    #[test]
+    fn test_check_plugin_id() {
+        assert_eq!(check_plugin_id(2, "aws_cloudtrail"), None);
+        assert_eq!(check_plugin_id(0, "foo"), Some(PluginIdIssue::Zero));
+        assert_eq!(
+            check_plugin_id(TEST_PLUGIN_ID, "test"),
+            Some(PluginIdIssue::Test)
+        );
+        assert_eq!(
+            check_plugin_id(1111, "dummy"),
+            Some(PluginIdIssue::Unknown(1111))
+        );
+        assert_eq!(
+            check_plugin_id(2, "my_source"),
+            Some(PluginIdIssue::Collision(*lookup_plugin_id(2).unwrap()))
+        );
+    }
+}
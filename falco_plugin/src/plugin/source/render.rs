@@ -0,0 +1,213 @@
+use std::ffi::CString;
+use std::fmt::Write;
+
+/// # Render plugin event data as a human-readable string
+///
+/// This is what the default implementation of
+/// [`SourcePlugin::event_to_string`](`crate::source::SourcePlugin::event_to_string`) uses.
+/// You can also call it from your own implementation, e.g. to render just a part
+/// of the event data.
+///
+/// The data is rendered:
+/// - as compact JSON, if it's a valid JSON document
+/// - as text, with control characters escaped, if it's valid UTF-8
+/// - as ASCII, with all non-printable bytes escaped, otherwise
+///
+/// The result is truncated to (roughly) `max_len` bytes, with `...` appended
+/// if anything got cut off.
+///
+/// ```
+/// use falco_plugin::source::render_event_data;
+///
+/// assert_eq!(render_event_data(br#"{ "a": 1 }"#, 256).as_bytes(), br#"{"a":1}"#);
+/// assert_eq!(render_event_data(b"line\n", 256).as_bytes(), br#"line\n"#);
+/// assert_eq!(render_event_data(b"\xff\x00", 256).as_bytes(), br#"\xff\x00"#);
+/// assert_eq!(render_event_data(b"hello, world", 5).as_bytes(), b"hello...");
+/// ```
+pub fn render_event_data(data: &[u8], max_len: usize) -> CString {
+    let mut out = if let Ok(json) = serde_json::from_slice::<serde_json::Value>(data) {
+        json.to_string()
+    } else if let Ok(text) = std::str::from_utf8(data) {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if c.is_control() {
+                let _ = write!(out, "{}", c.escape_default());
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    } else {
+        data.escape_ascii().to_string()
+    };
+
+    truncate_with_ellipsis(&mut out, max_len);
+
+    // none of the renderings above can contain a raw NUL byte
+    CString::new(out).unwrap_or_default()
+}
+
+fn truncate_with_ellipsis(out: &mut String, max_len: usize) {
+    if out.len() > max_len {
+        let mut end = max_len;
+        while !out.is_char_boundary(end) {
+            end -= 1;
+        }
+        out.truncate(end);
+        out.push_str("...");
+    }
+}
+
+/// # Render structured event data as `key=value` pairs
+///
+/// A small builder for [`SourcePlugin::event_to_string`](`crate::source::SourcePlugin::event_to_string`)
+/// implementations that want to show a few fields of the event rather than the raw payload.
+/// Using it makes `%evt.plugininfo` look the same across plugins:
+///
+/// - pairs are separated by single spaces, in the order they were added
+///   (or sorted by key, see [`KeyValueRenderer::sort_keys`])
+/// - keys may only contain ASCII alphanumerics, `_`, `-` and `.`; any other character
+///   is replaced with `_`
+/// - values that are empty or contain whitespace, `=`, `"` or `\` are quoted, with `"` and `\`
+///   escaped by a backslash
+/// - control characters are escaped (`\n`, `\t`, `\u{0}` etc.) in all values
+///
+/// The result is truncated to (roughly) `max_len` bytes, with `...` appended
+/// if anything got cut off.
+///
+/// ```
+/// use falco_plugin::source::KeyValueRenderer;
+///
+/// let rendered = KeyValueRenderer::default()
+///     .field("user", "root")
+///     .field("uid", 0)
+///     .field("cmd", "ls -l")
+///     .field_opt("tty", None::<&str>)
+///     .render(256);
+/// assert_eq!(rendered.as_bytes(), br#"user=root uid=0 cmd="ls -l""#);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct KeyValueRenderer {
+    fields: Vec<(String, String)>,
+    sort_keys: bool,
+}
+
+impl KeyValueRenderer {
+    /// Add a `key=value` pair
+    pub fn field(&mut self, key: &str, value: impl std::fmt::Display) -> &mut Self {
+        let key = key
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' => c,
+                _ => '_',
+            })
+            .collect();
+        self.fields.push((key, value.to_string()));
+        self
+    }
+
+    /// Add a `key=value` pair if the value is present
+    pub fn field_opt(&mut self, key: &str, value: Option<impl std::fmt::Display>) -> &mut Self {
+        if let Some(value) = value {
+            self.field(key, value);
+        }
+        self
+    }
+
+    /// Render the pairs sorted by key instead of in insertion order
+    ///
+    /// Pairs with the same key keep their relative order.
+    pub fn sort_keys(&mut self) -> &mut Self {
+        self.sort_keys = true;
+        self
+    }
+
+    /// Render all the pairs into a [`CString`], limited to (roughly) `max_len` bytes
+    pub fn render(&self, max_len: usize) -> CString {
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        if self.sort_keys {
+            fields.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        let mut out = String::new();
+        for (key, value) in fields {
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            out.push_str(key);
+            out.push('=');
+
+            let quote = value.is_empty()
+                || value
+                    .chars()
+                    .any(|c| c.is_whitespace() || matches!(c, '=' | '"' | '\\'));
+            if quote {
+                out.push('"');
+            }
+            for c in value.chars() {
+                match c {
+                    '"' | '\\' if quote => {
+                        out.push('\\');
+                        out.push(c);
+                    }
+                    c if c.is_control() => {
+                        let _ = write!(out, "{}", c.escape_default());
+                    }
+                    c => out.push(c),
+                }
+            }
+            if quote {
+                out.push('"');
+            }
+        }
+
+        truncate_with_ellipsis(&mut out, max_len);
+
+        // all control characters (including NUL) are escaped above
+        CString::new(out).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_event_data, KeyValueRenderer};
+
+    #[test]
+    fn test_truncate_at_char_boundary() {
+        let rendered = render_event_data("zażółć".as_bytes(), 3);
+        assert_eq!(rendered.to_str().unwrap(), "za...");
+    }
+
+    #[test]
+    fn test_escape_nul() {
+        let rendered = render_event_data(b"a\0b", 256);
+        assert_eq!(rendered.to_str().unwrap(), "a\\u{0}b");
+    }
+
+    #[test]
+    fn test_key_value_escaping() {
+        let rendered = KeyValueRenderer::default()
+            .field("empty", "")
+            .field("quoted", r#"say "hi"\now"#)
+            .field("eq", "a=b")
+            .field("ctrl", "a\nb\0")
+            .field("bad key!", 1)
+            .render(256);
+        assert_eq!(
+            rendered.to_str().unwrap(),
+            r#"empty="" quoted="say \"hi\"\\now" eq="a=b" ctrl="a\nb\u{0}" bad_key_=1"#
+        );
+    }
+
+    #[test]
+    fn test_key_value_sorted_truncated() {
+        let mut renderer = KeyValueRenderer::default();
+        renderer
+            .field("b", 2)
+            .field("a", 1)
+            .field("b", 3)
+            .sort_keys();
+        assert_eq!(renderer.render(256).to_str().unwrap(), "a=1 b=2 b=3");
+        assert_eq!(renderer.render(5).to_str().unwrap(), "a=1 b...");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::plugin::exported_tables::field::readonly::Readonly;
+use crate::plugin::exported_tables::table::Table as ExportedTable;
+use crate::plugin::tables::field::Field;
+use crate::plugin::tables::table::Table as ImportedTable;
+use crate::plugin::tables::vtable::{TableReader, TablesInput};
+use anyhow::Error;
+use std::ffi::{CStr, CString};
+use std::ops::ControlFlow;
+
+/// # A single version of a plugin event payload schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadSchema {
+    /// The payload version this schema describes
+    pub version: u64,
+    /// The schema format, e.g. `json-schema`
+    pub format: CString,
+    /// The schema document itself
+    pub schema: CString,
+}
+
+/// # An entry in a payload schema table
+///
+/// All fields are exported as read-only, so other plugins cannot modify the schemas.
+#[derive(Debug)]
+pub struct PayloadSchemaEntry {
+    /// The payload version (also used as the table key)
+    pub version: Readonly<u64>,
+    /// The schema format
+    pub format: Readonly<CString>,
+    /// The schema document
+    pub schema: Readonly<CString>,
+}
+
+crate::impl_export_table!(for PayloadSchemaEntry {
+    [0] c"PayloadSchemaEntry.version" (b"version\0") as version: Readonly<u64>
+    [1] c"PayloadSchemaEntry.format" (b"format\0") as format: Readonly<CString>
+    [2] c"PayloadSchemaEntry.schema" (b"schema\0") as schema: Readonly<CString>
+});
+
+/// # A table of payload schemas, keyed by version
+pub type PayloadSchemaTable = ExportedTable<u64, PayloadSchemaEntry>;
+
+/// # Publish the payload schemas of a source plugin
+///
+/// The plugin event payload is opaque to the framework, so extract and parse plugins
+/// written by other authors need to know its layout in advance. A source plugin can make
+/// this discoverable by exporting a table describing each payload version it may generate,
+/// e.g. in [`Plugin::new`](`crate::base::Plugin::new`):
+///
+/// ```
+/// use std::ffi::CStr;
+/// use falco_plugin::anyhow::Error;
+/// use falco_plugin::base::Plugin;
+/// use falco_plugin::source::{export_payload_schemas, PayloadSchema, PayloadSchemaTable};
+/// use falco_plugin::tables::TablesInput;
+///
+/// struct MySourcePlugin {
+///     schemas: Box<PayloadSchemaTable>,
+/// }
+///
+/// impl Plugin for MySourcePlugin {
+///     // ...
+/// #    const NAME: &'static CStr = c"my-source";
+/// #    const PLUGIN_VERSION: &'static CStr = c"0.0.1";
+/// #    const DESCRIPTION: &'static CStr = c"A source plugin with a published payload schema";
+/// #    const CONTACT: &'static CStr = c"you@example.com";
+/// #    type ConfigType = ();
+///
+///     fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+///         let input = input.ok_or_else(|| falco_plugin::anyhow::anyhow!("no tables input"))?;
+///         let schemas = export_payload_schemas(
+///             input,
+///             c"my_source.payload_schema",
+///             [PayloadSchema {
+///                 version: 1,
+///                 format: c"json-schema".to_owned(),
+///                 schema: cr#"{"type": "object"}"#.to_owned(),
+///             }],
+///         )?;
+///
+///         Ok(Self { schemas })
+///     }
+/// }
+/// ```
+///
+/// By convention, the table is called `<event source>.payload_schema`, so that consumers
+/// can find it knowing just the event source name. Consumers can read the table using
+/// [`PayloadSchemas`].
+///
+/// The returned table must be kept alive for as long as the plugin is loaded.
+pub fn export_payload_schemas(
+    input: &TablesInput,
+    name: &'static CStr,
+    schemas: impl IntoIterator<Item = PayloadSchema>,
+) -> Result<Box<PayloadSchemaTable>, Error> {
+    let mut table = PayloadSchemaTable::new(name)?;
+    for schema in schemas {
+        let mut entry = table.create_entry()?;
+        *entry.version = schema.version;
+        *entry.format = schema.format;
+        *entry.schema = schema.schema;
+        table.insert(&schema.version, entry);
+    }
+
+    input.add_table(table)
+}
+
+/// # Payload schemas published by another plugin
+///
+/// This is the consumer side of [`export_payload_schemas`]: an extract or parse plugin
+/// can use it to check which payload versions the source plugin generates and adapt to them:
+///
+/// ```ignore
+/// let schemas = PayloadSchemas::import(input, c"my_source.payload_schema")?;
+/// if let Some(schemas) = schemas {
+///     let latest = schemas.latest(&reader)?;
+///     // ...
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PayloadSchemas {
+    table: ImportedTable<u64>,
+    version: Field<u64>,
+    format: Field<CStr>,
+    schema: Field<CStr>,
+}
+
+impl PayloadSchemas {
+    /// Import a payload schema table
+    ///
+    /// Returns `Ok(None)` if the table does not exist (e.g. the source plugin is not loaded
+    /// or does not publish its payload schema).
+    pub fn import(input: &TablesInput, name: &CStr) -> Result<Option<Self>, Error> {
+        let Some(table) = ImportedTable::try_import(input, name)? else {
+            return Ok(None);
+        };
+
+        let version = table.get_field(input, c"version")?;
+        let format = table.get_field(input, c"format")?;
+        let schema = table.get_field(input, c"schema")?;
+
+        Ok(Some(Self {
+            table,
+            version,
+            format,
+            schema,
+        }))
+    }
+
+    /// Get the schema for a particular payload version
+    pub fn get(&self, reader: &TableReader, version: u64) -> Result<PayloadSchema, Error> {
+        let entry = self.table.get_entry(reader, &version)?;
+        Ok(PayloadSchema {
+            version,
+            format: entry.read_field(reader, &self.format)?.to_owned(),
+            schema: entry.read_field(reader, &self.schema)?.to_owned(),
+        })
+    }
+
+    /// List all published payload versions, in ascending order
+    pub fn versions(&self, reader: &TableReader) -> Result<Vec<u64>, Error> {
+        let mut versions = Vec::new();
+        let mut result = Ok(());
+        let _ = self.table.iter_entries_mut(reader, |entry| {
+            match entry.read_field(reader, &self.version) {
+                Ok(version) => versions.push(version),
+                Err(e) => {
+                    result = Err(e);
+                    return ControlFlow::Break(());
+                }
+            }
+            ControlFlow::Continue(())
+        });
+        result?;
+
+        versions.sort_unstable();
+        Ok(versions)
+    }
+
+    /// Get the schema for the most recent payload version, if any
+    pub fn latest(&self, reader: &TableReader) -> Result<Option<PayloadSchema>, Error> {
+        match self.versions(reader)?.last() {
+            Some(version) => Ok(Some(self.get(reader, *version)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use crate::plugin::base::metrics::{
+    intern_metric_name, Metric, MetricLabel, MetricType, MetricValue,
+};
+use crate::source::EventBatch;
+use crate::FailureReason;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+enum ShardMessage<T> {
+    Item(usize, T),
+    Failed(usize, anyhow::Error),
+}
+
+/// Get the (interned) metric names for a shard
+fn shard_metric_names(shard: usize) -> [&'static CStr; 2] {
+    [
+        intern_metric_name(format!("shard.{}.events", shard)),
+        intern_metric_name(format!("shard.{}.queue_full", shard)),
+    ]
+}
+
+#[derive(Debug)]
+struct ShardCounters {
+    names: [&'static CStr; 2],
+    events: AtomicU64,
+    queue_full: AtomicU64,
+}
+
+/// # Per-shard statistics of a [`ShardedCollector`]
+///
+/// This is a cheap handle that can be stored in the plugin and used to report per-shard
+/// metrics from [`Plugin::get_metrics`](`crate::base::Plugin::get_metrics`), while the collector
+/// itself lives in the source plugin instance:
+///
+/// - `shard.<n>.events` is the number of items sent by shard `n`
+/// - `shard.<n>.queue_full` is the number of times shard `n` had to wait for space in the queue
+///   (a high value means `next_batch` is the bottleneck, not the collectors)
+#[derive(Debug, Clone)]
+pub struct ShardStats(Arc<[ShardCounters]>);
+
+impl ShardStats {
+    /// Return the number of items sent by a particular shard
+    pub fn events(&self, shard: usize) -> Option<u64> {
+        Some(self.0.get(shard)?.events.load(Ordering::Relaxed))
+    }
+
+    /// Return the per-shard metrics
+    pub fn metrics(&self) -> impl Iterator<Item = Metric> + '_ {
+        self.0.iter().flat_map(|shard| {
+            let [events, queue_full] = shard.names;
+            [
+                MetricLabel::new(events, MetricType::Monotonic)
+                    .with_value(MetricValue::U64(shard.events.load(Ordering::Relaxed))),
+                MetricLabel::new(queue_full, MetricType::Monotonic)
+                    .with_value(MetricValue::U64(shard.queue_full.load(Ordering::Relaxed))),
+            ]
+        })
+    }
+}
+
+/// # The sending side of a single shard
+///
+/// Each collector thread started by [`ShardedCollector::spawn`] gets its own sender.
+#[derive(Debug)]
+pub struct ShardSender<T> {
+    shard: usize,
+    tx: SyncSender<ShardMessage<T>>,
+    stop: Arc<AtomicBool>,
+    stats: ShardStats,
+}
+
+impl<T> ShardSender<T> {
+    /// Return the index of this shard (`0..num_shards`)
+    pub fn shard(&self) -> usize {
+        self.shard
+    }
+
+    /// Check if the collector has been shut down
+    ///
+    /// Collectors that may block for a long time (e.g. waiting on a socket) should check this
+    /// regularly and return when it becomes true.
+    pub fn should_stop(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Send an item to `next_batch`
+    ///
+    /// Blocks while the queue is full. Fails when the collector has been shut down,
+    /// so collectors can simply use `sender.send(item)?` and exit on error.
+    pub fn send(&self, item: T) -> Result<(), anyhow::Error> {
+        if self.should_stop() {
+            anyhow::bail!("sharded collector stopped");
+        }
+
+        let counters = &self.stats.0[self.shard];
+        match self.tx.try_send(ShardMessage::Item(self.shard, item)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(msg)) => {
+                counters.queue_full.fetch_add(1, Ordering::Relaxed);
+                self.tx
+                    .send(msg)
+                    .map_err(|_| anyhow::anyhow!("sharded collector stopped"))?;
+            }
+            Err(TrySendError::Disconnected(_)) => anyhow::bail!("sharded collector stopped"),
+        }
+
+        counters.events.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// # Parallel event collection behind a single source instance
+///
+/// The plugin API calls [`next_batch`](`crate::source::SourcePluginInstance::next_batch`)
+/// from a single thread, which may be a bottleneck for high-throughput sources (e.g. reading
+/// from several sockets or partitions). A `ShardedCollector` runs a collector function
+/// in `num_shards` threads, each with its own [`ShardSender`], and merges their output through
+/// a bounded queue. The instance then fills its batches with
+/// [`ShardedCollector::next_batch`], so the host still sees a single source instance.
+///
+/// Items sent by a single shard are always delivered in order. There is no ordering between
+/// items from different shards. When the queue is full, the collectors block until
+/// `next_batch` catches up, so memory usage stays bounded.
+///
+/// If a collector fails, the error is returned from `next_batch` (after any items the shard
+/// sent before failing). When all collectors return, `next_batch` reports
+/// [`FailureReason::Eof`] once the queue is drained.
+///
+/// Dropping the collector (e.g. when the instance is closed) signals the collectors to stop
+/// and waits for all the threads to exit.
+///
+/// ```
+/// use falco_plugin::source::{ShardSender, ShardedCollector};
+///
+/// let mut collector = ShardedCollector::spawn(4, 1024, |sender: ShardSender<u64>| {
+///     let shard = sender.shard() as u64;
+///     for i in 0..10 {
+///         sender.send(shard * 100 + i)?;
+///     }
+///     Ok(())
+/// })?;
+///
+/// // in next_batch:
+/// // collector.next_batch(batch, Duration::from_millis(100), 512, |batch, shard, item| {
+/// //     batch.add(Self::plugin_event(&item.to_le_bytes()))?;
+/// //     Ok(())
+/// // })
+/// # let stats = collector.stats();
+/// # drop(collector);
+/// # assert!(stats.events(0).is_some());
+/// # Result::<(), falco_plugin::anyhow::Error>::Ok(())
+/// ```
+pub struct ShardedCollector<T> {
+    rx: Option<Receiver<ShardMessage<T>>>,
+    threads: Vec<JoinHandle<()>>,
+    stop: Arc<AtomicBool>,
+    stats: ShardStats,
+    pending_error: Option<anyhow::Error>,
+}
+
+impl<T> std::fmt::Debug for ShardedCollector<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardedCollector")
+            .field("num_shards", &self.threads.len())
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Send + 'static> ShardedCollector<T> {
+    /// Start `num_shards` collector threads, sharing a queue of up to `queue_size` items
+    ///
+    /// `collector` is called once in every thread, with that shard's [`ShardSender`].
+    /// It should keep sending items until the source is exhausted or the collector is stopped.
+    pub fn spawn<F>(
+        num_shards: usize,
+        queue_size: usize,
+        collector: F,
+    ) -> Result<Self, anyhow::Error>
+    where
+        F: Fn(ShardSender<T>) -> Result<(), anyhow::Error> + Send + Sync + 'static,
+    {
+        if num_shards == 0 {
+            anyhow::bail!("a sharded collector needs at least one shard");
+        }
+
+        let (tx, rx) = sync_channel(queue_size);
+        let stop = Arc::new(AtomicBool::new(false));
+        let stats = ShardStats(
+            (0..num_shards)
+                .map(|shard| ShardCounters {
+                    names: shard_metric_names(shard),
+                    events: AtomicU64::new(0),
+                    queue_full: AtomicU64::new(0),
+                })
+                .collect(),
+        );
+        let collector = Arc::new(collector);
+
+        let mut this = Self {
+            rx: Some(rx),
+            threads: Vec::with_capacity(num_shards),
+            stop,
+            stats,
+            pending_error: None,
+        };
+
+        for shard in 0..num_shards {
+            let sender = ShardSender {
+                shard,
+                tx: tx.clone(),
+                stop: Arc::clone(&this.stop),
+                stats: this.stats.clone(),
+            };
+            let collector = Arc::clone(&collector);
+            // on failure, `this` is dropped, stopping the shards started so far
+            let handle = std::thread::Builder::new()
+                .name(format!("shard-{}", shard))
+                .spawn(move || {
+                    let tx = sender.tx.clone();
+                    if let Err(e) = collector(sender) {
+                        let _ = tx.send(ShardMessage::Failed(shard, e));
+                    }
+                })?;
+            this.threads.push(handle);
+        }
+
+        Ok(this)
+    }
+
+    /// Return a handle to the per-shard statistics
+    pub fn stats(&self) -> ShardStats {
+        self.stats.clone()
+    }
+
+    /// Fill a batch with items received from the shards
+    ///
+    /// Waits up to `timeout` for the first item, then adds all the items already waiting
+    /// in the queue, up to `max_events`. Every item is converted to an event (or events)
+    /// and added to the batch by `add`, which also receives the index of the shard
+    /// that sent the item.
+    ///
+    /// Returns [`FailureReason::Timeout`] if no items arrived in time and [`FailureReason::Eof`]
+    /// when all the collectors have finished and the queue is empty, so the result can be
+    /// returned from [`next_batch`](`crate::source::SourcePluginInstance::next_batch`) directly.
+    pub fn next_batch<F>(
+        &mut self,
+        batch: &mut EventBatch,
+        timeout: Duration,
+        max_events: usize,
+        mut add: F,
+    ) -> Result<(), anyhow::Error>
+    where
+        F: FnMut(&mut EventBatch, usize, T) -> Result<(), anyhow::Error>,
+    {
+        if let Some(e) = self.pending_error.take() {
+            return Err(e);
+        }
+        let Some(rx) = &self.rx else {
+            return Err(anyhow::anyhow!("sharded collector stopped").context(FailureReason::Eof));
+        };
+
+        let mut msg = match rx.recv_timeout(timeout) {
+            Ok(msg) => Some(msg),
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(
+                    anyhow::anyhow!("no events from any shard").context(FailureReason::Timeout)
+                )
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(anyhow::anyhow!("all shards finished").context(FailureReason::Eof))
+            }
+        };
+
+        let mut count = 0;
+        while let Some(m) = msg {
+            match m {
+                ShardMessage::Item(shard, item) => {
+                    add(batch, shard, item)?;
+                    count += 1;
+                }
+                ShardMessage::Failed(shard, e) => {
+                    let e = e.context(format!("shard {} failed", shard));
+                    if count == 0 {
+                        return Err(e);
+                    }
+                    // return the events we already have, report the error next time
+                    self.pending_error = Some(e);
+                    break;
+                }
+            }
+
+            if count >= max_events {
+                break;
+            }
+            msg = rx.try_recv().ok();
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Drop for ShardedCollector<T> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // wake up any collectors blocked on a full queue
+        drop(self.rx.take());
+        for handle in self.threads.drain(..) {
+            if handle.join().is_err() {
+                log::warn!("A shard collector thread panicked");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shard_metric_names, ShardSender, ShardedCollector};
+    use crate::source::EventBatch;
+    use crate::FailureReason;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_per_shard_ordering() {
+        let mut collector = ShardedCollector::spawn(4, 8, |sender: ShardSender<(usize, u64)>| {
+            for i in 0..100 {
+                sender.send((sender.shard(), i))?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let mut alloc = bumpalo::Bump::new();
+        let mut batch = EventBatch::new(&mut alloc);
+        let mut next = [0u64; 4];
+        loop {
+            let res = collector.next_batch(&mut batch, TIMEOUT, 16, |_, shard, (sent_by, i)| {
+                assert_eq!(shard, sent_by);
+                assert_eq!(next[shard], i);
+                next[shard] += 1;
+                Ok(())
+            });
+            if let Err(e) = res {
+                assert!(
+                    matches!(e.downcast_ref::<FailureReason>(), Some(FailureReason::Eof)),
+                    "{:#}",
+                    e
+                );
+                break;
+            }
+        }
+
+        assert_eq!(next, [100; 4]);
+        let stats = collector.stats();
+        assert_eq!(stats.events(3), Some(100));
+        assert_eq!(stats.events(4), None);
+        assert_eq!(stats.metrics().count(), 8);
+    }
+
+    #[test]
+    fn test_shard_failure() {
+        let mut collector = ShardedCollector::spawn(1, 8, |sender: ShardSender<u64>| {
+            sender.send(1)?;
+            anyhow::bail!("boom")
+        })
+        .unwrap();
+
+        let mut alloc = bumpalo::Bump::new();
+        let mut batch = EventBatch::new(&mut alloc);
+        let mut items = Vec::new();
+        let mut errors = Vec::new();
+        for _ in 0..3 {
+            match collector.next_batch(&mut batch, TIMEOUT, 16, |_, _, item| {
+                items.push(item);
+                Ok(())
+            }) {
+                Ok(()) => {}
+                Err(e) => errors.push(format!("{:#}", e)),
+            }
+        }
+
+        assert_eq!(items, [1]);
+        assert_eq!(errors[0], "shard 0 failed: boom");
+    }
+
+    #[test]
+    fn test_drop_unblocks_collectors() {
+        let collector = ShardedCollector::spawn(2, 1, |sender: ShardSender<u64>| loop {
+            sender.send(0)?;
+        })
+        .unwrap();
+
+        // nothing is draining the queue, so the collectors are stuck in `send`
+        std::thread::sleep(Duration::from_millis(50));
+        let stats = collector.stats();
+        drop(collector);
+        assert!(stats
+            .0
+            .iter()
+            .all(|shard| shard.queue_full.load(Ordering::Relaxed) > 0));
+    }
+
+    #[test]
+    fn test_metric_names() {
+        assert_eq!(
+            shard_metric_names(2),
+            [c"shard.2.events", c"shard.2.queue_full"]
+        );
+        assert!(std::ptr::eq(
+            shard_metric_names(2)[0],
+            shard_metric_names(2)[0]
+        ));
+    }
+}
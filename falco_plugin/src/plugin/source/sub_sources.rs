@@ -0,0 +1,621 @@
+use crate::plugin::base::metrics::{
+    intern_metric_name, Metric, MetricLabel, MetricType, MetricValue,
+};
+use crate::source::{EventBatch, PayloadDecodeError, PluginEvent, PluginPayload};
+use crate::FailureReason;
+use falco_event::events::{Event, EventMetadata};
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// # The payload of an event generated by a sub-source
+///
+/// Every event generated through [`SubSources`] is a [`PluginEvent`] whose data starts
+/// with the id of the sub-source (a little-endian `u32`), followed by the data passed
+/// to [`SubSourceBatch::add`]. Parse and extract plugins can decode it with
+/// [`PluginPayload::decode`]:
+///
+/// ```
+/// use falco_plugin::source::{PluginPayload, SubSourceEvent};
+///
+/// let buf = SubSourceEvent { id: 3, data: b"hello" }.to_vec().unwrap();
+/// let event = SubSourceEvent::decode(&buf).unwrap();
+/// assert_eq!(event.id, 3);
+/// assert_eq!(event.data, b"hello");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SubSourceEvent<'a> {
+    /// the id of the sub-source that generated the event
+    pub id: u32,
+    /// the actual event data
+    pub data: &'a [u8],
+}
+
+impl<'a> PluginPayload<'a> for SubSourceEvent<'a> {
+    fn encode<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(&self.id.to_le_bytes())?;
+        writer.write_all(self.data)
+    }
+
+    fn decode(buf: &'a [u8]) -> Result<Self, PayloadDecodeError> {
+        let Some((id, data)) = buf.split_first_chunk::<4>() else {
+            return Err(PayloadDecodeError::Truncated {
+                expected: 4,
+                actual: buf.len(),
+            });
+        };
+
+        Ok(Self {
+            id: u32::from_le_bytes(*id),
+            data,
+        })
+    }
+}
+
+/// # A single logical event source inside a source plugin instance
+///
+/// See [`SubSources`] for details.
+pub trait SubSource {
+    /// # Start the sub-source
+    ///
+    /// This is called when the sub-source is added to a [`SubSources`] collection.
+    /// If it fails, the sub-source is not added.
+    fn open(&mut self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    /// # Add the events that are ready to the batch
+    ///
+    /// This is called once for every call to [`SubSources::next_batch`], so it should not
+    /// block waiting for events: return [`FailureReason::Timeout`] if there are none
+    /// at the moment and [`FailureReason::Eof`] if there will be no more events
+    /// from this sub-source. Any other error also shuts down this sub-source (but not the others).
+    fn next_batch(&mut self, batch: &mut SubSourceBatch) -> Result<(), anyhow::Error>;
+
+    /// # Shut down the sub-source
+    ///
+    /// This is called exactly once for every successfully opened sub-source: when it is removed,
+    /// when it finishes (or fails), or when the [`SubSources`] collection is dropped.
+    fn close(&mut self) {}
+}
+
+impl<S: SubSource + ?Sized> SubSource for Box<S> {
+    fn open(&mut self) -> Result<(), anyhow::Error> {
+        (**self).open()
+    }
+
+    fn next_batch(&mut self, batch: &mut SubSourceBatch) -> Result<(), anyhow::Error> {
+        (**self).next_batch(batch)
+    }
+
+    fn close(&mut self) {
+        (**self).close()
+    }
+}
+
+/// # A batch of events from a single sub-source
+///
+/// This wraps the [`EventBatch`] of the source plugin instance, tagging every event
+/// with the id of the sub-source.
+#[derive(Debug)]
+pub struct SubSourceBatch<'b, 'a> {
+    batch: &'b mut EventBatch<'a>,
+    id: u32,
+    plugin_id: u32,
+    events: usize,
+    buf: Vec<u8>,
+}
+
+impl SubSourceBatch<'_, '_> {
+    /// Return the id of the sub-source
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Add an event with `data` as the payload to the batch
+    pub fn add(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.add_with_metadata(EventMetadata::default(), data)
+    }
+
+    /// Add an event with `data` as the payload and explicit metadata (e.g. timestamp) to the batch
+    pub fn add_with_metadata(
+        &mut self,
+        metadata: EventMetadata,
+        data: &[u8],
+    ) -> std::io::Result<()> {
+        self.buf.clear();
+        SubSourceEvent { id: self.id, data }.encode(&mut self.buf)?;
+        self.batch.add(Event {
+            metadata,
+            params: PluginEvent {
+                plugin_id: Some(self.plugin_id),
+                event_data: Some(&self.buf),
+            },
+        })?;
+        self.events += 1;
+        Ok(())
+    }
+
+    /// Return the number of events added by this sub-source in the current batch
+    pub fn len(&self) -> usize {
+        self.events
+    }
+
+    /// Check if this sub-source has not added any events in the current batch
+    pub fn is_empty(&self) -> bool {
+        self.events == 0
+    }
+}
+
+/// Get the (interned) metric names for a sub-source
+fn sub_source_metric_names(name: &str) -> [&'static CStr; 2] {
+    [
+        intern_metric_name(format!("sub_source.{}.events", name)),
+        intern_metric_name(format!("sub_source.{}.errors", name)),
+    ]
+}
+
+#[derive(Debug)]
+struct SubSourceCounters {
+    names: [&'static CStr; 2],
+    events: u64,
+    errors: u64,
+}
+
+/// # Per-sub-source statistics of a [`SubSources`] collection
+///
+/// This is a cheap handle that can be stored in the plugin and used to report per-sub-source
+/// metrics from [`Plugin::get_metrics`](`crate::base::Plugin::get_metrics`), while the collection
+/// itself lives in the source plugin instance:
+///
+/// - `sub_source.<name>.events` is the number of events generated by the sub-source
+/// - `sub_source.<name>.errors` is the number of times the sub-source failed
+///
+/// The statistics of a sub-source remain available after it finishes, until it is
+/// explicitly removed with [`SubSources::remove`].
+#[derive(Debug, Clone, Default)]
+pub struct SubSourceStats(Arc<Mutex<BTreeMap<u32, SubSourceCounters>>>);
+
+impl SubSourceStats {
+    fn with_counters<R>(
+        &self,
+        id: u32,
+        func: impl FnOnce(&mut SubSourceCounters) -> R,
+    ) -> Option<R> {
+        let mut counters = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        counters.get_mut(&id).map(func)
+    }
+
+    /// Return the number of events generated by a particular sub-source
+    pub fn events(&self, id: u32) -> Option<u64> {
+        self.with_counters(id, |c| c.events)
+    }
+
+    /// Return the number of failures of a particular sub-source
+    pub fn errors(&self, id: u32) -> Option<u64> {
+        self.with_counters(id, |c| c.errors)
+    }
+
+    /// Return the per-sub-source metrics
+    pub fn metrics(&self) -> Vec<Metric> {
+        let counters = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        counters
+            .values()
+            .flat_map(|c| {
+                let [events, errors] = c.names;
+                [
+                    MetricLabel::new(events, MetricType::Monotonic)
+                        .with_value(MetricValue::U64(c.events)),
+                    MetricLabel::new(errors, MetricType::Monotonic)
+                        .with_value(MetricValue::U64(c.errors)),
+                ]
+            })
+            .collect()
+    }
+}
+
+struct SubSourceEntry<S> {
+    id: u32,
+    name: String,
+    source: S,
+}
+
+/// # Several logical event sources multiplexed into a single source plugin
+///
+/// Every source plugin needs its own [`PLUGIN_ID`](`crate::source::SourcePlugin::PLUGIN_ID`)
+/// and event source name, which makes it impractical to ship a separate plugin for every
+/// kind of input (e.g. one per cloud account or log file). Instead, a single source plugin
+/// can keep a `SubSources` collection in its instance, add a [`SubSource`] for every input
+/// (e.g. based on the open parameters or the plugin config), and fill its batches with
+/// [`SubSources::next_batch`].
+///
+/// Every event is tagged with the id of the sub-source that generated it
+/// (see [`SubSourceEvent`]), so parse and extract plugins can tell them apart.
+///
+/// Sub-sources are polled in a round-robin fashion, starting with a different one in every
+/// batch, so a busy sub-source cannot starve the others. When a sub-source finishes
+/// (or fails), it is closed and removed, while the others keep running. Failures are logged
+/// and counted in the per-sub-source [statistics](`SubSourceStats`).
+///
+/// ```
+/// use falco_plugin::anyhow::{self, Error};
+/// use falco_plugin::source::{SubSource, SubSourceBatch, SubSources};
+/// use falco_plugin::FailureReason;
+///
+/// struct Counter(u32);
+///
+/// impl SubSource for Counter {
+///     fn next_batch(&mut self, batch: &mut SubSourceBatch) -> Result<(), Error> {
+///         if self.0 == 0 {
+///             return Err(anyhow::anyhow!("done").context(FailureReason::Eof));
+///         }
+///         self.0 -= 1;
+///         batch.add(&self.0.to_le_bytes())?;
+///         Ok(())
+///     }
+/// }
+///
+/// // in SourcePlugin::open:
+/// let mut sources = SubSources::new(999);
+/// sources.add(1, "short", Counter(1))?;
+/// sources.add(2, "long", Counter(100))?;
+///
+/// // in SourcePluginInstance::next_batch:
+/// // sources.next_batch(batch)
+/// # Result::<(), Error>::Ok(())
+/// ```
+pub struct SubSources<S: SubSource = Box<dyn SubSource + Send>> {
+    plugin_id: u32,
+    sources: Vec<SubSourceEntry<S>>,
+    next: usize,
+    stats: SubSourceStats,
+    buf: Vec<u8>,
+}
+
+impl<S: SubSource> std::fmt::Debug for SubSources<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubSources")
+            .field("plugin_id", &self.plugin_id)
+            .field("ids", &self.ids().collect::<Vec<_>>())
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: SubSource> SubSources<S> {
+    /// # Create an empty collection
+    ///
+    /// `plugin_id` should be the [`PLUGIN_ID`](`crate::source::SourcePlugin::PLUGIN_ID`)
+    /// of your source plugin.
+    pub fn new(plugin_id: u32) -> Self {
+        Self {
+            plugin_id,
+            sources: Vec::new(),
+            next: 0,
+            stats: SubSourceStats::default(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Return a handle to the per-sub-source statistics
+    pub fn stats(&self) -> SubSourceStats {
+        self.stats.clone()
+    }
+
+    /// # Open a sub-source and add it to the collection
+    ///
+    /// The `name` is only used for metrics and log messages. Fails if a sub-source with the same
+    /// id is already running, if another sub-source (still running or not yet removed) has
+    /// the same name (as their metrics would collide) or if [`SubSource::open`] fails.
+    pub fn add(&mut self, id: u32, name: &str, mut source: S) -> Result<(), anyhow::Error> {
+        if self.contains(id) {
+            anyhow::bail!("sub-source {} is already running", id);
+        }
+
+        let names = sub_source_metric_names(name);
+        {
+            let counters = self.stats.0.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((other, _)) = counters
+                .iter()
+                .find(|(other, c)| **other != id && c.names == names)
+            {
+                anyhow::bail!("sub-source {} is already named {:?}", other, name);
+            }
+        }
+
+        source.open()?;
+        self.sources.push(SubSourceEntry {
+            id,
+            name: name.to_string(),
+            source,
+        });
+
+        let mut counters = self.stats.0.lock().unwrap_or_else(|e| e.into_inner());
+        counters.insert(
+            id,
+            SubSourceCounters {
+                names,
+                events: 0,
+                errors: 0,
+            },
+        );
+        Ok(())
+    }
+
+    /// # Close a sub-source and remove it from the collection
+    ///
+    /// This also removes the statistics of the sub-source (even if it has already finished).
+    /// Returns the sub-source, if it was still running.
+    pub fn remove(&mut self, id: u32) -> Option<S> {
+        self.stats
+            .0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id);
+
+        let pos = self.sources.iter().position(|entry| entry.id == id)?;
+        let mut entry = self.sources.remove(pos);
+        entry.source.close();
+        Some(entry.source)
+    }
+
+    /// Check if a sub-source with a particular id is running
+    pub fn contains(&self, id: u32) -> bool {
+        self.sources.iter().any(|entry| entry.id == id)
+    }
+
+    /// Return the ids of the running sub-sources
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.sources.iter().map(|entry| entry.id)
+    }
+
+    /// Return the number of running sub-sources
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Check if there are no running sub-sources
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Get a reference to a running sub-source
+    pub fn get(&self, id: u32) -> Option<&S> {
+        self.sources
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| &entry.source)
+    }
+
+    /// Get a mutable reference to a running sub-source
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut S> {
+        self.sources
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .map(|entry| &mut entry.source)
+    }
+
+    /// # Fill a batch with events from all the sub-sources
+    ///
+    /// Every running sub-source gets a chance to add its events to the batch.
+    ///
+    /// Returns [`FailureReason::Timeout`] if none of the sub-sources had any events
+    /// and [`FailureReason::Eof`] when there are no running sub-sources left,
+    /// so the result can be returned from
+    /// [`next_batch`](`crate::source::SourcePluginInstance::next_batch`) directly.
+    pub fn next_batch(&mut self, batch: &mut EventBatch) -> Result<(), anyhow::Error> {
+        if self.sources.is_empty() {
+            return Err(anyhow::anyhow!("no sub-sources left").context(FailureReason::Eof));
+        }
+
+        let start = self.next % self.sources.len();
+        self.next = start + 1;
+
+        let mut order: Vec<u32> = self.ids().collect();
+        order.rotate_left(start);
+
+        let mut num_events = 0;
+        for id in order {
+            let Some(pos) = self.sources.iter().position(|entry| entry.id == id) else {
+                continue;
+            };
+            let entry = &mut self.sources[pos];
+
+            let mut sub_batch = SubSourceBatch {
+                batch: &mut *batch,
+                id,
+                plugin_id: self.plugin_id,
+                events: 0,
+                buf: std::mem::take(&mut self.buf),
+            };
+            let res = entry.source.next_batch(&mut sub_batch);
+            let events = sub_batch.events;
+            self.buf = sub_batch.buf;
+
+            num_events += events;
+            let (finished, failed) = match &res {
+                Ok(()) => (false, false),
+                Err(e) => match e.downcast_ref::<FailureReason>() {
+                    Some(FailureReason::Timeout) => (false, false),
+                    Some(FailureReason::Eof) => (true, false),
+                    _ => {
+                        log::warn!("sub-source {} ({}) failed: {:#}", entry.name, id, e);
+                        (true, true)
+                    }
+                },
+            };
+
+            self.stats.with_counters(id, |c| {
+                c.events += events as u64;
+                if failed {
+                    c.errors += 1;
+                }
+            });
+
+            if finished {
+                let mut entry = self.sources.remove(pos);
+                entry.source.close();
+            }
+        }
+
+        if num_events == 0 {
+            if self.sources.is_empty() {
+                return Err(anyhow::anyhow!("no sub-sources left").context(FailureReason::Eof));
+            }
+            return Err(
+                anyhow::anyhow!("no events from any sub-source").context(FailureReason::Timeout)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: SubSource> Drop for SubSources<S> {
+    fn drop(&mut self) {
+        for entry in &mut self.sources {
+            entry.source.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SubSource, SubSourceBatch, SubSourceEvent, SubSources};
+    use crate::source::{EventBatch, PayloadDecodeError, PluginPayload};
+    use crate::FailureReason;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct TestSource {
+        remaining: usize,
+        fail: bool,
+        closed: Arc<AtomicUsize>,
+    }
+
+    impl TestSource {
+        fn new(remaining: usize, fail: bool, closed: &Arc<AtomicUsize>) -> Self {
+            Self {
+                remaining,
+                fail,
+                closed: Arc::clone(closed),
+            }
+        }
+    }
+
+    impl SubSource for TestSource {
+        fn next_batch(&mut self, batch: &mut SubSourceBatch) -> Result<(), anyhow::Error> {
+            if self.remaining == 0 {
+                if self.fail {
+                    anyhow::bail!("boom");
+                }
+                return Err(anyhow::anyhow!("done").context(FailureReason::Eof));
+            }
+            self.remaining -= 1;
+            batch.add(b"event")?;
+            Ok(())
+        }
+
+        fn close(&mut self) {
+            self.closed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_sub_source_event() {
+        let buf = SubSourceEvent {
+            id: 0x01020304,
+            data: b"data",
+        }
+        .to_vec()
+        .unwrap();
+        assert_eq!(buf, b"\x04\x03\x02\x01data");
+
+        assert_eq!(
+            SubSourceEvent::decode(b"\x01\x00\x00\x00"),
+            Ok(SubSourceEvent { id: 1, data: b"" })
+        );
+        assert_eq!(
+            SubSourceEvent::decode(b"\x01\x00"),
+            Err(PayloadDecodeError::Truncated {
+                expected: 4,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_sub_source_lifecycle() {
+        let closed = Arc::new(AtomicUsize::new(0));
+        let mut sources = SubSources::new(999);
+        let stats = sources.stats();
+
+        sources
+            .add(1, "one", TestSource::new(1, false, &closed))
+            .unwrap();
+        sources
+            .add(2, "two", TestSource::new(3, true, &closed))
+            .unwrap();
+        assert!(sources
+            .add(2, "dup", TestSource::new(0, false, &closed))
+            .is_err());
+        // the metrics of two sub-sources with the same name would collide
+        assert!(sources
+            .add(3, "one", TestSource::new(0, false, &closed))
+            .is_err());
+
+        let mut alloc = bumpalo::Bump::new();
+        let mut batch = EventBatch::new(&mut alloc);
+        let mut results = Vec::new();
+        loop {
+            match sources.next_batch(&mut batch) {
+                Ok(()) => results.push(sources.len()),
+                Err(e) => {
+                    assert!(matches!(
+                        e.downcast_ref::<FailureReason>(),
+                        Some(FailureReason::Eof)
+                    ));
+                    break;
+                }
+            }
+        }
+
+        // source 1 finishes in the second batch, source 2 fails in the fourth one
+        assert_eq!(results, [2, 1, 1]);
+        assert_eq!(batch.len(), 4);
+        assert_eq!(closed.load(Ordering::Relaxed), 2);
+
+        assert_eq!(stats.events(1), Some(1));
+        assert_eq!(stats.errors(1), Some(0));
+        assert_eq!(stats.events(2), Some(3));
+        assert_eq!(stats.errors(2), Some(1));
+        assert_eq!(stats.metrics().len(), 4);
+
+        assert!(sources.remove(1).is_none());
+        assert_eq!(stats.events(1), None);
+
+        // the name is free again once the statistics are removed
+        sources
+            .add(3, "one", TestSource::new(0, false, &closed))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sub_source_close_on_drop() {
+        let closed = Arc::new(AtomicUsize::new(0));
+        let mut sources = SubSources::<Box<dyn SubSource + Send>>::new(999);
+        sources
+            .add(1, "one", Box::new(TestSource::new(10, false, &closed)))
+            .unwrap();
+        sources
+            .add(2, "two", Box::new(TestSource::new(10, false, &closed)))
+            .unwrap();
+
+        assert!(sources.remove(1).is_some());
+        assert_eq!(closed.load(Ordering::Relaxed), 1);
+
+        drop(sources);
+        assert_eq!(closed.load(Ordering::Relaxed), 2);
+    }
+}
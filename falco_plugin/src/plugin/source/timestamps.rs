@@ -0,0 +1,137 @@
+use crate::plugin::convert;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// # How a source plugin instance handles event timestamps going backwards
+///
+/// Events with the default timestamp (`u64::MAX`) are stamped with the current time
+/// by the framework, so wall clock adjustments (NTP steps, manual changes) can make
+/// the timestamps go backwards. The same can happen with timestamps taken from the data
+/// source itself. Downstream consumers (e.g. rules looking at time intervals) may get
+/// confused by that.
+///
+/// With any policy other than [`TimestampPolicy::PassThrough`], the SDK stamps events with
+/// the default timestamp itself and guarantees that the timestamps within a single open
+/// instance never decrease. The number of adjusted events is reported in the
+/// `sdk.timestamp_clamps` metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampPolicy {
+    /// Pass the timestamps through unchanged
+    PassThrough,
+    /// Raise a timestamp that goes backwards to the last emitted timestamp
+    ///
+    /// The intervals between events before and after the jump are preserved, but all events
+    /// generated until the clock catches up get the same timestamp.
+    Clamp,
+    /// Shift the timestamps forward by the size of every backward jump
+    ///
+    /// This preserves the intervals between all events, at the cost of the timestamps
+    /// drifting away from the wall clock after each adjustment.
+    Offset,
+}
+
+#[derive(Debug)]
+pub(crate) struct MonotonicTimestamps {
+    policy: TimestampPolicy,
+    last: u64,
+    offset: u64,
+    clamps: u64,
+}
+
+impl MonotonicTimestamps {
+    pub(crate) fn new(policy: TimestampPolicy) -> Self {
+        Self {
+            policy,
+            last: 0,
+            offset: 0,
+            clamps: 0,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.policy != TimestampPolicy::PassThrough
+    }
+
+    /// Return the (adjusted) timestamp to use for an event with timestamp `ts`
+    pub(crate) fn adjust(&mut self, ts: u64) -> u64 {
+        if !self.is_enabled() {
+            return ts;
+        }
+
+        let ts = match ts {
+            u64::MAX => Self::now(),
+            ts => ts,
+        };
+
+        let ts = match self.policy {
+            TimestampPolicy::Offset => ts.saturating_add(self.offset),
+            _ => ts,
+        };
+
+        if ts < self.last {
+            self.clamps += 1;
+            if self.policy == TimestampPolicy::Offset {
+                self.offset += self.last - ts;
+            }
+            return self.last;
+        }
+
+        self.last = ts;
+        ts
+    }
+
+    /// Return the number of adjusted timestamps since the last call
+    pub(crate) fn take_clamps(&mut self) -> u64 {
+        std::mem::take(&mut self.clamps)
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| convert::saturating(d.as_nanos()))
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MonotonicTimestamps, TimestampPolicy};
+
+    #[test]
+    fn test_pass_through() {
+        let mut ts = MonotonicTimestamps::new(TimestampPolicy::PassThrough);
+        assert_eq!(ts.adjust(100), 100);
+        assert_eq!(ts.adjust(50), 50);
+        assert_eq!(ts.adjust(u64::MAX), u64::MAX);
+        assert_eq!(ts.take_clamps(), 0);
+    }
+
+    #[test]
+    fn test_clamp() {
+        let mut ts = MonotonicTimestamps::new(TimestampPolicy::Clamp);
+        assert_eq!(ts.adjust(100), 100);
+        assert_eq!(ts.adjust(50), 100);
+        assert_eq!(ts.adjust(80), 100);
+        assert_eq!(ts.adjust(120), 120);
+        assert_eq!(ts.take_clamps(), 2);
+        assert_eq!(ts.take_clamps(), 0);
+    }
+
+    #[test]
+    fn test_offset() {
+        let mut ts = MonotonicTimestamps::new(TimestampPolicy::Offset);
+        assert_eq!(ts.adjust(100), 100);
+        assert_eq!(ts.adjust(50), 100);
+        // the 50ns jump back is now added to all timestamps
+        assert_eq!(ts.adjust(80), 130);
+        assert_eq!(ts.adjust(120), 170);
+        assert_eq!(ts.take_clamps(), 1);
+    }
+
+    #[test]
+    fn test_default_timestamp() {
+        let mut ts = MonotonicTimestamps::new(TimestampPolicy::Clamp);
+        let now = ts.adjust(u64::MAX);
+        assert_ne!(now, u64::MAX);
+        assert!(ts.adjust(u64::MAX) >= now);
+    }
+}
@@ -1,8 +1,12 @@
 use crate::plugin::base::PluginWrapper;
+use crate::plugin::convert;
 use crate::plugin::error::ffi_result::FfiResult;
+use crate::plugin::error::strict;
+use crate::plugin::source::dedup::DedupWindow;
+use crate::plugin::source::timestamps::MonotonicTimestamps;
 use crate::plugin::source::SourcePluginInstanceWrapper;
+use crate::plugin::spans;
 use crate::source::{EventBatch, EventInput, SourcePlugin, SourcePluginInstance};
-use crate::strings::cstring_writer::WriteIntoCString;
 use crate::strings::from_ptr::try_str_from_ptr;
 use falco_plugin_api::plugin_api__bindgen_ty_1 as source_plugin_api;
 use falco_plugin_api::{
@@ -10,7 +14,6 @@ use falco_plugin_api::{
     ss_plugin_rc_SS_PLUGIN_FAILURE, ss_plugin_rc_SS_PLUGIN_SUCCESS, ss_plugin_t,
 };
 use std::ffi::c_char;
-use std::io::Write;
 
 pub trait SourcePluginFallbackApi {
     const SOURCE_API: source_plugin_api = source_plugin_api {
@@ -59,9 +62,11 @@ pub unsafe extern "C-unwind" fn plugin_list_open_params<T: SourcePlugin>(
 ) -> *const c_char {
     let plugin = plugin as *mut PluginWrapper<T>;
     let Some(plugin) = plugin.as_mut() else {
+        strict::unexpected_input("plugin_list_open_params", "NULL plugin");
         return std::ptr::null();
     };
     let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+        strict::unexpected_input("plugin_list_open_params", "plugin failed to initialize");
         return std::ptr::null();
     };
 
@@ -93,13 +98,16 @@ pub unsafe extern "C-unwind" fn plugin_open<T: SourcePlugin>(
     let plugin = plugin as *mut PluginWrapper<T>;
     unsafe {
         let Some(plugin) = plugin.as_mut() else {
+            strict::unexpected_input("plugin_open", "NULL plugin");
             return std::ptr::null_mut();
         };
         let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+            strict::unexpected_input("plugin_open", "plugin failed to initialize");
             return std::ptr::null_mut();
         };
 
         let Some(rc) = rc.as_mut() else {
+            strict::unexpected_input("plugin_open", "NULL rc");
             return std::ptr::null_mut();
         };
 
@@ -109,10 +117,7 @@ pub unsafe extern "C-unwind" fn plugin_open<T: SourcePlugin>(
             match try_str_from_ptr(&params) {
                 Ok(params) => Some(params),
                 Err(e) => {
-                    plugin
-                        .error_buf
-                        .write_into(|w| w.write_all(e.to_string().as_bytes()))
-                        .ok();
+                    plugin.error_buf.set(e);
                     *rc = ss_plugin_rc_SS_PLUGIN_FAILURE;
 
                     return std::ptr::null_mut();
@@ -120,12 +125,15 @@ pub unsafe extern "C-unwind" fn plugin_open<T: SourcePlugin>(
             }
         };
 
+        let _span = spans::open(T::NAME, params);
         match actual_plugin.plugin.open(params) {
             Ok(instance) => {
                 *rc = ss_plugin_rc_SS_PLUGIN_SUCCESS;
                 Box::into_raw(Box::new(SourcePluginInstanceWrapper {
                     instance,
                     batch: Default::default(),
+                    timestamps: MonotonicTimestamps::new(T::TIMESTAMP_POLICY),
+                    dedup: T::DEDUP_POLICY.map(DedupWindow::new),
                 }))
                 .cast()
             }
@@ -147,9 +155,11 @@ pub unsafe extern "C-unwind" fn plugin_close<T: SourcePlugin>(
 ) {
     let plugin = plugin as *mut PluginWrapper<T>;
     let Some(plugin) = plugin.as_mut() else {
+        strict::unexpected_input("plugin_close", "NULL plugin");
         return;
     };
     let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+        strict::unexpected_input("plugin_close", "plugin failed to initialize");
         return;
     };
 
@@ -173,25 +183,47 @@ pub unsafe extern "C-unwind" fn plugin_next_batch<T: SourcePlugin>(
     let instance = instance as *mut SourcePluginInstanceWrapper<T::Instance>;
     unsafe {
         let Some(plugin) = plugin.as_mut() else {
+            strict::unexpected_input("plugin_next_batch", "NULL plugin");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
         let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+            strict::unexpected_input("plugin_next_batch", "plugin failed to initialize");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
         let Some(instance) = instance.as_mut() else {
+            strict::unexpected_input("plugin_next_batch", "NULL instance");
             return ss_plugin_rc_SS_PLUGIN_FAILURE;
         };
 
+        let span = spans::next_batch(T::NAME);
         instance.batch.reset();
-        let mut batch = EventBatch::new(&mut instance.batch);
-        match instance
+        let limit = actual_plugin.plugin.batch_storage_limit();
+        instance.batch.set_allocation_limit(limit);
+        let mut batch = EventBatch::new(&mut instance.batch)
+            .with_timestamps(&mut instance.timestamps)
+            .with_dedup(instance.dedup.as_mut());
+        let result = instance
             .instance
-            .next_batch(&mut actual_plugin.plugin, &mut batch)
-        {
-            Ok(()) => {
+            .next_batch(&mut actual_plugin.plugin, &mut batch);
+        plugin.batch_storage_stats.record(batch.allocated_bytes());
+        if let Some(clamps) = batch.take_timestamp_clamps() {
+            *plugin.timestamp_clamps.get_or_insert(0) += clamps;
+        }
+        if let Some(dropped) = batch.take_dedup_dropped() {
+            *plugin.dedup_dropped.get_or_insert(0) += dropped;
+        }
+        let result =
+            result.and_then(|()| convert::checked(batch.get_events().len()).map_err(Into::into));
+        match result {
+            Ok(num_events) => {
                 let events = batch.get_events();
-                *nevts = events.len() as u32;
+                spans::record_batch(&span, events.len());
+                plugin
+                    .source_rates
+                    .get_or_insert_with(Default::default)
+                    .record(events.len(), batch.event_bytes());
+                *nevts = num_events;
                 *evts = events as *const _ as *mut _;
                 ss_plugin_rc_SS_PLUGIN_SUCCESS
             }
@@ -226,6 +258,7 @@ pub unsafe extern "C-unwind" fn plugin_get_progress<T: SourcePlugin>(
             None => std::ptr::null(),
         }
     } else {
+        strict::unexpected_input("plugin_get_progress", "NULL instance");
         unsafe {
             *progress_pct = 0;
         }
@@ -244,13 +277,16 @@ pub unsafe extern "C-unwind" fn plugin_event_to_string<T: SourcePlugin>(
     let plugin = plugin as *mut PluginWrapper<T>;
     unsafe {
         let Some(plugin) = plugin.as_mut() else {
+            strict::unexpected_input("plugin_event_to_string", "NULL plugin");
             return std::ptr::null();
         };
         let Some(ref mut actual_plugin) = &mut plugin.plugin else {
+            strict::unexpected_input("plugin_event_to_string", "plugin failed to initialize");
             return std::ptr::null();
         };
 
         let Some(event) = event.as_ref() else {
+            strict::unexpected_input("plugin_event_to_string", "NULL event");
             return std::ptr::null();
         };
         let event = EventInput(*event);
@@ -0,0 +1,145 @@
+//! Optional [`tracing`](https://docs.rs/tracing) spans around plugin callbacks
+//!
+//! With the `tracing` feature enabled, the FFI wrappers enter a span (with target `falco_plugin`)
+//! for the duration of every call into the plugin. Per-event callbacks get the event metadata
+//! attached as span fields. Without the feature, all of this compiles down to nothing.
+
+#[cfg(feature = "tracing")]
+use tracing::field::Empty;
+
+use crate::plugin::event::EventInput;
+use std::ffi::CStr;
+
+#[cfg(feature = "tracing")]
+pub(crate) type Span = tracing::span::EnteredSpan;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+#[cfg(feature = "tracing")]
+fn record_event(span: &tracing::Span, event: &EventInput) {
+    if span.is_disabled() {
+        return;
+    }
+
+    if let Some(source) = event.source() {
+        span.record("evt.source", source.to_string_lossy().as_ref());
+    }
+    if let Ok(raw) = event.event() {
+        span.record("evt.type", raw.event_type);
+        span.record("evt.ts", raw.metadata.ts);
+        span.record("evt.tid", raw.metadata.tid);
+    }
+}
+
+/// Enter a span for `Plugin::new`
+#[inline(always)]
+pub(crate) fn init(plugin: &CStr) -> Span {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::info_span!(target: "falco_plugin", "init", plugin = %plugin.to_string_lossy())
+            .entered()
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = plugin;
+        Span
+    }
+}
+
+/// Enter a span for `SourcePlugin::open`
+#[inline(always)]
+pub(crate) fn open(plugin: &CStr, params: Option<&str>) -> Span {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::info_span!(target: "falco_plugin", "open", plugin = %plugin.to_string_lossy(), params).entered()
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (plugin, params);
+        Span
+    }
+}
+
+/// Enter a span for `SourcePluginInstance::next_batch`
+///
+/// The number of generated events is recorded by [`record_batch`]
+#[inline(always)]
+pub(crate) fn next_batch(plugin: &CStr) -> Span {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::debug_span!(target: "falco_plugin", "next_batch", plugin = %plugin.to_string_lossy(), nevts = Empty)
+            .entered()
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = plugin;
+        Span
+    }
+}
+
+/// Record the number of events generated in a batch
+#[inline(always)]
+pub(crate) fn record_batch(span: &Span, nevts: usize) {
+    #[cfg(feature = "tracing")]
+    span.record("nevts", nevts);
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = (span, nevts);
+}
+
+/// Enter a span for `ParsePlugin::parse_event`
+#[inline(always)]
+pub(crate) fn parse_event(plugin: &CStr, event: &EventInput) -> Span {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::debug_span!(
+            target: "falco_plugin",
+            "parse_event",
+            plugin = %plugin.to_string_lossy(),
+            "evt.num" = event.event_number(),
+            "evt.source" = Empty,
+            "evt.type" = Empty,
+            "evt.ts" = Empty,
+            "evt.tid" = Empty,
+        );
+        record_event(&span, event);
+        span.entered()
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (plugin, event);
+        Span
+    }
+}
+
+/// Enter a span for `ExtractPlugin::extract_fields`
+#[inline(always)]
+pub(crate) fn extract_fields(plugin: &CStr, event: &EventInput, nfields: usize) -> Span {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::debug_span!(
+            target: "falco_plugin",
+            "extract_fields",
+            plugin = %plugin.to_string_lossy(),
+            nfields,
+            "evt.num" = event.event_number(),
+            "evt.source" = Empty,
+            "evt.type" = Empty,
+            "evt.ts" = Empty,
+            "evt.tid" = Empty,
+        );
+        record_event(&span, event);
+        span.entered()
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (plugin, event, nfields);
+        Span
+    }
+}
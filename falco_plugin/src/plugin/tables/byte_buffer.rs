@@ -0,0 +1,236 @@
+use crate::plugin::tables::byte_key::{hex_decode, hex_encode};
+use crate::plugin::tables::data::{seal, FieldTypeId, TableData, Value};
+use crate::plugin::tables::table::raw::RawTable;
+use crate::plugin::tables::traits::IntoOwnedValue;
+use crate::plugin::tables::vtable::TablesInput;
+use crate::strings::from_ptr::try_cstr_from_ptr;
+use falco_plugin_api::{ss_plugin_state_data, ss_plugin_table_field_t};
+use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Formatter};
+use thiserror::Error;
+
+/// # The default size limit of a [`ByteBuffer`], in bytes (before encoding)
+pub const MAX_BYTE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// # An error creating or decoding a [`ByteBuffer`]
+#[derive(Debug, Error)]
+pub enum ByteBufferError {
+    /// The data is larger than the limit
+    #[error("byte buffer too large ({size} bytes, limit is {limit})")]
+    TooLarge {
+        /// the size of the data, in bytes
+        size: usize,
+        /// the size limit
+        limit: usize,
+    },
+
+    /// The field does not contain a valid encoded byte buffer
+    #[error("invalid byte buffer encoding: {0}")]
+    Encoding(anyhow::Error),
+}
+
+/// # Binary data stored in a string table field
+///
+/// The plugin API has no byte buffer field type, so binary values (hashes, serialized messages etc.)
+/// are stored in string fields, encoded with [`hex_encode`](`crate::tables::hex_encode`),
+/// just like [`ByteKey`](`crate::tables::ByteKey`) does for keys. Other plugins see
+/// (and may set) the hex string, while your plugin works with the original bytes.
+///
+/// Since the encoding doubles the size of the data and the value is copied across the API
+/// boundary, buffers are limited to [`MAX_BYTE_BUFFER_SIZE`] bytes by default. Use
+/// [`ByteBuffer::with_limit`] and [`ByteBuffer::decode_with_limit`] to pick a different limit.
+///
+/// `ByteBuffer` can be used both as a field in exported tables (`Public<ByteBuffer>`)
+/// and as a field type in imported tables (`Field<ByteBuffer>`):
+///
+/// ```
+/// use falco_plugin::tables::ByteBuffer;
+///
+/// let buf = ByteBuffer::new(&[0xde, 0xad, 0xbe, 0xef]).unwrap();
+/// assert_eq!(buf.as_cstr(), c"deadbeef");
+/// assert_eq!(buf.decode().unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct ByteBuffer {
+    encoded: CString,
+}
+
+impl ByteBuffer {
+    /// Create a buffer from bytes, up to [`MAX_BYTE_BUFFER_SIZE`] bytes long
+    pub fn new(bytes: &[u8]) -> Result<Self, ByteBufferError> {
+        Self::with_limit(bytes, MAX_BYTE_BUFFER_SIZE)
+    }
+
+    /// Create a buffer from bytes, up to `limit` bytes long
+    pub fn with_limit(bytes: &[u8], limit: usize) -> Result<Self, ByteBufferError> {
+        if bytes.len() > limit {
+            return Err(ByteBufferError::TooLarge {
+                size: bytes.len(),
+                limit,
+            });
+        }
+
+        Ok(Self {
+            encoded: hex_encode(bytes),
+        })
+    }
+
+    /// # Wrap an encoded string (e.g. read from another plugin's table)
+    ///
+    /// The string is validated, so that every `ByteBuffer` decodes successfully
+    /// (with the default limit).
+    pub fn from_encoded(encoded: &CStr) -> Result<Self, ByteBufferError> {
+        let buf = Self {
+            encoded: encoded.to_owned(),
+        };
+        buf.decode()?;
+        Ok(buf)
+    }
+
+    /// Get the encoded representation, as seen by other plugins
+    pub fn as_cstr(&self) -> &CStr {
+        &self.encoded
+    }
+
+    /// The length of the decoded data, in bytes
+    pub fn len(&self) -> usize {
+        self.encoded.as_bytes().len() / 2
+    }
+
+    /// Check whether the buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.encoded.is_empty()
+    }
+
+    /// Decode the bytes, up to [`MAX_BYTE_BUFFER_SIZE`] bytes long
+    pub fn decode(&self) -> Result<Vec<u8>, ByteBufferError> {
+        self.decode_with_limit(MAX_BYTE_BUFFER_SIZE)
+    }
+
+    /// # Decode the bytes, up to `limit` bytes long
+    ///
+    /// The limit is checked before decoding, so oversized values written by other plugins
+    /// do not cause large allocations.
+    pub fn decode_with_limit(&self, limit: usize) -> Result<Vec<u8>, ByteBufferError> {
+        let size = self.encoded.as_bytes().len().div_ceil(2);
+        if size > limit {
+            return Err(ByteBufferError::TooLarge { size, limit });
+        }
+
+        hex_decode(&self.encoded).map_err(ByteBufferError::Encoding)
+    }
+}
+
+impl Debug for ByteBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ByteBuffer").field(&self.encoded).finish()
+    }
+}
+
+impl TryFrom<&[u8]> for ByteBuffer {
+    type Error = ByteBufferError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::new(bytes)
+    }
+}
+
+impl seal::Sealed for ByteBuffer {}
+
+impl TableData for ByteBuffer {
+    const TYPE_ID: FieldTypeId = FieldTypeId::String;
+
+    fn to_data(&self) -> ss_plugin_state_data {
+        ss_plugin_state_data {
+            str_: self.encoded.as_ptr(),
+        }
+    }
+}
+
+impl Value for ByteBuffer {
+    type AssocData = ();
+    /// The raw (not yet validated) value: call [`ByteBuffer::decode`] to get the bytes
+    type Value<'a> = ByteBuffer;
+
+    unsafe fn from_data_with_assoc<'a>(
+        data: &ss_plugin_state_data,
+        _assoc: &Self::AssocData,
+    ) -> Self::Value<'a> {
+        let encoded = unsafe { try_cstr_from_ptr(data.str_) }.unwrap_or(c"");
+        ByteBuffer {
+            encoded: encoded.to_owned(),
+        }
+    }
+
+    unsafe fn get_assoc_from_raw_table(
+        _table: &RawTable,
+        _field: *mut ss_plugin_table_field_t,
+        _tables_input: &TablesInput,
+    ) -> Result<Self::AssocData, anyhow::Error> {
+        Ok(())
+    }
+}
+
+impl IntoOwnedValue for ByteBuffer {
+    type Owned = ByteBuffer;
+
+    fn into_owned_value(self) -> Self::Owned {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ByteBuffer, ByteBufferError, MAX_BYTE_BUFFER_SIZE};
+    use crate::plugin::tables::data::{TableData, Value};
+
+    #[test]
+    fn test_byte_buffer_roundtrip() {
+        let bytes = [0x00, 0x01, 0x7f, 0x80, 0xff];
+        let buf = ByteBuffer::new(&bytes).unwrap();
+        assert_eq!(buf.len(), 5);
+        assert_eq!(buf.decode().unwrap(), bytes);
+
+        let data = buf.to_data();
+        let read = unsafe { ByteBuffer::from_data_with_assoc(&data, &()) };
+        assert_eq!(read, buf);
+
+        assert!(ByteBuffer::default().is_empty());
+        assert!(ByteBuffer::default().decode().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_byte_buffer_limits() {
+        let max = vec![0u8; MAX_BYTE_BUFFER_SIZE];
+        assert!(ByteBuffer::new(&max).is_ok());
+
+        let too_large = vec![0u8; MAX_BYTE_BUFFER_SIZE + 1];
+        assert!(matches!(
+            ByteBuffer::new(&too_large),
+            Err(ByteBufferError::TooLarge { size, limit })
+                if size == MAX_BYTE_BUFFER_SIZE + 1 && limit == MAX_BYTE_BUFFER_SIZE
+        ));
+
+        let buf = ByteBuffer::with_limit(&[1, 2, 3, 4], 4).unwrap();
+        assert!(ByteBuffer::with_limit(&[1, 2, 3, 4], 3).is_err());
+        assert!(buf.decode_with_limit(3).is_err());
+        assert_eq!(buf.decode_with_limit(4).unwrap(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_byte_buffer_invalid_encoding() {
+        assert!(ByteBuffer::from_encoded(c"0102").is_ok());
+        assert!(matches!(
+            ByteBuffer::from_encoded(c"not hex"),
+            Err(ByteBufferError::Encoding(_))
+        ));
+        assert!(ByteBuffer::from_encoded(c"abc").is_err());
+
+        // values set by other plugins are only validated on decode
+        let data = falco_plugin_api::ss_plugin_state_data {
+            str_: c"xyz".as_ptr(),
+        };
+        let read = unsafe { ByteBuffer::from_data_with_assoc(&data, &()) };
+        assert!(read.decode().is_err());
+    }
+}
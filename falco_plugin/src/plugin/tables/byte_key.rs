@@ -0,0 +1,181 @@
+use crate::plugin::tables::data::{seal, FieldTypeId, Key, TableData};
+use crate::strings::from_ptr::try_cstr_from_ptr;
+use falco_plugin_api::ss_plugin_state_data;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Formatter};
+
+/// # Encode bytes as a string usable as a table key
+///
+/// The result is a lowercase hex representation of `bytes`: it's deterministic (the same bytes
+/// always map to the same string) and never contains NUL bytes.
+pub fn hex_encode(bytes: &[u8]) -> CString {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut encoded = Vec::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        encoded.push(DIGITS[(b >> 4) as usize]);
+        encoded.push(DIGITS[(b & 0x0f) as usize]);
+    }
+
+    // SAFETY: hex digits are never NUL
+    unsafe { CString::from_vec_unchecked(encoded) }
+}
+
+/// # Decode a string produced by [`hex_encode`]
+///
+/// Only lowercase hex digits are accepted, so that every byte string has exactly one
+/// valid encoding.
+pub fn hex_decode(encoded: &CStr) -> Result<Vec<u8>, anyhow::Error> {
+    fn digit(c: u8) -> Result<u8, anyhow::Error> {
+        match c {
+            b'0'..=b'9' => Ok(c - b'0'),
+            b'a'..=b'f' => Ok(c - b'a' + 10),
+            _ => Err(anyhow::anyhow!("Invalid hex digit {:?}", c as char)),
+        }
+    }
+
+    let encoded = encoded.to_bytes();
+    if !encoded.len().is_multiple_of(2) {
+        anyhow::bail!("Odd length of hex string ({})", encoded.len());
+    }
+
+    encoded
+        .chunks_exact(2)
+        .map(|pair| Ok((digit(pair[0])? << 4) | digit(pair[1])?))
+        .collect()
+}
+
+/// # A table key made of arbitrary bytes
+///
+/// The plugin API only supports integer, boolean and string keys, so binary keys (e.g. content
+/// hashes) are represented as strings, using [`hex_encode`]. Other plugins see (and look up)
+/// the hex string, while your plugin works with the original bytes.
+///
+/// `B` is the storage type for the bytes, e.g. `Vec<u8>` or `[u8; 32]` for SHA-256 hashes:
+///
+/// ```
+/// use falco_plugin::tables::ByteKey;
+///
+/// let key = ByteKey::new([0xde, 0xad, 0xbe, 0xef]);
+/// assert_eq!(key.as_cstr(), c"deadbeef");
+/// assert_eq!(key.bytes(), &[0xde, 0xad, 0xbe, 0xef]);
+/// ```
+///
+/// Keys are compared (and ordered) by their bytes.
+#[derive(Clone)]
+pub struct ByteKey<B = Vec<u8>> {
+    bytes: B,
+    encoded: CString,
+}
+
+impl<B: AsRef<[u8]>> ByteKey<B> {
+    /// Create a key from bytes
+    pub fn new(bytes: B) -> Self {
+        let encoded = hex_encode(bytes.as_ref());
+        Self { bytes, encoded }
+    }
+
+    /// Get the bytes of the key
+    pub fn bytes(&self) -> &B {
+        &self.bytes
+    }
+
+    /// Get the string representation of the key, as seen by other plugins
+    pub fn as_cstr(&self) -> &CStr {
+        &self.encoded
+    }
+
+    /// Return the bytes of the key
+    pub fn into_inner(self) -> B {
+        self.bytes
+    }
+}
+
+impl<B: AsRef<[u8]>> From<B> for ByteKey<B> {
+    fn from(bytes: B) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl<B: AsRef<[u8]>> Debug for ByteKey<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ByteKey").field(&self.encoded).finish()
+    }
+}
+
+impl<B: AsRef<[u8]>> PartialEq for ByteKey<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes.as_ref() == other.bytes.as_ref()
+    }
+}
+
+impl<B: AsRef<[u8]>> Eq for ByteKey<B> {}
+
+impl<B: AsRef<[u8]>> PartialOrd for ByteKey<B> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<B: AsRef<[u8]>> Ord for ByteKey<B> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bytes.as_ref().cmp(other.bytes.as_ref())
+    }
+}
+
+impl<B> seal::Sealed for ByteKey<B> {}
+
+impl<B> TableData for ByteKey<B> {
+    const TYPE_ID: FieldTypeId = FieldTypeId::String;
+
+    fn to_data(&self) -> ss_plugin_state_data {
+        ss_plugin_state_data {
+            str_: self.encoded.as_ptr(),
+        }
+    }
+}
+
+impl<B: AsRef<[u8]> + TryFrom<Vec<u8>> + Clone> Key for ByteKey<B> {
+    unsafe fn from_data(data: &ss_plugin_state_data) -> Option<Cow<'_, Self>> {
+        let encoded = unsafe { try_cstr_from_ptr(data.str_) }?;
+        let bytes = hex_decode(encoded).ok()?.try_into().ok()?;
+
+        Some(Cow::Owned(Self {
+            bytes,
+            encoded: encoded.to_owned(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hex_decode, hex_encode, ByteKey};
+    use crate::plugin::tables::data::{Key, TableData};
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let bytes = [0x00, 0x01, 0x7f, 0x80, 0xff];
+        let encoded = hex_encode(&bytes);
+        assert_eq!(encoded.as_c_str(), c"00017f80ff");
+        assert_eq!(hex_decode(&encoded).unwrap(), bytes);
+
+        assert!(hex_decode(c"abc").is_err());
+        assert!(hex_decode(c"ABCD").is_err());
+        assert!(hex_decode(c"zz").is_err());
+    }
+
+    #[test]
+    fn test_key_from_data() {
+        let key = ByteKey::new([1u8, 2, 3, 4]);
+        let data = key.to_data();
+
+        let decoded = unsafe { ByteKey::<[u8; 4]>::from_data(&data) }.unwrap();
+        assert_eq!(*decoded, key);
+
+        // wrong length for the storage type
+        assert!(unsafe { ByteKey::<[u8; 8]>::from_data(&data) }.is_none());
+        assert!(unsafe { ByteKey::<Vec<u8>>::from_data(&data) }.is_some());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::plugin::tables::table::raw::RawTable;
+use crate::strings::from_ptr::try_cstr_from_ptr;
 use crate::tables::TablesInput;
 use falco_plugin_api::{
     ss_plugin_bool, ss_plugin_field_type_FTYPE_UINT64, ss_plugin_state_data,
@@ -10,6 +11,7 @@ use falco_plugin_api::{
     ss_plugin_table_field_t,
 };
 use num_derive::FromPrimitive;
+use std::borrow::Cow;
 use std::ffi::CStr;
 use std::fmt::{Debug, Formatter};
 
@@ -60,15 +62,19 @@ pub trait TableData: seal::Sealed {
 
 /// # A trait describing types usable as table keys
 pub trait Key: TableData {
-    /// # Borrow from the raw FFI representation
+    /// # Convert from the raw FFI representation
     ///
-    /// **Note**: this function only borrows the data and must return a reference.
-    /// This means that the types implementing this trait must be repr(C) and compatible
-    /// with the layout of `ss_plugin_state_data`.
+    /// Most key types borrow directly from the data (so they must be repr(C) and compatible
+    /// with the layout of `ss_plugin_state_data`), but some (like [`ByteKey`](`crate::tables::ByteKey`))
+    /// need to decode it into an owned value.
+    ///
+    /// Returns `None` if `data` is not a valid representation of the key.
     ///
     /// # Safety
     /// `data` must contain valid data of the correct type
-    unsafe fn from_data(data: &ss_plugin_state_data) -> &Self;
+    unsafe fn from_data(data: &ss_plugin_state_data) -> Option<Cow<'_, Self>>
+    where
+        Self: ToOwned;
 }
 
 /// # A trait describing types usable as table values
@@ -119,8 +125,8 @@ macro_rules! impl_table_data_direct {
         }
 
         impl Key for $ty {
-            unsafe fn from_data(data: &ss_plugin_state_data) -> &Self {
-                unsafe { &data.$field }
+            unsafe fn from_data(data: &ss_plugin_state_data) -> Option<Cow<'_, Self>> {
+                Some(Cow::Borrowed(unsafe { &data.$field }))
             }
         }
 
@@ -162,6 +168,10 @@ impl_table_data_direct!(i64 => s64: FieldTypeId::I64);
 /// value, we cannot convert it on the fly to the native Rust type.
 ///
 /// This type serves as a wrapper, exposing conversion methods to/from Rust bool.
+/// It can be compared directly with `bool` values and (de)serializes as a plain boolean.
+///
+/// Only table keys need to use this type. Imported table fields can be declared
+/// as `Field<bool, E>` instead, with the conversion done when reading and writing the field.
 #[derive(Copy, Clone)]
 #[repr(transparent)]
 pub struct Bool(pub(crate) ss_plugin_bool);
@@ -184,6 +194,57 @@ impl From<Bool> for bool {
     }
 }
 
+impl Default for Bool {
+    fn default() -> Self {
+        false.into()
+    }
+}
+
+impl std::fmt::Display for Bool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&bool::from(*self), f)
+    }
+}
+
+// any non-zero value is true, so compare the converted values, not the raw ones
+impl PartialEq for Bool {
+    fn eq(&self, other: &Self) -> bool {
+        bool::from(*self) == bool::from(*other)
+    }
+}
+
+impl Eq for Bool {}
+
+impl std::hash::Hash for Bool {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        bool::from(*self).hash(state)
+    }
+}
+
+impl PartialEq<bool> for Bool {
+    fn eq(&self, other: &bool) -> bool {
+        bool::from(*self) == *other
+    }
+}
+
+impl PartialEq<Bool> for bool {
+    fn eq(&self, other: &Bool) -> bool {
+        *self == bool::from(*other)
+    }
+}
+
+impl serde::Serialize for Bool {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool((*self).into())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Bool {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        bool::deserialize(deserializer).map(Into::into)
+    }
+}
+
 impl seal::Sealed for Bool {}
 
 impl TableData for Bool {
@@ -214,8 +275,43 @@ impl Value for Bool {
 }
 
 impl Key for Bool {
-    unsafe fn from_data(data: &ss_plugin_state_data) -> &Self {
-        unsafe { std::mem::transmute(&data.b) }
+    unsafe fn from_data(data: &ss_plugin_state_data) -> Option<Cow<'_, Self>> {
+        Some(Cow::Borrowed(unsafe {
+            std::mem::transmute::<&ss_plugin_bool, &Bool>(&data.b)
+        }))
+    }
+}
+
+// A plain `bool` has a different layout than `ss_plugin_bool`, so it cannot be borrowed
+// from the raw data and cannot be a [`Key`], but it works fine as a value
+impl seal::Sealed for bool {}
+
+impl TableData for bool {
+    const TYPE_ID: FieldTypeId = FieldTypeId::Bool;
+    fn to_data(&self) -> ss_plugin_state_data {
+        ss_plugin_state_data {
+            b: *self as ss_plugin_bool,
+        }
+    }
+}
+
+impl Value for bool {
+    type AssocData = ();
+    type Value<'a> = bool;
+
+    unsafe fn from_data_with_assoc<'a>(
+        data: &ss_plugin_state_data,
+        _assoc: &Self::AssocData,
+    ) -> Self::Value<'a> {
+        unsafe { data.b != 0 }
+    }
+
+    unsafe fn get_assoc_from_raw_table(
+        _table: &RawTable,
+        _field: *mut ss_plugin_table_field_t,
+        _tables_input: &TablesInput,
+    ) -> Result<Self::AssocData, anyhow::Error> {
+        Ok(())
     }
 }
 
@@ -232,8 +328,10 @@ impl TableData for CStr {
 }
 
 impl Key for CStr {
-    unsafe fn from_data(data: &ss_plugin_state_data) -> &CStr {
-        unsafe { CStr::from_ptr(data.str_) }
+    unsafe fn from_data(data: &ss_plugin_state_data) -> Option<Cow<'_, CStr>> {
+        // unlike a NULL value, a NULL key cannot be treated as an empty string:
+        // it would silently address a different entry
+        unsafe { try_cstr_from_ptr(data.str_) }.map(Cow::Borrowed)
     }
 }
 
@@ -245,7 +343,7 @@ impl Value for CStr {
         data: &ss_plugin_state_data,
         _assoc: &Self::AssocData,
     ) -> Self::Value<'a> {
-        unsafe { CStr::from_ptr(data.str_) }
+        unsafe { try_cstr_from_ptr(data.str_) }.unwrap_or(c"")
     }
 
     unsafe fn get_assoc_from_raw_table(
@@ -256,3 +354,44 @@ impl Value for CStr {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Bool, Key, TableData, Value};
+    use falco_plugin_api::ss_plugin_state_data;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_bool_conversions() {
+        let raw_true = Bool(2);
+        assert_eq!(raw_true, Bool::from(true));
+        assert_eq!(raw_true, true);
+        assert_eq!(false, Bool::default());
+        assert_eq!(raw_true.to_string(), "true");
+
+        assert_eq!(serde_json::to_string(&raw_true).unwrap(), "true");
+        let parsed: Bool = serde_json::from_str("false").unwrap();
+        assert_eq!(parsed, false);
+    }
+
+    #[test]
+    fn test_plain_bool_value() {
+        assert_eq!(unsafe { true.to_data().b }, 1);
+
+        let data = ss_plugin_state_data { b: 2 };
+        assert!(unsafe { bool::from_data_with_assoc(&data, &()) });
+    }
+
+    #[test]
+    fn test_null_string() {
+        let data = c"key".to_data();
+        let key = unsafe { CStr::from_data(&data) }.unwrap();
+        assert_eq!(key.as_ref(), c"key");
+
+        let data = ss_plugin_state_data {
+            str_: std::ptr::null(),
+        };
+        assert!(unsafe { CStr::from_data(&data) }.is_none());
+        assert_eq!(unsafe { CStr::from_data_with_assoc(&data, &()) }, c"");
+    }
+}
@@ -0,0 +1,224 @@
+use crate::plugin::error::as_result::{AsResult, WithLastError};
+use crate::plugin::tables::data::{FieldTypeId, Value};
+use crate::plugin::tables::entry::Entry;
+use crate::plugin::tables::field::Field;
+use crate::plugin::tables::vtable::{TableReader, TableWriter};
+use falco_plugin_api::{ss_plugin_state_data, ss_plugin_table_field_t};
+use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Formatter};
+
+/// A single buffered field write
+struct PendingWrite {
+    field: *const ss_plugin_table_field_t,
+    data: ss_plugin_state_data,
+    // string values are copied here, `data` points into this buffer
+    _owned: Option<CString>,
+}
+
+impl Debug for PendingWrite {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingWrite")
+            .field("field", &self.field)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The buffered writes, at most one per field, in the order of the first write to each field
+#[derive(Debug, Default)]
+struct PendingWrites(Vec<PendingWrite>);
+
+impl PendingWrites {
+    /// Buffer a write, replacing any pending write to the same field
+    ///
+    /// # Safety
+    /// `data` must contain valid data of type `type_id` (for strings: a valid C string pointer)
+    unsafe fn set(
+        &mut self,
+        field: *const ss_plugin_table_field_t,
+        type_id: FieldTypeId,
+        mut data: ss_plugin_state_data,
+    ) {
+        let owned = if type_id == FieldTypeId::String {
+            let owned = CString::from(unsafe { CStr::from_ptr(data.str_) });
+            data.str_ = owned.as_ptr();
+            Some(owned)
+        } else {
+            None
+        };
+
+        let write = PendingWrite {
+            field,
+            data,
+            _owned: owned,
+        };
+        match self.0.iter_mut().find(|w| w.field == field) {
+            Some(pending) => *pending = write,
+            None => self.0.push(write),
+        }
+    }
+
+    fn get(&self, field: *const ss_plugin_table_field_t) -> Option<&ss_plugin_state_data> {
+        self.0.iter().find(|w| w.field == field).map(|w| &w.data)
+    }
+}
+
+/// # A batch of buffered writes to a single table entry
+///
+/// Every [`Entry::write_field`] call crosses the plugin API boundary. When a plugin updates
+/// the same entry many times during a single callback (e.g. incrementing counters in a loop),
+/// it can use a write batch instead (see [`Entry::write_batch`]): writes to the same field
+/// are coalesced and only the last value of each field is written to the table, when
+/// the batch is flushed.
+///
+/// Reads through the batch ([`EntryWriteBatch::read_field`]) see the pending values,
+/// so the code doing the updates behaves the same as with unbuffered writes. Reads that bypass
+/// the batch (e.g. directly from the entry, or from another plugin) only see the changes
+/// after the batch is flushed.
+///
+/// The batch is flushed by calling [`EntryWriteBatch::flush`] or when it goes out of scope.
+/// Since it borrows the [`TableWriter`], it cannot outlive the callback that created it,
+/// so all writes reach the table before the callback returns. Errors that occur while flushing
+/// on drop can only be logged, so prefer calling [`EntryWriteBatch::flush`] explicitly.
+///
+/// ```ignore
+/// let mut batch = entry.write_batch(&parse_input.writer);
+/// for item in items {
+///     let count = batch.read_field(&parse_input.reader, &metadata.count)?;
+///     batch.write_field(&metadata.count, &(count + item.count))?;
+/// }
+/// batch.flush()?;
+/// ```
+pub struct EntryWriteBatch<'e, M> {
+    entry: &'e Entry<M>,
+    writer: &'e TableWriter,
+    pending: PendingWrites,
+}
+
+impl<M> Debug for EntryWriteBatch<'_, M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntryWriteBatch")
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'e, M> EntryWriteBatch<'e, M> {
+    pub(in crate::plugin::tables) fn new(entry: &'e Entry<M>, writer: &'e TableWriter) -> Self {
+        Self {
+            entry,
+            writer,
+            pending: Default::default(),
+        }
+    }
+
+    /// Get a field value for the entry, including pending writes
+    pub fn read_field<V: Value<AssocData = ()> + ?Sized>(
+        &self,
+        reader: &TableReader,
+        field: &Field<V, Entry<M>>,
+    ) -> Result<V::Value<'_>, anyhow::Error> {
+        field.validator.check(self.entry.table)?;
+        match self.pending.get(field.field.field) {
+            Some(data) => Ok(unsafe { V::from_data_with_assoc(data, &()) }),
+            None => self.entry.read_field(reader, field),
+        }
+    }
+
+    /// Set a field value for the entry
+    ///
+    /// The value is only written to the table when the batch is flushed. A later write
+    /// to the same field replaces the pending value.
+    pub fn write_field<V: Value<AssocData = ()> + ?Sized>(
+        &mut self,
+        field: &Field<V, Entry<M>>,
+        val: &V,
+    ) -> Result<(), anyhow::Error> {
+        field.validator.check(self.entry.table)?;
+        unsafe {
+            self.pending
+                .set(field.field.field, V::TYPE_ID, val.to_data())
+        };
+        Ok(())
+    }
+
+    /// Return the number of fields with pending writes
+    pub fn pending(&self) -> usize {
+        self.pending.0.len()
+    }
+
+    /// Write all pending values to the table
+    ///
+    /// If a write fails, the remaining values stay pending and the error is returned.
+    pub fn flush(&mut self) -> Result<(), anyhow::Error> {
+        let mut written = 0;
+        let result = self.pending.0.iter().try_for_each(|write| {
+            unsafe {
+                self.entry
+                    .raw_entry
+                    .write_field(self.writer, write.field, &write.data)
+                    .as_result()
+                    .with_last_error(&self.writer.last_error)?;
+            }
+            written += 1;
+            Ok(())
+        });
+
+        self.pending.0.drain(..written);
+        result
+    }
+}
+
+impl<M> Drop for EntryWriteBatch<'_, M> {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            log::warn!("Failed to flush table writes: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PendingWrites;
+    use crate::plugin::tables::data::{FieldTypeId, TableData};
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_coalesce_writes() {
+        // the field pointers are opaque, they only need to be distinct
+        let fields = [0u8; 3];
+        let field_a = fields[0..].as_ptr().cast();
+        let field_b = fields[1..].as_ptr().cast();
+        let mut pending = PendingWrites::default();
+
+        unsafe {
+            pending.set(field_a, FieldTypeId::U64, 1u64.to_data());
+            pending.set(field_b, FieldTypeId::U64, 2u64.to_data());
+            pending.set(field_a, FieldTypeId::U64, 3u64.to_data());
+        }
+
+        assert_eq!(pending.0.len(), 2);
+        assert_eq!(pending.0[0].field, field_a);
+        assert_eq!(unsafe { pending.get(field_a).unwrap().u64_ }, 3);
+        assert_eq!(unsafe { pending.get(field_b).unwrap().u64_ }, 2);
+        assert!(pending.get(fields[2..].as_ptr().cast()).is_none());
+    }
+
+    #[test]
+    fn test_read_after_write_string() {
+        let fields = [0u8; 1];
+        let field = fields.as_ptr().cast();
+        let mut pending = PendingWrites::default();
+
+        {
+            // the buffered value must not borrow from the written one
+            let value = c"first".to_owned();
+            unsafe { pending.set(field, FieldTypeId::String, value.as_c_str().to_data()) };
+        }
+        let data = pending.get(field).unwrap();
+        assert_eq!(unsafe { CStr::from_ptr(data.str_) }, c"first");
+
+        unsafe { pending.set(field, FieldTypeId::String, c"second".to_data()) };
+        let data = pending.get(field).unwrap();
+        assert_eq!(unsafe { CStr::from_ptr(data.str_) }, c"second");
+    }
+}
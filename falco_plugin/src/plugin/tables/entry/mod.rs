@@ -1,11 +1,16 @@
 use crate::plugin::error::as_result::{AsResult, WithLastError};
-use crate::plugin::tables::data::Value;
+use crate::plugin::tables::data::{Key, Value};
 use crate::plugin::tables::field::Field;
+use crate::plugin::tables::table::Table;
 use crate::plugin::tables::traits::{EntryWrite, TableMetadata};
 use crate::plugin::tables::vtable::{TableReader, TableWriter};
 use falco_plugin_api::ss_plugin_table_t;
+use std::fmt::{Debug, Formatter};
+use std::ops::ControlFlow;
 
+pub mod batch;
 pub(in crate::plugin::tables) mod raw;
+use batch::EntryWriteBatch;
 use raw::RawEntry;
 
 /// # An entry in a Falco plugin table
@@ -15,13 +20,19 @@ use raw::RawEntry;
 ///
 /// You can add methods to this type using the `#[derive(TableMetadata)]` macro.
 /// See the [module documentation](`crate::tables::import`) for details.
-#[derive(Debug)]
 pub struct Entry<M> {
     pub(in crate::plugin::tables) raw_entry: RawEntry,
     pub(in crate::plugin::tables) table: *mut ss_plugin_table_t,
     pub(in crate::plugin::tables) metadata: M,
 }
 
+// the entry is just an opaque pointer, so there's nothing useful to show
+impl<M> Debug for Entry<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Entry").finish_non_exhaustive()
+    }
+}
+
 impl<M: TableMetadata + Clone> crate::plugin::tables::traits::Entry for Entry<M> {
     type Metadata = M;
 
@@ -58,6 +69,27 @@ impl<M> Entry<M> {
         }
     }
 
+    /// Iterate over all entries of a nested table
+    ///
+    /// This reads the table-valued field and calls `func` for each entry in the nested table
+    /// (e.g. for every file descriptor of a thread). The iteration stops early if the closure
+    /// returns [`ControlFlow::Break`].
+    pub fn iter_subtable<K, E, NM, F>(
+        &self,
+        reader: &TableReader,
+        field: &Field<Table<K, E, NM>, Entry<M>>,
+        func: F,
+    ) -> Result<ControlFlow<()>, anyhow::Error>
+    where
+        K: Key + 'static,
+        E: crate::plugin::tables::traits::Entry<Metadata = NM> + 'static,
+        NM: TableMetadata + Clone + 'static,
+        F: FnMut(&mut E) -> ControlFlow<()>,
+    {
+        let table = self.read_field(reader, field)?;
+        Ok(table.iter_entries_mut(reader, func))
+    }
+
     /// Set a field value for this entry
     pub fn write_field<V: Value<AssocData = ()> + ?Sized>(
         &self,
@@ -73,6 +105,13 @@ impl<M> Entry<M> {
                 .with_last_error(&writer.last_error)
         }
     }
+
+    /// Start a batch of buffered writes to this entry
+    ///
+    /// See [`EntryWriteBatch`] for details.
+    pub fn write_batch<'e>(&'e self, writer: &'e TableWriter) -> EntryWriteBatch<'e, M> {
+        EntryWriteBatch::new(self, writer)
+    }
 }
 
 impl<M, V: Value<AssocData = ()> + ?Sized> EntryWrite<&Field<V, Entry<M>>, V> for Entry<M> {
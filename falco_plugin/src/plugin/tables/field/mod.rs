@@ -5,6 +5,7 @@ use crate::plugin::tables::runtime_table_validator::RuntimeTableValidator;
 use crate::plugin::tables::traits::RawFieldValueType;
 use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
+use thiserror::Error;
 
 pub(in crate::plugin::tables) mod raw;
 
@@ -22,17 +23,11 @@ pub struct Field<V: Value + ?Sized, T = RuntimeEntry<()>> {
     pub(in crate::plugin::tables) tag: PhantomData<T>,
 }
 
-impl<V, T> Debug for Field<V, T>
-where
-    V: Value + Debug + ?Sized,
-    V::AssocData: Debug,
-{
+impl<V: Value + ?Sized, T> Debug for Field<V, T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("field")
-            .field("field", &self.field)
-            .field("validator", &self.validator)
-            .field("tag", &self.tag)
-            .finish()
+        f.debug_struct("Field")
+            .field("type_id", &V::TYPE_ID)
+            .finish_non_exhaustive()
     }
 }
 
@@ -46,6 +41,15 @@ impl<V: Value + ?Sized, T> Field<V, T> {
     }
 }
 
+/// # An error returned when accessing an optional field missing from the table
+///
+/// Fields marked `#[optional]` in a `#[derive(TableMetadata)]` struct are allowed to be
+/// absent at initialization time (e.g. when running against an older Falco version).
+/// Reading such a field returns `Ok(None)`, but writing it fails with this error.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("field {0} is not available in this table")]
+pub struct FieldNotAvailable(pub &'static str);
+
 impl<V: Value + ?Sized, T> RawFieldValueType for Field<V, T> {
     type TableValue = V;
     type EntryValue<'a> = <V as Value>::Value<'a>
@@ -64,3 +68,20 @@ impl<V: Value + ?Sized, E> From<RawField<V>> for Field<V, E> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Field;
+    use crate::plugin::tables::field::raw::RawField;
+
+    #[test]
+    fn test_debug() {
+        let field: Field<u64> = RawField {
+            field: std::ptr::null_mut(),
+            assoc_data: (),
+        }
+        .into();
+
+        assert_eq!(format!("{:?}", field), "Field { type_id: U64, .. }");
+    }
+}
@@ -6,10 +6,12 @@ macro_rules! table_import_expose_internals {
         pub use $crate::plugin::tables::data::Value;
         pub use $crate::plugin::tables::traits::Entry;
         pub use $crate::plugin::tables::traits::EntryWrite;
+        pub use $crate::plugin::tables::traits::IntoOwnedValue;
         pub use $crate::plugin::tables::traits::RawFieldValueType;
         pub use $crate::plugin::tables::traits::TableAccess;
 
         pub use $crate::plugin::tables::traits::TableMetadata;
+        pub use $crate::plugin::tables::FieldNames;
         pub use $crate::plugin::tables::RawTable;
     };
 }
@@ -20,6 +22,7 @@ macro_rules! table_import_use_internals {
     () => {
         use $crate::internals::tables::Entry;
         use $crate::internals::tables::EntryWrite;
+        use $crate::internals::tables::IntoOwnedValue;
         use $crate::internals::tables::Key;
         use $crate::internals::tables::RawFieldValueType;
         use $crate::internals::tables::TableAccess;
@@ -30,24 +33,69 @@ macro_rules! table_import_use_internals {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! impl_import_table_metadata {
-    (for $meta:ident => { $($access_fn:ident($field:ident, $field_cstr:literal);)* }) => {
+    (for $meta:ident => { $($access_fn:ident($field:ident, $field_cstr:expr) $($optional:ident)?;)* }) => {
         impl $crate::internals::tables::TableMetadata for $meta {
             fn new(
                 raw_table: &$crate::internals::tables::RawTable,
                 tables_input: &$crate::tables::TablesInput)
             -> $crate::anyhow::Result<Self> {
-                Ok(Self {
-                    $($field: raw_table.$access_fn(tables_input, $field_cstr)?.into(),)*
-                })
+                // resolve all the fields before failing, so that the error lists
+                // every missing field, not just the first one
+                #[allow(unused_mut)]
+                let mut errors: Vec<String> = Vec::new();
+                $(
+                    let $field = $crate::impl_import_table_field!(
+                        raw_table, tables_input, $access_fn, $field_cstr $(, $optional)?
+                    )
+                    .map_err(|e: $crate::anyhow::Error| {
+                        errors.push(format!("{}: {:#}", stringify!($field), e))
+                    })
+                    .ok();
+                )*
+
+                match ($($field,)*) {
+                    ($(Some($field),)*) => Ok(Self {
+                        $($field,)*
+                    }),
+                    #[allow(unreachable_patterns)]
+                    _ => Err($crate::anyhow::anyhow!(
+                        "Failed to resolve table fields: {}",
+                        errors.join("; ")
+                    )),
+                }
             }
         }
     }
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_import_table_field {
+    ($raw_table:ident, $tables_input:ident, $access_fn:ident, $field_cstr:expr) => {
+        $raw_table
+            .$access_fn($tables_input, $field_cstr)
+            .map(Into::into)
+    };
+    ($raw_table:ident, $tables_input:ident, $access_fn:ident, $field_cstr:expr, optional) => {
+        match $raw_table.$access_fn($tables_input, $field_cstr) {
+            Ok(field) => Ok(Some(field.into())),
+            // only a missing field is fine, a field with a different type is still an error
+            Err(e) => {
+                use $crate::internals::tables::FieldNames;
+                if $raw_table.has_field($tables_input, ($field_cstr).field_names()) {
+                    Err(e)
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! impl_import_table_accessor_traits {
-    ($m:ident: $getter:ident,$table_getter:ident,$setter:ident) => {
+    ($m:ident: $getter:ident,$owned_getter:ident,$table_getter:ident,$iter:ident,$setter:ident $(, $sanitize:ident)?) => {
         #[allow(non_snake_case)]
         pub mod $m {
             #[allow(non_camel_case_types)]
@@ -61,6 +109,16 @@ macro_rules! impl_import_table_accessor_traits {
                 ) -> $crate::anyhow::Result<Self::EntryValue>;
             }
 
+            #[allow(non_camel_case_types)]
+            pub trait $owned_getter {
+                type OwnedValue;
+
+                fn $owned_getter(
+                    &self,
+                    reader: &$crate::tables::TableReader,
+                ) -> $crate::anyhow::Result<Self::OwnedValue>;
+            }
+
             #[allow(non_camel_case_types)]
             pub trait $table_getter<'a> {
                 type Key;
@@ -74,35 +132,71 @@ macro_rules! impl_import_table_accessor_traits {
             }
 
             #[allow(non_camel_case_types)]
-            pub trait $setter<'a> {
-                type ScalarValue: $crate::internals::tables::Value<AssocData = ()> + ?Sized;
+            pub trait $iter<'a> {
+                type Entry;
 
-                fn $setter(
+                fn $iter<F>(
                     &'a self,
-                    writer: &$crate::tables::TableWriter,
-                    value: &Self::ScalarValue,
-                ) -> $crate::anyhow::Result<()>;
+                    reader: &$crate::tables::TableReader,
+                    func: F,
+                ) -> $crate::anyhow::Result<std::ops::ControlFlow<()>>
+                where
+                    F: FnMut(&mut Self::Entry) -> std::ops::ControlFlow<()>;
             }
+
+            $crate::impl_import_table_setter_trait!($setter $($sanitize)?);
         }
 
         // make the traits available without a name, so we can
         // `use the_mod_the_macro_was_called_in::*` without polluting the outer namespace
         pub use $m::$getter as _;
+        pub use $m::$owned_getter as _;
+        pub use $m::$iter as _;
         pub use $m::$setter as _;
         pub use $m::$table_getter as _;
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_import_table_setter_trait {
+    ($setter:ident) => {
+        #[allow(non_camel_case_types)]
+        pub trait $setter<'a> {
+            type ScalarValue: $crate::internals::tables::Value<AssocData = ()> + ?Sized;
+
+            fn $setter(
+                &'a self,
+                writer: &$crate::tables::TableWriter,
+                value: &Self::ScalarValue,
+            ) -> $crate::anyhow::Result<()>;
+        }
+    };
+    ($setter:ident sanitize) => {
+        #[allow(non_camel_case_types)]
+        pub trait $setter<'a> {
+            fn $setter(
+                &'a self,
+                writer: &$crate::tables::TableWriter,
+                value: &[u8],
+            ) -> $crate::anyhow::Result<()>;
+        }
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! impl_import_table_accessor_impls {
     (use $m:path; $field:ident($field_ty:ty) for $entry_ty:ty; meta $meta_ty:ident =>
         $getter:ident,
+        $owned_getter:ident,
         $table_getter:ident,
-        $setter:ident) => {
+        $iter:ident,
+        $setter:ident
+        $(; sanitize($policy:expr))?) => {
         const _: () = {
             $crate::table_import_use_internals!();
-            use $m::{$getter, $setter, $table_getter};
+            use $m::{$getter, $iter, $owned_getter, $setter, $table_getter};
 
             impl<'a> $getter<'a> for $entry_ty {
                 type TableValue = <$field_ty as RawFieldValueType>::TableValue;
@@ -117,6 +211,18 @@ macro_rules! impl_import_table_accessor_impls {
                 }
             }
 
+            impl $owned_getter for $entry_ty {
+                type OwnedValue =
+                    <<$field_ty as RawFieldValueType>::EntryValue<'static> as IntoOwnedValue>::Owned;
+
+                fn $owned_getter(
+                    &self,
+                    reader: &$crate::tables::TableReader,
+                ) -> $crate::anyhow::Result<Self::OwnedValue> {
+                    Ok(self.$getter(reader)?.into_owned_value())
+                }
+            }
+
             impl<'a, E> $table_getter<'a> for E
             where
                 E: $getter<'a>,
@@ -137,40 +243,205 @@ macro_rules! impl_import_table_accessor_impls {
                 }
             }
 
-            impl<'a, E> $setter<'a> for E
+            impl<'a, E> $iter<'a> for E
             where
-                E: 'a,
                 E: $getter<'a>,
-                E::TableValue: Value<AssocData = ()>,
-                E: EntryWrite<&'a $field_ty, E::TableValue>,
-                E: Entry<Metadata = std::sync::Arc<$meta_ty>>,
+                <E as $getter<'a>>::EntryValue: TableAccess,
+                <<E as $getter<'a>>::EntryValue as TableAccess>::Entry: Entry + 'static,
             {
-                type ScalarValue = E::TableValue;
+                type Entry = <<E as $getter<'a>>::EntryValue as TableAccess>::Entry;
 
-                fn $setter(
+                fn $iter<F>(
                     &'a self,
-                    writer: &$crate::tables::TableWriter,
-                    value: &Self::ScalarValue,
-                ) -> $crate::anyhow::Result<()> {
+                    reader: &$crate::tables::TableReader,
+                    func: F,
+                ) -> $crate::anyhow::Result<std::ops::ControlFlow<()>>
+                where
+                    F: FnMut(&mut Self::Entry) -> std::ops::ControlFlow<()>,
+                {
+                    let value = self.$getter(reader)?;
+                    Ok(value.iter_entries_mut(reader, func))
+                }
+            }
+
+            $crate::impl_import_table_setter_impl!(
+                [] $field($field_ty); meta $meta_ty => $getter, $setter $(; sanitize($policy))?
+            );
+        };
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_import_table_optional_accessor_impls {
+    (use $m:path; $field:ident($field_ty:ty) for $entry_ty:ty; meta $meta_ty:ident =>
+        $getter:ident,
+        $owned_getter:ident,
+        $table_getter:ident,
+        $iter:ident,
+        $setter:ident
+        $(; sanitize($policy:expr))?) => {
+        const _: () = {
+            $crate::table_import_use_internals!();
+            use $m::{$getter, $iter, $owned_getter, $setter, $table_getter};
+
+            impl<'a> $getter<'a> for $entry_ty {
+                type TableValue = <$field_ty as RawFieldValueType>::TableValue;
+                type EntryValue = Option<<$field_ty as RawFieldValueType>::EntryValue<'a>>;
+
+                fn $getter(
+                    &'a self,
+                    reader: &$crate::tables::TableReader,
+                ) -> $crate::anyhow::Result<Self::EntryValue> {
                     let metadata = self.get_metadata();
-                    self.write_field(writer, &metadata.$field, value)
+                    match &metadata.$field {
+                        Some(field) => self.read_field(reader, field).map(Some),
+                        None => Ok(None),
+                    }
+                }
+            }
+
+            impl $owned_getter for $entry_ty {
+                type OwnedValue = Option<
+                    <<$field_ty as RawFieldValueType>::EntryValue<'static> as IntoOwnedValue>::Owned,
+                >;
+
+                fn $owned_getter(
+                    &self,
+                    reader: &$crate::tables::TableReader,
+                ) -> $crate::anyhow::Result<Self::OwnedValue> {
+                    Ok(self.$getter(reader)?.map(IntoOwnedValue::into_owned_value))
                 }
             }
+
+            impl<'a, E, V> $table_getter<'a> for E
+            where
+                E: $getter<'a, EntryValue = Option<V>>,
+                V: TableAccess,
+                <V as TableAccess>::Key: Key,
+                <V as TableAccess>::Entry: Entry + 'static,
+            {
+                type Key = <V as TableAccess>::Key;
+                type Entry = <V as TableAccess>::Entry;
+
+                fn $table_getter(
+                    &'a self,
+                    reader: &$crate::tables::TableReader,
+                    key: &Self::Key,
+                ) -> $crate::anyhow::Result<Self::Entry> {
+                    match self.$getter(reader)? {
+                        Some(value) => value.get_entry(reader, key),
+                        None => Err($crate::tables::import::FieldNotAvailable(stringify!($field)).into()),
+                    }
+                }
+            }
+
+            impl<'a, E, V> $iter<'a> for E
+            where
+                E: $getter<'a, EntryValue = Option<V>>,
+                V: TableAccess,
+                <V as TableAccess>::Entry: Entry + 'static,
+            {
+                type Entry = <V as TableAccess>::Entry;
+
+                fn $iter<F>(
+                    &'a self,
+                    reader: &$crate::tables::TableReader,
+                    func: F,
+                ) -> $crate::anyhow::Result<std::ops::ControlFlow<()>>
+                where
+                    F: FnMut(&mut Self::Entry) -> std::ops::ControlFlow<()>,
+                {
+                    match self.$getter(reader)? {
+                        Some(value) => Ok(value.iter_entries_mut(reader, func)),
+                        None => Err($crate::tables::import::FieldNotAvailable(stringify!($field)).into()),
+                    }
+                }
+            }
+
+            $crate::impl_import_table_setter_impl!(
+                [optional] $field($field_ty); meta $meta_ty => $getter, $setter $(; sanitize($policy))?
+            );
         };
     };
 }
 
+/// Implement the setter trait for an imported table field
+///
+/// This needs to be called inside a block with the setter trait and
+/// `table_import_use_internals!()` in scope.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_import_table_setter_impl {
+    (@write optional $self:ident, $writer:ident, $field:ident, $value:expr) => {{
+        let metadata = $self.get_metadata();
+        match &metadata.$field {
+            Some(field) => $self.write_field($writer, field, $value),
+            None => Err($crate::tables::import::FieldNotAvailable(stringify!($field)).into()),
+        }
+    }};
+    (@write $self:ident, $writer:ident, $field:ident, $value:expr) => {{
+        let metadata = $self.get_metadata();
+        $self.write_field($writer, &metadata.$field, $value)
+    }};
+    ([$($optional:ident)?] $field:ident($field_ty:ty); meta $meta_ty:ident => $getter:ident, $setter:ident) => {
+        impl<'a, E> $setter<'a> for E
+        where
+            E: 'a,
+            E: $getter<'a>,
+            E::TableValue: Value<AssocData = ()>,
+            E: EntryWrite<&'a $field_ty, E::TableValue>,
+            E: Entry<Metadata = std::sync::Arc<$meta_ty>>,
+        {
+            type ScalarValue = E::TableValue;
+
+            fn $setter(
+                &'a self,
+                writer: &$crate::tables::TableWriter,
+                value: &Self::ScalarValue,
+            ) -> $crate::anyhow::Result<()> {
+                $crate::impl_import_table_setter_impl!(
+                    @write $($optional)? self, writer, $field, value
+                )
+            }
+        }
+    };
+    ([$($optional:ident)?] $field:ident($field_ty:ty); meta $meta_ty:ident => $getter:ident, $setter:ident; sanitize($policy:expr)) => {
+        impl<'a, E> $setter<'a> for E
+        where
+            E: 'a,
+            E: $getter<'a, TableValue = std::ffi::CStr>,
+            E: EntryWrite<&'a $field_ty, std::ffi::CStr>,
+            E: Entry<Metadata = std::sync::Arc<$meta_ty>>,
+        {
+            fn $setter(
+                &'a self,
+                writer: &$crate::tables::TableWriter,
+                value: &[u8],
+            ) -> $crate::anyhow::Result<()> {
+                let policy: $crate::strings::NulPolicy = $policy;
+                let value = policy.sanitize(value)?;
+                $crate::impl_import_table_setter_impl!(
+                    @write $($optional)? self, writer, $field, value.as_c_str()
+                )
+            }
+        }
+    };
+}
+
 #[cfg(test)]
 #[allow(unused)]
 mod tests {
     use crate::plugin::tables::field::Field;
     use crate::plugin::tables::Entry;
-    use std::ffi::CStr;
+    use crate::tables::TableReader;
+    use std::ffi::{CStr, CString};
     use std::sync::Arc;
 
     struct ImportedMeta {
         u64_field: Field<u64, ImportedEntry>,
         string_field: Field<CStr, ImportedEntry>,
+        optional_field: Option<Field<u32, ImportedEntry>>,
     }
 
     type ImportedEntry = Entry<Arc<ImportedMeta>>;
@@ -178,14 +449,44 @@ mod tests {
     impl_import_table_metadata!(for ImportedMeta => {
         get_field(u64_field, c"u64_field");
         add_field(string_field, c"string_field");
+        get_field(optional_field, c"optional_field") optional;
     });
 
     mod private {
-        impl_import_table_accessor_traits!(__private_ImportedMeta: get_u64_field, get_u64_field_by_key, set_u64_field);
+        impl_import_table_accessor_traits!(__private_ImportedMeta: get_u64_field, get_u64_field_owned, get_u64_field_by_key, iter_u64_field, set_u64_field);
+        impl_import_table_accessor_traits!(__private_ImportedMeta_optional: get_optional_field, get_optional_field_owned, get_optional_field_by_key, iter_optional_field, set_optional_field);
+        impl_import_table_accessor_traits!(__private_ImportedMeta_string: get_string_field, get_string_field_owned, get_string_field_by_key, iter_string_field, set_string_field, sanitize);
     }
 
     impl_import_table_accessor_impls!(
         use private::__private_ImportedMeta;
         u64_field(Field<u64, ImportedEntry>) for ImportedEntry; meta ImportedMeta =>
-            get_u64_field, get_u64_field_by_key, set_u64_field);
+            get_u64_field, get_u64_field_owned, get_u64_field_by_key, iter_u64_field, set_u64_field);
+
+    impl_import_table_optional_accessor_impls!(
+        use private::__private_ImportedMeta_optional;
+        optional_field(Field<u32, ImportedEntry>) for ImportedEntry; meta ImportedMeta =>
+            get_optional_field, get_optional_field_owned, get_optional_field_by_key, iter_optional_field, set_optional_field);
+
+    impl_import_table_accessor_impls!(
+        use private::__private_ImportedMeta_string;
+        string_field(Field<CStr, ImportedEntry>) for ImportedEntry; meta ImportedMeta =>
+            get_string_field, get_string_field_owned, get_string_field_by_key, iter_string_field, set_string_field;
+            sanitize(crate::strings::NulPolicy::Strip));
+
+    // the owned getters must not borrow from the entry
+    fn read_owned(
+        entry: &ImportedEntry,
+        reader: &TableReader,
+    ) -> anyhow::Result<(u64, CString, Option<u32>)> {
+        use private::__private_ImportedMeta::get_u64_field_owned;
+        use private::__private_ImportedMeta_optional::get_optional_field_owned;
+        use private::__private_ImportedMeta_string::get_string_field_owned;
+
+        Ok((
+            entry.get_u64_field_owned(reader)?,
+            entry.get_string_field_owned(reader)?,
+            entry.get_optional_field_owned(reader)?,
+        ))
+    }
 }
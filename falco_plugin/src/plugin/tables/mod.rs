@@ -1,7 +1,10 @@
+pub mod byte_buffer;
+pub mod byte_key;
 pub mod data;
 pub mod entry;
 pub mod field;
 pub mod macros;
+pub mod ordered;
 pub mod runtime;
 pub(in crate::plugin::tables) mod runtime_table_validator;
 pub mod table;
@@ -9,4 +12,4 @@ pub mod traits;
 pub mod vtable;
 
 pub use entry::Entry;
-pub use table::raw::RawTable;
+pub use table::raw::{FieldNames, RawTable};
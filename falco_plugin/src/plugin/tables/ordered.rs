@@ -0,0 +1,239 @@
+use crate::plugin::tables::data::Key;
+use crate::plugin::tables::table::Table;
+use crate::plugin::tables::traits::{Entry, TableMetadata};
+use crate::plugin::tables::vtable::TableReader;
+use anyhow::Error;
+use std::collections::BTreeSet;
+use std::ops::{ControlFlow, RangeBounds};
+use std::time::{Duration, Instant};
+
+/// # A sorted key index over an imported table
+///
+/// The plugin API has no notion of key order: entries can only be looked up by key
+/// or iterated over in whatever order the table owner chooses (and the iteration does not
+/// even provide the keys). `OrderedKeys` keeps a sorted set of keys on the plugin side,
+/// so that the table can be traversed by key, e.g. by time bucket:
+///
+/// ```ignore
+/// // in parse_event, when the plugin itself inserts an entry
+/// self.buckets.insert(&reader, &writer, &bucket, entry)?;
+/// self.bucket_keys.insert(bucket);
+///
+/// // later: the 10 oldest buckets, in key order
+/// for (bucket, entry) in self.bucket_keys.entries(&self.buckets, &reader, ..).take(10) {
+///     // ...
+/// }
+/// ```
+///
+/// ## Staleness
+///
+/// The index only knows about the changes it's told about:
+/// - if the plugin owns all insertions and removals, calling [`OrderedKeys::insert`]
+///   and [`OrderedKeys::remove`] alongside them keeps the index exact,
+/// - otherwise (e.g. for tables filled in by Falco core or other plugins), call
+///   [`OrderedKeys::scan`] periodically to rebuild the index from the whole table.
+///   Use [`OrderedKeys::is_stale`] to decide when a rescan is due.
+///
+/// Between scans, the index may miss new entries and may still contain keys that have since
+/// been removed from the table. The latter are skipped by [`OrderedKeys::entries`] and can be
+/// dropped with [`OrderedKeys::prune`], but the former only show up after the next scan.
+#[derive(Debug)]
+pub struct OrderedKeys<K> {
+    keys: BTreeSet<K>,
+    scanned_at: Option<Instant>,
+}
+
+impl<K> Default for OrderedKeys<K> {
+    fn default() -> Self {
+        Self {
+            keys: BTreeSet::new(),
+            scanned_at: None,
+        }
+    }
+}
+
+impl<K: Key + Ord + Clone> OrderedKeys<K> {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a key inserted into the table
+    ///
+    /// Returns `true` if the key was not in the index yet.
+    pub fn insert(&mut self, key: K) -> bool {
+        self.keys.insert(key)
+    }
+
+    /// Record a key erased from the table
+    ///
+    /// Returns `true` if the key was in the index.
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.keys.remove(key)
+    }
+
+    /// Forget all keys (e.g. after clearing the table)
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// # Rebuild the index from a full table scan
+    ///
+    /// Since iterating over a table does not yield the keys, `key_of` needs to read the key
+    /// from the entry (most tables keep a copy of the key in one of the fields, e.g. the thread
+    /// id in the thread table). If it fails for any entry, or the iteration is stopped
+    /// by the framework before visiting all entries, the scan is aborted and the index
+    /// is left unchanged.
+    pub fn scan<E, M>(
+        &mut self,
+        table: &Table<K, E, M>,
+        reader_vtable: &TableReader,
+        mut key_of: impl FnMut(&mut E) -> Result<K, Error>,
+    ) -> Result<(), Error>
+    where
+        E: Entry<Metadata = M>,
+        M: TableMetadata + Clone,
+    {
+        let mut keys = BTreeSet::new();
+        let mut error = None;
+        let flow = table.iter_entries_mut(reader_vtable, |entry| match key_of(entry) {
+            Ok(key) => {
+                keys.insert(key);
+                ControlFlow::Continue(())
+            }
+            Err(e) => {
+                error = Some(e);
+                ControlFlow::Break(())
+            }
+        });
+
+        if let Some(e) = error {
+            return Err(e);
+        }
+        if flow.is_break() {
+            anyhow::bail!("Table iteration stopped before all keys were read");
+        }
+
+        self.keys = keys;
+        self.scanned_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// When the index was last rebuilt with [`OrderedKeys::scan`]
+    pub fn scanned_at(&self) -> Option<Instant> {
+        self.scanned_at
+    }
+
+    /// Check whether the last scan is older than `max_age` (or there was none at all)
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        match self.scanned_at {
+            Some(scanned_at) => scanned_at.elapsed() > max_age,
+            None => true,
+        }
+    }
+
+    /// The number of keys in the index
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check whether the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Check whether the index contains a key
+    pub fn contains(&self, key: &K) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Iterate over all keys, in order
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> {
+        self.keys.iter()
+    }
+
+    /// Iterate over a range of keys, in order
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> impl DoubleEndedIterator<Item = &K> {
+        self.keys.range(range)
+    }
+
+    /// Get the smallest key
+    pub fn first(&self) -> Option<&K> {
+        self.keys.first()
+    }
+
+    /// Get the largest key
+    pub fn last(&self) -> Option<&K> {
+        self.keys.last()
+    }
+
+    /// # Iterate over the table entries for a range of keys, in key order
+    ///
+    /// The entries are looked up lazily, one key at a time. Keys no longer present
+    /// in the table are skipped.
+    pub fn entries<'a, E, M, R>(
+        &'a self,
+        table: &'a Table<K, E, M>,
+        reader_vtable: &'a TableReader,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (&'a K, E)> + 'a
+    where
+        E: Entry<Metadata = M> + 'a,
+        M: TableMetadata + Clone + 'a,
+        R: RangeBounds<K> + 'a,
+    {
+        self.keys.range(range).filter_map(move |key| {
+            let entry = table.get_entry(reader_vtable, key).ok()?;
+            Some((key, entry))
+        })
+    }
+
+    /// # Drop keys no longer present in the table
+    ///
+    /// This looks up every key in the index. Returns the number of dropped keys.
+    pub fn prune<E, M>(&mut self, table: &Table<K, E, M>, reader_vtable: &TableReader) -> usize
+    where
+        E: Entry<Metadata = M>,
+        M: TableMetadata + Clone,
+    {
+        let before = self.keys.len();
+        self.keys
+            .retain(|key| table.get_entry(reader_vtable, key).is_ok());
+        before - self.keys.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderedKeys;
+    use std::time::Duration;
+
+    #[test]
+    fn test_ordered_keys() {
+        let mut keys = OrderedKeys::<u64>::new();
+        assert!(keys.is_stale(Duration::from_secs(3600)));
+
+        for key in [30, 10, 20, 40] {
+            assert!(keys.insert(key));
+        }
+        assert!(!keys.insert(10));
+        assert!(keys.remove(&40));
+        assert!(!keys.remove(&40));
+
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys.keys().copied().collect::<Vec<_>>(), [10, 20, 30]);
+        assert_eq!(keys.range(15..).copied().collect::<Vec<_>>(), [20, 30]);
+        assert_eq!(
+            keys.range(..=20).rev().copied().collect::<Vec<_>>(),
+            [20, 10]
+        );
+        assert_eq!(keys.first(), Some(&10));
+        assert_eq!(keys.last(), Some(&30));
+
+        // inserts do not count as a scan
+        assert!(keys.scanned_at().is_none());
+
+        keys.clear();
+        assert!(keys.is_empty());
+    }
+}
@@ -8,14 +8,23 @@ use crate::plugin::tables::vtable::{TableFields, TableReader, TableWriter, Table
 use crate::strings::from_ptr::FromPtrError;
 use anyhow::Error;
 use falco_plugin_api::{ss_plugin_state_data, ss_plugin_table_field_t, ss_plugin_table_fieldinfo};
+use std::collections::BTreeMap;
 use std::ffi::CStr;
+use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
 use std::ops::ControlFlow;
 
 pub(in crate::plugin::tables) mod raw;
 
+/// # An error returned when looking up a key that's not in the table
+///
+/// Use `err.is::<EntryNotFound>()` to tell a missing entry apart from other failures
+/// of [`Table::get_entry`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("table entry not found")]
+pub struct EntryNotFound;
+
 /// # A table imported via the Falco plugin API
-#[derive(Debug)]
 pub struct Table<K, E = super::entry::Entry<NoMetadata<()>>, M = <E as Entry>::Metadata> {
     pub(in crate::plugin::tables) raw_table: RawTable,
     pub(in crate::plugin::tables) metadata: M,
@@ -24,6 +33,15 @@ pub struct Table<K, E = super::entry::Entry<NoMetadata<()>>, M = <E as Entry>::M
     pub(in crate::plugin::tables) entry_type: PhantomData<E>,
 }
 
+impl<K: Key, E, M> Debug for Table<K, E, M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Table")
+            .field("key_type", &K::TYPE_ID)
+            .field("is_nested", &self.is_nested)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<K, E, M> TableAccess for Table<K, E, M>
 where
     K: Key,
@@ -51,6 +69,13 @@ where
     {
         Table::get_entry(self, reader_vtable, key)
     }
+
+    fn iter_entries_mut<F>(&self, reader_vtable: &TableReader, func: F) -> ControlFlow<()>
+    where
+        F: FnMut(&mut Self::Entry) -> ControlFlow<()>,
+    {
+        Table::iter_entries_mut(self, reader_vtable, func)
+    }
 }
 
 impl<K, E, M> Table<K, E, M>
@@ -59,6 +84,30 @@ where
     E: Entry<Metadata = M>,
     M: TableMetadata + Clone,
 {
+    /// Import a table from the Falco plugin API
+    ///
+    /// This is equivalent to [`TablesInput::get_table`], but lets you name the table type
+    /// at the call site (usually via a type alias), e.g.:
+    ///
+    /// ```ignore
+    /// type ImportedThingTable = import::Table<u64, ImportedThing>;
+    ///
+    /// let things = ImportedThingTable::import(input, c"things")?;
+    /// ```
+    ///
+    /// If any of the fields described by the entry metadata cannot be found,
+    /// the error lists all of them.
+    pub fn import(tables_input: &TablesInput, name: &CStr) -> Result<Self, Error> {
+        tables_input.get_table(name)
+    }
+
+    /// Import a table from the Falco plugin API, if it exists
+    ///
+    /// Returns `Ok(None)` if there is no table called `name`, see [`TablesInput::try_get_table`].
+    pub fn try_import(tables_input: &TablesInput, name: &CStr) -> Result<Option<Self>, Error> {
+        tables_input.try_get_table(name)
+    }
+
     /// Look up an entry in `table` corresponding to `key`
     pub fn get_entry(&self, reader_vtable: &TableReader, key: &K) -> Result<E, Error> {
         let raw_entry = self.raw_table.get_entry(reader_vtable, key)?;
@@ -69,6 +118,45 @@ where
         ))
     }
 
+    /// Look up the entries corresponding to several keys at once
+    ///
+    /// Keys without a corresponding entry are skipped, so the returned map only contains
+    /// the entries that were found. Any other error (e.g. a failure in the host) is returned.
+    /// Duplicate keys are looked up only once.
+    ///
+    /// This is a convenient way to resolve a handful of related keys (e.g. the thread,
+    /// its parent and its session leader) in a single call:
+    ///
+    /// ```ignore
+    /// let threads = self.threads.get_entries(&reader, [&tid, &ptid, &sid])?;
+    /// if let Some(parent) = threads.get(&ptid) {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn get_entries<'k>(
+        &self,
+        reader_vtable: &TableReader,
+        keys: impl IntoIterator<Item = &'k K>,
+    ) -> Result<BTreeMap<&'k K, E>, Error>
+    where
+        K: Ord + 'k,
+    {
+        let mut entries = BTreeMap::new();
+        for key in keys {
+            if entries.contains_key(key) {
+                continue;
+            }
+            match self.get_entry(reader_vtable, key) {
+                Ok(entry) => {
+                    entries.insert(key, entry);
+                }
+                Err(e) if e.is::<EntryNotFound>() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(entries)
+    }
+
     /// Erase a table entry by key
     pub fn erase(&self, writer_vtable: &TableWriter, key: &K) -> Result<(), Error> {
         unsafe { self.raw_table.erase(writer_vtable, key) }
@@ -256,6 +344,10 @@ where
     ///
     /// The iteration stops when either all entries have been processed or the closure returns
     /// [`ControlFlow::Break`].
+    ///
+    /// The closure runs inside a callback invoked by the plugin framework, so if it panics,
+    /// the panic is caught, the iteration is stopped and the panic is resumed only after
+    /// the framework returns control to the plugin.
     pub fn iter_entries_mut<F>(&self, reader_vtable: &TableReader, mut func: F) -> ControlFlow<()>
     where
         F: FnMut(&mut E) -> ControlFlow<()>,
@@ -308,3 +400,29 @@ where
         })?
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Table;
+    use crate::plugin::tables::runtime::RuntimeEntry;
+    use crate::plugin::tables::table::raw::RawTable;
+    use std::marker::PhantomData;
+
+    #[test]
+    fn test_debug() {
+        let table: Table<u32, RuntimeEntry<()>, ()> = Table {
+            raw_table: RawTable {
+                table: std::ptr::null_mut(),
+            },
+            metadata: (),
+            is_nested: true,
+            key_type: PhantomData,
+            entry_type: PhantomData,
+        };
+
+        assert_eq!(
+            format!("{:?}", table),
+            "Table { key_type: U32, is_nested: true, .. }"
+        );
+    }
+}
@@ -1,19 +1,42 @@
+use crate::plugin::convert;
 use crate::plugin::error::as_result::{AsResult, WithLastError};
 use crate::plugin::tables::data::{FieldTypeId, Key, Value};
 use crate::plugin::tables::entry::raw::RawEntry;
 use crate::plugin::tables::field::raw::RawField;
+use crate::plugin::tables::table::EntryNotFound;
 use crate::plugin::tables::traits::TableMetadata;
 use crate::plugin::tables::vtable::TableFields;
 use crate::plugin::tables::vtable::{TableReader, TableWriter, TablesInput};
-use crate::strings::from_ptr::{try_str_from_ptr_with_lifetime, FromPtrError};
+use crate::strings::from_ptr::{try_cstr_from_ptr, try_str_from_ptr_with_lifetime, FromPtrError};
 use falco_plugin_api::{
     ss_plugin_bool, ss_plugin_rc_SS_PLUGIN_SUCCESS, ss_plugin_state_data, ss_plugin_state_type,
     ss_plugin_table_entry_t, ss_plugin_table_field_t, ss_plugin_table_fieldinfo,
     ss_plugin_table_iterator_func_t, ss_plugin_table_iterator_state_t, ss_plugin_table_t,
 };
 use num_traits::FromPrimitive;
+use std::any::Any;
 use std::ffi::CStr;
 use std::ops::ControlFlow;
+use std::panic::AssertUnwindSafe;
+
+/// The name(s) of an imported field, as passed to [`RawTable::get_field`]
+/// or [`RawTable::get_field_any`]
+#[doc(hidden)]
+pub trait FieldNames {
+    fn field_names(&self) -> &[&CStr];
+}
+
+impl FieldNames for &CStr {
+    fn field_names(&self) -> &[&CStr] {
+        std::slice::from_ref(self)
+    }
+}
+
+impl<const N: usize> FieldNames for &[&CStr; N] {
+    fn field_names(&self) -> &[&CStr] {
+        self.as_slice()
+    }
+}
 
 /// # A low-level representation of a table
 ///
@@ -75,6 +98,45 @@ impl RawTable {
         })
     }
 
+    /// # Get a table field by one of several names
+    ///
+    /// The names are tried in order and the first field that exists (with the right type)
+    /// is returned. This lets a plugin work with several versions of the host, where a field
+    /// may have been renamed. The name that was resolved is logged at debug level.
+    pub fn get_field_any<V: Value + ?Sized>(
+        &self,
+        tables_input: &TablesInput,
+        names: &[&CStr],
+    ) -> Result<RawField<V>, anyhow::Error> {
+        let mut last_err = None;
+        for name in names {
+            match self.get_field(tables_input, name) {
+                Ok(field) => {
+                    log::debug!("Resolved table field {:?} (candidates: {:?})", name, names);
+                    return Ok(field);
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        Err(last_err
+            .unwrap_or_else(|| anyhow::anyhow!("No candidate names for table field"))
+            .context(format!("Failed to get table field, tried {:?}", names)))
+    }
+
+    /// # Check whether the table has a field with one of several names
+    ///
+    /// The field types are not checked, so this can tell a missing field apart from a field
+    /// that exists with a different type (the host does not distinguish the two when looking
+    /// up a field).
+    pub fn has_field(&self, tables_input: &TablesInput, names: &[&CStr]) -> bool {
+        self.list_fields(&tables_input.fields_ext)
+            .iter()
+            // SAFETY: field names are C strings owned by the table
+            .filter_map(|info| unsafe { try_cstr_from_ptr(info.name) })
+            .any(|name| names.contains(&name))
+    }
+
     /// # Add a table field
     ///
     /// The field will have the specified name and the type is derived from the generic argument.
@@ -124,7 +186,7 @@ impl RawTable {
             unsafe { (reader_vtable.get_table_entry)(self.table, &key.to_data() as *const _) };
 
         if entry.is_null() {
-            Err(anyhow::anyhow!("table entry not found"))
+            Err(EntryNotFound.into())
         } else {
             Ok(RawEntry {
                 table: self.table,
@@ -208,7 +270,7 @@ impl RawTable {
     ///
     /// Return the number of entries in the table
     pub fn get_size(&self, reader_vtable: &TableReader) -> usize {
-        unsafe { (reader_vtable.get_table_size)(self.table) as usize }
+        convert::saturating(unsafe { (reader_vtable.get_table_size)(self.table) })
     }
 
     /// # Iterate over all entries in a table with mutable access
@@ -217,7 +279,8 @@ impl RawTable {
     /// object as a parameter.
     ///
     /// The iteration stops when either all entries have been processed or the closure returns
-    /// [`ControlFlow::Break`].
+    /// [`ControlFlow::Break`]. A panic in the closure also stops the iteration and is resumed
+    /// after the framework returns control to the plugin.
     pub fn iter_entries_mut<F>(&self, reader_vtable: &TableReader, mut func: F) -> ControlFlow<()>
     where
         F: FnMut(RawEntry) -> ControlFlow<()>,
@@ -307,11 +370,19 @@ fn iter_inner<F>(
         it: ss_plugin_table_iterator_func_t,
         s: *mut ss_plugin_table_iterator_state_t,
     ) -> ss_plugin_bool,
-    mut func: F,
+    func: F,
 ) -> ControlFlow<()>
 where
     F: FnMut(*mut ss_plugin_table_entry_t) -> bool,
 {
+    struct IterState<WF> {
+        func: WF,
+        panic: Option<Box<dyn Any + Send>>,
+    }
+
+    // The callback is invoked by the host, so a panic in the closure must not unwind
+    // through the host's stack frames. Catch it, stop the iteration and rethrow it
+    // once we're back in Rust code.
     extern "C-unwind" fn iter_wrapper<WF>(
         s: *mut ss_plugin_table_iterator_state_t,
         entry: *mut ss_plugin_table_entry_t,
@@ -319,29 +390,116 @@ where
     where
         WF: FnMut(*mut ss_plugin_table_entry_t) -> bool,
     {
-        unsafe {
-            let Some(closure) = (s as *mut WF).as_mut() else {
-                return 0;
-            };
-            let res = closure(entry);
-            if res {
-                1
-            } else {
+        let Some(state) = (unsafe { (s as *mut IterState<WF>).as_mut() }) else {
+            return 0;
+        };
+        if state.panic.is_some() {
+            // the host ignored our request to stop
+            return 0;
+        }
+
+        match std::panic::catch_unwind(AssertUnwindSafe(|| (state.func)(entry))) {
+            Ok(true) => 1,
+            Ok(false) => 0,
+            Err(panic) => {
+                state.panic = Some(panic);
                 0
             }
         }
     }
 
+    let mut state = IterState { func, panic: None };
     let finished = unsafe {
         iterate_entries(
             table,
             Some(iter_wrapper::<F>),
-            &mut func as *mut _ as *mut ss_plugin_table_iterator_state_t,
+            &mut state as *mut _ as *mut ss_plugin_table_iterator_state_t,
         ) != 0
     };
 
+    if let Some(panic) = state.panic {
+        std::panic::resume_unwind(panic);
+    }
+
     match finished {
         true => ControlFlow::Continue(()),
         false => ControlFlow::Break(()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::iter_inner;
+    use falco_plugin_api::{
+        ss_plugin_bool, ss_plugin_table_entry_t, ss_plugin_table_iterator_func_t,
+        ss_plugin_table_iterator_state_t, ss_plugin_table_t,
+    };
+    use std::ops::ControlFlow;
+
+    // a fake host table with four entries (entry pointers 1..=4),
+    // storing the number of visited entries in the table pointer
+    unsafe extern "C-unwind" fn iterate_four(
+        t: *mut ss_plugin_table_t,
+        it: ss_plugin_table_iterator_func_t,
+        s: *mut ss_plugin_table_iterator_state_t,
+    ) -> ss_plugin_bool {
+        let visited = unsafe { &mut *(t as *mut usize) };
+        let it = it.unwrap();
+        for i in 1..=4usize {
+            *visited += 1;
+            if unsafe { it(s, i as *mut ss_plugin_table_entry_t) } == 0 {
+                return 0;
+            }
+        }
+        1
+    }
+
+    fn iterate(func: impl FnMut(*mut ss_plugin_table_entry_t) -> bool) -> (ControlFlow<()>, usize) {
+        let mut visited = 0usize;
+        let res = iter_inner(
+            &mut visited as *mut usize as *mut ss_plugin_table_t,
+            iterate_four,
+            func,
+        );
+        (res, visited)
+    }
+
+    #[test]
+    fn test_iter_complete() {
+        let (res, visited) = iterate(|_| true);
+        assert_eq!(res, ControlFlow::Continue(()));
+        assert_eq!(visited, 4);
+    }
+
+    #[test]
+    fn test_iter_break() {
+        let (res, visited) = iterate(|e| e as usize != 2);
+        assert_eq!(res, ControlFlow::Break(()));
+        assert_eq!(visited, 2);
+    }
+
+    #[test]
+    fn test_iter_panic() {
+        let mut visited = 0usize;
+        let mut seen = Vec::new();
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            iter_inner(
+                &mut visited as *mut usize as *mut ss_plugin_table_t,
+                iterate_four,
+                |e| {
+                    seen.push(e as usize);
+                    if e as usize == 2 {
+                        panic!("boom");
+                    }
+                    true
+                },
+            )
+        }));
+
+        // the panic is rethrown after the host stops iterating
+        let panic = res.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"boom"));
+        assert_eq!(seen, [1, 2]);
+        assert_eq!(visited, 2);
+    }
+}
@@ -1,8 +1,11 @@
 use crate::plugin::tables::data::{Key, Value};
 use crate::plugin::tables::entry::raw::RawEntry;
 use crate::plugin::tables::table::raw::RawTable;
+use crate::plugin::tables::table::Table;
 use crate::plugin::tables::vtable::{TableReader, TableWriter, TablesInput};
 use falco_plugin_api::ss_plugin_table_t;
+use std::ffi::{CStr, CString};
+use std::ops::ControlFlow;
 use std::sync::Arc;
 
 /// Metadata for tables
@@ -76,6 +79,11 @@ pub trait TableAccess: Sized {
     where
         Self::Key: Key,
         Self::Entry: Entry;
+
+    /// iterate over all entries
+    fn iter_entries_mut<F>(&self, reader_vtable: &TableReader, func: F) -> ControlFlow<()>
+    where
+        F: FnMut(&mut Self::Entry) -> ControlFlow<()>;
 }
 
 /// A trait containing some info about a raw field and its related types
@@ -92,3 +100,45 @@ pub trait RawFieldValueType {
     where
         Self: 'a;
 }
+
+/// A trait to convert values read from a table into owned values
+///
+/// String fields are read as `&CStr`, borrowed from the entry, which makes them awkward
+/// to store. This trait turns them into [`CString`]s and is a no-op for all other types.
+pub trait IntoOwnedValue {
+    /// the owned type
+    type Owned;
+
+    /// convert the value into an owned one
+    fn into_owned_value(self) -> Self::Owned;
+}
+
+macro_rules! impl_into_owned_value_identity {
+    ($($ty:ty),*) => {
+        $(impl IntoOwnedValue for $ty {
+            type Owned = $ty;
+
+            fn into_owned_value(self) -> Self::Owned {
+                self
+            }
+        })*
+    };
+}
+
+impl_into_owned_value_identity!(u8, i8, u16, i16, u32, i32, u64, i64, bool);
+
+impl IntoOwnedValue for &CStr {
+    type Owned = CString;
+
+    fn into_owned_value(self) -> Self::Owned {
+        self.to_owned()
+    }
+}
+
+impl<K, E, M> IntoOwnedValue for Table<K, E, M> {
+    type Owned = Self;
+
+    fn into_owned_value(self) -> Self::Owned {
+        self
+    }
+}
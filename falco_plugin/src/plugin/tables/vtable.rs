@@ -7,6 +7,7 @@ use crate::plugin::exported_tables::wrappers::{fields_vtable, reader_vtable, wri
 use crate::plugin::tables::data::Key;
 use crate::plugin::tables::table::raw::RawTable;
 use crate::plugin::tables::traits::{TableAccess, TableMetadata as ImportedTableMetadata};
+use anyhow::Context;
 use falco_plugin_api::{
     ss_plugin_bool, ss_plugin_init_input, ss_plugin_owner_t, ss_plugin_rc, ss_plugin_state_data,
     ss_plugin_state_type, ss_plugin_table_entry_t, ss_plugin_table_field_t,
@@ -17,10 +18,13 @@ use falco_plugin_api::{
     ss_plugin_table_writer_vtable_ext,
 };
 use std::ffi::CStr;
+use std::fmt::{Debug, Formatter};
 use thiserror::Error;
 
+/// # An error setting up access to tables
 #[derive(Error, Debug)]
 pub enum TableError {
+    /// The plugin framework did not provide a required table operation
     #[error("Missing entry {0} in table operations vtable")]
     BadVtable(&'static str),
 }
@@ -28,7 +32,52 @@ pub enum TableError {
 /// A vtable containing table read access methods
 ///
 /// It's used as a token to prove you're allowed to read tables in a particular context
-#[derive(Debug)]
+///
+/// The SDK only ever hands out references to the token, valid for the duration
+/// of a single callback, and the token cannot be sent to other threads, so it's not possible
+/// to keep it around for later use:
+///
+/// ```compile_fail,E0521
+/// # use std::ffi::CStr;
+/// # use falco_plugin::anyhow::Error;
+/// # use falco_plugin::base::Plugin;
+/// # use falco_plugin::event::events::types::EventType;
+/// # use falco_plugin::parse::{EventInput, ParseInput, ParsePlugin};
+/// # use falco_plugin::tables::{TableReader, TablesInput};
+/// struct MyPlugin {
+///     reader: Option<&'static TableReader>,
+/// }
+/// #
+/// # impl Plugin for MyPlugin {
+/// #     const NAME: &'static CStr = c"dummy";
+/// #     const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+/// #     const DESCRIPTION: &'static CStr = c"test plugin";
+/// #     const CONTACT: &'static CStr = c"rust@localdomain.pl";
+/// #     type ConfigType = ();
+/// #
+/// #     fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+/// #         Ok(Self { reader: None })
+/// #     }
+/// # }
+///
+/// impl ParsePlugin for MyPlugin {
+/// #     const EVENT_TYPES: &'static [EventType] = &[];
+/// #     const EVENT_SOURCES: &'static [&'static str] = &[];
+///     // ...
+///     fn parse_event(&mut self, _event: &EventInput, parse_input: &ParseInput)
+///         -> Result<(), Error> {
+///         self.reader.replace(&parse_input.reader); // error: borrowed data escapes outside of method
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// ```compile_fail,E0277
+/// # use falco_plugin::tables::TableReader;
+/// fn assert_send<T: Send>() {}
+///
+/// assert_send::<&TableReader>(); // error: TableReader is not Sync
+/// ```
 pub struct TableReader {
     pub(in crate::plugin::tables) get_table_name:
         unsafe extern "C-unwind" fn(t: *mut ss_plugin_table_t) -> *const ::std::os::raw::c_char,
@@ -58,6 +107,13 @@ pub struct TableReader {
     pub(in crate::plugin::tables) last_error: LastError,
 }
 
+// the vtables are just function pointers, so there's nothing useful to show
+impl Debug for TableReader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableReader").finish_non_exhaustive()
+    }
+}
+
 impl TableReader {
     pub(crate) fn try_from(
         reader_ext: &ss_plugin_table_reader_vtable_ext,
@@ -90,7 +146,16 @@ impl TableReader {
 /// A vtable containing table write access methods
 ///
 /// It's used as a token to prove you're allowed to write tables in a particular context
-#[derive(Debug)]
+///
+/// Just like [`TableReader`], it's only available by reference for the duration of a callback
+/// and cannot be sent to other threads.
+///
+/// ```compile_fail,E0277
+/// # use falco_plugin::tables::TableWriter;
+/// fn assert_send<T: Send>() {}
+///
+/// assert_send::<&TableWriter>(); // error: TableWriter is not Sync
+/// ```
 pub struct TableWriter {
     pub(in crate::plugin::tables) clear_table:
         unsafe extern "C-unwind" fn(t: *mut ss_plugin_table_t) -> ss_plugin_rc,
@@ -120,6 +185,12 @@ pub struct TableWriter {
     pub(in crate::plugin::tables) last_error: LastError,
 }
 
+impl Debug for TableWriter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableWriter").finish_non_exhaustive()
+    }
+}
+
 impl TableWriter {
     pub(crate) fn try_from(
         writer_ext: &ss_plugin_table_writer_vtable_ext,
@@ -149,7 +220,6 @@ impl TableWriter {
     }
 }
 
-#[derive(Debug)]
 pub struct TableFields {
     pub(in crate::plugin::tables) list_table_fields:
         unsafe extern "C-unwind" fn(
@@ -170,6 +240,12 @@ pub struct TableFields {
         ) -> *mut ss_plugin_table_field_t,
 }
 
+impl Debug for TableFields {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableFields").finish_non_exhaustive()
+    }
+}
+
 impl TableFields {
     fn try_from(fields_ext: &ss_plugin_table_fields_vtable_ext) -> Result<Self, TableError> {
         Ok(TableFields {
@@ -186,7 +262,6 @@ impl TableFields {
     }
 }
 
-#[derive(Debug)]
 /// An object containing table-related vtables
 ///
 /// It's used as a token to prove you're allowed to read/write tables
@@ -220,6 +295,12 @@ pub struct TablesInput {
     pub(in crate::plugin::tables) fields_ext: TableFields,
 }
 
+impl Debug for TablesInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TablesInput").finish_non_exhaustive()
+    }
+}
+
 impl TablesInput {
     pub(crate) fn try_from(value: &ss_plugin_init_input) -> Result<Option<Self>, TableError> {
         if let Some(table_init_input) = unsafe { value.tables.as_ref() } {
@@ -290,6 +371,21 @@ impl TablesInput {
     /// The key type is verified by the plugin API, so this method will return
     /// an error on mismatch
     pub fn get_table<T, K>(&self, name: &CStr) -> Result<T, anyhow::Error>
+    where
+        T: TableAccess<Key = K>,
+        K: Key,
+    {
+        self.try_get_table(name)?
+            .ok_or_else(|| anyhow::anyhow!("Could not get table {:?}", name))
+            .with_last_error(&self.last_error)
+    }
+
+    /// # Import a table from the Falco plugin API, if it exists
+    ///
+    /// This is like [`TablesInput::get_table`], except that it returns `Ok(None)`
+    /// if the table cannot be found (e.g. because the plugin providing it is not loaded).
+    /// Errors resolving the table fields are still returned as errors.
+    pub fn try_get_table<T, K>(&self, name: &CStr) -> Result<Option<T>, anyhow::Error>
     where
         T: TableAccess<Key = K>,
         K: Key,
@@ -302,12 +398,13 @@ impl TablesInput {
             )
         };
         if table.is_null() {
-            Err(anyhow::anyhow!("Could not get table {:?}", name)).with_last_error(&self.last_error)
+            Ok(None)
         } else {
             // Safety: we pass the data directly from FFI, the framework would never lie to us, right?
             let table = RawTable { table };
-            let metadata = T::Metadata::new(&table, self)?;
-            Ok(T::new(table, metadata, false))
+            let metadata = T::Metadata::new(&table, self)
+                .with_context(|| format!("Could not import table {:?}", name))?;
+            Ok(Some(T::new(table, metadata, false)))
         }
     }
 
@@ -366,3 +463,31 @@ impl TablesInput {
         Ok(table)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{TableReader, TableWriter};
+    use crate::plugin::error::last_error::LastError;
+    use crate::plugin::exported_tables::entry::dynamic::DynamicEntry;
+    use crate::plugin::exported_tables::wrappers::{reader_vtable, writer_vtable};
+    use falco_plugin_api::ss_plugin_owner_t;
+    use std::ffi::c_char;
+
+    unsafe extern "C-unwind" fn no_error(_owner: *mut ss_plugin_owner_t) -> *const c_char {
+        std::ptr::null()
+    }
+
+    #[test]
+    fn test_debug() {
+        let last_error = unsafe { LastError::new(std::ptr::null_mut(), no_error) };
+        let reader =
+            TableReader::try_from(&reader_vtable::<u64, DynamicEntry>(), last_error.clone())
+                .unwrap();
+        let writer =
+            TableWriter::try_from(&writer_vtable::<u64, DynamicEntry>(), last_error).unwrap();
+
+        // no function pointers in the output
+        assert_eq!(format!("{:?}", reader), "TableReader { .. }");
+        assert_eq!(format!("{:?}", writer), "TableWriter { .. }");
+    }
+}
@@ -0,0 +1,136 @@
+use crate::plugin::event::EventInput;
+use falco_event::events::EventToBytes;
+use falco_plugin_api::ss_plugin_event_input;
+use std::ffi::{CStr, CString};
+
+/// # An event input backed by an owned buffer
+///
+/// The plugin framework passes events to plugins as raw [`ss_plugin_event_input`] pointers.
+/// This type serializes a typed event and keeps the buffer (and the source name) alive,
+/// so that unit tests can call plugin methods (or the raw API wrappers) without any
+/// unsafe code:
+///
+/// ```
+/// use falco_plugin::event::events::{Event, EventMetadata};
+/// use falco_plugin::source::PluginEvent;
+/// use falco_plugin::testing::OwnedEventInput;
+///
+/// let event = Event {
+///     metadata: EventMetadata::default(),
+///     params: PluginEvent {
+///         plugin_id: Some(1111),
+///         event_data: Some(b"hello"),
+///     },
+/// };
+///
+/// let input = OwnedEventInput::new(&event).unwrap().with_source(c"dummy");
+/// let event = input.event_input();
+/// assert_eq!(event.source(), Some(c"dummy"));
+///
+/// let event = event.event().unwrap();
+/// let event = event.load::<PluginEvent>().unwrap();
+/// assert_eq!(event.params.event_data, Some(b"hello".as_slice()));
+/// ```
+#[derive(Debug)]
+pub struct OwnedEventInput {
+    // `input` points into the heap allocations of `buf` and `source`,
+    // which stay put when `Self` is moved
+    input: EventInput,
+    buf: Vec<u8>,
+    source: Option<CString>,
+}
+
+impl OwnedEventInput {
+    /// Serialize an event into a new event input
+    pub fn new(event: &impl EventToBytes) -> std::io::Result<Self> {
+        let mut buf = Vec::new();
+        event.write(&mut buf)?;
+        Ok(Self::from_bytes(buf))
+    }
+
+    /// Wrap an already serialized event (including the header)
+    ///
+    /// The buffer is not validated here, but all [`EventInput`] methods parsing the event
+    /// return an error on malformed data.
+    pub fn from_bytes(buf: Vec<u8>) -> Self {
+        let input = EventInput(ss_plugin_event_input {
+            evt: buf.as_ptr() as *const _,
+            evtnum: 1,
+            evtsrc: std::ptr::null(),
+        });
+
+        Self {
+            input,
+            buf,
+            source: None,
+        }
+    }
+
+    /// Set the event source name
+    pub fn with_source(mut self, source: &CStr) -> Self {
+        let source = self.source.insert(source.to_owned());
+        self.input.0.evtsrc = source.as_ptr();
+        self
+    }
+
+    /// Set the event number (1 by default)
+    pub fn with_event_number(mut self, evtnum: u64) -> Self {
+        self.input.0.evtnum = evtnum;
+        self
+    }
+
+    /// Get the event input, as passed to plugin methods
+    pub fn event_input(&self) -> &EventInput {
+        &self.input
+    }
+
+    /// Get a pointer to the raw event input, as passed to the plugin API
+    ///
+    /// The pointer remains valid for as long as `self` is alive (and not moved).
+    pub fn as_ptr(&self) -> *const ss_plugin_event_input {
+        &self.input.0
+    }
+
+    /// Get the serialized event
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OwnedEventInput;
+    use falco_event::events::types::PPME_PLUGINEVENT_E;
+    use falco_event::events::{Event, EventMetadata};
+
+    #[test]
+    fn test_owned_event_input() {
+        let event = Event {
+            metadata: EventMetadata { ts: 1, tid: 2 },
+            params: PPME_PLUGINEVENT_E {
+                plugin_id: Some(1111),
+                event_data: Some(b"payload"),
+            },
+        };
+
+        let input = OwnedEventInput::new(&event)
+            .unwrap()
+            .with_source(c"dummy")
+            .with_event_number(5);
+
+        // moving the input must not invalidate the pointers
+        let input = Box::new(input);
+        let event_input = input.event_input();
+        assert_eq!(event_input.source(), Some(c"dummy"));
+        assert_eq!(event_input.event_number(), 5);
+        assert_eq!(event_input.as_bytes().unwrap(), input.as_bytes());
+        assert_eq!(event_input.metadata().unwrap().tid, 2);
+
+        let raw = unsafe { &*input.as_ptr() };
+        assert_eq!(raw.evtnum, 5);
+
+        let event = event_input.event().unwrap();
+        let event = event.load::<PPME_PLUGINEVENT_E>().unwrap();
+        assert_eq!(event.params.event_data, Some(b"payload".as_slice()));
+    }
+}
@@ -2,24 +2,35 @@ use crate::strings::from_ptr::FromPtrError::NullPointer;
 use std::ffi::{c_char, CStr};
 use thiserror::Error;
 
+/// # An error converting a C string pointer to a Rust string
 #[derive(Error, Debug, Eq, PartialEq)]
 pub enum FromPtrError {
+    /// The pointer was NULL
     #[error("NULL pointer")]
     NullPointer,
 
+    /// The string was not valid UTF-8 (the lossy conversion is attached)
     #[error("UTF-8 error (raw string: {0})")]
     Utf8Error(String),
 }
 
+/// Convert a possibly-NULL C string pointer to a `CStr`
+///
+/// # Safety
+/// `ptr` must be NULL or point to a NUL-terminated string valid for `'a`
+pub(crate) unsafe fn try_cstr_from_ptr<'a>(ptr: *const c_char) -> Option<&'a CStr> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(ptr) })
+    }
+}
+
 pub(crate) unsafe fn try_str_from_ptr_with_lifetime<T>(
     ptr: *const c_char,
     _lifetime_donor: &T,
 ) -> Result<&str, FromPtrError> {
-    if ptr.is_null() {
-        return Err(NullPointer);
-    }
-
-    let cstr = unsafe { CStr::from_ptr(ptr.cast()) };
+    let cstr = unsafe { try_cstr_from_ptr(ptr) }.ok_or(NullPointer)?;
     cstr.to_str()
         .map_err(|_| FromPtrError::Utf8Error(cstr.to_string_lossy().to_string()))
 }
@@ -45,6 +56,13 @@ mod tests {
         assert_eq!(try_str_from_ptr(&ptr), Err(NullPointer));
     }
 
+    #[test]
+    fn test_cstr() {
+        let str = c"testing";
+        assert_eq!(unsafe { try_cstr_from_ptr(str.as_ptr()) }, Some(str));
+        assert_eq!(unsafe { try_cstr_from_ptr(std::ptr::null()) }, None);
+    }
+
     #[test]
     fn test_invalid_utf8() {
         let str = c"invalid\xeautf-8";
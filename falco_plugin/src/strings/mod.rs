@@ -7,9 +7,14 @@
 //! takes a writer.
 //!
 //! Another is to create a [`CStringWriter`] explicitly.
+//!
+//! To store data that may contain NUL bytes (e.g. taken straight from an event) in a C string,
+//! use [`NulPolicy::sanitize`].
 
 pub(crate) mod cstring_writer;
 pub(crate) mod from_ptr;
+pub(crate) mod sanitize;
 
 pub use cstring_writer::CStringWriter;
 pub use cstring_writer::WriteIntoCString;
+pub use sanitize::NulPolicy;
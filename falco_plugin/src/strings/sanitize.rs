@@ -0,0 +1,100 @@
+use memchr::memchr;
+use std::ffi::{CString, NulError};
+use std::num::NonZeroU8;
+
+/// # What to do with NUL bytes when converting arbitrary data to a [`CString`]
+///
+/// Event data (process names, file paths, command lines etc.) is controlled by whoever
+/// generates the events and may contain NUL bytes. These cannot be stored in a C string,
+/// so [`CString::new`] fails and, unless handled, aborts the processing of the whole event.
+///
+/// Use [`NulPolicy::sanitize`] to get a [`CString`] no matter what the input is:
+/// ```
+/// use std::num::NonZeroU8;
+/// use falco_plugin::strings::NulPolicy;
+///
+/// let comm = b"evil\0name";
+/// assert_eq!(NulPolicy::Truncate.sanitize(comm)?.as_c_str(), c"evil");
+/// assert_eq!(NulPolicy::Strip.sanitize(comm)?.as_c_str(), c"evilname");
+///
+/// let replace = NulPolicy::Replace(NonZeroU8::new(b'?').unwrap());
+/// assert_eq!(replace.sanitize(comm)?.as_c_str(), c"evil?name");
+///
+/// assert!(NulPolicy::Reject.sanitize(comm).is_err());
+/// # Result::<(), std::ffi::NulError>::Ok(())
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NulPolicy {
+    /// Cut the string at the first NUL byte (this is what C code reading the data would see)
+    #[default]
+    Truncate,
+    /// Remove all NUL bytes
+    Strip,
+    /// Replace every NUL byte with the specified (non-NUL) byte
+    Replace(NonZeroU8),
+    /// Fail the conversion, like [`CString::new`]
+    Reject,
+}
+
+impl NulPolicy {
+    /// Convert `data` to a [`CString`], handling any NUL bytes according to the policy
+    ///
+    /// This only fails for [`NulPolicy::Reject`]. Owned data without NUL bytes (e.g. a `Vec<u8>`
+    /// or a `String`) is reused as-is, while borrowed data is always copied into a new buffer.
+    pub fn sanitize(&self, data: impl Into<Vec<u8>>) -> Result<CString, NulError> {
+        let mut data = data.into();
+        let Some(pos) = memchr(0, &data) else {
+            // SAFETY: we just checked there are no NULs in the data
+            return Ok(unsafe { CString::from_vec_unchecked(data) });
+        };
+
+        match self {
+            NulPolicy::Truncate => data.truncate(pos),
+            NulPolicy::Strip => data.retain(|b| *b != 0),
+            NulPolicy::Replace(replacement) => data[pos..]
+                .iter_mut()
+                .filter(|b| **b == 0)
+                .for_each(|b| *b = replacement.get()),
+            NulPolicy::Reject => return CString::new(data),
+        }
+
+        // SAFETY: all the NULs have been removed above
+        Ok(unsafe { CString::from_vec_unchecked(data) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NulPolicy;
+    use std::num::NonZeroU8;
+
+    #[test]
+    fn test_no_nuls() {
+        for policy in [
+            NulPolicy::Truncate,
+            NulPolicy::Strip,
+            NulPolicy::Replace(NonZeroU8::new(b'_').unwrap()),
+            NulPolicy::Reject,
+        ] {
+            assert_eq!(policy.sanitize("/bin/sh").unwrap().as_c_str(), c"/bin/sh");
+            assert_eq!(policy.sanitize("").unwrap().as_c_str(), c"");
+        }
+    }
+
+    #[test]
+    fn test_nuls() {
+        let data = b"\0a\0\0b\0";
+        assert_eq!(NulPolicy::Truncate.sanitize(data).unwrap().as_c_str(), c"");
+        assert_eq!(NulPolicy::Strip.sanitize(data).unwrap().as_c_str(), c"ab");
+        assert_eq!(
+            NulPolicy::Replace(NonZeroU8::new(b'_').unwrap())
+                .sanitize(data)
+                .unwrap()
+                .as_c_str(),
+            c"_a__b_"
+        );
+
+        let err = NulPolicy::Reject.sanitize(data).unwrap_err();
+        assert_eq!(err.nul_position(), 0);
+    }
+}
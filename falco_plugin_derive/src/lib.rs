@@ -2,7 +2,10 @@
 use proc_macro::TokenStream;
 use proc_macro2::Ident;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, DeriveInput, Token};
 
 fn ident_to_cstr(ident: &Ident) -> syn::LitCStr {
     let mut name = ident.to_string();
@@ -13,13 +16,44 @@ fn ident_to_cstr(ident: &Ident) -> syn::LitCStr {
     )
 }
 
-fn ident_to_bstr(ident: &Ident) -> syn::LitByteStr {
-    let mut name = ident.to_string();
-    name.push('\0');
-    syn::LitByteStr::new(name.as_bytes(), ident.span())
+/// Parse the candidate names from the `#[name(...)]` attribute of a field, if it has one
+fn candidate_names(f: &syn::Field) -> syn::Result<Option<Vec<syn::LitCStr>>> {
+    const SUGGESTION: &str = r#"expected one or more non-empty C string literals, e.g. `#[name(c"comm")]` or `#[name(c"comm", c"name")]`"#;
+
+    let Some(attr) = f.attrs.iter().find(|a| a.path().is_ident("name")) else {
+        return Ok(None);
+    };
+    let names = attr
+        .parse_args_with(Punctuated::<syn::LitCStr, Token![,]>::parse_terminated)
+        .map_err(|e| syn::Error::new(e.span(), SUGGESTION))?;
+    if names.is_empty() {
+        return Err(syn::Error::new_spanned(attr, SUGGESTION));
+    }
+    if let Some(empty) = names.iter().find(|name| name.value().as_bytes().is_empty()) {
+        return Err(syn::Error::new_spanned(empty, SUGGESTION));
+    }
+
+    Ok(Some(names.into_iter().collect()))
 }
 
-#[proc_macro_derive(Entry)]
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+#[proc_macro_derive(Entry, attributes(name, skip, accessors))]
 pub fn derive_entry(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -45,30 +79,119 @@ pub fn derive_entry(input: TokenStream) -> TokenStream {
     };
 
     let fields = fields.named;
+    let with_accessors = input.attrs.iter().any(|a| a.path().is_ident("accessors"));
 
-    let static_fields = fields.iter().enumerate().map(|(i, f)| {
+    let mut static_fields = Vec::new();
+    let mut skipped_fields = Vec::new();
+    let mut accessors = Vec::new();
+    for f in &fields {
         let field_name = f.ident.as_ref().unwrap();
-        let field_name_bstr = ident_to_bstr(field_name);
-        let tag = format!("{}.{}\0", input.ident, field_name);
+        let is_skipped = f.attrs.iter().any(|a| a.path().is_ident("skip"));
+        let name_attr = f.attrs.iter().find(|a| a.path().is_ident("name"));
+
+        if is_skipped {
+            if name_attr.is_some() {
+                return TokenStream::from(
+                    syn::Error::new_spanned(f, "`#[skip]` fields cannot be renamed")
+                        .to_compile_error(),
+                );
+            }
+            skipped_fields.push(field_name);
+            continue;
+        }
+
+        let exported_name = match name_attr {
+            Some(attr) => match attr.parse_args::<syn::LitCStr>() {
+                Ok(name) => name.value().to_string_lossy().into_owned(),
+                Err(e) => return TokenStream::from(e.to_compile_error()),
+            },
+            None => field_name.to_string(),
+        };
+
+        let mut field_name_bstr = exported_name.clone().into_bytes();
+        field_name_bstr.push(0);
+        let field_name_bstr = syn::LitByteStr::new(&field_name_bstr, field_name.span());
+
+        let tag = format!("{}.{}\0", input.ident, exported_name);
         let field_tag = syn::LitCStr::new(
             std::ffi::CStr::from_bytes_with_nul(tag.as_bytes()).unwrap(),
             field_name.span(),
         );
 
+        let i = static_fields.len();
         let ty = &f.ty;
-        quote!( [#i] #field_tag (#field_name_bstr) as #field_name: #ty)
-    });
+        static_fields.push(quote!( [#i] #field_tag (#field_name_bstr) as #field_name: #ty));
+
+        if with_accessors {
+            let name_cstr = syn::LitCStr::new(
+                std::ffi::CStr::from_bytes_with_nul(&field_name_bstr.value()).unwrap(),
+                field_name.span(),
+            );
+            let const_name = syn::Ident::new(
+                &format!("FIELD_{}", field_name.to_string().to_uppercase()),
+                field_name.span(),
+            );
+            let getter = syn::Ident::new(&format!("get_{}", field_name), field_name.span());
+            let getter_mut = syn::Ident::new(&format!("get_{}_mut", field_name), field_name.span());
+            let target = quote!(<#ty as ::std::ops::Deref>::Target);
+            let const_doc = format!("A reference to the `{}` field", field_name);
+            let getter_doc = format!("Get the value of the `{}` field", field_name);
+            let getter_mut_doc = format!(
+                "Get a mutable reference to the value of the `{}` field",
+                field_name
+            );
+
+            accessors.push(quote!(
+                #[doc = #const_doc]
+                pub const #const_name: ::falco_plugin::tables::export::EntryField<Self, #target> =
+                    ::falco_plugin::tables::export::EntryField::new(
+                        #name_cstr,
+                        |entry| &*entry.#field_name,
+                        |entry| &mut *entry.#field_name,
+                    );
+
+                #[doc = #getter_doc]
+                pub fn #getter(&self) -> &#target {
+                    &*self.#field_name
+                }
+
+                #[doc = #getter_mut_doc]
+                pub fn #getter_mut(&mut self) -> &mut #target {
+                    &mut *self.#field_name
+                }
+            ));
+        }
+    }
+
+    let accessors = if with_accessors {
+        quote!(
+            impl #name {
+                #(#accessors)*
+            }
+        )
+    } else {
+        quote!()
+    };
 
     quote!(::falco_plugin::impl_export_table!(
         for #name
         {
             #(#static_fields)*
         }
-    );)
+        skip {
+            #(#skipped_fields)*
+        }
+    );
+
+    #accessors
+    )
     .into()
 }
 
-#[proc_macro_derive(TableMetadata, attributes(entry_type, name, custom))]
+#[proc_macro_derive(
+    TableMetadata,
+    attributes(entry_type, name, custom, optional, nul_policy)
+)]
 pub fn derive_table_metadata(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let syn::Data::Struct(data) = input.data else {
@@ -94,22 +217,58 @@ pub fn derive_table_metadata(input: TokenStream) -> TokenStream {
 
     let fields = fields.named;
 
+    for f in &fields {
+        let is_custom = f.attrs.iter().any(|a| a.path().is_ident("custom"));
+        let is_optional = f.attrs.iter().any(|a| a.path().is_ident("optional"));
+
+        if is_optional && is_custom {
+            return TokenStream::from(
+                syn::Error::new_spanned(f, "`#[custom]` fields cannot be `#[optional]`")
+                    .to_compile_error(),
+            );
+        }
+
+        let num_names = match candidate_names(f) {
+            Ok(names) => names.map_or(1, |names| names.len()),
+            Err(e) => return TokenStream::from(e.to_compile_error()),
+        };
+
+        if is_custom && num_names > 1 {
+            return TokenStream::from(
+                syn::Error::new_spanned(f, "`#[custom]` fields must have a single name")
+                    .to_compile_error(),
+            );
+        }
+
+        if is_optional && option_inner_type(&f.ty).is_none() {
+            return TokenStream::from(
+                syn::Error::new_spanned(&f.ty, "`#[optional]` fields must be of type `Option<_>`")
+                    .to_compile_error(),
+            );
+        }
+    }
+
     let metadata_macro_args = fields.iter().filter_map(|f| {
         let field = f.ident.as_ref()?;
-        let field_name = f
-            .attrs
-            .iter()
-            .filter(|a| a.path().is_ident("name"))
-            .filter_map(|a| a.parse_args::<syn::LitCStr>().ok())
-            .next()
-            .unwrap_or_else(|| ident_to_cstr(field));
+        // the names have already been validated above
+        let field_names = candidate_names(f)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| vec![ident_to_cstr(field)]);
 
         let is_custom = f.attrs.iter().any(|f| f.path().is_ident("custom"));
+        let is_optional = f.attrs.iter().any(|f| f.path().is_ident("optional"));
 
-        if is_custom {
-            Some(quote!(add_field(#field, #field_name)))
+        let (access_fn, field_name) = match field_names.as_slice() {
+            [name] if is_custom => (quote!(add_field), quote!(#name)),
+            [name] => (quote!(get_field), quote!(#name)),
+            names => (quote!(get_field_any), quote!(&[#(#names),*])),
+        };
+
+        if is_optional {
+            Some(quote!(#access_fn(#field, #field_name) optional))
         } else {
-            Some(quote!(get_field(#field, #field_name)))
+            Some(quote!(#access_fn(#field, #field_name)))
         }
     });
 
@@ -138,22 +297,56 @@ pub fn derive_table_metadata(input: TokenStream) -> TokenStream {
             let ty = &f.ty;
 
             let getter_name = Ident::new(&format!("get_{}", field_name), field_name.span());
+            let owned_getter_name =
+                Ident::new(&format!("get_{}_owned", field_name), field_name.span());
             let table_getter_name =
                 Ident::new(&format!("get_{}_by_key", field_name), field_name.span());
+            let iter_name = Ident::new(&format!("iter_{}", field_name), field_name.span());
             let setter_name = Ident::new(&format!("set_{}", field_name), field_name.span());
 
+            // `#[nul_policy]` or `#[nul_policy(policy)]`: the setter takes arbitrary bytes
+            // and handles NULs according to the policy
+            let sanitize = match f.attrs.iter().find(|a| a.path().is_ident("nul_policy")) {
+                None => None,
+                Some(attr) if matches!(attr.meta, syn::Meta::Path(_)) => {
+                    Some(quote!(::falco_plugin::strings::NulPolicy::Truncate))
+                }
+                Some(attr) => match attr.parse_args::<syn::Expr>() {
+                    Ok(policy) => Some(quote!(#policy)),
+                    Err(e) => return TokenStream::from(e.to_compile_error()),
+                },
+            };
+            let (sanitize_marker, sanitize_policy) = match sanitize {
+                Some(policy) => (quote!(, sanitize), quote!(; sanitize(#policy))),
+                None => (quote!(), quote!()),
+            };
+
             field_traits.push(quote!(
                 ::falco_plugin::impl_import_table_accessor_traits!(
-                    #field_name: #getter_name, #table_getter_name, #setter_name
-                );
-            ));
-            field_trait_impls.push(quote!(
-                ::falco_plugin::impl_import_table_accessor_impls!(
-                    use #private_ns::#field_name;
-                    #field_name(#ty) for #entry_type; meta #name =>
-                        #getter_name, #table_getter_name, #setter_name
+                    #field_name: #getter_name, #owned_getter_name, #table_getter_name, #iter_name, #setter_name
+                    #sanitize_marker
                 );
             ));
+            let is_optional = f.attrs.iter().any(|a| a.path().is_ident("optional"));
+            if let (true, Some(ty)) = (is_optional, option_inner_type(ty)) {
+                field_trait_impls.push(quote!(
+                    ::falco_plugin::impl_import_table_optional_accessor_impls!(
+                        use #private_ns::#field_name;
+                        #field_name(#ty) for #entry_type; meta #name =>
+                            #getter_name, #owned_getter_name, #table_getter_name, #iter_name, #setter_name
+                            #sanitize_policy
+                    );
+                ));
+            } else {
+                field_trait_impls.push(quote!(
+                    ::falco_plugin::impl_import_table_accessor_impls!(
+                        use #private_ns::#field_name;
+                        #field_name(#ty) for #entry_type; meta #name =>
+                            #getter_name, #owned_getter_name, #table_getter_name, #iter_name, #setter_name
+                            #sanitize_policy
+                    );
+                ));
+            }
         }
     }
 
@@ -170,3 +363,331 @@ pub fn derive_table_metadata(input: TokenStream) -> TokenStream {
     )
     .into()
 }
+
+struct FieldSpec {
+    description: Vec<syn::LitStr>,
+    display: Option<syn::LitStr>,
+    arg: Option<Ident>,
+    name: syn::LitStr,
+    method: Ident,
+    ty: syn::Type,
+}
+
+impl Parse for FieldSpec {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut description = Vec::new();
+        let mut display = None;
+        let mut arg = None;
+
+        for attr in input.call(syn::Attribute::parse_outer)? {
+            if attr.path().is_ident("doc") {
+                let syn::Meta::NameValue(nv) = &attr.meta else {
+                    continue;
+                };
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) = &nv.value
+                {
+                    description.push(s.clone());
+                }
+            } else if attr.path().is_ident("display") {
+                display = Some(attr.parse_args()?);
+            } else if attr.path().is_ident("arg") {
+                arg = Some(attr.parse_args()?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "unsupported attribute (expected `display` or `arg`)",
+                ));
+            }
+        }
+
+        let name = input.parse()?;
+        input.parse::<syn::Token![=>]>()?;
+        input.parse::<syn::Token![fn]>()?;
+        let method = input.parse()?;
+        let args;
+        syn::parenthesized!(args in input);
+        if !args.is_empty() {
+            return Err(args.error(
+                "field methods take no parameters here: the request and the argument \
+                 are passed implicitly (use `#[arg(...)]` to declare the argument type)",
+            ));
+        }
+        input.parse::<syn::Token![->]>()?;
+        let ty = input.parse()?;
+        input.parse::<syn::Token![;]>()?;
+
+        Ok(Self {
+            description,
+            display,
+            arg,
+            name,
+            method,
+            ty,
+        })
+    }
+}
+
+struct FieldSpecs {
+    trait_name: Ident,
+    plugin: syn::Type,
+    fields: Vec<FieldSpec>,
+}
+
+impl Parse for FieldSpecs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<syn::Token![trait]>()?;
+        let trait_name = input.parse()?;
+        input.parse::<syn::Token![for]>()?;
+        let plugin = input.parse()?;
+
+        let content;
+        syn::braced!(content in input);
+        let mut fields = Vec::new();
+        while !content.is_empty() {
+            fields.push(content.parse()?);
+        }
+
+        Ok(Self {
+            trait_name,
+            plugin,
+            fields,
+        })
+    }
+}
+
+#[proc_macro]
+pub fn extract_field_spec(input: TokenStream) -> TokenStream {
+    let spec = parse_macro_input!(input as FieldSpecs);
+    let trait_name = &spec.trait_name;
+    let plugin = &spec.plugin;
+
+    let methods = spec.fields.iter().map(|f| {
+        let method = &f.method;
+        let ty = &f.ty;
+        let docs = &f.description;
+        quote!(
+            #(#[doc = #docs])*
+            fn #method(
+                &mut self,
+                req: ::falco_plugin::extract::ExtractRequest<Self>,
+                arg: ::falco_plugin::extract::ExtractFieldRequestArg,
+            ) -> ::falco_plugin::anyhow::Result<#ty>;
+        )
+    });
+
+    let field_infos = spec.fields.iter().map(|f| {
+        let name = &f.name;
+        let method = &f.method;
+        let mut info =
+            quote!(::falco_plugin::extract::field(#name, &<Self as #trait_name>::#method));
+
+        if !f.description.is_empty() {
+            let description = f
+                .description
+                .iter()
+                .map(|line| line.value().trim().to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let description = description.trim();
+            info = quote!(#info.with_description(#description));
+        }
+
+        if let Some(display) = &f.display {
+            info = quote!(#info.with_display(#display));
+        }
+
+        if let Some(arg) = &f.arg {
+            info = quote!(#info.with_arg(::falco_plugin::extract::ExtractArgType::#arg));
+        }
+
+        info
+    });
+
+    quote!(
+        #[allow(missing_docs)]
+        pub trait #trait_name: ::falco_plugin::extract::ExtractPlugin {
+            #(#methods)*
+        }
+
+        impl #plugin {
+            /// The extractable fields, as declared in the field spec
+            ///
+            /// Use this as the value of `ExtractPlugin::EXTRACT_FIELDS`.
+            pub const FIELD_SPEC: &'static [::falco_plugin::extract::ExtractFieldInfo<Self>] = &[
+                #(#field_infos,)*
+            ];
+        }
+    )
+    .into()
+}
+
+fn payload_variant_tag(variant: &syn::Variant, index: usize) -> syn::Result<u8> {
+    for attr in &variant.attrs {
+        if attr.path().is_ident("tag") {
+            let value = &attr.meta.require_name_value()?.value;
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(tag),
+                ..
+            }) = value
+            else {
+                return Err(syn::Error::new_spanned(value, "Expected an integer tag"));
+            };
+            return tag.base10_parse();
+        }
+    }
+
+    u8::try_from(index).map_err(|_| {
+        syn::Error::new_spanned(
+            &variant.ident,
+            "Too many variants, use #[tag = N] to assign tags explicitly",
+        )
+    })
+}
+
+#[proc_macro_derive(PluginPayloadEnum, attributes(tag))]
+pub fn derive_plugin_payload_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let syn::Data::Enum(data) = &input.data else {
+        return TokenStream::from(
+            syn::Error::new(
+                input.ident.span(),
+                "Only enums can derive `PluginPayloadEnum`",
+            )
+            .to_compile_error(),
+        );
+    };
+
+    let name = &input.ident;
+    let mut lifetimes = input.generics.lifetimes();
+    let lifetime = match (lifetimes.next(), lifetimes.next()) {
+        (None, None) => None,
+        (Some(lt), None) => Some(lt.lifetime.clone()),
+        _ => {
+            return TokenStream::from(
+                syn::Error::new(
+                    input.generics.span(),
+                    "`PluginPayloadEnum` supports at most one lifetime parameter",
+                )
+                .to_compile_error(),
+            )
+        }
+    };
+    if input.generics.type_params().next().is_some()
+        || input.generics.const_params().next().is_some()
+    {
+        return TokenStream::from(
+            syn::Error::new(
+                input.generics.span(),
+                "`PluginPayloadEnum` does not support generic type parameters",
+            )
+            .to_compile_error(),
+        );
+    }
+
+    let (impl_lifetime, ty) = match &lifetime {
+        Some(lt) => (lt.clone(), quote!(#name<#lt>)),
+        None => (
+            syn::Lifetime::new("'__payload", proc_macro2::Span::call_site()),
+            quote!(#name),
+        ),
+    };
+
+    let mut tags = std::collections::BTreeSet::new();
+    let mut encode_arms = Vec::new();
+    let mut decode_arms = Vec::new();
+
+    for (index, variant) in data.variants.iter().enumerate() {
+        let tag = match payload_variant_tag(variant, index) {
+            Ok(tag) => tag,
+            Err(e) => return TokenStream::from(e.to_compile_error()),
+        };
+        if !tags.insert(tag) {
+            return TokenStream::from(
+                syn::Error::new_spanned(&variant.ident, format!("Duplicate tag {}", tag))
+                    .to_compile_error(),
+            );
+        }
+
+        let variant_name = &variant.ident;
+        match &variant.fields {
+            syn::Fields::Unit => {
+                encode_arms.push(quote!(
+                    Self::#variant_name => writer.write_all(&[#tag]),
+                ));
+                decode_arms.push(quote!(
+                    #tag => {
+                        <() as ::falco_plugin::source::PluginPayload>::decode(rest)?;
+                        Ok(Self::#variant_name)
+                    }
+                ));
+            }
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                encode_arms.push(quote!(
+                    Self::#variant_name(inner) => {
+                        writer.write_all(&[#tag])?;
+                        ::falco_plugin::source::PluginPayload::encode(inner, writer)
+                    }
+                ));
+                decode_arms.push(quote!(
+                    #tag => Ok(Self::#variant_name(
+                        ::falco_plugin::source::PluginPayload::decode(rest)?
+                    )),
+                ));
+            }
+            _ => {
+                return TokenStream::from(
+                    syn::Error::new_spanned(
+                        variant,
+                        "Only unit variants and variants with a single unnamed field are supported",
+                    )
+                    .to_compile_error(),
+                )
+            }
+        }
+    }
+
+    quote!(
+        impl<#impl_lifetime> ::falco_plugin::source::PluginPayload<#impl_lifetime> for #ty {
+            fn encode<W: ::std::io::Write>(&self, mut writer: W) -> ::std::io::Result<()> {
+                match self {
+                    #(#encode_arms)*
+                }
+            }
+
+            fn decode(
+                buf: &#impl_lifetime [u8],
+            ) -> Result<Self, ::falco_plugin::source::PayloadDecodeError> {
+                let Some((tag, rest)) = buf.split_first() else {
+                    return Err(::falco_plugin::source::PayloadDecodeError::Empty);
+                };
+                match *tag {
+                    #(#decode_arms)*
+                    tag => Err(::falco_plugin::source::PayloadDecodeError::UnknownTag(tag)),
+                }
+            }
+        }
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldSpec;
+
+    #[test]
+    fn test_field_spec_parens() {
+        let spec: FieldSpec = syn::parse_str(r#""spec.ten" => fn extract_ten() -> u64;"#).unwrap();
+        assert_eq!(spec.name.value(), "spec.ten");
+        assert_eq!(spec.method, "extract_ten");
+
+        let err = syn::parse_str::<FieldSpec>(r#""spec.echo" => fn extract_echo(x) -> u64;"#)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("take no parameters"));
+
+        assert!(syn::parse_str::<FieldSpec>(r#""spec.echo" => fn extract_echo -> u64;"#).is_err());
+    }
+}
@@ -46,10 +46,10 @@ void SinspTestDriver::load_capture_file(const char* path)
     m_sinsp.start_capture();
 }
 
-void SinspTestDriver::start_capture(const char* name, const char* config)
+void SinspTestDriver::start_capture(const char* name, const char* open_params)
 {
     std::scoped_lock m(s_sinsp_lock);
-    m_sinsp.open_plugin(name, config, sinsp_plugin_platform::SINSP_PLATFORM_GENERIC);
+    m_sinsp.open_plugin(name, open_params, sinsp_plugin_platform::SINSP_PLATFORM_GENERIC);
     m_sinsp.start_capture();
 }
 
@@ -19,7 +19,7 @@ public:
     std::shared_ptr<sinsp_plugin> register_plugin(const Api* api, const char* config);
     void add_filterchecks(const std::shared_ptr<sinsp_plugin>& plugin, const char* source);
     void load_capture_file(const char* path);
-    void start_capture(const char* name, const char* config);
+    void start_capture(const char* name, const char* open_params);
     SinspEvent next();
     std::unique_ptr<std::string> event_field_as_string(const char* field_name, const SinspEvent& event);
     std::unique_ptr<std::vector<SinspMetric>> get_metrics();
@@ -0,0 +1,245 @@
+use crate::Api;
+use falco_plugin::api::{
+    plugin_api, ss_instance_t, ss_plugin_async_event_handler_t, ss_plugin_capture_listen_input,
+    ss_plugin_event, ss_plugin_event_input, ss_plugin_event_parse_input,
+    ss_plugin_field_extract_input, ss_plugin_init_input, ss_plugin_metric, ss_plugin_owner_t,
+    ss_plugin_rc, ss_plugin_schema_type, ss_plugin_set_config_input, ss_plugin_t,
+};
+use std::ffi::{c_char, CStr};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The maximum number of plugins traced at the same time (across all drivers)
+pub const MAX_TRACED_PLUGINS: usize = 8;
+
+/// A single call from the framework into a plugin
+#[derive(Debug, Clone)]
+pub struct ApiCall {
+    /// The name of the plugin
+    pub plugin: String,
+    /// The name of the called function, as in the `plugin_api` struct
+    pub name: &'static str,
+    /// A summary of the arguments (raw pointers and values)
+    pub args: String,
+    /// The status code, for functions that return one (directly or via an `rc` argument)
+    pub rc: Option<ss_plugin_rc>,
+    /// The time spent in the plugin
+    pub latency: Duration,
+}
+
+/// The calls recorded for all plugins registered with a driver, in order
+pub type ApiTrace = Arc<Mutex<Vec<ApiCall>>>;
+
+struct Slot {
+    api: plugin_api,
+    plugin: String,
+    trace: ApiTrace,
+}
+
+static SLOTS: [Mutex<Option<Slot>>; MAX_TRACED_PLUGINS] =
+    [const { Mutex::new(None) }; MAX_TRACED_PLUGINS];
+
+fn original<const SLOT: usize>() -> plugin_api {
+    SLOTS[SLOT]
+        .lock()
+        .unwrap()
+        .as_ref()
+        .expect("traced plugin already released")
+        .api
+}
+
+fn record<const SLOT: usize>(
+    name: &'static str,
+    args: String,
+    rc: Option<ss_plugin_rc>,
+    latency: Duration,
+) {
+    let (plugin, trace) = match SLOTS[SLOT].lock().unwrap().as_ref() {
+        Some(slot) => (slot.plugin.clone(), Arc::clone(&slot.trace)),
+        None => return,
+    };
+
+    trace.lock().unwrap().push(ApiCall {
+        plugin,
+        name,
+        args,
+        rc,
+        latency,
+    });
+}
+
+trait ReturnCode {
+    fn rc(&self) -> Option<ss_plugin_rc>;
+}
+
+impl ReturnCode for ss_plugin_rc {
+    fn rc(&self) -> Option<ss_plugin_rc> {
+        Some(*self)
+    }
+}
+
+macro_rules! no_return_code {
+    ($($ty:ty),*) => {
+        $(impl ReturnCode for $ty {
+            fn rc(&self) -> Option<ss_plugin_rc> {
+                None
+            }
+        })*
+    };
+}
+
+no_return_code!((), u32);
+
+impl<T> ReturnCode for *const T {
+    fn rc(&self) -> Option<ss_plugin_rc> {
+        None
+    }
+}
+
+impl<T> ReturnCode for *mut T {
+    fn rc(&self) -> Option<ss_plugin_rc> {
+        None
+    }
+}
+
+fn summarize(args: &[(&str, &dyn Debug)]) -> String {
+    args.iter()
+        .map(|(name, value)| format!("{}={:?}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+macro_rules! trampolines {
+    ($($($field:ident).+ ($($arg:ident: $ty:ty),*) -> $ret:ty $(, rc = $rc:ident)?;)*) => {
+        fn wrap_api<const SLOT: usize>(api: &mut plugin_api) {
+            $(
+                if api.$($field).+.is_some() {
+                    unsafe extern "C-unwind" fn trampoline<const SLOT: usize>($($arg: $ty),*) -> $ret {
+                        let func = original::<SLOT>().$($field).+.unwrap();
+                        let args = summarize(&[$((stringify!($arg), &$arg as &dyn Debug)),*]);
+
+                        let start = Instant::now();
+                        let ret = unsafe { func($($arg),*) };
+                        let latency = start.elapsed();
+
+                        #[allow(unused_mut)]
+                        let mut status = ret.rc();
+                        $(if !$rc.is_null() {
+                            status = Some(unsafe { *$rc });
+                        })?
+
+                        // strip the `__bindgen_anon_N.` prefix for capability-specific functions
+                        let name = stringify!($($field).+).rsplit('.').next().unwrap().trim();
+                        record::<SLOT>(name, args, status, latency);
+                        ret
+                    }
+
+                    api.$($field).+ = Some(trampoline::<SLOT>);
+                }
+            )*
+        }
+    };
+}
+
+trampolines! {
+    get_required_api_version() -> *const c_char;
+    get_init_schema(schema_type: *mut ss_plugin_schema_type) -> *const c_char;
+    init(input: *const ss_plugin_init_input, rc: *mut ss_plugin_rc) -> *mut ss_plugin_t, rc = rc;
+    destroy(s: *mut ss_plugin_t) -> ();
+    get_last_error(s: *mut ss_plugin_t) -> *const c_char;
+    get_name() -> *const c_char;
+    get_description() -> *const c_char;
+    get_contact() -> *const c_char;
+    get_version() -> *const c_char;
+    set_config(s: *mut ss_plugin_t, i: *const ss_plugin_set_config_input) -> ss_plugin_rc;
+    get_metrics(s: *mut ss_plugin_t, num_metrics: *mut u32) -> *mut ss_plugin_metric;
+
+    __bindgen_anon_1.get_id() -> u32;
+    __bindgen_anon_1.get_event_source() -> *const c_char;
+    __bindgen_anon_1.open(s: *mut ss_plugin_t, params: *const c_char, rc: *mut ss_plugin_rc) -> *mut ss_instance_t, rc = rc;
+    __bindgen_anon_1.close(s: *mut ss_plugin_t, h: *mut ss_instance_t) -> ();
+    __bindgen_anon_1.list_open_params(s: *mut ss_plugin_t, rc: *mut ss_plugin_rc) -> *const c_char, rc = rc;
+    __bindgen_anon_1.get_progress(s: *mut ss_plugin_t, h: *mut ss_instance_t, progress_pct: *mut u32) -> *const c_char;
+    __bindgen_anon_1.event_to_string(s: *mut ss_plugin_t, evt: *const ss_plugin_event_input) -> *const c_char;
+    __bindgen_anon_1.next_batch(s: *mut ss_plugin_t, h: *mut ss_instance_t, nevts: *mut u32, evts: *mut *mut *mut ss_plugin_event) -> ss_plugin_rc;
+
+    __bindgen_anon_2.get_extract_event_types(numtypes: *mut u32, s: *mut ss_plugin_t) -> *mut u16;
+    __bindgen_anon_2.get_extract_event_sources() -> *const c_char;
+    __bindgen_anon_2.get_fields() -> *const c_char;
+    __bindgen_anon_2.extract_fields(s: *mut ss_plugin_t, evt: *const ss_plugin_event_input, in_: *const ss_plugin_field_extract_input) -> ss_plugin_rc;
+
+    __bindgen_anon_3.get_parse_event_types(numtypes: *mut u32, s: *mut ss_plugin_t) -> *mut u16;
+    __bindgen_anon_3.get_parse_event_sources() -> *const c_char;
+    __bindgen_anon_3.parse_event(s: *mut ss_plugin_t, evt: *const ss_plugin_event_input, in_: *const ss_plugin_event_parse_input) -> ss_plugin_rc;
+
+    __bindgen_anon_4.get_async_event_sources() -> *const c_char;
+    __bindgen_anon_4.get_async_events() -> *const c_char;
+    __bindgen_anon_4.set_async_event_handler(s: *mut ss_plugin_t, owner: *mut ss_plugin_owner_t, handler: ss_plugin_async_event_handler_t) -> ss_plugin_rc;
+
+    __bindgen_anon_5.capture_open(s: *mut ss_plugin_t, i: *const ss_plugin_capture_listen_input) -> ss_plugin_rc;
+    __bindgen_anon_5.capture_close(s: *mut ss_plugin_t, i: *const ss_plugin_capture_listen_input) -> ss_plugin_rc;
+}
+
+/// # A plugin API wrapped to record every call into an [`ApiTrace`]
+///
+/// The wrapped API must outlive every use by the framework: the driver keeps it around
+/// until the sinsp instance is destroyed.
+pub struct TracedApi {
+    api: Box<Api>,
+    slot: usize,
+}
+
+impl TracedApi {
+    pub fn new(api: &Api, trace: &ApiTrace) -> anyhow::Result<Self> {
+        let plugin = match api.0.get_name {
+            Some(get_name) => unsafe { CStr::from_ptr(get_name()) }
+                .to_string_lossy()
+                .into_owned(),
+            None => String::new(),
+        };
+
+        let mut wrapped = api.0;
+        for (slot, entry) in SLOTS.iter().enumerate() {
+            let mut entry = entry.lock().unwrap();
+            if entry.is_some() {
+                continue;
+            }
+
+            match slot {
+                0 => wrap_api::<0>(&mut wrapped),
+                1 => wrap_api::<1>(&mut wrapped),
+                2 => wrap_api::<2>(&mut wrapped),
+                3 => wrap_api::<3>(&mut wrapped),
+                4 => wrap_api::<4>(&mut wrapped),
+                5 => wrap_api::<5>(&mut wrapped),
+                6 => wrap_api::<6>(&mut wrapped),
+                7 => wrap_api::<7>(&mut wrapped),
+                _ => unreachable!(),
+            }
+
+            *entry = Some(Slot {
+                api: api.0,
+                plugin,
+                trace: Arc::clone(trace),
+            });
+
+            return Ok(Self {
+                api: Box::new(Api(wrapped)),
+                slot,
+            });
+        }
+
+        anyhow::bail!("too many traced plugins (max {})", MAX_TRACED_PLUGINS)
+    }
+
+    pub fn api(&self) -> &Api {
+        &self.api
+    }
+}
+
+impl Drop for TracedApi {
+    fn drop(&mut self) {
+        *SLOTS[self.slot].lock().unwrap() = None;
+    }
+}
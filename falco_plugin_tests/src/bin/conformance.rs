@@ -0,0 +1,19 @@
+//! Run the host API conformance checks against libsinsp
+
+use falco_plugin_tests::conformance::{conformance_api, take_report};
+use falco_plugin_tests::{new_test_driver, Api};
+use std::process::ExitCode;
+
+fn main() -> anyhow::Result<ExitCode> {
+    let mut driver = new_test_driver()?;
+    driver.register_plugin(&Api(conformance_api()), c"")?;
+    drop(driver);
+
+    let report = take_report();
+    println!("{}", report);
+
+    Ok(match report.passed() {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    })
+}
@@ -0,0 +1,385 @@
+//! # Host API conformance checks
+//!
+//! Plugins built with [`falco_plugin`] make some assumptions about the host (Falco,
+//! libsinsp or any other program loading plugins): which pointers are never NULL, which
+//! vtable entries are always filled in, in what order the plugin API is called etc.
+//! This module packages these assumptions as a set of checks, run by a small plugin
+//! against whatever host loads it.
+//!
+//! To check a host, register the plugin API returned by [`conformance_api`], let the host
+//! initialize and destroy the plugin, then call [`take_report`]:
+//!
+//! ```ignore
+//! let mut driver = new_test_driver()?;
+//! driver.register_plugin(&Api(conformance_api()), c"")?;
+//! drop(driver);
+//!
+//! let report = take_report();
+//! assert!(report.passed(), "{}", report);
+//! ```
+//!
+//! The `conformance` binary in this crate does exactly that against libsinsp.
+
+use falco_plugin::anyhow::Error;
+use falco_plugin::api::{
+    plugin_api, ss_plugin_init_input, ss_plugin_init_tables_input,
+    ss_plugin_log_severity_SS_PLUGIN_LOG_SEV_INFO, ss_plugin_rc,
+    ss_plugin_state_type_SS_PLUGIN_ST_UINT64, ss_plugin_t,
+};
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::parse::{EventInput, ParseInput, ParsePlugin};
+use falco_plugin::static_plugin;
+use falco_plugin::tables::TablesInput;
+use std::ffi::{c_char, CStr};
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+
+/// # The outcome of a single check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// The host behaved as expected
+    Passed,
+    /// The host violated an assumption the SDK relies on
+    Failed(String),
+    /// The host behaved in an unusual (but allowed) way, e.g. left out a deprecated vtable
+    Warning(String),
+}
+
+/// # The result of a single check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    /// The name of the check, e.g. `init.owner`
+    pub name: &'static str,
+    /// The outcome
+    pub status: CheckStatus,
+}
+
+/// # The results of all the checks run against a host
+#[derive(Debug, Clone, Default)]
+pub struct ConformanceReport {
+    /// The checks, in the order they were run
+    pub checks: Vec<CheckResult>,
+}
+
+impl ConformanceReport {
+    /// Check whether the host passed all checks (warnings are allowed)
+    pub fn passed(&self) -> bool {
+        !self.checks.is_empty()
+            && self
+                .checks
+                .iter()
+                .all(|check| !matches!(check.status, CheckStatus::Failed(_)))
+    }
+
+    /// Iterate over the failed checks
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks
+            .iter()
+            .filter(|check| matches!(check.status, CheckStatus::Failed(_)))
+    }
+
+    fn pass(&mut self, name: &'static str) {
+        self.push(name, CheckStatus::Passed)
+    }
+
+    fn fail(&mut self, name: &'static str, msg: impl Into<String>) {
+        self.push(name, CheckStatus::Failed(msg.into()))
+    }
+
+    fn warn(&mut self, name: &'static str, msg: impl Into<String>) {
+        self.push(name, CheckStatus::Warning(msg.into()))
+    }
+
+    fn check(&mut self, name: &'static str, ok: bool, msg: &str) -> bool {
+        match ok {
+            true => self.pass(name),
+            false => self.fail(name, msg),
+        }
+        ok
+    }
+
+    fn push(&mut self, name: &'static str, status: CheckStatus) {
+        self.checks.push(CheckResult { name, status });
+    }
+}
+
+impl Display for ConformanceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for check in &self.checks {
+            match &check.status {
+                CheckStatus::Passed => writeln!(f, "PASS {}", check.name)?,
+                CheckStatus::Failed(msg) => writeln!(f, "FAIL {}: {}", check.name, msg)?,
+                CheckStatus::Warning(msg) => writeln!(f, "WARN {}: {}", check.name, msg)?,
+            }
+        }
+
+        let failed = self.failures().count();
+        write!(f, "{} checks, {} failed", self.checks.len(), failed)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    report: ConformanceReport,
+    api_version_requested: bool,
+    plugin: Option<usize>,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+fn with_state<R>(func: impl FnOnce(&mut State) -> R) -> R {
+    let mut state = STATE.lock().unwrap();
+    func(state.get_or_insert_with(Default::default))
+}
+
+/// # Get the results of the checks run so far
+///
+/// This resets the state, so that the conformance plugin can be loaded again
+/// (possibly by another host).
+pub fn take_report() -> ConformanceReport {
+    STATE
+        .lock()
+        .unwrap()
+        .take()
+        .map(|state| state.report)
+        .unwrap_or_default()
+}
+
+/// # The plugin API of the conformance plugin
+///
+/// The plugin itself is a no-op parse plugin. The checks run when the host calls
+/// into the plugin API (most of them in `init`).
+pub fn conformance_api() -> plugin_api {
+    let mut api = CONFORMANCE_INNER_API;
+    api.get_required_api_version = Some(conformance_get_required_api_version);
+    api.init = Some(conformance_init);
+    api.destroy = Some(conformance_destroy);
+    api
+}
+
+unsafe extern "C-unwind" fn conformance_get_required_api_version() -> *const c_char {
+    with_state(|state| state.api_version_requested = true);
+    let inner = CONFORMANCE_INNER_API.get_required_api_version.unwrap();
+    unsafe { inner() }
+}
+
+unsafe extern "C-unwind" fn conformance_init(
+    input: *const ss_plugin_init_input,
+    rc: *mut ss_plugin_rc,
+) -> *mut ss_plugin_t {
+    with_state(|state| {
+        let report = &mut state.report;
+        report.check(
+            "lifecycle.api_version_before_init",
+            state.api_version_requested,
+            "init called before get_required_api_version",
+        );
+        report.check("init.rc", !rc.is_null(), "NULL rc pointer");
+        if let Some(input) = unsafe { input.as_ref() } {
+            report.pass("init.input");
+            unsafe { check_init_input(report, input) };
+        } else {
+            report.fail("init.input", "NULL init input");
+        }
+    });
+
+    let inner = CONFORMANCE_INNER_API.init.unwrap();
+    let plugin = unsafe { inner(input, rc) };
+
+    with_state(|state| state.plugin = Some(plugin as usize));
+    plugin
+}
+
+unsafe extern "C-unwind" fn conformance_destroy(plugin: *mut ss_plugin_t) {
+    with_state(|state| {
+        let ok = state.plugin.take() == Some(plugin as usize);
+        state.report.check(
+            "lifecycle.destroy",
+            ok,
+            "destroy called with a pointer not returned from init (or called twice)",
+        );
+    });
+
+    let inner = CONFORMANCE_INNER_API.destroy.unwrap();
+    unsafe { inner(plugin) }
+}
+
+unsafe fn check_init_input(report: &mut ConformanceReport, input: &ss_plugin_init_input) {
+    report.check(
+        "init.config",
+        !input.config.is_null(),
+        "NULL config (pass an empty string instead)",
+    );
+
+    if !report.check("init.owner", !input.owner.is_null(), "NULL owner") {
+        return;
+    }
+
+    let last_error_present = report.check(
+        "init.get_owner_last_error",
+        input.get_owner_last_error.is_some(),
+        "missing get_owner_last_error",
+    );
+
+    match input.log_fn {
+        Some(log_fn) => {
+            unsafe {
+                log_fn(
+                    input.owner,
+                    c"conformance".as_ptr(),
+                    c"running host conformance checks".as_ptr(),
+                    ss_plugin_log_severity_SS_PLUGIN_LOG_SEV_INFO,
+                )
+            };
+            report.pass("init.log_fn");
+        }
+        None => report.fail("init.log_fn", "missing log_fn"),
+    }
+
+    match unsafe { input.tables.as_ref() } {
+        Some(tables) => {
+            report.pass("init.tables");
+            unsafe { check_tables(report, input, tables, last_error_present) };
+        }
+        None => report.fail("init.tables", "NULL tables input"),
+    }
+}
+
+macro_rules! check_vtable {
+    ($report:expr, $name:literal, $vtable:expr => $($field:ident),*) => {
+        match unsafe { $vtable.as_ref() } {
+            Some(vtable) => {
+                let missing: Vec<&str> = [$((stringify!($field), vtable.$field.is_some())),*]
+                    .into_iter()
+                    .filter(|(_, present)| !present)
+                    .map(|(name, _)| name)
+                    .collect();
+                $report.check(
+                    $name,
+                    missing.is_empty(),
+                    &format!("missing {}", missing.join(", ")),
+                );
+            }
+            None => $report.fail($name, "NULL vtable"),
+        }
+    };
+}
+
+unsafe fn check_tables(
+    report: &mut ConformanceReport,
+    input: &ss_plugin_init_input,
+    tables: &ss_plugin_init_tables_input,
+    last_error_present: bool,
+) {
+    check_vtable!(report, "tables.fields_ext", tables.fields_ext =>
+        list_table_fields, get_table_field, add_table_field);
+    check_vtable!(report, "tables.reader_ext", tables.reader_ext =>
+        get_table_name, get_table_size, get_table_entry, read_entry_field,
+        release_table_entry, iterate_entries);
+    check_vtable!(report, "tables.writer_ext", tables.writer_ext =>
+        clear_table, erase_table_entry, create_table_entry, destroy_table_entry,
+        add_table_entry, write_entry_field);
+
+    let fields = &tables.fields;
+    if fields.list_table_fields.is_none()
+        || fields.get_table_field.is_none()
+        || fields.add_table_field.is_none()
+    {
+        report.warn(
+            "tables.fields",
+            "incomplete deprecated fields vtable (plugins built for older API versions may fail)",
+        );
+    } else {
+        report.pass("tables.fields");
+    }
+
+    match tables.list_tables {
+        Some(list_tables) => {
+            let mut ntables = 0u32;
+            let infos = unsafe { list_tables(input.owner, &mut ntables) };
+            if ntables > 0 && infos.is_null() {
+                report.fail(
+                    "tables.list_tables",
+                    "NULL table list with a non-zero count",
+                );
+            } else if ntables > 0 {
+                let infos = unsafe { std::slice::from_raw_parts(infos, ntables as usize) };
+                report.check(
+                    "tables.list_tables",
+                    infos.iter().all(|info| !info.name.is_null()),
+                    "table with a NULL name",
+                );
+            } else {
+                report.pass("tables.list_tables");
+            }
+        }
+        None => report.fail("tables.list_tables", "missing list_tables"),
+    }
+
+    match tables.get_table {
+        Some(get_table) => {
+            let table = unsafe {
+                get_table(
+                    input.owner,
+                    c"__falco_plugin_conformance_missing".as_ptr(),
+                    ss_plugin_state_type_SS_PLUGIN_ST_UINT64,
+                )
+            };
+            report.check(
+                "tables.get_table_missing",
+                table.is_null(),
+                "got a table for a name that was never registered",
+            );
+
+            if let (true, Some(get_last_error)) = (last_error_present, input.get_owner_last_error) {
+                let err = unsafe { get_last_error(input.owner) };
+                if err.is_null() {
+                    report.warn(
+                        "tables.get_table_missing_error",
+                        "no error message for a missing table",
+                    );
+                } else {
+                    let err = unsafe { CStr::from_ptr(err) };
+                    report.check(
+                        "tables.get_table_missing_error",
+                        err.to_str().is_ok(),
+                        "last error is not valid UTF-8",
+                    );
+                }
+            }
+        }
+        None => report.fail("tables.get_table", "missing get_table"),
+    }
+
+    report.check(
+        "tables.add_table",
+        tables.add_table.is_some(),
+        "missing add_table",
+    );
+}
+
+struct ConformancePlugin;
+
+impl Plugin for ConformancePlugin {
+    const NAME: &'static CStr = c"conformance";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"host API conformance checks";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self)
+    }
+}
+
+impl ParsePlugin for ConformancePlugin {
+    const EVENT_TYPES: &'static [EventType] = &[];
+    const EVENT_SOURCES: &'static [&'static str] = &[];
+
+    fn parse_event(&mut self, _event: &EventInput, _parse_input: &ParseInput) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+static_plugin!(CONFORMANCE_INNER_API = ConformancePlugin);
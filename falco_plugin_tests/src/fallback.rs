@@ -1,4 +1,4 @@
-use crate::{Api, CaptureNotStarted, CaptureStarted, ScapStatus};
+use crate::{Api, ApiCall, CaptureNotStarted, CaptureStarted, ScapStatus};
 use std::ffi::CStr;
 use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
@@ -26,11 +26,27 @@ impl<S> Debug for SinspTestDriver<S> {
     }
 }
 
+impl<S> SinspTestDriver<S> {
+    pub fn api_trace(&self) -> Vec<ApiCall> {
+        Vec::new()
+    }
+
+    pub fn clear_api_trace(&self) {}
+}
+
 impl SinspTestDriver<CaptureNotStarted> {
     pub fn register_plugin(&mut self, _api: &Api, _config: &CStr) -> anyhow::Result<SinspPlugin> {
         anyhow::bail!("not implemented")
     }
 
+    pub fn register_plugin_traced(
+        &mut self,
+        _api: &Api,
+        _config: &CStr,
+    ) -> anyhow::Result<SinspPlugin> {
+        anyhow::bail!("not implemented")
+    }
+
     /// # Safety
     ///
     /// `plugin` must be a pointer accepted by the sinsp API
@@ -60,7 +76,7 @@ impl SinspTestDriver<CaptureNotStarted> {
     pub fn start_capture(
         self,
         _name: &CStr,
-        _config: &CStr,
+        _open_params: &CStr,
     ) -> anyhow::Result<SinspTestDriver<CaptureStarted>> {
         anyhow::bail!("not implemented")
     }
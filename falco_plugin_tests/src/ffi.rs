@@ -1,4 +1,5 @@
 use super::ScapStatus;
+use crate::api_trace::{ApiCall, ApiTrace, TracedApi};
 use crate::common::{Api, CaptureNotStarted, CaptureStarted};
 use cxx;
 use cxx::UniquePtr;
@@ -51,7 +52,7 @@ mod ffi {
         unsafe fn start_capture(
             self: Pin<&mut SinspTestDriver>,
             name: *const c_char,
-            config: *const c_char,
+            open_params: *const c_char,
         ) -> Result<()>;
 
         fn next(self: Pin<&mut SinspTestDriver>) -> SinspEvent;
@@ -89,6 +90,9 @@ impl Debug for SinspPlugin {
 
 pub struct SinspTestDriver<S> {
     driver: UniquePtr<ffi::SinspTestDriver>,
+    // must be dropped after `driver`, which still calls into the traced plugins
+    traced: Vec<TracedApi>,
+    trace: ApiTrace,
     state: PhantomData<S>,
 }
 
@@ -98,6 +102,19 @@ impl<S> Debug for SinspTestDriver<S> {
     }
 }
 
+impl<S> SinspTestDriver<S> {
+    /// Get all the calls made into plugins registered with
+    /// [`register_plugin_traced`](`SinspTestDriver::register_plugin_traced`)
+    pub fn api_trace(&self) -> Vec<ApiCall> {
+        self.trace.lock().unwrap().clone()
+    }
+
+    /// Forget the calls recorded so far
+    pub fn clear_api_trace(&self) {
+        self.trace.lock().unwrap().clear()
+    }
+}
+
 impl SinspTestDriver<CaptureNotStarted> {
     pub fn register_plugin(&mut self, api: &Api, config: &CStr) -> anyhow::Result<SinspPlugin> {
         let plugin = unsafe {
@@ -109,6 +126,20 @@ impl SinspTestDriver<CaptureNotStarted> {
         Ok(SinspPlugin { plugin })
     }
 
+    /// Register a plugin, recording every call into its API
+    ///
+    /// The calls are available via [`api_trace`](`SinspTestDriver::api_trace`).
+    pub fn register_plugin_traced(
+        &mut self,
+        api: &Api,
+        config: &CStr,
+    ) -> anyhow::Result<SinspPlugin> {
+        let traced = TracedApi::new(api, &self.trace)?;
+        let plugin = self.register_plugin(traced.api(), config);
+        self.traced.push(traced);
+        plugin
+    }
+
     /// # Safety
     ///
     /// `plugin` must be a pointer accepted by the sinsp API
@@ -149,24 +180,32 @@ impl SinspTestDriver<CaptureNotStarted> {
 
         Ok(SinspTestDriver::<CaptureStarted> {
             driver: self.driver,
+            traced: self.traced,
+            trace: self.trace,
             state: PhantomData,
         })
     }
 
+    /// Open an instance of the source plugin `name` and start the capture
+    ///
+    /// `open_params` is passed to [`SourcePlugin::open`](`falco_plugin::source::SourcePlugin::open`),
+    /// just like the `open_params` key from the Falco config (use `c""` for no parameters).
     pub fn start_capture(
         mut self,
         name: &CStr,
-        config: &CStr,
+        open_params: &CStr,
     ) -> anyhow::Result<SinspTestDriver<CaptureStarted>> {
         unsafe {
             self.driver
                 .as_mut()
                 .unwrap()
-                .start_capture(name.as_ptr(), config.as_ptr())?;
+                .start_capture(name.as_ptr(), open_params.as_ptr())?;
         }
 
         Ok(SinspTestDriver::<CaptureStarted> {
             driver: self.driver,
+            traced: self.traced,
+            trace: self.trace,
             state: PhantomData,
         })
     }
@@ -226,6 +265,8 @@ pub fn new_test_driver() -> anyhow::Result<SinspTestDriver<CaptureNotStarted>> {
     anyhow::ensure!(!driver.is_null(), "null driver");
     Ok(SinspTestDriver {
         driver,
+        traced: Vec::new(),
+        trace: ApiTrace::default(),
         state: PhantomData,
     })
 }
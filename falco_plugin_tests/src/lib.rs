@@ -16,9 +16,14 @@ mod fallback;
 #[cfg(not(have_libsinsp))]
 pub use fallback::*;
 
+pub mod api_trace;
 pub mod common;
+pub mod conformance;
+pub use api_trace::ApiCall;
 pub use common::*;
 
+pub mod plugin_collection;
+
 pub fn init_plugin(
     api: falco_plugin::api::plugin_api,
     config: &CStr,
@@ -0,0 +1,71 @@
+use falco_plugin::anyhow::{self, Error};
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::source::PluginEvent;
+use falco_plugin::tables::TablesInput;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+
+/// # The configuration of a [`PayloadExtractor`]
+pub trait PayloadExtractSpec: 'static {
+    /// The name of the plugin
+    const NAME: &'static CStr;
+    /// The version of the plugin
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    /// A description of the plugin
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    /// Contact information
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+
+    /// The event sources to extract fields from (empty for all sources)
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+
+    /// The name of the extracted field
+    const FIELD: &'static str;
+
+    /// Compute the value of the field from the plugin event payload
+    fn extract(payload: &[u8]) -> Result<CString, Error>;
+}
+
+/// # An extract plugin exposing a single string field computed from plugin event payloads
+pub struct PayloadExtractor<S>(PhantomData<S>);
+
+impl<S: PayloadExtractSpec> Plugin for PayloadExtractor<S> {
+    const NAME: &'static CStr = S::NAME;
+    const PLUGIN_VERSION: &'static CStr = S::PLUGIN_VERSION;
+    const DESCRIPTION: &'static CStr = S::DESCRIPTION;
+    const CONTACT: &'static CStr = S::CONTACT;
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self(PhantomData))
+    }
+}
+
+impl<S: PayloadExtractSpec> PayloadExtractor<S> {
+    fn extract_field(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let event = req.event.event()?;
+        let event = event.load::<PluginEvent>()?;
+        let payload = event
+            .params
+            .event_data
+            .ok_or_else(|| anyhow::anyhow!("no payload in event"))?;
+
+        S::extract(payload)
+    }
+}
+
+impl<S: PayloadExtractSpec> ExtractPlugin for PayloadExtractor<S> {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = S::EVENT_SOURCES;
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+        &[field(S::FIELD, &Self::extract_field)];
+}
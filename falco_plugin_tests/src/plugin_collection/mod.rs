@@ -0,0 +1,26 @@
+//! # Reusable building blocks for test plugins
+//!
+//! Each plugin here is generic over a *spec* trait, implemented on an (empty) marker type.
+//! The spec provides the plugin metadata and the scenario-specific behavior, so that tests
+//! can compose the plugins they need without copying whole plugin implementations:
+//!
+//! ```ignore
+//! struct ThreeEvents;
+//!
+//! impl PayloadSourceSpec for ThreeEvents {
+//!     const NAME: &'static CStr = c"dummy";
+//!     const NUM_EVENTS: usize = 3;
+//!
+//!     fn payload(index: usize, num_events: usize) -> Vec<u8> {
+//!         format!("event {} of {}", index + 1, num_events).into_bytes()
+//!     }
+//! }
+//!
+//! static_plugin!(DUMMY_PLUGIN_API = PayloadSource<ThreeEvents>);
+//! ```
+
+mod extract;
+mod source;
+
+pub use extract::{PayloadExtractSpec, PayloadExtractor};
+pub use source::{PayloadSource, PayloadSourceInstance, PayloadSourceSpec};
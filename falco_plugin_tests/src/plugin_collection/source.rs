@@ -0,0 +1,130 @@
+use falco_plugin::anyhow::{self, Error};
+use falco_plugin::base::{Metric, MetricLabel, MetricType, MetricValue, Plugin};
+use falco_plugin::source::{
+    EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
+};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::FailureReason;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+
+/// # The configuration of a [`PayloadSource`]
+pub trait PayloadSourceSpec: 'static {
+    /// The name of the plugin
+    const NAME: &'static CStr;
+    /// The version of the plugin
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    /// A description of the plugin
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    /// Contact information
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+
+    /// The event source of the plugin
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    /// The plugin id
+    const PLUGIN_ID: u32 = 1111;
+
+    /// The number of events to emit before reporting EOF
+    ///
+    /// This can be overridden per capture by passing a number as the open params.
+    const NUM_EVENTS: usize;
+
+    /// The maximum number of events in a single batch
+    const BATCH_SIZE: usize = 1;
+
+    /// The payload of the `index`-th event (counting from zero)
+    ///
+    /// `num_events` is the total number of events in the capture.
+    fn payload(index: usize, num_events: usize) -> Vec<u8>;
+}
+
+/// # A source plugin emitting a configurable sequence of plugin events
+///
+/// The plugin reports the number of `next_batch` calls as the `next_batch_call_count` metric
+/// and renders events as their (lossily decoded) payload.
+pub struct PayloadSource<S> {
+    num_batches: usize,
+    batch_count: MetricLabel,
+    spec: PhantomData<S>,
+}
+
+impl<S: PayloadSourceSpec> Plugin for PayloadSource<S> {
+    const NAME: &'static CStr = S::NAME;
+    const PLUGIN_VERSION: &'static CStr = S::PLUGIN_VERSION;
+    const DESCRIPTION: &'static CStr = S::DESCRIPTION;
+    const CONTACT: &'static CStr = S::CONTACT;
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self {
+            num_batches: 0,
+            batch_count: MetricLabel::new(c"next_batch_call_count", MetricType::Monotonic),
+            spec: PhantomData,
+        })
+    }
+
+    fn get_metrics(&mut self) -> impl IntoIterator<Item = Metric> {
+        [self
+            .batch_count
+            .with_value(MetricValue::U64(self.num_batches as u64))]
+    }
+}
+
+/// # The capture instance of a [`PayloadSource`]
+pub struct PayloadSourceInstance<S> {
+    next: usize,
+    num_events: usize,
+    spec: PhantomData<S>,
+}
+
+impl<S: PayloadSourceSpec> SourcePluginInstance for PayloadSourceInstance<S> {
+    type Plugin = PayloadSource<S>;
+
+    fn next_batch(
+        &mut self,
+        plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        plugin.num_batches += 1;
+        if self.next >= self.num_events {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        let end = self
+            .num_events
+            .min(self.next.saturating_add(S::BATCH_SIZE.max(1)));
+        for index in self.next..end {
+            let payload = S::payload(index, self.num_events);
+            batch.add(Self::plugin_event(&payload))?;
+        }
+        self.next = end;
+
+        Ok(())
+    }
+}
+
+impl<S: PayloadSourceSpec> SourcePlugin for PayloadSource<S> {
+    type Instance = PayloadSourceInstance<S>;
+    const EVENT_SOURCE: &'static CStr = S::EVENT_SOURCE;
+    const PLUGIN_ID: u32 = S::PLUGIN_ID;
+
+    fn open(&mut self, params: Option<&str>) -> Result<Self::Instance, Error> {
+        let num_events = match params {
+            Some(params) if !params.is_empty() => params.parse()?,
+            _ => S::NUM_EVENTS,
+        };
+
+        Ok(PayloadSourceInstance {
+            next: 0,
+            num_events,
+            spec: PhantomData,
+        })
+    }
+
+    fn event_to_string(&mut self, event: &EventInput) -> Result<CString, Error> {
+        let event = event.event()?;
+        let plugin_event = event.load::<PluginEvent>()?;
+        let payload = plugin_event.params.event_data.unwrap_or_default();
+        Ok(CString::new(String::from_utf8_lossy(payload).as_bytes())?)
+    }
+}
@@ -0,0 +1,92 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::static_plugin;
+use falco_plugin_tests::plugin_collection::{
+    PayloadExtractSpec, PayloadExtractor, PayloadSource, PayloadSourceSpec,
+};
+use std::ffi::{CStr, CString};
+
+struct TwoEvents;
+
+impl PayloadSourceSpec for TwoEvents {
+    const NAME: &'static CStr = c"dummy";
+    const NUM_EVENTS: usize = 2;
+
+    fn payload(index: usize, _num_events: usize) -> Vec<u8> {
+        format!("event {}", index).into_bytes()
+    }
+}
+
+struct PayloadField;
+
+impl PayloadExtractSpec for PayloadField {
+    const NAME: &'static CStr = c"payload";
+    const FIELD: &'static str = "dummy.payload";
+
+    fn extract(payload: &[u8]) -> Result<CString, Error> {
+        Ok(CString::new(payload)?)
+    }
+}
+
+type DummyPlugin = PayloadSource<TwoEvents>;
+type DummyExtractor = PayloadExtractor<PayloadField>;
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_EXTRACT_API = DummyExtractor);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{new_test_driver, Api, ApiCall, ScapStatus};
+
+    fn count(trace: &[ApiCall], plugin: &str, name: &str) -> usize {
+        trace
+            .iter()
+            .filter(|call| call.plugin == plugin && call.name == name)
+            .count()
+    }
+
+    #[test]
+    fn test_api_trace() {
+        let mut driver = new_test_driver().unwrap();
+        driver
+            .register_plugin_traced(&Api(super::DUMMY_PLUGIN_API), c"")
+            .unwrap();
+        let extract_plugin = driver
+            .register_plugin_traced(&Api(super::DUMMY_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        let trace = driver.api_trace();
+        let init = trace
+            .iter()
+            .find(|call| call.plugin == "dummy" && call.name == "init")
+            .unwrap();
+        assert_eq!(init.rc, Some(0));
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+        driver.clear_api_trace();
+
+        // skip the first event without extracting anything
+        driver.next_event().unwrap();
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.payload", &event)
+                .unwrap()
+                .unwrap(),
+            "event 1"
+        );
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+
+        let trace = driver.api_trace();
+        assert_eq!(count(&trace, "dummy", "next_batch"), 3);
+        assert_eq!(count(&trace, "payload", "extract_fields"), 1);
+
+        let extract = trace
+            .iter()
+            .find(|call| call.name == "extract_fields")
+            .unwrap();
+        assert_eq!(extract.rc, Some(0));
+        assert!(extract.args.contains("evt="));
+    }
+}
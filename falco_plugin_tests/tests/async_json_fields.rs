@@ -0,0 +1,155 @@
+use falco_plugin::anyhow::{self, Error};
+use falco_plugin::async_event::{
+    AsyncEvent, AsyncEventPlugin, AsyncHandler, BackgroundTask, JsonAsyncFieldExtractor,
+    JsonAsyncFieldNames, JsonAsyncFields,
+};
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::{Event, EventMetadata};
+use falco_plugin::extract::EventInput;
+use falco_plugin::source::{EventBatch, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{json_async_field_names, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::panic;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+#[derive(Default)]
+struct DummyPlugin {
+    task: Arc<BackgroundTask>,
+    thread: Option<JoinHandle<Result<(), Error>>>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"dummy async plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Default::default())
+    }
+}
+
+struct DummyPluginInstance;
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        _batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        std::thread::sleep(Duration::from_millis(20));
+        Err(anyhow::anyhow!("this plugin does nothing").context(FailureReason::Timeout))
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance)
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::from(c"what event?"))
+    }
+}
+
+impl AsyncEventPlugin for DummyPlugin {
+    const ASYNC_EVENTS: &'static [&'static str] = &["container_added"];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+
+    fn start_async(&mut self, handler: AsyncHandler) -> Result<(), Error> {
+        if self.thread.is_some() {
+            self.stop_async()?;
+        }
+
+        self.thread = Some(self.task.spawn(Duration::from_millis(100), move || {
+            let event = AsyncEvent {
+                plugin_id: Some(0),
+                name: Some(c"container_added"),
+                data: Some(br#"{"id":"abc123","image":"nginx","labels":{"app":"web"}}"#),
+            };
+
+            let metadata = EventMetadata::default();
+
+            let event = Event {
+                metadata,
+                params: event,
+            };
+            handler.emit(event)
+        })?);
+
+        Ok(())
+    }
+
+    fn stop_async(&mut self) -> Result<(), Error> {
+        self.task.request_stop_and_notify()?;
+
+        let Some(handle) = self.thread.take() else {
+            return Ok(());
+        };
+
+        match handle.join() {
+            Ok(res) => res,
+            Err(e) => panic::resume_unwind(e),
+        }
+    }
+}
+
+struct ContainerFields;
+
+impl JsonAsyncFields for ContainerFields {
+    const NAME: &'static CStr = c"container_fields";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"fields from container async events";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    const FIELDS: JsonAsyncFieldNames = json_async_field_names!("dummy_async");
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    const ASYNC_EVENTS: &'static [&'static str] = &["container_added"];
+}
+
+type ContainerExtractor = JsonAsyncFieldExtractor<ContainerFields>;
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(CONTAINER_EXTRACT_API = ContainerExtractor);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api};
+
+    #[test]
+    fn test_async_json_fields() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::CONTAINER_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        let event = loop {
+            if let Ok(event) = driver.next_event() {
+                break event;
+            }
+        };
+
+        let mut field =
+            |name: &std::ffi::CStr| driver.event_field_as_string(name, &event).unwrap().unwrap();
+
+        assert_eq!(field(c"dummy_async.name"), "container_added");
+        assert_eq!(field(c"dummy_async.value[id]"), "abc123");
+        assert_eq!(field(c"dummy_async.value[labels]"), r#"{"app":"web"}"#);
+        assert_eq!(field(c"dummy_async.value[/labels/app]"), "web");
+        assert_eq!(field(c"dummy_async.has_key[image]"), "true");
+        assert_eq!(field(c"dummy_async.has_key[/labels/tier]"), "false");
+        assert_eq!(field(c"dummy_async.keys"), "(id,image,labels)");
+    }
+}
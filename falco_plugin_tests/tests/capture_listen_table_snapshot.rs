@@ -0,0 +1,138 @@
+use falco_plugin::anyhow::{self, Error};
+use falco_plugin::base::Plugin;
+use falco_plugin::extract::EventInput;
+use falco_plugin::listen::{CaptureListenInput, CaptureListenPlugin};
+use falco_plugin::source::{EventBatch, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::export;
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+
+type UserTable = export::Table<u64, User>;
+
+#[derive(export::Entry)]
+struct User {
+    name: export::Public<CString>,
+    password: export::Readonly<CString>,
+    logins: export::Public<u32>,
+    cache: export::Private<Vec<u8>>,
+}
+
+struct DummyPlugin {
+    users: Box<UserTable>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"table snapshot plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let mut users: Box<UserTable> = input.add_table(UserTable::new(c"users")?)?;
+
+        for (uid, name) in [(0, c"root"), (1, c"daemon"), (2, c"nobody")] {
+            let mut entry = users.create_entry()?;
+            *entry.name = name.to_owned();
+            *entry.password = c"hunter2".to_owned();
+            *entry.logins = uid as u32 * 10;
+            entry.cache.extend_from_slice(b"private");
+            users.insert(&uid, entry);
+        }
+
+        Ok(Self { users })
+    }
+}
+
+struct DummyPluginInstance;
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        _batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        Err(anyhow::anyhow!("this plugin does nothing").context(FailureReason::Eof))
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance)
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::from(c"what event?"))
+    }
+}
+
+impl CaptureListenPlugin for DummyPlugin {
+    fn capture_open(&mut self, _listen_input: &CaptureListenInput) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn capture_close(&mut self, _listen_input: &CaptureListenInput) -> Result<(), Error> {
+        export::TableSnapshot::new(snapshot_path())
+            .max_entries(2)
+            .redact([c"password"])
+            .dump(&[&*self.users])
+    }
+}
+
+fn snapshot_path() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("{}-users-snapshot.json", std::process::id()))
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, ScapStatus};
+    use serde_json::json;
+
+    #[test]
+    fn test_snapshot_on_close() {
+        let path = super::snapshot_path();
+        let _ = std::fs::remove_file(&path);
+
+        let (driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+
+        // closing the capture runs the listener
+        drop(driver);
+
+        let snapshot: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            snapshot,
+            json!({
+                "users": {
+                    "size": 3,
+                    "truncated": true,
+                    "entries": [
+                        {
+                            "key": 0,
+                            "fields": {"name": "root", "password": "<redacted>", "logins": 0},
+                        },
+                        {
+                            "key": 1,
+                            "fields": {"name": "daemon", "password": "<redacted>", "logins": 10},
+                        },
+                    ],
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,19 @@
+#[cfg(test)]
+mod tests {
+    use falco_plugin_tests::conformance::{conformance_api, take_report, CheckStatus};
+    use falco_plugin_tests::init_plugin;
+
+    #[test]
+    fn test_sinsp_conformance() {
+        let (driver, plugin) = init_plugin(conformance_api(), c"").unwrap();
+        drop(driver);
+        drop(plugin);
+
+        let report = take_report();
+        assert!(report.passed(), "{}", report);
+        assert!(report
+            .checks
+            .iter()
+            .any(|check| check.name == "lifecycle.destroy" && check.status == CheckStatus::Passed));
+    }
+}
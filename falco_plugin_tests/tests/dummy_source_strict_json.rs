@@ -0,0 +1,96 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::{Plugin, StrictJson};
+use falco_plugin::schemars::JsonSchema;
+use falco_plugin::serde::Deserialize;
+use falco_plugin::source::{EventBatch, EventInput, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+
+struct DummyPlugin;
+
+#[derive(JsonSchema, Deserialize)]
+#[schemars(crate = "falco_plugin::schemars")]
+#[serde(crate = "falco_plugin::serde")]
+struct DummyConfig {
+    five: u64,
+    // only declared so that the key is accepted
+    #[serde(default)]
+    #[allow(dead_code)]
+    six: Option<u64>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"dummy no-op plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = StrictJson<DummyConfig>;
+
+    fn new(
+        _input: Option<&TablesInput>,
+        StrictJson(config): Self::ConfigType,
+    ) -> Result<Self, Error> {
+        if config.five != 5 {
+            anyhow::bail!("I wanted five");
+        }
+
+        Ok(Self)
+    }
+}
+
+struct DummyPluginInstance;
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        _batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        Err(anyhow::anyhow!("this plugin does nothing").context(FailureReason::Eof))
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance)
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::from(c"what event?"))
+    }
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin_tests::init_plugin;
+
+    #[test]
+    fn test_strict_init() {
+        init_plugin(super::DUMMY_PLUGIN_API, c"{\"five\": 5}").unwrap();
+        init_plugin(super::DUMMY_PLUGIN_API, c"{\"five\": 5, \"six\": 6}").unwrap();
+    }
+
+    #[test]
+    fn test_strict_init_unknown_keys() {
+        let res = init_plugin(
+            super::DUMMY_PLUGIN_API,
+            c"{\"five\": 5, \"sixx\": 6, \"seven\": 7}",
+        );
+
+        let err = format!("{:#}", res.unwrap_err());
+        assert!(
+            err.contains("unknown configuration keys: sixx, seven"),
+            "{}",
+            err
+        );
+    }
+}
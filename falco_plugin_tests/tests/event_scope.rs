@@ -0,0 +1,193 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::{Json, Plugin};
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::schemars::JsonSchema;
+use falco_plugin::serde::Deserialize;
+use falco_plugin::source::{
+    EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
+};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+
+struct DummyPlugin;
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self)
+    }
+}
+
+struct DummyPluginInstance(bool);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if std::mem::take(&mut self.0) {
+            batch.add(Self::plugin_event(b"hello"))?;
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof))
+        }
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(true))
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::default())
+    }
+}
+
+#[derive(JsonSchema, Deserialize)]
+#[schemars(crate = "falco_plugin::schemars")]
+#[serde(crate = "falco_plugin::serde")]
+struct ScopedConfig {
+    prefix: String,
+}
+
+struct ScopedExtractPlugin {
+    prefix: String,
+}
+
+impl Plugin for ScopedExtractPlugin {
+    const NAME: &'static CStr = c"scoped_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = Json<ScopedConfig>;
+
+    fn new(_input: Option<&TablesInput>, Json(config): Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self {
+            prefix: config.prefix,
+        })
+    }
+}
+
+impl ScopedExtractPlugin {
+    fn extract_payload(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let event = req.event.event()?;
+        let event = event.load::<PluginEvent>()?;
+        let payload = event.params.event_data.unwrap_or_default();
+
+        let mut value = self.prefix.clone().into_bytes();
+        value.extend_from_slice(payload);
+        Ok(CString::new(value)?)
+    }
+}
+
+impl ExtractPlugin for ScopedExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+        &[field("scoped.payload", &Self::extract_payload)];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(SCOPED_EXTRACT_API = ScopedExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+    use std::ffi::CStr;
+
+    fn extract_payload(config: &CStr) -> Option<String> {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::SCOPED_EXTRACT_API), config)
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+        let event = driver.next_event().unwrap();
+        let payload = driver
+            .event_field_as_string(c"scoped.payload", &event)
+            .unwrap();
+
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+        payload
+    }
+
+    #[test]
+    fn test_no_scope() {
+        assert_eq!(
+            extract_payload(cr#"{"prefix": "> "}"#).as_deref(),
+            Some("> hello")
+        );
+    }
+
+    #[test]
+    fn test_matching_scope() {
+        // the SDK keys are removed and the rest of the config reaches the plugin
+        assert_eq!(
+            extract_payload(cr#"{"prefix": "> ", "sdk_event_sources": ["dummy"]}"#).as_deref(),
+            Some("> hello")
+        );
+        assert_eq!(
+            extract_payload(cr#"{"prefix": "> ", "sdk_event_types": ["PLUGINEVENT_E"]}"#)
+                .as_deref(),
+            Some("> hello")
+        );
+    }
+
+    #[test]
+    fn test_narrowed_scope() {
+        assert_eq!(
+            extract_payload(cr#"{"prefix": "> ", "sdk_event_sources": ["other"]}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invalid_scope() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let err = driver
+            .register_plugin(
+                &Api(super::SCOPED_EXTRACT_API),
+                cr#"{"prefix": "> ", "sdk_event_types": ["NOPE"]}"#,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("sdk_event_types"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_undeclared_scope() {
+        // the plugin only declares PLUGINEVENT_E, so it would never get any events
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let err = driver
+            .register_plugin(
+                &Api(super::SCOPED_EXTRACT_API),
+                cr#"{"prefix": "> ", "sdk_event_types": ["ASYNCEVENT_E"]}"#,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("sdk_event_types"), "{:#}", err);
+    }
+}
@@ -0,0 +1,155 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::source::{EventBatch, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::tables::{export, import};
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::collections::HashMap;
+use std::ffi::CStr;
+
+#[derive(export::Entry)]
+struct Counter {
+    #[name(c"counter.value")]
+    value: export::Readonly<u64>,
+
+    #[skip]
+    _cache: HashMap<u64, u64>,
+}
+
+type CounterTable = export::Table<u64, Counter>;
+
+struct DummyPlugin {
+    _counters: Box<CounterTable>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+
+        let mut counters = CounterTable::new(c"counters")?;
+        let mut entry = counters.create_entry()?;
+        *entry.value = 42;
+        counters.insert(&0, entry);
+
+        Ok(Self {
+            _counters: input.add_table(counters)?,
+        })
+    }
+}
+
+struct DummyPluginInstance(bool);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if self.0 {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        self.0 = true;
+        batch.add(Self::plugin_event(b"tick"))?;
+        Ok(())
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(false))
+    }
+}
+
+struct DummyExtractPlugin {
+    counters: import::Table<u64>,
+    value: import::Field<u64>,
+}
+
+impl Plugin for DummyExtractPlugin {
+    const NAME: &'static CStr = c"dummy_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let counters: import::Table<u64> = input.get_table(c"counters")?;
+
+        // the Rust field names are not exposed
+        if counters.get_field::<u64>(input, c"value").is_ok() {
+            anyhow::bail!("field exported under its Rust name");
+        }
+        if counters.get_field::<u64>(input, c"_cache").is_ok() {
+            anyhow::bail!("skipped field exported");
+        }
+
+        let value = counters.get_field(input, c"counter.value")?;
+        Ok(Self { counters, value })
+    }
+}
+
+impl DummyExtractPlugin {
+    fn extract_value(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let entry = self.counters.get_entry(req.table_reader, &0)?;
+        entry.read_field(req.table_reader, &self.value)
+    }
+}
+
+impl ExtractPlugin for DummyExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+        &[field("dummy.counter", &Self::extract_value)];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_EXTRACT_API = DummyExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api};
+
+    #[test]
+    fn test_export_field_names() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::DUMMY_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.counter", &event)
+                .unwrap()
+                .unwrap(),
+            "42"
+        );
+    }
+}
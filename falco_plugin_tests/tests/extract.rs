@@ -3,7 +3,8 @@ use falco_plugin::base::{Metric, MetricLabel, MetricType, MetricValue, Plugin};
 use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
 use falco_plugin::event::events::types::{EventType, PPME_PLUGINEVENT_E};
 use falco_plugin::extract::{
-    field, ExtractArgType, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+    field, field_index, ExtractArgType, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin,
+    ExtractRequest, PostProcess,
 };
 use falco_plugin::source::{
     EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
@@ -192,6 +193,26 @@ impl DummyPlugin {
         let remaining = first_char.parse()?;
         Ok(remaining)
     }
+
+    /// A helper shared by several fields, describing the field that invoked it
+    fn describe_request(req: &ExtractRequest<Self>) -> CString {
+        let desc = match req.arg() {
+            ExtractFieldRequestArg::None => req.field_name().to_string(),
+            ExtractFieldRequestArg::Int(i) => format!("{}[{}]", req.field_name(), i),
+            ExtractFieldRequestArg::String(s) => {
+                format!("{}[{}]", req.field_name(), s.to_string_lossy())
+            }
+        };
+        CString::new(desc).unwrap_or_default()
+    }
+
+    fn extract_whoami(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        Ok(Self::describe_request(&req))
+    }
 }
 
 impl ExtractPlugin for DummyPlugin {
@@ -210,16 +231,47 @@ impl ExtractPlugin for DummyPlugin {
             &Self::extract_events_remaining_with_maybe_override,
         )
         .with_arg(ExtractArgType::OptionalKey),
+        field("dummy.payload_shouting", &Self::extract_payload_repeated)
+            .with_arg(ExtractArgType::RequiredIndex)
+            .with_post_process(&[
+                PostProcess::StripSuffix(" remaining"),
+                PostProcess::Uppercase,
+            ]),
+        field("dummy.whoami", &Self::extract_whoami).with_arg(ExtractArgType::OptionalKey),
+        field("dummy.whoami_indexed", &Self::extract_whoami)
+            .with_arg(ExtractArgType::RequiredIndex),
     ];
 }
 
 static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
 
+// the framework refers to fields by index, so pin the order at compile time
+const _: () = assert!(matches!(
+    field_index(DummyPlugin::EXTRACT_FIELDS, "dummy.remaining"),
+    Some(2)
+));
+const _: () = assert!(field_index(DummyPlugin::EXTRACT_FIELDS, "dummy.nonexistent").is_none());
+
 #[cfg(test)]
 mod tests {
     use falco_plugin::base::Plugin;
     use falco_plugin_tests::{init_plugin, CaptureStarted, ScapStatus, SinspTestDriver};
 
+    #[test]
+    fn test_field_index() {
+        use falco_plugin::extract::ExtractPlugin;
+
+        assert_eq!(super::DummyPlugin::field_index("dummy.payload"), Some(0));
+        assert_eq!(
+            super::DummyPlugin::field_index("dummy.whoami_indexed"),
+            Some(7)
+        );
+        assert_eq!(
+            super::DummyPlugin::field_index("dummy.whoami_indexed2"),
+            None
+        );
+    }
+
     fn check_metrics(driver: &mut SinspTestDriver<CaptureStarted>, n: usize) {
         let metrics = driver.get_metrics().unwrap();
         let mut metrics = metrics.iter();
@@ -229,7 +281,20 @@ mod tests {
         assert_eq!(m.name, "dummy.next_batch_call_count");
         assert_eq!(m.value, n as u64);
 
-        assert!(metrics.next().is_none());
+        // the SDK throughput metrics come after the plugin ones
+        let sdk_metrics: Vec<_> = metrics.map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            sdk_metrics,
+            [
+                "dummy.sdk.source.events",
+                "dummy.sdk.source.bytes",
+                "dummy.sdk.source.batches",
+                "dummy.sdk.source.events_per_sec",
+                "dummy.sdk.source.bytes_per_sec",
+                "dummy.sdk.source.batches_per_sec",
+                "dummy.sdk.source.avg_batch_size",
+            ]
+        );
     }
 
     #[test]
@@ -253,6 +318,13 @@ mod tests {
                 .unwrap(),
             "3 events remaining"
         );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.payload_shouting[2]", &event)
+                .unwrap()
+                .unwrap(),
+            "(3 EVENTS,3 EVENTS)"
+        );
         assert_eq!(
             driver
                 .event_field_as_string(c"dummy.payload_repeated[2]", &event)
@@ -291,6 +363,27 @@ mod tests {
                 .unwrap(),
             "(3,3,3,3,3)"
         );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.whoami", &event)
+                .unwrap()
+                .unwrap(),
+            "dummy.whoami"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.whoami[foo]", &event)
+                .unwrap()
+                .unwrap(),
+            "dummy.whoami[foo]"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.whoami_indexed[5]", &event)
+                .unwrap()
+                .unwrap(),
+            "dummy.whoami_indexed[5]"
+        );
         check_metrics(&mut driver, 1);
 
         assert_eq!(
@@ -314,4 +407,21 @@ mod tests {
         check_metrics(&mut driver, 2);
         assert!(matches!(event, Err(ScapStatus::Eof)))
     }
+
+    #[test]
+    fn test_field_info_debug() {
+        use falco_plugin::extract::ExtractPlugin;
+
+        let field = super::DummyPlugin::EXTRACT_FIELDS
+            .iter()
+            .find(|f| f.name == "dummy.payload_shouting")
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", field),
+            "ExtractFieldInfo { name: \"dummy.payload_shouting\", field_type: String, \
+             is_list: true, arg: RequiredIndex, display_name: None, \
+             description: \"dummy.payload_shouting\", \
+             post_process: [StripSuffix(\" remaining\"), Uppercase], .. }"
+        );
+    }
 }
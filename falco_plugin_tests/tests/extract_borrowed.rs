@@ -0,0 +1,134 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::extract::{
+    borrowed_field, BorrowedStr, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin,
+    ExtractRequest, PostProcess,
+};
+use falco_plugin::source::{EventBatch, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::CStr;
+
+struct DummyPlugin;
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self)
+    }
+}
+
+struct DummyPluginInstance(bool);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if self.0 {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        self.0 = true;
+        batch.add(Self::plugin_event(b"key=value"))?;
+        batch.add(Self::plugin_event(b"terminated\0"))?;
+        Ok(())
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(false))
+    }
+}
+
+impl DummyPlugin {
+    fn payload<'e>(req: &ExtractRequest<'_, 'e, '_, Self>) -> Result<&'e [u8], Error> {
+        // skip the event header (26 bytes), the two parameter lengths and the plugin id
+        let len = req.event.as_bytes()?.len();
+        Ok(req.event.payload_slice(38..len)?)
+    }
+
+    fn extract_payload<'e>(
+        &mut self,
+        req: ExtractRequest<'_, 'e, '_, Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<BorrowedStr<'e>, Error> {
+        Ok(Self::payload(&req)?.into())
+    }
+
+    fn extract_key<'e>(
+        &mut self,
+        req: ExtractRequest<'_, 'e, '_, Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<BorrowedStr<'e>, Error> {
+        let payload = Self::payload(&req)?;
+        let key = payload.split(|c| *c == b'=').next().unwrap_or(payload);
+        Ok(key.into())
+    }
+}
+
+impl ExtractPlugin for DummyPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        borrowed_field("dummy.payload", &Self::extract_payload),
+        borrowed_field("dummy.key", &Self::extract_key)
+            .with_post_process(&[PostProcess::Uppercase]),
+    ];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::init_plugin;
+
+    #[test]
+    fn test_borrowed_fields() {
+        let (mut driver, plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        driver.add_filterchecks(&plugin, c"dummy").unwrap();
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.payload", &event)
+                .unwrap()
+                .unwrap(),
+            "key=value"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.key", &event)
+                .unwrap()
+                .unwrap(),
+            "KEY"
+        );
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.payload", &event)
+                .unwrap()
+                .unwrap(),
+            "terminated"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::source::{EventBatch, EventInput, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::export;
+use falco_plugin::tables::import;
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::sync::Arc;
+
+type ThingTable = export::Table<u64, Thing>;
+
+// a "newer" version of the table, with all the fields renamed
+#[derive(export::Entry)]
+struct Thing {
+    count_v2: export::Public<u64>,
+    delta_v2: export::Public<i32>,
+    small_v2: export::Public<u8>,
+    flag_v2: export::Public<bool>,
+    label_v2: export::Public<CString>,
+}
+
+type ThingImportTable = import::Table<u64, ThingImport>;
+type ThingImport = import::Entry<Arc<ThingImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(ThingImport)]
+struct ThingImportMetadata {
+    #[name(c"count", c"count_v2")]
+    count: import::Field<u64, ThingImport>,
+    #[name(c"delta", c"delta_v2")]
+    delta: import::Field<i32, ThingImport>,
+    #[name(c"small", c"small_v2")]
+    small: import::Field<u8, ThingImport>,
+    #[name(c"flag", c"flag_v2")]
+    flag: import::Field<bool, ThingImport>,
+    #[name(c"label", c"label_v2")]
+    label: import::Field<CStr, ThingImport>,
+
+    // count_v2 exists, but it's not a string, so label_v2 is used
+    #[name(c"count_v2", c"label_v2")]
+    first_string: import::Field<CStr, ThingImport>,
+
+    #[optional]
+    #[name(c"gone", c"gone_v2")]
+    gone: Option<import::Field<u64, ThingImport>>,
+}
+
+type BadThingImportTable = import::Table<u64, BadThingImport>;
+type BadThingImport = import::Entry<Arc<BadThingImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(BadThingImport)]
+#[allow(dead_code)]
+struct BadThingImportMetadata {
+    // none of the names exist
+    #[name(c"gone", c"gone_v2")]
+    gone: import::Field<u64, BadThingImport>,
+}
+
+struct DummyPlugin {
+    #[allow(unused)]
+    things: Box<ThingTable>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let mut things = input.add_table(ThingTable::new(c"things")?)?;
+
+        let mut entry = things.create_entry()?;
+        *entry.count_v2 = 5;
+        *entry.delta_v2 = -3;
+        *entry.small_v2 = 7;
+        *entry.flag_v2 = true;
+        *entry.label_v2 = c"five".to_owned();
+        let _ = things.insert(&0, entry);
+
+        Ok(Self { things })
+    }
+}
+
+struct DummyPluginInstance(bool);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if std::mem::take(&mut self.0) {
+            batch.add(Self::plugin_event(b"0"))?;
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof))
+        }
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(true))
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::default())
+    }
+}
+
+struct DummyExtractPlugin {
+    things: ThingImportTable,
+}
+
+impl Plugin for DummyExtractPlugin {
+    const NAME: &'static CStr = c"dummy_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let things = input.get_table(c"things")?;
+
+        Ok(Self { things })
+    }
+}
+
+impl DummyExtractPlugin {
+    fn extract_count(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        entry.get_count(req.table_reader)
+    }
+
+    fn extract_delta(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        let delta = entry.get_delta(req.table_reader)?;
+        Ok(CString::new(delta.to_string())?)
+    }
+
+    fn extract_small(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        Ok(entry.get_small(req.table_reader)?.into())
+    }
+
+    fn extract_flag(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        Ok(entry.get_flag(req.table_reader)?.into())
+    }
+
+    fn extract_label(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        Ok(entry.get_label(req.table_reader)?.to_owned())
+    }
+
+    fn extract_first_string(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        Ok(entry.get_first_string(req.table_reader)?.to_owned())
+    }
+
+    fn extract_gone(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        Ok(match entry.get_gone(req.table_reader)? {
+            Some(gone) => CString::new(gone.to_string())?,
+            None => c"missing".to_owned(),
+        })
+    }
+}
+
+impl ExtractPlugin for DummyExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field("things.count", &Self::extract_count),
+        field("things.delta", &Self::extract_delta),
+        field("things.small", &Self::extract_small),
+        field("things.flag", &Self::extract_flag),
+        field("things.label", &Self::extract_label),
+        field("things.first_string", &Self::extract_first_string),
+        field("things.gone", &Self::extract_gone),
+    ];
+}
+
+struct BadExtractPlugin {
+    #[allow(unused)]
+    things: BadThingImportTable,
+}
+
+impl Plugin for BadExtractPlugin {
+    const NAME: &'static CStr = c"bad_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let things = input.get_table(c"things")?;
+
+        Ok(Self { things })
+    }
+}
+
+impl BadExtractPlugin {
+    fn extract_nothing(
+        &mut self,
+        _req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        Ok(0)
+    }
+}
+
+impl ExtractPlugin for BadExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+        &[field("bad.nothing", &Self::extract_nothing)];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_EXTRACT_API = DummyExtractPlugin);
+static_plugin!(BAD_EXTRACT_API = BadExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+
+    #[test]
+    fn test_field_name_candidates() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::DUMMY_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        let event = driver.next_event().unwrap();
+        for (field, expected) in [
+            (c"things.count", "5"),
+            (c"things.delta", "-3"),
+            (c"things.small", "7"),
+            (c"things.flag", "1"),
+            (c"things.label", "five"),
+            (c"things.first_string", "five"),
+            (c"things.gone", "missing"),
+        ] {
+            assert_eq!(
+                driver
+                    .event_field_as_string(field, &event)
+                    .unwrap()
+                    .unwrap(),
+                expected,
+                "{:?}",
+                field
+            );
+        }
+
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+    }
+
+    #[test]
+    fn test_no_field_name_matches() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let err = driver
+            .register_plugin(&Api(super::BAD_EXTRACT_API), c"")
+            .unwrap_err();
+        assert!(err.to_string().contains("gone_v2"), "{:#}", err);
+    }
+}
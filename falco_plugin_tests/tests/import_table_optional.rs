@@ -0,0 +1,364 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::extract::{
+    field, ExtractArgType, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::source::{EventBatch, EventInput, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::export;
+use falco_plugin::tables::import;
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+type ThingTable = export::Table<u64, Thing>;
+
+#[derive(export::Entry)]
+struct Thing {
+    count: export::Public<u64>,
+    parts: Box<PartTable>,
+}
+
+type PartTable = export::Table<u64, Part>;
+
+#[derive(export::Entry)]
+struct Part {
+    weight: export::Public<u64>,
+}
+
+type ThingImportTable = import::Table<u64, ThingImport>;
+type ThingImport = import::Entry<Arc<ThingImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(ThingImport)]
+struct ThingImportMetadata {
+    count: import::Field<u64, ThingImport>,
+    #[optional]
+    size: Option<import::Field<u64, ThingImport>>,
+    #[optional]
+    parts: Option<import::Field<PartImportTable, ThingImport>>,
+    #[optional]
+    spare_parts: Option<import::Field<PartImportTable, ThingImport>>,
+}
+
+type PartImportTable = import::Table<u64, PartImport>;
+type PartImport = import::Entry<Arc<PartImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(PartImport)]
+struct PartImportMetadata {
+    weight: import::Field<u64, PartImport>,
+}
+
+type BadThingImportTable = import::Table<u64, BadThingImport>;
+type BadThingImport = import::Entry<Arc<BadThingImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(BadThingImport)]
+#[allow(dead_code)]
+struct BadThingImportMetadata {
+    // the field exists, but it's a u64
+    #[optional]
+    count: Option<import::Field<CStr, BadThingImport>>,
+}
+
+struct DummyPlugin {
+    #[allow(unused)]
+    things: Box<ThingTable>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let mut things = input.add_table(ThingTable::new(c"things")?)?;
+
+        let mut entry = things.create_entry()?;
+        *entry.count = 5;
+        for i in 0..3 {
+            let mut part = entry.parts.create_entry()?;
+            *part.weight = 10 * (i + 1);
+            let _ = entry.parts.insert(&i, part);
+        }
+        let _ = things.insert(&0, entry);
+
+        Ok(Self { things })
+    }
+}
+
+struct DummyPluginInstance(bool);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if std::mem::take(&mut self.0) {
+            batch.add(Self::plugin_event(b"0"))?;
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof))
+        }
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(true))
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::default())
+    }
+}
+
+struct DummyExtractPlugin {
+    things: ThingImportTable,
+}
+
+impl Plugin for DummyExtractPlugin {
+    const NAME: &'static CStr = c"dummy_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let things = input.get_table(c"things")?;
+
+        Ok(Self { things })
+    }
+}
+
+impl DummyExtractPlugin {
+    fn extract_count(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        entry.get_count(req.table_reader)
+    }
+
+    fn extract_size(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        Ok(match entry.get_size(req.table_reader)? {
+            Some(size) => CString::new(size.to_string())?,
+            None => c"missing".to_owned(),
+        })
+    }
+
+    fn extract_part_weight(
+        &mut self,
+        req: ExtractRequest<Self>,
+        arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let ExtractFieldRequestArg::Int(arg) = arg else {
+            anyhow::bail!("required arg missing")
+        };
+
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        entry
+            .get_parts_by_key(req.table_reader, &arg)?
+            .get_weight(req.table_reader)
+    }
+
+    fn extract_total_weight(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let reader = req.table_reader;
+        let entry = self.things.get_entry(reader, &0)?;
+        let mut total = 0;
+        entry.iter_parts(reader, |part| {
+            total += part.get_weight(reader).unwrap_or_default();
+            ControlFlow::Continue(())
+        })?;
+        Ok(total)
+    }
+
+    fn extract_spare_part_weight(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        entry
+            .get_spare_parts_by_key(req.table_reader, &0)?
+            .get_weight(req.table_reader)
+    }
+
+    fn extract_spare_parts_count(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let entry = self.things.get_entry(req.table_reader, &0)?;
+        let mut count = 0;
+        entry.iter_spare_parts(req.table_reader, |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        })?;
+        Ok(count)
+    }
+}
+
+impl ExtractPlugin for DummyExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field("things.count", &Self::extract_count),
+        field("things.size", &Self::extract_size),
+        field("things.part_weight", &Self::extract_part_weight)
+            .with_arg(ExtractArgType::RequiredIndex),
+        field("things.total_weight", &Self::extract_total_weight),
+        field("things.spare_part_weight", &Self::extract_spare_part_weight),
+        field("things.spare_parts_count", &Self::extract_spare_parts_count),
+    ];
+}
+
+struct BadExtractPlugin {
+    #[allow(unused)]
+    things: BadThingImportTable,
+}
+
+impl Plugin for BadExtractPlugin {
+    const NAME: &'static CStr = c"bad_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let things = input.get_table(c"things")?;
+
+        Ok(Self { things })
+    }
+}
+
+impl BadExtractPlugin {
+    fn extract_nothing(
+        &mut self,
+        _req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        Ok(0)
+    }
+}
+
+impl ExtractPlugin for BadExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+        &[field("bad.nothing", &Self::extract_nothing)];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_EXTRACT_API = DummyExtractPlugin);
+static_plugin!(BAD_EXTRACT_API = BadExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+
+    #[test]
+    fn test_missing_optional_field() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::DUMMY_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"things.count", &event)
+                .unwrap()
+                .unwrap(),
+            "5"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"things.size", &event)
+                .unwrap()
+                .unwrap(),
+            "missing"
+        );
+
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+    }
+
+    #[test]
+    fn test_optional_nested_table() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::DUMMY_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"things.part_weight[1]", &event)
+                .unwrap()
+                .unwrap(),
+            "20"
+        );
+        assert!(driver
+            .event_field_as_string(c"things.part_weight[3]", &event)
+            .is_err());
+        assert_eq!(
+            driver
+                .event_field_as_string(c"things.total_weight", &event)
+                .unwrap()
+                .unwrap(),
+            "60"
+        );
+
+        // the spare_parts field does not exist in the exported table
+        assert!(driver
+            .event_field_as_string(c"things.spare_part_weight", &event)
+            .is_err());
+        assert!(driver
+            .event_field_as_string(c"things.spare_parts_count", &event)
+            .is_err());
+    }
+
+    #[test]
+    fn test_optional_field_type_mismatch() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let err = driver
+            .register_plugin(&Api(super::BAD_EXTRACT_API), c"")
+            .unwrap_err();
+        assert!(err.to_string().contains("count"), "{:#}", err);
+    }
+}
@@ -0,0 +1,309 @@
+//! # Reference plugins: a JSON-lines file source and an extractor for its keys
+//!
+//! The source plugin reads a file containing one JSON object per line and emits every line
+//! as the payload of a plugin event. The extract plugin parses the payload (once per event,
+//! using the extraction context) and exposes its keys as fields with the `JsonData` helper.
+//!
+//! This pair is meant as a starting point for new plugins, so it sticks to the most common
+//! patterns: JSON config, open parameters, batching, a per-event extraction context
+//! and key arguments for fields.
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::{Json, Plugin};
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::extract::{
+    field, ExtractArgType, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+    JsonData,
+};
+use falco_plugin::schemars::JsonSchema;
+use falco_plugin::serde::Deserialize;
+use falco_plugin::source::{EventBatch, PluginEvent, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+#[derive(JsonSchema, Deserialize)]
+#[schemars(crate = "falco_plugin::schemars")]
+#[serde(crate = "falco_plugin::serde")]
+struct JsonLinesConfig {
+    /// Keep waiting for new lines at the end of the file (like `tail -f`)
+    #[serde(default)]
+    follow: bool,
+    /// The maximum number of events in a single batch
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+}
+
+fn default_batch_size() -> usize {
+    64
+}
+
+struct JsonLinesPlugin {
+    follow: bool,
+    batch_size: usize,
+}
+
+impl Plugin for JsonLinesPlugin {
+    const NAME: &'static CStr = c"jsonl";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"read events from a JSON-lines file";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = Json<JsonLinesConfig>;
+
+    fn new(_input: Option<&TablesInput>, Json(config): Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self {
+            follow: config.follow,
+            batch_size: config.batch_size,
+        })
+    }
+}
+
+struct JsonLinesInstance {
+    reader: BufReader<File>,
+    line: String,
+}
+
+impl SourcePluginInstance for JsonLinesInstance {
+    type Plugin = JsonLinesPlugin;
+
+    fn next_batch(
+        &mut self,
+        plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        while batch.len() < plugin.batch_size {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                break;
+            }
+
+            let line = self.line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+
+            // reject malformed lines early, so that extractors can rely on valid JSON
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(line)?;
+            batch.add(Self::plugin_event(line.as_bytes()))?;
+        }
+
+        match (batch.is_empty(), plugin.follow) {
+            (false, _) => Ok(()),
+            (true, true) => Err(anyhow::anyhow!("no new lines").context(FailureReason::Timeout)),
+            (true, false) => Err(anyhow::anyhow!("end of file").context(FailureReason::Eof)),
+        }
+    }
+}
+
+impl SourcePlugin for JsonLinesPlugin {
+    type Instance = JsonLinesInstance;
+    const EVENT_SOURCE: &'static CStr = c"jsonl";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, params: Option<&str>) -> Result<Self::Instance, Error> {
+        let path = params
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("open params must contain the file path"))?;
+        let file = File::open(path)?;
+
+        Ok(JsonLinesInstance {
+            reader: BufReader::new(file),
+            line: String::new(),
+        })
+    }
+
+    // event_to_string is not overridden: the default implementation renders
+    // JSON payloads as-is
+}
+
+struct JsonLinesExtractPlugin;
+
+impl Plugin for JsonLinesExtractPlugin {
+    const NAME: &'static CStr = c"jsonl_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"extract fields from JSON-lines events";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self)
+    }
+}
+
+/// The parsed event payload, shared between all fields extracted from a single event
+#[derive(Default)]
+struct ParsedLine(Option<JsonData>);
+
+impl JsonLinesExtractPlugin {
+    fn parsed<'a>(req: &'a mut ExtractRequest<Self>) -> Result<&'a JsonData, Error> {
+        let parsed = &mut req.context.0;
+        if parsed.is_none() {
+            let event = req.event.event()?;
+            let event = event.load::<PluginEvent>()?;
+            *parsed = Some(JsonData::parse(
+                event.params.event_data.unwrap_or_default(),
+            )?);
+        }
+
+        // we have just filled it in
+        Ok(parsed.as_ref().unwrap())
+    }
+
+    fn extract_value(
+        &mut self,
+        mut req: ExtractRequest<Self>,
+        arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        Self::parsed(&mut req)?.value(&arg)
+    }
+
+    fn extract_has_key(
+        &mut self,
+        mut req: ExtractRequest<Self>,
+        arg: ExtractFieldRequestArg,
+    ) -> Result<bool, Error> {
+        Self::parsed(&mut req)?.has_key(&arg)
+    }
+
+    fn extract_keys(
+        &mut self,
+        mut req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<Vec<CString>, Error> {
+        Self::parsed(&mut req)?.keys()
+    }
+}
+
+impl ExtractPlugin for JsonLinesExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["jsonl"];
+    type ExtractContext = ParsedLine;
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field("jsonl.value", &Self::extract_value)
+            .with_arg(ExtractArgType::RequiredKey)
+            .with_description("the value of a key or JSON pointer (strings as-is, others as JSON)"),
+        field("jsonl.has_key", &Self::extract_has_key)
+            .with_arg(ExtractArgType::RequiredKey)
+            .with_description("true if the event contains the key or JSON pointer"),
+        field("jsonl.keys", &Self::extract_keys)
+            .with_description("all the top-level keys of the event"),
+    ];
+}
+
+static_plugin!(JSONL_API = JsonLinesPlugin);
+static_plugin!(JSONL_EXTRACT_API = JsonLinesExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+    use std::ffi::CString;
+    use std::path::PathBuf;
+
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+            std::fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let file = TempFile::new(
+            "events.jsonl",
+            "{\"uid\":0,\"user\":\"root\"}\n\n{\"user\": \"nobody\", \"tags\": [1, 2]}\n",
+        );
+        let path = CString::new(file.0.to_str().unwrap()).unwrap();
+
+        let (mut driver, _plugin) = init_plugin(super::JSONL_API, c"{\"batch_size\": 1}").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::JSONL_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"jsonl").unwrap();
+
+        let mut driver = driver
+            .start_capture(super::JsonLinesPlugin::NAME, &path)
+            .unwrap();
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"jsonl.value[user]", &event)
+                .unwrap()
+                .unwrap(),
+            "root"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"jsonl.value[uid]", &event)
+                .unwrap()
+                .unwrap(),
+            "0"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"jsonl.has_key[tags]", &event)
+                .unwrap()
+                .unwrap(),
+            "false"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"jsonl.keys", &event)
+                .unwrap()
+                .unwrap(),
+            "(uid,user)"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"evt.plugininfo", &event)
+                .unwrap()
+                .unwrap(),
+            "{\"uid\":0,\"user\":\"root\"}"
+        );
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"jsonl.value[tags]", &event)
+                .unwrap()
+                .unwrap(),
+            "[1,2]"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"jsonl.has_key[tags]", &event)
+                .unwrap()
+                .unwrap(),
+            "true"
+        );
+
+        let event = driver.next_event();
+        assert!(matches!(event, Err(ScapStatus::Eof)))
+    }
+
+    #[test]
+    fn test_json_lines_malformed() {
+        let file = TempFile::new("malformed.jsonl", "{\"user\": \"root\"}\nnot json\n");
+        let path = CString::new(file.0.to_str().unwrap()).unwrap();
+
+        let (driver, _plugin) = init_plugin(super::JSONL_API, c"{\"batch_size\": 1}").unwrap();
+        let mut driver = driver
+            .start_capture(super::JsonLinesPlugin::NAME, &path)
+            .unwrap();
+
+        driver.next_event().unwrap();
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Failure)));
+    }
+}
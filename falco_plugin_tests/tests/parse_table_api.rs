@@ -9,7 +9,7 @@ use falco_plugin::parse::{ParseInput, ParsePlugin};
 use falco_plugin::source::{
     EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
 };
-use falco_plugin::strings::{CStringWriter, WriteIntoCString};
+use falco_plugin::strings::{CStringWriter, NulPolicy, WriteIntoCString};
 use falco_plugin::tables::export;
 use falco_plugin::tables::import;
 use falco_plugin::tables::TablesInput;
@@ -169,7 +169,12 @@ struct RemainingCounterImportMetadataWithExtraFields {
     #[custom]
     is_even: import::Field<import::Bool, RemainingCounterImportWithExtraFields>,
     #[custom]
+    is_odd: import::Field<bool, RemainingCounterImportWithExtraFields>,
+    #[custom]
     as_string: import::Field<CStr, RemainingCounterImportWithExtraFields>,
+    #[custom]
+    #[nul_policy(NulPolicy::Strip)]
+    label: import::Field<CStr, RemainingCounterImportWithExtraFields>,
 }
 
 struct DummyParsePlugin {
@@ -208,8 +213,13 @@ impl ParsePlugin for DummyParsePlugin {
         string_rep.write_into(|w| write!(w, "{} events remaining", remaining))?;
 
         entry.set_is_even(&parse_input.writer, &is_even)?;
+        entry.set_is_odd(&parse_input.writer, &(remaining % 2 != 0))?;
         entry.set_as_string(&parse_input.writer, string_rep.as_c_str())?;
 
+        // the setter strips the NUL instead of failing
+        let label = format!("{}\0left", remaining);
+        entry.set_label(&parse_input.writer, label.as_bytes())?;
+
         Ok(())
     }
 }
@@ -267,6 +277,21 @@ impl DummyExtractPlugin {
         Ok(is_even.into())
     }
 
+    fn extract_is_odd(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let event_num = req.event.event_number() as u64;
+
+        let entry = self
+            .remaining_table
+            .get_entry(req.table_reader, &event_num)?;
+        let is_odd = entry.get_is_odd(req.table_reader)?;
+
+        Ok(is_odd.into())
+    }
+
     fn extract_string_rep(
         &mut self,
         req: ExtractRequest<Self>,
@@ -281,6 +306,21 @@ impl DummyExtractPlugin {
 
         Ok(CString::from(string_rep))
     }
+
+    fn extract_label(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let event_num = req.event.event_number() as u64;
+
+        let entry = self
+            .remaining_table
+            .get_entry(req.table_reader, &event_num)?;
+        let label = entry.get_label(req.table_reader)?;
+
+        Ok(CString::from(label))
+    }
 }
 
 impl ExtractPlugin for DummyExtractPlugin {
@@ -290,7 +330,9 @@ impl ExtractPlugin for DummyExtractPlugin {
     const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
         field("dummy_extract.remaining", &Self::extract_remaining),
         field("dummy_extract.is_even", &Self::extract_is_even),
+        field("dummy_extract.is_odd", &Self::extract_is_odd),
         field("dummy_extract.as_string", &Self::extract_string_rep),
+        field("dummy_extract.label", &Self::extract_label),
     ];
 }
 
@@ -331,6 +373,13 @@ mod tests {
                 .unwrap(),
             "0"
         );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy_extract.is_odd", &event)
+                .unwrap()
+                .unwrap(),
+            "1"
+        );
         assert_eq!(
             driver
                 .event_field_as_string(c"dummy_extract.as_string", &event)
@@ -338,6 +387,13 @@ mod tests {
                 .unwrap(),
             "3 events remaining"
         );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy_extract.label", &event)
+                .unwrap()
+                .unwrap(),
+            "3left"
+        );
 
         let event = driver.next_event().unwrap();
         assert_eq!(
@@ -0,0 +1,305 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::event::events::types::{EventType, PPME_PLUGINEVENT_E};
+use falco_plugin::extract::{
+    field, ExtractArgType, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::parse::{ParseInput, ParsePlugin};
+use falco_plugin::source::{
+    EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
+};
+use falco_plugin::strings::CStringWriter;
+use falco_plugin::tables::export;
+use falco_plugin::tables::import;
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::io::Write;
+use std::sync::Arc;
+
+// exporting a table with a nested table attached at runtime
+type RemainingEntryTable = export::Table<u64, RemainingCounter>;
+
+#[derive(export::Entry)]
+struct RemainingCounter {
+    remaining: export::Public<u64>,
+}
+
+type CountdownTable = export::Table<u64, Countdown>;
+
+#[derive(export::Entry)]
+struct Countdown {
+    count: export::Public<u64>,
+}
+
+struct DummyPlugin {
+    remaining_table: Box<RemainingEntryTable>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+
+        let mut remaining_table = RemainingEntryTable::new(c"remaining")?;
+        // the field must exist before other plugins look it up
+        remaining_table.add_nested_table_field(c"countdown")?;
+        let remaining_table = input.add_table(remaining_table)?;
+
+        Ok(Self { remaining_table })
+    }
+}
+
+struct DummyPluginInstance(Option<usize>);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if let Some(mut num_events) = self.0.take() {
+            while num_events > 0 {
+                num_events -= 1;
+                let event = format!("{} events remaining", num_events);
+                let event = Self::plugin_event(event.as_bytes());
+                batch.add(event)?;
+            }
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof))
+        }
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(Some(4)))
+    }
+
+    fn event_to_string(&mut self, event: &EventInput) -> Result<CString, Error> {
+        let event = event.event()?;
+        let plugin_event = event.load::<PluginEvent>()?;
+        let mut writer = CStringWriter::default();
+        write!(
+            writer,
+            "{}",
+            plugin_event
+                .params
+                .event_data
+                .map(|e| String::from_utf8_lossy(e))
+                .unwrap_or_default()
+        )?;
+        Ok(writer.into_cstring())
+    }
+}
+
+impl ParsePlugin for DummyPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+
+    fn parse_event(&mut self, event: &EventInput, _parse_input: &ParseInput) -> anyhow::Result<()> {
+        let event_num = event.event_number() as u64;
+        let event = event.event()?;
+        let event = event.load::<PPME_PLUGINEVENT_E>()?;
+        let payload = event
+            .params
+            .event_data
+            .ok_or_else(|| anyhow::anyhow!("no payload in event"))?;
+
+        let first_char = &payload[0..1];
+        let first_char = std::str::from_utf8(first_char)?;
+        let remaining: u64 = first_char.parse()?;
+
+        let mut entry = self.remaining_table.create_entry()?;
+        *entry.remaining = remaining;
+
+        let countdown_table = Box::new(CountdownTable::new(c"countdown")?);
+        self.remaining_table
+            .set_nested_table(&mut entry, c"countdown", countdown_table)?;
+
+        {
+            let countdown_table = self
+                .remaining_table
+                .nested_table_mut::<u64, Countdown>(&mut entry, c"countdown")
+                .ok_or_else(|| anyhow::anyhow!("nested table not found"))?;
+            for i in 0..=remaining {
+                let mut countdown_entry = countdown_table.create_entry()?;
+                *countdown_entry.count = remaining - i;
+
+                let _ = countdown_table
+                    .insert(&i, countdown_entry)
+                    .ok_or_else(|| anyhow::anyhow!("boo"))?;
+            }
+        }
+
+        let _ = self
+            .remaining_table
+            .insert(&event_num, entry)
+            .ok_or_else(|| anyhow::anyhow!("boo"))?;
+        Ok(())
+    }
+}
+
+// the importing side does not know (or care) that the nested table is dynamic
+type RemainingCounterImportTable = import::Table<u64, RemainingCounterImport>;
+type RemainingCounterImport = import::Entry<Arc<RemainingCounterImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(RemainingCounterImport)]
+struct RemainingCounterImportMetadata {
+    remaining: import::Field<u64, RemainingCounterImport>,
+    countdown: import::Field<CountdownImportTable, RemainingCounterImport>,
+}
+
+type CountdownImportTable = import::Table<u64, CountdownImport>;
+type CountdownImport = import::Entry<Arc<CountdownImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(CountdownImport)]
+struct CountdownImportMetadata {
+    count: import::Field<u64, CountdownImport>,
+}
+
+struct DummyExtractPlugin {
+    remaining_table: RemainingCounterImportTable,
+}
+
+impl Plugin for DummyExtractPlugin {
+    const NAME: &'static CStr = c"dummy_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let remaining_table = input.get_table(c"remaining")?;
+
+        Ok(Self { remaining_table })
+    }
+}
+
+impl DummyExtractPlugin {
+    fn extract_remaining(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let event_num = req.event.event_number() as u64;
+
+        let entry = self
+            .remaining_table
+            .get_entry(req.table_reader, &event_num)?;
+        let remaining = entry.get_remaining(req.table_reader)?;
+
+        Ok(remaining)
+    }
+
+    fn extract_count(
+        &mut self,
+        req: ExtractRequest<Self>,
+        arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let ExtractFieldRequestArg::Int(arg) = arg else {
+            anyhow::bail!("required arg missing")
+        };
+
+        let event_num = req.event.event_number() as u64;
+
+        let entry = self
+            .remaining_table
+            .get_entry(req.table_reader, &event_num)?;
+
+        let count = entry
+            .get_countdown_by_key(req.table_reader, &arg)?
+            .get_count(req.table_reader)?;
+
+        Ok(count)
+    }
+}
+
+impl ExtractPlugin for DummyExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field("dummy_extract.remaining", &Self::extract_remaining),
+        field("dummy_extract.count", &Self::extract_count).with_arg(ExtractArgType::RequiredIndex),
+    ];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_EXTRACT_API = DummyExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+
+    #[test]
+    fn test_nested_dynamic_table() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::DUMMY_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy_extract.remaining", &event)
+                .unwrap()
+                .unwrap(),
+            "3"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy_extract.count[0]", &event)
+                .unwrap()
+                .unwrap(),
+            "3"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy_extract.count[3]", &event)
+                .unwrap()
+                .unwrap(),
+            "0"
+        );
+        assert!(driver
+            .event_field_as_string(c"dummy_extract.count[4]", &event)
+            .is_err());
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy_extract.count[0]", &event)
+                .unwrap()
+                .unwrap(),
+            "2"
+        );
+        assert!(driver
+            .event_field_as_string(c"dummy_extract.count[3]", &event)
+            .is_err());
+
+        driver.next_event().unwrap();
+        driver.next_event().unwrap();
+
+        let event = driver.next_event();
+        assert!(matches!(event, Err(ScapStatus::Eof)))
+    }
+}
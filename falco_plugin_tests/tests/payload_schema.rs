@@ -0,0 +1,178 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::extract::{
+    field, ExtractArgType, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::source::{
+    export_payload_schemas, EventBatch, PayloadSchema, PayloadSchemaTable, PayloadSchemas,
+    SourcePlugin, SourcePluginInstance,
+};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+
+struct DummyPlugin {
+    _schemas: Box<PayloadSchemaTable>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+
+        let schemas = export_payload_schemas(
+            input,
+            c"dummy.payload_schema",
+            [
+                PayloadSchema {
+                    version: 1,
+                    format: c"text".to_owned(),
+                    schema: c"<number>".to_owned(),
+                },
+                PayloadSchema {
+                    version: 2,
+                    format: c"json-schema".to_owned(),
+                    schema: cr#"{"type": "integer"}"#.to_owned(),
+                },
+            ],
+        )?;
+
+        Ok(Self { _schemas: schemas })
+    }
+}
+
+struct DummyPluginInstance(bool);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if self.0 {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        self.0 = true;
+        batch.add(Self::plugin_event(b"5"))?;
+        Ok(())
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(false))
+    }
+}
+
+struct DummyExtractPlugin {
+    schemas: PayloadSchemas,
+}
+
+impl Plugin for DummyExtractPlugin {
+    const NAME: &'static CStr = c"dummy_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let schemas = PayloadSchemas::import(input, c"dummy.payload_schema")?
+            .ok_or_else(|| anyhow::anyhow!("no payload schema published"))?;
+
+        Ok(Self { schemas })
+    }
+}
+
+impl DummyExtractPlugin {
+    fn extract_version(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let latest = self
+            .schemas
+            .latest(req.table_reader)?
+            .ok_or_else(|| anyhow::anyhow!("no payload versions"))?;
+        Ok(latest.version)
+    }
+
+    fn extract_format(
+        &mut self,
+        req: ExtractRequest<Self>,
+        arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let ExtractFieldRequestArg::Int(version) = arg else {
+            anyhow::bail!("version argument required");
+        };
+
+        Ok(self.schemas.get(req.table_reader, version)?.format)
+    }
+}
+
+impl ExtractPlugin for DummyExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field("dummy.schema_version", &Self::extract_version),
+        field("dummy.schema_format", &Self::extract_format).with_arg(ExtractArgType::RequiredIndex),
+    ];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_EXTRACT_API = DummyExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api};
+
+    #[test]
+    fn test_payload_schema() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::DUMMY_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+        let event = driver.next_event().unwrap();
+
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.schema_version", &event)
+                .unwrap()
+                .unwrap(),
+            "2"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.schema_format[1]", &event)
+                .unwrap()
+                .unwrap(),
+            "text"
+        );
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.schema_format[2]", &event)
+                .unwrap()
+                .unwrap(),
+            "json-schema"
+        );
+    }
+}
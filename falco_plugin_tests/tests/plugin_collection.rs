@@ -0,0 +1,78 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::static_plugin;
+use falco_plugin_tests::plugin_collection::{
+    PayloadExtractSpec, PayloadExtractor, PayloadSource, PayloadSourceSpec,
+};
+use std::ffi::{CStr, CString};
+
+struct NumberedEvents;
+
+impl PayloadSourceSpec for NumberedEvents {
+    const NAME: &'static CStr = c"numbered";
+    const NUM_EVENTS: usize = 2;
+    const BATCH_SIZE: usize = 2;
+
+    fn payload(index: usize, num_events: usize) -> Vec<u8> {
+        format!("event {} of {}", index + 1, num_events).into_bytes()
+    }
+}
+
+struct UppercasePayload;
+
+impl PayloadExtractSpec for UppercasePayload {
+    const NAME: &'static CStr = c"uppercase";
+    const FIELD: &'static str = "numbered.upper";
+
+    fn extract(payload: &[u8]) -> Result<CString, Error> {
+        Ok(CString::new(payload.to_ascii_uppercase())?)
+    }
+}
+
+type NumberedSource = PayloadSource<NumberedEvents>;
+type UppercaseExtractor = PayloadExtractor<UppercasePayload>;
+
+static_plugin!(SOURCE_API = NumberedSource);
+static_plugin!(EXTRACT_API = UppercaseExtractor);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+
+    #[test]
+    fn test_composed_plugins() {
+        let (mut driver, _plugin) = init_plugin(super::SOURCE_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+        let mut driver = driver
+            .start_capture(super::NumberedSource::NAME, c"")
+            .unwrap();
+
+        for expected in ["EVENT 1 OF 2", "EVENT 2 OF 2"] {
+            let event = driver.next_event().unwrap();
+            let field = driver
+                .event_field_as_string(c"numbered.upper", &event)
+                .unwrap()
+                .unwrap();
+            assert_eq!(field, expected);
+        }
+
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+    }
+
+    #[test]
+    fn test_num_events_from_open_params() {
+        let (driver, _plugin) = init_plugin(super::SOURCE_API, c"").unwrap();
+        let mut driver = driver
+            .start_capture(super::NumberedSource::NAME, c"3")
+            .unwrap();
+
+        for expected in ["event 1 of 3", "event 2 of 3", "event 3 of 3"] {
+            assert_eq!(driver.next_event_as_str().unwrap().unwrap(), expected);
+        }
+
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+    }
+}
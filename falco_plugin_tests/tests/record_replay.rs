@@ -0,0 +1,244 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::event::events::types::{EventType, PPME_PLUGINEVENT_E};
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::parse::{ParseInput, ParsePlugin};
+use falco_plugin::source::{EventBatch, EventInput, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::export;
+use falco_plugin::tables::import;
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::sync::Arc;
+
+type CounterTable = export::Table<u64, Counter>;
+
+#[derive(export::Entry)]
+struct Counter {
+    remaining: export::Public<u64>,
+}
+
+type CounterImportTable = import::Table<u64, CounterImport>;
+type CounterImport = import::Entry<Arc<CounterImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(CounterImport)]
+struct CounterImportMetadata {
+    remaining: import::Field<u64, CounterImport>,
+}
+
+struct DummyPlugin {
+    counters: Box<CounterTable>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let counters = input.add_table(CounterTable::new(c"counters")?)?;
+
+        Ok(Self { counters })
+    }
+}
+
+struct DummyPluginInstance(Option<usize>);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if let Some(mut num_events) = self.0.take() {
+            while num_events > 0 {
+                num_events -= 1;
+                let event = format!("{}", num_events);
+                let event = Self::plugin_event(event.as_bytes());
+                batch.add(event)?;
+            }
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof))
+        }
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(Some(3)))
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::default())
+    }
+}
+
+impl ParsePlugin for DummyPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+
+    fn parse_event(&mut self, event: &EventInput, _parse_input: &ParseInput) -> anyhow::Result<()> {
+        let event_num = event.event_number() as u64;
+        let event = event.event()?;
+        let event = event.load::<PPME_PLUGINEVENT_E>()?;
+        let payload = event
+            .params
+            .event_data
+            .ok_or_else(|| anyhow::anyhow!("no payload in event"))?;
+        let remaining: u64 = std::str::from_utf8(payload)?.parse()?;
+
+        let mut entry = self.counters.create_entry()?;
+        *entry.remaining = remaining;
+        let _ = self.counters.insert(&event_num, entry);
+
+        Ok(())
+    }
+}
+
+struct DummyExtractPlugin {
+    counters: CounterImportTable,
+}
+
+impl Plugin for DummyExtractPlugin {
+    const NAME: &'static CStr = c"dummy_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let counters = input.get_table(c"counters")?;
+
+        Ok(Self { counters })
+    }
+}
+
+impl DummyExtractPlugin {
+    fn extract_remaining(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let event_num = req.event.event_number() as u64;
+
+        let entry = self.counters.get_entry(req.table_reader, &event_num)?;
+        entry.get_remaining(req.table_reader)
+    }
+
+    fn extract_table_size(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        Ok(self.counters.get_size(req.table_reader) as u64)
+    }
+}
+
+impl ExtractPlugin for DummyExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field("dummy_extract.remaining", &Self::extract_remaining),
+        field("dummy_extract.table_size", &Self::extract_table_size),
+    ];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_EXTRACT_API = DummyExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin::record::{ReplayStep, Replayer};
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+    use std::ffi::CString;
+
+    #[test]
+    fn test_record_replay() {
+        let tape = std::env::temp_dir().join(format!("record_replay.{}.tape", std::process::id()));
+        let config = serde_json::json!({ "sdk_record": tape }).to_string();
+        let config = CString::new(config).unwrap();
+
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::DUMMY_EXTRACT_API), &config)
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        // only one recording at a time is supported
+        let (mut other_driver, _other_plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let other_tape = tape.with_extension("other");
+        let other_config = serde_json::json!({ "sdk_record": other_tape }).to_string();
+        let err = other_driver
+            .register_plugin(
+                &Api(super::DUMMY_EXTRACT_API),
+                &CString::new(other_config).unwrap(),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("sdk_record"), "{:#}", err);
+        assert!(!other_tape.exists());
+        drop(other_driver);
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        let mut recorded = Vec::new();
+        loop {
+            let event = match driver.next_event() {
+                Ok(event) => event,
+                Err(ScapStatus::Eof) => break,
+                Err(e) => panic!("{:?}", e),
+            };
+            recorded.push(
+                driver
+                    .event_field_as_string(c"dummy_extract.remaining", &event)
+                    .unwrap()
+                    .unwrap(),
+            );
+            driver
+                .event_field_as_string(c"dummy_extract.table_size", &event)
+                .unwrap()
+                .unwrap();
+        }
+        assert_eq!(recorded, ["2", "1", "0"]);
+
+        // destroying the plugin stops the recording
+        drop(driver);
+
+        let mut replayer = Replayer::open(&tape).unwrap();
+        let mut plugin = replayer.init::<super::DummyExtractPlugin>().unwrap();
+
+        let mut replayed = Vec::new();
+        while let Some(step) = replayer.next_step().unwrap() {
+            let ReplayStep::Extract(step) = step else {
+                panic!("unexpected parse step");
+            };
+            let outcome = step.run(&mut plugin).unwrap();
+            assert!(outcome.matches(), "{:?}", outcome);
+            replayed.extend(outcome.replayed.unwrap());
+        }
+
+        std::fs::remove_file(&tape).unwrap();
+        // one extraction request per field per event
+        assert_eq!(replayed.len(), 6);
+        assert_eq!(
+            replayed.iter().step_by(2).collect::<Vec<_>>(),
+            ["2", "1", "0"]
+        );
+    }
+}
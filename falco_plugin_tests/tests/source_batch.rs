@@ -1,90 +1,20 @@
-use falco_plugin::anyhow::Error;
-use falco_plugin::base::{Metric, MetricLabel, MetricType, MetricValue, Plugin};
-use falco_plugin::source::{
-    EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
-};
-use falco_plugin::strings::CStringWriter;
-use falco_plugin::tables::TablesInput;
-use falco_plugin::{anyhow, static_plugin, FailureReason};
-use std::ffi::{CStr, CString};
-use std::io::Write;
+use falco_plugin::static_plugin;
+use falco_plugin_tests::plugin_collection::{PayloadSource, PayloadSourceSpec};
+use std::ffi::CStr;
 
-struct DummyPlugin {
-    num_batches: usize,
-    batch_count: MetricLabel,
-}
+struct RemainingEvents;
 
-impl Plugin for DummyPlugin {
+impl PayloadSourceSpec for RemainingEvents {
     const NAME: &'static CStr = c"dummy";
-    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
-    const DESCRIPTION: &'static CStr = c"test plugin";
-    const CONTACT: &'static CStr = c"rust@localdomain.pl";
-    type ConfigType = ();
-
-    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
-        Ok(Self {
-            num_batches: 0,
-            batch_count: MetricLabel::new(c"next_batch_call_count", MetricType::Monotonic),
-        })
-    }
-
-    fn get_metrics(&mut self) -> impl IntoIterator<Item = Metric> {
-        [self
-            .batch_count
-            .with_value(MetricValue::U64(self.num_batches as u64))]
-    }
-}
-
-struct DummyPluginInstance(Option<usize>);
-
-impl SourcePluginInstance for DummyPluginInstance {
-    type Plugin = DummyPlugin;
+    const NUM_EVENTS: usize = 4;
+    const BATCH_SIZE: usize = usize::MAX;
 
-    fn next_batch(
-        &mut self,
-        plugin: &mut Self::Plugin,
-        batch: &mut EventBatch,
-    ) -> Result<(), Error> {
-        plugin.num_batches += 1;
-        if let Some(mut num_events) = self.0.take() {
-            while num_events > 0 {
-                num_events -= 1;
-                let event = format!("{} events remaining", num_events);
-                let event = Self::plugin_event(event.as_bytes());
-                batch.add(event)?;
-            }
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof))
-        }
+    fn payload(index: usize, num_events: usize) -> Vec<u8> {
+        format!("{} events remaining", num_events - index - 1).into_bytes()
     }
 }
 
-impl SourcePlugin for DummyPlugin {
-    type Instance = DummyPluginInstance;
-    const EVENT_SOURCE: &'static CStr = c"dummy";
-    const PLUGIN_ID: u32 = 1111;
-
-    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
-        Ok(DummyPluginInstance(Some(4)))
-    }
-
-    fn event_to_string(&mut self, event: &EventInput) -> Result<CString, Error> {
-        let event = event.event()?;
-        let plugin_event = event.load::<PluginEvent>()?;
-        let mut writer = CStringWriter::default();
-        write!(
-            writer,
-            "{}",
-            plugin_event
-                .params
-                .event_data
-                .map(|e| String::from_utf8_lossy(e))
-                .unwrap_or_default()
-        )?;
-        Ok(writer.into_cstring())
-    }
-}
+type DummyPlugin = PayloadSource<RemainingEvents>;
 
 static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
 
@@ -101,7 +31,24 @@ mod tests {
         assert_eq!(m.name, "dummy.next_batch_call_count");
         assert_eq!(m.value, n as u64);
 
-        assert!(metrics.next().is_none());
+        // the SDK throughput metrics come after the plugin ones
+        let sdk_metrics: Vec<_> = metrics.collect();
+        let names: Vec<_> = sdk_metrics.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "dummy.sdk.source.events",
+                "dummy.sdk.source.bytes",
+                "dummy.sdk.source.batches",
+                "dummy.sdk.source.events_per_sec",
+                "dummy.sdk.source.bytes_per_sec",
+                "dummy.sdk.source.batches_per_sec",
+                "dummy.sdk.source.avg_batch_size",
+            ]
+        );
+
+        // all the events are generated in the first batch
+        assert_eq!(sdk_metrics[0].value, 4);
     }
 
     #[test]
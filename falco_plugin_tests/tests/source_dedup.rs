@@ -0,0 +1,102 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::source::{
+    DedupPolicy, EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
+};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::time::Duration;
+
+struct DummyPlugin;
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self)
+    }
+}
+
+struct DummyPluginInstance(bool);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if self.0 {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        self.0 = true;
+        for line in [
+            "1 hello",
+            "2 world",
+            "1 hello again",
+            "3 !",
+            "2 world again",
+        ] {
+            batch.add(Self::plugin_event(line.as_bytes()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Use the record number (before the first space) as the deduplication key
+fn record_number(data: &[u8]) -> Option<u64> {
+    let (number, _) = std::str::from_utf8(data).ok()?.split_once(' ')?;
+    number.parse().ok()
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+    const DEDUP_POLICY: Option<DedupPolicy> = Some(DedupPolicy {
+        key: record_number,
+        max_entries: 100,
+        max_age: Duration::from_secs(60),
+    });
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(false))
+    }
+
+    fn event_to_string(&mut self, event: &EventInput) -> Result<CString, Error> {
+        let event = event.event()?;
+        let event = event.load::<PluginEvent>()?;
+        Ok(CString::new(event.params.event_data.unwrap_or_default())?)
+    }
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, ScapStatus};
+
+    #[test]
+    fn test_dedup() {
+        let (driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        for expected in ["1 hello", "2 world", "3 !"] {
+            assert_eq!(driver.next_event_as_str().unwrap().unwrap(), expected);
+        }
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+
+        let metrics = driver.get_metrics().unwrap();
+        let metric = |name: &str| metrics.iter().find(|m| m.name == name).unwrap().value;
+        assert_eq!(metric("dummy.sdk.dedup_dropped"), 2);
+        assert_eq!(metric("dummy.sdk.source.events"), 3);
+    }
+}
@@ -0,0 +1,115 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::source::{
+    serialize_open_params, EventBatch, OpenParam, SourcePlugin, SourcePluginInstance,
+};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+
+struct DummyPlugin {
+    open_params: CString,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self {
+            open_params: CString::default(),
+        })
+    }
+}
+
+/// Emit a single event for every value in the open params, then finish
+struct DummyPluginInstance(Vec<String>);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if self.0.is_empty() {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        for value in self.0.drain(..) {
+            batch.add(Self::plugin_event(value.as_bytes()))?;
+        }
+        Ok(())
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn list_open_params(&mut self) -> Result<&CStr, Error> {
+        serialize_open_params(
+            &[OpenParam::Seq {
+                values: &["a", "b"],
+                desc: "the values to emit",
+                separator: ';',
+            }],
+            &mut self.open_params,
+        )
+    }
+
+    fn open(&mut self, params: Option<&str>) -> Result<Self::Instance, Error> {
+        let values = match params {
+            None => vec![String::from("<none>")],
+            Some("") => vec![String::from("<empty>")],
+            Some(params) => params.split(';').map(String::from).collect(),
+        };
+        Ok(DummyPluginInstance(values))
+    }
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, ScapStatus};
+    use std::ffi::CStr;
+
+    fn capture(open_params: &CStr) -> Vec<String> {
+        let (driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let mut driver = driver
+            .start_capture(super::DummyPlugin::NAME, open_params)
+            .unwrap();
+
+        let mut events = Vec::new();
+        loop {
+            match driver.next_event_as_str() {
+                Ok(event) => events.push(event.unwrap()),
+                Err(e) => {
+                    assert!(matches!(
+                        e.downcast_ref::<ScapStatus>(),
+                        Some(ScapStatus::Eof)
+                    ));
+                    break;
+                }
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_open_params() {
+        assert_eq!(capture(c"foo;bar;baz"), ["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn test_empty_open_params() {
+        assert_eq!(capture(c""), ["<empty>"]);
+    }
+}
@@ -1,83 +1,19 @@
-use falco_plugin::anyhow::Error;
-use falco_plugin::base::{Metric, MetricLabel, MetricType, MetricValue, Plugin};
-use falco_plugin::source::{
-    EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
-};
-use falco_plugin::strings::CStringWriter;
-use falco_plugin::tables::TablesInput;
-use falco_plugin::{anyhow, static_plugin, FailureReason};
-use std::ffi::{CStr, CString};
-use std::io::Write;
+use falco_plugin::static_plugin;
+use falco_plugin_tests::plugin_collection::{PayloadSource, PayloadSourceSpec};
+use std::ffi::CStr;
 
-struct DummyPlugin(usize);
+struct RemainingEvents;
 
-impl Plugin for DummyPlugin {
+impl PayloadSourceSpec for RemainingEvents {
     const NAME: &'static CStr = c"dummy";
-    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
-    const DESCRIPTION: &'static CStr = c"test plugin";
-    const CONTACT: &'static CStr = c"rust@localdomain.pl";
-    type ConfigType = ();
+    const NUM_EVENTS: usize = 3;
 
-    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
-        Ok(Self(0))
-    }
-
-    fn get_metrics(&mut self) -> impl IntoIterator<Item = Metric> {
-        [Metric::new(
-            MetricLabel::new(c"next_batch_call_count", MetricType::Monotonic),
-            MetricValue::U64(self.0 as u64),
-        )]
-    }
-}
-
-struct DummyPluginInstance(usize);
-
-impl SourcePluginInstance for DummyPluginInstance {
-    type Plugin = DummyPlugin;
-
-    fn next_batch(
-        &mut self,
-        plugin: &mut Self::Plugin,
-        batch: &mut EventBatch,
-    ) -> Result<(), Error> {
-        plugin.0 += 1;
-        if self.0 > 0 {
-            self.0 -= 1;
-            let event = format!("{} events remaining", self.0);
-            let event = Self::plugin_event(event.as_bytes());
-            batch.add(event)?;
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof))
-        }
+    fn payload(index: usize, num_events: usize) -> Vec<u8> {
+        format!("{} events remaining", num_events - index - 1).into_bytes()
     }
 }
 
-impl SourcePlugin for DummyPlugin {
-    type Instance = DummyPluginInstance;
-    const EVENT_SOURCE: &'static CStr = c"dummy";
-    const PLUGIN_ID: u32 = 1111;
-
-    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
-        Ok(DummyPluginInstance(3))
-    }
-
-    fn event_to_string(&mut self, event: &EventInput) -> Result<CString, Error> {
-        let event = event.event()?;
-        let plugin_event = event.load::<PluginEvent>()?;
-        let mut writer = CStringWriter::default();
-        write!(
-            writer,
-            "{}",
-            plugin_event
-                .params
-                .event_data
-                .map(|e| String::from_utf8_lossy(e))
-                .unwrap_or_default()
-        )?;
-        Ok(writer.into_cstring())
-    }
-}
+type DummyPlugin = PayloadSource<RemainingEvents>;
 
 static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
 
@@ -95,7 +31,20 @@ mod tests {
         assert_eq!(m.name, "dummy.next_batch_call_count");
         assert_eq!(m.value, n as u64);
 
-        assert!(metrics.next().is_none());
+        // the SDK throughput metrics come after the plugin ones
+        let sdk_metrics: Vec<_> = metrics.map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            sdk_metrics,
+            [
+                "dummy.sdk.source.events",
+                "dummy.sdk.source.bytes",
+                "dummy.sdk.source.batches",
+                "dummy.sdk.source.events_per_sec",
+                "dummy.sdk.source.bytes_per_sec",
+                "dummy.sdk.source.batches_per_sec",
+                "dummy.sdk.source.avg_batch_size",
+            ]
+        );
     }
 
     #[test]
@@ -0,0 +1,100 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::{Event, EventMetadata};
+use falco_plugin::source::{
+    EventBatch, PluginEvent, SourcePlugin, SourcePluginInstance, TimestampPolicy,
+};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::CStr;
+
+struct DummyPlugin;
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self)
+    }
+}
+
+struct DummyPluginInstance(bool);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if self.0 {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        self.0 = true;
+        for ts in [300, 100, 200, 400] {
+            batch.add(Event {
+                metadata: EventMetadata { ts, tid: 1 },
+                params: PluginEvent {
+                    plugin_id: Some(DummyPlugin::PLUGIN_ID),
+                    event_data: Some(b"tick"),
+                },
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+    const TIMESTAMP_POLICY: TimestampPolicy = TimestampPolicy::Clamp;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(false))
+    }
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, ScapStatus};
+
+    #[test]
+    fn test_clamped_timestamps() {
+        let (driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        for expected in ["300", "300", "300", "400"] {
+            let event = driver.next_event().unwrap();
+            assert_eq!(
+                driver
+                    .event_field_as_string(c"evt.rawtime", &event)
+                    .unwrap()
+                    .unwrap(),
+                expected
+            );
+        }
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+
+        let metrics = driver.get_metrics().unwrap();
+        let clamps = metrics
+            .iter()
+            .find(|m| m.name == "dummy.sdk.timestamp_clamps")
+            .unwrap();
+        assert_eq!(clamps.value, 2);
+
+        let metric = |name: &str| metrics.iter().find(|m| m.name == name).unwrap().value;
+        assert_eq!(metric("dummy.sdk.source.events"), 4);
+        assert_eq!(metric("dummy.sdk.source.batches"), 1);
+        assert!(metric("dummy.sdk.source.bytes") > 4 * 26);
+    }
+}
@@ -0,0 +1,262 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::{Metric, Plugin};
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::event::events::types::PPME_PLUGINEVENT_E;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::source::{EventBatch, EventInput, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::export;
+use falco_plugin::tables::import;
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::sync::Arc;
+
+type InventoryTable = export::Table<u64, Item>;
+
+#[derive(export::Entry)]
+struct Item {
+    count: export::Public<u64>,
+    name: export::Public<CString>,
+}
+
+type InventoryImportTable = import::Table<u64, ItemImport>;
+type ItemImport = import::Entry<Arc<ItemImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(ItemImport)]
+struct ItemImportMetadata {
+    count: import::Field<u64, ItemImport>,
+    name: import::Field<CStr, ItemImport>,
+}
+
+struct DummyPlugin {
+    #[allow(unused)]
+    inventory: Box<InventoryTable>,
+    load_stats: export::BulkLoadStats,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let mut inventory = input.add_table(InventoryTable::new(c"inventory")?)?;
+
+        // keys in reverse order, with a duplicate: the last one wins
+        let items = (0..1000u64).rev().map(|i| (i, i * 2)).chain([(7, 1)]);
+        let load_stats = inventory.bulk_load(items, |entry, count| {
+            *entry.count = count;
+            *entry.name = CString::new(format!("item {}", count))?;
+            Ok(())
+        })?;
+
+        Ok(Self {
+            inventory,
+            load_stats,
+        })
+    }
+
+    fn get_metrics(&mut self) -> impl IntoIterator<Item = Metric> {
+        self.load_stats.metrics(
+            c"inventory.bulk_load.entries",
+            c"inventory.bulk_load.duration_ns",
+        )
+    }
+}
+
+struct DummyPluginInstance(Option<Vec<u64>>);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        let Some(keys) = self.0.take() else {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        };
+
+        for key in keys {
+            let event = key.to_string();
+            batch.add(Self::plugin_event(event.as_bytes()))?;
+        }
+        Ok(())
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(Some(vec![0, 7, 999])))
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::default())
+    }
+}
+
+struct DummyExtractPlugin {
+    inventory: InventoryImportTable,
+}
+
+impl Plugin for DummyExtractPlugin {
+    const NAME: &'static CStr = c"dummy_extract";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let inventory = input.get_table(c"inventory")?;
+
+        Ok(Self { inventory })
+    }
+}
+
+impl DummyExtractPlugin {
+    fn get_key(req: &ExtractRequest<Self>) -> Result<u64, Error> {
+        let event = req.event.event()?;
+        let event = event.load::<PPME_PLUGINEVENT_E>()?;
+        let payload = event
+            .params
+            .event_data
+            .ok_or_else(|| anyhow::anyhow!("no payload in event"))?;
+        Ok(std::str::from_utf8(payload)?.parse()?)
+    }
+
+    fn extract_count(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let key = Self::get_key(&req)?;
+        let entry = self.inventory.get_entry(req.table_reader, &key)?;
+        entry.get_count(req.table_reader)
+    }
+
+    fn extract_name(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let key = Self::get_key(&req)?;
+        let entry = self.inventory.get_entry(req.table_reader, &key)?;
+        Ok(entry.get_name(req.table_reader)?.to_owned())
+    }
+
+    fn extract_found(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let key = Self::get_key(&req)?;
+        // the missing key is skipped and the duplicate one is only looked up once
+        let entries = self
+            .inventory
+            .get_entries(req.table_reader, [&key, &u64::MAX, &key])?;
+        Ok(entries.len() as u64)
+    }
+
+    fn extract_size(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        Ok(self.inventory.get_size(req.table_reader) as u64)
+    }
+}
+
+impl ExtractPlugin for DummyExtractPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field("inventory.count", &Self::extract_count),
+        field("inventory.name", &Self::extract_name),
+        field("inventory.found", &Self::extract_found),
+        field("inventory.size", &Self::extract_size),
+    ];
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_EXTRACT_API = DummyExtractPlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+
+    #[test]
+    fn test_bulk_load() {
+        let (mut driver, _plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        let extract_plugin = driver
+            .register_plugin(&Api(super::DUMMY_EXTRACT_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&extract_plugin, c"dummy").unwrap();
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        for (count, name) in [("0", "item 0"), ("1", "item 1"), ("1998", "item 1998")] {
+            let event = driver.next_event().unwrap();
+            assert_eq!(
+                driver
+                    .event_field_as_string(c"inventory.count", &event)
+                    .unwrap()
+                    .unwrap(),
+                count
+            );
+            assert_eq!(
+                driver
+                    .event_field_as_string(c"inventory.name", &event)
+                    .unwrap()
+                    .unwrap(),
+                name
+            );
+            assert_eq!(
+                driver
+                    .event_field_as_string(c"inventory.found", &event)
+                    .unwrap()
+                    .unwrap(),
+                "1"
+            );
+            assert_eq!(
+                driver
+                    .event_field_as_string(c"inventory.size", &event)
+                    .unwrap()
+                    .unwrap(),
+                "1000"
+            );
+        }
+
+        assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+
+        let metrics = driver.get_metrics().unwrap();
+        let entries = metrics
+            .iter()
+            .find(|m| m.name == "dummy.inventory.bulk_load.entries")
+            .unwrap();
+        // the duplicate key counts as a separate entry
+        assert_eq!(entries.value, 1001);
+        assert!(metrics
+            .iter()
+            .any(|m| m.name == "dummy.inventory.bulk_load.duration_ns"));
+
+        // the throughput metrics only come from the source plugin
+        assert!(!metrics
+            .iter()
+            .any(|m| m.name.starts_with("dummy_extract.sdk.source.")));
+    }
+}
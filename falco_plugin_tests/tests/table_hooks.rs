@@ -0,0 +1,226 @@
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::types::EventType::PLUGINEVENT_E;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::parse::{ParseInput, ParsePlugin};
+use falco_plugin::source::{EventBatch, EventInput, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::export;
+use falco_plugin::tables::import;
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{anyhow, static_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+use std::sync::{Arc, Mutex};
+
+// exporting a table
+type CounterTable = export::Table<u64, Counter>;
+
+#[derive(export::Entry)]
+struct Counter {
+    count: export::Public<u64>,
+}
+
+// same table, but imported
+type CounterImportTable = import::Table<u64, CounterImport>;
+type CounterImport = import::Entry<Arc<CounterImportMetadata>>;
+
+#[derive(import::TableMetadata)]
+#[entry_type(CounterImport)]
+struct CounterImportMetadata {
+    count: import::Field<u64, CounterImport>,
+}
+
+struct DummyPlugin {
+    #[allow(unused)]
+    counters: Box<CounterTable>,
+    hook_log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Plugin for DummyPlugin {
+    const NAME: &'static CStr = c"dummy";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let hook_log = Arc::new(Mutex::new(Vec::new()));
+
+        let mut counters = CounterTable::new(c"counters")?;
+        let log = Arc::clone(&hook_log);
+        counters.on_clear(move || log.lock().unwrap().push(String::from("clear")));
+        let log = Arc::clone(&hook_log);
+        counters.on_erase(move |key| log.lock().unwrap().push(format!("erase {}", key)));
+
+        let counters = input.add_table(counters)?;
+
+        Ok(Self { counters, hook_log })
+    }
+}
+
+struct DummyPluginInstance(usize);
+
+impl SourcePluginInstance for DummyPluginInstance {
+    type Plugin = DummyPlugin;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if self.0 == 0 {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        self.0 -= 1;
+        let event = Self::plugin_event(b"tick");
+        batch.add(event)?;
+        Ok(())
+    }
+}
+
+impl SourcePlugin for DummyPlugin {
+    type Instance = DummyPluginInstance;
+    const EVENT_SOURCE: &'static CStr = c"dummy";
+    const PLUGIN_ID: u32 = 1111;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(DummyPluginInstance(3))
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::from(c"tick"))
+    }
+}
+
+impl DummyPlugin {
+    fn extract_hooks(
+        &mut self,
+        _req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let log = self.hook_log.lock().unwrap();
+        if log.is_empty() {
+            return Ok(CString::from(c"none"));
+        }
+
+        Ok(CString::new(log.join(", "))?)
+    }
+}
+
+impl ExtractPlugin for DummyPlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+    type ExtractContext = ();
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+        &[field("dummy.hooks", &Self::extract_hooks)];
+}
+
+struct DummyParsePlugin {
+    counters: CounterImportTable,
+    num_events: u64,
+}
+
+impl Plugin for DummyParsePlugin {
+    const NAME: &'static CStr = c"dummy_parse";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"test plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let counters = input.get_table(c"counters")?;
+
+        Ok(Self {
+            counters,
+            num_events: 0,
+        })
+    }
+}
+
+impl ParsePlugin for DummyParsePlugin {
+    const EVENT_TYPES: &'static [EventType] = &[PLUGINEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &["dummy"];
+
+    fn parse_event(&mut self, _event: &EventInput, parse_input: &ParseInput) -> anyhow::Result<()> {
+        self.num_events += 1;
+        let key = self.num_events;
+
+        let r = &parse_input.reader;
+        let w = &parse_input.writer;
+
+        if key == 3 {
+            // erasing a missing key must not invoke the hook
+            self.counters.erase(w, &1000)?;
+            self.counters.clear(w)?;
+        }
+
+        let entry = self.counters.create_entry(w)?;
+        entry.set_count(w, &key)?;
+        let _ = self.counters.insert(r, w, &key, entry)?;
+
+        match key {
+            2 => self.counters.erase(w, &1)?,
+            3 => self.counters.erase(w, &3)?,
+            _ => (),
+        }
+
+        Ok(())
+    }
+}
+
+static_plugin!(DUMMY_PLUGIN_API = DummyPlugin);
+static_plugin!(DUMMY_PARSE_API = DummyParsePlugin);
+
+#[cfg(test)]
+mod tests {
+    use falco_plugin::base::Plugin;
+    use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+
+    #[test]
+    fn test_table_hooks() {
+        let (mut driver, plugin) = init_plugin(super::DUMMY_PLUGIN_API, c"").unwrap();
+        driver
+            .register_plugin(&Api(super::DUMMY_PARSE_API), c"")
+            .unwrap();
+        driver.add_filterchecks(&plugin, c"dummy").unwrap();
+
+        let mut driver = driver.start_capture(super::DummyPlugin::NAME, c"").unwrap();
+
+        // the parse plugin only inserted an entry
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.hooks", &event)
+                .unwrap()
+                .unwrap(),
+            "none"
+        );
+
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.hooks", &event)
+                .unwrap()
+                .unwrap(),
+            "erase 1"
+        );
+
+        // the hooks are invoked in the order of the API calls
+        let event = driver.next_event().unwrap();
+        assert_eq!(
+            driver
+                .event_field_as_string(c"dummy.hooks", &event)
+                .unwrap()
+                .unwrap(),
+            "erase 1, clear, erase 3"
+        );
+
+        let event = driver.next_event();
+        assert!(matches!(event, Err(ScapStatus::Eof)))
+    }
+}
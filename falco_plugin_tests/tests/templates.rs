@@ -0,0 +1,40 @@
+//! The `cargo generate` templates are not workspace members, so make sure
+//! they keep up with the SDK version
+
+use std::path::PathBuf;
+
+fn repo_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+fn sdk_version() -> String {
+    let manifest = std::fs::read_to_string(repo_root().join("falco_plugin/Cargo.toml")).unwrap();
+    manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("version = "))
+        .unwrap()
+        .trim_matches('"')
+        .to_string()
+}
+
+#[test]
+fn test_templates_use_current_sdk() {
+    let dependency = format!("falco_plugin = \"{}\"", sdk_version());
+
+    for template in ["source", "extract", "parse", "async"] {
+        let dir = repo_root().join("templates").join(template);
+        let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        assert!(
+            manifest.lines().any(|line| line == dependency),
+            "{} template does not depend on {}",
+            template,
+            dependency
+        );
+
+        assert!(dir.join("cargo-generate.toml").exists());
+        assert!(dir.join("src/lib.rs").exists());
+    }
+}
@@ -0,0 +1,191 @@
+//! {{description}}
+//!
+//! The plugin listens for HTTP requests on the configured address and emits the body
+//! of every request as an async event, e.g.
+//! ```sh
+//! curl -d '{"hello": "world"}' http://127.0.0.1:8765/
+//! ```
+//! The body is available in rules as the `{{crate_name}}.body` field.
+
+use falco_plugin::anyhow::{self, Context, Error};
+use falco_plugin::async_event::{AsyncEvent, AsyncEventPlugin, AsyncHandler, BackgroundTask};
+use falco_plugin::base::{Json, Plugin};
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::event::events::EventMetadata;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::schemars::JsonSchema;
+use falco_plugin::serde::Deserialize;
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{async_event_plugin, extract_plugin, plugin};
+use std::ffi::{CStr, CString};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// The name of the emitted async events
+const EVENT_NAME: &CStr = c"{{crate_name}}";
+
+/// The largest accepted request body
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// The plugin configuration, passed as JSON in `init_config`
+#[derive(JsonSchema, Deserialize)]
+#[schemars(crate = "falco_plugin::schemars")]
+#[serde(crate = "falco_plugin::serde")]
+pub struct Config {
+    /// The address to listen on
+    #[serde(default = "default_listen")]
+    pub listen: String,
+}
+
+fn default_listen() -> String {
+    String::from("127.0.0.1:8765")
+}
+
+pub struct {{project-name | pascal_case}} {
+    listen: String,
+    task: Arc<BackgroundTask>,
+    thread: Option<JoinHandle<Result<(), Error>>>,
+}
+
+impl Plugin for {{project-name | pascal_case}} {
+    const NAME: &'static CStr = c"{{project-name}}";
+    const PLUGIN_VERSION: &'static CStr = c"0.1.0";
+    const DESCRIPTION: &'static CStr = c"{{description}}";
+    const CONTACT: &'static CStr = c"{{authors}}";
+    type ConfigType = Json<Config>;
+
+    fn new(_input: Option<&TablesInput>, Json(config): Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self {
+            listen: config.listen,
+            task: Default::default(),
+            thread: None,
+        })
+    }
+}
+
+/// Read a single HTTP request and return its body
+fn read_body(stream: &mut TcpStream) -> Result<Vec<u8>, Error> {
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            anyhow::bail!("connection closed before the end of headers");
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_SIZE {
+        anyhow::bail!("request body too large ({} bytes)", content_length);
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
+
+fn handle_request(stream: &mut TcpStream, handler: &AsyncHandler) -> Result<(), Error> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let status = match read_body(stream) {
+        Ok(body) => {
+            handler.emit_data(EventMetadata::default(), EVENT_NAME, body)?;
+            "204 No Content"
+        }
+        Err(e) => {
+            log::warn!("bad request: {}", e);
+            "400 Bad Request"
+        }
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    )?;
+    Ok(())
+}
+
+impl AsyncEventPlugin for {{project-name | pascal_case}} {
+    const ASYNC_EVENTS: &'static [&'static str] = &["{{crate_name}}"];
+    const EVENT_SOURCES: &'static [&'static str] = &[]; // attach to all event sources
+
+    fn start_async(&mut self, handler: AsyncHandler) -> Result<(), Error> {
+        if self.thread.is_some() {
+            self.stop_async()?;
+        }
+
+        let listener = TcpListener::bind(&self.listen)
+            .with_context(|| format!("failed to listen on {}", self.listen))?;
+        listener.set_nonblocking(true)?;
+
+        // check for new connections every 100ms (or until stopped)
+        self.thread = Some(self.task.spawn(Duration::from_millis(100), move || {
+            while let Ok((mut stream, _)) = listener.accept() {
+                if let Err(e) = handle_request(&mut stream, &handler) {
+                    log::warn!("failed to handle request: {}", e);
+                }
+            }
+            Ok(())
+        })?);
+
+        Ok(())
+    }
+
+    fn stop_async(&mut self) -> Result<(), Error> {
+        self.task.request_stop_and_notify()?;
+        let Some(handle) = self.thread.take() else {
+            return Ok(());
+        };
+
+        match handle.join() {
+            Ok(res) => res,
+            Err(e) => std::panic::resume_unwind(e),
+        }
+    }
+}
+
+impl {{project-name | pascal_case}} {
+    fn extract_body(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<CString, Error> {
+        let event = req.event.event()?;
+        let event = event.load::<AsyncEvent>()?;
+        if event.params.name != Some(EVENT_NAME) {
+            anyhow::bail!("not a {{crate_name}} event");
+        }
+
+        let body = event.params.data.unwrap_or_default();
+        Ok(CString::new(body)?)
+    }
+}
+
+impl ExtractPlugin for {{project-name | pascal_case}} {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::ASYNCEVENT_E];
+    const EVENT_SOURCES: &'static [&'static str] = &[]; // all event sources
+    type ExtractContext = ();
+
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+        &[field("{{crate_name}}.body", &Self::extract_body)];
+}
+
+plugin!({{project-name | pascal_case}});
+async_event_plugin!({{project-name | pascal_case}});
+extract_plugin!({{project-name | pascal_case}});
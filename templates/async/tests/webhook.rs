@@ -0,0 +1,114 @@
+use falco_plugin::anyhow::{self, Error};
+use falco_plugin::base::Plugin;
+use falco_plugin::source::{EventBatch, EventInput, SourcePlugin, SourcePluginInstance};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{static_plugin, FailureReason};
+use falco_plugin_tests::{init_plugin, Api};
+use std::ffi::{CStr, CString};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+use {{crate_name}}::{{project-name | pascal_case}};
+
+/// A source plugin that never produces any events, so that only async events show up
+struct IdleSource;
+
+impl Plugin for IdleSource {
+    const NAME: &'static CStr = c"idle";
+    const PLUGIN_VERSION: &'static CStr = c"0.0.0";
+    const DESCRIPTION: &'static CStr = c"idle source plugin";
+    const CONTACT: &'static CStr = c"rust@localdomain.pl";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self)
+    }
+}
+
+struct IdleSourceInstance;
+
+impl SourcePluginInstance for IdleSourceInstance {
+    type Plugin = IdleSource;
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        _batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        std::thread::sleep(Duration::from_millis(20));
+        Err(anyhow::anyhow!("no events").context(FailureReason::Timeout))
+    }
+}
+
+impl SourcePlugin for IdleSource {
+    type Instance = IdleSourceInstance;
+    const EVENT_SOURCE: &'static CStr = c"idle";
+    const PLUGIN_ID: u32 = 999;
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok(IdleSourceInstance)
+    }
+
+    fn event_to_string(&mut self, _event: &EventInput) -> Result<CString, Error> {
+        Ok(CString::default())
+    }
+}
+
+static_plugin!(SOURCE_API = IdleSource);
+static_plugin!(PLUGIN_API = {{project-name | pascal_case}});
+
+fn post(addr: &str, body: &str) {
+    // the listener starts along with the capture, so retry for a while
+    let mut stream = (0..50)
+        .find_map(|_| {
+            TcpStream::connect(addr).ok().or_else(|| {
+                std::thread::sleep(Duration::from_millis(100));
+                None
+            })
+        })
+        .expect("failed to connect to the plugin");
+
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 204"), "{}", response);
+}
+
+#[test]
+fn test_webhook() {
+    let addr = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    };
+    // (no format! here: double braces would clash with the template syntax)
+    let config = CString::new(["{\"listen\": \"", addr.as_str(), "\"}"].concat()).unwrap();
+
+    let (mut driver, _source) = init_plugin(SOURCE_API, c"").unwrap();
+    let plugin = driver
+        .register_plugin(&Api(PLUGIN_API), config.as_c_str())
+        .unwrap();
+    driver.add_filterchecks(&plugin, c"idle").unwrap();
+    let mut driver = driver.start_capture(IdleSource::NAME, c"").unwrap();
+
+    let sender = std::thread::spawn(move || post(&addr, r#"{"hello": "world"}"#));
+
+    let event = loop {
+        if let Ok(event) = driver.next_event() {
+            break event;
+        }
+    };
+    sender.join().unwrap();
+
+    let body = driver
+        .event_field_as_string(c"{{crate_name}}.body", &event)
+        .unwrap()
+        .unwrap();
+    assert_eq!(body, r#"{"hello": "world"}"#);
+}
@@ -0,0 +1,59 @@
+//! {{description}}
+
+use falco_plugin::anyhow::Error;
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{extract_plugin, plugin};
+use std::ffi::CStr;
+
+pub struct {{project-name | pascal_case}};
+
+impl Plugin for {{project-name | pascal_case}} {
+    const NAME: &'static CStr = c"{{project-name}}";
+    const PLUGIN_VERSION: &'static CStr = c"0.1.0";
+    const DESCRIPTION: &'static CStr = c"{{description}}";
+    const CONTACT: &'static CStr = c"{{authors}}";
+    type ConfigType = ();
+
+    fn new(_input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self)
+    }
+}
+
+impl {{project-name | pascal_case}} {
+    fn extract_event_type(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let event = req.event.event()?;
+        Ok(event.event_type as u64)
+    }
+
+    fn extract_len(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let event = req.event.event()?;
+        Ok(event.len as u64)
+    }
+}
+
+impl ExtractPlugin for {{project-name | pascal_case}} {
+    const EVENT_TYPES: &'static [EventType] = &[]; // all event types
+    const EVENT_SOURCES: &'static [&'static str] = &[]; // all event sources
+    type ExtractContext = ();
+
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] = &[
+        field("{{crate_name}}.event_type", &Self::extract_event_type),
+        field("{{crate_name}}.len", &Self::extract_len),
+    ];
+}
+
+plugin!({{project-name | pascal_case}});
+extract_plugin!({{project-name | pascal_case}});
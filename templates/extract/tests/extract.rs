@@ -0,0 +1,49 @@
+use falco_plugin::event::events::EventPayload;
+use falco_plugin::source::PluginEvent;
+use falco_plugin::static_plugin;
+use falco_plugin_tests::plugin_collection::{PayloadSource, PayloadSourceSpec};
+use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+use std::ffi::CStr;
+use {{crate_name}}::{{project-name | pascal_case}};
+
+struct TestEvents;
+
+impl PayloadSourceSpec for TestEvents {
+    const NAME: &'static CStr = c"test_source";
+    const NUM_EVENTS: usize = 2;
+
+    fn payload(index: usize, _num_events: usize) -> Vec<u8> {
+        vec![b'x'; index + 1]
+    }
+}
+
+static_plugin!(SOURCE_API = PayloadSource<TestEvents>);
+static_plugin!(PLUGIN_API = {{project-name | pascal_case}});
+
+#[test]
+fn test_extract() {
+    let (mut driver, _source) = init_plugin(SOURCE_API, c"").unwrap();
+    let plugin = driver.register_plugin(&Api(PLUGIN_API), c"").unwrap();
+    driver.add_filterchecks(&plugin, c"dummy").unwrap();
+    let mut driver = driver.start_capture(TestEvents::NAME, c"").unwrap();
+
+    let mut lengths = Vec::new();
+    while let Ok(event) = driver.next_event() {
+        let event_type = driver
+            .event_field_as_string(c"{{crate_name}}.event_type", &event)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event_type, (PluginEvent::ID as u16).to_string());
+
+        let len = driver
+            .event_field_as_string(c"{{crate_name}}.len", &event)
+            .unwrap()
+            .unwrap();
+        lengths.push(len.parse::<u64>().unwrap());
+    }
+
+    // the second event carries one more payload byte
+    assert_eq!(lengths.len(), 2);
+    assert_eq!(lengths[1], lengths[0] + 1);
+    assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+}
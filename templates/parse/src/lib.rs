@@ -0,0 +1,90 @@
+//! {{description}}
+
+use falco_plugin::anyhow::{self, Error};
+use falco_plugin::base::Plugin;
+use falco_plugin::event::events::types::EventType;
+use falco_plugin::extract::{
+    field, ExtractFieldInfo, ExtractFieldRequestArg, ExtractPlugin, ExtractRequest,
+};
+use falco_plugin::parse::{EventInput, ParseInput, ParsePlugin};
+use falco_plugin::tables::{export, TablesInput};
+use falco_plugin::{extract_plugin, parse_plugin, plugin};
+use std::ffi::CStr;
+
+/// The number of events seen so far, per event type
+///
+/// The table is exported as `{{crate_name}}_counts`, so other plugins can read it as well.
+pub type EventCountTable = export::Table<u64, EventCount>;
+
+#[derive(export::Entry)]
+pub struct EventCount {
+    count: export::Public<u64>,
+}
+
+pub struct {{project-name | pascal_case}} {
+    counts: Box<EventCountTable>,
+}
+
+impl Plugin for {{project-name | pascal_case}} {
+    const NAME: &'static CStr = c"{{project-name}}";
+    const PLUGIN_VERSION: &'static CStr = c"0.1.0";
+    const DESCRIPTION: &'static CStr = c"{{description}}";
+    const CONTACT: &'static CStr = c"{{authors}}";
+    type ConfigType = ();
+
+    fn new(input: Option<&TablesInput>, _config: Self::ConfigType) -> Result<Self, Error> {
+        let input = input.ok_or_else(|| anyhow::anyhow!("did not get table input"))?;
+        let counts = input.add_table(EventCountTable::new(c"{{crate_name}}_counts")?)?;
+
+        Ok(Self { counts })
+    }
+}
+
+impl ParsePlugin for {{project-name | pascal_case}} {
+    const EVENT_TYPES: &'static [EventType] = &[]; // all event types
+    const EVENT_SOURCES: &'static [&'static str] = &[]; // all event sources
+
+    fn parse_event(&mut self, event: &EventInput, _parse_input: &ParseInput) -> Result<(), Error> {
+        let event_type = event.event()?.event_type as u64;
+        let count = self
+            .counts
+            .lookup(&event_type)
+            .map(|entry| *entry.count)
+            .unwrap_or_default();
+
+        let mut entry = self.counts.create_entry()?;
+        *entry.count = count + 1;
+        self.counts.insert(&event_type, entry);
+
+        Ok(())
+    }
+}
+
+impl {{project-name | pascal_case}} {
+    fn extract_count(
+        &mut self,
+        req: ExtractRequest<Self>,
+        _arg: ExtractFieldRequestArg,
+    ) -> Result<u64, Error> {
+        let event_type = req.event.event()?.event_type as u64;
+        let entry = self
+            .counts
+            .lookup(&event_type)
+            .ok_or_else(|| anyhow::anyhow!("event type {} not seen yet", event_type))?;
+
+        Ok(*entry.count)
+    }
+}
+
+impl ExtractPlugin for {{project-name | pascal_case}} {
+    const EVENT_TYPES: &'static [EventType] = &[]; // all event types
+    const EVENT_SOURCES: &'static [&'static str] = &[]; // all event sources
+    type ExtractContext = ();
+
+    const EXTRACT_FIELDS: &'static [ExtractFieldInfo<Self>] =
+        &[field("{{crate_name}}.count", &Self::extract_count)];
+}
+
+plugin!({{project-name | pascal_case}});
+parse_plugin!({{project-name | pascal_case}});
+extract_plugin!({{project-name | pascal_case}});
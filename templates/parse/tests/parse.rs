@@ -0,0 +1,39 @@
+use falco_plugin::static_plugin;
+use falco_plugin_tests::plugin_collection::{PayloadSource, PayloadSourceSpec};
+use falco_plugin_tests::{init_plugin, Api, ScapStatus};
+use std::ffi::CStr;
+use {{crate_name}}::{{project-name | pascal_case}};
+
+struct TestEvents;
+
+impl PayloadSourceSpec for TestEvents {
+    const NAME: &'static CStr = c"test_source";
+    const NUM_EVENTS: usize = 3;
+
+    fn payload(index: usize, _num_events: usize) -> Vec<u8> {
+        format!("event {}", index).into_bytes()
+    }
+}
+
+static_plugin!(SOURCE_API = PayloadSource<TestEvents>);
+static_plugin!(PLUGIN_API = {{project-name | pascal_case}});
+
+#[test]
+fn test_parse_and_extract() {
+    let (mut driver, _source) = init_plugin(SOURCE_API, c"").unwrap();
+    let plugin = driver.register_plugin(&Api(PLUGIN_API), c"").unwrap();
+    driver.add_filterchecks(&plugin, c"dummy").unwrap();
+    let mut driver = driver.start_capture(TestEvents::NAME, c"").unwrap();
+
+    // events are parsed before fields are extracted, so the count includes the current event
+    for expected in ["1", "2", "3"] {
+        let event = driver.next_event().unwrap();
+        let count = driver
+            .event_field_as_string(c"{{crate_name}}.count", &event)
+            .unwrap()
+            .unwrap();
+        assert_eq!(count, expected);
+    }
+
+    assert!(matches!(driver.next_event(), Err(ScapStatus::Eof)));
+}
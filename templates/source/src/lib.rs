@@ -0,0 +1,94 @@
+//! {{description}}
+
+use falco_plugin::anyhow::{self, Error};
+use falco_plugin::base::{Json, Plugin};
+use falco_plugin::schemars::JsonSchema;
+use falco_plugin::serde::Deserialize;
+use falco_plugin::source::{
+    EventBatch, EventInput, PluginEvent, SourcePlugin, SourcePluginInstance,
+};
+use falco_plugin::tables::TablesInput;
+use falco_plugin::{plugin, source_plugin, FailureReason};
+use std::ffi::{CStr, CString};
+
+/// The plugin configuration, passed as JSON in `init_config`
+#[derive(JsonSchema, Deserialize)]
+#[schemars(crate = "falco_plugin::schemars")]
+#[serde(crate = "falco_plugin::serde")]
+pub struct Config {
+    /// The number of events to generate in every capture
+    #[serde(default = "default_num_events")]
+    pub num_events: u64,
+}
+
+fn default_num_events() -> u64 {
+    10
+}
+
+pub struct {{project-name | pascal_case}} {
+    num_events: u64,
+}
+
+impl Plugin for {{project-name | pascal_case}} {
+    const NAME: &'static CStr = c"{{project-name}}";
+    const PLUGIN_VERSION: &'static CStr = c"0.1.0";
+    const DESCRIPTION: &'static CStr = c"{{description}}";
+    const CONTACT: &'static CStr = c"{{authors}}";
+    type ConfigType = Json<Config>;
+
+    fn new(_input: Option<&TablesInput>, Json(config): Self::ConfigType) -> Result<Self, Error> {
+        Ok(Self {
+            num_events: config.num_events,
+        })
+    }
+
+    fn set_config(&mut self, Json(config): Self::ConfigType) -> Result<(), Error> {
+        self.num_events = config.num_events;
+        Ok(())
+    }
+}
+
+pub struct {{project-name | pascal_case}}Instance {
+    remaining: u64,
+}
+
+impl SourcePluginInstance for {{project-name | pascal_case}}Instance {
+    type Plugin = {{project-name | pascal_case}};
+
+    fn next_batch(
+        &mut self,
+        _plugin: &mut Self::Plugin,
+        batch: &mut EventBatch,
+    ) -> Result<(), Error> {
+        if self.remaining == 0 {
+            return Err(anyhow::anyhow!("all events produced").context(FailureReason::Eof));
+        }
+
+        self.remaining -= 1;
+        let payload = format!("{} events remaining", self.remaining);
+        batch.add(Self::plugin_event(payload.as_bytes()))?;
+        Ok(())
+    }
+}
+
+impl SourcePlugin for {{project-name | pascal_case}} {
+    type Instance = {{project-name | pascal_case}}Instance;
+    const EVENT_SOURCE: &'static CStr = c"{{project-name}}";
+    const PLUGIN_ID: u32 = {{plugin_id}};
+
+    fn open(&mut self, _params: Option<&str>) -> Result<Self::Instance, Error> {
+        Ok({{project-name | pascal_case}}Instance {
+            remaining: self.num_events,
+        })
+    }
+
+    fn event_to_string(&mut self, event: &EventInput) -> Result<CString, Error> {
+        let event = event.event()?;
+        let event = event.load::<PluginEvent>()?;
+        let payload = event.params.event_data.unwrap_or_default();
+        Ok(CString::new(payload)?)
+    }
+}
+
+plugin!({{project-name | pascal_case}});
+source_plugin!({{project-name | pascal_case}});
@@ -0,0 +1,31 @@
+use falco_plugin::base::Plugin;
+use falco_plugin::static_plugin;
+use falco_plugin_tests::{init_plugin, ScapStatus};
+use {{crate_name}}::{{project-name | pascal_case}};
+
+static_plugin!(PLUGIN_API = {{project-name | pascal_case}});
+
+#[test]
+fn test_events() {
+    let (driver, _plugin) = init_plugin(PLUGIN_API, cr#"{"num_events": 2}"#).unwrap();
+    let mut driver = driver
+        .start_capture({{project-name | pascal_case}}::NAME, c"")
+        .unwrap();
+
+    assert_eq!(
+        driver.next_event_as_str().unwrap().unwrap(),
+        "1 events remaining"
+    );
+    assert_eq!(
+        driver.next_event_as_str().unwrap().unwrap(),
+        "0 events remaining"
+    );
+
+    let event = driver.next_event();
+    assert!(matches!(event, Err(ScapStatus::Eof)));
+}
+
+#[test]
+fn test_bad_config() {
+    assert!(init_plugin(PLUGIN_API, cr#"{"num_events": "many"}"#).is_err());
+}